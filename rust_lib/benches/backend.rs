@@ -0,0 +1,43 @@
+//! The hot loops the field backend runs, on BLS12-381 Fr at 2^16 points:
+//! pointwise products, scaling and coset transforms. Run once with and
+//! once without `--features blst` to compare blst's multiplication with
+//! arkworks':
+//!
+//!   cargo bench --bench backend
+//!   cargo bench --bench backend --features blst
+
+use ark_bls12_381::Fr;
+use ark_poly::{univariate::DensePolynomial, DenseUVPolynomial};
+use ark_std::UniformRand;
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion, Throughput};
+use plonk_lib::evaluations::Evaluations;
+use plonk_lib::fft::EvaluationDomain;
+
+const LOG_N: u32 = 16;
+
+fn bench_backend(c: &mut Criterion) {
+    let mut rng = ark_std::test_rng();
+    let n = 1usize << LOG_N;
+    let domain = EvaluationDomain::<Fr>::new_for_size(n).unwrap();
+    // Degree n/2 − 1, so one product still fits the domain
+    let poly = |rng: &mut _| DensePolynomial::from_coefficients_vec((0..n / 2).map(|_| Fr::rand(rng)).collect());
+    let a = Evaluations::from_poly(&poly(&mut rng), &domain).unwrap();
+    let b = Evaluations::from_poly(&poly(&mut rng), &domain).unwrap();
+    let s = Fr::rand(&mut rng);
+    let coeffs: Vec<Fr> = (0..n).map(|_| Fr::rand(&mut rng)).collect();
+
+    let mut group = c.benchmark_group(format!("backend/{}", plonk_lib::backend_name()));
+    group.sample_size(10);
+    group.throughput(Throughput::Elements(n as u64));
+    group.bench_function("mul", |bench| bench.iter(|| a.mul(&b).unwrap()));
+    group.bench_function("scale", |bench| {
+        bench.iter_batched_ref(|| a.clone(), |evals| evals.scale(s), BatchSize::LargeInput)
+    });
+    group.bench_function("coset_fft", |bench| {
+        bench.iter_batched_ref(|| coeffs.clone(), |evals| domain.coset_fft(evals).unwrap(), BatchSize::LargeInput)
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_backend);
+criterion_main!(benches);
@@ -0,0 +1,34 @@
+//! FFT throughput from 2^12 to 2^20 points. Run once with and once
+//! without `--features parallel` to see how the transform scales:
+//!
+//!   cargo bench --bench fft
+//!   cargo bench --bench fft --features parallel
+
+use ark_bls12_381::Fr;
+use ark_std::UniformRand;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use plonk_lib::fft::{fft, root_of_unity, EvaluationDomain};
+
+fn bench_fft(c: &mut Criterion) {
+    let mut rng = ark_std::test_rng();
+    let mut group = c.benchmark_group("fft");
+    group.sample_size(10);
+    for log_n in [12u32, 16, 20] {
+        let n = 1usize << log_n;
+        let omega = root_of_unity::<Fr>(log_n).unwrap();
+        let coeffs: Vec<Fr> = (0..n).map(|_| Fr::rand(&mut rng)).collect();
+        group.throughput(Throughput::Elements(n as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(log_n), &coeffs, |b, coeffs| {
+            b.iter_batched_ref(|| coeffs.clone(), |evals| fft(evals, omega).unwrap(), criterion::BatchSize::LargeInput)
+        });
+        // The same transform over twiddles cached in the domain
+        let domain = EvaluationDomain::new(n, omega).unwrap();
+        group.bench_with_input(BenchmarkId::new("domain", log_n), &coeffs, |b, coeffs| {
+            b.iter_batched_ref(|| coeffs.clone(), |evals| domain.fft(evals).unwrap(), criterion::BatchSize::LargeInput)
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_fft);
+criterion_main!(benches);
@@ -0,0 +1,52 @@
+/*
+ * C interface of plonk-lib over BN254, built with the `ffi` feature.
+ *
+ * Keys, witnesses and proofs are the library's artifact bytes; public
+ * inputs are a little-endian u64 count followed by 32-byte little-endian
+ * field elements. Buffers written by the library are freed with
+ * plonk_buffer_free, setups with plonk_setup_free.
+ */
+
+#ifndef PLONK_H
+#define PLONK_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef enum {
+    PLONK_OK = 0,
+    PLONK_NULL_POINTER = 1,
+    PLONK_MALFORMED = 2,
+    PLONK_PROVE_FAILED = 3,
+    PLONK_INVALID_PROOF = 4,
+    PLONK_PANIC = 5,
+} PlonkStatus;
+
+typedef struct PlonkSetup PlonkSetup;
+
+typedef struct {
+    uint8_t *data;
+    size_t len;
+} PlonkBuffer;
+
+PlonkStatus plonk_setup_load(const uint8_t *key, size_t key_len, PlonkSetup **setup);
+
+void plonk_setup_free(PlonkSetup *setup);
+
+PlonkStatus plonk_prove(const PlonkSetup *setup, const uint8_t *witness, size_t witness_len, PlonkBuffer *proof);
+
+PlonkStatus plonk_verify(const uint8_t *verifying_key, size_t verifying_key_len,
+                         const uint8_t *public_inputs, size_t public_inputs_len,
+                         const uint8_t *proof, size_t proof_len);
+
+void plonk_buffer_free(PlonkBuffer buffer);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
@@ -0,0 +1,420 @@
+//! Noir backend adapter: ACIR, Noir's intermediate representation,
+//! lowered to this crate's gates and lookup tables.
+//!
+//! `Program` mirrors the constrained part of an `acir` crate circuit.
+//! Witnesses are numbered from 0 up to `current_witness_index`, and the
+//! public inputs are the public parameters followed by the return values.
+//! The opcodes are lowered as follows:
+//!
+//! - `AssertZero`: a mul gate per product term, then the weighted sum of
+//!   products and witnesses asserted zero
+//! - `Range`: `gadgets::range::assert_bits`
+//! - `And` and `Xor`: operands and result split into 4-bit limbs, one
+//!   lookup per limb into a 256-row table of the operation, and each value
+//!   tied to its limbs. As in ACIR, the operands' range is left to the
+//!   opcodes that produced them.
+//!
+//! Serialized bytecode is not decoded here: its encoding changes between
+//! Noir releases, so it takes the `acir` crate of the release that
+//! compiled it, which this crate does not depend on. A caller decoding
+//! with it maps each opcode onto `Opcode`. Memory opcodes, calls, Brillig
+//! calls and the black boxes without a lowering have variants too, and
+//! `Program::circuit` rejects them with `AcirError::Unsupported`.
+
+use alloc::{collections::{BTreeMap, BTreeSet}, vec, vec::Vec};
+use ark_ff::{BigInteger, PrimeField};
+
+use crate::circuit::{Circuit, Variable};
+use crate::constraint_system::Synthesize;
+use crate::gadgets::range::assert_bits;
+use crate::lookup::Table;
+use crate::lowering::{GateCount, Lowering};
+
+/// Bits per limb of the `And` and `Xor` lookups
+const LIMB_BITS: u32 = 4;
+
+/// Index of a witness in the program
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Witness(pub u32);
+
+/// Values ACVM solved for each witness
+pub type WitnessMap<F> = BTreeMap<Witness, F>;
+
+/// Σ q_m·a·b + Σ q_l·w + q_c
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Expression<F> {
+    pub mul_terms: Vec<(F, Witness, Witness)>,
+    pub linear_combinations: Vec<(F, Witness)>,
+    pub q_c: F,
+}
+
+/// A black box function argument of `num_bits` bits
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FunctionInput {
+    pub witness: Witness,
+    pub num_bits: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlackBoxFuncCall {
+    Range { input: FunctionInput },
+    And { lhs: FunctionInput, rhs: FunctionInput, output: Witness },
+    Xor { lhs: FunctionInput, rhs: FunctionInput, output: Witness },
+    /// Any other black box, by its ACIR name, such as "sha256"; not lowered
+    Other { name: &'static str },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Opcode<F> {
+    /// The expression is zero
+    AssertZero(Expression<F>),
+    BlackBoxFuncCall(BlackBoxFuncCall),
+    /// Initializes memory block `block_id` with `init`; not lowered
+    MemoryInit { block_id: u32, init: Vec<Witness> },
+    /// A read or write of memory block `block_id`; not lowered
+    MemoryOp { block_id: u32 },
+    /// A call of unconstrained Brillig function `id`; not lowered
+    BrilligCall { id: u32 },
+    /// A call of ACIR function `id`; not lowered
+    Call { id: u32 },
+}
+
+impl<F> Opcode<F> {
+    /// Name of the opcode when it has no lowering here
+    fn unsupported(&self) -> Option<&'static str> {
+        match self {
+            Opcode::AssertZero(_) | Opcode::BlackBoxFuncCall(BlackBoxFuncCall::Range { .. } | BlackBoxFuncCall::And { .. } | BlackBoxFuncCall::Xor { .. }) => None,
+            Opcode::BlackBoxFuncCall(BlackBoxFuncCall::Other { name }) => Some(name),
+            Opcode::MemoryInit { .. } => Some("MemoryInit"),
+            Opcode::MemoryOp { .. } => Some("MemoryOp"),
+            Opcode::BrilligCall { .. } => Some("BrilligCall"),
+            Opcode::Call { .. } => Some("Call"),
+        }
+    }
+}
+
+/// A constrained ACIR circuit
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Program<F> {
+    /// Highest witness index in use
+    pub current_witness_index: u32,
+    pub opcodes: Vec<Opcode<F>>,
+    pub public_parameters: Vec<Witness>,
+    pub return_values: Vec<Witness>,
+}
+
+/// Why a program cannot be lowered
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AcirError {
+    /// A witness past `current_witness_index`
+    WitnessOutOfRange(Witness),
+    /// A bit size at least the field's, which no range check can express
+    BitSize(u32),
+    /// An opcode or black box function with no lowering here, by name
+    Unsupported(&'static str),
+}
+
+impl core::fmt::Display for AcirError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            AcirError::WitnessOutOfRange(witness) => write!(f, "witness {} is past the last one", witness.0),
+            AcirError::BitSize(bits) => write!(f, "{} bits do not fit the field", bits),
+            AcirError::Unsupported(name) => write!(f, "{} has no lowering to this crate's gates", name),
+        }
+    }
+}
+
+/// A checked `Program` with its witness, ready to synthesize
+#[derive(Debug, Clone, Copy)]
+pub struct AcirCircuit<'a, F> {
+    program: &'a Program<F>,
+    witness: &'a WitnessMap<F>,
+}
+
+impl<F: PrimeField> Program<F> {
+    /// The circuit of this program over `witness`. Witnesses missing from
+    /// the map are zero, so an empty map lays out the gates for keying.
+    pub fn circuit<'a>(&'a self, witness: &'a WitnessMap<F>) -> Result<AcirCircuit<'a, F>, AcirError> {
+        let check = |witness: &Witness| {
+            if witness.0 > self.current_witness_index {
+                return Err(AcirError::WitnessOutOfRange(*witness));
+            }
+            Ok(())
+        };
+        self.public_inputs().try_for_each(check)?;
+        for opcode in &self.opcodes {
+            if let Some(name) = opcode.unsupported() {
+                return Err(AcirError::Unsupported(name));
+            }
+            match opcode {
+                Opcode::AssertZero(e) => {
+                    e.mul_terms.iter().flat_map(|(_, a, b)| [a, b]).chain(e.linear_combinations.iter().map(|(_, w)| w)).try_for_each(check)?
+                }
+                Opcode::MemoryInit { .. } | Opcode::MemoryOp { .. } | Opcode::BrilligCall { .. } | Opcode::Call { .. } => {}
+                Opcode::BlackBoxFuncCall(call) => {
+                    let (inputs, output) = match call {
+                        BlackBoxFuncCall::Range { input } => (vec![*input], None),
+                        BlackBoxFuncCall::And { lhs, rhs, output } | BlackBoxFuncCall::Xor { lhs, rhs, output } => (vec![*lhs, *rhs], Some(*output)),
+                        BlackBoxFuncCall::Other { .. } => continue,
+                    };
+                    if let Some(input) = inputs.iter().find(|input| input.num_bits >= F::MODULUS_BIT_SIZE) {
+                        return Err(AcirError::BitSize(input.num_bits));
+                    }
+                    inputs.iter().map(|input| &input.witness).chain(output.as_ref()).try_for_each(check)?;
+                }
+            }
+        }
+        Ok(AcirCircuit { program: self, witness })
+    }
+
+    /// Public parameters, then return values
+    fn public_inputs(&self) -> impl Iterator<Item = &Witness> {
+        self.public_parameters.iter().chain(&self.return_values)
+    }
+
+    /// Gates the lowering adds, the capacity to synthesize with
+    pub fn gates(&self) -> usize {
+        let mut count = GateCount::<F>::default();
+        let mut public = BTreeSet::new();
+        for witness in self.public_inputs() {
+            // A repeated public input is tied to the first by `assert_equal`
+            count.gates += if public.insert(witness) { 1 } else { 2 };
+        }
+        for opcode in &self.opcodes {
+            match opcode {
+                Opcode::AssertZero(e) => {
+                    count.gates += e.mul_terms.len() + 1;
+                    let coefficients = e.mul_terms.iter().map(|(q, _, _)| *q).chain(e.linear_combinations.iter().map(|(q, _)| *q));
+                    count.sum(coefficients, e.q_c);
+                }
+                Opcode::BlackBoxFuncCall(BlackBoxFuncCall::Range { input }) => count.gates += input.num_bits.max(1) as usize,
+                Opcode::BlackBoxFuncCall(BlackBoxFuncCall::And { lhs, rhs, .. } | BlackBoxFuncCall::Xor { lhs, rhs, .. }) => {
+                    let limbs = limbs(lhs, rhs);
+                    count.gates += limbs + 3;
+                    if matches!(opcode, Opcode::BlackBoxFuncCall(BlackBoxFuncCall::Xor { .. })) {
+                        for _ in 0..limbs {
+                            count.sum([F::one()], F::from(XOR_TAG));
+                        }
+                    }
+                    for _ in 0..3 {
+                        count.sum(limb_weights::<F>(limbs), F::zero());
+                    }
+                }
+                // Rejected by `circuit`
+                _ => {}
+            }
+        }
+        count.gates
+    }
+}
+
+/// Added to the result column of the XOR table, keeping its rows apart
+/// from the AND table's
+const XOR_TAG: u64 = 1 << LIMB_BITS;
+
+fn limbs(lhs: &FunctionInput, rhs: &FunctionInput) -> usize {
+    lhs.num_bits.max(rhs.num_bits).div_ceil(LIMB_BITS) as usize
+}
+
+/// 16^i for each limb
+fn limb_weights<F: PrimeField>(limbs: usize) -> impl Iterator<Item = F> {
+    core::iter::successors(Some(F::one()), |w| Some(*w * F::from(1u64 << LIMB_BITS))).take(limbs)
+}
+
+impl<F: PrimeField> Synthesize<F> for AcirCircuit<'_, F> {
+    fn synthesize(&self, circuit: &mut Circuit<F>) {
+        circuit.namespace("acir", |circuit| {
+            let mut lowering = AcirLowering { lowering: Lowering::new(circuit), witness: self.witness, vars: BTreeMap::new(), tables: [false; 2] };
+            for witness in self.program.public_inputs() {
+                lowering.public(*witness);
+            }
+            for opcode in &self.program.opcodes {
+                lowering.opcode(opcode);
+            }
+        });
+    }
+}
+
+/// A `Lowering` with the variable of each witness, made on first use
+struct AcirLowering<'a, 'c, F: PrimeField> {
+    lowering: Lowering<'c, F>,
+    witness: &'a WitnessMap<F>,
+    vars: BTreeMap<Witness, Variable>,
+    /// Whether the AND and the XOR table were added
+    tables: [bool; 2],
+}
+
+impl<F: PrimeField> AcirLowering<'_, '_, F> {
+    fn value(&self, witness: Witness) -> F {
+        self.witness.get(&witness).copied().unwrap_or_default()
+    }
+
+    fn var(&mut self, witness: Witness) -> Variable {
+        if let Some(var) = self.vars.get(&witness) {
+            return *var;
+        }
+        let var = self.lowering.circuit.alloc(self.value(witness));
+        self.vars.insert(witness, var);
+        var
+    }
+
+    fn public(&mut self, witness: Witness) {
+        let var = Variable::from(&self.lowering.circuit.add_public_input(self.value(witness)));
+        match self.vars.get(&witness) {
+            Some(first) => self.lowering.circuit.assert_equal(*first, var),
+            None => {
+                self.vars.insert(witness, var);
+            }
+        }
+    }
+
+    fn opcode(&mut self, opcode: &Opcode<F>) {
+        match opcode {
+            Opcode::AssertZero(e) => {
+                let products: Vec<(F, Variable)> = e
+                    .mul_terms
+                    .iter()
+                    .map(|(q, a, b)| {
+                        let (a, b) = (self.var(*a), self.var(*b));
+                        (*q, self.lowering.circuit.mul(a, b))
+                    })
+                    .collect();
+                let linear: Vec<(F, Variable)> = e.linear_combinations.iter().map(|(q, w)| (*q, self.var(*w))).collect();
+                let sum = self.lowering.sum(products.into_iter().chain(linear), e.q_c);
+                self.lowering.circuit.assert_zero(sum);
+            }
+            Opcode::BlackBoxFuncCall(BlackBoxFuncCall::Range { input }) => {
+                let var = self.var(input.witness);
+                assert_bits(self.lowering.circuit, var, input.num_bits as usize);
+            }
+            Opcode::BlackBoxFuncCall(BlackBoxFuncCall::And { lhs, rhs, output }) => self.bitwise(lhs, rhs, *output, false),
+            Opcode::BlackBoxFuncCall(BlackBoxFuncCall::Xor { lhs, rhs, output }) => self.bitwise(lhs, rhs, *output, true),
+            // `Program::circuit` rejected the rest
+            _ => {}
+        }
+    }
+
+    fn bitwise(&mut self, lhs: &FunctionInput, rhs: &FunctionInput, output: Witness, xor: bool) {
+        let limbs = limbs(lhs, rhs);
+        if !core::mem::replace(&mut self.tables[xor as usize], true) {
+            let table = Table::from_binary_op(XOR_TAG, |x, y| if xor { (x ^ y) + XOR_TAG } else { x & y });
+            self.lowering.circuit.add_table(table);
+        }
+        let [l, r, o] = [lhs.witness, rhs.witness, output].map(|witness| {
+            let bits = self.value(witness).into_bigint().to_bits_le();
+            let limb_values = bits.chunks(LIMB_BITS as usize).map(|chunk| chunk.iter().rev().fold(0u64, |acc, bit| 2 * acc + *bit as u64));
+            let values: Vec<u64> = limb_values.chain(core::iter::repeat(0)).take(limbs).collect();
+            let vars: Vec<Variable> = values.into_iter().map(|value| self.lowering.circuit.alloc(F::from(value))).collect();
+            let recombined = self.lowering.sum(limb_weights(limbs).zip(vars.iter().copied()), F::zero());
+            let var = self.var(witness);
+            self.lowering.circuit.assert_equal(var, recombined);
+            vars
+        });
+        for ((l, r), o) in l.into_iter().zip(r).zip(o) {
+            let o = if xor { self.lowering.sum([(F::one(), o)], F::from(XOR_TAG)) } else { o };
+            self.lowering.circuit.lookup(l, r, o);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bn254::Fr;
+
+    use crate::constraint_system::ConstraintSystem;
+
+    /// fn main(x: u8, y: pub u8) -> pub u8 { let p = x * y; ((x ^ y) & y) + p }
+    /// with witnesses x = 0, y = 1, x ^ y = 2, the result 3, p = 4 and
+    /// the sum returned in 5
+    fn program() -> Program<Fr> {
+        let input = |w: u32| FunctionInput { witness: Witness(w), num_bits: 8 };
+        let one = Fr::from(1u64);
+        Program {
+            current_witness_index: 5,
+            opcodes: vec![
+                Opcode::BlackBoxFuncCall(BlackBoxFuncCall::Range { input: input(0) }),
+                Opcode::BlackBoxFuncCall(BlackBoxFuncCall::Range { input: input(1) }),
+                Opcode::BlackBoxFuncCall(BlackBoxFuncCall::Xor { lhs: input(0), rhs: input(1), output: Witness(2) }),
+                Opcode::BlackBoxFuncCall(BlackBoxFuncCall::And { lhs: input(2), rhs: input(1), output: Witness(3) }),
+                Opcode::AssertZero(Expression { mul_terms: vec![(one, Witness(0), Witness(1))], linear_combinations: vec![(-one, Witness(4))], q_c: Fr::from(0u64) }),
+                Opcode::AssertZero(Expression {
+                    mul_terms: vec![],
+                    linear_combinations: vec![(one, Witness(3)), (one, Witness(4)), (-one, Witness(5))],
+                    q_c: Fr::from(0u64),
+                }),
+            ],
+            public_parameters: vec![Witness(1)],
+            return_values: vec![Witness(5)],
+        }
+    }
+
+    fn solve(x: u64, y: u64) -> WitnessMap<Fr> {
+        let values = [x, y, x ^ y, (x ^ y) & y, x * y, ((x ^ y) & y) + x * y];
+        values.iter().enumerate().map(|(i, value)| (Witness(i as u32), Fr::from(*value))).collect()
+    }
+
+    #[test]
+    fn test_lowering() {
+        let program = program();
+        let witness = solve(0xa5, 0x3c);
+        let acir = program.circuit(&witness).unwrap();
+        let mut circuit = Circuit::new(program.gates());
+        acir.synthesize(&mut circuit);
+        assert_eq!((circuit.error(), circuit.gates.len()), (None, program.gates()));
+        assert!(circuit.first_unsatisfied().is_none());
+        assert!(circuit.verify_constraints().is_satisfied() && circuit.verify_copy_constraints());
+        assert_eq!(circuit.public_input_values(), [0x3c, 0x18 + 0xa5 * 0x3c].map(Fr::from));
+        assert_eq!(circuit.table.len(), 512);
+
+        // A wrong XOR result misses the table
+        let mut wrong = witness.clone();
+        wrong.insert(Witness(2), Fr::from(0x98u64));
+        let mut circuit = Circuit::new(program.gates());
+        program.circuit(&wrong).unwrap().synthesize(&mut circuit);
+        assert!(circuit.first_unsatisfied().is_some_and(|gate| gate.namespace == "acir"));
+        assert!(!circuit.verify_constraints().is_satisfied());
+
+        // The layout does not depend on the witness
+        let empty = WitnessMap::new();
+        let keyed = ConstraintSystem::synthesize(&program.circuit(&empty).unwrap(), program.gates()).unwrap().0;
+        assert_eq!(ConstraintSystem::synthesize(&acir, program.gates()).unwrap().0, keyed);
+
+        let mut bad = program.clone();
+        bad.return_values.push(Witness(6));
+        assert_eq!(bad.circuit(&witness).unwrap_err(), AcirError::WitnessOutOfRange(Witness(6)));
+        bad.return_values.pop();
+        bad.opcodes.push(Opcode::BlackBoxFuncCall(BlackBoxFuncCall::Range { input: FunctionInput { witness: Witness(0), num_bits: 254 } }));
+        assert_eq!(bad.circuit(&witness).unwrap_err(), AcirError::BitSize(254));
+
+        for (opcode, name) in [
+            (Opcode::BlackBoxFuncCall(BlackBoxFuncCall::Other { name: "sha256" }), "sha256"),
+            (Opcode::MemoryInit { block_id: 0, init: vec![Witness(0)] }, "MemoryInit"),
+            (Opcode::MemoryOp { block_id: 0 }, "MemoryOp"),
+            (Opcode::BrilligCall { id: 0 }, "BrilligCall"),
+            (Opcode::Call { id: 1 }, "Call"),
+        ] {
+            let mut unsupported = program.clone();
+            unsupported.opcodes.insert(1, opcode);
+            assert_eq!(unsupported.circuit(&witness).unwrap_err(), AcirError::Unsupported(name));
+        }
+    }
+
+    #[test]
+    #[cfg(all(feature = "prover", feature = "verifier"))]
+    fn test_lowered_program_proves() {
+        use crate::bn254::{Params, Proof, ProvingKey};
+        use crate::prover::Prover;
+        use crate::verifier::Verifier;
+
+        let mut rng = ark_std::test_rng();
+        let program = program();
+        let (cs, witness) = ConstraintSystem::synthesize(&program.circuit(&solve(7, 200)).unwrap(), program.gates()).unwrap();
+        let circuit = cs.assign(&witness).unwrap();
+        let pk = ProvingKey::preprocess(Params::setup(ProvingKey::srs_degree_for(cs.rows), &mut rng), &circuit).unwrap();
+        let proof: Proof = Prover::prove(&circuit, &pk, &mut rng).unwrap();
+        let statement = [200u64, ((7 ^ 200) & 200) + 7 * 200].map(Fr::from);
+        assert_eq!(Verifier::verify(&pk.vk, &statement, &proof), Ok(()));
+        assert!(Verifier::verify(&pk.vk, &[statement[0], statement[1] + Fr::from(1u64)], &proof).is_err());
+    }
+}
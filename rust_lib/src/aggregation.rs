@@ -0,0 +1,441 @@
+//! Aggregating many proofs of one circuit into one, for batch settlement.
+//!
+//! The proofs are the leaves of a binary tree of wrapper circuits. A
+//! wrapper verifies its two children with `gadgets::plonk`: it replays
+//! each child's Poseidon transcript and makes every check of the
+//! verifier that lives in the scalar field, then hashes the children's
+//! statements and the values their deferred openings need into its one
+//! public input, a digest. The wrappers of a level are proved in
+//! parallel with the `parallel` feature, and a level with an odd number
+//! of nodes pairs its last one with itself. The root's digest stands for
+//! every statement in the batch.
+//!
+//! What no wrapper checks is a pairing: a child's opening has its
+//! coordinates in the base field. Settling an `Aggregate` verifies the
+//! root, recomputes the digests up from the leaves' statements, and
+//! checks every opening below the root in one multi-pairing with a
+//! `DeferredAccumulator`. That needs the commitments of every proof in
+//! the tree, but none of their transcripts or field checks.
+
+use alloc::{vec, vec::Vec};
+#[cfg(feature = "prover")]
+use ark_ec::AffineRepr;
+use ark_ff::PrimeField;
+#[cfg(feature = "prover")]
+use ark_std::rand::{rngs::StdRng, Rng, SeedableRng};
+#[cfg(feature = "prover")]
+use ark_std::Zero;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+use crate::circuit::{Circuit, CircuitError, Variable};
+#[cfg(feature = "prover")]
+use crate::constraint_system::{ConstraintSystem, Witness};
+use crate::gadgets::plonk::{assign_proof, exposed, verify_deferred, DeferredAccumulator, SettleError};
+use crate::gadgets::poseidon::Gates;
+#[cfg(feature = "prover")]
+use crate::kgz::KZGParams;
+use crate::msm::CurvePairing;
+use crate::poseidon::{Arithmetic, Native};
+#[cfg(feature = "prover")]
+use crate::proof::ProofEvaluations;
+use crate::proof::{Proof, VerifyingKey};
+#[cfg(feature = "prover")]
+use crate::prover::{KeyError, PlonkCircuit, ProveError, Prover, ProverConfig, ProvingKey};
+use crate::transcript::{PoseidonTranscript, TranscriptHash};
+use crate::verifier::{Verifier, VerifyError};
+
+/// Protocol label of the digest a wrapper makes public
+const DIGEST_LABEL: &[u8] = b"plonk-aggregate-v1";
+
+/// Rows of a wrapper circuit
+pub const WRAPPER_ROWS: usize = 1 << 16;
+
+/// What a wrapper exposed of each of its children, the values
+/// `plonk::exposed` lists
+pub type Exposed<F> = [Vec<F>; 2];
+
+/// A child as `digest` takes it: its statement and what was exposed of it
+type Child<'a, T> = (&'a [T], &'a [T]);
+
+/// A wrapper proof, with what it hashed of its children
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Node<E: CurvePairing> {
+    pub proof: Proof<E>,
+    pub exposed: Exposed<E::ScalarField>,
+}
+
+/// A tree of wrapper proofs over a batch
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Aggregate<E: CurvePairing> {
+    /// Each level of wrappers from the leaves' parents up, the last one
+    /// holding the root alone
+    pub levels: Vec<Vec<Node<E>>>,
+}
+
+/// Why aggregating or settling a batch failed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AggregateError {
+    /// Nothing to aggregate
+    Empty,
+    /// More proofs than the keyed levels hold
+    TooMany { max: usize, got: usize },
+    /// A wrapper circuit recorded an error, such as a statement of the
+    /// wrong length for the leaf key
+    Circuit(CircuitError),
+    #[cfg(feature = "prover")]
+    Key(KeyError),
+    #[cfg(feature = "prover")]
+    Prove(ProveError),
+    /// Levels that are not those of a tree over the leaves
+    Shape,
+    /// Exposed values that are not a child proof's, or openings that do
+    /// not verify
+    Settle(SettleError),
+    /// The root proof does not verify against the recomputed digest
+    Root(VerifyError),
+}
+
+impl core::fmt::Display for AggregateError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            AggregateError::Empty => write!(f, "no proofs to aggregate"),
+            AggregateError::TooMany { max, got } => write!(f, "{} proofs exceed the {} the keyed levels hold", got, max),
+            AggregateError::Circuit(e) => write!(f, "wrapper circuit: {}", e),
+            #[cfg(feature = "prover")]
+            AggregateError::Key(e) => write!(f, "keying a wrapper: {}", e),
+            #[cfg(feature = "prover")]
+            AggregateError::Prove(e) => write!(f, "proving a wrapper: {}", e),
+            AggregateError::Shape => write!(f, "aggregate levels do not match the batch"),
+            AggregateError::Settle(e) => write!(f, "{}", e),
+            AggregateError::Root(e) => write!(f, "root proof: {}", e),
+        }
+    }
+}
+
+/// Levels of wrappers over `proofs` leaves, at least one
+fn depth_for(proofs: usize) -> usize {
+    (proofs.next_power_of_two().trailing_zeros() as usize).max(1)
+}
+
+/// The two children of node `j` on a level above `below` nodes
+fn children(j: usize, below: usize) -> [usize; 2] {
+    [2 * j, (2 * j + 1).min(below - 1)]
+}
+
+/// The statement of a wrapper whose children have the given statements
+/// and exposed values, natively or in a circuit
+pub fn digest<F: PrimeField, A: Arithmetic<F>>(arith: A, children: [Child<A::Value>; 2]) -> A::Value {
+    let mut transcript = PoseidonTranscript::new(arith, DIGEST_LABEL);
+    for (statement, exposed) in children {
+        transcript.absorb(b"statement", statement);
+        transcript.absorb(b"exposed", exposed);
+    }
+    transcript.squeeze(b"digest")
+}
+
+/// Lays out a wrapper over `children`, each a statement and a proof made
+/// with `TranscriptHash::Poseidon` in `session` for the circuit of `vk`,
+/// with their `digest` as its one public input. Returns what it exposed
+/// of each child.
+pub fn wrap<E: CurvePairing>(
+    cs: &mut Circuit<E::ScalarField>,
+    vk: &VerifyingKey<E>,
+    session: &[u8],
+    children: [(&[E::ScalarField], &Proof<E>); 2],
+) -> Exposed<E::ScalarField> {
+    let [left, right] = children.map(|(statement, proof)| {
+        let public_inputs: Vec<Variable> = statement.iter().map(|x| cs.alloc(*x)).collect();
+        let assigned = assign_proof(cs, vk, session, &public_inputs, proof);
+        let opening = verify_deferred(cs, vk, &public_inputs, &assigned);
+        (public_inputs, exposed(&assigned, &opening))
+    });
+    let root = cs.namespace("digest", |cs| digest(Gates::new(cs), [(&left.0, &left.1), (&right.0, &right.1)]));
+    let public = cs.add_public_input(cs.value(root));
+    cs.assert_equal(root, Variable::from(&public));
+    [left.1, right.1].map(|vars| vars.into_iter().map(|var| cs.value(var)).collect())
+}
+
+/// What settling an `Aggregate` takes: the leaf circuit's key and the
+/// key of each level of wrappers. The leaves' session is checked in the
+/// first level's circuit, so its key stands for it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AggregationKeys<E: CurvePairing> {
+    pub leaf: VerifyingKey<E>,
+    pub levels: Vec<VerifyingKey<E>>,
+}
+
+impl<E: CurvePairing> AggregationKeys<E> {
+    /// Checks that `aggregate` proves the statement of every proof in
+    /// `leaves`, in order: the root against the digests recomputed from
+    /// them, then every deferred opening in one multi-pairing
+    pub fn verify(&self, leaves: &[(&[E::ScalarField], &Proof<E>)], aggregate: &Aggregate<E>) -> Result<(), AggregateError> {
+        if leaves.is_empty() {
+            return Err(AggregateError::Empty);
+        }
+        if aggregate.levels.len() != depth_for(leaves.len()) || aggregate.levels.len() > self.levels.len() {
+            return Err(AggregateError::Shape);
+        }
+
+        let mut accumulator = DeferredAccumulator::new();
+        let mut statements: Vec<Vec<E::ScalarField>> = leaves.iter().map(|(statement, _)| statement.to_vec()).collect();
+        let mut proofs: Vec<&Proof<E>> = leaves.iter().map(|(_, proof)| *proof).collect();
+        let mut vk = &self.leaf;
+        for (nodes, level_vk) in aggregate.levels.iter().zip(&self.levels) {
+            if nodes.len() != proofs.len().div_ceil(2) {
+                return Err(AggregateError::Shape);
+            }
+            let mut digests = Vec::with_capacity(nodes.len());
+            for (j, node) in nodes.iter().enumerate() {
+                let [left, right] = children(j, proofs.len());
+                for (child, exposed) in [left, right].into_iter().zip(&node.exposed) {
+                    accumulator.push(vk, proofs[child], exposed).map_err(AggregateError::Settle)?;
+                }
+                let [left, right] = [(left, &node.exposed[0]), (right, &node.exposed[1])];
+                digests.push(vec![digest(Native, [(&statements[left.0], left.1), (&statements[right.0], right.1)])]);
+            }
+            statements = digests;
+            proofs = nodes.iter().map(|node| &node.proof).collect();
+            vk = level_vk;
+        }
+        Verifier::verify_with_transcript(vk, &[], TranscriptHash::Poseidon, &statements[0], proofs[0]).map_err(AggregateError::Root)?;
+        accumulator.settle().map_err(AggregateError::Settle)
+    }
+}
+
+/// Proves trees of wrappers over proofs of one circuit
+#[cfg(feature = "prover")]
+pub struct Aggregator<E: CurvePairing> {
+    leaf: VerifyingKey<E>,
+    session: Vec<u8>,
+    /// One per level, the leaves' parents first
+    levels: Vec<ProvingKey<E>>,
+}
+
+#[cfg(feature = "prover")]
+impl<E: CurvePairing> Aggregator<E> {
+    /// Keys the wrappers of trees over up to 2^`depth` proofs made with
+    /// `TranscriptHash::Poseidon` in `session` for the circuit of `leaf`.
+    /// Every level gets its own circuit, each verifying the one below,
+    /// all keyed with `params`.
+    pub fn setup(params: &KZGParams<E>, leaf: VerifyingKey<E>, session: &[u8], depth: usize) -> Result<Self, AggregateError> {
+        let placeholder = placeholder::<E>();
+        let mut levels: Vec<ProvingKey<E>> = Vec::new();
+        for _ in 0..depth.max(1) {
+            let (vk, session) = match levels.last() {
+                Some(pk) => (&pk.vk, &[][..]),
+                None => (&leaf, session),
+            };
+            let statement = vec![E::ScalarField::zero(); vk.public_rows.len()];
+            let (circuit, _) = synthesize(vk, session, [(&statement, &placeholder); 2])?;
+            let pk = ProvingKey::preprocess(params.clone(), &circuit).map_err(AggregateError::Key)?;
+            levels.push(pk);
+        }
+        Ok(Self { leaf, session: session.to_vec(), levels })
+    }
+
+    /// The keys that settle this aggregator's trees
+    pub fn keys(&self) -> AggregationKeys<E> {
+        AggregationKeys {
+            leaf: self.leaf.clone(),
+            levels: self.levels.iter().map(|pk| pk.vk.clone()).collect(),
+        }
+    }
+
+    /// Proves the tree over `leaves`, each a statement and its proof, a
+    /// level at a time. The order of `leaves` is part of the result.
+    pub fn aggregate<R: Rng>(&self, leaves: &[(&[E::ScalarField], &Proof<E>)], rng: &mut R) -> Result<Aggregate<E>, AggregateError> {
+        if leaves.is_empty() {
+            return Err(AggregateError::Empty);
+        }
+        let depth = depth_for(leaves.len());
+        if depth > self.levels.len() {
+            return Err(AggregateError::TooMany { max: 1 << self.levels.len(), got: leaves.len() });
+        }
+
+        let config = ProverConfig { transcript: TranscriptHash::Poseidon, ..Default::default() };
+        let mut statements: Vec<Vec<E::ScalarField>> = leaves.iter().map(|(statement, _)| statement.to_vec()).collect();
+        let mut levels: Vec<Vec<Node<E>>> = Vec::with_capacity(depth);
+        for (k, pk) in self.levels[..depth].iter().enumerate() {
+            let (vk, session) = match k {
+                0 => (&self.leaf, &self.session[..]),
+                _ => (&self.levels[k - 1].vk, &[][..]),
+            };
+            let proofs: Vec<&Proof<E>> = match levels.last() {
+                Some(nodes) => nodes.iter().map(|node| &node.proof).collect(),
+                None => leaves.iter().map(|(_, proof)| *proof).collect(),
+            };
+            // Drawn up front, so the nodes do not depend on how many
+            // threads prove them
+            let seeds: Vec<[u8; 32]> = (0..proofs.len().div_ceil(2)).map(|_| rng.gen()).collect();
+            let prove = |(j, seed): (usize, &[u8; 32])| -> Result<_, AggregateError> {
+                let children = children(j, proofs.len()).map(|child| (&statements[child][..], proofs[child]));
+                let (circuit, exposed) = synthesize(vk, session, children)?;
+                let proof = Prover::prove_with_config(&circuit, pk, &config, &mut StdRng::from_seed(*seed)).map_err(AggregateError::Prove)?;
+                Ok((Node { proof, exposed }, circuit.public_inputs.clone()))
+            };
+            #[cfg(feature = "parallel")]
+            let proved: Vec<_> = seeds.par_iter().enumerate().map(prove).collect::<Result<_, _>>()?;
+            #[cfg(not(feature = "parallel"))]
+            let proved: Vec<_> = seeds.iter().enumerate().map(prove).collect::<Result<_, _>>()?;
+            let (nodes, digests) = proved.into_iter().unzip();
+            statements = digests;
+            levels.push(nodes);
+        }
+        Ok(Aggregate { levels })
+    }
+}
+
+/// A wrapper circuit as the prover takes it, with what it exposed
+#[cfg(feature = "prover")]
+type Synthesized<F> = (PlonkCircuit<F>, Exposed<F>);
+
+/// A wrapper over `children` as the prover takes it, with what it
+/// exposed of each
+#[cfg(feature = "prover")]
+fn synthesize<E: CurvePairing>(
+    vk: &VerifyingKey<E>,
+    session: &[u8],
+    children: [(&[E::ScalarField], &Proof<E>); 2],
+) -> Result<Synthesized<E::ScalarField>, AggregateError> {
+    let mut cs = Circuit::new(WRAPPER_ROWS);
+    let exposed = wrap(&mut cs, vk, session, children);
+    if let Some(error) = cs.error() {
+        return Err(AggregateError::Circuit(error.clone()));
+    }
+    let system = ConstraintSystem::from_circuit(&cs);
+    let witness = Witness::from_circuit(&cs, system.rows);
+    let circuit = system.assign(&witness).map_err(AggregateError::Circuit)?;
+    Ok((circuit, exposed))
+}
+
+/// A proof with the generator for every point, to lay out the wrapper
+/// circuits by; their layout does not depend on the values
+#[cfg(feature = "prover")]
+fn placeholder<E: CurvePairing>() -> Proof<E> {
+    let (g, zero) = (E::G1Affine::generator(), E::ScalarField::zero());
+    Proof {
+        wire_commitments: [g; 3],
+        lookup_commitments: [g; 2],
+        z_commitment: g,
+        z_lookup_commitment: g,
+        quotient_commitments: [g; 3],
+        evaluations: ProofEvaluations {
+            wires: [zero; 3],
+            sigmas: [zero; 2],
+            z_omega: zero,
+            lookup_selector: zero,
+            table: [zero; 3],
+            permuted: [zero; 2],
+            z_lookup_omega: zero,
+            permuted_input_omega: zero,
+            linearization: zero,
+        },
+        w_zeta: g,
+        w_zeta_omega: g,
+    }
+}
+
+#[cfg(all(test, feature = "prover"))]
+mod tests {
+    use super::*;
+    use crate::bn254::{Fr, Params, Proof, ProvingKey};
+    use crate::permutation::{Cell, PermutationBuilder};
+
+    const SESSION: &[u8] = b"batch-7";
+
+    /// (2 + 3)·k exposed as the statement
+    fn circuit(k: i64) -> PlonkCircuit<Fr> {
+        let f = |values: [i64; 4]| values.map(Fr::from).to_vec();
+        let mut builder = PermutationBuilder::new(3, 4);
+        builder.connect(Cell::new(0, 0), Cell::new(2, 2)).unwrap();
+        builder.connect(Cell::new(2, 1), Cell::new(0, 2)).unwrap();
+        PlonkCircuit {
+            q_l: f([1, 1, 0, 0]),
+            q_r: f([0, 1, 0, 0]),
+            q_o: f([0, -1, -1, 0]),
+            q_m: f([0, 0, 1, 0]),
+            q_c: f([0, 0, 0, 0]),
+            q_k: f([0, 0, 0, 0]),
+            a: f([5 * k, 2, 5, 0]),
+            b: f([0, 3, k, 0]),
+            c: f([0, 5, 5 * k, 0]),
+            permutation: builder.build(),
+            public_rows: vec![0],
+            public_inputs: vec![Fr::from(5 * k)],
+            table: Vec::new(),
+            custom_gates: Vec::new(),
+            q_custom: Vec::new(),
+        }
+    }
+
+    /// Statements and proofs for k = 1..=n
+    fn leaves(pk: &ProvingKey, n: i64) -> (Vec<[Fr; 1]>, Vec<Proof>) {
+        let config = ProverConfig { transcript: TranscriptHash::Poseidon, session: SESSION.to_vec(), ..Default::default() };
+        let mut rng = ark_std::test_rng();
+        let statements = (1..=n).map(|k| [Fr::from(5 * k)]).collect();
+        let proofs = (1..=n).map(|k| Prover::prove_with_config(&circuit(k), pk, &config, &mut rng).unwrap()).collect();
+        (statements, proofs)
+    }
+
+    #[test]
+    fn test_wrapper_checks_children() {
+        let pk = ProvingKey::preprocess(Params::setup(9, &mut ark_std::test_rng()), &circuit(1)).unwrap();
+        let (statements, proofs) = leaves(&pk, 2);
+        let children = [0, 1].map(|i| (&statements[i][..], &proofs[i]));
+
+        let mut cs = Circuit::new(WRAPPER_ROWS);
+        let exposed = wrap(&mut cs, &pk.vk, SESSION, children);
+        assert_eq!((cs.error(), cs.first_unsatisfied()), (None, None));
+        let expected = digest(Native, [0, 1].map(|i| (&statements[i][..], &exposed[i][..])));
+        assert_eq!(cs.public_input_values(), vec![expected]);
+
+        // The openings the wrapper deferred settle against the children
+        let mut accumulator = DeferredAccumulator::new();
+        for ((_, proof), exposed) in children.iter().zip(&exposed) {
+            accumulator.push(&pk.vk, proof, exposed).unwrap();
+        }
+        assert_eq!(accumulator.settle(), Ok(()));
+
+        // A child under another statement or session fails the wrapper
+        let wrong = [Fr::from(1u64)];
+        for (session, statement) in [(SESSION, &wrong[..]), (&b"batch-8"[..], &statements[1][..])] {
+            let mut cs = Circuit::new(WRAPPER_ROWS);
+            wrap(&mut cs, &pk.vk, session, [children[0], (statement, &proofs[1])]);
+            assert_eq!(cs.first_unsatisfied().map(|gate| gate.namespace.as_str()), Some("plonk"));
+        }
+    }
+
+    #[test]
+    #[ignore = "proves three wrappers of 2^16 rows, run in release"]
+    fn test_aggregate_tree() {
+        let mut rng = ark_std::test_rng();
+        let params = Params::setup(ProvingKey::srs_degree_for(WRAPPER_ROWS), &mut rng);
+        let pk = ProvingKey::preprocess(params.clone(), &circuit(1)).unwrap();
+        let aggregator = Aggregator::setup(&params, pk.vk.clone(), SESSION, 2).unwrap();
+        let keys = aggregator.keys();
+        let (statements, proofs) = leaves(&pk, 5);
+        let items: Vec<(&[Fr], &Proof)> = statements.iter().map(|s| &s[..]).zip(&proofs).collect();
+
+        // Three leaves pair the last with itself
+        let aggregate = aggregator.aggregate(&items[..3], &mut rng).unwrap();
+        assert_eq!(aggregate.levels.iter().map(Vec::len).collect::<Vec<_>>(), [2, 1]);
+        assert_eq!(keys.verify(&items[..3], &aggregate), Ok(()));
+
+        assert_eq!(keys.verify(&items[..2], &aggregate), Err(AggregateError::Shape));
+        assert_eq!(keys.verify(&items[1..4], &aggregate), Err(AggregateError::Settle(SettleError::Commitments)));
+        let wrong = [Fr::from(1u64)];
+        let mut claimed = items[..3].to_vec();
+        claimed[0].0 = &wrong;
+        assert!(matches!(keys.verify(&claimed, &aggregate), Err(AggregateError::Root(_))));
+        let mut forged = aggregate.clone();
+        forged.levels[0][1].exposed[0][0] += Fr::from(1u64);
+        assert_eq!(keys.verify(&items[..3], &forged), Err(AggregateError::Settle(SettleError::Commitments)));
+        let mut forged = aggregate.clone();
+        *forged.levels[0][1].exposed[0].last_mut().unwrap() += Fr::from(1u64);
+        assert!(matches!(keys.verify(&items[..3], &forged), Err(AggregateError::Root(_))));
+
+        assert_eq!(aggregator.aggregate(&items, &mut rng), Err(AggregateError::TooMany { max: 4, got: 5 }));
+        assert_eq!(aggregator.aggregate(&[], &mut rng), Err(AggregateError::Empty));
+    }
+}
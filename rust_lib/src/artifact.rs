@@ -0,0 +1,150 @@
+//! Setup artifacts generated once and shipped: the SRS to provers and
+//! verifiers, proving keys to provers, verifying keys to verifiers. Proofs,
+//! witnesses and constraint systems use the same framing when stored or
+//! sent on their own.
+//!
+//! An artifact is an 8-byte tag naming its kind and format version,
+//! followed by its canonical compressed serialization. Decoding validates
+//! every point, so a file from an untrusted source is checked on load
+//! (for a large SRS this is most of the loading time).
+
+use alloc::vec::Vec;
+use ark_ff::PrimeField;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+#[cfg(feature = "std")]
+use std::path::Path;
+
+use crate::constraint_system::{ConstraintSystem, Witness};
+use crate::kgz::KZGParams;
+use crate::msm::CurvePairing;
+use crate::proof::{Proof, VerifyingKey};
+#[cfg(feature = "prover")]
+use crate::prover::ProvingKey;
+
+/// Why an artifact could not be read or written
+#[derive(Debug)]
+pub enum ArtifactError {
+    #[cfg(feature = "std")]
+    Io(std::io::Error),
+    /// The tag names another kind of artifact or another version
+    WrongKind,
+    /// The bytes after the tag do not decode
+    Malformed,
+}
+
+impl core::fmt::Display for ArtifactError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            #[cfg(feature = "std")]
+            ArtifactError::Io(e) => write!(f, "artifact I/O error: {}", e),
+            ArtifactError::WrongKind => write!(f, "artifact is of another kind or version"),
+            ArtifactError::Malformed => write!(f, "malformed artifact"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<std::io::Error> for ArtifactError {
+    fn from(e: std::io::Error) -> Self {
+        ArtifactError::Io(e)
+    }
+}
+
+/// A value that can be stored as a tagged setup artifact
+pub trait Artifact: CanonicalSerialize + CanonicalDeserialize {
+    /// Kind and format version
+    const TAG: [u8; 8];
+
+    fn encode_artifact(&self) -> Vec<u8> {
+        let mut bytes = Self::TAG.to_vec();
+        self.serialize_compressed(&mut bytes).expect("writing to a Vec");
+        bytes
+    }
+
+    fn decode_artifact(bytes: &[u8]) -> Result<Self, ArtifactError> {
+        let body = bytes.strip_prefix(&Self::TAG[..]).ok_or(ArtifactError::WrongKind)?;
+        Self::deserialize_compressed(body).map_err(|_| ArtifactError::Malformed)
+    }
+
+    #[cfg(feature = "std")]
+    fn save(&self, path: impl AsRef<Path>) -> Result<(), ArtifactError> {
+        Ok(std::fs::write(path, self.encode_artifact())?)
+    }
+
+    #[cfg(feature = "std")]
+    fn load(path: impl AsRef<Path>) -> Result<Self, ArtifactError> {
+        Self::decode_artifact(&std::fs::read(path)?)
+    }
+}
+
+impl<E: CurvePairing> Artifact for KZGParams<E> {
+    const TAG: [u8; 8] = *b"plnksrs1";
+}
+
+#[cfg(feature = "prover")]
+impl<E: CurvePairing> Artifact for ProvingKey<E> {
+    const TAG: [u8; 8] = *b"plnkpk01";
+}
+
+impl<E: CurvePairing> Artifact for VerifyingKey<E> {
+    const TAG: [u8; 8] = *b"plnkvk01";
+}
+
+/// Version 1 is the body of `Proof::to_bytes`: the commitments, the
+/// `ProofEvaluations` in field order, then W_ζ and W_ζω
+impl<E: CurvePairing> Artifact for Proof<E> {
+    const TAG: [u8; 8] = *b"plnkpf01";
+}
+
+/// Circuit structure, what a prover and a verifier in different
+/// processes must agree on; the layout is on its `CanonicalSerialize`
+impl<F: PrimeField> Artifact for ConstraintSystem<F> {
+    const TAG: [u8; 8] = *b"plnkcs01";
+}
+
+/// Secret, so only moved between a prover and its own front end
+impl<F: PrimeField> Artifact for Witness<F> {
+    const TAG: [u8; 8] = *b"plnkwt01";
+}
+
+#[cfg(all(test, feature = "prover", feature = "verifier"))]
+mod tests {
+    use super::*;
+    use crate::prover::fixtures::public_circuit;
+    use crate::prover::Prover;
+    use crate::verifier::Verifier;
+    use ark_bls12_381::{Bls12_381, Fr};
+
+    #[test]
+    fn test_artifact_files() {
+        let mut rng = ark_std::test_rng();
+        let dir = std::env::temp_dir().join(format!("plonk-artifacts-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let params = KZGParams::<Bls12_381>::setup(9, &mut rng);
+        params.save(dir.join("srs")).unwrap();
+        let loaded = KZGParams::<Bls12_381>::load(dir.join("srs")).unwrap();
+        assert_eq!(loaded, params);
+
+        // Preprocessed once, shipped to a prover and a verifier
+        ProvingKey::preprocess(loaded, &public_circuit(15)).unwrap().save(dir.join("pk")).unwrap();
+        let pk = ProvingKey::<Bls12_381>::load(dir.join("pk")).unwrap();
+        pk.vk.save(dir.join("vk")).unwrap();
+        let vk = VerifyingKey::<Bls12_381>::load(dir.join("vk")).unwrap();
+        let proof = Prover::prove(&public_circuit(15), &pk, &mut rng).unwrap();
+        assert!(Verifier::verify(&vk, &[Fr::from(15u64)], &proof).is_ok());
+
+        // The tag, then the canonical compressed bytes unchanged
+        let bytes = proof.encode_artifact();
+        assert_eq!((&bytes[..8], &bytes[8..]), (&b"plnkpf01"[..], &proof.to_bytes()[..]));
+        assert_eq!(Proof::<Bls12_381>::decode_artifact(&bytes).unwrap(), proof);
+        assert!(matches!(Proof::<Bls12_381>::decode_artifact(&vk.encode_artifact()), Err(ArtifactError::WrongKind)));
+
+        assert!(matches!(VerifyingKey::<Bls12_381>::load(dir.join("srs")), Err(ArtifactError::WrongKind)));
+        let mut bytes = vk.encode_artifact();
+        bytes.truncate(bytes.len() - 1);
+        assert!(matches!(VerifyingKey::<Bls12_381>::decode_artifact(&bytes), Err(ArtifactError::Malformed)));
+        assert!(matches!(KZGParams::<Bls12_381>::load(dir.join("missing")), Err(ArtifactError::Io(_))));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,172 @@
+use ark_ff::Field;
+
+/// Field arithmetic used in the hot loops (FFT butterflies, pointwise
+/// products). The implementation is picked at compile time: arkworks by
+/// default, blst's assembly for BLS12-381 Fr with the `blst` feature.
+/// Every operation takes whole slices, so a backend that accelerates only
+/// some fields picks its path once per call rather than per element.
+pub(crate) trait FieldBackend {
+    const NAME: &'static str;
+
+    /// a[i] *= b[i] over the common length
+    fn mul_slices<F: Field>(a: &mut [F], b: &[F]);
+
+    /// a[i] *= s
+    fn scale_slice<F: Field>(a: &mut [F], s: F);
+
+    /// The radix-2 butterflies of a transform on bit-reversed input
+    fn butterflies<F: Field>(a: &mut [F], twiddles: &[F]);
+}
+
+#[cfg_attr(feature = "blst", allow(dead_code))]
+pub(crate) struct Arkworks;
+
+impl FieldBackend for Arkworks {
+    const NAME: &'static str = "arkworks";
+
+    fn mul_slices<F: Field>(a: &mut [F], b: &[F]) {
+        mul_slices_with(a, b, |x, y| x * y);
+    }
+
+    fn scale_slice<F: Field>(a: &mut [F], s: F) {
+        for x in a.iter_mut() {
+            *x *= s;
+        }
+    }
+
+    fn butterflies<F: Field>(a: &mut [F], twiddles: &[F]) {
+        crate::fft::butterflies_with(a, twiddles, |x, y| x * y);
+    }
+}
+
+fn mul_slices_with<F: Field>(a: &mut [F], b: &[F], mul: impl Fn(F, F) -> F) {
+    for (x, y) in a.iter_mut().zip(b) {
+        *x = mul(*x, *y);
+    }
+}
+
+/// blst-backed arithmetic, only BLS12-381 Fr is accelerated and every
+/// other field falls back to arkworks
+#[cfg(feature = "blst")]
+pub(crate) struct Blst;
+
+#[cfg(feature = "blst")]
+impl FieldBackend for Blst {
+    const NAME: &'static str = "blst";
+
+    fn mul_slices<F: Field>(a: &mut [F], b: &[F]) {
+        match (as_fr_mut(a), as_fr(b)) {
+            (Some(a), Some(b)) => mul_slices_with(a, b, blst_fr_mul),
+            _ => Arkworks::mul_slices(a, b),
+        }
+    }
+
+    fn scale_slice<F: Field>(a: &mut [F], s: F) {
+        match (as_fr_mut(a), as_fr(core::slice::from_ref(&s))) {
+            (Some(a), Some(&[s])) => {
+                for x in a.iter_mut() {
+                    *x = blst_fr_mul(*x, s);
+                }
+            }
+            _ => Arkworks::scale_slice(a, s),
+        }
+    }
+
+    fn butterflies<F: Field>(a: &mut [F], twiddles: &[F]) {
+        match (as_fr_mut(a), as_fr(twiddles)) {
+            (Some(a), Some(twiddles)) => crate::fft::butterflies_with(a, twiddles, blst_fr_mul),
+            _ => Arkworks::butterflies(a, twiddles),
+        }
+    }
+}
+
+/// `a` as BLS12-381 Fr values if that is what F is
+#[cfg(feature = "blst")]
+fn as_fr<F: Field>(a: &[F]) -> Option<&[ark_bls12_381::Fr]> {
+    use core::any::TypeId;
+    // Safety: F and Fr are the same type, so the slice is reinterpreted
+    // as itself
+    (TypeId::of::<F>() == TypeId::of::<ark_bls12_381::Fr>())
+        .then(|| unsafe { core::slice::from_raw_parts(a.as_ptr().cast(), a.len()) })
+}
+
+/// `as_fr` for a mutable slice
+#[cfg(feature = "blst")]
+fn as_fr_mut<F: Field>(a: &mut [F]) -> Option<&mut [ark_bls12_381::Fr]> {
+    use core::any::TypeId;
+    // Safety: as in `as_fr`, and the borrow of `a` moves into the result
+    (TypeId::of::<F>() == TypeId::of::<ark_bls12_381::Fr>())
+        .then(|| unsafe { core::slice::from_raw_parts_mut(a.as_mut_ptr().cast(), a.len()) })
+}
+
+// Both libraries keep Fr in Montgomery form with R = 2^256, so the limbs
+// can be handed over as they are
+#[cfg(feature = "blst")]
+fn blst_fr_mul(a: ark_bls12_381::Fr, b: ark_bls12_381::Fr) -> ark_bls12_381::Fr {
+    let a = blst::blst_fr { l: a.0 .0 };
+    let b = blst::blst_fr { l: b.0 .0 };
+    let mut out = blst::blst_fr::default();
+    // Safety: all pointers refer to live, initialized blst_fr values
+    unsafe { blst::blst_fr_mul(&mut out, &a, &b) };
+    ark_bls12_381::Fr::new_unchecked(ark_ff::BigInt(out.l))
+}
+
+#[cfg(feature = "blst")]
+pub(crate) type Backend = Blst;
+
+#[cfg(not(feature = "blst"))]
+pub(crate) type Backend = Arkworks;
+
+/// Name of the field arithmetic backend compiled in
+pub fn backend_name() -> &'static str {
+    Backend::NAME
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bls12_381::Fr as ScalarField;
+    use ark_std::UniformRand;
+
+    fn check_backend<B: FieldBackend>() {
+        let mut rng = ark_std::test_rng();
+        let a: Vec<ScalarField> = (0..16).map(|_| ScalarField::rand(&mut rng)).collect();
+        let b: Vec<ScalarField> = (0..16).map(|_| ScalarField::rand(&mut rng)).collect();
+
+        let mut products = a.clone();
+        B::mul_slices(&mut products, &b);
+        for i in 0..16 {
+            assert_eq!(products[i], a[i] * b[i]);
+        }
+
+        let mut scaled = a.clone();
+        B::scale_slice(&mut scaled, b[0]);
+        assert_eq!(scaled[5], a[5] * b[0]);
+
+        let mut transformed = a.clone();
+        B::butterflies(&mut transformed, &b[..8]);
+        let mut expected = a.clone();
+        Arkworks::butterflies(&mut expected, &b[..8]);
+        assert_eq!(transformed, expected);
+
+        // fields the backend does not accelerate still work
+        let x: Vec<ark_bn254::Fr> = (0..4).map(|_| ark_bn254::Fr::rand(&mut rng)).collect();
+        let mut y = x.clone();
+        B::mul_slices(&mut y, &x);
+        assert_eq!(y[3], x[3] * x[3]);
+        B::scale_slice(&mut y, x[0]);
+        assert_eq!(y[1], x[1] * x[1] * x[0]);
+    }
+
+    #[test]
+    fn test_arkworks_backend() {
+        check_backend::<Arkworks>();
+    }
+
+    #[cfg(feature = "blst")]
+    #[test]
+    fn test_blst_backend() {
+        check_backend::<Blst>();
+        assert_eq!(backend_name(), "blst");
+    }
+}
@@ -0,0 +1,12 @@
+//! Command-line front end; see `plonk_lib::cli` for the file formats.
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match plonk_lib::cli::run(&args) {
+        Ok(message) => println!("{}", message),
+        Err(e) => {
+            eprintln!("error: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
@@ -0,0 +1,87 @@
+//! BN254 (alt_bn128), the curve of the EVM's ecAdd, ecMul and ecPairing
+//! precompiles, so a proof made here can be checked by a contract for the
+//! gas of a few precompile calls.
+//!
+//! The stack is generic over `Pairing`; this module only names the
+//! instantiation. Its scalar field has two-adicity 28, so circuits are
+//! limited to 2^28 rows, against 2^32 on BLS12-381. Proofs and keys meant
+//! for a contract are written with `Encoding::EVM`: each G1 point as the
+//! 64 bytes x ‖ y the precompiles take, each scalar as a 32-byte word.
+//! `contract_gen::solidity_verifier` writes the contract for a key.
+
+/// The pairing proofs are made over
+pub type Bn254 = ark_bn254::Bn254;
+
+/// Field circuits are written over
+pub type Fr = ark_bn254::Fr;
+
+pub type Params = crate::kgz::KZGParams<Bn254>;
+
+pub type Proof = crate::proof::Proof<Bn254>;
+
+pub type VerifyingKey = crate::proof::VerifyingKey<Bn254>;
+
+#[cfg(feature = "prover")]
+pub type ProvingKey = crate::prover::ProvingKey<Bn254>;
+
+#[cfg(all(test, feature = "prover", feature = "verifier"))]
+mod tests {
+    use super::*;
+    use crate::circuit::{Circuit, Variable};
+    use crate::constraint_system::{ConstraintSystem, Synthesize};
+    use crate::encoding::Encoding;
+    use crate::gadgets::merkle::{alloc_path, compute_root, verify_path};
+    use crate::poseidon::PoseidonParams;
+    use crate::prover::{Profile, Prover};
+    use crate::verifier::Verifier;
+    use ark_ec::AffineRepr;
+    use ark_ff::{BigInteger, PrimeField};
+
+    /// Membership of a private leaf under a public Merkle root
+    struct Membership {
+        params: PoseidonParams<Fr>,
+        leaf: Fr,
+        path: Vec<(Fr, bool)>,
+    }
+
+    impl Membership {
+        fn root(&self) -> Fr {
+            compute_root(&self.params, self.leaf, &self.path)
+        }
+    }
+
+    impl Synthesize<Fr> for Membership {
+        fn synthesize(&self, circuit: &mut Circuit<Fr>) {
+            let root = Variable::from(&circuit.add_public_input(self.root()));
+            let leaf = circuit.alloc(self.leaf);
+            let nodes = alloc_path(circuit, &self.path);
+            verify_path(circuit, &self.params, root, leaf, &nodes);
+        }
+    }
+
+    #[test]
+    fn test_prove_on_bn254() {
+        let mut rng = ark_std::test_rng();
+        let params = PoseidonParams::<Fr>::new();
+        let membership = |leaf: u64| Membership { params: params.clone(), leaf: Fr::from(leaf), path: vec![(Fr::from(7u64), false), (Fr::from(9u64), true)] };
+
+        let (cs, witness) = ConstraintSystem::synthesize(&membership(42), 1 << 12).unwrap();
+        let srs = Params::setup(ProvingKey::srs_degree_for(cs.rows), &mut rng);
+        let pk = ProvingKey::preprocess(srs, &cs.assign(&witness).unwrap()).unwrap();
+        let statement = [membership(42).root()];
+        let proof: Proof = Prover::prove(&cs.assign(&witness).unwrap(), &pk, &mut rng).unwrap();
+        assert_eq!(Verifier::verify(&pk.vk, &statement, &proof), Ok(()));
+        assert!(Verifier::verify(&pk.vk, &[membership(43).root()], &proof).is_err());
+
+        // What a contract reads: the first wire commitment as big-endian x ‖ y
+        let bytes = proof.to_bytes_with(Encoding::EVM);
+        assert_eq!(Profile::CheapVerifier.encoding(), Encoding::EVM);
+        // Proof sizes on BN254, whatever the custom gates
+        assert_eq!((bytes.len(), proof.to_bytes_with(Profile::SmallProof.encoding()).len()), (1248, 864));
+        let (x, y) = proof.wire_commitments[0].xy().unwrap();
+        assert_eq!(bytes[..64], [x.into_bigint().to_bytes_be(), y.into_bigint().to_bytes_be()].concat());
+        let decoded = Proof::from_bytes_with(&bytes, Encoding::EVM).unwrap();
+        let vk = VerifyingKey::from_bytes_with(&pk.vk.to_bytes_with(Encoding::EVM).unwrap(), Encoding::EVM).unwrap();
+        assert_eq!(Verifier::verify(&vk, &statement, &decoded), Ok(()));
+    }
+}
@@ -0,0 +1,141 @@
+use ark_ec::hashing::curve_maps::wb::WBMap;
+use ark_ec::hashing::map_to_curve_hasher::MapToCurveBasedHasher;
+use ark_ec::hashing::HashToCurve;
+use ark_ec::pairing::Pairing;
+use ark_ec::{AffineRepr, CurveGroup, PrimeGroup};
+use ark_ff::field_hashers::DefaultFieldHasher;
+use ark_std::rand::Rng;
+use ark_std::{UniformRand, Zero};
+use sha2::Sha256;
+use zeroize::Zeroize;
+
+use ark_bls12_377::{Bls12_377, Fr as InnerScalar, G1Affine, G1Projective, G2Affine, G2Projective};
+
+/// Curve configuration for proving statements about BLS12-377 pairings.
+///
+/// Proofs are made over BW6-761, whose scalar field is the base field of
+/// BLS12-377, so G1/G2 coordinates of the inner curve are native field
+/// elements of the circuit, with no non-native emulation. This module
+/// provides the configuration and native BLS signatures;
+/// `gadgets::bls12_377::verify_signature` checks them inside a circuit.
+pub type OuterPairing = ark_bw6_761::BW6_761;
+
+/// Pairing whose signatures and keys are checked inside the circuit
+pub type InnerPairing = Bls12_377;
+
+/// Field the circuit is written over; equal to `ark_bls12_377::Fq`
+pub type CircuitField = ark_bw6_761::Fr;
+
+/// Embeds a BLS12-377 base field element (a point coordinate) as a circuit
+/// value. This only compiles because the two fields are the same type.
+pub fn embed_coordinate(x: ark_bls12_377::Fq) -> CircuitField {
+    x
+}
+
+type G1Hasher = MapToCurveBasedHasher<
+    G1Projective,
+    DefaultFieldHasher<Sha256, 128>,
+    WBMap<ark_bls12_377::g1::Config>,
+>;
+
+const BLS_DST: &[u8] = b"BLS_SIG_BLS12377G1_XMD:SHA-256_SSWU_RO_NUL_";
+
+pub fn hash_to_g1(message: &[u8]) -> G1Affine {
+    G1Hasher::new(BLS_DST)
+        .expect("BLS12-377 G1 map parameters are valid")
+        .hash(message)
+        .expect("hash to curve cannot fail for BLS12-377 G1")
+}
+
+/// BLS key over BLS12-377 with signatures in G1 and public keys in G2, the
+/// layout validator sets on this curve commonly use
+#[derive(Debug, Clone)]
+pub struct BlsKeypair {
+    secret: InnerScalar,
+    pub public: G2Affine,
+}
+
+impl BlsKeypair {
+    pub fn generate<R: Rng>(rng: &mut R) -> Self {
+        let secret = InnerScalar::rand(rng);
+        Self {
+            secret,
+            public: (G2Projective::generator() * secret).into_affine(),
+        }
+    }
+
+    pub fn sign(&self, message: &[u8]) -> G1Affine {
+        (hash_to_g1(message) * self.secret).into_affine()
+    }
+}
+
+impl Drop for BlsKeypair {
+    fn drop(&mut self) {
+        self.secret.zeroize();
+    }
+}
+
+/// Native BLS verification:
+/// e(signature, g2) · e(-H(m), public) == 1
+pub fn bls_verify(public: &G2Affine, message: &[u8], signature: &G1Affine) -> bool {
+    verify_hashed(public, &hash_to_g1(message), signature)
+}
+
+/// Same check with the message already hashed to G1, the part
+/// `gadgets::bls12_377::verify_signature` arithmetizes
+pub fn verify_hashed(public: &G2Affine, hashed: &G1Affine, signature: &G1Affine) -> bool {
+    InnerPairing::multi_pairing(
+        [signature.into_group(), -hashed.into_group()],
+        [G2Affine::generator(), *public],
+    )
+    .is_zero()
+}
+
+/// Aggregate signature of many signers over one message, checked against
+/// the sum of their public keys
+pub fn aggregate(signatures: &[G1Affine]) -> G1Affine {
+    signatures.iter().fold(G1Projective::zero(), |acc, s| acc + s).into_affine()
+}
+
+pub fn aggregate_public_keys(keys: &[G2Affine]) -> G2Affine {
+    keys.iter().fold(G2Projective::zero(), |acc, k| acc + k).into_affine()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::kgz::KZGParams;
+    use ark_poly::univariate::DensePolynomial;
+    use ark_poly::DenseUVPolynomial;
+
+    #[test]
+    fn test_bls_sign_verify() {
+        let mut rng = ark_std::test_rng();
+        let keys: Vec<_> = (0..3).map(|_| BlsKeypair::generate(&mut rng)).collect();
+        let message = b"block 42";
+
+        let signature = keys[0].sign(message);
+        assert!(bls_verify(&keys[0].public, message, &signature));
+        assert!(!bls_verify(&keys[1].public, message, &signature));
+        assert!(!bls_verify(&keys[0].public, b"block 43", &signature));
+
+        let signatures: Vec<_> = keys.iter().map(|k| k.sign(message)).collect();
+        let publics: Vec<_> = keys.iter().map(|k| k.public).collect();
+        assert!(bls_verify(&aggregate_public_keys(&publics), message, &aggregate(&signatures)));
+    }
+
+    #[test]
+    fn test_outer_curve_commitments() {
+        let mut rng = ark_std::test_rng();
+        let params = KZGParams::<OuterPairing>::setup(9, &mut rng);
+
+        let signature = BlsKeypair::generate(&mut rng).sign(b"m");
+        let coeffs = vec![embed_coordinate(signature.x), embed_coordinate(signature.y), CircuitField::from(1u64)];
+        let poly = DensePolynomial::from_coefficients_vec(coeffs);
+
+        let commitment = params.commit(&poly).unwrap();
+        let z = CircuitField::from(5u64);
+        let (proof, value) = params.open(&poly, z).unwrap();
+        assert!(params.verify(&commitment, &proof, z, value));
+    }
+}
@@ -1,77 +1,303 @@
-use std::collections::HashMap;
-use std::ops::{Add, Mul};
-use ark_std::rand::Rng;
-
-// for testing purposes
-#[derive(Debug, Clone, Copy, PartialEq)]
-struct F(u64);
-impl F {
-    pub fn zero() -> Self {
-        F(0)
-    }
-    pub fn one() -> Self {
-        F(1)
-    }
-    pub fn rand<R: Rng>(rng: &mut R) -> Self {
-        F(rng.gen_range(0..=u64::MAX))
-    }
-}
-impl Add for F {
-    type Output = Self;
-
-    fn add(self, other: Self) -> Self {
-        F(self.0.wrapping_add(other.0))
-    }
-}
-impl Mul for F {
-    type Output = Self;
-
-    fn mul(self, other: Self) -> Self {
-        F(self.0.wrapping_mul(other.0))
-    }
-}
+use alloc::{collections::{BTreeMap, BTreeSet}, format, string::{String, ToString}, vec, vec::Vec};
+use ark_ff::PrimeField;
+use ark_poly::polynomial::univariate::DensePolynomial;
+use ark_poly::DenseUVPolynomial;
+use core::time::Duration;
 
+use crate::custom_gate::{CustomGate, MAX_IDENTITY_DEGREE};
+use crate::proof::ScheduleError;
+use crate::fft::{ifft, DomainError};
+use crate::lookup::Table;
+use crate::permutation::{coset_multipliers, Cell, Permutation, PermutationBuilder};
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum GateType {
     Add,
     Mul,
+    /// q_l·a + q_r·b + q_o·c + q_c = 0 with coefficients chosen by the
+    /// caller: constants, assertions and weighted sums. Added through
+    /// `constant`, `assert_equal`, `assert_zero` and `weighted_sum`, not
+    /// `add_gate`.
+    Linear,
+    /// (a, b, c) is a row of the circuit's lookup table, q_k = 1
+    Lookup,
+    /// The identities of custom gate i hold on (a, b, c), with its own
+    /// selector set and every built-in one zero
+    Custom(usize),
 }
 
 #[derive(Debug, Clone)]
-pub struct Wire {
+pub struct Wire<F> {
     pub index: usize,
     pub value: F,
 }
 
 #[derive(Debug)]
-pub struct Gate {
+pub struct Gate<F> {
     pub gate_type: GateType,
-    pub left_wire: Wire,
-    pub right_wire: Wire,
-    pub output_wire: Wire,
+    pub left_wire: Wire<F>,
+    pub right_wire: Wire<F>,
+    pub output_wire: Wire<F>,
 }
 
-/// Main circuit
+/// Main circuit, over the scalar field the prover commits in
 #[derive(Debug)]
-pub struct Circuit {
+pub struct Circuit<F: PrimeField> {
     pub n: usize,// Number of gates
     pub a: Vec<F>,// Left wire values
     pub b: Vec<F>, // right wire values
     pub c: Vec<F>, // output wire values
-    pub gates: Vec<Gate>, // gates
-    pub selectors: CircuitSelectors, // selectors
+    pub gates: Vec<Gate<F>>, // gates
+    pub selectors: CircuitSelectors<F>, // selectors
+    pub copies: Vec<(Cell, Cell)>, // explicit copy constraints
+    pub public_inputs: Vec<Cell>, // cells exposed to the verifier, in statement order
+    pub table: Vec<[F; 3]>, // rows of every table added, for lookup gates
+    pub custom_gates: Vec<CustomGate<F>>, // gates registered with `register_gate`
+    namespace: Vec<String>, // gadget path new gates are attributed to
+    namespace_runs: Vec<(usize, String)>, // first row of each stretch of gates under one path
+    wire_values: BTreeMap<usize, F>, // first value seen for each wire index
+    first_unsatisfied: Option<UnsatisfiedGate>,
+    error: Option<CircuitError>,
 }
 
-// elector polynomials
-#[derive(Debug, Clone)]
-pub struct CircuitSelectors {
-    pub q_add: Vec<F>,
-    pub q_mul: Vec<F>,
+/// Handle to a value in a `Circuit`, returned by `alloc`, `add` and `mul`.
+/// Every gate a variable is used in reads the same wire, so the copy
+/// constraints between those uses come for free.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Variable(usize);
+
+impl<F> From<&Wire<F>> for Variable {
+    fn from(wire: &Wire<F>) -> Self {
+        Variable(wire.index)
+    }
+}
+
+/// What was wrong with a gate when it was added
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Violation {
+    /// The values do not satisfy the gate's own constraint
+    Gate,
+    /// A wire carries a different value than an earlier use of its index
+    WireValue { index: usize },
+}
+
+/// First gate found unsatisfied while the witness was being built
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnsatisfiedGate {
+    pub row: usize,
+    /// Gadget namespaces enclosing the gate, joined by `/`
+    pub namespace: String,
+    pub violation: Violation,
+}
+
+impl core::fmt::Display for UnsatisfiedGate {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let namespace = if self.namespace.is_empty() { "<root>" } else { &self.namespace };
+        match self.violation {
+            Violation::Gate => write!(f, "gate {} in {} is not satisfied", self.row, namespace),
+            Violation::WireValue { index } => {
+                write!(f, "gate {} in {} reuses wire {} with a different value", self.row, namespace, index)
+            }
+        }
+    }
+}
+
+/// A gate `verify_constraints` found unsatisfied, with the wire values
+/// it was checked on
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FailedGate<F> {
+    pub row: usize,
+    pub gate_type: GateType,
+    pub left: F,
+    pub right: F,
+    pub output: F,
+    /// Gadget namespaces enclosing the gate, joined by `/`
+    pub namespace: String,
+}
+
+/// Every unsatisfied gate of a circuit, in row order
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConstraintReport<F> {
+    pub failures: Vec<FailedGate<F>>,
+}
+
+impl<F> ConstraintReport<F> {
+    pub fn is_satisfied(&self) -> bool {
+        self.failures.is_empty()
+    }
+}
+
+impl<F: core::fmt::Display> core::fmt::Display for ConstraintReport<F> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        if self.is_satisfied() {
+            return write!(f, "all gates are satisfied");
+        }
+        write!(f, "{} gates are not satisfied", self.failures.len())?;
+        for gate in &self.failures {
+            let namespace = if gate.namespace.is_empty() { "<root>" } else { &gate.namespace };
+            write!(
+                f,
+                "\n  gate {} ({:?}) in {}: a = {}, b = {}, c = {}",
+                gate.row, gate.gate_type, namespace, gate.left, gate.right, gate.output
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// A circuit that could not be built as asked. The gate-adding methods
+/// keep the first one in `Circuit::error` and skip the offending gate, so
+/// gadgets need not thread a `Result` through every call.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CircuitError {
+    /// More gates than the capacity given to `new`
+    Full { capacity: usize },
+    /// A gate added after `pad_to`
+    AlreadyPadded,
+    /// `pad_to` asked for fewer rows than there are gates
+    PadTooSmall { gates: usize, rows: usize },
+    /// A `Linear` gate through `add_gate`; only `constant`, `assert_*`
+    /// and `weighted_sum` supply its coefficients
+    LinearGate,
+    /// A custom gate id that `register_gate` never returned
+    UnknownGate(usize),
+    /// A `Variable` of another circuit, by wire index
+    UnknownVariable(usize),
+    /// `connect` on a cell of no gate
+    DanglingCell(Cell),
+    /// `switch` over fewer than two branches
+    TooFewBranches(usize),
+    /// A custom gate identity above `MAX_IDENTITY_DEGREE`
+    DegreeTooHigh { gate: String, degree: u32 },
+    /// A witness or batch that does not fit the rows of the constraint
+    /// system
+    WitnessRows { expected: usize, got: usize },
+    /// Edwards arithmetic on a curve whose addition law has exceptional
+    /// points
+    IncompleteCurve,
+    /// A multi-exponentiation window outside 1..=`MAX_WINDOW_BITS`
+    WindowSize(usize),
+    /// A blob evaluated over a domain whose size is not a power of two
+    BlobSize(usize),
+    /// A verifying key `gadgets::plonk::verify_deferred` cannot check
+    /// against, one `Verifier` rejects as malformed
+    MalformedKey,
+    /// A statement for `gadgets::plonk::verify_deferred` of the wrong
+    /// length
+    PublicInputCount { expected: usize, got: usize },
+    /// A public input on a row that already holds one
+    DuplicatePublicRow(usize),
+    /// `gadgets::plonk::assign_proof` replayed a transcript that left the
+    /// proof schedule
+    Schedule(ScheduleError),
+}
+
+impl core::fmt::Display for CircuitError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            CircuitError::Full { capacity } => write!(f, "circuit is full at {} gates", capacity),
+            CircuitError::AlreadyPadded => write!(f, "gate added after padding"),
+            CircuitError::PadTooSmall { gates, rows } => write!(f, "cannot pad {} gates into {} rows", gates, rows),
+            CircuitError::LinearGate => write!(f, "linear gates carry their own coefficients, add them with constant, assert_* or weighted_sum"),
+            CircuitError::UnknownGate(id) => write!(f, "no custom gate {} registered", id),
+            CircuitError::UnknownVariable(index) => write!(f, "wire {} is not a variable of this circuit", index),
+            CircuitError::DanglingCell(cell) => write!(f, "cell {:?} does not belong to any gate", cell),
+            CircuitError::TooFewBranches(n) => write!(f, "switch needs at least two branches, got {}", n),
+            CircuitError::DegreeTooHigh { gate, degree } => {
+                write!(f, "gate {} has degree {}, at most {} is supported", gate, degree, MAX_IDENTITY_DEGREE)
+            }
+            CircuitError::WitnessRows { expected, got } => write!(f, "witness has {} rows, needs {}", got, expected),
+            CircuitError::IncompleteCurve => write!(f, "curve addition law is not complete"),
+            CircuitError::WindowSize(bits) => write!(f, "window of {} bits is not supported", bits),
+            CircuitError::BlobSize(size) => write!(f, "blob of {} values is not a power of two", size),
+            CircuitError::MalformedKey => write!(f, "malformed verifying key"),
+            CircuitError::PublicInputCount { expected, got } => write!(f, "expected {} public inputs, got {}", expected, got),
+            CircuitError::DuplicatePublicRow(row) => write!(f, "row {} holds more than one public input", row),
+            CircuitError::Schedule(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+// selector polynomials, one row satisfying
+//   q_l·a + q_r·b + q_o·c + q_m·a·b + q_c + PI = 0
+// and, where q_k = 1, having (a, b, c) in the lookup table; `custom`
+// holds one more column per registered custom gate
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct CircuitSelectors<F> {
+    pub q_l: Vec<F>,
+    pub q_r: Vec<F>,
+    pub q_o: Vec<F>,
+    pub q_m: Vec<F>,
     pub q_c: Vec<F>,
+    pub q_k: Vec<F>,
+    pub custom: Vec<Vec<F>>,
 }
 
-impl Circuit {
+impl<F> CircuitSelectors<F> {
+    pub const NAMES: [&'static str; 6] = ["q_l", "q_r", "q_o", "q_m", "q_c", "q_k"];
+
+    pub fn from_columns([q_l, q_r, q_o, q_m, q_c, q_k]: [Vec<F>; 6]) -> Self {
+        Self { q_l, q_r, q_o, q_m, q_c, q_k, custom: Vec::new() }
+    }
+
+    /// The columns in `NAMES` order
+    pub fn columns(&self) -> [&Vec<F>; 6] {
+        [&self.q_l, &self.q_r, &self.q_o, &self.q_m, &self.q_c, &self.q_k]
+    }
+
+    pub fn columns_mut(&mut self) -> [&mut Vec<F>; 6] {
+        [&mut self.q_l, &mut self.q_r, &mut self.q_o, &mut self.q_m, &mut self.q_c, &mut self.q_k]
+    }
+}
+
+/// How rows between the last gate and the end of the evaluation domain
+/// are filled. Either way every padded row satisfies the row identity and
+/// its wires are not connected to anything, so they map to themselves in
+/// the permutation; padding never changes whether a circuit is satisfied.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PaddingStrategy {
+    /// All selectors and wire values zero
+    #[default]
+    Zero,
+    /// Copies of the last gate's row, selectors and values included
+    RepeatLast,
+}
+
+/// Gate and wiring counts of a circuit, from `Circuit::stats`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CircuitStats {
+    pub gates: usize,
+    /// Add gates other than public inputs
+    pub add: usize,
+    pub mul: usize,
+    pub linear: usize,
+    pub lookup: usize,
+    /// Gates of each registered custom gate, by id
+    pub custom: Vec<usize>,
+    pub public_inputs: usize,
+    /// Equalities σ enforces: each use of a wire after its first, and
+    /// each `connect`
+    pub copy_constraints: usize,
+    /// Rows the prover works over
+    pub domain_size: usize,
+    /// Single-threaded proving time from `MSM_NANOS_PER_POINT` and
+    /// `FFT_NANOS_PER_BUTTERFLY`, for comparing layouts rather than
+    /// predicting a given machine
+    pub estimated_proving_time: Duration,
+}
+
+/// Rough cost of one point of a G1 multi-exponentiation
+const MSM_NANOS_PER_POINT: u64 = 4_000;
+/// Rough cost of one butterfly of an FFT over the scalar field
+const FFT_NANOS_PER_BUTTERFLY: u64 = 30;
+/// Commitments a proof makes: three wires, A', S', z, z_L, three
+/// quotient pieces and two openings
+const PROOF_MSMS: u64 = 12;
+/// Polynomials the quotient round evaluates on its coset, besides one
+/// per custom gate
+const QUOTIENT_POLYNOMIALS: u64 = 20;
+
+impl<F: PrimeField> Circuit<F> {
     /// Creates a new empty circuit with specified size
     pub fn new(size: usize) -> Self {
         Circuit {
@@ -80,70 +306,730 @@ impl Circuit {
             b: Vec::with_capacity(size),
             c: Vec::with_capacity(size),
             gates: Vec::with_capacity(size),
-            selectors: CircuitSelectors {
-                q_add: vec![F::zero(); size],
-                q_mul: vec![F::zero(); size],
-                q_c: vec![F::zero(); size],
-            },
+            selectors: CircuitSelectors::from_columns([0, 1, 2, 3, 4, 5].map(|_| vec![F::zero(); size])),
+            copies: Vec::new(),
+            public_inputs: Vec::new(),
+            table: Vec::new(),
+            custom_gates: Vec::new(),
+            namespace: Vec::new(),
+            namespace_runs: Vec::new(),
+            wire_values: BTreeMap::new(),
+            first_unsatisfied: None,
+            error: None,
         }
     }
 
     /// Adds a new gate to the circuit
-    pub fn add_gate(&mut self, gate: Gate) {
+    pub fn add_gate(&mut self, gate: Gate<F>) {
+        let (zero, one) = (F::zero(), F::one());
+        let selectors = match gate.gate_type {
+            GateType::Add => [one, one, -one, zero, zero, zero],
+            GateType::Mul => [zero, zero, -one, one, zero, zero],
+            GateType::Lookup => [zero, zero, zero, zero, zero, one],
+            GateType::Custom(_) => [zero; 6],
+            GateType::Linear => return self.skip(&gate, CircuitError::LinearGate),
+        };
+        self.add_row(gate, selectors);
+    }
+
+    /// Adds `gate` with selectors q_l, q_r, q_o, q_m, q_c, q_k
+    fn add_row(&mut self, gate: Gate<F>, selectors: [F; 6]) {
         let idx = self.gates.len();
 
         if idx >= self.n {
-            panic!("Circuit is full. Cannot add more than {} gates.", self.n);
+            return self.skip(&gate, CircuitError::Full { capacity: self.n });
+        }
+        if self.a.len() > idx {
+            return self.skip(&gate, CircuitError::AlreadyPadded);
+        }
+        if let GateType::Custom(id) = gate.gate_type {
+            if id >= self.custom_gates.len() {
+                return self.skip(&gate, CircuitError::UnknownGate(id));
+            }
         }
         
-        match gate.gate_type {
-            GateType::Add => self.selectors.q_add[idx] = F::one(),
-            GateType::Mul => self.selectors.q_mul[idx] = F::one(),
+        for (column, q) in self.selectors.columns_mut().into_iter().zip(selectors) {
+            column[idx] = q;
+        }
+        if let GateType::Custom(id) = gate.gate_type {
+            self.selectors.custom[id][idx] = F::one();
         }
         
         self.a.push(gate.left_wire.value);
         self.b.push(gate.right_wire.value);
         self.c.push(gate.output_wire.value);
         self.gates.push(gate);
+        self.check_gate(idx);
+    }
+
+    /// Checks a freshly added gate and its wires against the values seen
+    /// so far, remembering the first violation
+    fn check_gate(&mut self, row: usize) {
+        let gate = &self.gates[row];
+        let mut violation = (!self.gate_satisfied(row)).then_some(Violation::Gate);
+        for wire in [&gate.left_wire, &gate.right_wire, &gate.output_wire] {
+            let seen = *self.wire_values.entry(wire.index).or_insert(wire.value);
+            if violation.is_none() && seen != wire.value {
+                violation = Some(Violation::WireValue { index: wire.index });
+            }
+        }
+        if let (Some(violation), None) = (violation, &self.first_unsatisfied) {
+            self.first_unsatisfied = Some(UnsatisfiedGate { row, namespace: self.namespace.join("/"), violation });
+        }
+    }
+
+    /// Runs `f` with every gate it adds attributed to `name`, nested under
+    /// the current namespace
+    pub fn namespace<T>(&mut self, name: &str, f: impl FnOnce(&mut Self) -> T) -> T {
+        self.namespace.push(name.to_string());
+        self.start_namespace_run();
+        let result = f(self);
+        self.namespace.pop();
+        self.start_namespace_run();
+        result
     }
 
-    /// Verifies that all constraints in the circuit are satisfied
-    pub fn verify_constraints(&self) -> bool {
-        for (i, gate) in self.gates.iter().enumerate() {
-            let a = self.a[i];
-            let b = self.b[i];
-            let c = self.c[i];
+    /// Attributes the gates from the next row on to the current namespace
+    fn start_namespace_run(&mut self) {
+        let run = (self.gates.len(), self.namespace.join("/"));
+        match self.namespace_runs.last_mut() {
+            Some(last) if last.0 == run.0 => *last = run,
+            _ => self.namespace_runs.push(run),
+        }
+    }
+
+    /// Namespace gate `row` was added under
+    fn namespace_of(&self, row: usize) -> &str {
+        match self.namespace_runs.partition_point(|(start, _)| *start <= row) {
+            0 => "",
+            i => &self.namespace_runs[i - 1].1,
+        }
+    }
+
+    /// Keeps `error` unless an earlier one is already kept
+    pub(crate) fn fail(&mut self, error: CircuitError) {
+        self.error.get_or_insert(error);
+    }
+
+    /// Fails with `error` instead of adding `gate`, still recording its
+    /// wire values so the variables it outputs can be read
+    fn skip(&mut self, gate: &Gate<F>, error: CircuitError) {
+        for wire in [&gate.left_wire, &gate.right_wire, &gate.output_wire] {
+            self.wire_values.entry(wire.index).or_insert(wire.value);
+        }
+        self.fail(error);
+    }
+
+    /// The first error met while building the circuit, if any. The gate
+    /// that caused it was not added, so the circuit must not be proved.
+    pub fn error(&self) -> Option<&CircuitError> {
+        self.error.as_ref()
+    }
+
+    /// The first gate that was unsatisfied when added, if any. Gates are
+    /// checked as they are added, so a bad witness is caught at the gadget
+    /// that produced it rather than when the prover divides by Z_H.
+    pub fn first_unsatisfied(&self) -> Option<&UnsatisfiedGate> {
+        self.first_unsatisfied.as_ref()
+    }
+
+    /// Smallest power-of-two domain that fits every gate, at least two
+    /// rows so that even an empty circuit has a first and a last row
+    pub fn padded_size(&self) -> usize {
+        domain_size(self.gates.len())
+    }
+
+    /// Fills the rows after the last gate up to `size` according to
+    /// `strategy` and grows the circuit to `size` rows. Padded rows exist
+    /// only in the wire and selector columns, not in `gates`.
+    pub fn pad_to(&mut self, size: usize, strategy: PaddingStrategy) -> Result<(), CircuitError> {
+        let used = self.gates.len();
+        if size < used {
+            return Err(CircuitError::PadTooSmall { gates: used, rows: size });
+        }
+
+        // A public input row only balances against its own PI term
+        let last_is_public = self.public_inputs.iter().any(|cell| cell.row + 1 == used);
+        let (row, selectors, custom) = match (strategy, used) {
+            (PaddingStrategy::RepeatLast, 1..) if !last_is_public => {
+                let last = used - 1;
+                (
+                    (self.a[last], self.b[last], self.c[last]),
+                    self.selectors.columns().map(|column| column[last]),
+                    self.selectors.custom.iter().map(|column| column[last]).collect(),
+                )
+            }
+            _ => ((F::zero(), F::zero(), F::zero()), [F::zero(); 6], vec![F::zero(); self.custom_gates.len()]),
+        };
+
+        self.a.resize(size, row.0);
+        self.b.resize(size, row.1);
+        self.c.resize(size, row.2);
+        for (column, value) in self.selectors.columns_mut().into_iter().zip(selectors) {
+            column.truncate(used);
+            column.resize(size, value);
+        }
+        for (column, value) in self.selectors.custom.iter_mut().zip(custom) {
+            column.truncate(used);
+            column.resize(size, value);
+        }
+        self.n = size;
+        Ok(())
+    }
+
+    /// Exposes `value` to the verifier and returns the wire carrying it.
+    /// The input takes the next row, wherever that falls, so circuits can
+    /// interleave inputs and outputs with their gates. Each is an add gate
+    /// whose right and output wires share an index, leaving a + PI = 0
+    /// with PI = −value on that row. A row `public_inputs` already lists
+    /// is not exposed twice: the input is skipped and the circuit records
+    /// `CircuitError::DuplicatePublicRow`.
+    pub fn add_public_input(&mut self, value: F) -> Wire<F> {
+        let index = self.next_wire_index();
+        let zero = Wire { index: index + 1, value: F::zero() };
+        let row = self.gates.len();
+        if self.public_inputs.iter().any(|cell| cell.row == row) {
+            self.fail(CircuitError::DuplicatePublicRow(row));
+            return Wire { index, value };
+        }
+        // Public before the gate is checked, and dropped again if the
+        // gate was skipped
+        self.public_inputs.push(Cell::new(0, row));
+        self.add_gate(Gate {
+            gate_type: GateType::Add,
+            left_wire: Wire { index, value },
+            right_wire: zero.clone(),
+            output_wire: zero,
+        });
+        if self.gates.len() == row {
+            self.public_inputs.pop();
+        }
+        Wire { index, value }
+    }
+
+    /// Values the verifier has to supply, in row order
+    pub fn public_input_values(&self) -> Vec<F> {
+        self.public_inputs.iter().map(|cell| self.a[cell.row]).collect()
+    }
+
+    /// PI over the wire rows: −x on each public row, zero elsewhere
+    fn public_input_evaluations(&self, rows: usize) -> Vec<F> {
+        let mut evals = vec![F::zero(); rows];
+        for cell in &self.public_inputs {
+            evals[cell.row] = -self.a[cell.row];
+        }
+        evals
+    }
+
+    /// PI(X) in coefficient form over the smallest subgroup holding the
+    /// `n` rows, the term the gate identity gains at proving and
+    /// verification time. Zero without public inputs.
+    pub fn public_input_polynomial(&self) -> Result<DensePolynomial<F>, DomainError> {
+        let rows = domain_size(self.n);
+        let omega = F::get_root_of_unity(rows as u64).ok_or(DomainError::NoSubgroup(rows))?;
+        let mut coeffs = self.public_input_evaluations(rows);
+        ifft(&mut coeffs, omega.pow([rows as u64 - 1]))?;
+        Ok(DensePolynomial::from_coefficients_vec(coeffs))
+    }
+
+    /// Checks the selector form of the gate identity on every row,
+    /// padded ones included:
+    ///   q_l·a + q_r·b + q_o·c + q_m·a·b + q_c + PI = 0
+    /// table membership on the rows with q_k set, and the identities of
+    /// each custom gate scaled by its selector
+    pub fn verify_rows(&self) -> bool {
+        let pi = self.public_input_evaluations(self.a.len());
+        (0..self.a.len()).all(|i| self.row_satisfied(i, pi[i]))
+    }
+
+    /// Checks gate `row` on its own, with the PI term if it is public
+    fn gate_satisfied(&self, row: usize) -> bool {
+        let pi = if self.public_inputs.iter().any(|cell| cell.row == row) { -self.a[row] } else { F::zero() };
+        self.row_satisfied(row, pi)
+    }
+
+    fn row_satisfied(&self, row: usize, pi: F) -> bool {
+        let s = &self.selectors;
+        let (a, b, c) = (self.a[row], self.b[row], self.c[row]);
+        let gate = s.q_l[row] * a + s.q_r[row] * b + s.q_o[row] * c + s.q_m[row] * a * b + s.q_c[row] + pi;
+        let custom = self.custom_gates.iter().zip(&s.custom).all(|(gate, q)| {
+            gate.identities.iter().all(|identity| (q[row] * identity.evaluate([a, b, c])).is_zero())
+        });
+        gate.is_zero() && custom && (s.q_k[row].is_zero() || self.table.contains(&[a, b, c]))
+    }
+
+    /// Checks every gate on its own, public input term included, and
+    /// reports the ones that do not hold
+    pub fn verify_constraints(&self) -> ConstraintReport<F> {
+        let failures = (0..self.gates.len())
+            .filter(|row| !self.gate_satisfied(*row))
+            .map(|row| FailedGate {
+                row,
+                gate_type: self.gates[row].gate_type.clone(),
+                left: self.a[row],
+                right: self.b[row],
+                output: self.c[row],
+                namespace: self.namespace_of(row).to_string(),
+            })
+            .collect();
+        ConstraintReport { failures }
+    }
+
+    /// Requires the values in two wire cells to be equal. Column 0 is the
+    /// left wire, 1 the right and 2 the output, and `row` is the gate
+    /// index: the output of gate 3 feeding the left input of gate 7 is
+    /// `connect(Cell::new(2, 3), Cell::new(0, 7))`.
+    pub fn connect(&mut self, a: Cell, b: Cell) {
+        match [a, b].into_iter().find(|cell| cell.column >= 3 || cell.row >= self.gates.len()) {
+            Some(cell) => self.fail(CircuitError::DanglingCell(cell)),
+            None => self.copies.push((a, b)),
+        }
+    }
+
+    /// σ over the a, b, c columns of all `n` rows. Besides the explicit
+    /// `connect` calls, gate wires sharing an index are joined, as they
+    /// denote the same value.
+    pub fn permutation(&self) -> Permutation {
+        self.permutation_over(self.n)
+    }
+
+    /// σ as `permutation` builds it, over `rows` ≥ `n` rows
+    pub(crate) fn permutation_over(&self, rows: usize) -> Permutation {
+        // `add_gate` keeps at most `n` ≤ `rows` gates and `connect` only
+        // keeps cells of gates, so every cell below lies in the grid
+        let mut builder = PermutationBuilder::new(3, rows);
+        let mut first_use = BTreeMap::new();
+        for (row, gate) in self.gates.iter().enumerate() {
+            for (column, wire) in [&gate.left_wire, &gate.right_wire, &gate.output_wire].into_iter().enumerate() {
+                let cell = Cell::new(column, row);
+                let first = *first_use.entry(wire.index).or_insert(cell);
+                builder.connect(first, cell).expect("gate cells lie within the rows");
+            }
+        }
+        for (a, b) in &self.copies {
+            builder.connect(*a, *b).expect("connect only keeps cells of gates");
+        }
+        builder.build()
+    }
+
+    /// σ_a, σ_b, σ_c in coefficient form over the same subgroup as
+    /// `public_input_polynomial`, with the column cosets from
+    /// `coset_multipliers`; the polynomials the prover's grand product is
+    /// taken against
+    pub fn sigma_polynomials(&self) -> Result<[DensePolynomial<F>; 3], DomainError> {
+        let rows = domain_size(self.n);
+        let omega = F::get_root_of_unity(rows as u64).ok_or(DomainError::NoSubgroup(rows))?;
+        let ks = coset_multipliers::<F>(3, rows);
+        // Refused only for another number of multipliers than columns,
+        // three of each here
+        let evals = self.permutation_over(rows).sigma_evaluations(omega, &ks).expect("three multipliers for three columns");
+        let mut sigmas = [(); 3].map(|_| DensePolynomial::from_coefficients_vec(Vec::new()));
+        for (sigma, mut coeffs) in sigmas.iter_mut().zip(evals) {
+            ifft(&mut coeffs, omega.pow([rows as u64 - 1]))?;
+            *sigma = DensePolynomial::from_coefficients_vec(coeffs);
+        }
+        Ok(sigmas)
+    }
+
+    /// Checks that wire values agree on every cycle of `permutation`;
+    /// rows without a gate count as zero
+    pub fn verify_copy_constraints(&self) -> bool {
+        let column = |values: &Vec<F>| {
+            let mut values = values.clone();
+            values.resize(self.n, F::zero());
+            values
+        };
+        self.permutation().is_satisfied_by(&[column(&self.a), column(&self.b), column(&self.c)])
+    }
+
+    /// Overwrites all wire values with zero. Called on drop so witness
+    /// values do not outlive the circuit.
+    pub fn zeroize_witness(&mut self) {
+        for value in self.a.iter_mut().chain(&mut self.b).chain(&mut self.c) {
+            value.zeroize();
+        }
+        for gate in &mut self.gates {
+            gate.left_wire.value.zeroize();
+            gate.right_wire.value.zeroize();
+            gate.output_wire.value.zeroize();
+        }
+        for value in self.wire_values.values_mut() {
+            value.zeroize();
+        }
+    }
+
+    /// Smallest wire index not used by any gate or `alloc` yet
+    pub fn next_wire_index(&self) -> usize {
+        self.wire_values.last_key_value().map_or(0, |(i, _)| i + 1)
+    }
+
+    /// A fresh variable holding `value`. It is constrained only by the
+    /// gates it is later used in.
+    pub fn alloc(&mut self, value: F) -> Variable {
+        let index = self.next_wire_index();
+        self.wire_values.insert(index, value);
+        Variable(index)
+    }
+
+    /// Value currently assigned to `var`, zero for a variable this
+    /// circuit never made. Using such a variable in a gate fails the
+    /// circuit with `CircuitError::UnknownVariable`.
+    pub fn value(&self, var: Variable) -> F {
+        self.wire_values.get(&var.0).copied().unwrap_or_else(F::zero)
+    }
+
+    /// `var` as a wire, for the gadgets taking wires
+    pub fn wire(&self, var: Variable) -> Wire<F> {
+        Wire { index: var.0, value: self.value(var) }
+    }
+
+    /// `var` as a wire for a gate of this circuit, failing it if `var`
+    /// came from elsewhere
+    fn input(&mut self, var: Variable) -> Wire<F> {
+        if !self.wire_values.contains_key(&var.0) {
+            self.fail(CircuitError::UnknownVariable(var.0));
+        }
+        self.wire(var)
+    }
+
+    fn binary(&mut self, gate_type: GateType, a: Variable, b: Variable, value: F) -> Variable {
+        let (left, right) = (self.input(a), self.input(b));
+        let output = Wire { index: self.next_wire_index(), value };
+        Variable::from(&self.push(gate_type, &left, &right, output))
+    }
+
+    /// a + b, in one add gate
+    pub fn add(&mut self, a: Variable, b: Variable) -> Variable {
+        let value = self.value(a) + self.value(b);
+        self.binary(GateType::Add, a, b, value)
+    }
+
+    /// a · b, in one mul gate
+    pub fn mul(&mut self, a: Variable, b: Variable) -> Variable {
+        let value = self.value(a) * self.value(b);
+        self.binary(GateType::Mul, a, b, value)
+    }
+
+    /// A variable fixed to `value` by one gate, q_l = 1 and q_c = −value
+    pub fn constant(&mut self, value: F) -> Variable {
+        let index = self.next_wire_index();
+        let var = Wire { index, value };
+        self.linear(var.clone(), Wire { index: index + 1, value: F::zero() }, [F::one(), F::zero(), -value]);
+        Variable(index)
+    }
+
+    /// Requires a = b, in one gate with q_l = 1 and q_r = −1
+    pub fn assert_equal(&mut self, a: Variable, b: Variable) {
+        let (left, right) = (self.input(a), self.input(b));
+        self.linear(left, right, [F::one(), -F::one(), F::zero()]);
+    }
+
+    /// Requires a = 0, in one gate with q_l = 1
+    pub fn assert_zero(&mut self, a: Variable) {
+        let left = self.input(a);
+        let zero = Wire { index: self.next_wire_index(), value: F::zero() };
+        self.linear(left, zero, [F::one(), F::zero(), F::zero()]);
+    }
+
+    /// x·a + y·b + offset, in one `Linear` gate with q_l = x, q_r = y,
+    /// q_o = −1 and q_c = offset
+    pub fn weighted_sum(&mut self, (x, a): (F, Variable), (y, b): (F, Variable), offset: F) -> Variable {
+        let (left, right) = (self.input(a), self.input(b));
+        let output = Wire { index: self.next_wire_index(), value: x * left.value + y * right.value + offset };
+        let (zero, one) = (F::zero(), F::one());
+        self.add_row(
+            Gate { gate_type: GateType::Linear, left_wire: left, right_wire: right, output_wire: output.clone() },
+            [x, y, -one, zero, offset, zero],
+        );
+        Variable::from(&output)
+    }
 
-            // Check gate constraints
-            match &gate.gate_type {
-                GateType::Add => {
-                    if a + b != c {
-                        return false;
-                    }
+    /// A `Linear` gate q_l·a + q_r·b + q_c = 0 over `left` and `right`,
+    /// with a fresh zero output wire
+    fn linear(&mut self, left: Wire<F>, right: Wire<F>, [q_l, q_r, q_c]: [F; 3]) {
+        let index = self.next_wire_index().max(left.index + 1).max(right.index + 1);
+        let output_wire = Wire { index, value: F::zero() };
+        let zero = F::zero();
+        self.add_row(
+            Gate { gate_type: GateType::Linear, left_wire: left, right_wire: right, output_wire },
+            [q_l, q_r, zero, zero, q_c, zero],
+        );
+    }
+
+    /// Adds the rows of `table` to the circuit's lookup table. All tables
+    /// share one set of columns, so a lookup matches a row of any of
+    /// them; tag a column to keep tables apart.
+    pub fn add_table(&mut self, table: Table<F>) {
+        self.table.extend(table.rows);
+    }
+
+    /// Requires (a, b, c) to be a row of the lookup table, in one gate
+    pub fn lookup(&mut self, a: Variable, b: Variable, c: Variable) {
+        let [left_wire, right_wire, output_wire] = [a, b, c].map(|var| self.input(var));
+        self.add_gate(Gate { gate_type: GateType::Lookup, left_wire, right_wire, output_wire });
+    }
+
+    /// Adds `gate` with a selector column of its own and returns its id
+    /// for `custom_gate`. A gate registered before keeps its id and
+    /// column, so gadgets can register what they use on every call.
+    ///
+    /// `CustomGate`'s fields are public, so a gate built without
+    /// `CustomGate::new` is checked here as well: one of degree above
+    /// `MAX_IDENTITY_DEGREE` is not registered, the circuit records
+    /// `CircuitError::DegreeTooHigh`, and the id returned belongs to no
+    /// gate, so `custom_gate` skips every row that names it.
+    pub fn register_gate(&mut self, gate: CustomGate<F>) -> usize {
+        if let Some(id) = self.custom_gates.iter().position(|known| *known == gate) {
+            return id;
+        }
+        let degree = gate.degree();
+        if degree > MAX_IDENTITY_DEGREE {
+            self.fail(CircuitError::DegreeTooHigh { gate: gate.name, degree });
+            return usize::MAX;
+        }
+        self.custom_gates.push(gate);
+        self.selectors.custom.push(vec![F::zero(); self.n]);
+        self.custom_gates.len() - 1
+    }
+
+    /// Requires the identities of custom gate `id` to hold on (a, b, c),
+    /// in one gate
+    pub fn custom_gate(&mut self, id: usize, a: Variable, b: Variable, c: Variable) {
+        let [left_wire, right_wire, output_wire] = [a, b, c].map(|var| self.input(var));
+        self.add_gate(Gate { gate_type: GateType::Custom(id), left_wire, right_wire, output_wire });
+    }
+
+    fn push(&mut self, gate_type: GateType, left: &Wire<F>, right: &Wire<F>, output: Wire<F>) -> Wire<F> {
+        self.add_gate(Gate {
+            gate_type,
+            left_wire: left.clone(),
+            right_wire: right.clone(),
+            output_wire: output.clone(),
+        });
+        output
+    }
+
+    /// Selects `branches[i]` where `i` is the value of `selector`, using a
+    /// one-hot vector s_0..s_{N-1}. Wires sharing an index are the same
+    /// value, so the gadget is wired to its inputs through their indices;
+    /// `one` must carry the constant 1.
+    ///
+    /// Constraints, 5N - 3 gates in total:
+    /// - s_i * s_i = s_i (each s_i is a bit)
+    /// - suffix sums S_k = s_k + S_{k+1} with S_0 = one (exactly one bit set)
+    /// - (S_1 + ... + S_{N-1}) * one = selector, since that sum is the
+    ///   position of the set bit
+    /// - `output = sum of s_i * branches[i]`
+    ///
+    /// Fewer than two branches add nothing, set `error` and return `one`.
+    pub fn switch(&mut self, one: &Wire<F>, selector: &Wire<F>, branches: &[Wire<F>]) -> Wire<F> {
+        self.namespace("switch", |circuit| circuit.switch_gates(one, selector, branches))
+    }
+
+    fn switch_gates(&mut self, one: &Wire<F>, selector: &Wire<F>, branches: &[Wire<F>]) -> Wire<F> {
+        let n = branches.len();
+        if n < 2 {
+            self.fail(CircuitError::TooFewBranches(n));
+            return one.clone();
+        }
+
+        let mut next = branches
+            .iter()
+            .chain([one, selector])
+            .fold(self.next_wire_index(), |next, w| next.max(w.index + 1));
+        let mut fresh = |value: F| {
+            next += 1;
+            Wire { index: next - 1, value }
+        };
+
+        let bits: Vec<Wire<F>> = (0..n)
+            .map(|i| fresh(if selector.value == F::from(i as u64) { F::one() } else { F::zero() }))
+            .collect();
+        for bit in &bits {
+            self.push(GateType::Mul, bit, bit, bit.clone());
+        }
+
+        // suffix[k] holds S_k; S_0 lands on the `one` wire
+        let mut suffix = vec![bits[n - 1].clone(); n];
+        for k in (0..n - 1).rev() {
+            let value = bits[k].value + suffix[k + 1].value;
+            let output = if k == 0 { Wire { index: one.index, value } } else { fresh(value) };
+            suffix[k] = self.push(GateType::Add, &bits[k], &suffix[k + 1], output);
+        }
+
+        let mut position = suffix[1].clone();
+        for s in &suffix[2..] {
+            let value = position.value + s.value;
+            position = self.push(GateType::Add, &position, s, fresh(value));
+        }
+        let value = position.value * one.value;
+        self.push(GateType::Mul, &position, one, Wire { index: selector.index, value });
+
+        let mut output: Option<Wire<F>> = None;
+        for (bit, branch) in bits.iter().zip(branches) {
+            let product = fresh(bit.value * branch.value);
+            let product = self.push(GateType::Mul, bit, branch, product);
+            output = Some(match output {
+                None => product,
+                Some(acc) => {
+                    let value = acc.value + product.value;
+                    self.push(GateType::Add, &acc, &product, fresh(value))
                 }
-                GateType::Mul => {
-                    if a * b != c {
-                        return false;
-                    }
+            });
+        }
+        // Set by the first branch; fewer than two returned early
+        output.expect("at least two branches")
+    }
+
+    /// Checks that every use of a wire index carries the same value
+    pub fn wires_consistent(&self) -> bool {
+        let mut values = BTreeMap::new();
+        self.gates
+            .iter()
+            .flat_map(|g| [&g.left_wire, &g.right_wire, &g.output_wire])
+            .all(|w| *values.entry(w.index).or_insert(w.value) == w.value)
+    }
+
+    /// Counts per gate type, copy constraints and public inputs, with the
+    /// domain the prover will use and how long it would roughly take:
+    /// `PROOF_MSMS` multi-exponentiations over the domain, and an FFT
+    /// over the quotient coset for each of the `QUOTIENT_POLYNOMIALS` and
+    /// custom selectors
+    pub fn stats(&self) -> CircuitStats {
+        let mut stats = CircuitStats {
+            gates: self.gates.len(),
+            add: 0,
+            mul: 0,
+            linear: 0,
+            lookup: 0,
+            custom: vec![0; self.custom_gates.len()],
+            public_inputs: self.public_inputs.len(),
+            copy_constraints: self.copies.len(),
+            domain_size: domain_size(self.n.max(self.table.len())),
+            estimated_proving_time: Duration::ZERO,
+        };
+        let mut used = BTreeSet::new();
+        for gate in &self.gates {
+            match gate.gate_type {
+                GateType::Add => stats.add += 1,
+                GateType::Mul => stats.mul += 1,
+                GateType::Linear => stats.linear += 1,
+                GateType::Lookup => stats.lookup += 1,
+                GateType::Custom(id) => stats.custom[id] += 1,
+            }
+            for wire in [&gate.left_wire, &gate.right_wire, &gate.output_wire] {
+                if !used.insert(wire.index) {
+                    stats.copy_constraints += 1;
                 }
             }
         }
-        true
+        stats.add = stats.add.saturating_sub(stats.public_inputs);
+
+        let n = stats.domain_size as u64;
+        let coset = quotient_domain_size(stats.domain_size) as u64;
+        let ffts = QUOTIENT_POLYNOMIALS + self.custom_gates.len() as u64;
+        let butterflies = ffts * coset / 2 * coset.ilog2() as u64;
+        stats.estimated_proving_time = Duration::from_nanos(PROOF_MSMS * n * MSM_NANOS_PER_POINT + butterflies * FFT_NANOS_PER_BUTTERFLY);
+        stats
+    }
+
+    /// The gates as a Graphviz digraph, one node per gate with a port per
+    /// wire. A solid edge labelled `w<index>` runs from the first use of
+    /// each wire to every later use, a dashed one joins the two cells of
+    /// each `connect`, and unsatisfied gates are drawn red. Wire values
+    /// are left out, so the graph can be shared without the witness.
+    pub fn to_dot(&self) -> String {
+        let mut out = String::from("digraph circuit {\n    node [shape=record];\n");
+        for (row, gate) in self.gates.iter().enumerate() {
+            let name = if self.public_inputs.iter().any(|cell| cell.row == row) {
+                "public"
+            } else {
+                match gate.gate_type {
+                    GateType::Add => "add",
+                    GateType::Mul => "mul",
+                    GateType::Linear => "linear",
+                    GateType::Lookup => "lookup",
+                    GateType::Custom(id) => &self.custom_gates[id].name,
+                }
+            };
+            let color = if self.gate_satisfied(row) { "" } else { ", color=red" };
+            out += &format!("    g{0} [label=\"{{<a> a|<b> b|<c> c}}|{0}: {1}\"{2}];\n", row, dot_escape(name), color);
+        }
+
+        const PORTS: [&str; 3] = ["a", "b", "c"];
+        let mut first_use = BTreeMap::new();
+        for (row, gate) in self.gates.iter().enumerate() {
+            for (column, wire) in [&gate.left_wire, &gate.right_wire, &gate.output_wire].into_iter().enumerate() {
+                let cell = Cell::new(column, row);
+                let first = *first_use.entry(wire.index).or_insert(cell);
+                if first != cell {
+                    out += &format!("    g{}:{} -> g{}:{} [label=\"w{}\"];\n", first.row, PORTS[first.column], row, PORTS[column], wire.index);
+                }
+            }
+        }
+        for (a, b) in &self.copies {
+            out += &format!("    g{}:{} -> g{}:{} [style=dashed, dir=none];\n", a.row, PORTS[a.column], b.row, PORTS[b.column]);
+        }
+        out += "}\n";
+        out
+    }
+}
+
+/// `text` with the characters a record label gives meaning to escaped
+fn dot_escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '\n' => out.push(' '),
+            '{' | '}' | '|' | '<' | '>' | '"' | '\\' => {
+                out.push('\\');
+                out.push(ch);
+            }
+            _ => out.push(ch),
+        }
+    }
+    out
+}
+
+/// Rows of the evaluation domain for `rows` rows of gates: the next power
+/// of two, and never fewer than two so that the first and last rows the
+/// permutation argument pins down exist
+pub fn domain_size(rows: usize) -> usize {
+    rows.next_power_of_two().max(2)
+}
+
+/// Points of the coset the quotient is computed on: above deg t = 3n + 5,
+/// so 4n from n = 8 and 8n below
+pub(crate) fn quotient_domain_size(n: usize) -> usize {
+    (4 * n).max((3 * n + 6).next_power_of_two())
+}
+
+impl<F: PrimeField> Drop for Circuit<F> {
+    fn drop(&mut self) {
+        self.zeroize_witness();
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use ark_ff::{FftField, One, Zero};
     use ark_std::rand::thread_rng;
+    use ark_std::UniformRand;
+
+    type F = ark_bls12_381::Fr;
+
+    fn f(value: u64) -> F {
+        F::from(value)
+    }
 
     #[test]
     fn test_new_circuit() {
-        let circuit = Circuit::new(2);
+        let circuit = Circuit::<F>::new(2);
         assert_eq!(circuit.n, 2);
         assert_eq!(circuit.gates.len(), 0);
-        assert_eq!(circuit.selectors.q_add.len(), 2);
-        assert_eq!(circuit.selectors.q_mul.len(), 2);
+        assert_eq!(circuit.selectors.q_l.len(), 2);
+        assert_eq!(circuit.selectors.q_m.len(), 2);
     }
 
     #[test]
@@ -167,8 +1053,10 @@ mod tests {
         assert_eq!(circuit.a[0], a);
         assert_eq!(circuit.b[0], b);
         assert_eq!(circuit.c[0], c);
-        assert_eq!(circuit.selectors.q_add[0], F::one());
-        assert_eq!(circuit.selectors.q_mul[0], F::zero());
+        assert_eq!(circuit.selectors.q_l[0], F::one());
+        assert_eq!(circuit.selectors.q_r[0], F::one());
+        assert_eq!(circuit.selectors.q_o[0], -F::one());
+        assert_eq!(circuit.selectors.q_m[0], F::zero());
     }
 
     #[test]
@@ -192,8 +1080,9 @@ mod tests {
         assert_eq!(circuit.a[0], a);
         assert_eq!(circuit.b[0], b);
         assert_eq!(circuit.c[0], c);
-        assert_eq!(circuit.selectors.q_add[0], F::zero());
-        assert_eq!(circuit.selectors.q_mul[0], F::one());
+        assert_eq!(circuit.selectors.q_l[0], F::zero());
+        assert_eq!(circuit.selectors.q_m[0], F::one());
+        assert_eq!(circuit.selectors.q_o[0], -F::one());
     }
 
     #[test]
@@ -224,7 +1113,7 @@ mod tests {
         circuit.add_gate(add_gate);
         circuit.add_gate(mul_gate);
         
-        assert!(circuit.verify_constraints());
+        assert!(circuit.verify_constraints().is_satisfied());
     }
 
     #[test]
@@ -244,6 +1133,449 @@ mod tests {
 
         circuit.add_gate(invalid_gate);
         
-        assert!(!circuit.verify_constraints());
+        let report = circuit.verify_constraints();
+        assert_eq!(report.failures.len(), 1);
+        let failure = &report.failures[0];
+        assert_eq!((failure.row, &failure.gate_type, failure.namespace.as_str()), (0, &GateType::Add, ""));
+        assert_eq!((failure.left, failure.right, failure.output), (a, b, a * b));
+    }
+
+    #[test]
+    fn test_constraint_report_namespaces() {
+        let mut circuit = Circuit::new(8);
+        let x = circuit.alloc(f(3));
+        let y = circuit.namespace("outer", |circuit| {
+            let square = circuit.mul(x, x);
+            let y = circuit.namespace("inner", |circuit| circuit.constant(f(8)));
+            circuit.assert_equal(square, y);
+            y
+        });
+        circuit.namespace("empty", |_| ());
+        let nine = circuit.constant(f(9));
+        circuit.assert_equal(y, nine);
+
+        let report = circuit.verify_constraints();
+        let failed: Vec<_> = report.failures.iter().map(|gate| (gate.row, gate.gate_type.clone(), gate.namespace.as_str())).collect();
+        assert_eq!(failed, vec![(2, GateType::Linear, "outer"), (4, GateType::Linear, "")]);
+        assert_eq!((report.failures[0].left, report.failures[0].right), (f(9), f(8)));
+        assert_eq!(circuit.first_unsatisfied().map(|gate| gate.namespace.as_str()), Some("outer"));
+        assert!(report.to_string().starts_with("2 gates are not satisfied\n  gate 2 (Linear) in outer: a = 9, b = 8, c = 0"));
+    }
+    #[test]
+    fn test_switch() {
+        let one = Wire { index: 0, value: F::one() };
+        let branches: Vec<Wire<F>> = (0..4).map(|i| Wire { index: 1 + i, value: f(10 * i as u64) }).collect();
+
+        for i in 0..4 {
+            let selector = Wire { index: 5, value: f(i) };
+            let mut circuit = Circuit::new(17);
+            let output = circuit.switch(&one, &selector, &branches);
+
+            assert_eq!(output.value, f(10 * i));
+            assert_eq!(circuit.gates.len(), 17);
+            assert!(circuit.verify_constraints().is_satisfied());
+            assert!(circuit.wires_consistent());
+        }
+
+        // No branch matches: the one-hot sum cannot reach `one`
+        let selector = Wire { index: 5, value: f(7) };
+        let mut circuit = Circuit::new(17);
+        circuit.switch(&one, &selector, &branches);
+        assert!(circuit.verify_constraints().is_satisfied());
+        assert!(!circuit.wires_consistent());
+    }
+
+    #[test]
+    fn test_build_errors() {
+        let wire = |index| Wire { index, value: F::one() };
+        let gate = |gate_type| Gate { gate_type, left_wire: wire(0), right_wire: wire(1), output_wire: wire(2) };
+
+        // Only the first error is kept, and the offending gates are skipped
+        let mut circuit = Circuit::<F>::new(1);
+        circuit.add_gate(gate(GateType::Linear));
+        circuit.add_gate(gate(GateType::Add));
+        circuit.add_gate(gate(GateType::Add));
+        assert_eq!(circuit.error(), Some(&CircuitError::LinearGate));
+        assert_eq!(circuit.gates.len(), 1);
+
+        let mut circuit = Circuit::<F>::new(1);
+        circuit.add_gate(gate(GateType::Add));
+        circuit.add_gate(gate(GateType::Add));
+        assert_eq!(circuit.error(), Some(&CircuitError::Full { capacity: 1 }));
+
+        let mut circuit = Circuit::<F>::new(2);
+        circuit.connect(Cell::new(0, 0), Cell::new(3, 0));
+        assert_eq!(circuit.error(), Some(&CircuitError::DanglingCell(Cell::new(0, 0))));
+
+        let mut circuit = Circuit::<F>::new(4);
+        circuit.switch(&wire(0), &wire(1), &[wire(2)]);
+        assert_eq!(circuit.error(), Some(&CircuitError::TooFewBranches(1)));
+
+        let mut circuit = Circuit::<F>::new(4);
+        let x = circuit.alloc(F::one());
+        circuit.custom_gate(3, x, x, x);
+        assert_eq!(circuit.error(), Some(&CircuitError::UnknownGate(3)));
+
+        // A gate literal skips `CustomGate::new`, not the degree check
+        let a = crate::custom_gate::Expression::<F>::a();
+        let quartic = CustomGate { name: "quartic".to_string(), identities: vec![a.clone() * a.clone() * a.clone() * a] };
+        let mut circuit = Circuit::<F>::new(4);
+        let id = circuit.register_gate(quartic);
+        let x = circuit.alloc(F::one());
+        circuit.custom_gate(id, x, x, x);
+        assert_eq!(circuit.error(), Some(&CircuitError::DegreeTooHigh { gate: "quartic".to_string(), degree: 4 }));
+        assert!(circuit.custom_gates.is_empty() && circuit.gates.is_empty());
+
+        let mut circuit = Circuit::<F>::new(4);
+        circuit.add_gate(gate(GateType::Add));
+        circuit.add_gate(gate(GateType::Add));
+        assert_eq!(circuit.pad_to(1, PaddingStrategy::Zero), Err(CircuitError::PadTooSmall { gates: 2, rows: 1 }));
+        circuit.pad_to(4, PaddingStrategy::Zero).unwrap();
+        circuit.add_gate(gate(GateType::Add));
+        assert_eq!(circuit.error(), Some(&CircuitError::AlreadyPadded));
+        assert_eq!(circuit.error().unwrap().to_string(), "gate added after padding");
+    }
+
+    #[test]
+    fn test_first_unsatisfied() {
+        let circuit = two_gate_circuit(true);
+        assert_eq!(circuit.first_unsatisfied(), None);
+
+        // The switch is attributed to its caller's namespace
+        let one = Wire { index: 10, value: F::one() };
+        let branches = [Wire { index: 11, value: f(1) }, Wire { index: 12, value: f(2) }];
+        let mut circuit = Circuit::new(16);
+        circuit.namespace("lookup", |circuit| {
+            circuit.switch(&one, &Wire { index: 13, value: f(7) }, &branches)
+        });
+        let failure = circuit.first_unsatisfied().unwrap();
+        assert_eq!(failure.namespace, "lookup/switch");
+        assert!(matches!(failure.violation, Violation::WireValue { .. }));
+        assert!(failure.row < circuit.gates.len());
+
+        let circuit = two_gate_circuit(false);
+        let failure = circuit.first_unsatisfied().unwrap();
+        assert_eq!((failure.row, &failure.violation), (1, &Violation::Gate));
+        assert_eq!(failure.to_string(), "gate 1 in <root> is not satisfied");
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_zeroize_witness() {
+        let mut circuit = Circuit::new(1);
+        circuit.add_gate(Gate {
+            gate_type: GateType::Mul,
+            left_wire: Wire { index: 0, value: f(3) },
+            right_wire: Wire { index: 1, value: f(5) },
+            output_wire: Wire { index: 2, value: f(15) },
+        });
+
+        circuit.zeroize_witness();
+        assert_eq!(circuit.a, vec![F::zero()]);
+        assert_eq!(circuit.gates[0].output_wire.value, F::zero());
+        assert!(circuit.verify_constraints().is_satisfied());
+    }
+
+    #[test]
+    fn test_degenerate_domains() {
+        use ark_ff::Field;
+        use ark_poly::Polynomial;
+
+        assert_eq!([0, 1, 2, 3, 5].map(domain_size), [2, 2, 2, 4, 8]);
+
+        let empty = Circuit::<F>::new(0);
+        assert_eq!(empty.padded_size(), 2);
+        assert!(empty.public_input_polynomial().unwrap().is_zero());
+        assert!(empty.verify_rows() && empty.verify_copy_constraints());
+
+        // Three rows are keyed over a subgroup of order four
+        let circuit = two_gate_circuit(true);
+        let omega = F::get_root_of_unity(4).unwrap();
+        assert!(circuit.public_input_polynomial().unwrap().is_zero());
+        let sigmas = circuit.sigma_polynomials().unwrap();
+        let ks = coset_multipliers::<F>(3, 4);
+        let sigma = circuit.permutation_over(4).sigma_evaluations(omega, &ks).unwrap();
+        for (poly, evals) in sigmas.iter().zip(&sigma) {
+            assert_eq!(&(0..4).map(|i| poly.evaluate(&omega.pow([i]))).collect::<Vec<_>>(), evals);
+        }
+    }
+
+    fn two_gate_circuit(valid: bool) -> Circuit<F> {
+        let mut circuit = Circuit::new(3);
+        circuit.add_gate(Gate {
+            gate_type: GateType::Add,
+            left_wire: Wire { index: 0, value: f(2) },
+            right_wire: Wire { index: 1, value: f(3) },
+            output_wire: Wire { index: 2, value: f(5) },
+        });
+        circuit.add_gate(Gate {
+            gate_type: GateType::Mul,
+            left_wire: Wire { index: 2, value: f(5) },
+            right_wire: Wire { index: 1, value: f(3) },
+            output_wire: Wire { index: 3, value: f(if valid { 15 } else { 16 }) },
+        });
+        circuit
+    }
+
+    #[test]
+    fn test_padding_keeps_verdict() {
+        for valid in [true, false] {
+            for strategy in [PaddingStrategy::Zero, PaddingStrategy::RepeatLast] {
+                let mut circuit = two_gate_circuit(valid);
+                assert_eq!(circuit.verify_rows(), valid);
+
+                let size = circuit.padded_size() * 4;
+                circuit.pad_to(size, strategy).unwrap();
+                assert_eq!(circuit.n, 8);
+                assert_eq!(circuit.a.len(), 8);
+                assert_eq!(circuit.selectors.q_m.len(), 8);
+                assert_eq!(circuit.verify_rows(), valid);
+                assert_eq!(circuit.verify_constraints().is_satisfied(), valid);
+                assert!(crate::lint::lint(&circuit).is_empty());
+            }
+        }
+
+        let mut circuit = two_gate_circuit(true);
+        circuit.pad_to(4, PaddingStrategy::RepeatLast).unwrap();
+        assert_eq!(circuit.c[3], f(15));
+        assert_eq!(circuit.selectors.q_m[3], F::one());
+    }
+
+    #[test]
+    fn test_copy_constraints() {
+        use ark_bls12_381::Fr;
+        use ark_poly::Polynomial;
+
+        // Shared wire indices already tie gate 0's output to gate 1's left input
+        let mut circuit = two_gate_circuit(true);
+        let sigma = circuit.permutation();
+        assert_eq!(sigma.image(Cell::new(2, 0)), Cell::new(0, 1));
+        assert_eq!(sigma.image(Cell::new(0, 0)), Cell::new(0, 0));
+        assert!(circuit.verify_copy_constraints());
+
+        // Explicitly connecting 2 to 15 breaks the copy constraints only
+        circuit.connect(Cell::new(0, 0), Cell::new(2, 1));
+        assert!(circuit.verify_constraints().is_satisfied());
+        assert!(!circuit.verify_copy_constraints());
+        assert_eq!(circuit.permutation().image(Cell::new(0, 0)), Cell::new(2, 1));
+
+        circuit.pad_to(4, PaddingStrategy::Zero).unwrap();
+        let omega = <Fr as FftField>::get_root_of_unity(4).unwrap();
+        let ks = coset_multipliers::<Fr>(3, 4);
+        let evals = circuit.permutation().sigma_evaluations(omega, &ks).unwrap();
+        let sigmas = circuit.sigma_polynomials().unwrap();
+        for (sigma, column) in sigmas.iter().zip(&evals) {
+            let mut x = Fr::from(1u64);
+            for eval in column {
+                assert_eq!(sigma.evaluate(&x), *eval);
+                x *= omega;
+            }
+        }
+    }
+
+    #[test]
+    fn test_public_inputs() {
+        use ark_bls12_381::Fr;
+        use ark_poly::Polynomial;
+
+        let build = |statement: u64, product: u64| {
+            let mut circuit = Circuit::new(4);
+            let exposed = circuit.add_public_input(f(statement));
+            circuit.add_gate(Gate {
+                gate_type: GateType::Mul,
+                left_wire: Wire { index: 2, value: f(5) },
+                right_wire: Wire { index: 3, value: f(3) },
+                output_wire: Wire { index: exposed.index, value: f(product) },
+            });
+            circuit
+        };
+
+        let mut circuit = build(15, 15);
+        assert_eq!(circuit.public_inputs, vec![Cell::new(0, 0)]);
+        assert_eq!(circuit.public_input_values(), vec![f(15)]);
+        assert!(circuit.verify_constraints().is_satisfied() && circuit.verify_rows());
+        assert!(circuit.verify_copy_constraints());
+
+        // The row balances for any value, the copy to the product does not
+        let forged = build(16, 15);
+        assert!(forged.verify_constraints().is_satisfied());
+        assert!(!forged.verify_copy_constraints());
+
+        for strategy in [PaddingStrategy::Zero, PaddingStrategy::RepeatLast] {
+            let mut padded = build(15, 15);
+            padded.pad_to(4, strategy).unwrap();
+            assert!(padded.verify_rows());
+            assert!(crate::lint::lint(&padded).is_empty());
+        }
+
+        let pi = circuit.public_input_polynomial().unwrap();
+        let omega = <Fr as FftField>::get_root_of_unity(4).unwrap();
+        assert_eq!(pi.evaluate(&Fr::from(1u64)), -Fr::from(15u64));
+        assert_eq!(pi.evaluate(&omega), Fr::from(0u64));
+
+        circuit.zeroize_witness();
+        assert_eq!(circuit.public_input_values(), vec![F::zero()]);
+
+        // A row listed by hand is not exposed a second time
+        let mut twice = Circuit::<F>::new(4);
+        twice.public_inputs.push(Cell::new(0, 0));
+        twice.add_public_input(f(15));
+        assert_eq!(twice.public_inputs, vec![Cell::new(0, 0)]);
+        assert_eq!(twice.error(), Some(&CircuitError::DuplicatePublicRow(0)));
+    }
+
+    #[test]
+    fn test_variables() {
+        // x³ + x + 5 = 35 with x = 3, 5 and 35 public
+        let mut circuit = Circuit::new(6);
+        let five = Variable::from(&circuit.add_public_input(f(5)));
+        let out = Variable::from(&circuit.add_public_input(f(35)));
+        let x = circuit.alloc(f(3));
+        let x2 = circuit.mul(x, x);
+        let x3 = circuit.mul(x2, x);
+        let sum = circuit.add(x3, x);
+        let result = circuit.add(sum, five);
+        assert_eq!(circuit.value(result), f(35));
+        assert_ne!(result, out);
+
+        // Route the result onto the public output
+        circuit.connect(Cell::new(2, circuit.gates.len() - 1), Cell::new(0, 1));
+        assert_eq!(circuit.gates.len(), 6);
+        assert_eq!(circuit.first_unsatisfied(), None);
+        assert!(circuit.verify_constraints().is_satisfied() && circuit.verify_copy_constraints());
+        assert_eq!(circuit.wire(x).index, circuit.gates[2].left_wire.index);
+
+        let mut circuit = Circuit::new(2);
+        let x = circuit.alloc(f(3));
+        let y = circuit.alloc(f(4));
+        assert_ne!(x, y);
+        let product = circuit.mul(x, y);
+        assert_eq!(circuit.value(product), f(12));
+        assert_eq!(circuit.next_wire_index(), 3);
+    }
+
+    #[test]
+    fn test_constants_and_assertions() {
+        let mut circuit = Circuit::new(8);
+        let x = circuit.alloc(f(3));
+        let square = circuit.mul(x, x);
+        let nine = circuit.constant(f(9));
+        circuit.assert_equal(square, nine);
+        let zero = circuit.constant(f(0));
+        circuit.assert_zero(zero);
+        assert_eq!(circuit.value(nine), f(9));
+        assert_eq!(circuit.first_unsatisfied(), None);
+        assert!(circuit.verify_constraints().is_satisfied() && circuit.verify_rows());
+        assert!(crate::lint::lint(&circuit).is_empty());
+
+        let ten = circuit.constant(f(10));
+        circuit.assert_equal(square, ten);
+        let unsatisfied = circuit.first_unsatisfied().unwrap();
+        assert_eq!((unsatisfied.row, &unsatisfied.violation), (6, &Violation::Gate));
+        assert!(!circuit.verify_constraints().is_satisfied());
+    }
+
+    #[test]
+    fn test_weighted_sum() {
+        let mut circuit = Circuit::new(4);
+        let (x, y) = (circuit.alloc(f(3)), circuit.alloc(f(5)));
+        let sum = circuit.weighted_sum((f(2), x), (-f(1), y), f(7));
+        assert_eq!(circuit.value(sum), f(8));
+        let doubled = circuit.weighted_sum((f(2), sum), (f(0), sum), f(0));
+        assert_eq!(circuit.value(doubled), f(16));
+        assert_eq!(circuit.first_unsatisfied(), None);
+        assert!(circuit.verify_constraints().is_satisfied() && circuit.verify_rows());
+        assert!(crate::lint::lint(&circuit).is_empty());
+
+        circuit.c[1] += f(1);
+        assert!(!circuit.verify_constraints().is_satisfied());
+    }
+
+    #[test]
+    fn test_lookup_gates() {
+        let mut circuit = Circuit::new(4);
+        circuit.add_table(Table::from_column((0..4u64).map(f)));
+        let zero = circuit.constant(f(0));
+        let small = circuit.alloc(f(3));
+        circuit.lookup(small, zero, zero);
+        assert_eq!(circuit.first_unsatisfied(), None);
+        assert_eq!(circuit.selectors.q_k[1], f(1));
+        assert!(circuit.verify_constraints().is_satisfied() && circuit.verify_rows());
+        assert!(crate::lint::lint(&circuit).is_empty());
+
+        let large = circuit.alloc(f(4));
+        circuit.lookup(large, zero, zero);
+        assert_eq!(circuit.first_unsatisfied().map(|gate| gate.row), Some(2));
+        assert!(!circuit.verify_rows());
+    }
+
+    #[test]
+    fn test_stats() {
+        let mut circuit = Circuit::new(8);
+        let x = Variable::from(&circuit.add_public_input(f(3)));
+        let square = circuit.mul(x, x);
+        let nine = circuit.constant(f(9));
+        circuit.assert_equal(square, nine);
+        let sum = circuit.add(square, x);
+        circuit.connect(Cell::new(2, 4), Cell::new(0, 0));
+        let id = circuit.register_gate(CustomGate::quintic_sbox());
+        circuit.register_gate(CustomGate::new("unused", vec![]).unwrap());
+        circuit.custom_gate(id, sum, sum, sum);
+
+        let stats = circuit.stats();
+        assert_eq!((stats.gates, stats.add, stats.mul, stats.linear, stats.lookup), (6, 1, 1, 2, 0));
+        assert_eq!((stats.custom, stats.public_inputs, stats.domain_size), (vec![1, 0], 1, 8));
+        // x twice more, the public input's zero wire, square twice, nine,
+        // sum three times, and the connect
+        assert_eq!(stats.copy_constraints, 11);
+
+        let mut larger = Circuit::<F>::new(1 << 10);
+        larger.add_table(Table::from_column((0..4u64).map(f)));
+        assert_eq!(larger.stats().domain_size, 1 << 10);
+        assert!(larger.stats().estimated_proving_time > stats.estimated_proving_time);
+    }
+
+    #[test]
+    fn test_foreign_variable() {
+        let mut other = Circuit::<F>::new(4);
+        other.alloc(f(1));
+        let y = other.alloc(f(2));
+        let mut circuit = Circuit::<F>::new(4);
+        let z = circuit.alloc(f(3));
+        assert_eq!(circuit.value(y), F::zero());
+        circuit.add(z, y);
+        assert_eq!(circuit.error(), Some(&CircuitError::UnknownVariable(1)));
+    }
+
+    #[test]
+    fn test_public_input_on_full_circuit() {
+        let mut circuit = Circuit::new(1);
+        circuit.add_public_input(f(3));
+        circuit.add_public_input(f(4));
+        assert_eq!(circuit.error(), Some(&CircuitError::Full { capacity: 1 }));
+        assert_eq!(circuit.public_input_values(), vec![f(3)]);
+        let stats = circuit.stats();
+        assert_eq!((stats.gates, stats.add, stats.public_inputs), (1, 0, 1));
+    }
+
+    #[test]
+    fn test_to_dot() {
+        let mut circuit = two_gate_circuit(false);
+        circuit.connect(Cell::new(0, 0), Cell::new(2, 1));
+        let dot = circuit.to_dot();
+        assert!(dot.starts_with("digraph circuit {\n") && dot.ends_with("}\n"));
+        assert!(dot.contains("g0 [label=\"{<a> a|<b> b|<c> c}|0: add\"];"));
+        assert!(dot.contains("g1 [label=\"{<a> a|<b> b|<c> c}|1: mul\", color=red];"));
+        assert!(dot.contains("g0:c -> g1:a [label=\"w2\"];"));
+        assert!(dot.contains("g0:b -> g1:b [label=\"w1\"];"));
+        assert!(dot.contains("g0:a -> g1:c [style=dashed, dir=none];"));
+        assert_eq!(dot.matches(" -> ").count(), 3);
+
+        let mut circuit = Circuit::<F>::new(2);
+        circuit.add_public_input(f(7));
+        let id = circuit.register_gate(CustomGate::new("x|y", vec![]).unwrap());
+        let x = circuit.alloc(f(1));
+        circuit.custom_gate(id, x, x, x);
+        let dot = circuit.to_dot();
+        assert!(dot.contains("0: public\"]") && dot.contains("1: x\\|y\"]"));
+    }
+}
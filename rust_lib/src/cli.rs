@@ -0,0 +1,361 @@
+//! The `plonk-cli` binary: setup, keys, proofs and verification over
+//! BN254 from files, without writing Rust.
+//!
+//! A circuit file is a JSON list of gates over named variables, and the
+//! variables to expose, in statement order:
+//!
+//! ```json
+//! {
+//!   "public": ["out"],
+//!   "gates": [
+//!     { "op": "mul", "a": "x", "b": "x", "out": "square" },
+//!     { "op": "add", "a": "square", "b": "x", "out": "out" },
+//!     { "op": "constant", "value": "3", "out": "three" },
+//!     { "op": "assert_equal", "a": "x", "b": "three" }
+//!   ]
+//! }
+//! ```
+//!
+//! A variable no gate outputs is an input, valued by the witness file: a
+//! JSON object from input names to decimal strings. Statements are JSON
+//! arrays of decimal strings. The SRS, the keys and the proof are written
+//! as `Artifact`s.
+
+use ark_std::Zero;
+use serde::Deserialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::artifact::Artifact;
+use crate::bn254::{Fr, Params, Proof, ProvingKey, VerifyingKey};
+use crate::circuit::{Circuit, Variable};
+use crate::constraint_system::{ConstraintSystem, Synthesize, Witness};
+use crate::error::{ArtifactError, CircuitError, Error, KeyError, ProveError, VerifyError};
+use crate::prover::Prover;
+use crate::verifier::Verifier;
+
+pub const USAGE: &str = "usage:
+  plonk-cli setup --degree N [--out srs.bin]
+  plonk-cli compile --circuit c.json [--srs srs.bin] [--pk pk.bin] [--vk vk.bin]
+  plonk-cli prove --circuit c.json --witness w.json [--pk pk.bin] [--proof proof.bin] [--public public.json]
+  plonk-cli verify [--proof proof.bin] [--vk vk.bin] [--public public.json]
+
+setup draws the SRS trapdoor locally and is for testing only; production
+keys need an SRS from a ceremony.";
+
+#[derive(Debug)]
+pub enum CliError {
+    /// Bad arguments; the message says which
+    Usage(String),
+    Io(std::io::Error),
+    /// A circuit, witness or statement file that is not valid JSON of its
+    /// format
+    Json(serde_json::Error),
+    /// A circuit or witness that parses but does not make sense
+    Invalid(String),
+    Plonk(Error),
+}
+
+impl core::fmt::Display for CliError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            CliError::Usage(message) => write!(f, "{}\n\n{}", message, USAGE),
+            CliError::Io(e) => write!(f, "{}", e),
+            CliError::Json(e) => write!(f, "{}", e),
+            CliError::Invalid(message) => write!(f, "{}", message),
+            CliError::Plonk(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl From<std::io::Error> for CliError {
+    fn from(e: std::io::Error) -> Self {
+        CliError::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for CliError {
+    fn from(e: serde_json::Error) -> Self {
+        CliError::Json(e)
+    }
+}
+
+macro_rules! from_plonk_error {
+    ($($error:ty),*) => {
+        $(
+            impl From<$error> for CliError {
+                fn from(e: $error) -> Self {
+                    CliError::Plonk(e.into())
+                }
+            }
+        )*
+    };
+}
+
+from_plonk_error!(ArtifactError, CircuitError, KeyError, ProveError, VerifyError);
+
+/// A circuit file
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CircuitDescription {
+    /// Variables exposed to the verifier, in statement order
+    #[serde(default)]
+    pub public: Vec<String>,
+    pub gates: Vec<GateDescription>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum GateDescription {
+    Add { a: String, b: String, out: String },
+    Mul { a: String, b: String, out: String },
+    Constant { value: String, out: String },
+    AssertEqual { a: String, b: String },
+}
+
+impl CircuitDescription {
+    /// Variables no gate outputs, in order of first use. Fails on a gate
+    /// output named like a variable already in use.
+    pub fn inputs(&self) -> Result<Vec<&str>, CliError> {
+        let mut seen = BTreeSet::new();
+        let mut inputs = Vec::new();
+        for gate in &self.gates {
+            let (operands, out) = match gate {
+                GateDescription::Add { a, b, out } | GateDescription::Mul { a, b, out } => (vec![a, b], Some(out)),
+                GateDescription::Constant { value, out } => {
+                    parse(value)?;
+                    (vec![], Some(out))
+                }
+                GateDescription::AssertEqual { a, b } => (vec![a, b], None),
+            };
+            inputs.extend(operands.into_iter().filter(|name| seen.insert(name.as_str())).map(String::as_str));
+            if let Some(out) = out {
+                if !seen.insert(out) {
+                    return Err(CliError::Invalid(format!("variable {:?} is assigned twice", out)));
+                }
+            }
+        }
+        inputs.extend(self.public.iter().filter(|name| seen.insert(name.as_str())).map(String::as_str));
+        Ok(inputs)
+    }
+
+    /// Structure and values of the circuit with `witness` giving every
+    /// input; `None` sets them all to zero, which is enough for keying
+    pub fn synthesize(&self, witness: Option<&BTreeMap<String, String>>) -> Result<(ConstraintSystem<Fr>, Witness<Fr>), CliError> {
+        let inputs = self.inputs()?;
+        let values = match witness {
+            None => inputs.iter().map(|name| (name.to_string(), Fr::zero())).collect(),
+            Some(witness) => {
+                if let Some(name) = witness.keys().find(|name| !inputs.contains(&name.as_str())) {
+                    return Err(CliError::Invalid(format!("{:?} is not an input of the circuit", name)));
+                }
+                let value = |name: &str| witness.get(name).ok_or_else(|| CliError::Invalid(format!("no value for input {:?}", name)));
+                inputs.iter().map(|name| Ok((name.to_string(), parse(value(name)?)?))).collect::<Result<_, CliError>>()?
+            }
+        };
+        // Each public input takes a row and a gate tying it to its variable
+        let capacity = self.gates.len() + 2 * self.public.len();
+        Ok(ConstraintSystem::synthesize(&Instance { description: self, values }, capacity)?)
+    }
+}
+
+fn parse(value: &str) -> Result<Fr, CliError> {
+    Fr::from_str(value).map_err(|_| CliError::Invalid(format!("{:?} is not a field element", value)))
+}
+
+/// A description with every input valued
+struct Instance<'a> {
+    description: &'a CircuitDescription,
+    values: BTreeMap<String, Fr>,
+}
+
+impl Instance<'_> {
+    fn variable(&self, circuit: &mut Circuit<Fr>, variables: &mut BTreeMap<String, Variable>, name: &str) -> Variable {
+        *variables.entry(name.to_string()).or_insert_with(|| circuit.alloc(self.values[name]))
+    }
+}
+
+impl Synthesize<Fr> for Instance<'_> {
+    fn synthesize(&self, circuit: &mut Circuit<Fr>) {
+        let mut variables = BTreeMap::new();
+        for gate in &self.description.gates {
+            match gate {
+                GateDescription::Add { a, b, out } | GateDescription::Mul { a, b, out } => {
+                    let a = self.variable(circuit, &mut variables, a);
+                    let b = self.variable(circuit, &mut variables, b);
+                    let var = match gate {
+                        GateDescription::Add { .. } => circuit.add(a, b),
+                        _ => circuit.mul(a, b),
+                    };
+                    variables.insert(out.clone(), var);
+                }
+                GateDescription::Constant { value, out } => {
+                    let var = circuit.constant(parse(value).expect("checked by inputs"));
+                    variables.insert(out.clone(), var);
+                }
+                GateDescription::AssertEqual { a, b } => {
+                    let a = self.variable(circuit, &mut variables, a);
+                    let b = self.variable(circuit, &mut variables, b);
+                    circuit.assert_equal(a, b);
+                }
+            }
+        }
+        for name in &self.description.public {
+            let var = self.variable(circuit, &mut variables, name);
+            let value = circuit.value(var);
+            let public = Variable::from(&circuit.add_public_input(value));
+            circuit.assert_equal(var, public);
+        }
+    }
+}
+
+/// Runs the command in `args`, the arguments after the program name, and
+/// returns what to print
+pub fn run(args: &[String]) -> Result<String, CliError> {
+    let Some((command, flags)) = args.split_first() else {
+        return Ok(USAGE.to_string());
+    };
+    let allowed: &[&str] = match command.as_str() {
+        "setup" => &["degree", "out"],
+        "compile" => &["circuit", "srs", "pk", "vk"],
+        "prove" => &["circuit", "witness", "pk", "proof", "public"],
+        "verify" => &["proof", "vk", "public"],
+        "help" | "--help" | "-h" => return Ok(USAGE.to_string()),
+        _ => return Err(CliError::Usage(format!("unknown command {:?}", command))),
+    };
+    let flags = Flags::parse(flags, allowed)?;
+    match command.as_str() {
+        "setup" => {
+            let degree: usize = flags.required("degree")?.parse().map_err(|_| CliError::Usage("--degree takes a number".to_string()))?;
+            let out = flags.get("out", "srs.bin");
+            Params::setup(degree, &mut ark_std::rand::thread_rng()).save(out)?;
+            Ok(format!("wrote an SRS of degree {} to {}", degree, out))
+        }
+        "compile" => {
+            let description = read_circuit(flags.required("circuit")?)?;
+            let (cs, witness) = description.synthesize(None)?;
+            let params = Params::load(flags.get("srs", "srs.bin"))?;
+            let pk = ProvingKey::preprocess(params, &cs.assign(&witness)?)?;
+            pk.save(flags.get("pk", "pk.bin"))?;
+            pk.vk.save(flags.get("vk", "vk.bin"))?;
+            Ok(format!("compiled {} rows and {} public inputs; needs an SRS of degree {}", cs.rows, cs.public_rows.len(), ProvingKey::srs_degree_for(cs.rows)))
+        }
+        "prove" => {
+            let description = read_circuit(flags.required("circuit")?)?;
+            let witness: BTreeMap<String, String> = serde_json::from_slice(&std::fs::read(flags.required("witness")?)?)?;
+            let (cs, witness) = description.synthesize(Some(&witness))?;
+            let circuit = cs.assign(&witness)?;
+            if !circuit.is_satisfied() {
+                return Err(CliError::Invalid("the witness does not satisfy the circuit".to_string()));
+            }
+            let pk = ProvingKey::load(flags.get("pk", "pk.bin"))?;
+            let proof: Proof = Prover::prove(&circuit, &pk, &mut ark_std::rand::thread_rng())?;
+            proof.save(flags.get("proof", "proof.bin"))?;
+            let statement: Vec<String> = circuit.public_inputs.iter().map(|x| x.to_string()).collect();
+            std::fs::write(flags.get("public", "public.json"), serde_json::to_vec(&statement)?)?;
+            Ok(format!("wrote {} and {}", flags.get("proof", "proof.bin"), flags.get("public", "public.json")))
+        }
+        _ => {
+            let proof = Proof::load(flags.get("proof", "proof.bin"))?;
+            let vk = VerifyingKey::load(flags.get("vk", "vk.bin"))?;
+            let statement: Vec<String> = serde_json::from_slice(&std::fs::read(flags.get("public", "public.json"))?)?;
+            let statement = statement.iter().map(|x| parse(x)).collect::<Result<Vec<_>, _>>()?;
+            Verifier::verify(&vk, &statement, &proof)?;
+            Ok("proof is valid".to_string())
+        }
+    }
+}
+
+fn read_circuit(path: impl AsRef<Path>) -> Result<CircuitDescription, CliError> {
+    Ok(serde_json::from_slice(&std::fs::read(path)?)?)
+}
+
+/// `--name value` pairs
+struct Flags<'a>(BTreeMap<&'a str, &'a str>);
+
+impl<'a> Flags<'a> {
+    fn parse(args: &'a [String], allowed: &[&str]) -> Result<Self, CliError> {
+        let mut flags = BTreeMap::new();
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            let name = arg
+                .strip_prefix("--")
+                .filter(|name| allowed.contains(name))
+                .ok_or_else(|| CliError::Usage(format!("unexpected argument {:?}", arg)))?;
+            let value = args.next().ok_or_else(|| CliError::Usage(format!("--{} needs a value", name)))?;
+            if flags.insert(name, value.as_str()).is_some() {
+                return Err(CliError::Usage(format!("--{} given twice", name)));
+            }
+        }
+        Ok(Self(flags))
+    }
+
+    fn get(&self, name: &str, default: &'a str) -> &'a str {
+        self.0.get(name).copied().unwrap_or(default)
+    }
+
+    fn required(&self, name: &str) -> Result<&'a str, CliError> {
+        self.0.get(name).copied().ok_or_else(|| CliError::Usage(format!("--{} is required", name)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CIRCUIT: &str = r#"{
+        "public": ["out"],
+        "gates": [
+            { "op": "mul", "a": "x", "b": "x", "out": "square" },
+            { "op": "add", "a": "square", "b": "y", "out": "out" },
+            { "op": "constant", "value": "3", "out": "three" },
+            { "op": "assert_equal", "a": "x", "b": "three" }
+        ]
+    }"#;
+
+    #[test]
+    fn test_setup_compile_prove_verify() {
+        let dir = std::env::temp_dir().join(format!("plonk-cli-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = |name: &str| dir.join(name).to_str().unwrap().to_string();
+        let run = |args: &[&str]| {
+            let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+            run(&args)
+        };
+        std::fs::write(path("c.json"), CIRCUIT).unwrap();
+        std::fs::write(path("w.json"), r#"{ "x": "3", "y": "4" }"#).unwrap();
+
+        run(&["setup", "--degree", "20", "--out", &path("srs.bin")]).unwrap();
+        let compiled = run(&["compile", "--circuit", &path("c.json"), "--srs", &path("srs.bin"), "--pk", &path("pk.bin"), "--vk", &path("vk.bin")]).unwrap();
+        assert_eq!(compiled, "compiled 8 rows and 1 public inputs; needs an SRS of degree 13");
+        let prove = ["prove", "--circuit", &path("c.json"), "--witness", &path("w.json"), "--pk", &path("pk.bin")];
+        run(&[&prove[..], &["--proof", &path("proof.bin"), "--public", &path("public.json")]].concat()).unwrap();
+        assert_eq!(std::fs::read_to_string(path("public.json")).unwrap(), r#"["13"]"#);
+        let verify = ["verify", "--proof", &path("proof.bin"), "--vk", &path("vk.bin"), "--public", &path("public.json")];
+        assert_eq!(run(&verify).unwrap(), "proof is valid");
+
+        std::fs::write(path("public.json"), r#"["14"]"#).unwrap();
+        assert!(matches!(run(&verify), Err(CliError::Plonk(Error::Verify(VerifyError::QuotientMismatch)))));
+        // x = 2 breaks the assertion x = 3
+        std::fs::write(path("w.json"), r#"{ "x": "2", "y": "4" }"#).unwrap();
+        assert!(matches!(run(&prove), Err(CliError::Invalid(_))));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_malformed_input() {
+        let description: CircuitDescription = serde_json::from_str(CIRCUIT).unwrap();
+        assert_eq!(description.inputs().unwrap(), ["x", "y"]);
+        let witness = |pairs: &[(&str, &str)]| pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect::<BTreeMap<_, _>>();
+        assert!(matches!(description.synthesize(Some(&witness(&[("x", "3")]))), Err(CliError::Invalid(_))));
+        assert!(matches!(description.synthesize(Some(&witness(&[("x", "3"), ("y", "1"), ("z", "0")]))), Err(CliError::Invalid(_))));
+        assert!(matches!(description.synthesize(Some(&witness(&[("x", "3"), ("y", "one")]))), Err(CliError::Invalid(_))));
+
+        let twice = r#"{ "gates": [{ "op": "add", "a": "x", "b": "x", "out": "x" }] }"#;
+        assert!(matches!(serde_json::from_str::<CircuitDescription>(twice).unwrap().inputs(), Err(CliError::Invalid(_))));
+        assert!(matches!(run(&["prove".to_string()]), Err(CliError::Usage(_))));
+        assert!(matches!(run(&["verify".to_string(), "--key".to_string(), "vk.bin".to_string()]), Err(CliError::Usage(_))));
+        assert!(matches!(run(&["sign".to_string()]), Err(CliError::Usage(_))));
+    }
+}
@@ -0,0 +1,665 @@
+use alloc::collections::BTreeSet;
+use alloc::{vec, vec::Vec};
+use ark_ff::PrimeField;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, Compress, Read, SerializationError, Valid, Validate, Write};
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use zeroize::Zeroize;
+
+use crate::circuit::{domain_size, Circuit, CircuitError, CircuitSelectors};
+use crate::custom_gate::CustomGate;
+use crate::permutation::Permutation;
+
+/// A circuit's structure with the values taken out: selectors, copy
+/// constraints, the lookup table, custom gates and which rows are public.
+/// It is what gets keyed, once, and each `Witness` for it can then be
+/// proved against the same key. Its serialization is stable, so processes
+/// that load the same bytes (or whose `digest::fingerprint`s match) key
+/// the same circuit.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConstraintSystem<F: PrimeField> {
+    /// Power-of-two number of rows, at least 2
+    pub rows: usize,
+    pub selectors: CircuitSelectors<F>,
+    pub permutation: Permutation,
+    /// Rows whose `a` value is a public input, in statement order
+    pub public_rows: Vec<usize>,
+    pub table: Vec<[F; 3]>,
+    /// Gates owning the `selectors.custom` columns, in the same order
+    pub custom_gates: Vec<CustomGate<F>>,
+}
+
+/// Values of the a, b, c columns for one run of a circuit, one
+/// contiguous vector per column
+#[derive(Debug, Clone, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct Witness<F: PrimeField> {
+    pub a: Vec<F>,
+    pub b: Vec<F>,
+    pub c: Vec<F>,
+}
+
+impl<F: PrimeField> Drop for Witness<F> {
+    fn drop(&mut self) {
+        self.a.zeroize();
+        self.b.zeroize();
+        self.c.zeroize();
+    }
+}
+
+/// A gadget whose witness can be computed for many independent instances
+/// at once. Instance i of a batch fills `ROWS` consecutive rows of each
+/// column, written straight into the witness without building gates.
+pub trait BatchGadget<F: PrimeField>: Sync {
+    const ROWS: usize;
+    type Input: Sync;
+
+    /// Writes one instance's values into `ROWS`-long slices of a, b and c
+    fn assign(&self, input: &Self::Input, a: &mut [F], b: &mut [F], c: &mut [F]);
+}
+
+/// Sizes the chunks of a batch: one task gets as many instances as `F`
+/// values fit in this many bytes
+const CHUNK_BYTES: usize = 1 << 12;
+
+/// A circuit that lays out its gates on a `Circuit`. The layout must not
+/// depend on the values assigned, so the structure synthesized from any
+/// one instance keys every other.
+pub trait Synthesize<F: PrimeField> {
+    fn synthesize(&self, circuit: &mut Circuit<F>);
+}
+
+impl<F: PrimeField> ConstraintSystem<F> {
+    /// Structure of `circuit`, zero-padded to a power-of-two row count
+    /// that also fits the lookup table
+    pub fn from_circuit(circuit: &Circuit<F>) -> Self {
+        let rows = domain_size(circuit.n.max(circuit.table.len()));
+        let column = |values: &Vec<F>| {
+            let mut values = values.clone();
+            values.resize(rows, F::zero());
+            values
+        };
+        let mut selectors = CircuitSelectors::from_columns(circuit.selectors.columns().map(column));
+        selectors.custom = circuit.selectors.custom.iter().map(column).collect();
+        Self {
+            rows,
+            selectors,
+            permutation: circuit.permutation_over(rows),
+            public_rows: circuit.public_inputs.iter().map(|cell| cell.row).collect(),
+            table: circuit.table.clone(),
+            custom_gates: circuit.custom_gates.clone(),
+        }
+    }
+
+    /// Runs `source` on a circuit of `capacity` gates and splits the
+    /// result into structure and values. Fails with the first error the
+    /// circuit recorded, such as running out of capacity.
+    pub fn synthesize<S: Synthesize<F>>(source: &S, capacity: usize) -> Result<(Self, Witness<F>), CircuitError> {
+        let mut circuit = Circuit::new(capacity);
+        source.synthesize(&mut circuit);
+        if let Some(error) = circuit.error() {
+            return Err(error.clone());
+        }
+        let cs = Self::from_circuit(&circuit);
+        let witness = Witness::from_circuit(&circuit, cs.rows);
+        Ok((cs, witness))
+    }
+
+    /// Public input values carried by `witness`
+    pub fn public_inputs(&self, witness: &Witness<F>) -> Vec<F> {
+        self.public_rows.iter().map(|row| witness.a[*row]).collect()
+    }
+
+    /// The prover's form of this structure filled in with `witness`
+    #[cfg(feature = "prover")]
+    pub fn assign(&self, witness: &Witness<F>) -> Result<crate::prover::PlonkCircuit<F>, CircuitError> {
+        if let Some(column) = [&witness.a, &witness.b, &witness.c].into_iter().find(|column| column.len() != self.rows) {
+            return Err(CircuitError::WitnessRows { expected: self.rows, got: column.len() });
+        }
+        Ok(crate::prover::PlonkCircuit {
+            q_l: self.selectors.q_l.clone(),
+            q_r: self.selectors.q_r.clone(),
+            q_o: self.selectors.q_o.clone(),
+            q_m: self.selectors.q_m.clone(),
+            q_c: self.selectors.q_c.clone(),
+            q_k: self.selectors.q_k.clone(),
+            a: witness.a.clone(),
+            b: witness.b.clone(),
+            c: witness.c.clone(),
+            permutation: self.permutation.clone(),
+            public_rows: self.public_rows.clone(),
+            public_inputs: self.public_inputs(witness),
+            table: self.table.clone(),
+            custom_gates: self.custom_gates.clone(),
+            q_custom: self.selectors.custom.clone(),
+        })
+    }
+}
+
+impl<F: PrimeField> ConstraintSystem<F> {
+    fn is_well_formed(&self) -> bool {
+        let n = self.rows;
+        n.is_power_of_two()
+            && n >= 2
+            && self.selectors.columns().into_iter().chain(&self.selectors.custom).all(|column| column.len() == n)
+            && self.permutation.columns() == 3
+            && self.permutation.rows() == n
+            && self.public_rows.iter().all(|row| *row < n)
+            && self.public_rows.iter().collect::<BTreeSet<_>>().len() == self.public_rows.len()
+            && self.table.len() <= n
+            && self.selectors.custom.len() == self.custom_gates.len()
+    }
+}
+
+/// Version 1 of the layout: `rows`, the selector columns in
+/// `CircuitSelectors::NAMES` order, the custom selector columns, σ,
+/// `public_rows`, the table and the custom gates, each in its canonical
+/// encoding
+impl<F: PrimeField> CanonicalSerialize for ConstraintSystem<F> {
+    fn serialize_with_mode<W: Write>(&self, mut writer: W, compress: Compress) -> Result<(), SerializationError> {
+        self.rows.serialize_with_mode(&mut writer, compress)?;
+        for column in self.selectors.columns() {
+            column.serialize_with_mode(&mut writer, compress)?;
+        }
+        self.selectors.custom.serialize_with_mode(&mut writer, compress)?;
+        self.permutation.serialize_with_mode(&mut writer, compress)?;
+        self.public_rows.serialize_with_mode(&mut writer, compress)?;
+        self.table.serialize_with_mode(&mut writer, compress)?;
+        self.custom_gates.serialize_with_mode(&mut writer, compress)
+    }
+
+    fn serialized_size(&self, compress: Compress) -> usize {
+        self.rows.serialized_size(compress)
+            + self.selectors.columns().iter().map(|column| column.serialized_size(compress)).sum::<usize>()
+            + self.selectors.custom.serialized_size(compress)
+            + self.permutation.serialized_size(compress)
+            + self.public_rows.serialized_size(compress)
+            + self.table.serialized_size(compress)
+            + self.custom_gates.serialized_size(compress)
+    }
+}
+
+/// Rejects columns of the wrong length, rows outside the domain and a
+/// custom selector count that does not match the gates
+impl<F: PrimeField> Valid for ConstraintSystem<F> {
+    fn check(&self) -> Result<(), SerializationError> {
+        if self.is_well_formed() {
+            Ok(())
+        } else {
+            Err(SerializationError::InvalidData)
+        }
+    }
+}
+
+impl<F: PrimeField> CanonicalDeserialize for ConstraintSystem<F> {
+    fn deserialize_with_mode<R: Read>(mut reader: R, compress: Compress, validate: Validate) -> Result<Self, SerializationError> {
+        let rows = usize::deserialize_with_mode(&mut reader, compress, validate)?;
+        let mut selectors = CircuitSelectors::default();
+        for column in selectors.columns_mut() {
+            *column = Vec::deserialize_with_mode(&mut reader, compress, validate)?;
+        }
+        selectors.custom = Vec::deserialize_with_mode(&mut reader, compress, validate)?;
+        let cs = Self {
+            rows,
+            selectors,
+            permutation: Permutation::deserialize_with_mode(&mut reader, compress, validate)?,
+            public_rows: Vec::deserialize_with_mode(&mut reader, compress, validate)?,
+            table: Vec::deserialize_with_mode(&mut reader, compress, validate)?,
+            custom_gates: Vec::deserialize_with_mode(&mut reader, compress, validate)?,
+        };
+        if validate == Validate::Yes {
+            cs.check()?;
+        }
+        Ok(cs)
+    }
+}
+
+impl<F: PrimeField> Witness<F> {
+    /// Wire values of `circuit`, with rows past its gates set to zero up
+    /// to `rows`
+    pub fn from_circuit(circuit: &Circuit<F>, rows: usize) -> Self {
+        let column = |values: &Vec<F>| {
+            let mut values = values.clone();
+            values.resize(rows, F::zero());
+            values
+        };
+        Self {
+            a: column(&circuit.a),
+            b: column(&circuit.b),
+            c: column(&circuit.c),
+        }
+    }
+
+    /// `rows` rows of zeros, allocated once up front
+    pub fn zeros(rows: usize) -> Self {
+        Self {
+            a: vec![F::zero(); rows],
+            b: vec![F::zero(); rows],
+            c: vec![F::zero(); rows],
+        }
+    }
+
+    pub fn rows(&self) -> usize {
+        self.a.len()
+    }
+
+    /// Assigns one instance of `gadget` per input, instance i on the rows
+    /// from `offset + i·ROWS`. With `parallel` the batch is cut into
+    /// chunks of `CHUNK_BYTES / size_of::<F>()` instances, one per task. The
+    /// columns are plain `Vec`s aligned only to `F`, so neighbouring chunks
+    /// may still share the cache line at their boundary.
+    pub fn assign_batch<G: BatchGadget<F>>(&mut self, gadget: &G, offset: usize, inputs: &[G::Input]) -> Result<(), CircuitError> {
+        let end = offset + inputs.len() * G::ROWS;
+        if end > self.rows() {
+            return Err(CircuitError::WitnessRows { expected: end, got: self.rows() });
+        }
+        if G::ROWS == 0 || inputs.is_empty() {
+            return Ok(());
+        }
+
+        let instances = (CHUNK_BYTES / core::mem::size_of::<F>()).max(1);
+        let rows = instances * G::ROWS;
+        let assign = |a: &mut [F], b: &mut [F], c: &mut [F], inputs: &[G::Input]| {
+            let rows = a.chunks_exact_mut(G::ROWS).zip(b.chunks_exact_mut(G::ROWS)).zip(c.chunks_exact_mut(G::ROWS));
+            for (((a, b), c), input) in rows.zip(inputs) {
+                gadget.assign(input, a, b, c);
+            }
+        };
+        let (a, b, c) = (&mut self.a[offset..end], &mut self.b[offset..end], &mut self.c[offset..end]);
+
+        #[cfg(feature = "parallel")]
+        a.par_chunks_mut(rows)
+            .zip(b.par_chunks_mut(rows))
+            .zip(c.par_chunks_mut(rows))
+            .zip(inputs.par_chunks(instances))
+            .for_each(|(((a, b), c), inputs)| assign(a, b, c, inputs));
+
+        #[cfg(not(feature = "parallel"))]
+        a.chunks_mut(rows)
+            .zip(b.chunks_mut(rows))
+            .zip(c.chunks_mut(rows))
+            .zip(inputs.chunks(instances))
+            .for_each(|(((a, b), c), inputs)| assign(a, b, c, inputs));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuit::{Gate, GateType, Wire};
+    use ark_bls12_381::Fr;
+
+    /// Exposes x · y after computing it from private x and y
+    struct Product {
+        x: u64,
+        y: u64,
+    }
+
+    impl Synthesize<Fr> for Product {
+        fn synthesize(&self, circuit: &mut Circuit<Fr>) {
+            let product = circuit.add_public_input(Fr::from(self.x * self.y));
+            let next = circuit.next_wire_index();
+            circuit.add_gate(Gate {
+                gate_type: GateType::Mul,
+                left_wire: Wire { index: next, value: Fr::from(self.x) },
+                right_wire: Wire { index: next + 1, value: Fr::from(self.y) },
+                output_wire: product,
+            });
+        }
+    }
+
+    #[test]
+    fn test_structure_is_independent_of_witness() {
+        let (cs, witness) = ConstraintSystem::synthesize(&Product { x: 3, y: 5 }, 3).unwrap();
+        let (other_cs, other) = ConstraintSystem::synthesize(&Product { x: 2, y: 7 }, 3).unwrap();
+        assert_eq!(cs, other_cs);
+        assert_ne!(witness, other);
+        assert_eq!(cs.rows, 4);
+        assert_eq!(cs.public_inputs(&witness), vec![Fr::from(15u64)]);
+        assert_eq!(cs.public_inputs(&other), vec![Fr::from(14u64)]);
+    }
+
+    #[test]
+    #[cfg(all(feature = "prover", feature = "verifier"))]
+    fn test_key_once_prove_many() {
+        use crate::kgz::KZGParams;
+        use crate::prover::{Prover, ProvingKey};
+        use crate::verifier::Verifier;
+        use ark_bls12_381::Bls12_381;
+
+        let mut rng = ark_std::test_rng();
+        let (cs, witness) = ConstraintSystem::synthesize(&Product { x: 3, y: 5 }, 3).unwrap();
+        let pk = ProvingKey::<Bls12_381>::preprocess(KZGParams::setup(9, &mut rng), &cs.assign(&witness).unwrap()).unwrap();
+
+        for (x, y) in [(3, 5), (2, 7), (11, 13)] {
+            let (_, witness) = ConstraintSystem::synthesize(&Product { x, y }, 3).unwrap();
+            let circuit = cs.assign(&witness).unwrap();
+            assert!(circuit.is_satisfied());
+            let proof = Prover::prove(&circuit, &pk, &mut rng).unwrap();
+            assert_eq!(Verifier::verify(&pk.vk, &[Fr::from(x * y)], &proof), Ok(()));
+        }
+    }
+
+    #[test]
+    #[cfg(all(feature = "prover", feature = "verifier"))]
+    fn test_constant_gates_prove() {
+        use crate::kgz::KZGParams;
+        use crate::prover::{Prover, ProvingKey};
+        use crate::verifier::Verifier;
+        use ark_bls12_381::Bls12_381;
+
+        /// Proves knowledge of a square root of a constant
+        struct SquareRoot {
+            root: u64,
+        }
+
+        impl Synthesize<Fr> for SquareRoot {
+            fn synthesize(&self, circuit: &mut Circuit<Fr>) {
+                let x = circuit.alloc(Fr::from(self.root));
+                let square = circuit.mul(x, x);
+                let nine = circuit.constant(Fr::from(9u64));
+                circuit.assert_equal(square, nine);
+            }
+        }
+
+        let mut rng = ark_std::test_rng();
+        let (cs, witness) = ConstraintSystem::synthesize(&SquareRoot { root: 3 }, 3).unwrap();
+        let circuit = cs.assign(&witness).unwrap();
+        let pk = ProvingKey::<Bls12_381>::preprocess(KZGParams::setup(9, &mut rng), &circuit).unwrap();
+        let proof = Prover::prove(&circuit, &pk, &mut rng).unwrap();
+        assert_eq!(Verifier::verify(&pk.vk, &[], &proof), Ok(()));
+
+        let (_, witness) = ConstraintSystem::synthesize(&SquareRoot { root: 4 }, 3).unwrap();
+        assert!(!cs.assign(&witness).unwrap().is_satisfied());
+    }
+
+    #[test]
+    #[cfg(all(feature = "prover", feature = "verifier"))]
+    fn test_sparse_public_inputs() {
+        use crate::circuit::Variable;
+        use crate::kgz::KZGParams;
+        use crate::prover::{Prover, ProvingKey};
+        use crate::verifier::{Verifier, VerifyError};
+        use ark_bls12_381::Bls12_381;
+
+        /// Public x on row 0, private y, public x · y on row 2
+        struct Scale {
+            x: u64,
+            y: u64,
+        }
+
+        impl Synthesize<Fr> for Scale {
+            fn synthesize(&self, circuit: &mut Circuit<Fr>) {
+                let x = Variable::from(&circuit.add_public_input(Fr::from(self.x)));
+                let y = circuit.alloc(Fr::from(self.y));
+                let product = circuit.mul(x, y);
+                let out = Variable::from(&circuit.add_public_input(Fr::from(self.x * self.y)));
+                circuit.assert_equal(product, out);
+            }
+        }
+
+        let mut rng = ark_std::test_rng();
+        let (cs, witness) = ConstraintSystem::synthesize(&Scale { x: 3, y: 5 }, 4).unwrap();
+        assert_eq!(cs.public_rows, vec![0, 2]);
+        let circuit = cs.assign(&witness).unwrap();
+        assert!(circuit.is_satisfied());
+        let pk = ProvingKey::<Bls12_381>::preprocess(KZGParams::setup(9, &mut rng), &circuit).unwrap();
+        assert_eq!(pk.vk.public_rows, vec![0, 2]);
+        let proof = Prover::prove(&circuit, &pk, &mut rng).unwrap();
+
+        let statement = [3u64, 15].map(Fr::from);
+        assert_eq!(Verifier::verify(&pk.vk, &statement, &proof), Ok(()));
+        assert_eq!(
+            Verifier::verify(&pk.vk, &[3u64, 16].map(Fr::from), &proof),
+            Err(VerifyError::QuotientMismatch)
+        );
+        assert_eq!(
+            Verifier::verify(&pk.vk, &statement[..1], &proof),
+            Err(VerifyError::PublicInputCount { expected: 2, got: 1 })
+        );
+    }
+
+    #[test]
+    fn test_batch_matches_synthesis() {
+        use crate::circuit::Variable;
+
+        /// x³ over two mul rows: x · x = x², x² · x = x³
+        struct Cube;
+
+        impl BatchGadget<Fr> for Cube {
+            const ROWS: usize = 2;
+            type Input = Fr;
+
+            fn assign(&self, x: &Fr, a: &mut [Fr], b: &mut [Fr], c: &mut [Fr]) {
+                let square = *x * x;
+                (a[0], b[0], c[0]) = (*x, *x, square);
+                (a[1], b[1], c[1]) = (square, *x, square * x);
+            }
+        }
+
+        struct Cubes(Vec<u64>);
+
+        impl Synthesize<Fr> for Cubes {
+            fn synthesize(&self, circuit: &mut Circuit<Fr>) {
+                for x in &self.0 {
+                    let x = circuit.alloc(Fr::from(*x));
+                    let square = circuit.mul(x, x);
+                    let _: Variable = circuit.mul(square, x);
+                }
+            }
+        }
+
+        // Spans several chunks, the last one partial
+        let xs: Vec<u64> = (0..300).collect();
+        let (cs, expected) = ConstraintSystem::synthesize(&Cubes(xs.clone()), 600).unwrap();
+        let mut witness = Witness::zeros(cs.rows);
+        witness.assign_batch(&Cube, 0, &xs.iter().map(|x| Fr::from(*x)).collect::<Vec<_>>()).unwrap();
+        assert_eq!(witness, expected);
+        assert_eq!(witness.c[2 * 299 + 1], Fr::from(299u64 * 299 * 299));
+
+        // An offset batch leaves the rows before it alone
+        let mut shifted = Witness::zeros(8);
+        shifted.assign_batch(&Cube, 3, &[Fr::from(2u64), Fr::from(3u64)]).unwrap();
+        assert_eq!(shifted.c[3..7], [4u64, 8, 9, 27].map(Fr::from));
+        assert_eq!(shifted.a[..3], [Fr::from(0u64); 3]);
+    }
+
+    #[test]
+    fn test_serialization_round_trip() {
+        use crate::artifact::{Artifact, ArtifactError};
+        use crate::custom_gate::CustomGate;
+        use crate::digest::{fingerprint, Sha256};
+        use crate::lookup::Table;
+
+        /// A public S-box output and one XOR lookup, so every part of the
+        /// structure is non-empty
+        struct Mixed;
+
+        impl Synthesize<Fr> for Mixed {
+            fn synthesize(&self, circuit: &mut Circuit<Fr>) {
+                let sbox = circuit.register_gate(CustomGate::quintic_sbox());
+                circuit.add_table(Table::from_binary_op(4, |x, y| x ^ y));
+                let y = circuit.add_public_input(Fr::from(243u64));
+                let [x, square] = [3u64, 9].map(|value| circuit.alloc(Fr::from(value)));
+                circuit.custom_gate(sbox, x, square, (&y).into());
+                let [p, q, r] = [1u64, 2, 3].map(|value| circuit.alloc(Fr::from(value)));
+                circuit.lookup(p, q, r);
+            }
+        }
+
+        let (cs, _) = ConstraintSystem::synthesize(&Mixed, 4).unwrap();
+        let bytes = cs.encode_artifact();
+        let decoded = ConstraintSystem::<Fr>::decode_artifact(&bytes).unwrap();
+        assert_eq!(decoded, cs);
+        assert_eq!(fingerprint::<Sha256, _>(&decoded), fingerprint::<Sha256, _>(&cs));
+        assert_eq!(bytes[8..16], (cs.rows as u64).to_le_bytes());
+
+        // Each inconsistency is caught on decode
+        let rejected = |edit: &dyn Fn(&mut ConstraintSystem<Fr>)| {
+            let mut cs = cs.clone();
+            edit(&mut cs);
+            matches!(ConstraintSystem::<Fr>::decode_artifact(&cs.encode_artifact()), Err(ArtifactError::Malformed))
+        };
+        assert!(rejected(&|cs| cs.public_rows.push(cs.rows)));
+        assert!(rejected(&|cs| {
+            cs.selectors.q_m.pop();
+        }));
+        assert!(rejected(&|cs| cs.custom_gates.clear()));
+        assert!(rejected(&|cs| cs.rows = 12));
+        assert!(!rejected(&|_| ()));
+    }
+
+    #[test]
+    #[cfg(all(feature = "prover", feature = "verifier"))]
+    fn test_lookups_prove() {
+        use crate::kgz::KZGParams;
+        use crate::lookup::Table;
+        use crate::prover::{Prover, ProvingKey};
+        use crate::verifier::Verifier;
+        use ark_bls12_381::Bls12_381;
+
+        /// x ⊕ y = z on 2-bit limbs, each checked against the XOR table
+        struct Xor(Vec<[u64; 3]>);
+
+        impl Synthesize<Fr> for Xor {
+            fn synthesize(&self, circuit: &mut Circuit<Fr>) {
+                circuit.add_table(Table::from_binary_op(4, |x, y| x ^ y));
+                for row in &self.0 {
+                    let [x, y, z] = row.map(|value| circuit.alloc(Fr::from(value)));
+                    circuit.lookup(x, y, z);
+                }
+            }
+        }
+
+        let mut rng = ark_std::test_rng();
+        let (cs, witness) = ConstraintSystem::synthesize(&Xor(vec![[1, 2, 3], [3, 3, 0], [2, 3, 1]]), 3).unwrap();
+        // The table sets the domain size
+        assert_eq!(cs.rows, 16);
+        let circuit = cs.assign(&witness).unwrap();
+        assert!(circuit.is_satisfied());
+        let pk = ProvingKey::<Bls12_381>::preprocess(KZGParams::setup(21, &mut rng), &circuit).unwrap();
+        let proof = Prover::prove(&circuit, &pk, &mut rng).unwrap();
+        assert_eq!(Verifier::verify(&pk.vk, &[], &proof), Ok(()));
+
+        let (_, witness) = ConstraintSystem::synthesize(&Xor(vec![[1, 2, 3], [3, 3, 0], [2, 3, 2]]), 3).unwrap();
+        let circuit = cs.assign(&witness).unwrap();
+        assert!(!circuit.is_satisfied());
+        let proof = Prover::prove(&circuit, &pk, &mut rng).unwrap();
+        assert!(Verifier::verify(&pk.vk, &[], &proof).is_err());
+    }
+
+    #[test]
+    #[cfg(all(feature = "prover", feature = "verifier"))]
+    fn test_custom_gates_prove() {
+        use crate::custom_gate::CustomGate;
+        use crate::kgz::KZGParams;
+        use crate::prover::{Prover, ProvingKey};
+        use crate::verifier::{Verifier, VerifyError};
+        use ark_bls12_381::Bls12_381;
+
+        /// Public y = x⁵ for private x, through one S-box row; `square`
+        /// is the value claimed for x²
+        struct FifthPower {
+            x: u64,
+            square: u64,
+        }
+
+        impl Synthesize<Fr> for FifthPower {
+            fn synthesize(&self, circuit: &mut Circuit<Fr>) {
+                let sbox = circuit.register_gate(CustomGate::quintic_sbox());
+                let y = circuit.add_public_input(Fr::from(self.x.pow(5)));
+                let x = circuit.alloc(Fr::from(self.x));
+                let square = circuit.alloc(Fr::from(self.square));
+                circuit.custom_gate(sbox, x, square, (&y).into());
+            }
+        }
+
+        let mut rng = ark_std::test_rng();
+        let (cs, witness) = ConstraintSystem::synthesize(&FifthPower { x: 3, square: 9 }, 2).unwrap();
+        let circuit = cs.assign(&witness).unwrap();
+        assert!(circuit.is_satisfied());
+        let pk = ProvingKey::<Bls12_381>::preprocess(KZGParams::setup(9, &mut rng), &circuit).unwrap();
+        let proof = Prover::prove(&circuit, &pk, &mut rng).unwrap();
+        assert_eq!(Verifier::verify(&pk.vk, &[Fr::from(243u64)], &proof), Ok(()));
+        assert_eq!(
+            Verifier::verify(&pk.vk, &[Fr::from(244u64)], &proof),
+            Err(VerifyError::QuotientMismatch)
+        );
+
+        // The gate reaches the verifier only through its selector
+        // commitment in [r], so another commitment rejects the proof
+        let mut vk = pk.vk.clone();
+        vk.custom_selector_commitments[0] = vk.selector_commitments[0];
+        assert!(Verifier::verify(&vk, &[Fr::from(243u64)], &proof).is_err());
+
+        // c = a·b² still holds, but b is not a²
+        let (_, witness) = ConstraintSystem::synthesize(&FifthPower { x: 3, square: 9 }, 2).unwrap();
+        let mut circuit = cs.assign(&witness).unwrap();
+        circuit.b[1] = Fr::from(27u64);
+        circuit.c[1] = Fr::from(3u64 * 27 * 27);
+        circuit.a[0] = circuit.c[1];
+        circuit.public_inputs[0] = circuit.c[1];
+        assert!(!circuit.is_satisfied());
+        let proof = Prover::prove(&circuit, &pk, &mut rng).unwrap();
+        assert!(Verifier::verify(&pk.vk, &circuit.public_inputs, &proof).is_err());
+    }
+
+    #[test]
+    #[cfg(all(feature = "prover", feature = "verifier"))]
+    fn test_degenerate_circuits_prove() {
+        use crate::kgz::KZGParams;
+        use crate::prover::{Prover, ProvingKey};
+        use crate::verifier::{Verifier, VerifyError};
+        use ark_bls12_381::Bls12_381;
+
+        /// No gates at all
+        struct Empty;
+
+        impl Synthesize<Fr> for Empty {
+            fn synthesize(&self, _: &mut Circuit<Fr>) {}
+        }
+
+        /// A single constant gate and nothing else
+        struct Constant;
+
+        impl Synthesize<Fr> for Constant {
+            fn synthesize(&self, circuit: &mut Circuit<Fr>) {
+                circuit.constant(Fr::from(5u64));
+            }
+        }
+
+        /// A single public input
+        struct Echo;
+
+        impl Synthesize<Fr> for Echo {
+            fn synthesize(&self, circuit: &mut Circuit<Fr>) {
+                circuit.add_public_input(Fr::from(5u64));
+            }
+        }
+
+        fn prove<S: Synthesize<Fr>>(source: &S, capacity: usize) -> (ProvingKey<Bls12_381>, crate::proof::Proof<Bls12_381>) {
+            let mut rng = ark_std::test_rng();
+            let (cs, witness) = ConstraintSystem::synthesize(source, capacity).unwrap();
+            assert_eq!(cs.rows, 2);
+            let circuit = cs.assign(&witness).unwrap();
+            assert!(circuit.is_satisfied());
+            let pk = ProvingKey::preprocess(KZGParams::setup(9, &mut rng), &circuit).unwrap();
+            let proof = Prover::prove(&circuit, &pk, &mut rng).unwrap();
+            (pk, proof)
+        }
+
+        for capacity in [0, 1] {
+            let (pk, proof) = prove(&Empty, capacity);
+            assert_eq!(Verifier::verify(&pk.vk, &[], &proof), Ok(()));
+        }
+        let (pk, proof) = prove(&Constant, 1);
+        assert_eq!(Verifier::verify(&pk.vk, &[], &proof), Ok(()));
+        assert_eq!(
+            Verifier::verify(&pk.vk, &[Fr::from(5u64)], &proof),
+            Err(VerifyError::PublicInputCount { expected: 0, got: 1 })
+        );
+        let (pk, proof) = prove(&Echo, 1);
+        assert_eq!(Verifier::verify(&pk.vk, &[Fr::from(5u64)], &proof), Ok(()));
+        assert!(Verifier::verify(&pk.vk, &[Fr::from(6u64)], &proof).is_err());
+    }
+}
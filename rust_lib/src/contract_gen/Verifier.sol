@@ -0,0 +1,349 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.19;
+
+/// PLONK verifier for one circuit of {{ROWS}} rows, generated by plonk-lib from
+/// its verifying key. A proof is `Proof::to_bytes_with(Encoding::EVM)` and
+/// the statement its public inputs as field elements, in statement order.
+contract {{NAME}} {
+    /// Scalar field of BN254
+    uint256 internal constant R = 21888242871839275222246405745257275088548364400416034343698204186575808495617;
+    /// Base field of BN254
+    uint256 internal constant Q = 21888242871839275222246405745257275088696311157297823662689037894645226208583;
+
+    uint256 internal constant PROOF_LENGTH = 1248;
+    uint256 internal constant PUBLIC_INPUTS = {{PUBLIC_INPUTS}};
+    uint256 internal constant N = {{ROWS}};
+    uint256 internal constant OMEGA = {{OMEGA}};
+    /// ω^(n−1), the last row
+    uint256 internal constant OMEGA_LAST = {{OMEGA_LAST}};
+    uint256 internal constant K_A = {{K_A}};
+    uint256 internal constant K_B = {{K_B}};
+    uint256 internal constant K_C = {{K_C}};
+
+{{POINTS}}
+    /// The transcript up to the public inputs: protocol label, verifying
+    /// key, empty session and the length prefix of the statement
+    bytes internal constant TRANSCRIPT_PREFIX = hex"{{TRANSCRIPT_PREFIX}}";
+
+    struct Challenges {
+        uint256 theta;
+        uint256 beta;
+        uint256 gamma;
+        uint256 alpha;
+        uint256 zeta;
+        uint256 v;
+        uint256 u;
+    }
+
+    /// Values at ζ every verifier computes itself
+    struct Point {
+        uint256 zetaN;
+        /// Z_H(ζ) = ζ^n − 1
+        uint256 vanishing;
+        uint256 l1;
+        uint256 lLast;
+        uint256 pi;
+    }
+
+    /// Whether `proof` proves the statement `publicInputs`. Reverts when a
+    /// point of the proof is not on the curve.
+    function verify(bytes calldata proof, uint256[] calldata publicInputs) external view returns (bool) {
+        if (proof.length != PROOF_LENGTH || publicInputs.length != PUBLIC_INPUTS) {
+            return false;
+        }
+        for (uint256 i = 0; i < 15; i++) {
+            if (eval(proof, i) >= R) {
+                return false;
+            }
+        }
+        for (uint256 i = 0; i < publicInputs.length; i++) {
+            if (publicInputs[i] >= R) {
+                return false;
+            }
+        }
+
+        Challenges memory ch = challenges(proof, publicInputs);
+        Point memory p;
+        p.zetaN = expmod(ch.zeta, N);
+        p.vanishing = addmod(p.zetaN, R - 1, R);
+        if (p.vanishing == 0) {
+            return false;
+        }
+        p.l1 = lagrange(1, ch.zeta, p.vanishing);
+        p.lLast = lagrange(OMEGA_LAST, ch.zeta, p.vanishing);
+        p.pi = publicInputPolynomial(publicInputs, ch.zeta, p.vanishing);
+
+        // The quotient identity at ζ, r̄ + r₀ = 0
+        if (addmod(eval(proof, 14), constantTerm(proof, ch, p), R) != 0) {
+            return false;
+        }
+        (uint256[2] memory lhs, uint256[2] memory rhs) = openingInputs(proof, ch, p);
+        return pairing(lhs, rhs);
+    }
+
+    /// Replays the prover's Fiat-Shamir transcript
+    function challenges(bytes calldata proof, uint256[] calldata publicInputs) internal pure returns (Challenges memory ch) {
+        bytes memory t = TRANSCRIPT_PREFIX;
+        for (uint256 i = 0; i < publicInputs.length; i++) {
+            t = abi.encodePacked(t, le(publicInputs[i]));
+        }
+        t = abi.encodePacked(t, "wire_commitments", commitment(proof, 0), commitment(proof, 1), commitment(proof, 2));
+        (ch.theta, t) = squeeze(t, "theta");
+        t = abi.encodePacked(t, "lookup_commitments", commitment(proof, 3), commitment(proof, 4));
+        (ch.beta, t) = squeeze(t, "beta");
+        (ch.gamma, t) = squeeze(t, "gamma");
+        t = abi.encodePacked(t, "z_commitment", commitment(proof, 5), "z_lookup_commitment", commitment(proof, 6));
+        (ch.alpha, t) = squeeze(t, "alpha");
+        t = abi.encodePacked(t, "quotient_commitments", commitment(proof, 7), commitment(proof, 8), commitment(proof, 9));
+        (ch.zeta, t) = squeeze(t, "zeta");
+        t = abi.encodePacked(t, "wire_evaluations", evalLe(proof, 0), evalLe(proof, 1), evalLe(proof, 2));
+        t = abi.encodePacked(t, "sigma_evaluations", evalLe(proof, 3), evalLe(proof, 4), "z_omega_evaluation", evalLe(proof, 5));
+        t = abi.encodePacked(t, "table_evaluations", evalLe(proof, 7), evalLe(proof, 8), evalLe(proof, 9));
+        t = abi.encodePacked(t, "lookup_evaluations", evalLe(proof, 6), evalLe(proof, 10), evalLe(proof, 11));
+        t = abi.encodePacked(t, "lookup_omega_evaluations", evalLe(proof, 12), evalLe(proof, 13));
+        t = abi.encodePacked(t, "linearization_evaluation", evalLe(proof, 14));
+        (ch.v, t) = squeeze(t, "v");
+        t = abi.encodePacked(t, "opening_proofs", commitment(proof, 10), commitment(proof, 11));
+        (ch.u, ) = squeeze(t, "u");
+    }
+
+    /// Challenge from everything absorbed and `label`; the hash, not the
+    /// label, is absorbed after it
+    function squeeze(bytes memory t, bytes memory label) internal pure returns (uint256, bytes memory) {
+        bytes32 h = sha256(abi.encodePacked(t, label));
+        return (reverse(uint256(h)) % R, abi.encodePacked(t, h));
+    }
+
+    /// (a + β·σ_a + γ)(b + β·σ_b + γ)·z(ζω)
+    function permutedPrefix(bytes calldata proof, Challenges memory ch) internal pure returns (uint256) {
+        uint256 x = addmod(addmod(eval(proof, 0), mulmod(ch.beta, eval(proof, 3), R), R), ch.gamma, R);
+        uint256 y = addmod(addmod(eval(proof, 1), mulmod(ch.beta, eval(proof, 4), R), R), ch.gamma, R);
+        return mulmod(mulmod(x, y, R), eval(proof, 5), R);
+    }
+
+    /// r₀, the terms of the identity at ζ that no polynomial of r carries
+    function constantTerm(bytes calldata proof, Challenges memory ch, Point memory p) internal pure returns (uint256 r0) {
+        uint256 aP = eval(proof, 10);
+        uint256 sP = eval(proof, 11);
+        uint256[6] memory terms;
+        terms[0] = R - mulmod(permutedPrefix(proof, ch), addmod(eval(proof, 2), ch.gamma, R), R);
+        terms[1] = R - p.l1;
+        terms[2] = mulmod(mulmod(eval(proof, 12), addmod(aP, ch.beta, R), R), addmod(sP, ch.gamma, R), R);
+        terms[3] = R - p.l1;
+        terms[4] = mulmod(p.lLast, addmod(aP, R - sP, R), R);
+        terms[5] = mulmod(addmod(aP, R - sP, R), addmod(aP, R - eval(proof, 13), R), R);
+        r0 = p.pi;
+        uint256 power = ch.alpha;
+        for (uint256 i = 0; i < 6; i++) {
+            r0 = addmod(r0, mulmod(power, terms[i], R), R);
+            power = mulmod(power, ch.alpha, R);
+        }
+    }
+
+    /// [r], the linearization commitment, added to `acc`
+    function linearization(uint256[2] memory acc, bytes calldata proof, Challenges memory ch, Point memory p) internal view {
+        gateTerms(acc, proof);
+        (uint256 zScalar, uint256 sigmaScalar) = permutationScalars(proof, ch, p);
+        mulAddProof(acc, proof, 5, zScalar);
+        mulAdd(acc, SIGMA_C_X, SIGMA_C_Y, sigmaScalar);
+        mulAddProof(acc, proof, 6, lookupScalar(proof, ch, p));
+{{CUSTOM_GATES_CALL}}
+        // −Z_H(ζ)·(t_lo + ζ^n·t_mid + ζ^2n·t_hi)
+        uint256 power = R - p.vanishing;
+        for (uint256 j = 0; j < 3; j++) {
+            mulAddProof(acc, proof, 7 + j, power);
+            power = mulmod(power, p.zetaN, R);
+        }
+    }
+
+    /// q_l·a + q_r·b + q_o·c + q_m·a·b + q_c
+    function gateTerms(uint256[2] memory acc, bytes calldata proof) internal view {
+        uint256 a = eval(proof, 0);
+        uint256 b = eval(proof, 1);
+        mulAdd(acc, Q_L_X, Q_L_Y, a);
+        mulAdd(acc, Q_R_X, Q_R_Y, b);
+        mulAdd(acc, Q_O_X, Q_O_Y, eval(proof, 2));
+        mulAdd(acc, Q_M_X, Q_M_Y, mulmod(a, b, R));
+        mulAdd(acc, Q_C_X, Q_C_Y, 1);
+    }
+
+    /// α·[Π (w_j + β·k_j·ζ + γ)·z − (…)·β·σ_c·z(ζω)] + α²·L_1·z, as the
+    /// scalars of z and σ_c
+    function permutationScalars(bytes calldata proof, Challenges memory ch, Point memory p) internal pure returns (uint256, uint256) {
+        uint256 betaZeta = mulmod(ch.beta, ch.zeta, R);
+        uint256 s = addmod(addmod(eval(proof, 0), mulmod(betaZeta, K_A, R), R), ch.gamma, R);
+        s = mulmod(s, addmod(addmod(eval(proof, 1), mulmod(betaZeta, K_B, R), R), ch.gamma, R), R);
+        s = mulmod(s, addmod(addmod(eval(proof, 2), mulmod(betaZeta, K_C, R), R), ch.gamma, R), R);
+        uint256 zScalar = addmod(mulmod(ch.alpha, s, R), mulmod(mulmod(ch.alpha, ch.alpha, R), p.l1, R), R);
+        return (zScalar, R - mulmod(mulmod(ch.alpha, ch.beta, R), permutedPrefix(proof, ch), R));
+    }
+
+    /// −α³·(A + β)(T + γ) + α⁴·L_1, the scalar of z_L
+    function lookupScalar(bytes calldata proof, Challenges memory ch, Point memory p) internal pure returns (uint256) {
+        uint256 qk = eval(proof, 6);
+        uint256 table = compress(eval(proof, 7), eval(proof, 8), eval(proof, 9), ch.theta);
+        uint256 input = compress(eval(proof, 0), eval(proof, 1), eval(proof, 2), ch.theta);
+        input = addmod(mulmod(qk, input, R), mulmod(addmod(1, R - qk, R), table, R), R);
+        uint256 alpha3 = mulmod(mulmod(ch.alpha, ch.alpha, R), ch.alpha, R);
+        uint256 s = mulmod(mulmod(alpha3, addmod(input, ch.beta, R), R), addmod(table, ch.gamma, R), R);
+        return addmod(R - s, mulmod(mulmod(alpha3, ch.alpha, R), p.l1, R), R);
+    }
+{{CUSTOM_GATES}}
+    /// A running Σ power·[P] in `acc` and Σ power·value in `sum`, the
+    /// power advancing by v after each term
+    struct Batch {
+        uint256[2] acc;
+        uint256 sum;
+        uint256 power;
+        uint256 v;
+    }
+
+    function add(Batch memory b, uint256 x, uint256 y, uint256 value) internal view {
+        mulAdd(b.acc, x, y, b.power);
+        b.sum = addmod(b.sum, mulmod(b.power, value, R), R);
+        b.power = mulmod(b.power, b.v, R);
+    }
+
+    /// `add` for commitment `index` of the proof and evaluation `k`
+    function addProof(Batch memory b, bytes calldata proof, uint256 index, uint256 k) internal view {
+        (uint256 x, uint256 y) = point(proof, index);
+        add(b, x, y, eval(proof, k));
+    }
+
+    /// L and R of the opening check e(L, [s]) = e(R, [1]), which with
+    /// F = [r] + Σ v^(i+1)·[f_i], y = r̄ + Σ v^(i+1)·f_i(ζ) over the
+    /// polynomials opened at ζ and F', y' likewise at ζω is
+    ///   e(W + u·W', [s]) = e(ζ·W + u·ζω·W' + F − [y] + u·(F' − [y']), [1])
+    function openingInputs(bytes calldata proof, Challenges memory ch, Point memory p)
+        internal
+        view
+        returns (uint256[2] memory lhs, uint256[2] memory rhs)
+    {
+        Batch memory b;
+        linearization(b.acc, proof, ch, p);
+        b.sum = eval(proof, 14);
+        b.power = ch.v;
+        b.v = ch.v;
+        addProof(b, proof, 0, 0);
+        addProof(b, proof, 1, 1);
+        addProof(b, proof, 2, 2);
+        add(b, SIGMA_A_X, SIGMA_A_Y, eval(proof, 3));
+        add(b, SIGMA_B_X, SIGMA_B_Y, eval(proof, 4));
+        add(b, Q_K_X, Q_K_Y, eval(proof, 6));
+        add(b, TABLE_1_X, TABLE_1_Y, eval(proof, 7));
+        add(b, TABLE_2_X, TABLE_2_Y, eval(proof, 8));
+        add(b, TABLE_3_X, TABLE_3_Y, eval(proof, 9));
+        addProof(b, proof, 3, 10);
+        addProof(b, proof, 4, 11);
+
+        // z, z_L and A' at ζω, scaled by u
+        b.power = ch.u;
+        addProof(b, proof, 5, 5);
+        addProof(b, proof, 6, 12);
+        addProof(b, proof, 3, 13);
+
+        rhs = b.acc;
+        mulAddProof(rhs, proof, 10, ch.zeta);
+        mulAddProof(rhs, proof, 11, mulmod(mulmod(ch.u, ch.zeta, R), OMEGA, R));
+        mulAdd(rhs, G1_X, G1_Y, R - b.sum);
+        mulAddProof(lhs, proof, 10, 1);
+        mulAddProof(lhs, proof, 11, ch.u);
+    }
+
+    /// e(L, [s])·e(−R, [1]) = 1
+    function pairing(uint256[2] memory lhs, uint256[2] memory rhs) internal view returns (bool) {
+        uint256[12] memory input = [
+            lhs[0], lhs[1], G2_S_X_C1, G2_S_X_C0, G2_S_Y_C1, G2_S_Y_C0,
+            rhs[0], rhs[1] == 0 ? 0 : Q - rhs[1], G2_X_C1, G2_X_C0, G2_Y_C1, G2_Y_C0
+        ];
+        (bool ok, bytes memory out) = address(8).staticcall(abi.encodePacked(input));
+        return ok && out.length == 32 && abi.decode(out, (uint256)) == 1;
+    }
+
+    /// acc += s·(x, y)
+    function mulAdd(uint256[2] memory acc, uint256 x, uint256 y, uint256 s) internal view {
+        (bool ok, bytes memory out) = address(7).staticcall(abi.encode(x, y, s));
+        require(ok && out.length == 64, "invalid point");
+        (uint256 mx, uint256 my) = abi.decode(out, (uint256, uint256));
+        (ok, out) = address(6).staticcall(abi.encode(acc[0], acc[1], mx, my));
+        require(ok && out.length == 64, "invalid point");
+        (acc[0], acc[1]) = abi.decode(out, (uint256, uint256));
+    }
+
+    /// `mulAdd` for commitment `index` of the proof
+    function mulAddProof(uint256[2] memory acc, bytes calldata proof, uint256 index, uint256 s) internal view {
+        (uint256 x, uint256 y) = point(proof, index);
+        mulAdd(acc, x, y, s);
+    }
+
+    /// −Σ x_j·L_{i_j}(ζ) over the public input rows i_j
+{{PUBLIC_INPUT_POLYNOMIAL}}
+    /// L_i(ζ) = ω^i·Z_H(ζ) / (n·(ζ − ω^i)), for `w` = ω^i
+    function lagrange(uint256 w, uint256 zeta, uint256 vanishing) internal view returns (uint256) {
+        uint256 denominator = mulmod(N, addmod(zeta, R - w, R), R);
+        return mulmod(mulmod(w, vanishing, R), expmod(denominator, R - 2), R);
+    }
+
+    /// x + θ·y + θ²·z
+    function compress(uint256 x, uint256 y, uint256 z, uint256 theta) internal pure returns (uint256) {
+        return addmod(x, mulmod(theta, addmod(y, mulmod(theta, z, R), R), R), R);
+    }
+
+    function expmod(uint256 base, uint256 exponent) internal view returns (uint256) {
+        (bool ok, bytes memory out) = address(5).staticcall(abi.encode(uint256(32), uint256(32), uint256(32), base, exponent, R));
+        require(ok && out.length == 32, "modexp failed");
+        return abi.decode(out, (uint256));
+    }
+
+    /// Word `i` of the proof, big-endian
+    function word(bytes calldata proof, uint256 i) internal pure returns (uint256) {
+        return uint256(bytes32(proof[32 * i:32 * i + 32]));
+    }
+
+    /// Evaluation `k` of the proof, in `ProofEvaluations` field order
+    function eval(bytes calldata proof, uint256 k) internal pure returns (uint256) {
+        return word(proof, 20 + k);
+    }
+
+    function evalLe(bytes calldata proof, uint256 k) internal pure returns (bytes32) {
+        return le(eval(proof, k));
+    }
+
+    /// Commitment `index` of the proof: the ten before the evaluations,
+    /// then W and W' as 10 and 11
+    function point(bytes calldata proof, uint256 index) internal pure returns (uint256, uint256) {
+        uint256 i = index < 10 ? 2 * index : 2 * index + 15;
+        return (word(proof, i), word(proof, i + 1));
+    }
+
+    /// Compressed encoding of commitment `index` of the proof
+    function commitment(bytes calldata proof, uint256 index) internal pure returns (bytes32) {
+        (uint256 x, uint256 y) = point(proof, index);
+        if (x == 0 && y == 0) {
+            return le(1 << 254);
+        }
+        require(y < Q, "invalid point");
+        if (y > Q - y) {
+            x |= 1 << 255;
+        }
+        return le(x);
+    }
+
+    /// The 32 little-endian bytes of `x`, as arkworks writes field elements
+    function le(uint256 x) internal pure returns (bytes32) {
+        return bytes32(reverse(x));
+    }
+
+    function reverse(uint256 x) internal pure returns (uint256 v) {
+        v = x;
+        v = ((v & 0xFF00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF00) >> 8)
+            | ((v & 0x00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF) << 8);
+        v = ((v & 0xFFFF0000FFFF0000FFFF0000FFFF0000FFFF0000FFFF0000FFFF0000FFFF0000) >> 16)
+            | ((v & 0x0000FFFF0000FFFF0000FFFF0000FFFF0000FFFF0000FFFF0000FFFF0000FFFF) << 16);
+        v = ((v & 0xFFFFFFFF00000000FFFFFFFF00000000FFFFFFFF00000000FFFFFFFF00000000) >> 32)
+            | ((v & 0x00000000FFFFFFFF00000000FFFFFFFF00000000FFFFFFFF00000000FFFFFFFF) << 32);
+        v = ((v & 0xFFFFFFFFFFFFFFFF0000000000000000FFFFFFFFFFFFFFFF0000000000000000) >> 64)
+            | ((v & 0x0000000000000000FFFFFFFFFFFFFFFF0000000000000000FFFFFFFFFFFFFFFF) << 64);
+        v = (v >> 128) | (v << 128);
+    }
+}
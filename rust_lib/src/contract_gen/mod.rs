@@ -0,0 +1,415 @@
+//! Solidity verifier contracts for BN254 verifying keys.
+//!
+//! The generated contract hard-codes one circuit: its commitments, domain
+//! and public input rows become constants, and the verifying key's
+//! transcript prefix is stored as bytes so the contract replays the
+//! SHA-256 Fiat-Shamir transcript exactly as `Verifier` does. Curve
+//! arithmetic goes through the ecAdd, ecMul and ecPairing precompiles,
+//! inversions through modexp.
+
+use alloc::{format, string::{String, ToString}, vec::Vec};
+use ark_ec::AffineRepr;
+use ark_ff::{FftField, Field, PrimeField};
+use ark_serialize::CanonicalSerialize;
+
+use crate::bn254::{Fr, VerifyingKey};
+use crate::custom_gate::Expression;
+use crate::proof::PROTOCOL_LABEL;
+
+const TEMPLATE: &str = include_str!("Verifier.sol");
+
+/// Why no contract could be generated
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ContractError {
+    /// The domain is not a power of two the field supports, or a public
+    /// row lies outside it
+    MalformedKey,
+    /// The contract name is not a Solidity identifier
+    InvalidName(String),
+}
+
+impl core::fmt::Display for ContractError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            ContractError::MalformedKey => write!(f, "malformed verifying key"),
+            ContractError::InvalidName(name) => write!(f, "{:?} is not a valid contract name", name),
+        }
+    }
+}
+
+/// Source of a contract named `name` whose `verify(proof, publicInputs)`
+/// checks a proof for `vk` written by `Proof::to_bytes_with(Encoding::EVM)`.
+///
+/// The contract follows `Verifier::verify`. Its transcript is tested
+/// against the verifier's, and `test_contract_verifies_proofs` compiles
+/// it with solc and runs proofs through it on revm; that test is ignored
+/// unless asked for, as it needs solc (`SOLC` or on the `PATH`).
+pub fn solidity_verifier(vk: &VerifyingKey, name: &str) -> Result<String, ContractError> {
+    let mut chars = name.chars();
+    let identifier = chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if !identifier {
+        return Err(ContractError::InvalidName(name.to_string()));
+    }
+    let n = vk.domain_size;
+    if n < 2 || !n.is_power_of_two() || !vk.is_consistent() {
+        return Err(ContractError::MalformedKey);
+    }
+    let omega = Fr::get_root_of_unity(n).ok_or(ContractError::MalformedKey)?;
+
+    let [k_a, k_b, k_c] = vk.coset_multipliers.map(decimal);
+    let (custom_call, custom_gates) = custom_gate_terms(vk);
+    Ok(TEMPLATE
+        .replace("{{NAME}}", name)
+        .replace("{{ROWS}}", &n.to_string())
+        .replace("{{PUBLIC_INPUTS}}", &vk.public_rows.len().to_string())
+        .replace("{{OMEGA}}", &decimal(omega))
+        .replace("{{OMEGA_LAST}}", &decimal(omega.pow([n - 1])))
+        .replace("{{K_A}}", &k_a)
+        .replace("{{K_B}}", &k_b)
+        .replace("{{K_C}}", &k_c)
+        .replace("{{POINTS}}", &points(vk))
+        .replace("{{TRANSCRIPT_PREFIX}}", &hex(&transcript_prefix(vk)))
+        .replace("{{CUSTOM_GATES_CALL}}\n", &custom_call)
+        .replace("{{CUSTOM_GATES}}", &custom_gates)
+        .replace("{{PUBLIC_INPUT_POLYNOMIAL}}", &public_input_polynomial(vk.public_rows.iter().map(|row| omega.pow([*row])))))
+}
+
+fn decimal<F: PrimeField>(x: F) -> String {
+    x.into_bigint().to_string()
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Everything the transcript absorbs before the first public input: the
+/// protocol label, the key, the empty session and the statement length
+pub(crate) fn transcript_prefix(vk: &VerifyingKey) -> Vec<u8> {
+    let mut bytes = PROTOCOL_LABEL.to_vec();
+    let mut absorb = |label: &str, item: &dyn Fn(&mut Vec<u8>)| {
+        bytes.extend_from_slice(label.as_bytes());
+        item(&mut bytes);
+    };
+    absorb("verifying_key", &|out| vk.serialize_compressed(out).expect("writing to a Vec"));
+    absorb("session", &|out| out.extend_from_slice(&0u64.to_le_bytes()));
+    absorb("public_inputs", &|out| out.extend_from_slice(&(vk.public_rows.len() as u64).to_le_bytes()));
+    bytes
+}
+
+/// G1 constants as x and y, the point at infinity as (0, 0); G2 constants
+/// as the precompile reads them, c1 before c0
+fn points(vk: &VerifyingKey) -> String {
+    let mut g1 = Vec::new();
+    for (name, point) in ["Q_L", "Q_R", "Q_O", "Q_M", "Q_C", "Q_K"].iter().zip(&vk.selector_commitments) {
+        g1.push((name.to_string(), *point));
+    }
+    for (name, point) in ["SIGMA_A", "SIGMA_B", "SIGMA_C"].iter().zip(&vk.sigma_commitments) {
+        g1.push((name.to_string(), *point));
+    }
+    for (name, point) in ["TABLE_1", "TABLE_2", "TABLE_3"].iter().zip(&vk.table_commitments) {
+        g1.push((name.to_string(), *point));
+    }
+    for (g, point) in vk.custom_selector_commitments.iter().enumerate() {
+        g1.push((format!("CUSTOM_{}", g), *point));
+    }
+    g1.push(("G1".to_string(), vk.opening_key.g1));
+
+    let mut out = String::new();
+    for (name, point) in g1 {
+        let (x, y) = point.xy().unwrap_or_default();
+        out += &format!("    uint256 internal constant {}_X = {};\n", name, decimal(x));
+        out += &format!("    uint256 internal constant {}_Y = {};\n", name, decimal(y));
+    }
+    for (name, point) in [("G2", vk.opening_key.g2), ("G2_S", vk.opening_key.g2_s)] {
+        let (x, y) = point.xy().unwrap_or_default();
+        for (coordinate, value) in [("X", x), ("Y", y)] {
+            out += &format!("    uint256 internal constant {}_{}_C1 = {};\n", name, coordinate, decimal(value.c1));
+            out += &format!("    uint256 internal constant {}_{}_C0 = {};\n", name, coordinate, decimal(value.c0));
+        }
+    }
+    out
+}
+
+/// The call adding q_g·Σ α^k·e_k(a, b, c) for every custom gate, with α^k
+/// continuing from α⁷ as in `linear_terms`, and the function it calls
+fn custom_gate_terms(vk: &VerifyingKey) -> (String, String) {
+    if vk.custom_gates.is_empty() {
+        return (String::new(), String::new());
+    }
+    // Only the wires some identity reads, so none is left unused
+    let mut body = String::new();
+    for (j, wire) in ["a", "b", "c"].iter().enumerate() {
+        let identities = vk.custom_gates.iter().flat_map(|gate| &gate.identities);
+        if identities.flat_map(|identity| identity.terms.keys()).any(|exponents| exponents[j] > 0) {
+            body += &format!("        uint256 {} = eval(proof, {});\n", wire, j);
+        }
+    }
+    body += "        uint256 power = expmod(ch.alpha, 7);\n        uint256 s;\n";
+    for (g, gate) in vk.custom_gates.iter().enumerate() {
+        body += &format!("        // {}\n        s = 0;\n", gate.name.replace('\n', " "));
+        for identity in &gate.identities {
+            body += &format!("        s = addmod(s, mulmod(power, {}, R), R);\n", expression(identity));
+            body += "        power = mulmod(power, ch.alpha, R);\n";
+        }
+        body += &format!("        mulAdd(acc, CUSTOM_{g}_X, CUSTOM_{g}_Y, s);\n");
+    }
+    let function = format!(
+        "
+    /// q_g·Σ α^k·e_k(a, b, c) for each custom gate g
+    function customGateTerms(uint256[2] memory acc, bytes calldata proof, Challenges memory ch) internal view {{
+{body}    }}
+"
+    );
+    ("        customGateTerms(acc, proof, ch);\n".to_string(), function)
+}
+
+/// `e` at the wires a, b, c as nested addmod and mulmod
+fn expression(e: &Expression<Fr>) -> String {
+    e.terms.iter().fold("0".to_string(), |sum, (exponents, coeff)| {
+        let term = ["a", "b", "c"]
+            .iter()
+            .zip(exponents)
+            .flat_map(|(wire, power)| core::iter::repeat_n(*wire, *power as usize))
+            .fold(decimal(*coeff), |product, wire| format!("mulmod({}, {}, R)", product, wire));
+        format!("addmod({}, {}, R)", sum, term)
+    })
+}
+
+/// PI(ζ) from the roots ω^i of the public rows, in statement order
+fn public_input_polynomial(rows: impl Iterator<Item = Fr>) -> String {
+    let lines: Vec<String> = rows
+        .enumerate()
+        .map(|(j, w)| format!("        pi = addmod(pi, R - mulmod(x[{}], lagrange({}, zeta, vanishing), R), R);\n", j, decimal(w)))
+        .collect();
+    if lines.is_empty() {
+        return "    function publicInputPolynomial(uint256[] calldata, uint256, uint256) internal pure returns (uint256) {\n        return 0;\n    }\n".to_string();
+    }
+    format!(
+        "    function publicInputPolynomial(uint256[] calldata x, uint256 zeta, uint256 vanishing) internal view returns (uint256 pi) {{\n{}    }}\n",
+        lines.concat()
+    )
+}
+
+#[cfg(all(test, feature = "prover"))]
+mod tests {
+    use super::*;
+    use ark_bn254::Fq;
+    use ark_ff::BigInteger;
+    use sha2::{Digest, Sha256};
+
+    use crate::bn254::{Params, Proof, ProvingKey};
+    use crate::custom_gate::CustomGate;
+    use crate::encoding::Encoding;
+    use crate::proof::{challenges, Step, SCHEDULE};
+    use crate::prover::fixtures::public_circuit;
+    use crate::prover::{PlonkCircuit, Prover};
+    use crate::transcript::TranscriptHash;
+
+    /// `public_circuit(15)` with 2⁵ = 32 through the quintic S-box on the
+    /// last row
+    fn circuit() -> PlonkCircuit<Fr> {
+        let mut circuit = public_circuit(15);
+        circuit.custom_gates = vec![CustomGate::quintic_sbox()];
+        circuit.q_custom = vec![[0u64, 0, 0, 1].map(Fr::from).to_vec()];
+        [circuit.a[3], circuit.b[3], circuit.c[3]] = [2u64, 4, 32].map(Fr::from);
+        circuit
+    }
+
+    /// The contract's `challenges`, step for step, on the EVM encoding
+    fn contract_challenges(vk: &VerifyingKey, public_inputs: &[Fr], proof: &[u8]) -> [Fr; 7] {
+        let word = |i: usize| proof[32 * i..32 * i + 32].to_vec();
+        let point = |index: usize| if index < 10 { 2 * index } else { 2 * index + 15 };
+        let le = |mut bytes: Vec<u8>| {
+            bytes.reverse();
+            bytes
+        };
+        let eval = |k: usize| le(word(20 + k));
+        let commitment = |index: usize| {
+            let (mut x, y) = (word(point(index)), word(point(index) + 1));
+            if x.iter().chain(&y).all(|byte| *byte == 0) {
+                x[0] = 0x40;
+            } else {
+                let y = Fq::from_be_bytes_mod_order(&y);
+                if y > -y {
+                    x[0] |= 0x80;
+                }
+            }
+            le(x)
+        };
+        let mut t = transcript_prefix(vk);
+        public_inputs.iter().for_each(|x| t.extend(le(x.into_bigint().to_bytes_be())));
+        let mut challenges = Vec::new();
+        let mut squeeze = |t: &mut Vec<u8>, label: &str| {
+            let h = Sha256::new().chain_update(&t).chain_update(label).finalize();
+            t.extend(h);
+            challenges.push(Fr::from_le_bytes_mod_order(&h));
+        };
+        let absorb = |t: &mut Vec<u8>, label: &str, items: Vec<Vec<u8>>| {
+            t.extend(label.as_bytes());
+            items.into_iter().for_each(|item| t.extend(item));
+        };
+        absorb(&mut t, "wire_commitments", (0..3).map(commitment).collect());
+        squeeze(&mut t, "theta");
+        absorb(&mut t, "lookup_commitments", (3..5).map(commitment).collect());
+        squeeze(&mut t, "beta");
+        squeeze(&mut t, "gamma");
+        absorb(&mut t, "z_commitment", vec![commitment(5)]);
+        absorb(&mut t, "z_lookup_commitment", vec![commitment(6)]);
+        squeeze(&mut t, "alpha");
+        absorb(&mut t, "quotient_commitments", (7..10).map(commitment).collect());
+        squeeze(&mut t, "zeta");
+        absorb(&mut t, "wire_evaluations", (0..3).map(eval).collect());
+        absorb(&mut t, "sigma_evaluations", (3..5).map(eval).collect());
+        absorb(&mut t, "z_omega_evaluation", vec![eval(5)]);
+        absorb(&mut t, "table_evaluations", (7..10).map(eval).collect());
+        absorb(&mut t, "lookup_evaluations", [6, 10, 11].map(eval).to_vec());
+        absorb(&mut t, "lookup_omega_evaluations", (12..14).map(eval).collect());
+        absorb(&mut t, "linearization_evaluation", vec![eval(14)]);
+        squeeze(&mut t, "v");
+        absorb(&mut t, "opening_proofs", (10..12).map(commitment).collect());
+        squeeze(&mut t, "u");
+        challenges.try_into().unwrap()
+    }
+
+    #[test]
+    fn test_contract_transcript_matches_verifier() {
+        let mut rng = ark_std::test_rng();
+        let pk = ProvingKey::preprocess(Params::setup(ProvingKey::srs_degree_for(4), &mut rng), &circuit()).unwrap();
+        let statement = [Fr::from(15u64)];
+        let proof: Proof = Prover::prove(&circuit(), &pk, &mut rng).unwrap();
+        let ch = challenges(&pk.vk, &[], TranscriptHash::Sha256, &statement, &proof).unwrap();
+        let expected = [ch.theta, ch.beta, ch.gamma, ch.alpha, ch.zeta, ch.v, ch.u];
+        assert_eq!(contract_challenges(&pk.vk, &statement, &proof.to_bytes_with(Encoding::EVM)), expected);
+
+        // Commitments at infinity take the infinity flag
+        let mut at_infinity = proof.clone();
+        at_infinity.lookup_commitments = [ark_bn254::G1Affine::zero(); 2];
+        let ch = challenges(&pk.vk, &[], TranscriptHash::Sha256, &statement, &at_infinity).unwrap();
+        assert_eq!(contract_challenges(&pk.vk, &statement, &at_infinity.to_bytes_with(Encoding::EVM))[1], ch.beta);
+    }
+
+    #[test]
+    fn test_contract_source() {
+        let mut rng = ark_std::test_rng();
+        let pk = ProvingKey::preprocess(Params::setup(ProvingKey::srs_degree_for(4), &mut rng), &circuit()).unwrap();
+        let source = solidity_verifier(&pk.vk, "SboxVerifier").unwrap();
+        assert!(source.contains("contract SboxVerifier {"));
+        assert!(source.contains("uint256 internal constant N = 4;"));
+        assert!(source.contains("uint256 internal constant PUBLIC_INPUTS = 1;"));
+        assert!(source.contains(&format!("TRANSCRIPT_PREFIX = hex\"{}\"", hex(&transcript_prefix(&pk.vk)))));
+        assert!(!source.contains("{{"));
+
+        // a² − b, then a·b² − c, each with its power of α
+        assert!(source.contains("function customGateTerms"));
+        assert!(source.contains("mulAdd(acc, CUSTOM_0_X, CUSTOM_0_Y, s);"));
+        assert_eq!(source.matches("s = addmod(s, mulmod(power, ").count(), 2);
+
+        // Every label of the schedule, in order
+        let mut rest = source.as_str();
+        for step in SCHEDULE.iter().skip(3) {
+            let label = match step {
+                Step::Absorb(label) | Step::Squeeze(label) => format!("\"{}\"", label),
+            };
+            let at = rest.find(&label).unwrap_or_else(|| panic!("{} missing or out of order", label));
+            rest = &rest[at + label.len()..];
+        }
+
+        assert_eq!(solidity_verifier(&pk.vk, "1st").unwrap_err(), ContractError::InvalidName("1st".to_string()));
+        let mut vk = pk.vk.clone();
+        vk.custom_gates.clear();
+        vk.custom_selector_commitments.clear();
+        assert!(!solidity_verifier(&vk, "Plain").unwrap().contains("customGateTerms"));
+        vk.domain_size = 6;
+        assert_eq!(solidity_verifier(&vk, "Plain").unwrap_err(), ContractError::MalformedKey);
+    }
+
+    /// Creation bytecode of contract `name` in `source`, from solc
+    fn compile(source: &str, name: &str) -> Vec<u8> {
+        use std::io::Write;
+        use std::process::{Command, Stdio};
+
+        let solc = std::env::var("SOLC").unwrap_or_else(|_| "solc".to_string());
+        let mut child = Command::new(&solc)
+            .args(["--optimize", "--combined-json", "bin", "-"])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .unwrap_or_else(|error| panic!("running {}: {}", solc, error));
+        child.stdin.take().unwrap().write_all(source.as_bytes()).unwrap();
+        let output = child.wait_with_output().unwrap();
+        assert!(output.status.success(), "solc failed:\n{}", String::from_utf8_lossy(&output.stderr));
+        let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        let bin = json["contracts"][format!("<stdin>:{}", name)]["bin"].as_str().unwrap();
+        (0..bin.len()).step_by(2).map(|i| u8::from_str_radix(&bin[i..i + 2], 16).unwrap()).collect()
+    }
+
+    /// Calldata of `verify(bytes proof, uint256[] publicInputs)`
+    fn verify_call(proof: &[u8], public_inputs: &[Fr]) -> Vec<u8> {
+        use sha3::Keccak256;
+
+        let word = |x: usize| [[0u8; 24].as_slice(), &(x as u64).to_be_bytes()].concat();
+        let padded = proof.len().div_ceil(32) * 32;
+        let mut data = Keccak256::digest(b"verify(bytes,uint256[])")[..4].to_vec();
+        data.extend(word(0x40));
+        data.extend(word(0x60 + padded));
+        data.extend(word(proof.len()));
+        data.extend(proof);
+        data.resize(data.len() + padded - proof.len(), 0);
+        data.extend(word(public_inputs.len()));
+        for x in public_inputs {
+            data.extend(x.into_bigint().to_bytes_be());
+        }
+        data
+    }
+
+    #[test]
+    #[ignore = "needs solc, from SOLC or the PATH"]
+    fn test_contract_verifies_proofs() {
+        use revm::primitives::{Address, ExecutionResult, Output, TxKind, U256};
+        use revm::{Evm, InMemoryDB};
+
+        let mut rng = ark_std::test_rng();
+        let pk = ProvingKey::preprocess(Params::setup(ProvingKey::srs_degree_for(4), &mut rng), &circuit()).unwrap();
+        let statement = [Fr::from(15u64)];
+        let proof: Proof = Prover::prove(&circuit(), &pk, &mut rng).unwrap();
+        let bytecode = compile(&solidity_verifier(&pk.vk, "SboxVerifier").unwrap(), "SboxVerifier");
+
+        let caller = Address::with_last_byte(1);
+        let mut evm = Evm::builder()
+            .with_db(InMemoryDB::default())
+            .modify_tx_env(|tx| {
+                tx.caller = caller;
+                tx.gas_limit = 30_000_000;
+                tx.transact_to = TxKind::Create;
+                tx.data = bytecode.into();
+            })
+            .build();
+        let contract = match evm.transact_commit().unwrap() {
+            ExecutionResult::Success { output: Output::Create(_, Some(address)), .. } => address,
+            result => panic!("deployment failed: {:?}", result),
+        };
+        let mut verify = |proof: &[u8], public_inputs: &[Fr]| {
+            let tx = evm.tx_mut();
+            tx.transact_to = TxKind::Call(contract);
+            tx.data = verify_call(proof, public_inputs).into();
+            tx.nonce = None;
+            match evm.transact().unwrap().result {
+                ExecutionResult::Success { output, .. } => U256::from_be_slice(output.data()) == U256::from(1),
+                _ => false,
+            }
+        };
+
+        let bytes = proof.to_bytes_with(Encoding::EVM);
+        assert!(verify(&bytes, &statement));
+        assert!(!verify(&bytes, &[Fr::from(16u64)]));
+        assert!(!verify(&bytes, &[]));
+        let mut forged = proof.clone();
+        forged.w_zeta = (forged.w_zeta + pk.vk.opening_key.g1).into();
+        assert!(!verify(&forged.to_bytes_with(Encoding::EVM), &statement));
+        let mut tampered = bytes.clone();
+        tampered[20 * 32 + 31] ^= 1;
+        assert!(!verify(&tampered, &statement));
+    }
+}
@@ -0,0 +1,225 @@
+use alloc::{collections::BTreeMap, string::{String, ToString}, vec, vec::Vec};
+use ark_ff::PrimeField;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+
+use crate::circuit::CircuitError;
+use crate::encoding::{Decoder, Encoder};
+
+/// Highest degree of an identity in the wires. Its selector adds one, the
+/// degree the permutation argument already reaches, so the quotient keeps
+/// its three parts.
+///
+/// The quotient is not split further for higher-degree gates: an identity
+/// such as c - a⁵ is rejected with `CircuitError::DegreeTooHigh` and has to
+/// be spread over intermediate wires, as `CustomGate::quintic_sbox` does.
+pub const MAX_IDENTITY_DEGREE: u32 = 3;
+
+/// Polynomial in the wires a, b, c of one row, kept as its monomials:
+/// exponents of (a, b, c) mapped to the coefficient
+#[derive(Debug, Clone, PartialEq, Eq, Default, CanonicalSerialize, CanonicalDeserialize)]
+pub struct Expression<F: PrimeField> {
+    pub terms: BTreeMap<[u32; 3], F>,
+}
+
+impl<F: PrimeField> Expression<F> {
+    pub fn constant(value: F) -> Self {
+        Self::monomial(value, [0, 0, 0])
+    }
+
+    /// Wire column `j`: 0 for a, 1 for b, 2 for c
+    pub fn wire(j: usize) -> Self {
+        let mut exponents = [0; 3];
+        exponents[j] = 1;
+        Self::monomial(F::one(), exponents)
+    }
+
+    pub fn a() -> Self {
+        Self::wire(0)
+    }
+
+    pub fn b() -> Self {
+        Self::wire(1)
+    }
+
+    pub fn c() -> Self {
+        Self::wire(2)
+    }
+
+    fn monomial(coeff: F, exponents: [u32; 3]) -> Self {
+        let mut terms = BTreeMap::new();
+        if !coeff.is_zero() {
+            terms.insert(exponents, coeff);
+        }
+        Self { terms }
+    }
+
+    /// Total degree in a, b, c; zero for constants and the zero polynomial
+    pub fn degree(&self) -> u32 {
+        self.terms.keys().map(|e| e.iter().sum()).max().unwrap_or(0)
+    }
+
+    pub fn evaluate(&self, wires: [F; 3]) -> F {
+        self.terms
+            .iter()
+            .map(|(exponents, coeff)| {
+                (0..3).fold(*coeff, |acc, j| acc * wires[j].pow([u64::from(exponents[j])]))
+            })
+            .sum()
+    }
+
+    fn accumulate(&mut self, exponents: [u32; 3], coeff: F) {
+        let sum = *self.terms.get(&exponents).unwrap_or(&F::zero()) + coeff;
+        if sum.is_zero() {
+            self.terms.remove(&exponents);
+        } else {
+            self.terms.insert(exponents, sum);
+        }
+    }
+}
+
+impl<F: PrimeField> core::ops::Add for Expression<F> {
+    type Output = Self;
+
+    fn add(mut self, other: Self) -> Self {
+        for (exponents, coeff) in other.terms {
+            self.accumulate(exponents, coeff);
+        }
+        self
+    }
+}
+
+impl<F: PrimeField> core::ops::Neg for Expression<F> {
+    type Output = Self;
+
+    fn neg(mut self) -> Self {
+        for coeff in self.terms.values_mut() {
+            *coeff = -*coeff;
+        }
+        self
+    }
+}
+
+impl<F: PrimeField> core::ops::Sub for Expression<F> {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        self + -other
+    }
+}
+
+impl<F: PrimeField> core::ops::Mul for Expression<F> {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self {
+        let mut product = Self::default();
+        for (x, p) in &self.terms {
+            for (y, q) in &other.terms {
+                product.accumulate([x[0] + y[0], x[1] + y[1], x[2] + y[2]], *p * *q);
+            }
+        }
+        product
+    }
+}
+
+/// A gate with its own selector column q_g. Every row sets q_g to 0 or
+/// 1 and must satisfy q_g·e(a, b, c) = 0 for each identity e of the gate.
+#[derive(Debug, Clone, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct CustomGate<F: PrimeField> {
+    pub name: String,
+    pub identities: Vec<Expression<F>>,
+}
+
+impl<F: PrimeField> CustomGate<F> {
+    pub fn new(name: &str, identities: Vec<Expression<F>>) -> Result<Self, CircuitError> {
+        let gate = Self { name: name.to_string(), identities };
+        match gate.degree() {
+            degree if degree > MAX_IDENTITY_DEGREE => Err(CircuitError::DegreeTooHigh { gate: gate.name, degree }),
+            _ => Ok(gate),
+        }
+    }
+
+    /// c = a⁵ for the Poseidon S-box, through b = a² so that both
+    /// identities stay within `MAX_IDENTITY_DEGREE`
+    pub fn quintic_sbox() -> Self {
+        let (a, b, c) = (Expression::a(), Expression::b(), Expression::c());
+        Self { name: "quintic_sbox".to_string(), identities: vec![a.clone() * a.clone() - b.clone(), a * b.clone() * b - c] }
+    }
+
+    /// Highest degree among the identities
+    pub fn degree(&self) -> u32 {
+        self.identities.iter().map(Expression::degree).max().unwrap_or(0)
+    }
+
+    pub fn is_satisfied(&self, wires: [F; 3]) -> bool {
+        self.identities.iter().all(|identity| identity.evaluate(wires).is_zero())
+    }
+
+    /// The name, then each identity as its monomials: exponents of a, b, c
+    /// followed by the coefficient
+    pub(crate) fn write(&self, out: &mut Encoder) {
+        out.string(&self.name);
+        out.u64(self.identities.len() as u64);
+        for identity in &self.identities {
+            out.u64(identity.terms.len() as u64);
+            for (exponents, coeff) in &identity.terms {
+                exponents.iter().for_each(|e| out.u64(u64::from(*e)));
+                out.field(coeff);
+            }
+        }
+    }
+
+    /// Reads what `write` wrote, rejecting gates the prover could not key
+    pub(crate) fn read(input: &mut Decoder) -> Option<Self> {
+        let name = input.string()?;
+        let count = input.count(8)?;
+        let exponent = |e: u64| u32::try_from(e).ok().filter(|e| *e <= MAX_IDENTITY_DEGREE);
+        let identities = (0..count)
+            .map(|_| {
+                let terms = input.count(24)?;
+                let terms = (0..terms)
+                    .map(|_| {
+                        let exponents = [exponent(input.u64()?)?, exponent(input.u64()?)?, exponent(input.u64()?)?];
+                        Some((exponents, input.field()?))
+                    })
+                    .collect::<Option<_>>()?;
+                Some(Expression { terms })
+            })
+            .collect::<Option<_>>()?;
+        let gate = Self { name, identities };
+        (gate.degree() <= MAX_IDENTITY_DEGREE).then_some(gate)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bls12_381::Fr;
+
+    #[test]
+    fn test_expressions() {
+        let (a, b) = (Expression::<Fr>::a(), Expression::b());
+        let square = (a.clone() + b.clone()) * (a.clone() - b.clone());
+        assert_eq!(square, a.clone() * a.clone() - b.clone() * b);
+        assert_eq!(square.degree(), 2);
+        let wires = [5u64, 3, 0].map(Fr::from);
+        assert_eq!(square.evaluate(wires), Fr::from(16u64));
+        assert_eq!((a.clone() - a).terms.len(), 0);
+
+        let sbox = CustomGate::quintic_sbox();
+        assert!(sbox.is_satisfied([2u64, 4, 32].map(Fr::from)));
+        assert!(!sbox.is_satisfied([2u64, 4, 31].map(Fr::from)));
+    }
+
+    #[test]
+    fn test_degree_limit() {
+        let a = Expression::<Fr>::a();
+        let quartic = CustomGate::new("quartic", vec![a.clone() * a.clone() * a.clone() * a.clone()]);
+        assert_eq!(quartic, Err(CircuitError::DegreeTooHigh { gate: "quartic".to_string(), degree: 4 }));
+        assert_eq!(quartic.unwrap_err().to_string(), "gate quartic has degree 4, at most 3 is supported");
+
+        let a5 = (0..4).fold(a.clone(), |power, _| power * a.clone());
+        let quintic = CustomGate::new("quintic", vec![a5 - Expression::c()]);
+        assert_eq!(quintic.unwrap_err().to_string(), "gate quintic has degree 5, at most 3 is supported");
+        assert_eq!(CustomGate::<Fr>::quintic_sbox().degree(), MAX_IDENTITY_DEGREE);
+    }
+}
@@ -0,0 +1,98 @@
+use alloc::vec::Vec;
+use ark_ff::PrimeField;
+use ark_serialize::CanonicalSerialize;
+use core::marker::PhantomData;
+
+use crate::poseidon::Native;
+use crate::transcript::{byte_chunks, PoseidonTranscript};
+
+/// 32-byte identifier of a circuit
+pub type CircuitDigest = [u8; 32];
+
+/// Hash function behind circuit and verifying-key fingerprints. Pick the
+/// one that is cheap where the digest gets recomputed: SHA-256 natively,
+/// Keccak-256 on the EVM, Poseidon inside a circuit.
+pub trait DigestScheme {
+    const NAME: &'static str;
+
+    fn digest(bytes: &[u8]) -> CircuitDigest;
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Sha256;
+
+impl DigestScheme for Sha256 {
+    const NAME: &'static str = "sha256";
+
+    fn digest(bytes: &[u8]) -> CircuitDigest {
+        use sha2::Digest;
+        sha2::Sha256::digest(bytes).into()
+    }
+}
+
+/// Keccak-256 as the EVM computes it, not NIST SHA3-256
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Keccak256;
+
+impl DigestScheme for Keccak256 {
+    const NAME: &'static str = "keccak256";
+
+    fn digest(bytes: &[u8]) -> CircuitDigest {
+        use sha3::Digest;
+        sha3::Keccak256::digest(bytes).into()
+    }
+}
+
+/// Poseidon over `F`: the input length, then the bytes in 31-byte
+/// little-endian chunks, squeezed into one element whose little-endian
+/// encoding is the digest. Wider fields keep its low 32 bytes.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Poseidon<F>(PhantomData<F>);
+
+impl<F: PrimeField> DigestScheme for Poseidon<F> {
+    const NAME: &'static str = "poseidon";
+
+    fn digest(bytes: &[u8]) -> CircuitDigest {
+        let mut transcript = PoseidonTranscript::new(Native, b"plonk-lib-digest");
+        let chunks: Vec<F> = byte_chunks(bytes);
+        transcript.absorb(b"length", &[F::from(bytes.len() as u64)]);
+        transcript.absorb(b"bytes", &chunks);
+        let mut encoded = Vec::new();
+        transcript.squeeze(b"digest").serialize_compressed(&mut encoded).expect("writing to a Vec");
+        encoded.truncate(32);
+        let mut digest = [0u8; 32];
+        digest[..encoded.len()].copy_from_slice(&encoded);
+        digest
+    }
+}
+
+/// `S` over the compressed encoding of `item`
+pub fn fingerprint<S: DigestScheme, T: CanonicalSerialize + ?Sized>(item: &T) -> CircuitDigest {
+    let mut bytes = Vec::new();
+    item.serialize_compressed(&mut bytes).expect("writing to a Vec");
+    S::digest(&bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_schemes() {
+        // Keccak-256 and SHA-256 of the empty string
+        assert_eq!(Keccak256::digest(b"")[..4], [0xc5, 0xd2, 0x46, 0x01]);
+        assert_eq!(Sha256::digest(b"")[..4], [0xe3, 0xb0, 0xc4, 0x42]);
+
+        type P = Poseidon<ark_bn254::Fr>;
+        let long = [7u8; 40];
+        assert_eq!(P::digest(&long), P::digest(&long));
+        assert_ne!(P::digest(&long), P::digest(&long[..39]));
+        // Trailing zero bytes would vanish from the last chunk without the length
+        assert_ne!(P::digest(&[1]), P::digest(&[1, 0]));
+
+        let item = ark_bls12_381::Fr::from(5u64);
+        let digests = [fingerprint::<Sha256, _>(&item), fingerprint::<Keccak256, _>(&item), fingerprint::<P, _>(&item)];
+        assert_ne!(digests[0], digests[1]);
+        assert_ne!(digests[1], digests[2]);
+    }
+}
@@ -0,0 +1,109 @@
+use ark_ec::{AffineRepr, CurveGroup, PrimeGroup};
+use ark_ed_on_bls12_381::{EdwardsAffine, EdwardsProjective, Fq, Fr as JubjubScalar};
+use ark_std::rand::Rng;
+use ark_std::UniformRand;
+use zeroize::Zeroize;
+
+/// ElGamal over Jubjub, the curve whose base field is the BLS12-381 scalar
+/// field. A value `v` in that field is masked with the x-coordinate of the
+/// shared point:
+///
+///   c1 = r·G,  c2 = v + (r·pk).x
+///
+/// Only curve arithmetic and one field addition are needed to check this
+/// relation, so it can be enforced in-circuit over BLS12-381 Fr, and
+/// decryption needs no discrete log. `gadgets::elgamal` is that circuit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Ciphertext {
+    pub c1: EdwardsAffine,
+    pub c2: Fq,
+}
+
+#[derive(Debug, Clone)]
+pub struct Keypair {
+    pub secret: JubjubScalar,
+    pub public: EdwardsAffine,
+}
+
+impl Keypair {
+    pub fn generate<R: Rng>(rng: &mut R) -> Self {
+        let secret = JubjubScalar::rand(rng);
+        Self {
+            secret,
+            public: (EdwardsProjective::generator() * secret).into_affine(),
+        }
+    }
+
+    pub fn decrypt(&self, ciphertext: &Ciphertext) -> Fq {
+        let shared = (ciphertext.c1 * self.secret).into_affine();
+        ciphertext.c2 - shared.x
+    }
+}
+
+impl Drop for Keypair {
+    fn drop(&mut self) {
+        self.secret.zeroize();
+    }
+}
+
+/// Encrypts `value` under `public` with explicit randomness `r`
+pub fn encrypt_with(public: &EdwardsAffine, value: Fq, r: JubjubScalar) -> Ciphertext {
+    let c1 = (EdwardsProjective::generator() * r).into_affine();
+    let shared = (*public * r).into_affine();
+    Ciphertext { c1, c2: value + shared.x }
+}
+
+pub fn encrypt<R: Rng>(public: &EdwardsAffine, value: Fq, rng: &mut R) -> Ciphertext {
+    encrypt_with(public, value, JubjubScalar::rand(rng))
+}
+
+impl Ciphertext {
+    /// The relation an encryption gadget proves: this ciphertext encrypts
+    /// `value` under `public` with randomness `r`
+    pub fn is_encryption_of(&self, public: &EdwardsAffine, value: Fq, r: JubjubScalar) -> bool {
+        *self == encrypt_with(public, value, r)
+    }
+
+    /// Decrypts from the shared point `sk·c1`, however it was obtained
+    /// (e.g. combined from threshold shares)
+    pub fn decrypt_with_shared(&self, shared: &EdwardsAffine) -> Fq {
+        self.c2 - shared.x
+    }
+}
+
+/// sk·c1, the point whose x-coordinate masks the value
+pub fn shared_point(secret: JubjubScalar, c1: &EdwardsAffine) -> EdwardsAffine {
+    (c1.into_group() * secret).into_affine()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encrypt_decrypt() {
+        let mut rng = ark_std::test_rng();
+        let keys = Keypair::generate(&mut rng);
+        let value = Fq::rand(&mut rng);
+
+        let ciphertext = encrypt(&keys.public, value, &mut rng);
+        assert_eq!(keys.decrypt(&ciphertext), value);
+        assert_eq!(ciphertext.decrypt_with_shared(&shared_point(keys.secret, &ciphertext.c1)), value);
+
+        let other = Keypair::generate(&mut rng);
+        assert_ne!(other.decrypt(&ciphertext), value);
+    }
+
+    #[test]
+    fn test_encryption_relation() {
+        let mut rng = ark_std::test_rng();
+        let keys = Keypair::generate(&mut rng);
+        let value = Fq::from(42u64);
+        let r = JubjubScalar::rand(&mut rng);
+
+        let ciphertext = encrypt_with(&keys.public, value, r);
+        assert!(ciphertext.is_encryption_of(&keys.public, value, r));
+        assert!(!ciphertext.is_encryption_of(&keys.public, value + Fq::from(1u64), r));
+        assert!(!ciphertext.is_encryption_of(&keys.public, value, r + JubjubScalar::from(1u64)));
+    }
+}
@@ -0,0 +1,154 @@
+//! Moving elements between prime fields. A value that fits is converted
+//! as is; anything wider, such as a base field coordinate in the scalar
+//! field, travels as 64-bit limbs, which fit every field of
+//! cryptographic size. `gadgets::embedding` range checks the same limbs
+//! in a circuit.
+//!
+//! A commitment bound into a statement is exposed the same way: each
+//! point becomes the public inputs `point_to_limbs` (KZG, short
+//! Weierstrass) or `edwards_to_limbs` (Pedersen over Jubjub) lists, one
+//! commitment after another. `gadgets::embedding::expose_point` and
+//! `expose_edwards_point` lay them out on the prover side, and the
+//! verifier passes `commitment_inputs` in the same place among its
+//! public inputs.
+
+use alloc::vec::Vec;
+use ark_ec::short_weierstrass::{Affine, SWCurveConfig};
+use ark_ec::twisted_edwards::{self, TECurveConfig};
+use ark_ff::PrimeField;
+
+/// Width of every limb
+pub const LIMB_BITS: usize = 64;
+
+/// Limbs needed for any element of `T`
+pub fn num_limbs<T: PrimeField>() -> usize {
+    (T::MODULUS_BIT_SIZE as usize).div_ceil(LIMB_BITS)
+}
+
+/// `x` in `B` with the same integer value, `None` if it is not below
+/// the modulus of `B`
+pub fn convert<A: PrimeField, B: PrimeField>(x: A) -> Option<B> {
+    let limbs = x.into_bigint();
+    let mut out = B::BigInt::default();
+    let width = out.as_ref().len();
+    if limbs.as_ref().iter().skip(width).any(|limb| *limb != 0) {
+        return None;
+    }
+    for (dst, src) in out.as_mut().iter_mut().zip(limbs.as_ref()) {
+        *dst = *src;
+    }
+    B::from_bigint(out)
+}
+
+/// The canonical value of `x` as `num_limbs::<T>()` limbs, least
+/// significant first
+pub fn to_limbs<T: PrimeField, F: PrimeField>(x: T) -> Vec<F> {
+    x.into_bigint().as_ref()[..num_limbs::<T>()].iter().map(|limb| F::from(*limb)).collect()
+}
+
+/// Inverse of `to_limbs`. `None` unless there are `num_limbs::<T>()`
+/// limbs, each below 2^64, and their value is below the modulus of `T`.
+pub fn from_limbs<T: PrimeField, F: PrimeField>(limbs: &[F]) -> Option<T> {
+    if limbs.len() != num_limbs::<T>() {
+        return None;
+    }
+    let mut value = T::BigInt::default();
+    for (dst, limb) in value.as_mut().iter_mut().zip(limbs) {
+        *dst = limb_value(*limb)?;
+    }
+    T::from_bigint(value)
+}
+
+/// `limb` as a u64, `None` if it does not fit
+pub(crate) fn limb_value<F: PrimeField>(limb: F) -> Option<u64> {
+    let bigint = limb.into_bigint();
+    let (low, high) = bigint.as_ref().split_first()?;
+    high.iter().all(|limb| *limb == 0).then_some(*low)
+}
+
+/// The limbs of x, then of y, then 1 for the point at infinity and 0
+/// otherwise. The identity has x = y = 0 in arkworks, so its limbs are
+/// all zero.
+pub fn point_to_limbs<P: SWCurveConfig, F: PrimeField>(point: &Affine<P>) -> Vec<F>
+where
+    P::BaseField: PrimeField,
+{
+    let mut limbs = to_limbs(point.x);
+    limbs.extend(to_limbs::<P::BaseField, F>(point.y));
+    limbs.push(F::from(point.infinity));
+    limbs
+}
+
+/// The limbs of x, then of y. The identity is (0, 1), so Edwards points
+/// need no infinity flag.
+pub fn edwards_to_limbs<P: TECurveConfig, F: PrimeField>(point: &twisted_edwards::Affine<P>) -> Vec<F>
+where
+    P::BaseField: PrimeField,
+{
+    let mut limbs = to_limbs(point.x);
+    limbs.extend(to_limbs::<P::BaseField, F>(point.y));
+    limbs
+}
+
+/// Public inputs binding `commitments`, in order, as `expose_point`
+/// exposes them
+pub fn commitment_inputs<P: SWCurveConfig, F: PrimeField>(commitments: &[Affine<P>]) -> Vec<F>
+where
+    P::BaseField: PrimeField,
+{
+    commitments.iter().flat_map(point_to_limbs).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bls12_381::{Fq, Fr, G1Affine};
+    use ark_ec::AffineRepr;
+    use ark_ff::{BigInteger, Field};
+    use ark_std::UniformRand;
+
+    #[test]
+    fn test_limbs_roundtrip() {
+        let mut rng = ark_std::test_rng();
+        assert_eq!((num_limbs::<Fq>(), num_limbs::<Fr>()), (6, 4));
+        for x in [Fq::from(0u64), -Fq::ONE, Fq::rand(&mut rng)] {
+            let limbs: Vec<Fr> = to_limbs(x);
+            assert_eq!(limbs.len(), 6);
+            assert_eq!(from_limbs::<Fq, Fr>(&limbs), Some(x));
+        }
+
+        // The modulus itself, a wide limb and a short slice are rejected
+        let modulus: Vec<Fr> = Fq::MODULUS.as_ref().iter().map(|limb| Fr::from(*limb)).collect();
+        assert_eq!(from_limbs::<Fq, Fr>(&modulus), None);
+        let mut wide = to_limbs::<Fq, Fr>(Fq::from(1u64));
+        wide[0] = Fr::from(u64::MAX) + Fr::ONE;
+        assert_eq!(from_limbs::<Fq, Fr>(&wide), None);
+        assert_eq!(from_limbs::<Fq, Fr>(&wide[..5]), None);
+    }
+
+    #[test]
+    fn test_convert() {
+        let x = Fr::rand(&mut ark_std::test_rng());
+        let y: Fq = convert(x).unwrap();
+        assert_eq!(convert::<Fq, Fr>(y), Some(x));
+        assert_eq!(convert::<Fq, Fr>(-Fq::ONE), None);
+        assert_eq!(convert::<Fr, Fq>(-Fr::ONE), Some(Fq::from_le_bytes_mod_order(&Fr::MODULUS.to_bytes_le()) - Fq::ONE));
+    }
+
+    #[test]
+    fn test_point_limbs() {
+        let g = G1Affine::generator();
+        let limbs: Vec<Fr> = point_to_limbs(&g);
+        assert_eq!(limbs.len(), 13);
+        assert_eq!(from_limbs::<Fq, Fr>(&limbs[..6]), Some(g.x));
+        assert_eq!(from_limbs::<Fq, Fr>(&limbs[6..12]), Some(g.y));
+        assert_eq!(limbs[12], Fr::from(0u64));
+        assert_eq!(point_to_limbs::<_, Fr>(&G1Affine::identity()), [vec![Fr::from(0u64); 12], vec![Fr::from(1u64)]].concat());
+        assert_eq!(commitment_inputs::<_, Fr>(&[g, G1Affine::identity()]).len(), 26);
+
+        let h = ark_ed_on_bls12_381::EdwardsAffine::generator();
+        let limbs: Vec<Fr> = edwards_to_limbs(&h);
+        assert_eq!(limbs.len(), 8);
+        assert_eq!(from_limbs::<ark_ed_on_bls12_381::Fq, Fr>(&limbs[4..]), Some(h.y));
+    }
+}
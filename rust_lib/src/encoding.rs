@@ -0,0 +1,312 @@
+use alloc::{string::String, vec::Vec};
+use ark_ec::{AffineRepr, CurveGroup};
+use ark_ff::{Field, PrimeField};
+use ark_serialize::CanonicalSerialize;
+use ark_std::Zero;
+
+/// How curve points are written
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PointFormat {
+    /// x only, with the sign of y and the infinity flag in its spare bits
+    #[default]
+    Compressed,
+    /// x then y with no flags; the point at infinity is all zero bytes
+    Uncompressed,
+}
+
+/// Byte order of every field element, point coordinates included
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Endianness {
+    #[default]
+    Little,
+    Big,
+}
+
+/// Wire format for proofs and verifying keys. The default is the arkworks
+/// canonical encoding, byte for byte what `to_bytes` produces.
+///
+/// Compressed points are the curve's arkworks encoding, reversed as a whole
+/// when big-endian. Otherwise big-endian reverses each field element, so an
+/// extension field element c0 + c1·u comes out as c1 then c0, the order
+/// the EVM pairing precompile expects for G2 coordinates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Encoding {
+    pub points: PointFormat,
+    pub endianness: Endianness,
+}
+
+impl Encoding {
+    pub const ARKWORKS: Self = Self {
+        points: PointFormat::Compressed,
+        endianness: Endianness::Little,
+    };
+
+    /// Uncompressed big-endian words, as Solidity verifiers read them
+    pub const EVM: Self = Self {
+        points: PointFormat::Uncompressed,
+        endianness: Endianness::Big,
+    };
+}
+
+fn to_bytes<T: CanonicalSerialize + ?Sized>(item: &T, compressed: bool) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    if compressed {
+        item.serialize_compressed(&mut bytes).expect("writing to a Vec");
+    } else {
+        item.serialize_uncompressed(&mut bytes).expect("writing to a Vec");
+    }
+    bytes
+}
+
+/// Appends values to a buffer in a given `Encoding`
+pub(crate) struct Encoder {
+    encoding: Encoding,
+    bytes: Vec<u8>,
+}
+
+impl Encoder {
+    pub fn new(encoding: Encoding) -> Self {
+        Self { encoding, bytes: Vec::new() }
+    }
+
+    fn ordered(&mut self, mut bytes: Vec<u8>) {
+        if self.encoding.endianness == Endianness::Big {
+            bytes.reverse();
+        }
+        self.bytes.extend_from_slice(&bytes);
+    }
+
+    pub fn u64(&mut self, value: u64) {
+        self.ordered(value.to_le_bytes().to_vec());
+    }
+
+    pub fn field<F: Field>(&mut self, value: &F) {
+        self.ordered(to_bytes(value, false));
+    }
+
+    /// Length, then the UTF-8 bytes in order whatever the endianness
+    pub fn string(&mut self, value: &str) {
+        self.u64(value.len() as u64);
+        self.bytes.extend_from_slice(value.as_bytes());
+    }
+
+    pub fn point<A: AffineRepr>(&mut self, point: &A) {
+        match (self.encoding.points, point.xy()) {
+            (PointFormat::Compressed, _) => self.ordered(to_bytes(point, true)),
+            (PointFormat::Uncompressed, Some((x, y))) => {
+                self.field(&x);
+                self.field(&y);
+            }
+            (PointFormat::Uncompressed, None) => {
+                let size = 2 * A::BaseField::zero().uncompressed_size();
+                self.bytes.resize(self.bytes.len() + size, 0);
+            }
+        }
+    }
+
+    pub fn finish(self) -> Vec<u8> {
+        self.bytes
+    }
+}
+
+/// Reads values written by `Encoder` with the same `Encoding`. Points are
+/// checked to be on the curve and in the prime-order subgroup.
+pub(crate) struct Decoder<'a> {
+    encoding: Encoding,
+    bytes: &'a [u8],
+}
+
+impl<'a> Decoder<'a> {
+    pub fn new(encoding: Encoding, bytes: &'a [u8]) -> Self {
+        Self { encoding, bytes }
+    }
+
+    /// The next `size` bytes in little-endian order
+    fn take(&mut self, size: usize) -> Option<Vec<u8>> {
+        if self.bytes.len() < size {
+            return None;
+        }
+        let (head, rest) = self.bytes.split_at(size);
+        self.bytes = rest;
+        let mut head = head.to_vec();
+        if self.encoding.endianness == Endianness::Big {
+            head.reverse();
+        }
+        Some(head)
+    }
+
+    pub fn u64(&mut self) -> Option<u64> {
+        let bytes = self.take(8)?;
+        Some(u64::from_le_bytes(bytes.try_into().ok()?))
+    }
+
+    /// A length prefix for items of at least `item_size` bytes each,
+    /// rejected when the input is too short to hold them all
+    pub fn count(&mut self, item_size: usize) -> Option<usize> {
+        let count = usize::try_from(self.u64()?).ok()?;
+        (count.checked_mul(item_size.max(1))? <= self.bytes.len()).then_some(count)
+    }
+
+    pub fn string(&mut self) -> Option<String> {
+        let len = self.count(1)?;
+        let (head, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        String::from_utf8(head.to_vec()).ok()
+    }
+
+    pub fn field<F: Field>(&mut self) -> Option<F> {
+        let bytes = self.take(F::zero().uncompressed_size())?;
+        F::deserialize_uncompressed(&bytes[..]).ok()
+    }
+
+    pub fn point<A: AffineRepr>(&mut self) -> Option<A> {
+        match self.encoding.points {
+            PointFormat::Compressed => {
+                let bytes = self.take(A::zero().compressed_size())?;
+                A::deserialize_compressed(&bytes[..]).ok()
+            }
+            PointFormat::Uncompressed => {
+                let x: A::BaseField = self.field()?;
+                let y: A::BaseField = self.field()?;
+                if x.is_zero() && y.is_zero() {
+                    return Some(A::zero());
+                }
+                // Curve implementations lay out their own uncompressed bytes
+                // differently (BLS12-381 uses the zcash format), so recover
+                // the point from x and pick the sign matching y
+                let candidate = A::from_random_bytes(&to_bytes(&x, false))?;
+                let point = [candidate, (-candidate.into_group()).into_affine()]
+                    .into_iter()
+                    .find(|p| p.xy() == Some((x, y)))?;
+                point.mul_bigint(A::ScalarField::MODULUS).is_zero().then_some(point)
+            }
+        }
+    }
+
+    /// Succeeds only if every byte was consumed
+    pub fn finish(self) -> Option<()> {
+        self.bytes.is_empty().then_some(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_std::UniformRand;
+
+    const MODES: [Encoding; 4] = [
+        Encoding::ARKWORKS,
+        Encoding::EVM,
+        Encoding { points: PointFormat::Compressed, endianness: Endianness::Big },
+        Encoding { points: PointFormat::Uncompressed, endianness: Endianness::Little },
+    ];
+
+    #[test]
+    fn test_point_and_field_roundtrip() {
+        use ark_bn254::{Fr, G1Projective, G2Projective};
+
+        let mut rng = ark_std::test_rng();
+        let g1 = G1Projective::rand(&mut rng).into_affine();
+        let g2 = G2Projective::rand(&mut rng).into_affine();
+        let x = Fr::rand(&mut rng);
+
+        for encoding in MODES {
+            let mut encoder = Encoder::new(encoding);
+            encoder.point(&g1);
+            encoder.point(&g2);
+            encoder.point(&ark_bn254::G1Affine::zero());
+            encoder.field(&x);
+            encoder.u64(7);
+            encoder.string("sbox");
+            let bytes = encoder.finish();
+
+            let mut decoder = Decoder::new(encoding, &bytes);
+            assert_eq!(decoder.point(), Some(g1));
+            assert_eq!(decoder.point(), Some(g2));
+            assert_eq!(decoder.point(), Some(ark_bn254::G1Affine::zero()));
+            assert_eq!(decoder.field(), Some(x));
+            assert_eq!(decoder.u64(), Some(7));
+            assert_eq!(decoder.string().as_deref(), Some("sbox"));
+            assert_eq!(decoder.finish(), Some(()));
+        }
+
+        // EVM layout: 32-byte big-endian x then y
+        let mut encoder = Encoder::new(Encoding::EVM);
+        encoder.point(&g1);
+        let bytes = encoder.finish();
+        let mut x_be = to_bytes(&g1.x, false);
+        x_be.reverse();
+        assert_eq!(bytes.len(), 64);
+        assert_eq!(bytes[..32], x_be[..]);
+    }
+
+    #[test]
+    fn test_rejects_invalid_points() {
+        use ark_bls12_381::G1Affine;
+
+        let mut rng = ark_std::test_rng();
+        let point = ark_bls12_381::G1Projective::rand(&mut rng).into_affine();
+        let mut encoder = Encoder::new(Encoding::EVM);
+        encoder.point(&point);
+        let mut bytes = encoder.finish();
+
+        bytes[95] ^= 1;
+        assert_eq!(Decoder::new(Encoding::EVM, &bytes).point::<G1Affine>(), None);
+        assert_eq!(Decoder::new(Encoding::EVM, &bytes[..95]).point::<G1Affine>(), None);
+    }
+
+    #[test]
+    fn test_proof_and_key_roundtrip() {
+        use crate::proof::{Proof, ProofEvaluations, VerifyingKey};
+        use ark_bls12_381::{Bls12_381, Fr, G1Projective, G2Projective};
+
+        let mut rng = ark_std::test_rng();
+        let mut g1 = || G1Projective::rand(&mut rng).into_affine();
+        let [a, b, c] = [g1(), g1(), g1()];
+        let proof = Proof::<Bls12_381> {
+            wire_commitments: [a, b, c],
+            lookup_commitments: [b, a],
+            z_commitment: ark_bls12_381::G1Affine::zero(),
+            z_lookup_commitment: c,
+            quotient_commitments: [c, b, a],
+            evaluations: ProofEvaluations {
+                wires: [1u64, 2, 3].map(Fr::from),
+                sigmas: [7u64, 8].map(Fr::from),
+                z_omega: Fr::from(11u64),
+                lookup_selector: Fr::from(17u64),
+                table: [18u64, 19, 20].map(Fr::from),
+                permuted: [21u64, 22].map(Fr::from),
+                z_lookup_omega: Fr::from(24u64),
+                permuted_input_omega: Fr::from(25u64),
+                linearization: Fr::from(26u64),
+            },
+            w_zeta: g1(),
+            w_zeta_omega: g1(),
+        };
+        let g2 = G2Projective::rand(&mut rng).into_affine();
+        let vk = VerifyingKey::<Bls12_381> {
+            domain_size: 8,
+            coset_multipliers: [1u64, 2, 3].map(Fr::from),
+            selector_commitments: [a, b, c, b, a, c],
+            sigma_commitments: [b, c, a],
+            table_commitments: [c, a, b],
+            public_rows: vec![0, 5],
+            custom_gates: vec![crate::custom_gate::CustomGate::quintic_sbox()],
+            custom_selector_commitments: vec![b],
+            opening_key: crate::kgz::KZGVerifierKey { g1: c, g2, g2_s: g2 },
+        };
+
+        assert_eq!(proof.to_bytes_with(Encoding::ARKWORKS), proof.to_bytes());
+        for encoding in MODES {
+            let bytes = proof.to_bytes_with(encoding);
+            assert_eq!(Proof::from_bytes_with(&bytes, encoding).as_ref(), Some(&proof));
+            assert_eq!(Proof::<Bls12_381>::from_bytes_with(&bytes[1..], encoding), None);
+
+            let bytes = vk.to_bytes_with(encoding).unwrap();
+            assert_eq!(VerifyingKey::from_bytes_with(&bytes, encoding).as_ref(), Some(&vk));
+        }
+        let mut unpaired = vk.clone();
+        unpaired.custom_selector_commitments.push(a);
+        assert_eq!(unpaired.to_bytes_with(Encoding::EVM), None);
+    }
+}
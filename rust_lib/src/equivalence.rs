@@ -0,0 +1,311 @@
+//! Proof that the data behind a KZG blob commitment are the leaves under
+//! a Poseidon Merkle root, for moving data between a DA layer that
+//! commits with KZG and a rollup state that commits with a tree.
+//!
+//! The blob is n values committed as the polynomial p with p(ω^i) =
+//! data_i, the `VectorCommitment` of the data. The protocol is public
+//! coin: the challenge z hashes the commitment and the root, a KZG
+//! opening gives y = p(z), and a PLONK proof shows that the leaves under
+//! the root satisfy the barycentric formula
+//!
+//!   y = (z^n − 1)/n · Σ data_i·ω^i/(z − ω^i)
+//!
+//! Two distinct polynomials of degree below n agree at a random z with
+//! probability at most n/|F|, so the leaves are the blob.
+
+#[cfg(feature = "prover")]
+use alloc::vec;
+use alloc::vec::Vec;
+use ark_ff::PrimeField;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+
+use crate::circuit::{Circuit, CircuitError, Variable};
+use crate::constraint_system::Synthesize;
+use crate::fft::{root_of_unity, DomainError};
+use crate::gadgets::merkle::hash_tree;
+use crate::kgz::CommitError;
+use crate::msm::CurvePairing;
+use crate::poseidon::PoseidonParams;
+use crate::proof::Proof;
+use crate::transcript::Transcript;
+#[cfg(feature = "prover")]
+use {
+    crate::constraint_system::ConstraintSystem,
+    crate::gadgets::merkle::tree_root,
+    crate::kgz::KZGParams,
+    crate::prover::{interpolate, KeyError, ProveError, Prover, ProvingKey},
+    ark_std::rand::Rng,
+    ark_std::Zero,
+};
+#[cfg(feature = "verifier")]
+use {
+    ark_ec::AffineRepr,
+    crate::kgz::check_opening,
+    crate::proof::VerifyingKey,
+    crate::verifier::{Verifier, VerifyError},
+};
+
+/// Why an equivalence proof could not be made or did not verify
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EquivalenceError {
+    /// The blob size is not a power of two the field has a subgroup for
+    Domain(DomainError),
+    Commit(CommitError),
+    Circuit(CircuitError),
+    #[cfg(feature = "prover")]
+    Key(KeyError),
+    #[cfg(feature = "prover")]
+    Prove(ProveError),
+    /// The KZG opening does not show y = p(z)
+    InvalidOpening,
+    #[cfg(feature = "verifier")]
+    Proof(VerifyError),
+}
+
+impl core::fmt::Display for EquivalenceError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            EquivalenceError::Domain(e) => write!(f, "blob domain: {}", e),
+            EquivalenceError::Commit(e) => write!(f, "blob commitment: {}", e),
+            EquivalenceError::Circuit(e) => write!(f, "equivalence circuit: {}", e),
+            #[cfg(feature = "prover")]
+            EquivalenceError::Key(e) => write!(f, "equivalence key: {}", e),
+            #[cfg(feature = "prover")]
+            EquivalenceError::Prove(e) => write!(f, "equivalence proof: {}", e),
+            EquivalenceError::InvalidOpening => write!(f, "blob opening does not verify"),
+            #[cfg(feature = "verifier")]
+            EquivalenceError::Proof(e) => write!(f, "equivalence proof: {}", e),
+        }
+    }
+}
+
+/// Both commitments, y = p(z) with its KZG opening, and the PLONK proof
+/// over the public inputs z, root, y
+#[derive(Debug, Clone, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct EquivalenceProof<E: CurvePairing> {
+    pub y: E::ScalarField,
+    pub opening: E::G1Affine,
+    pub proof: Proof<E>,
+}
+
+/// The circuit: `data` hashed into a tree and evaluated at `z`. Public
+/// inputs are z, the root and y, in that order.
+#[derive(Debug, Clone)]
+pub struct EquivalenceCircuit<F: PrimeField> {
+    pub params: PoseidonParams<F>,
+    pub data: Vec<F>,
+    pub z: F,
+}
+
+impl<F: PrimeField> Synthesize<F> for EquivalenceCircuit<F> {
+    fn synthesize(&self, cs: &mut Circuit<F>) {
+        let omega = match blob_root(self.data.len()) {
+            Ok(omega) => omega,
+            Err(_) => return cs.fail(CircuitError::BlobSize(self.data.len())),
+        };
+        let z = Variable::from(&cs.add_public_input(self.z));
+        let leaves: Vec<Variable> = self.data.iter().map(|value| cs.alloc(*value)).collect();
+        let root = hash_tree(cs, &self.params, &leaves);
+        let y = cs.namespace("barycentric", |cs| evaluate(cs, z, &leaves, omega));
+        for output in [root, y] {
+            let public = Variable::from(&cs.add_public_input(cs.value(output)));
+            cs.assert_equal(public, output);
+        }
+    }
+}
+
+/// (z^n − 1)/n · Σ leaves_i·ω^i/(z − ω^i), with each inverse allocated
+/// and checked. A z on the domain leaves an unsatisfiable circuit.
+fn evaluate<F: PrimeField>(cs: &mut Circuit<F>, z: Variable, leaves: &[Variable], omega: F) -> Variable {
+    let one = cs.constant(F::ONE);
+    let mut sum = cs.constant(F::ZERO);
+    let mut point = F::ONE;
+    for leaf in leaves {
+        let shift = cs.constant(-point);
+        let difference = cs.add(z, shift);
+        let inverse = cs.alloc(cs.value(difference).inverse().unwrap_or(F::ZERO));
+        let product = cs.mul(difference, inverse);
+        cs.assert_equal(product, one);
+        let scale = cs.constant(point);
+        let weighted = cs.mul(*leaf, inverse);
+        let term = cs.mul(weighted, scale);
+        sum = cs.add(sum, term);
+        point *= omega;
+    }
+    let mut power = z;
+    for _ in 0..leaves.len().trailing_zeros() {
+        power = cs.mul(power, power);
+    }
+    let minus_one = cs.constant(-F::ONE);
+    let vanishing = cs.add(power, minus_one);
+    let inverse_n = cs.constant(F::from(leaves.len() as u64).inverse().expect("the domain size is below the characteristic"));
+    let scaled = cs.mul(vanishing, inverse_n);
+    cs.mul(scaled, sum)
+}
+
+/// The generator of the order-`size` subgroup the blob lives on
+fn blob_root<F: PrimeField>(size: usize) -> Result<F, DomainError> {
+    if !size.is_power_of_two() {
+        return Err(DomainError::NotPowerOfTwo(size));
+    }
+    root_of_unity(size.trailing_zeros())
+}
+
+/// The evaluation point z, bound to both commitments
+pub fn challenge<E: CurvePairing>(commitment: &E::G1Affine, root: &E::ScalarField) -> E::ScalarField {
+    let mut transcript = Transcript::new(b"plonk-equivalence");
+    transcript.absorb(b"commitment", commitment);
+    transcript.absorb(b"root", root);
+    transcript.squeeze(b"z")
+}
+
+/// Key for blobs of `size` values. `params` must reach
+/// `ProvingKey::srs_degree_for` the rows of the circuit, which synthesizing
+/// an `EquivalenceCircuit` of `size` zeros within `capacity` gates gives.
+#[cfg(feature = "prover")]
+pub fn preprocess<E: CurvePairing>(
+    params: KZGParams<E>,
+    poseidon: &PoseidonParams<E::ScalarField>,
+    size: usize,
+    capacity: usize,
+) -> Result<ProvingKey<E>, EquivalenceError> {
+    let blank = EquivalenceCircuit { params: poseidon.clone(), data: vec![E::ScalarField::zero(); size], z: E::ScalarField::zero() };
+    let (cs, witness) = ConstraintSystem::synthesize(&blank, capacity)?;
+    Ok(ProvingKey::preprocess(params, &cs.assign(&witness)?)?)
+}
+
+/// Blob commitment, root and the proof tying them
+#[cfg(feature = "prover")]
+type Equivalence<E> = (<E as ark_ec::pairing::Pairing>::G1Affine, <E as ark_ec::pairing::Pairing>::ScalarField, EquivalenceProof<E>);
+
+/// Commits to `data` as a blob with the SRS of `pk` and as a tree, and
+/// proves the two hold the same values. Returns the blob commitment, the
+/// root and the proof.
+#[cfg(feature = "prover")]
+pub fn prove<E: CurvePairing, R: Rng>(
+    pk: &ProvingKey<E>,
+    poseidon: &PoseidonParams<E::ScalarField>,
+    data: &[E::ScalarField],
+    rng: &mut R,
+) -> Result<Equivalence<E>, EquivalenceError> {
+    let blob = interpolate(data, blob_root(data.len())?);
+    let commitment = pk.params.commit(&blob)?;
+    let root = tree_root(poseidon, data);
+    let z = challenge::<E>(&commitment, &root);
+    let (opening, y) = pk.params.open(&blob, z)?;
+
+    let circuit = EquivalenceCircuit { params: poseidon.clone(), data: data.to_vec(), z };
+    let (cs, witness) = ConstraintSystem::synthesize(&circuit, pk.vk.domain_size as usize)?;
+    let proof = Prover::prove(&cs.assign(&witness)?, pk, rng)?;
+    Ok((commitment, root, EquivalenceProof { y, opening, proof }))
+}
+
+/// Checks that the blob under `commitment` and the leaves under `root`
+/// are the same values
+#[cfg(feature = "verifier")]
+pub fn verify<E: CurvePairing>(
+    vk: &VerifyingKey<E>,
+    commitment: &E::G1Affine,
+    root: E::ScalarField,
+    proof: &EquivalenceProof<E>,
+) -> Result<(), EquivalenceError> {
+    let z = challenge::<E>(commitment, &root);
+    if !check_opening::<E>(&vk.opening_key.g2, &vk.opening_key.g2_s, commitment, &proof.opening, vk.opening_key.g1.into_group() * proof.y, vk.opening_key.g2.into_group() * z) {
+        return Err(EquivalenceError::InvalidOpening);
+    }
+    Verifier::verify(vk, &[z, root, proof.y], &proof.proof)?;
+    Ok(())
+}
+
+impl From<DomainError> for EquivalenceError {
+    fn from(error: DomainError) -> Self {
+        EquivalenceError::Domain(error)
+    }
+}
+
+impl From<CommitError> for EquivalenceError {
+    fn from(error: CommitError) -> Self {
+        EquivalenceError::Commit(error)
+    }
+}
+
+impl From<CircuitError> for EquivalenceError {
+    fn from(error: CircuitError) -> Self {
+        EquivalenceError::Circuit(error)
+    }
+}
+
+#[cfg(feature = "prover")]
+impl From<KeyError> for EquivalenceError {
+    fn from(error: KeyError) -> Self {
+        EquivalenceError::Key(error)
+    }
+}
+
+#[cfg(feature = "prover")]
+impl From<ProveError> for EquivalenceError {
+    fn from(error: ProveError) -> Self {
+        EquivalenceError::Prove(error)
+    }
+}
+
+#[cfg(feature = "verifier")]
+impl From<VerifyError> for EquivalenceError {
+    fn from(error: VerifyError) -> Self {
+        EquivalenceError::Proof(error)
+    }
+}
+
+#[cfg(all(test, feature = "prover", feature = "verifier"))]
+mod tests {
+    use super::*;
+    use crate::vector_commitment::VectorCommitment;
+    use ark_bls12_381::{Bls12_381, Fr};
+    use ark_ff::Field;
+    use ark_std::Zero;
+
+    #[test]
+    fn test_blob_and_tree_equivalence() {
+        let mut rng = ark_std::test_rng();
+        let poseidon = PoseidonParams::<Fr>::new();
+        let data: Vec<Fr> = [3u64, 1].map(Fr::from).to_vec();
+
+        let blank = EquivalenceCircuit { params: poseidon.clone(), data: vec![Fr::zero(); 2], z: Fr::zero() };
+        let (cs, _) = ConstraintSystem::synthesize(&blank, 1 << 12).unwrap();
+        let srs = KZGParams::<Bls12_381>::setup(ProvingKey::<Bls12_381>::srs_degree_for(cs.rows), &mut rng);
+        let pk = preprocess(srs, &poseidon, 2, 1 << 12).unwrap();
+
+        let (commitment, root, proof) = prove(&pk, &poseidon, &data, &mut rng).unwrap();
+        let blob = VectorCommitment::new(&pk.params, 2).unwrap();
+        assert_eq!(commitment, blob.commit(&data).unwrap());
+        assert_eq!(root, tree_root(&poseidon, &data));
+        assert_eq!(verify(&pk.vk, &commitment, root, &proof), Ok(()));
+
+        // Another tree, or another blob, is not equivalent
+        let other = tree_root(&poseidon, &[3u64, 2].map(Fr::from));
+        assert!(matches!(verify(&pk.vk, &commitment, other, &proof), Err(EquivalenceError::InvalidOpening)));
+        let other = blob.commit(&[2u64, 7].map(Fr::from)).unwrap();
+        assert!(verify(&pk.vk, &other, root, &proof).is_err());
+        let wrong_y = EquivalenceProof { y: proof.y + Fr::ONE, ..proof.clone() };
+        assert_eq!(verify(&pk.vk, &commitment, root, &wrong_y), Err(EquivalenceError::InvalidOpening));
+
+        assert_eq!(prove(&pk, &poseidon, &[Fr::ONE; 3], &mut rng).unwrap_err(), EquivalenceError::Domain(DomainError::NotPowerOfTwo(3)));
+    }
+
+    #[test]
+    fn test_circuit_evaluates_the_blob() {
+        let poseidon = PoseidonParams::<Fr>::new();
+        let data: Vec<Fr> = [5u64, 0, 2, 9, 6, 5, 3, 5].map(Fr::from).to_vec();
+        let z = Fr::from(11u64);
+        let blob = interpolate(&data, blob_root::<Fr>(8).unwrap());
+        let mut cs = Circuit::new(1 << 14);
+        EquivalenceCircuit { params: poseidon.clone(), data: data.clone(), z }.synthesize(&mut cs);
+        assert_eq!(cs.public_input_values(), vec![z, tree_root(&poseidon, &data), ark_poly::Polynomial::evaluate(&blob, &z)]);
+        assert!(cs.verify_rows() && cs.verify_copy_constraints());
+
+        // z on the domain has no inverse to offer
+        let mut on_domain = Circuit::new(1 << 14);
+        EquivalenceCircuit { params: poseidon, data, z: Fr::ONE }.synthesize(&mut on_domain);
+        assert!(!on_domain.verify_rows());
+    }
+}
@@ -0,0 +1,217 @@
+//! Error types of the crate.
+//!
+//! Library code does not panic on anything a caller controls: sizes,
+//! witnesses, keys, proofs and bytes off the wire all come back as one of
+//! the per-module errors below, layered the way the modules are:
+//!
+//! - [`DomainError`]: FFT sizes, evaluation counts and degrees
+//! - [`MsmError`]: bases and scalars of different lengths
+//! - [`CommitError`]: SRS too small, or a domain error underneath
+//! - [`SrsError`]: ceremony files that do not parse or check out
+//! - [`PermutationError`]: cells outside the grid
+//! - [`CircuitError`]: building and padding circuits, recorded on the
+//!   circuit and returned by `synthesize`
+//! - [`ScheduleError`]: a Fiat-Shamir transcript that leaves the proof
+//!   schedule
+//! - [`KeyError`] and [`ProveError`], wrapping commit, schedule and
+//!   pipeline stage errors, and [`SelfTestError`] over both
+//! - [`VerifyError`], [`BatchError`], [`AggregateError`] and
+//!   [`RegistryError`] on the verifier
+//! - [`ArtifactError`] on saving and loading setup artifacts
+//! - [`ContractError`] when generating a Solidity verifier
+//! - [`R1csError`] when importing an R1CS, under `r1cs`, and
+//!   [`AcirError`] when lowering an ACIR program
+//! - [`PolicyError`], [`RemoteError`], [`ThresholdError`],
+//!   [`WitnessDecryptError`] and [`EquivalenceError`]
+//!
+//! [`Error`] wraps all of them for callers that want a single type, and
+//! implements `std::error::Error` under `std`. The
+//! `expect`s left in library code guard invariants that no input can
+//! reach, and each says which. Indexing a `PolyStore` out of bounds
+//! panics like indexing a slice.
+
+pub use crate::acir::AcirError;
+#[cfg(feature = "verifier")]
+pub use crate::aggregation::AggregateError;
+pub use crate::artifact::ArtifactError;
+pub use crate::circuit::CircuitError;
+#[cfg(feature = "verifier")]
+pub use crate::contract_gen::ContractError;
+pub use crate::equivalence::EquivalenceError;
+pub use crate::fft::DomainError;
+pub use crate::kgz::CommitError;
+pub use crate::metadata::PolicyError;
+pub use crate::msm::MsmError;
+pub use crate::permutation::PermutationError;
+pub use crate::proof::ScheduleError;
+#[cfg(feature = "r1cs")]
+pub use crate::r1cs::R1csError;
+#[cfg(feature = "prover")]
+pub use crate::pipeline::StageError;
+#[cfg(feature = "prover")]
+pub use crate::prover::{KeyError, ProveError, SelfTestError};
+#[cfg(feature = "verifier")]
+pub use crate::registry::RegistryError;
+pub use crate::remote::RemoteError;
+pub use crate::srs::SrsError;
+pub use crate::threshold::ThresholdError;
+#[cfg(feature = "verifier")]
+pub use crate::verifier::{BatchError, VerifyError};
+pub use crate::witness_encryption::WitnessDecryptError;
+
+/// Any error the crate returns
+#[derive(Debug)]
+pub enum Error {
+    Domain(DomainError),
+    Msm(MsmError),
+    Commit(CommitError),
+    Srs(SrsError),
+    Permutation(PermutationError),
+    Circuit(CircuitError),
+    Schedule(ScheduleError),
+    #[cfg(feature = "prover")]
+    Stage(StageError),
+    #[cfg(feature = "prover")]
+    Key(KeyError),
+    #[cfg(feature = "prover")]
+    Prove(ProveError),
+    #[cfg(feature = "prover")]
+    SelfTest(SelfTestError),
+    #[cfg(feature = "verifier")]
+    Verify(VerifyError),
+    #[cfg(feature = "verifier")]
+    Batch(BatchError),
+    #[cfg(feature = "verifier")]
+    Aggregate(AggregateError),
+    #[cfg(feature = "verifier")]
+    Registry(RegistryError),
+    Artifact(ArtifactError),
+    #[cfg(feature = "verifier")]
+    Contract(ContractError),
+    #[cfg(feature = "r1cs")]
+    R1cs(R1csError),
+    Acir(AcirError),
+    Policy(PolicyError),
+    Remote(RemoteError),
+    Threshold(ThresholdError),
+    WitnessDecrypt(WitnessDecryptError),
+    Equivalence(EquivalenceError),
+}
+
+impl core::fmt::Display for Error {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Error::Domain(e) => write!(f, "{}", e),
+            Error::Msm(e) => write!(f, "{}", e),
+            Error::Commit(e) => write!(f, "{}", e),
+            Error::Srs(e) => write!(f, "{}", e),
+            Error::Permutation(e) => write!(f, "{}", e),
+            Error::Circuit(e) => write!(f, "{}", e),
+            Error::Schedule(e) => write!(f, "{}", e),
+            #[cfg(feature = "prover")]
+            Error::Stage(e) => write!(f, "{}", e),
+            #[cfg(feature = "prover")]
+            Error::Key(e) => write!(f, "{}", e),
+            #[cfg(feature = "prover")]
+            Error::Prove(e) => write!(f, "{}", e),
+            #[cfg(feature = "prover")]
+            Error::SelfTest(e) => write!(f, "{}", e),
+            #[cfg(feature = "verifier")]
+            Error::Verify(e) => write!(f, "{}", e),
+            #[cfg(feature = "verifier")]
+            Error::Batch(e) => write!(f, "{}", e),
+            #[cfg(feature = "verifier")]
+            Error::Aggregate(e) => write!(f, "{}", e),
+            #[cfg(feature = "verifier")]
+            Error::Registry(e) => write!(f, "{}", e),
+            Error::Artifact(e) => write!(f, "{}", e),
+            #[cfg(feature = "verifier")]
+            Error::Contract(e) => write!(f, "{}", e),
+            #[cfg(feature = "r1cs")]
+            Error::R1cs(e) => write!(f, "{}", e),
+            Error::Acir(e) => write!(f, "{}", e),
+            Error::Policy(e) => write!(f, "{}", e),
+            Error::Remote(e) => write!(f, "{}", e),
+            Error::Threshold(e) => write!(f, "{}", e),
+            Error::WitnessDecrypt(e) => write!(f, "{}", e),
+            Error::Equivalence(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
+macro_rules! from_error {
+    ($($(#[$cfg:meta])* $variant:ident($error:ty)),* $(,)?) => {
+        $(
+            $(#[$cfg])*
+            impl From<$error> for Error {
+                fn from(error: $error) -> Self {
+                    Error::$variant(error)
+                }
+            }
+        )*
+    };
+}
+
+from_error!(
+    Domain(DomainError),
+    Msm(MsmError),
+    Commit(CommitError),
+    Srs(SrsError),
+    Permutation(PermutationError),
+    Circuit(CircuitError),
+    Schedule(ScheduleError),
+    #[cfg(feature = "prover")]
+    Stage(StageError),
+    #[cfg(feature = "prover")]
+    Key(KeyError),
+    #[cfg(feature = "prover")]
+    Prove(ProveError),
+    #[cfg(feature = "prover")]
+    SelfTest(SelfTestError),
+    #[cfg(feature = "verifier")]
+    Verify(VerifyError),
+    #[cfg(feature = "verifier")]
+    Batch(BatchError),
+    #[cfg(feature = "verifier")]
+    Aggregate(AggregateError),
+    #[cfg(feature = "verifier")]
+    Registry(RegistryError),
+    Artifact(ArtifactError),
+    #[cfg(feature = "verifier")]
+    Contract(ContractError),
+    #[cfg(feature = "r1cs")]
+    R1cs(R1csError),
+    Acir(AcirError),
+    Policy(PolicyError),
+    Remote(RemoteError),
+    Threshold(ThresholdError),
+    WitnessDecrypt(WitnessDecryptError),
+    Equivalence(EquivalenceError),
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_errors_convert() {
+        fn commit() -> Result<(), Error> {
+            Err(DomainError::NotPowerOfTwo(3))?
+        }
+        let error = commit().unwrap_err();
+        assert!(matches!(error, Error::Domain(DomainError::NotPowerOfTwo(3))));
+        assert_eq!(error.to_string(), DomainError::NotPowerOfTwo(3).to_string());
+
+        let error = Error::from(CircuitError::Full { capacity: 4 });
+        assert_eq!(error.to_string(), CircuitError::Full { capacity: 4 }.to_string());
+
+        #[cfg(feature = "std")]
+        {
+            let boxed: Box<dyn std::error::Error> = Box::new(error);
+            assert_eq!(boxed.to_string(), CircuitError::Full { capacity: 4 }.to_string());
+        }
+    }
+}
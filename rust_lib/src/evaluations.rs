@@ -0,0 +1,201 @@
+//! Polynomials held by their values on an `EvaluationDomain` or its coset.
+//!
+//! Sums and products are pointwise there, so the prover can build the
+//! quotient numerator out of products of wires, selectors and z without
+//! multiplying coefficient vectors. Each value carries an upper bound on
+//! its degree: a product whose bound reaches the domain size no longer
+//! determines its polynomial, and is refused rather than silently
+//! wrapped around mod X^n − 1.
+
+use alloc::vec::Vec;
+use ark_ff::FftField;
+use ark_poly::polynomial::univariate::DensePolynomial;
+use ark_poly::polynomial::{DenseUVPolynomial, Polynomial};
+
+use crate::backend::{Backend, FieldBackend};
+use crate::fft::{DomainError, EvaluationDomain};
+
+/// Values of a polynomial of degree at most `degree` on H or g·H
+#[derive(Debug, Clone)]
+pub struct Evaluations<'d, F: FftField> {
+    evals: Vec<F>,
+    domain: &'d EvaluationDomain<F>,
+    coset: bool,
+    degree: usize,
+}
+
+impl<'d, F: FftField> Evaluations<'d, F> {
+    /// `poly` evaluated on H
+    pub fn from_poly(poly: &DensePolynomial<F>, domain: &'d EvaluationDomain<F>) -> Result<Self, DomainError> {
+        Self::evaluate(poly, domain, false)
+    }
+
+    /// `poly` evaluated on the coset g·H, where Z_H has no zeros
+    pub fn from_poly_on_coset(poly: &DensePolynomial<F>, domain: &'d EvaluationDomain<F>) -> Result<Self, DomainError> {
+        Self::evaluate(poly, domain, true)
+    }
+
+    /// Values already computed on H, of a polynomial the caller knows to
+    /// have degree at most `degree`
+    pub fn from_vec(evals: Vec<F>, domain: &'d EvaluationDomain<F>, degree: usize) -> Result<Self, DomainError> {
+        if evals.len() != domain.size {
+            return Err(DomainError::LengthMismatch { evals: evals.len(), domain: domain.size });
+        }
+        check_degree(degree, domain)?;
+        Ok(Self { evals, domain, coset: false, degree })
+    }
+
+    fn evaluate(poly: &DensePolynomial<F>, domain: &'d EvaluationDomain<F>, coset: bool) -> Result<Self, DomainError> {
+        let degree = poly.degree();
+        check_degree(degree, domain)?;
+        let mut evals = poly.coeffs.clone();
+        evals.resize(domain.size, F::zero());
+        if coset {
+            domain.coset_fft(&mut evals)?;
+        } else {
+            domain.fft(&mut evals)?;
+        }
+        Ok(Self { evals, domain, coset, degree })
+    }
+
+    /// The polynomial these values determine
+    pub fn interpolate(self) -> DensePolynomial<F> {
+        let mut coeffs = self.evals;
+        if self.coset {
+            self.domain.coset_ifft(&mut coeffs)
+        } else {
+            self.domain.ifft(&mut coeffs)
+        }
+        .expect("the values were checked against the domain");
+        coeffs.truncate(self.degree + 1);
+        DensePolynomial::from_coefficients_vec(coeffs)
+    }
+
+    pub fn evals(&self) -> &[F] {
+        &self.evals
+    }
+
+    pub fn domain(&self) -> &'d EvaluationDomain<F> {
+        self.domain
+    }
+
+    /// Whether the values are on g·H rather than H
+    pub fn is_coset(&self) -> bool {
+        self.coset
+    }
+
+    /// Upper bound on the degree, from the inputs and the operations
+    /// applied since
+    pub fn degree(&self) -> usize {
+        self.degree
+    }
+
+    pub fn add(&self, other: &Self) -> Result<Self, DomainError> {
+        self.zip(other, self.degree.max(other.degree), |a, b| {
+            for (x, y) in a.iter_mut().zip(b) {
+                *x += y;
+            }
+        })
+    }
+
+    pub fn sub(&self, other: &Self) -> Result<Self, DomainError> {
+        self.zip(other, self.degree.max(other.degree), |a, b| {
+            for (x, y) in a.iter_mut().zip(b) {
+                *x -= y;
+            }
+        })
+    }
+
+    /// The product, refused once its degree no longer fits the domain
+    pub fn mul(&self, other: &Self) -> Result<Self, DomainError> {
+        self.zip(other, self.degree + other.degree, Backend::mul_slices)
+    }
+
+    /// Multiplies every value by `s`, in place
+    pub fn scale(&mut self, s: F) {
+        Backend::scale_slice(&mut self.evals, s);
+    }
+
+    /// `op` applied to a copy of these values and `other`'s, in place
+    fn zip(&self, other: &Self, degree: usize, op: impl Fn(&mut [F], &[F])) -> Result<Self, DomainError> {
+        let same_domain = core::ptr::eq(self.domain, other.domain) || self.domain.omega == other.domain.omega;
+        if !same_domain || self.coset != other.coset {
+            return Err(DomainError::DomainMismatch);
+        }
+        check_degree(degree, self.domain)?;
+        let mut evals = self.evals.clone();
+        op(&mut evals, &other.evals);
+        Ok(Self { evals, domain: self.domain, coset: self.coset, degree })
+    }
+}
+
+fn check_degree<F: FftField>(degree: usize, domain: &EvaluationDomain<F>) -> Result<(), DomainError> {
+    if degree >= domain.size {
+        return Err(DomainError::DegreeTooLarge { degree, domain: domain.size });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bls12_381::Fr;
+    use ark_std::{One, UniformRand};
+
+    use crate::naive;
+
+    #[test]
+    fn test_pointwise_arithmetic_matches_coefficients() {
+        let mut rng = ark_std::test_rng();
+        let domain = EvaluationDomain::<Fr>::new_for_size(16).unwrap();
+        let a = DensePolynomial::<Fr>::rand(5, &mut rng);
+        let b = DensePolynomial::<Fr>::rand(7, &mut rng);
+        let s = Fr::rand(&mut rng);
+
+        for on_coset in [false, true] {
+            let eval = |poly| if on_coset { Evaluations::from_poly_on_coset(poly, &domain) } else { Evaluations::from_poly(poly, &domain) }.unwrap();
+            let (a_evals, b_evals) = (eval(&a), eval(&b));
+            assert_eq!(a_evals.is_coset(), on_coset);
+
+            let product = a_evals.mul(&b_evals).unwrap();
+            assert_eq!(product.degree(), 12);
+            assert_eq!(product.interpolate().coeffs, naive::multiply(&a.coeffs, &b.coeffs));
+
+            let sum = a_evals.add(&b_evals).unwrap();
+            assert_eq!(sum.degree(), 7);
+            assert_eq!(sum.interpolate(), &a + &b);
+            assert_eq!(a_evals.sub(&b_evals).unwrap().interpolate(), &a - &b);
+
+            let mut scaled = b_evals.clone();
+            scaled.scale(s);
+            assert_eq!(scaled.interpolate(), &b * s);
+        }
+    }
+
+    #[test]
+    fn test_degree_and_domain_are_checked() {
+        let mut rng = ark_std::test_rng();
+        let domain = EvaluationDomain::<Fr>::new_for_size(8).unwrap();
+        let a = Evaluations::from_poly(&DensePolynomial::rand(4, &mut rng), &domain).unwrap();
+        assert_eq!(a.mul(&a).unwrap_err(), DomainError::DegreeTooLarge { degree: 8, domain: 8 });
+        assert_eq!(
+            Evaluations::from_poly(&DensePolynomial::rand(8, &mut rng), &domain).unwrap_err(),
+            DomainError::DegreeTooLarge { degree: 8, domain: 8 }
+        );
+        assert_eq!(
+            Evaluations::from_vec(vec![Fr::one(); 4], &domain, 0).unwrap_err(),
+            DomainError::LengthMismatch { evals: 4, domain: 8 }
+        );
+
+        let on_coset = Evaluations::from_poly_on_coset(&DensePolynomial::rand(2, &mut rng), &domain).unwrap();
+        assert_eq!(a.add(&on_coset).unwrap_err(), DomainError::DomainMismatch);
+        let larger = EvaluationDomain::<Fr>::new_for_size(16).unwrap();
+        let b = Evaluations::from_poly(&DensePolynomial::rand(2, &mut rng), &larger).unwrap();
+        assert_eq!(a.add(&b).unwrap_err(), DomainError::DomainMismatch);
+
+        // A copy of the same domain is the same domain
+        let copy = domain.clone();
+        let c = Evaluations::from_vec(vec![Fr::one(); 8], &copy, 0).unwrap();
+        assert_eq!(a.add(&c).unwrap().interpolate().coeffs[0], a.clone().interpolate().coeffs[0] + Fr::one());
+    }
+}
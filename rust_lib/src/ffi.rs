@@ -0,0 +1,217 @@
+//! C ABI over BN254, for embedding the prover and verifier in Go, Swift
+//! or C++ services; `include/plonk.h` declares it.
+//!
+//! Keys, witnesses and proofs cross as their `Artifact` bytes, statements
+//! as the canonical compressed `Vec<Fr>`, as in the wasm bindings. A
+//! service decodes its proving key once with `plonk_setup_load` and proves
+//! against the handle. Every function returns a `PlonkStatus`; a panic is
+//! caught and reported rather than unwound into the caller. Build the
+//! library with `cargo rustc --release --features ffi --crate-type
+//! cdylib` (or `staticlib`).
+
+use ark_serialize::CanonicalDeserialize;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+use crate::artifact::Artifact;
+use crate::bn254::{Fr, Proof, ProvingKey, VerifyingKey};
+use crate::constraint_system::Witness;
+use crate::prover::Prover;
+use crate::verifier::Verifier;
+
+/// Outcome of a call; anything but `Ok` leaves the outputs untouched
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlonkStatus {
+    Ok = 0,
+    /// A required pointer was null
+    NullPointer = 1,
+    /// Key, witness, statement or proof bytes that do not decode
+    Malformed = 2,
+    /// The witness does not fit the key
+    ProveFailed = 3,
+    /// The proof is not valid for the key and statement
+    InvalidProof = 4,
+    /// A bug in the library
+    Panic = 5,
+}
+
+/// A decoded proving key, owned by the caller until `plonk_setup_free`
+pub struct PlonkSetup(ProvingKey);
+
+/// Bytes allocated by the library, released with `plonk_buffer_free`
+#[repr(C)]
+#[derive(Debug)]
+pub struct PlonkBuffer {
+    pub data: *mut u8,
+    pub len: usize,
+}
+
+impl PlonkBuffer {
+    fn new(bytes: Vec<u8>) -> Self {
+        let len = bytes.len();
+        Self { data: Box::into_raw(bytes.into_boxed_slice()).cast(), len }
+    }
+}
+
+/// `len` bytes at `data`; null is read as empty when `len` is zero
+unsafe fn bytes<'a>(data: *const u8, len: usize) -> Result<&'a [u8], PlonkStatus> {
+    match (data.is_null(), len) {
+        (true, 0) => Ok(&[]),
+        (true, _) => Err(PlonkStatus::NullPointer),
+        (false, _) => Ok(std::slice::from_raw_parts(data, len)),
+    }
+}
+
+fn guard(body: impl FnOnce() -> Result<(), PlonkStatus>) -> PlonkStatus {
+    match catch_unwind(AssertUnwindSafe(body)) {
+        Ok(Ok(())) => PlonkStatus::Ok,
+        Ok(Err(status)) => status,
+        Err(_) => PlonkStatus::Panic,
+    }
+}
+
+/// Decodes the proving key artifact in `key` into `*setup`.
+///
+/// # Safety
+///
+/// `key` must point to `key_len` readable bytes and `setup` to writable
+/// memory for a pointer.
+#[no_mangle]
+pub unsafe extern "C" fn plonk_setup_load(key: *const u8, key_len: usize, setup: *mut *mut PlonkSetup) -> PlonkStatus {
+    guard(|| {
+        let key = bytes(key, key_len)?;
+        if setup.is_null() {
+            return Err(PlonkStatus::NullPointer);
+        }
+        let pk = ProvingKey::decode_artifact(key).map_err(|_| PlonkStatus::Malformed)?;
+        *setup = Box::into_raw(Box::new(PlonkSetup(pk)));
+        Ok(())
+    })
+}
+
+/// # Safety
+///
+/// `setup` must be null or come from `plonk_setup_load`, and not be used
+/// afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn plonk_setup_free(setup: *mut PlonkSetup) {
+    if !setup.is_null() {
+        drop(Box::from_raw(setup));
+    }
+}
+
+/// Proves the witness artifact in `witness` against `setup` and writes
+/// the proof artifact to `*proof`.
+///
+/// # Safety
+///
+/// `setup` must come from `plonk_setup_load`, `witness` must point to
+/// `witness_len` readable bytes and `proof` to a writable `PlonkBuffer`.
+#[no_mangle]
+pub unsafe extern "C" fn plonk_prove(
+    setup: *const PlonkSetup,
+    witness: *const u8,
+    witness_len: usize,
+    proof: *mut PlonkBuffer,
+) -> PlonkStatus {
+    guard(|| {
+        let witness = bytes(witness, witness_len)?;
+        let (Some(setup), false) = (setup.as_ref(), proof.is_null()) else {
+            return Err(PlonkStatus::NullPointer);
+        };
+        let witness = Witness::<Fr>::decode_artifact(witness).map_err(|_| PlonkStatus::Malformed)?;
+        let made: Proof = Prover::prove_witness(&witness, &setup.0, &mut ark_std::rand::thread_rng()).map_err(|_| PlonkStatus::ProveFailed)?;
+        *proof = PlonkBuffer::new(made.encode_artifact());
+        Ok(())
+    })
+}
+
+/// Checks `proof` against `verifying_key` and `public_inputs`: `Ok` for a
+/// valid proof, `InvalidProof` for one that decodes but does not verify.
+///
+/// # Safety
+///
+/// Each pointer must point to as many readable bytes as its length says.
+#[no_mangle]
+pub unsafe extern "C" fn plonk_verify(
+    verifying_key: *const u8,
+    verifying_key_len: usize,
+    public_inputs: *const u8,
+    public_inputs_len: usize,
+    proof: *const u8,
+    proof_len: usize,
+) -> PlonkStatus {
+    guard(|| {
+        let (vk, public_inputs, proof) =
+            (bytes(verifying_key, verifying_key_len)?, bytes(public_inputs, public_inputs_len)?, bytes(proof, proof_len)?);
+        let vk = VerifyingKey::decode_artifact(vk).map_err(|_| PlonkStatus::Malformed)?;
+        let public_inputs = Vec::<Fr>::deserialize_compressed(public_inputs).map_err(|_| PlonkStatus::Malformed)?;
+        let proof = Proof::decode_artifact(proof).map_err(|_| PlonkStatus::Malformed)?;
+        Verifier::verify(&vk, &public_inputs, &proof).map_err(|_| PlonkStatus::InvalidProof)
+    })
+}
+
+/// # Safety
+///
+/// `buffer` must have been filled by this library and not freed before.
+#[no_mangle]
+pub unsafe extern "C" fn plonk_buffer_free(buffer: PlonkBuffer) {
+    if !buffer.data.is_null() {
+        drop(Box::from_raw(std::ptr::slice_from_raw_parts_mut(buffer.data, buffer.len)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_serialize::CanonicalSerialize;
+
+    use crate::bn254::Params;
+    use crate::circuit::{Circuit, Variable};
+    use crate::constraint_system::{ConstraintSystem, Synthesize};
+
+    /// x·y = 21, with 21 public
+    struct Product;
+
+    impl Synthesize<Fr> for Product {
+        fn synthesize(&self, circuit: &mut Circuit<Fr>) {
+            let [x, y] = [3u64, 7].map(|value| circuit.alloc(Fr::from(value)));
+            let product = circuit.mul(x, y);
+            let public = Variable::from(&circuit.add_public_input(Fr::from(21u64)));
+            circuit.assert_equal(product, public);
+        }
+    }
+
+    #[test]
+    fn test_ffi_round_trip() {
+        let (cs, witness) = ConstraintSystem::synthesize(&Product, 4).unwrap();
+        let pk = ProvingKey::preprocess(Params::setup(12, &mut ark_std::test_rng()), &cs.assign(&witness).unwrap()).unwrap();
+        let (pk_bytes, vk_bytes, witness_bytes) = (pk.encode_artifact(), pk.vk.encode_artifact(), witness.encode_artifact());
+        let statement = |value: u64| {
+            let mut bytes = Vec::new();
+            vec![Fr::from(value)].serialize_compressed(&mut bytes).unwrap();
+            bytes
+        };
+
+        unsafe {
+            let mut setup = std::ptr::null_mut();
+            assert_eq!(plonk_setup_load(pk_bytes.as_ptr(), pk_bytes.len(), &mut setup), PlonkStatus::Ok);
+            let mut proof = PlonkBuffer { data: std::ptr::null_mut(), len: 0 };
+            assert_eq!(plonk_prove(setup, witness_bytes.as_ptr(), witness_bytes.len(), &mut proof), PlonkStatus::Ok);
+            let verify = |statement: &[u8], proof: &PlonkBuffer| {
+                plonk_verify(vk_bytes.as_ptr(), vk_bytes.len(), statement.as_ptr(), statement.len(), proof.data, proof.len)
+            };
+            assert_eq!(verify(&statement(21), &proof), PlonkStatus::Ok);
+            assert_eq!(verify(&statement(22), &proof), PlonkStatus::InvalidProof);
+            assert_eq!(verify(&[1, 2], &proof), PlonkStatus::Malformed);
+
+            // A verifying key where a witness belongs, and missing outputs
+            assert_eq!(plonk_prove(setup, vk_bytes.as_ptr(), vk_bytes.len(), &mut proof), PlonkStatus::Malformed);
+            assert_eq!(plonk_prove(setup, witness_bytes.as_ptr(), witness_bytes.len(), std::ptr::null_mut()), PlonkStatus::NullPointer);
+            assert_eq!(plonk_setup_load(vk_bytes.as_ptr(), vk_bytes.len(), &mut setup), PlonkStatus::Malformed);
+            assert_eq!(plonk_setup_load(std::ptr::null(), 1, &mut setup), PlonkStatus::NullPointer);
+            plonk_buffer_free(proof);
+            plonk_setup_free(setup);
+        }
+    }
+}
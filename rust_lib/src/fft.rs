@@ -1,33 +1,154 @@
-use ark_ff::Field;
-use ark_std::{Zero, One};
-use ark_bls12_381::Fr as ScalarField;
-
-use ark_poly::polynomial::{Polynomial, DenseUVPolynomial};
+use alloc::vec::Vec;
+use ark_ff::{FftField, Field};
+use ark_poly::polynomial::DenseUVPolynomial;
 use ark_poly::polynomial::univariate::DensePolynomial;
 
+use crate::backend::{Backend, FieldBackend};
+
+/// Inputs no transform can be taken over
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DomainError {
+    /// The radix-2 transforms need a power-of-two length
+    NotPowerOfTwo(usize),
+    /// `interpolate` got a different number of evaluations and points
+    LengthMismatch { evals: usize, domain: usize },
+    /// The field has no multiplicative subgroup of this order
+    NoSubgroup(usize),
+    /// A polynomial of this degree does not fit in the domain's points
+    DegreeTooLarge { degree: usize, domain: usize },
+    /// Pointwise arithmetic on values over different domains or cosets
+    DomainMismatch,
+}
+
+impl core::fmt::Display for DomainError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            DomainError::NotPowerOfTwo(n) => write!(f, "length {} is not a power of two", n),
+            DomainError::LengthMismatch { evals, domain } => {
+                write!(f, "{} evaluations over a domain of {} points", evals, domain)
+            }
+            DomainError::NoSubgroup(n) => write!(f, "field has no subgroup of order {}", n),
+            DomainError::DegreeTooLarge { degree, domain } => {
+                write!(f, "degree {} polynomial over a domain of {} points", degree, domain)
+            }
+            DomainError::DomainMismatch => write!(f, "evaluations over different domains"),
+        }
+    }
+}
 
 #[derive(Debug, Clone)]
 pub struct EvaluationDomain<F: Field> {
     pub size: usize, // size of the domain
     pub omega: F,// genrator
     pub omega_inv: F, // inverse of the generator
+    /// 1 / size, the scale `ifft` ends with
+    pub size_inv: F,
+    /// ω^i for i < size / 2, every twiddle factor a transform reads
+    twiddles: Vec<F>,
+    /// ω^−i for i < size / 2
+    inv_twiddles: Vec<F>,
 }
 
 impl<F: Field> EvaluationDomain<F> {
-    pub fn new(size: usize, omega: F) -> Self {
-        let omega_inv = omega.inverse().unwrap();
-        Self {
+    pub fn new(size: usize, omega: F) -> Result<Self, DomainError> {
+        let omega_inv = omega.inverse().ok_or(DomainError::NoSubgroup(size))?;
+        // size is zero in the field only in characteristic 2, which has
+        // no subgroup of even order
+        let size_inv = F::from(size as u64).inverse().ok_or(DomainError::NoSubgroup(size))?;
+        Ok(Self {
             size,
             omega,
             omega_inv,
+            size_inv,
+            twiddles: twiddles(omega, size),
+            inv_twiddles: twiddles(omega_inv, size),
+        })
+    }
+
+    /// Evaluations of `coeffs` on the domain, in place, over the cached
+    /// twiddles
+    pub fn fft(&self, coeffs: &mut [F]) -> Result<(), DomainError> {
+        self.check_len(coeffs.len())?;
+        transform(coeffs, &self.twiddles)
+    }
+
+    /// Coefficients of the polynomial taking `evals` on the domain, in
+    /// place, over the cached inverse twiddles
+    pub fn ifft(&self, evals: &mut [F]) -> Result<(), DomainError> {
+        self.check_len(evals.len())?;
+        transform(evals, &self.inv_twiddles)?;
+        Backend::scale_slice(evals, self.size_inv);
+        Ok(())
+    }
+
+    fn check_len(&self, len: usize) -> Result<(), DomainError> {
+        if len != self.size {
+            return Err(DomainError::LengthMismatch { evals: len, domain: self.size });
         }
+        Ok(())
     }
 }
 
+impl<F: FftField> EvaluationDomain<F> {
+    /// The smallest power-of-two domain holding `n` points, over the root
+    /// `root_of_unity` picks for it. Fails when the field has no subgroup
+    /// that large.
+    pub fn new_for_size(n: usize) -> Result<Self, DomainError> {
+        let size = n.checked_next_power_of_two().ok_or(DomainError::NoSubgroup(n))?;
+        Self::new(size, root_of_unity(size.trailing_zeros())?)
+    }
+
+    /// g of the coset g·H the quotient is evaluated on, the generator of
+    /// the multiplicative group and so outside every subgroup H
+    pub fn coset_generator(&self) -> F {
+        F::GENERATOR
+    }
+
+    /// Evaluations of `coeffs` on g·H, in place
+    pub fn coset_fft(&self, coeffs: &mut [F]) -> Result<(), DomainError> {
+        self.check_len(coeffs.len())?;
+        scale_powers(coeffs, self.coset_generator());
+        self.fft(coeffs)
+    }
+
+    /// Coefficients of the polynomial taking `evals` on g·H, in place
+    pub fn coset_ifft(&self, evals: &mut [F]) -> Result<(), DomainError> {
+        self.ifft(evals)?;
+        let g_inv = self.coset_generator().inverse().expect("the generator of the multiplicative group is nonzero");
+        scale_powers(evals, g_inv);
+        Ok(())
+    }
+}
+
+/// The primitive 2^`log_size`-th root of unity, `F::TWO_ADIC_ROOT_OF_UNITY`
+/// squared down to that order. Every transform of size 2^`log_size` in
+/// the crate runs over its powers.
+pub fn root_of_unity<F: FftField>(log_size: u32) -> Result<F, DomainError> {
+    let size = 1usize.checked_shl(log_size).filter(|_| log_size < 64).unwrap_or(usize::MAX);
+    if log_size > F::TWO_ADICITY {
+        return Err(DomainError::NoSubgroup(size));
+    }
+    F::get_root_of_unity(1u64 << log_size).ok_or(DomainError::NoSubgroup(size))
+}
+
 /// FFT usingCooley-Tukey algorithm
-pub fn fft<F: Field>(poly_coeffs: &mut [F], omega: F) {
+pub fn fft<F: Field>(poly_coeffs: &mut [F], omega: F) -> Result<(), DomainError> {
+    transform(poly_coeffs, &twiddles(omega, poly_coeffs.len()))
+}
+
+/// ω^i for i < n / 2: stage m of a size-n transform reads every
+/// (n / m)-th of them
+fn twiddles<F: Field>(omega: F, n: usize) -> Vec<F> {
+    core::iter::successors(Some(F::one()), |w| Some(*w * omega)).take(n / 2).collect()
+}
+
+/// Bit-reversal followed by the butterflies, over `twiddles` of the
+/// transform's length
+fn transform<F: Field>(poly_coeffs: &mut [F], twiddles: &[F]) -> Result<(), DomainError> {
     let n = poly_coeffs.len();
-    assert!(n.is_power_of_two(), "Length must be a power of 2");
+    if !n.is_power_of_two() {
+        return Err(DomainError::NotPowerOfTwo(n));
+    }
 
     for i in 0..n {
         let j = reverse_bits(i, n.trailing_zeros() as usize);
@@ -36,45 +157,148 @@ pub fn fft<F: Field>(poly_coeffs: &mut [F], omega: F) {
         }
     }
 
+    Backend::butterflies(poly_coeffs, twiddles);
+    Ok(())
+}
+
+/// The butterflies of `transform` with the field multiplication `mul`,
+/// across threads once the transform is large enough
+pub(crate) fn butterflies_with<F: Field>(poly_coeffs: &mut [F], twiddles: &[F], mul: impl Fn(F, F) -> F + Sync) {
+    #[cfg(feature = "parallel")]
+    if poly_coeffs.len() >= PARALLEL_MIN_SIZE {
+        parallel_butterflies(poly_coeffs, twiddles, mul);
+        return;
+    }
+    butterflies(poly_coeffs, twiddles, mul);
+}
+
+/// The log2(n) stages of butterflies on bit-reversed input
+fn butterflies<F: Field>(poly_coeffs: &mut [F], twiddles: &[F], mul: impl Fn(F, F) -> F) {
+    let n = poly_coeffs.len();
     let mut m = 1;
     while m < n {
         let half_m = m;
         m *= 2;
-        let w_m = omega.pow(&[(n / m) as u64]);
+        let stride = n / m;
         
         for k in (0..n).step_by(m) {
-            let mut w = F::one();
             for j in 0..half_m {
-                let t = w * poly_coeffs[k + j + half_m];
+                let t = mul(twiddles[j * stride], poly_coeffs[k + j + half_m]);
                 poly_coeffs[k + j + half_m] = poly_coeffs[k + j] - t;
-                poly_coeffs[k + j] = poly_coeffs[k + j] + t;
-                w *= w_m;
+                poly_coeffs[k + j] += t;
             }
         }
     }
 }
 
-pub fn ifft<F: Field>(evals: &mut [F], omega_inv: F) {
+/// Smallest transform split across threads; below it the tasks cost
+/// more than the butterflies they run
+#[cfg(feature = "parallel")]
+pub const PARALLEL_MIN_SIZE: usize = 1 << 12;
+
+/// `butterflies` on the rayon pool. Early stages have many small
+/// blocks, which go to threads whole; late stages have a few large ones,
+/// whose butterflies are split instead. The arithmetic is that of the
+/// serial loop, so the output is identical.
+#[cfg(feature = "parallel")]
+fn parallel_butterflies<F: Field>(a: &mut [F], twiddles: &[F], mul: impl Fn(F, F) -> F + Sync) {
+    use rayon::prelude::*;
+
+    let n = a.len();
+    let mut m = 1;
+    while m < n {
+        let half_m = m;
+        m *= 2;
+        let stride = n / m;
+        let butterfly = |((lo, hi), w): ((&mut F, &mut F), &F)| {
+            let t = mul(*w, *hi);
+            *hi = *lo - t;
+            *lo += t;
+        };
+        a.par_chunks_mut(m).for_each(|block| {
+            let (lo, hi) = block.split_at_mut(half_m);
+            if half_m >= PARALLEL_MIN_SIZE {
+                lo.par_iter_mut().zip(hi.par_iter_mut()).zip(twiddles.par_iter().step_by(stride)).for_each(butterfly);
+            } else {
+                lo.iter_mut().zip(hi.iter_mut()).zip(twiddles.iter().step_by(stride)).for_each(butterfly);
+            }
+        });
+    }
+}
+
+pub fn ifft<F: Field>(evals: &mut [F], omega_inv: F) -> Result<(), DomainError> {
     let n = evals.len();
-    fft(evals, omega_inv);
+    fft(evals, omega_inv)?;
+
+    // n is zero in the field only in characteristic 2, which has no
+    // subgroup of even order
+    let n_inv = F::from(n as u64).inverse().ok_or(DomainError::NoSubgroup(n))?;
+    Backend::scale_slice(evals, n_inv);
+    Ok(())
+}
+
+/// `fft` over the coset g·⟨omega⟩: p(X) evaluated on g·ω^i is p(g·X)
+/// on ω^i, so coefficient i is scaled by g^i first
+pub fn coset_fft<F: Field>(coeffs: &mut [F], omega: F, g: F) -> Result<(), DomainError> {
+    scale_powers(coeffs, g);
+    fft(coeffs, omega)
+}
 
-    let n_inv = F::from(n as u64).inverse().unwrap();
-    evals.iter_mut().for_each(|eval| *eval *= n_inv);
+/// Inverse of `coset_fft`, taking ω⁻¹ and g⁻¹
+pub fn coset_ifft<F: Field>(evals: &mut [F], omega_inv: F, g_inv: F) -> Result<(), DomainError> {
+    ifft(evals, omega_inv)?;
+    scale_powers(evals, g_inv);
+    Ok(())
 }
 
-pub fn interpolate<F: Field>(evals: &[F], domain: &[F]) -> DensePolynomial<F> {
-    assert_eq!(evals.len(), domain.len(), "Evaluation and domain size mismatch");
+/// a[i] *= g^i
+fn scale_powers<F: Field>(a: &mut [F], g: F) {
+    let powers: Vec<F> = core::iter::successors(Some(F::one()), |power| Some(*power * g)).take(a.len()).collect();
+    Backend::mul_slices(a, &powers);
+}
+
+pub fn interpolate<F: Field>(evals: &[F], domain: &[F]) -> Result<DensePolynomial<F>, DomainError> {
+    if evals.len() != domain.len() {
+        return Err(DomainError::LengthMismatch { evals: evals.len(), domain: domain.len() });
+    }
     let n = evals.len();
     
     let mut coeffs = evals.to_vec();
-    let omega_inv = domain[1].pow(&[n as u64 - 1]);
+    if n == 1 {
+        // A single point: the constant polynomial, and no domain[1] to
+        // derive omega from
+        return Ok(DensePolynomial::from_coefficients_vec(coeffs));
+    }
+    let omega_inv = domain.get(1).ok_or(DomainError::NotPowerOfTwo(n))?.pow([n as u64 - 1]);
 
-    ifft(&mut coeffs, omega_inv);
+    ifft(&mut coeffs, omega_inv)?;
     
-    DensePolynomial::from_coefficients_vec(coeffs)
+    Ok(DensePolynomial::from_coefficients_vec(coeffs))
+}
+
+/// Product of two polynomials by evaluating both on a large enough
+/// power-of-two domain and interpolating the pointwise product
+pub fn multiply<F: FftField>(a: &DensePolynomial<F>, b: &DensePolynomial<F>) -> Result<DensePolynomial<F>, DomainError> {
+    if a.coeffs.is_empty() || b.coeffs.is_empty() {
+        return Ok(DensePolynomial::from_coefficients_vec(Vec::new()));
+    }
+    let n = (a.coeffs.len() + b.coeffs.len() - 1).next_power_of_two();
+    let omega = F::get_root_of_unity(n as u64).ok_or(DomainError::NoSubgroup(n))?;
+
+    let mut a_evals = a.coeffs.clone();
+    let mut b_evals = b.coeffs.clone();
+    a_evals.resize(n, F::zero());
+    b_evals.resize(n, F::zero());
+    fft(&mut a_evals, omega)?;
+    fft(&mut b_evals, omega)?;
+
+    Backend::mul_slices(&mut a_evals, &b_evals);
+    // ω⁻¹ = ω^(n−1) for ω of order n
+    ifft(&mut a_evals, omega.pow([n as u64 - 1]))?;
+    Ok(DensePolynomial::from_coefficients_vec(a_evals))
 }
 
-fn reverse_bits(mut num: usize, bits: usize) -> usize {
+fn reverse_bits(num: usize, bits: usize) -> usize {
     let mut result = 0;
     for i in 0..bits {
         if num & (1 << i) != 0 {
@@ -87,31 +311,259 @@ fn reverse_bits(mut num: usize, bits: usize) -> usize {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::naive;
+    use ark_poly::Polynomial;
+    use ark_ff::{BigInteger, PrimeField};
+    use ark_std::{One, UniformRand, Zero};
+    use ark_bls12_381::Fr as ScalarField;
 
-    #[test]
-    fn test_fft_ifft() {
+    fn check_fft_ifft<F: FftField>() {
         let mut coeffs = vec![
-            ScalarField::one(),
-            ScalarField::one(),
-            ScalarField::zero(),
-            ScalarField::zero(),
+            F::one(),
+            F::one(),
+            F::zero(),
+            F::zero(),
         ];
         
-        let omega = ScalarField::from(5u64).pow(&[
-            0xc19139cb84c680a6u64,
-            0x26fe7e3811dead04u64,
-            0x154e9c24a5f559c7u64,
-            0x8495b4e4c316u64,
-        ]);
+        let domain = EvaluationDomain::<F>::new_for_size(coeffs.len()).unwrap();
         
         let original_coeffs = coeffs.clone();
 
-        fft(&mut coeffs, omega);
+        fft(&mut coeffs, domain.omega).unwrap();
         
-        ifft(&mut coeffs, omega.inverse().unwrap());
+        ifft(&mut coeffs, domain.omega_inv).unwrap();
         
         for (a, b) in coeffs.iter().zip(original_coeffs.iter()) {
             assert_eq!(a, b);
         }
     }
+
+    #[test]
+    fn test_fft_ifft() {
+        check_fft_ifft::<ScalarField>();
+        check_fft_ifft::<ark_bn254::Fr>();
+    }
+
+    #[test]
+    fn test_bad_lengths_are_errors() {
+        let mut odd = vec![ScalarField::one(); 3];
+        assert_eq!(fft(&mut odd, ScalarField::one()), Err(DomainError::NotPowerOfTwo(3)));
+        assert_eq!(ifft(&mut [], ScalarField::one()), Err(DomainError::NotPowerOfTwo(0)));
+        assert_eq!(
+            interpolate(&odd, &domain(4)).map(|poly| poly.coeffs),
+            Err(DomainError::LengthMismatch { evals: 3, domain: 4 })
+        );
+        assert!(EvaluationDomain::new(4, ScalarField::zero()).is_err());
+    }
+
+    /// A scalar field's two-adicity, multiplicative generator g and
+    /// largest root of unity g^((p − 1) / 2^s), big-endian hex
+    struct RootVector {
+        two_adicity: u32,
+        generator: u64,
+        root: &'static str,
+    }
+
+    const BLS12_381: RootVector = RootVector {
+        two_adicity: 32,
+        generator: 7,
+        root: "16a2a19edfe81f20d09b681922c813b4b63683508c2280b93829971f439f0d2b",
+    };
+    const BN254: RootVector = RootVector {
+        two_adicity: 28,
+        generator: 5,
+        root: "2a3c09f0a58a7e8500e0a7eb8ef62abc402d111e41112ed49bd61b6e725b19f0",
+    };
+    #[cfg(feature = "bw6")]
+    const BLS12_377: RootVector = RootVector {
+        two_adicity: 47,
+        generator: 22,
+        root: "11d4b7f60cb92cc160c69477d1a8a12f9b506ee363e3f04a476ef4a4ec2a895e",
+    };
+    #[cfg(feature = "bw6")]
+    const BW6_761: RootVector = RootVector {
+        two_adicity: 46,
+        generator: 15,
+        root: "0036a92e05198a8030f152488aeffc9b40fbe05b4512a3d4b44d994a0ddff8c606df0a4306fe0bc37eca603cc563b9a1",
+    };
+
+    /// Checks `vector` against the field's constants and a derivation from
+    /// its generator, and every smaller root for its exact order
+    fn check_roots<F: FftField + PrimeField>(vector: &RootVector) {
+        let s = vector.two_adicity;
+        let bytes: Vec<u8> = (0..vector.root.len()).step_by(2).map(|i| u8::from_str_radix(&vector.root[i..i + 2], 16).unwrap()).collect();
+        let root = F::from_be_bytes_mod_order(&bytes);
+        assert_eq!((F::TWO_ADICITY, F::GENERATOR), (s, F::from(vector.generator)));
+        let mut exponent = F::MODULUS_MINUS_ONE_DIV_TWO;
+        for _ in 1..s {
+            exponent.div2();
+        }
+        assert_eq!(F::from(vector.generator).pow(exponent), root);
+        assert_eq!(root_of_unity::<F>(s), Ok(root));
+
+        for log_size in 0..=s {
+            let omega = root_of_unity::<F>(log_size).unwrap();
+            assert_eq!(omega.pow([1u64 << log_size]), F::one());
+            if log_size > 0 {
+                assert_eq!(omega.pow([1u64 << (log_size - 1)]), -F::one());
+            }
+        }
+        assert_eq!(root_of_unity::<F>(s + 1), Err(DomainError::NoSubgroup(1 << (s + 1))));
+    }
+
+    #[test]
+    fn test_root_of_unity_vectors() {
+        check_roots::<ScalarField>(&BLS12_381);
+        check_roots::<ark_bn254::Fr>(&BN254);
+        #[cfg(feature = "bw6")]
+        {
+            check_roots::<ark_bls12_377::Fr>(&BLS12_377);
+            check_roots::<ark_bw6_761::Fr>(&BW6_761);
+        }
+        assert_eq!(root_of_unity::<ScalarField>(64), Err(DomainError::NoSubgroup(usize::MAX)));
+    }
+
+    #[test]
+    fn test_domain_for_size() {
+        for (n, size) in [(0, 1), (1, 1), (2, 2), (5, 8), (1000, 1024), (1 << 20, 1 << 20)] {
+            let domain = EvaluationDomain::<ScalarField>::new_for_size(n).unwrap();
+            assert_eq!(domain.size, size);
+            assert_eq!(domain.omega, root_of_unity::<ScalarField>(size.trailing_zeros()).unwrap());
+            assert_eq!(domain.omega * domain.omega_inv, ScalarField::one());
+        }
+        assert_eq!(EvaluationDomain::<ark_bn254::Fr>::new_for_size((1 << 28) + 1).unwrap_err(), DomainError::NoSubgroup(1 << 29));
+        assert_eq!(EvaluationDomain::<ScalarField>::new_for_size(usize::MAX).unwrap_err(), DomainError::NoSubgroup(usize::MAX));
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_parallel_fft_matches_serial() {
+        let mut rng = ark_std::test_rng();
+        let n = PARALLEL_MIN_SIZE * 4;
+        let omega = root_of_unity::<ScalarField>(n.trailing_zeros()).unwrap();
+        let coeffs: Vec<ScalarField> = (0..n).map(|_| ScalarField::rand(&mut rng)).collect();
+        let mut parallel = coeffs.clone();
+        fft(&mut parallel, omega).unwrap();
+        for (i, y) in parallel.iter().enumerate().step_by(997) {
+            assert_eq!(DensePolynomial::from_coefficients_slice(&coeffs).evaluate(&omega.pow([i as u64])), *y);
+        }
+        let mut serial = coeffs.clone();
+        for i in 0..n {
+            let j = reverse_bits(i, n.trailing_zeros() as usize);
+            if i < j {
+                serial.swap(i, j);
+            }
+        }
+        butterflies(&mut serial, &twiddles(omega, n), |x, y| x * y);
+        assert_eq!(serial, parallel);
+        ifft(&mut parallel, omega.inverse().unwrap()).unwrap();
+        assert_eq!(parallel, coeffs);
+    }
+
+    #[test]
+    fn test_domain_fft_matches_free_fft() {
+        let mut rng = ark_std::test_rng();
+        for log_n in 0..7 {
+            let domain = EvaluationDomain::<ScalarField>::new_for_size(1 << log_n).unwrap();
+            assert_eq!(domain.size_inv * ScalarField::from(domain.size as u64), ScalarField::one());
+            let coeffs: Vec<ScalarField> = (0..domain.size).map(|_| ScalarField::rand(&mut rng)).collect();
+            let (mut cached, mut free) = (coeffs.clone(), coeffs.clone());
+            domain.fft(&mut cached).unwrap();
+            fft(&mut free, domain.omega).unwrap();
+            assert_eq!(cached, free);
+            domain.ifft(&mut cached).unwrap();
+            assert_eq!(cached, coeffs);
+        }
+        let domain = EvaluationDomain::<ScalarField>::new_for_size(8).unwrap();
+        assert_eq!(domain.ifft(&mut [ScalarField::one(); 16]), Err(DomainError::LengthMismatch { evals: 16, domain: 8 }));
+        let uneven = EvaluationDomain::new(3, ScalarField::one()).unwrap();
+        assert_eq!(uneven.fft(&mut [ScalarField::one(); 3]), Err(DomainError::NotPowerOfTwo(3)));
+    }
+
+    #[test]
+    fn test_coset_fft() {
+        let mut rng = ark_std::test_rng();
+        let domain = EvaluationDomain::<ScalarField>::new_for_size(8).unwrap();
+        let g = domain.coset_generator();
+        let poly = DensePolynomial::<ScalarField>::rand(7, &mut rng);
+        let mut evals = poly.coeffs.clone();
+        domain.coset_fft(&mut evals).unwrap();
+        for (i, y) in evals.iter().enumerate() {
+            let x = g * domain.omega.pow([i as u64]);
+            assert_eq!(poly.evaluate(&x), *y);
+            // g·H misses H, where Z_H = X^8 − 1 vanishes
+            assert_ne!(x.pow([8u64]), ScalarField::one());
+        }
+        domain.coset_ifft(&mut evals).unwrap();
+        assert_eq!(evals, poly.coeffs);
+
+        let mut short = vec![ScalarField::one(); 4];
+        assert_eq!(domain.coset_fft(&mut short), Err(DomainError::LengthMismatch { evals: 4, domain: 8 }));
+        assert_eq!(coset_fft(&mut [ScalarField::one(); 3], domain.omega, g), Err(DomainError::NotPowerOfTwo(3)));
+    }
+
+    fn domain(n: usize) -> Vec<ScalarField> {
+        let omega = ScalarField::get_root_of_unity(n as u64).unwrap();
+        (0..n as u64).map(|i| omega.pow([i])).collect()
+    }
+
+    #[test]
+    fn test_interpolate_matches_naive() {
+        let mut rng = ark_std::test_rng();
+        for log_n in 0..7 {
+            let n = 1 << log_n;
+            let domain = domain(n);
+            let cases = [
+                vec![ScalarField::zero(); n],
+                vec![ScalarField::from(7u64); n],
+                (0..n).map(|_| ScalarField::rand(&mut rng)).collect(),
+            ];
+            for evals in cases {
+                let poly = interpolate(&evals, &domain).unwrap();
+                assert_eq!(naive::trim(&poly.coeffs), naive::trim(&naive::interpolate(&evals, &domain)));
+                for (x, y) in domain.iter().zip(&evals) {
+                    assert_eq!(poly.evaluate(x), *y);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_multiply_matches_naive() {
+        let mut rng = ark_std::test_rng();
+        for (len_a, len_b) in [(1, 1), (1, 5), (2, 3), (4, 4), (7, 9), (16, 17), (33, 1)] {
+            let a: Vec<ScalarField> = (0..len_a).map(|_| ScalarField::rand(&mut rng)).collect();
+            let b: Vec<ScalarField> = (0..len_b).map(|_| ScalarField::rand(&mut rng)).collect();
+            let product = multiply(
+                &DensePolynomial::from_coefficients_vec(a.clone()),
+                &DensePolynomial::from_coefficients_vec(b.clone()),
+            )
+            .unwrap();
+            assert_eq!(product.coeffs, naive::multiply(&a, &b));
+        }
+        let zero = DensePolynomial::<ScalarField>::from_coefficients_vec(Vec::new());
+        assert!(multiply(&zero, &DensePolynomial::from_coefficients_vec(vec![ScalarField::one()])).unwrap().is_zero());
+    }
+
+    #[test]
+    fn test_division_matches_naive() {
+        let mut rng = ark_std::test_rng();
+        for (len_n, len_d) in [(1, 1), (5, 1), (5, 2), (8, 8), (3, 6), (20, 7), (64, 2)] {
+            let numerator: Vec<ScalarField> = (0..len_n).map(|_| ScalarField::rand(&mut rng)).collect();
+            let divisor: Vec<ScalarField> = (0..len_d).map(|_| ScalarField::rand(&mut rng)).collect();
+            let (quotient, remainder) = naive::divide(&numerator, &divisor);
+
+            // numerator = quotient * divisor + remainder, deg(remainder) < deg(divisor)
+            let mut recombined = naive::multiply(&quotient, &divisor);
+            recombined.resize(len_n.max(recombined.len()), ScalarField::zero());
+            for (r, x) in recombined.iter_mut().zip(&remainder) {
+                *r += x;
+            }
+            assert_eq!(naive::trim(&recombined), naive::trim(&numerator));
+            assert!(remainder.len() < divisor.len());
+
+            let ark = &DensePolynomial::from_coefficients_vec(numerator) / &DensePolynomial::from_coefficients_vec(divisor);
+            assert_eq!(ark.coeffs, quotient);
+        }
+    }
 }
@@ -0,0 +1,712 @@
+//! The BLS12-377 pairing in a circuit over BW6-761's scalar field, which
+//! is BLS12-377's base field Fq, and BLS signature verification with it.
+//!
+//! `Tower` computes in Fq2, Fq6 and Fq12 over any `Inversion<Fq>`:
+//! natively over `Native`, or as gates over `poseidon::Gates`, whose
+//! weighted gates take each Fq-linear combination in one row per two
+//! terms. The Miller loop and the final exponentiation follow arkworks'
+//! `Bls12` step for step (G2 lines in homogeneous projective
+//! coordinates, the hard part after eprint 2020/875 with Granger-Scott
+//! squaring), so `Tower<Native>` reproduces `Bls12_377::multi_miller_loop`
+//! and `multi_pairing` exactly, and the gates compute the same values.
+//!
+//! `verify_signature` checks a signature in G1 against a key in G2, the
+//! layout of `bw6::BlsKeypair`. The message enters hashed to G1: hashing
+//! to the curve stays with whoever builds the statement, as in
+//! `bw6::verify_hashed`. Points at infinity are not representable.
+
+use alloc::{vec, vec::Vec};
+use ark_bls12_377::{Config, Fq, Fq12 as ArkFq12, Fq2 as ArkFq2, Fq2Config, Fq6 as ArkFq6, Fr, G1Affine, G2Affine};
+use ark_ec::bls12::{Bls12Config, G2Prepared};
+use ark_ec::short_weierstrass::SWCurveConfig;
+use ark_ec::AffineRepr;
+use ark_ff::{BitIteratorBE, Field, Fp2Config, One, PrimeField, Zero};
+
+use crate::circuit::{Circuit, Variable};
+use crate::gadgets::poseidon::Gates;
+use crate::poseidon::{Arithmetic, Native};
+
+/// c0 + c1·u with u² = −5
+pub type Fq2<V> = [V; 2];
+/// c0 + c1·v + c2·v² with v³ = u
+pub type Fq6<V> = [Fq2<V>; 3];
+/// c0 + c1·w with w² = v
+pub type Fq12<V> = [Fq6<V>; 2];
+/// The coefficients of one line of the Miller loop, as arkworks'
+/// `EllCoeff`
+pub type Line<V> = [Fq2<V>; 3];
+/// A G1 point and the lines of a G2 point, one factor of a pairing product
+pub type Pair<V> = (G1Point<V>, Vec<Line<V>>);
+
+/// A G1 point in affine coordinates
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct G1Point<V> {
+    pub x: V,
+    pub y: V,
+}
+
+/// A G2 point, on the twist over Fq2, in affine coordinates
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct G2Point<V> {
+    pub x: Fq2<V>,
+    pub y: Fq2<V>,
+}
+
+impl From<&G1Affine> for G1Point<Fq> {
+    fn from(p: &G1Affine) -> Self {
+        Self { x: p.x, y: p.y }
+    }
+}
+
+impl From<&G2Affine> for G2Point<Fq> {
+    fn from(q: &G2Affine) -> Self {
+        Self { x: [q.x.c0, q.x.c1], y: [q.y.c0, q.y.c1] }
+    }
+}
+
+/// `Arithmetic` that also inverts, for the final exponentiation and the
+/// point checks
+pub trait Inversion<F: PrimeField>: Arithmetic<F> {
+    /// x⁻¹: zero for x = 0 natively, an unsatisfiable gate in a circuit
+    fn inverse(&mut self, x: &Self::Value) -> Self::Value;
+}
+
+impl<F: PrimeField> Inversion<F> for Native {
+    fn inverse(&mut self, x: &F) -> F {
+        x.inverse().unwrap_or_default()
+    }
+}
+
+impl<F: PrimeField> Inversion<F> for Gates<'_, F> {
+    /// Witnessed and checked by x·x⁻¹ = 1
+    fn inverse(&mut self, x: &Variable) -> Variable {
+        let value = self.circuit().value(*x).inverse().unwrap_or_default();
+        let inverse = self.circuit().alloc(value);
+        let product = self.mul(x, &inverse);
+        let one = self.constant(F::one());
+        self.circuit().assert_equal(product, one);
+        inverse
+    }
+}
+
+/// u, the non-residue v³ equals
+fn xi() -> ArkFq2 {
+    ArkFq2::new(Fq::zero(), Fq::one())
+}
+
+fn scalar(x: i64) -> ArkFq2 {
+    ArkFq2::from(x)
+}
+
+/// Field towers and the pairing over an `Inversion<Fq>`
+pub struct Tower<A: Inversion<Fq>> {
+    arith: A,
+}
+
+impl<A: Inversion<Fq>> Tower<A> {
+    pub fn new(arith: A) -> Self {
+        Self { arith }
+    }
+
+    pub fn into_inner(self) -> A {
+        self.arith
+    }
+
+    /// Σ c·x + offset, skipping zero coefficients and a bare copy
+    fn lin(&mut self, terms: &[(Fq, &A::Value)], offset: Fq) -> A::Value {
+        let terms: Vec<(Fq, A::Value)> = terms.iter().filter(|(c, _)| !c.is_zero()).map(|(c, x)| (*c, (*x).clone())).collect();
+        match &terms[..] {
+            [(c, x)] if c.is_one() && offset.is_zero() => x.clone(),
+            _ => self.arith.linear(&terms, offset),
+        }
+    }
+
+    fn mul(&mut self, a: &A::Value, b: &A::Value) -> A::Value {
+        self.arith.mul(a, b)
+    }
+
+    fn fq2_constant(&mut self, x: ArkFq2) -> Fq2<A::Value> {
+        [self.arith.constant(x.c0), self.arith.constant(x.c1)]
+    }
+
+    /// Σ k·x + offset with constant k in Fq2, one linear combination per
+    /// coefficient
+    fn fq2_lin(&mut self, terms: &[(ArkFq2, &Fq2<A::Value>)], offset: ArkFq2) -> Fq2<A::Value> {
+        let beta = Fq2Config::NONRESIDUE;
+        let mut c0 = Vec::new();
+        let mut c1 = Vec::new();
+        for (k, x) in terms {
+            c0.extend([(k.c0, &x[0]), (beta * k.c1, &x[1])]);
+            c1.extend([(k.c1, &x[0]), (k.c0, &x[1])]);
+        }
+        [self.lin(&c0, offset.c0), self.lin(&c1, offset.c1)]
+    }
+
+    fn fq2_add(&mut self, a: &Fq2<A::Value>, b: &Fq2<A::Value>) -> Fq2<A::Value> {
+        self.fq2_lin(&[(scalar(1), a), (scalar(1), b)], ArkFq2::zero())
+    }
+
+    /// Karatsuba, three products
+    fn fq2_mul(&mut self, a: &Fq2<A::Value>, b: &Fq2<A::Value>) -> Fq2<A::Value> {
+        let one = Fq::one();
+        let v0 = self.mul(&a[0], &b[0]);
+        let v1 = self.mul(&a[1], &b[1]);
+        let sa = self.lin(&[(one, &a[0]), (one, &a[1])], Fq::zero());
+        let sb = self.lin(&[(one, &b[0]), (one, &b[1])], Fq::zero());
+        let m = self.mul(&sa, &sb);
+        let c0 = self.lin(&[(one, &v0), (Fq2Config::NONRESIDUE, &v1)], Fq::zero());
+        let c1 = self.lin(&[(one, &m), (-one, &v0), (-one, &v1)], Fq::zero());
+        [c0, c1]
+    }
+
+    /// The complex method, two products
+    fn fq2_square(&mut self, a: &Fq2<A::Value>) -> Fq2<A::Value> {
+        let (one, beta) = (Fq::one(), Fq2Config::NONRESIDUE);
+        let v = self.mul(&a[0], &a[1]);
+        let s = self.lin(&[(one, &a[0]), (one, &a[1])], Fq::zero());
+        let t = self.lin(&[(one, &a[0]), (beta, &a[1])], Fq::zero());
+        let m = self.mul(&s, &t);
+        let c0 = self.lin(&[(one, &m), (-one - beta, &v)], Fq::zero());
+        let c1 = self.lin(&[(Fq::from(2u64), &v)], Fq::zero());
+        [c0, c1]
+    }
+
+    fn fq2_scale(&mut self, a: &Fq2<A::Value>, x: &A::Value) -> Fq2<A::Value> {
+        [self.mul(&a[0], x), self.mul(&a[1], x)]
+    }
+
+    /// (c0 − c1·u) / (c0² + 5·c1²)
+    fn fq2_inverse(&mut self, a: &Fq2<A::Value>) -> Fq2<A::Value> {
+        let s0 = self.mul(&a[0], &a[0]);
+        let s1 = self.mul(&a[1], &a[1]);
+        let norm = self.lin(&[(Fq::one(), &s0), (-Fq2Config::NONRESIDUE, &s1)], Fq::zero());
+        let inverse = self.arith.inverse(&norm);
+        let c0 = self.mul(&a[0], &inverse);
+        let c1 = self.mul(&a[1], &inverse);
+        [c0, self.lin(&[(-Fq::one(), &c1)], Fq::zero())]
+    }
+
+    /// Σ k·x + Σ k·v·y, componentwise with v·(y0, y1, y2) = (u·y2, y0, y1)
+    fn fq6_lin(&mut self, terms: &[(ArkFq2, &Fq6<A::Value>)], times_v: &[(ArkFq2, &Fq6<A::Value>)]) -> Fq6<A::Value> {
+        let component = |i: usize| -> Vec<(ArkFq2, &Fq2<A::Value>)> {
+            let plain = terms.iter().map(|(k, x)| (*k, &x[i]));
+            let shifted = times_v.iter().map(|(k, y)| match i {
+                0 => (*k * xi(), &y[2]),
+                _ => (*k, &y[i - 1]),
+            });
+            plain.chain(shifted).collect()
+        };
+        let [c0, c1, c2] = [0, 1, 2].map(component);
+        [
+            self.fq2_lin(&c0, ArkFq2::zero()),
+            self.fq2_lin(&c1, ArkFq2::zero()),
+            self.fq2_lin(&c2, ArkFq2::zero()),
+        ]
+    }
+
+    /// Karatsuba, six Fq2 products
+    fn fq6_mul(&mut self, a: &Fq6<A::Value>, b: &Fq6<A::Value>) -> Fq6<A::Value> {
+        let (one, xi) = (scalar(1), xi());
+        let v0 = self.fq2_mul(&a[0], &b[0]);
+        let v1 = self.fq2_mul(&a[1], &b[1]);
+        let v2 = self.fq2_mul(&a[2], &b[2]);
+        let mut cross = |i: usize, j: usize| {
+            let sa = self.fq2_add(&a[i], &a[j]);
+            let sb = self.fq2_add(&b[i], &b[j]);
+            self.fq2_mul(&sa, &sb)
+        };
+        let (t12, t01, t02) = (cross(1, 2), cross(0, 1), cross(0, 2));
+        let c0 = self.fq2_lin(&[(one, &v0), (xi, &t12), (-xi, &v1), (-xi, &v2)], ArkFq2::zero());
+        let c1 = self.fq2_lin(&[(one, &t01), (-one, &v0), (-one, &v1), (xi, &v2)], ArkFq2::zero());
+        let c2 = self.fq2_lin(&[(one, &t02), (-one, &v0), (-one, &v2), (one, &v1)], ArkFq2::zero());
+        [c0, c1, c2]
+    }
+
+    /// a·(c0 + c1·v), five Fq2 products
+    fn fq6_mul_by_01(&mut self, a: &Fq6<A::Value>, c0: &Fq2<A::Value>, c1: &Fq2<A::Value>) -> Fq6<A::Value> {
+        let one = scalar(1);
+        let v0 = self.fq2_mul(&a[0], c0);
+        let v1 = self.fq2_mul(&a[1], c1);
+        let sa = self.fq2_add(&a[0], &a[1]);
+        let sc = self.fq2_add(c0, c1);
+        let t = self.fq2_mul(&sa, &sc);
+        let a2c1 = self.fq2_mul(&a[2], c1);
+        let a2c0 = self.fq2_mul(&a[2], c0);
+        [
+            self.fq2_lin(&[(one, &v0), (xi(), &a2c1)], ArkFq2::zero()),
+            self.fq2_lin(&[(one, &t), (-one, &v0), (-one, &v1)], ArkFq2::zero()),
+            self.fq2_lin(&[(one, &v1), (one, &a2c0)], ArkFq2::zero()),
+        ]
+    }
+
+    fn fq6_inverse(&mut self, a: &Fq6<A::Value>) -> Fq6<A::Value> {
+        let (one, xi) = (scalar(1), xi());
+        let [c0, c1, c2] = a;
+        let s0 = self.fq2_square(c0);
+        let s1 = self.fq2_square(c1);
+        let s2 = self.fq2_square(c2);
+        let c01 = self.fq2_mul(c0, c1);
+        let c02 = self.fq2_mul(c0, c2);
+        let c12 = self.fq2_mul(c1, c2);
+        let t0 = self.fq2_lin(&[(one, &s0), (-xi, &c12)], ArkFq2::zero());
+        let t1 = self.fq2_lin(&[(xi, &s2), (-one, &c01)], ArkFq2::zero());
+        let t2 = self.fq2_lin(&[(one, &s1), (-one, &c02)], ArkFq2::zero());
+        let n0 = self.fq2_mul(c0, &t0);
+        let n1 = self.fq2_mul(c2, &t1);
+        let n2 = self.fq2_mul(c1, &t2);
+        let norm = self.fq2_lin(&[(one, &n0), (xi, &n1), (xi, &n2)], ArkFq2::zero());
+        let inverse = self.fq2_inverse(&norm);
+        [self.fq2_mul(&t0, &inverse), self.fq2_mul(&t1, &inverse), self.fq2_mul(&t2, &inverse)]
+    }
+
+    fn fq12_constant(&mut self, x: ArkFq12) -> Fq12<A::Value> {
+        let mut fq6 = |y: ArkFq6| [y.c0, y.c1, y.c2].map(|z| self.fq2_constant(z));
+        [fq6(x.c0), fq6(x.c1)]
+    }
+
+    /// Three Fq6 products
+    pub fn fq12_mul(&mut self, a: &Fq12<A::Value>, b: &Fq12<A::Value>) -> Fq12<A::Value> {
+        let one = scalar(1);
+        let aa = self.fq6_mul(&a[0], &b[0]);
+        let bb = self.fq6_mul(&a[1], &b[1]);
+        let sa = self.fq6_lin(&[(one, &a[0]), (one, &a[1])], &[]);
+        let sb = self.fq6_lin(&[(one, &b[0]), (one, &b[1])], &[]);
+        let t = self.fq6_mul(&sa, &sb);
+        let c0 = self.fq6_lin(&[(one, &aa)], &[(one, &bb)]);
+        let c1 = self.fq6_lin(&[(one, &t), (-one, &aa), (-one, &bb)], &[]);
+        [c0, c1]
+    }
+
+    /// The complex method, two Fq6 products
+    fn fq12_square(&mut self, a: &Fq12<A::Value>) -> Fq12<A::Value> {
+        let one = scalar(1);
+        let ab = self.fq6_mul(&a[0], &a[1]);
+        let s = self.fq6_lin(&[(one, &a[0]), (one, &a[1])], &[]);
+        let t = self.fq6_lin(&[(one, &a[0])], &[(one, &a[1])]);
+        let m = self.fq6_mul(&s, &t);
+        let c0 = self.fq6_lin(&[(one, &m), (-one, &ab)], &[(-one, &ab)]);
+        let c1 = self.fq6_lin(&[(scalar(2), &ab)], &[]);
+        [c0, c1]
+    }
+
+    /// The square of an element of the cyclotomic subgroup, after
+    /// Granger and Scott, as arkworks' `cyclotomic_square`
+    fn cyclotomic_square(&mut self, a: &Fq12<A::Value>) -> Fq12<A::Value> {
+        let (one, u) = (scalar(1), xi());
+        let [[r0, r4, r3], [r2, r1, r5]] = a;
+        // (z + z'·y)² for the pairs (r0, r1), (r2, r3), (r4, r5): the
+        // product z·z' and (z + z')(z + ξ·z')
+        let mut halves = |z: &Fq2<A::Value>, z_: &Fq2<A::Value>| {
+            let product = self.fq2_mul(z, z_);
+            let s = self.fq2_add(z, z_);
+            let t = self.fq2_lin(&[(one, z), (u, z_)], ArkFq2::zero());
+            (product, self.fq2_mul(&s, &t))
+        };
+        let (p01, m01) = halves(r0, r1);
+        let (p23, m23) = halves(r2, r3);
+        let (p45, m45) = halves(r4, r5);
+        // t_even = m − (1 + ξ)·p, t_odd = 2p; then z = 3t ∓ 2r
+        fn even<'a, V>(m: &'a Fq2<V>, p: &'a Fq2<V>, r: &'a Fq2<V>) -> [(ArkFq2, &'a Fq2<V>); 3] {
+            let three = scalar(3);
+            [(three, m), (-three * (scalar(1) + xi()), p), (-scalar(2), r)]
+        }
+        let six = scalar(6);
+        let z0 = self.fq2_lin(&even(&m01, &p01, r0), ArkFq2::zero());
+        let z1 = self.fq2_lin(&[(six, &p01), (scalar(2), r1)], ArkFq2::zero());
+        let z2 = self.fq2_lin(&[(six * u, &p45), (scalar(2), r2)], ArkFq2::zero());
+        let z3 = self.fq2_lin(&even(&m45, &p45, r3), ArkFq2::zero());
+        let z4 = self.fq2_lin(&even(&m23, &p23, r4), ArkFq2::zero());
+        let z5 = self.fq2_lin(&[(six, &p23), (scalar(2), r5)], ArkFq2::zero());
+        [[z0, z4, z3], [z2, z1, z5]]
+    }
+
+    /// f^(p^6), the inverse in the cyclotomic subgroup
+    fn conjugate(&mut self, a: &Fq12<A::Value>) -> Fq12<A::Value> {
+        [a[0].clone(), self.fq6_lin(&[(-scalar(1), &a[1])], &[])]
+    }
+
+    fn fq12_inverse(&mut self, a: &Fq12<A::Value>) -> Fq12<A::Value> {
+        let one = scalar(1);
+        let s0 = self.fq6_mul(&a[0], &a[0]);
+        let s1 = self.fq6_mul(&a[1], &a[1]);
+        let norm = self.fq6_lin(&[(one, &s0)], &[(-one, &s1)]);
+        let inverse = self.fq6_inverse(&norm);
+        let c0 = self.fq6_mul(&a[0], &inverse);
+        let c1 = self.fq6_mul(&a[1], &inverse);
+        [c0, self.fq6_lin(&[(-one, &c1)], &[])]
+    }
+
+    /// f^(p^power), Fq-linear, applied through its matrix on the twelve
+    /// coefficients
+    fn frobenius(&mut self, a: &Fq12<A::Value>, power: usize) -> Fq12<A::Value> {
+        let flat: Vec<&A::Value> = a.iter().flatten().flatten().collect();
+        let columns: Vec<[Fq; 12]> = (0..12)
+            .map(|k| {
+                let mut basis = [Fq::zero(); 12];
+                basis[k] = Fq::one();
+                let mut x = unflatten(basis);
+                x.frobenius_map_in_place(power);
+                flatten(&x)
+            })
+            .collect();
+        let rows: Vec<A::Value> = (0..12)
+            .map(|i| {
+                let terms: Vec<(Fq, &A::Value)> = columns.iter().zip(&flat).map(|(column, x)| (column[i], *x)).collect();
+                self.lin(&terms, Fq::zero())
+            })
+            .collect();
+        let fq2 = |i: usize| [rows[i].clone(), rows[i + 1].clone()];
+        [[fq2(0), fq2(2), fq2(4)], [fq2(6), fq2(8), fq2(10)]]
+    }
+
+    /// f·(c0 + (c3 + c4·v)·w), the sparse product with a line of a D-type
+    /// twist, as arkworks' `mul_by_034`
+    fn mul_by_034(&mut self, f: &Fq12<A::Value>, c0: &Fq2<A::Value>, c3: &Fq2<A::Value>, c4: &Fq2<A::Value>) -> Fq12<A::Value> {
+        let one = scalar(1);
+        let a = [0, 1, 2].map(|i| self.fq2_mul(&f[0][i], c0));
+        let b = self.fq6_mul_by_01(&f[1], c3, c4);
+        let s = self.fq2_add(c0, c3);
+        let e = self.fq6_lin(&[(one, &f[0]), (one, &f[1])], &[]);
+        let e = self.fq6_mul_by_01(&e, &s, c4);
+        let c1 = self.fq6_lin(&[(one, &e), (-one, &a), (-one, &b)], &[]);
+        let c0 = self.fq6_lin(&[(one, &a)], &[(one, &b)]);
+        [c0, c1]
+    }
+
+    /// The lines of the Miller loop through `q`, in the order the loop
+    /// takes them
+    pub fn prepare(&mut self, q: &G2Point<A::Value>) -> Vec<Line<A::Value>> {
+        let one = scalar(1);
+        let two_inv = ArkFq2::new(Fq::from(2u64).inverse().expect("odd characteristic"), Fq::zero());
+        let b = <ark_bls12_377::g2::Config as SWCurveConfig>::COEFF_B;
+        let mut lines = Vec::new();
+        let (mut x, mut y) = (q.x.clone(), q.y.clone());
+        let mut z = self.fq2_constant(ArkFq2::one());
+        for bit in BitIteratorBE::new(Config::X).skip(1) {
+            // Doubling, as arkworks' `double_in_place`
+            let xy = self.fq2_mul(&x, &y);
+            let b_ = self.fq2_square(&y);
+            let c = self.fq2_square(&z);
+            let e = self.fq2_lin(&[(scalar(3) * b, &c)], ArkFq2::zero());
+            let g = self.fq2_lin(&[(two_inv, &b_), (scalar(9) * b * two_inv, &c)], ArkFq2::zero());
+            let yz = self.fq2_add(&y, &z);
+            let yz = self.fq2_square(&yz);
+            let h = self.fq2_lin(&[(one, &yz), (-one, &b_), (-one, &c)], ArkFq2::zero());
+            let i = self.fq2_lin(&[(one, &e), (-one, &b_)], ArkFq2::zero());
+            let j = self.fq2_square(&x);
+            let e_square = self.fq2_square(&e);
+            let a = self.fq2_lin(&[(two_inv, &xy)], ArkFq2::zero());
+            let b_f = self.fq2_lin(&[(one, &b_), (-scalar(9) * b, &c)], ArkFq2::zero());
+            x = self.fq2_mul(&a, &b_f);
+            let g_square = self.fq2_square(&g);
+            y = self.fq2_lin(&[(one, &g_square), (-scalar(3), &e_square)], ArkFq2::zero());
+            z = self.fq2_mul(&b_, &h);
+            let minus_h = self.fq2_lin(&[(-one, &h)], ArkFq2::zero());
+            let three_j = self.fq2_lin(&[(scalar(3), &j)], ArkFq2::zero());
+            lines.push([minus_h, three_j, i]);
+
+            if bit {
+                // Adding q, as arkworks' `add_in_place`
+                let qy_z = self.fq2_mul(&q.y, &z);
+                let qx_z = self.fq2_mul(&q.x, &z);
+                let theta = self.fq2_lin(&[(one, &y), (-one, &qy_z)], ArkFq2::zero());
+                let lambda = self.fq2_lin(&[(one, &x), (-one, &qx_z)], ArkFq2::zero());
+                let c = self.fq2_square(&theta);
+                let d = self.fq2_square(&lambda);
+                let e = self.fq2_mul(&lambda, &d);
+                let f = self.fq2_mul(&z, &c);
+                let g = self.fq2_mul(&x, &d);
+                let h = self.fq2_lin(&[(one, &e), (one, &f), (-scalar(2), &g)], ArkFq2::zero());
+                let g_h = self.fq2_lin(&[(one, &g), (-one, &h)], ArkFq2::zero());
+                let theta_gh = self.fq2_mul(&theta, &g_h);
+                let e_y = self.fq2_mul(&e, &y);
+                x = self.fq2_mul(&lambda, &h);
+                y = self.fq2_lin(&[(one, &theta_gh), (-one, &e_y)], ArkFq2::zero());
+                z = self.fq2_mul(&z, &e);
+                let theta_qx = self.fq2_mul(&theta, &q.x);
+                let lambda_qy = self.fq2_mul(&lambda, &q.y);
+                let j = self.fq2_lin(&[(one, &theta_qx), (-one, &lambda_qy)], ArkFq2::zero());
+                let minus_theta = self.fq2_lin(&[(-one, &theta)], ArkFq2::zero());
+                lines.push([lambda, minus_theta, j]);
+            }
+        }
+        lines
+    }
+
+    /// The lines through a constant point, computed natively
+    fn prepare_constant(&mut self, q: &G2Affine) -> Vec<Line<A::Value>> {
+        let prepared = G2Prepared::<Config>::from(*q);
+        prepared.ell_coeffs.into_iter().map(|(c0, c1, c2)| [c0, c1, c2].map(|c| self.fq2_constant(c))).collect()
+    }
+
+    /// The product of the Miller loops of `pairs`, each a G1 point and
+    /// the `prepare`d lines of a G2 point
+    pub fn miller_loop(&mut self, pairs: &[Pair<A::Value>]) -> Fq12<A::Value> {
+        let mut f = self.fq12_constant(ArkFq12::one());
+        let mut lines: Vec<_> = pairs.iter().map(|(p, lines)| (p, lines.iter())).collect();
+        let mut ell = |tower: &mut Self, f: &Fq12<A::Value>| {
+            let mut f = f.clone();
+            for (p, lines) in lines.iter_mut() {
+                let [c0, c1, c2] = lines.next().expect("a line per step");
+                let c0 = tower.fq2_scale(c0, &p.y);
+                let c1 = tower.fq2_scale(c1, &p.x);
+                f = tower.mul_by_034(&f, &c0, &c1, c2);
+            }
+            f
+        };
+        for bit in BitIteratorBE::without_leading_zeros(Config::X).skip(1) {
+            f = self.fq12_square(&f);
+            f = ell(self, &f);
+            if bit {
+                f = ell(self, &f);
+            }
+        }
+        f
+    }
+
+    /// f^x by square and multiply, for f in the cyclotomic subgroup
+    fn exp_by_x(&mut self, f: &Fq12<A::Value>) -> Fq12<A::Value> {
+        let mut result = f.clone();
+        for bit in BitIteratorBE::without_leading_zeros(Config::X).skip(1) {
+            result = self.cyclotomic_square(&result);
+            if bit {
+                result = self.fq12_mul(&result, f);
+            }
+        }
+        result
+    }
+
+    /// f^((p¹² − 1)/r), as arkworks' `Bls12::final_exponentiation`
+    pub fn final_exponentiation(&mut self, f: &Fq12<A::Value>) -> Fq12<A::Value> {
+        // Easy part, f^((p⁶ − 1)(p² + 1))
+        let f1 = self.conjugate(f);
+        let f2 = self.fq12_inverse(f);
+        let r = self.fq12_mul(&f1, &f2);
+        let f2 = r.clone();
+        let r = self.frobenius(&r, 2);
+        let r = self.fq12_mul(&r, &f2);
+
+        let y0 = self.cyclotomic_square(&r);
+        let y1 = self.exp_by_x(&r);
+        let y2 = self.conjugate(&r);
+        let y1 = self.fq12_mul(&y1, &y2);
+        let y2 = self.exp_by_x(&y1);
+        let y1 = self.conjugate(&y1);
+        let y1 = self.fq12_mul(&y1, &y2);
+        let y2 = self.exp_by_x(&y1);
+        let y1 = self.frobenius(&y1, 1);
+        let y1 = self.fq12_mul(&y1, &y2);
+        let r = self.fq12_mul(&r, &y0);
+        let y0 = self.exp_by_x(&y1);
+        let y2 = self.exp_by_x(&y0);
+        let y0 = self.frobenius(&y1, 2);
+        let y1 = self.conjugate(&y1);
+        let y1 = self.fq12_mul(&y1, &y2);
+        let y1 = self.fq12_mul(&y1, &y0);
+        self.fq12_mul(&r, &y1)
+    }
+
+    /// y² − x³ − b, zero on G1
+    fn g1_curve_equation(&mut self, p: &G1Point<A::Value>) -> A::Value {
+        let b = <ark_bls12_377::g1::Config as SWCurveConfig>::COEFF_B;
+        let y2 = self.mul(&p.y, &p.y);
+        let x2 = self.mul(&p.x, &p.x);
+        let x3 = self.mul(&x2, &p.x);
+        self.lin(&[(Fq::one(), &y2), (-Fq::one(), &x3)], -b)
+    }
+
+    /// y² − x³ − b' over Fq2, zero on the twist
+    fn g2_curve_equation(&mut self, q: &G2Point<A::Value>) -> Fq2<A::Value> {
+        let b = <ark_bls12_377::g2::Config as SWCurveConfig>::COEFF_B;
+        let y2 = self.fq2_square(&q.y);
+        let x2 = self.fq2_square(&q.x);
+        let x3 = self.fq2_mul(&x2, &q.x);
+        self.fq2_lin(&[(scalar(1), &y2), (-scalar(1), &x3)], -b)
+    }
+
+    /// p + q by the chord, for p ≠ ±q
+    fn g1_add(&mut self, p: &G1Point<A::Value>, q: &G1Point<A::Value>) -> G1Point<A::Value> {
+        let one = Fq::one();
+        let dy = self.lin(&[(one, &q.y), (-one, &p.y)], Fq::zero());
+        let dx = self.lin(&[(one, &q.x), (-one, &p.x)], Fq::zero());
+        let inverse = self.arith.inverse(&dx);
+        let lambda = self.mul(&dy, &inverse);
+        self.g1_chord(p, q, &lambda)
+    }
+
+    /// 2p by the tangent, for y ≠ 0
+    fn g1_double(&mut self, p: &G1Point<A::Value>) -> G1Point<A::Value> {
+        let x2 = self.mul(&p.x, &p.x);
+        let two_y = self.lin(&[(Fq::from(2u64), &p.y)], Fq::zero());
+        let inverse = self.arith.inverse(&two_y);
+        let lambda = self.lin(&[(Fq::from(3u64), &x2)], Fq::zero());
+        let lambda = self.mul(&lambda, &inverse);
+        self.g1_chord(p, p, &lambda)
+    }
+
+    /// The third point on the line of slope λ through p and q, negated
+    fn g1_chord(&mut self, p: &G1Point<A::Value>, q: &G1Point<A::Value>, lambda: &A::Value) -> G1Point<A::Value> {
+        let one = Fq::one();
+        let square = self.mul(lambda, lambda);
+        let x = self.lin(&[(one, &square), (-one, &p.x), (-one, &q.x)], Fq::zero());
+        let dx = self.lin(&[(one, &p.x), (-one, &x)], Fq::zero());
+        let t = self.mul(lambda, &dx);
+        let y = self.lin(&[(one, &t), (-one, &p.y)], Fq::zero());
+        G1Point { x, y }
+    }
+
+    /// Values that are zero exactly when [r − 1]·p = −p, so p has order r
+    /// and lies in G1. The additions are incomplete, which an order-r
+    /// point never meets; any other point leaves an inverse of zero.
+    fn g1_subgroup_equation(&mut self, p: &G1Point<A::Value>) -> [A::Value; 2] {
+        let r_minus_one = (-Fr::one()).into_bigint();
+        let mut acc = p.clone();
+        for bit in BitIteratorBE::without_leading_zeros(r_minus_one).skip(1) {
+            acc = self.g1_double(&acc);
+            if bit {
+                acc = self.g1_add(&acc, p);
+            }
+        }
+        let one = Fq::one();
+        [self.lin(&[(one, &acc.x), (-one, &p.x)], Fq::zero()), self.lin(&[(one, &acc.y), (one, &p.y)], Fq::zero())]
+    }
+}
+
+/// `p` as private variables
+pub fn alloc_g1(cs: &mut Circuit<Fq>, p: &G1Affine) -> G1Point<Variable> {
+    G1Point { x: cs.alloc(p.x), y: cs.alloc(p.y) }
+}
+
+/// `q` as private variables
+pub fn alloc_g2(cs: &mut Circuit<Fq>, q: &G2Affine) -> G2Point<Variable> {
+    let mut fq2 = |x: ArkFq2| [cs.alloc(x.c0), cs.alloc(x.c1)];
+    G2Point { x: fq2(q.x), y: fq2(q.y) }
+}
+
+/// Requires `signature` to be a BLS signature under `public` on the
+/// message whose hash to G1 is `hashed`: `hashed` and `signature` on the
+/// curve, `signature` in G1, `public` on the twist, and
+///
+///   e(σ, g₂)·e(−H(m), pk) = 1
+///
+/// Runs in namespace "bls" and fits in 2^17 rows with the allocations.
+/// `public` is not checked to lie in G2, which is left to whoever
+/// registers the key, as with proofs of possession.
+pub fn verify_signature(cs: &mut Circuit<Fq>, public: &G2Point<Variable>, hashed: &G1Point<Variable>, signature: &G1Point<Variable>) {
+    cs.namespace("bls", |cs| {
+        let mut tower = Tower::new(Gates::new(cs));
+        let mut zeros = vec![tower.g1_curve_equation(signature), tower.g1_curve_equation(hashed)];
+        zeros.extend(tower.g2_curve_equation(public));
+        zeros.extend(tower.g1_subgroup_equation(signature));
+
+        let negated = G1Point { x: hashed.x, y: tower.lin(&[(-Fq::one(), &hashed.y)], Fq::zero()) };
+        let generator = tower.prepare_constant(&G2Affine::generator());
+        let key = tower.prepare(public);
+        let f = tower.miller_loop(&[(*signature, generator), (negated, key)]);
+        let f = tower.final_exponentiation(&f);
+        let one = tower.fq12_constant(ArkFq12::one());
+
+        let mut gates = tower.into_inner();
+        for (value, expected) in f.iter().flatten().flatten().zip(one.iter().flatten().flatten()) {
+            gates.circuit().assert_equal(*value, *expected);
+        }
+        for zero in zeros {
+            gates.circuit().assert_zero(zero);
+        }
+    });
+}
+
+/// The twelve Fq coefficients of `x`, c0.c0.c0 first
+fn flatten(x: &ArkFq12) -> [Fq; 12] {
+    let mut flat = [Fq::zero(); 12];
+    for (i, c) in [x.c0.c0, x.c0.c1, x.c0.c2, x.c1.c0, x.c1.c1, x.c1.c2].into_iter().enumerate() {
+        flat[2 * i] = c.c0;
+        flat[2 * i + 1] = c.c1;
+    }
+    flat
+}
+
+fn unflatten(flat: [Fq; 12]) -> ArkFq12 {
+    let fq2 = |i: usize| ArkFq2::new(flat[i], flat[i + 1]);
+    ArkFq12::new(ArkFq6::new(fq2(0), fq2(2), fq2(4)), ArkFq6::new(fq2(6), fq2(8), fq2(10)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bw6::{hash_to_g1, verify_hashed, BlsKeypair};
+    use ark_bls12_377::{Bls12_377, G1Projective, G2Projective};
+    use ark_ec::pairing::Pairing;
+    use ark_ec::CurveGroup;
+    use ark_std::UniformRand;
+
+    fn unflatten_native(x: &Fq12<Fq>) -> ArkFq12 {
+        let mut flat = [Fq::zero(); 12];
+        for (slot, value) in flat.iter_mut().zip(x.iter().flatten().flatten()) {
+            *slot = *value;
+        }
+        unflatten(flat)
+    }
+
+    #[test]
+    fn test_native_tower_matches_arkworks() {
+        let mut rng = ark_std::test_rng();
+        let ps: Vec<G1Affine> = (0..2).map(|_| G1Projective::rand(&mut rng).into_affine()).collect();
+        let qs: Vec<G2Affine> = (0..2).map(|_| G2Projective::rand(&mut rng).into_affine()).collect();
+
+        let mut tower = Tower::new(Native);
+        let pairs: Vec<_> = ps.iter().zip(&qs).map(|(p, q)| (G1Point::from(p), tower.prepare(&G2Point::from(q)))).collect();
+        let f = tower.miller_loop(&pairs);
+        let expected = Bls12_377::multi_miller_loop(ps.clone(), qs.clone());
+        assert_eq!(unflatten_native(&f), expected.0);
+        let e = tower.final_exponentiation(&f);
+        assert_eq!(unflatten_native(&e), Bls12_377::multi_pairing(ps, qs).0);
+
+        let a = ArkFq12::rand(&mut rng);
+        let x = tower.fq12_constant(a);
+        assert_eq!(unflatten_native(&tower.fq12_inverse(&x)), a.inverse().unwrap());
+        assert_eq!(unflatten_native(&tower.frobenius(&x, 3)), {
+            let mut b = a;
+            b.frobenius_map_in_place(3);
+            b
+        });
+    }
+
+    #[test]
+    fn test_verify_signature_in_circuit() {
+        let mut rng = ark_std::test_rng();
+        let key = BlsKeypair::generate(&mut rng);
+        let hashed = hash_to_g1(b"block 42");
+        let signature = key.sign(b"block 42");
+
+        let check = |public: &G2Affine, hashed: &G1Affine, signature: &G1Affine| {
+            let mut cs = Circuit::new(1 << 17);
+            let (public, hashed, signature) = (alloc_g2(&mut cs, public), alloc_g1(&mut cs, hashed), alloc_g1(&mut cs, signature));
+            verify_signature(&mut cs, &public, &hashed, &signature);
+            assert_eq!(cs.error(), None);
+            cs
+        };
+        let cs = check(&key.public, &hashed, &signature);
+        assert_eq!(cs.first_unsatisfied(), None);
+
+        // Another message or key fails the pairing
+        let other = BlsKeypair::generate(&mut rng);
+        for (public, hashed) in [(key.public, hash_to_g1(b"block 43")), (other.public, hashed)] {
+            let cs = check(&public, &hashed, &signature);
+            assert_eq!(cs.first_unsatisfied().map(|gate| gate.namespace.as_str()), Some("bls"));
+        }
+
+        // A point of order dividing the cofactor added to the signature
+        // leaves the pairing unchanged, but not the subgroup check
+        let torsion = (0u64..)
+            .find_map(|x| G1Affine::get_point_from_x_unchecked(Fq::from(x), false))
+            .map(|p| p.mul_bigint(Fr::MODULUS).into_affine())
+            .unwrap();
+        assert!(!torsion.is_zero());
+        let malleated = (signature + torsion).into_affine();
+        assert!(verify_hashed(&key.public, &hashed, &malleated));
+        let cs = check(&key.public, &hashed, &malleated);
+        assert_eq!(cs.first_unsatisfied().map(|gate| gate.namespace.as_str()), Some("bls"));
+
+    }
+}
@@ -0,0 +1,57 @@
+use alloc::{string::ToString, vec};
+use ark_ff::PrimeField;
+
+use crate::circuit::{Circuit, Gate, GateType, Variable};
+use crate::custom_gate::{CustomGate, Expression};
+
+/// c = a − b
+pub fn difference_gate<F: PrimeField>() -> CustomGate<F> {
+    CustomGate { name: "difference".to_string(), identities: vec![Expression::a() - Expression::b() - Expression::c()] }
+}
+
+/// Requires `var` to be 0 or 1, in one mul gate b·b = b
+pub fn assert_boolean<F: PrimeField>(cs: &mut Circuit<F>, var: Variable) {
+    let wire = cs.wire(var);
+    cs.namespace("boolean", |cs| {
+        cs.add_gate(Gate { gate_type: GateType::Mul, left_wire: wire.clone(), right_wire: wire.clone(), output_wire: wire })
+    });
+}
+
+/// `a` if `cond` is 1 and `b` if it is 0, as cond·(a − b) + b. `cond` is
+/// constrained boolean here, so callers need not check it first; four
+/// gates in total.
+pub fn select<F: PrimeField>(cs: &mut Circuit<F>, cond: Variable, a: Variable, b: Variable) -> Variable {
+    cs.namespace("select", |cs| {
+        assert_boolean(cs, cond);
+        let gate = cs.register_gate(difference_gate());
+        let diff = cs.alloc(cs.value(a) - cs.value(b));
+        cs.custom_gate(gate, a, b, diff);
+        let scaled = cs.mul(cond, diff);
+        cs.add(scaled, b)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bls12_381::Fr;
+
+    #[test]
+    fn test_boolean_and_select() {
+        let mut cs = Circuit::<Fr>::new(16);
+        let (x, y) = (cs.alloc(Fr::from(7u64)), cs.alloc(Fr::from(9u64)));
+        for (bit, expected) in [(1u64, 7u64), (0, 9)] {
+            let cond = cs.alloc(Fr::from(bit));
+            let out = select(&mut cs, cond, x, y);
+            assert_eq!(cs.value(out), Fr::from(expected));
+        }
+        assert_eq!(cs.gates.len(), 8);
+        assert_eq!(cs.custom_gates.len(), 1);
+        assert!(cs.first_unsatisfied().is_none());
+        assert!(cs.verify_rows() && cs.verify_copy_constraints());
+
+        let cond = cs.alloc(Fr::from(2u64));
+        select(&mut cs, cond, x, y);
+        assert_eq!(cs.first_unsatisfied().map(|gate| gate.namespace.as_str()), Some("select/boolean"));
+    }
+}
@@ -0,0 +1,372 @@
+//! Twisted Edwards arithmetic over a curve whose base field is the
+//! circuit's own field, such as Jubjub inside BLS12-381's scalar field.
+//! Coordinates are native variables.
+//!
+//! Every operation goes through the unified addition law, which is
+//! complete when A is a square and d is not (`is_complete`, true for
+//! Jubjub): it adds any two curve points, including the identity, a
+//! point to itself or to its inverse, and points of small order, so no
+//! gadget here branches on exceptional cases and `scalar_mul` is sound
+//! for every base and every scalar. Montgomery points enter through
+//! `from_montgomery`, whose map has no exceptional input on such a
+//! curve either; the incomplete Montgomery formulas are not offered.
+
+use alloc::{string::ToString, vec};
+use ark_ec::twisted_edwards::{Affine, TECurveConfig};
+use ark_ff::{AdditiveGroup, Field, PrimeField};
+
+use super::boolean::{assert_boolean, difference_gate, select};
+use crate::circuit::{Circuit, CircuitError, Variable};
+use crate::custom_gate::{CustomGate, Expression};
+
+/// A curve point as two native variables
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Point {
+    pub x: Variable,
+    pub y: Variable,
+}
+
+/// c = a·(1 + d·b): x3 times the x denominator, with b = x1·x2·y1·y2
+pub fn x_gate<P: TECurveConfig>() -> CustomGate<P::BaseField>
+where
+    P::BaseField: PrimeField,
+{
+    let (a, b, c) = (Expression::a(), Expression::b(), Expression::c());
+    let d = Expression::constant(P::COEFF_D);
+    CustomGate { name: "edwards_x".to_string(), identities: vec![a.clone() + d * a * b - c] }
+}
+
+/// c = a·(1 − d·b), the same for y3
+pub fn y_gate<P: TECurveConfig>() -> CustomGate<P::BaseField>
+where
+    P::BaseField: PrimeField,
+{
+    let (a, b, c) = (Expression::a(), Expression::b(), Expression::c());
+    let d = Expression::constant(P::COEFF_D);
+    CustomGate { name: "edwards_y".to_string(), identities: vec![a.clone() - d * a * b - c] }
+}
+
+/// c = a − A·b, the y numerator y1·y2 − A·x1·x2
+pub fn numerator_gate<P: TECurveConfig>() -> CustomGate<P::BaseField>
+where
+    P::BaseField: PrimeField,
+{
+    let (a, b, c) = (Expression::a(), Expression::b(), Expression::c());
+    let coeff = Expression::constant(P::COEFF_A);
+    CustomGate { name: "edwards_numerator".to_string(), identities: vec![a - coeff * b - c] }
+}
+
+/// A·a + b = 1 + d·a·b for a = x² and b = y², the curve equation
+pub fn curve_gate<P: TECurveConfig>() -> CustomGate<P::BaseField>
+where
+    P::BaseField: PrimeField,
+{
+    let (a, b) = (Expression::a(), Expression::b());
+    let (coeff, d) = (Expression::constant(P::COEFF_A), Expression::constant(P::COEFF_D));
+    let one = Expression::constant(P::BaseField::ONE);
+    CustomGate { name: "edwards_curve".to_string(), identities: vec![coeff * a.clone() + b.clone() - one - d * a * b] }
+}
+
+/// c·b = a, u = x·v for the Montgomery point (u, v)
+pub fn montgomery_x_gate<F: PrimeField>() -> CustomGate<F> {
+    let (a, b, c) = (Expression::a(), Expression::b(), Expression::c());
+    CustomGate { name: "montgomery_x".to_string(), identities: vec![c * b - a] }
+}
+
+/// c·(a + 1) = a − 1, y·(u + 1) = u − 1 for the Montgomery u
+pub fn montgomery_y_gate<F: PrimeField>() -> CustomGate<F> {
+    let (a, c) = (Expression::a(), Expression::c());
+    let one = Expression::constant(F::ONE);
+    CustomGate { name: "montgomery_y".to_string(), identities: vec![c * (a.clone() + one.clone()) - a + one] }
+}
+
+/// Whether the unified law of `P` is complete: A a square and d not, so
+/// its denominators 1 ± d·x1·x2·y1·y2 never vanish on curve points
+pub fn is_complete<P: TECurveConfig>() -> bool
+where
+    P::BaseField: PrimeField,
+{
+    P::COEFF_A.legendre().is_qr() && P::COEFF_D.legendre().is_qnr()
+}
+
+/// Allocates `point` and checks it is on the curve
+pub fn alloc_point<P: TECurveConfig>(cs: &mut Circuit<P::BaseField>, point: &Affine<P>) -> Point
+where
+    P::BaseField: PrimeField,
+{
+    let point = Point { x: cs.alloc(point.x), y: cs.alloc(point.y) };
+    assert_on_curve::<P>(cs, point);
+    point
+}
+
+/// `point` as constants
+pub fn constant_point<P: TECurveConfig>(cs: &mut Circuit<P::BaseField>, point: &Affine<P>) -> Point
+where
+    P::BaseField: PrimeField,
+{
+    Point { x: cs.constant(point.x), y: cs.constant(point.y) }
+}
+
+/// Requires A·x² + y² = 1 + d·x²·y², in three gates. Points outside the
+/// prime-order subgroup pass; callers needing it clear the cofactor.
+pub fn assert_on_curve<P: TECurveConfig>(cs: &mut Circuit<P::BaseField>, point: Point)
+where
+    P::BaseField: PrimeField,
+{
+    cs.namespace("edwards", |cs| {
+        let gate = cs.register_gate(curve_gate::<P>());
+        let xx = cs.mul(point.x, point.x);
+        let yy = cs.mul(point.y, point.y);
+        cs.custom_gate(gate, xx, yy, yy);
+    })
+}
+
+/// Allocates the Montgomery point (u, v) and returns its Edwards image
+pub fn alloc_montgomery<P: TECurveConfig>(cs: &mut Circuit<P::BaseField>, u: P::BaseField, v: P::BaseField) -> Point
+where
+    P::BaseField: PrimeField,
+{
+    let (u, v) = (cs.alloc(u), cs.alloc(v));
+    from_montgomery::<P>(cs, u, v)
+}
+
+/// The Edwards point of (u, v) on the Montgomery curve
+/// B·v² = u³ + A'·u² + u of `P`,
+///   x = u / v, y = (u − 1) / (u + 1)
+/// checked on the Edwards curve, in five gates. On a complete curve
+/// u = −1 has no point and v = 0 only at (0, 0), where the curve check
+/// leaves x = 0 as the only choice, so every input has exactly one
+/// image. v is not bound when u = 0; callers using it elsewhere check
+/// (u, v) on the Montgomery curve.
+pub fn from_montgomery<P: TECurveConfig>(cs: &mut Circuit<P::BaseField>, u: Variable, v: Variable) -> Point
+where
+    P::BaseField: PrimeField,
+{
+    let point = cs.namespace("edwards", |cs| {
+        let (x_id, y_id) = (cs.register_gate(montgomery_x_gate()), cs.register_gate(montgomery_y_gate()));
+        let (u_value, v_value) = (cs.value(u), cs.value(v));
+        let x = cs.alloc(u_value * v_value.inverse().unwrap_or_default());
+        let y = cs.alloc((u_value - P::BaseField::ONE) * (u_value + P::BaseField::ONE).inverse().unwrap_or_default());
+        cs.custom_gate(x_id, u, v, x);
+        cs.custom_gate(y_id, u, u, y);
+        Point { x, y }
+    });
+    assert_on_curve::<P>(cs, point);
+    point
+}
+
+/// (u, v) of the Montgomery point of `point`, for witnesses. None for the
+/// identity and (0, −1), which have no finite Montgomery image under the
+/// inverse map.
+pub fn to_montgomery<P: TECurveConfig>(point: &Affine<P>) -> Option<(P::BaseField, P::BaseField)>
+where
+    P::BaseField: PrimeField,
+{
+    let u = (P::BaseField::ONE + point.y) * (P::BaseField::ONE - point.y).inverse()?;
+    Some((u, u * point.x.inverse()?))
+}
+
+/// p + q by the unified law
+///   x3 = (x1·y2 + y1·x2) / (1 + d·x1·x2·y1·y2)
+///   y3 = (y1·y2 − A·x1·x2) / (1 − d·x1·x2·y1·y2)
+/// in nine gates. The denominators never vanish on a complete curve; on
+/// any other the circuit fails with `CircuitError::IncompleteCurve`.
+pub fn add<P: TECurveConfig>(cs: &mut Circuit<P::BaseField>, p: Point, q: Point) -> Point
+where
+    P::BaseField: PrimeField,
+{
+    if !is_complete::<P>() {
+        cs.fail(CircuitError::IncompleteCurve);
+    }
+    cs.namespace("edwards", |cs| {
+        let (x_id, y_id, numerator_id) =
+            (cs.register_gate(x_gate::<P>()), cs.register_gate(y_gate::<P>()), cs.register_gate(numerator_gate::<P>()));
+        let x1y2 = cs.mul(p.x, q.y);
+        let y1x2 = cs.mul(p.y, q.x);
+        let x1x2 = cs.mul(p.x, q.x);
+        let y1y2 = cs.mul(p.y, q.y);
+        let product = cs.mul(x1x2, y1y2);
+        let x_numerator = cs.add(x1y2, y1x2);
+        let y_numerator = cs.alloc(cs.value(y1y2) - P::COEFF_A * cs.value(x1x2));
+        cs.custom_gate(numerator_id, y1y2, x1x2, y_numerator);
+
+        let dk = P::COEFF_D * cs.value(product);
+        let x3 = cs.value(x_numerator) * (P::BaseField::ONE + dk).inverse().unwrap_or_default();
+        let y3 = cs.value(y_numerator) * (P::BaseField::ONE - dk).inverse().unwrap_or_default();
+        let (x3, y3) = (cs.alloc(x3), cs.alloc(y3));
+        cs.custom_gate(x_id, x3, product, x_numerator);
+        cs.custom_gate(y_id, y3, product, y_numerator);
+        Point { x: x3, y: y3 }
+    })
+}
+
+/// 2·p, as `add(p, p)`
+pub fn double<P: TECurveConfig>(cs: &mut Circuit<P::BaseField>, p: Point) -> Point
+where
+    P::BaseField: PrimeField,
+{
+    add::<P>(cs, p, p)
+}
+
+/// −p = (−x, y), in two gates
+pub fn neg<P: TECurveConfig>(cs: &mut Circuit<P::BaseField>, p: Point) -> Point
+where
+    P::BaseField: PrimeField,
+{
+    cs.namespace("edwards", |cs| {
+        let gate = cs.register_gate(difference_gate());
+        let zero = cs.constant(P::BaseField::ZERO);
+        let x = cs.alloc(-cs.value(p.x));
+        cs.custom_gate(gate, zero, p.x, x);
+        Point { x, y: p.y }
+    })
+}
+
+/// p − q, as p + (−q)
+pub fn sub<P: TECurveConfig>(cs: &mut Circuit<P::BaseField>, p: Point, q: Point) -> Point
+where
+    P::BaseField: PrimeField,
+{
+    let q = neg::<P>(cs, q);
+    add::<P>(cs, p, q)
+}
+
+/// [k]·`point` for k = Σ 2^i·bits[i], least significant bit first. Each
+/// bit is checked boolean here; getting them from a scalar is the
+/// caller's choice, such as `range::assert_bits` with fewer bits than
+/// the field so the decomposition is unique. 24 gates per bit.
+pub fn scalar_mul<P: TECurveConfig>(cs: &mut Circuit<P::BaseField>, bits: &[Variable], point: Point) -> Point
+where
+    P::BaseField: PrimeField,
+{
+    cs.namespace("edwards", |cs| {
+        let one = cs.constant(P::BaseField::ONE);
+        let mut acc = Point { x: cs.constant(P::BaseField::ZERO), y: one };
+        let mut base = point;
+        for (i, bit) in bits.iter().enumerate() {
+            // base where the bit is set, the identity (0, 1) elsewhere
+            assert_boolean(cs, *bit);
+            let addend = Point { x: cs.mul(*bit, base.x), y: select(cs, *bit, base.y, one) };
+            acc = add::<P>(cs, acc, addend);
+            if i + 1 < bits.len() {
+                base = double::<P>(cs, base);
+            }
+        }
+        acc
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gadgets::range::assert_bits;
+    use ark_bls12_381::Fr;
+    use ark_ec::twisted_edwards::MontCurveConfig;
+    use ark_ec::{AffineRepr, CurveGroup};
+    use ark_ff::BigInteger;
+    use ark_ed_on_bls12_381::{EdwardsAffine, EdwardsConfig, Fr as JubjubScalar};
+    use ark_std::UniformRand;
+
+    #[test]
+    fn test_matches_native() {
+        let mut rng = ark_std::test_rng();
+        let g = EdwardsAffine::generator();
+        let h = (g * JubjubScalar::rand(&mut rng)).into_affine();
+        let mut cs = Circuit::<Fr>::new(1 << 14);
+        let (p, q) = (alloc_point(&mut cs, &g), alloc_point(&mut cs, &h));
+
+        let sum = add::<EdwardsConfig>(&mut cs, p, q);
+        let expected = (g + h).into_affine();
+        assert_eq!((cs.value(sum.x), cs.value(sum.y)), (expected.x, expected.y));
+        let doubled = double::<EdwardsConfig>(&mut cs, p);
+        let expected = (g + g).into_affine();
+        assert_eq!((cs.value(doubled.x), cs.value(doubled.y)), (expected.x, expected.y));
+
+        // A Pedersen commitment [m]·G + [r]·H from the bits of m and r
+        let (m, r) = (Fr::from(1234u64), Fr::rand(&mut rng));
+        let m_var = cs.alloc(m);
+        let r_var = cs.alloc(r);
+        let m_bits = assert_bits(&mut cs, m_var, 16);
+        let r_bits = assert_bits(&mut cs, r_var, 255);
+        let mg = scalar_mul::<EdwardsConfig>(&mut cs, &m_bits, p);
+        let rh = scalar_mul::<EdwardsConfig>(&mut cs, &r_bits, q);
+        let commitment = add::<EdwardsConfig>(&mut cs, mg, rh);
+        let r_scalar = JubjubScalar::from_le_bytes_mod_order(&r.into_bigint().to_bytes_le());
+        let expected = (g * JubjubScalar::from(1234u64) + h * r_scalar).into_affine();
+        assert_eq!((cs.value(commitment.x), cs.value(commitment.y)), (expected.x, expected.y));
+
+        assert!(cs.error().is_none());
+        assert!(cs.first_unsatisfied().is_none());
+        assert!(cs.verify_rows() && cs.verify_copy_constraints());
+
+        // (1, 1) is not on the curve
+        let mut cs = Circuit::<Fr>::new(16);
+        let point = Point { x: cs.alloc(Fr::ONE), y: cs.alloc(Fr::ONE) };
+        assert_on_curve::<EdwardsConfig>(&mut cs, point);
+        assert_eq!(cs.first_unsatisfied().map(|gate| gate.namespace.as_str()), Some("edwards"));
+    }
+
+    #[test]
+    fn test_edge_points() {
+        let mut rng = ark_std::test_rng();
+        let native = |cs: &Circuit<Fr>, point: Point| EdwardsAffine::new_unchecked(cs.value(point.x), cs.value(point.y));
+        let identity = EdwardsAffine::zero();
+        let g = EdwardsAffine::generator();
+        // (√(1/A), 0) has order 4 and (0, −1) order 2, outside the
+        // prime-order subgroup
+        let i = (Fr::ONE / <EdwardsConfig as TECurveConfig>::COEFF_A).sqrt().unwrap();
+        let order_4 = EdwardsAffine::new_unchecked(i, Fr::ZERO);
+        let order_2 = EdwardsAffine::new_unchecked(Fr::ZERO, -Fr::ONE);
+        assert!(is_complete::<EdwardsConfig>());
+
+        let mut cs = Circuit::<Fr>::new(1 << 14);
+        let points = [identity, g, order_4, order_2].map(|point| alloc_point(&mut cs, &point));
+        let [e, p, t4, t2] = points;
+        for (a, b) in [(p, e), (e, e), (p, p), (t4, t4), (t4, t2), (t2, t2), (p, t4)] {
+            let sum = add::<EdwardsConfig>(&mut cs, a, b);
+            assert_eq!(native(&cs, sum), (native(&cs, a) + native(&cs, b)).into_affine());
+        }
+        let minus_p = neg::<EdwardsConfig>(&mut cs, p);
+        assert_eq!(native(&cs, minus_p), -g);
+        for difference in [add::<EdwardsConfig>(&mut cs, p, minus_p), sub::<EdwardsConfig>(&mut cs, t4, t4)] {
+            assert_eq!(native(&cs, difference), identity);
+        }
+        let doubled = double::<EdwardsConfig>(&mut cs, t4);
+        assert_eq!(native(&cs, doubled), order_2);
+
+        // Zero, the group order and any scalar on a small-order base
+        let zero = cs.constant(Fr::ZERO);
+        let bits: Vec<_> = (0..4).map(|_| zero).collect();
+        let out = scalar_mul::<EdwardsConfig>(&mut cs, &bits, p);
+        assert_eq!(native(&cs, out), identity);
+        let order = JubjubScalar::MODULUS;
+        let bits: Vec<_> = order.to_bits_le().into_iter().take(252).map(|bit| cs.constant(Fr::from(bit))).collect();
+        let out = scalar_mul::<EdwardsConfig>(&mut cs, &bits, p);
+        assert_eq!(native(&cs, out), identity);
+        let bits: Vec<_> = [1u64, 1, 0].map(|bit| cs.constant(Fr::from(bit))).to_vec();
+        let out = scalar_mul::<EdwardsConfig>(&mut cs, &bits, t4);
+        assert_eq!(native(&cs, out), (order_4 * JubjubScalar::from(3u64)).into_affine());
+        let out = scalar_mul::<EdwardsConfig>(&mut cs, &bits, e);
+        assert_eq!(native(&cs, out), identity);
+
+        // Montgomery points, the order-2 (0, 0) included
+        let h = (g * JubjubScalar::rand(&mut rng)).into_affine();
+        let (u, v) = to_montgomery(&h).unwrap();
+        let (a, b) = (<EdwardsConfig as MontCurveConfig>::COEFF_A, <EdwardsConfig as MontCurveConfig>::COEFF_B);
+        assert_eq!(b * v.square(), u * (u.square() + a * u + Fr::ONE));
+        let out = alloc_montgomery::<EdwardsConfig>(&mut cs, u, v);
+        assert_eq!(native(&cs, out), h);
+        let out = alloc_montgomery::<EdwardsConfig>(&mut cs, Fr::ZERO, Fr::ZERO);
+        assert_eq!(native(&cs, out), order_2);
+        assert_eq!(to_montgomery(&identity), None);
+
+        assert!(cs.error().is_none());
+        assert!(cs.first_unsatisfied().is_none());
+        assert!(cs.verify_rows() && cs.verify_copy_constraints());
+
+        // u = 2 with a v off the Montgomery curve has no Edwards image
+        let mut cs = Circuit::<Fr>::new(64);
+        alloc_montgomery::<EdwardsConfig>(&mut cs, Fr::from(2u64), Fr::ONE);
+        assert_eq!(cs.first_unsatisfied().map(|gate| gate.namespace.as_str()), Some("edwards"));
+    }
+}
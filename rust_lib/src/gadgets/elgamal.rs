@@ -0,0 +1,99 @@
+//! The relation of `crate::elgamal` in a circuit over BLS12-381 Fr, the
+//! Jubjub base field:
+//!
+//!   c1 = r·G,  c2 = v + (r·pk).x
+//!
+//! with both scalar multiplications done by `edwards::scalar_mul` on the
+//! bits of r, so a proof shows a ciphertext encrypts a committed value
+//! without revealing it or the randomness.
+
+use alloc::vec::Vec;
+use ark_ec::AffineRepr;
+use ark_ed_on_bls12_381::{EdwardsAffine, EdwardsConfig, Fq, Fr as JubjubScalar};
+use ark_ff::{BigInteger, PrimeField};
+
+use super::edwards::{alloc_point, constant_point, scalar_mul, Point};
+use super::range::assert_bits;
+use crate::circuit::{Circuit, Variable};
+use crate::elgamal::Ciphertext;
+
+/// A ciphertext as circuit variables
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AssignedCiphertext {
+    pub c1: Point,
+    pub c2: Variable,
+}
+
+/// Allocates `ciphertext`, checking c1 is on the curve
+pub fn alloc_ciphertext(cs: &mut Circuit<Fq>, ciphertext: &Ciphertext) -> AssignedCiphertext {
+    AssignedCiphertext { c1: alloc_point(cs, &ciphertext.c1), c2: cs.alloc(ciphertext.c2) }
+}
+
+/// Allocates the randomness `r` as its bits, least significant first. A
+/// Jubjub scalar has fewer bits than Fr, so the decomposition is unique.
+pub fn alloc_randomness(cs: &mut Circuit<Fq>, r: JubjubScalar) -> Vec<Variable> {
+    let r = cs.alloc(Fq::from_le_bytes_mod_order(&r.into_bigint().to_bytes_le()));
+    assert_bits(cs, r, JubjubScalar::MODULUS_BIT_SIZE as usize)
+}
+
+/// The encryption of `value` under `public` with randomness `r_bits`, in
+/// two scalar multiplications and one addition
+pub fn encrypt(cs: &mut Circuit<Fq>, public: Point, value: Variable, r_bits: &[Variable]) -> AssignedCiphertext {
+    cs.namespace("elgamal", |cs| {
+        let generator = constant_point(cs, &EdwardsAffine::generator());
+        let c1 = scalar_mul::<EdwardsConfig>(cs, r_bits, generator);
+        let shared = scalar_mul::<EdwardsConfig>(cs, r_bits, public);
+        let c2 = cs.add(value, shared.x);
+        AssignedCiphertext { c1, c2 }
+    })
+}
+
+/// Requires `ciphertext` to encrypt `value` under `public` with
+/// randomness `r_bits`, `Ciphertext::is_encryption_of` in the circuit
+pub fn assert_encryption_of(
+    cs: &mut Circuit<Fq>,
+    ciphertext: AssignedCiphertext,
+    public: Point,
+    value: Variable,
+    r_bits: &[Variable],
+) {
+    let expected = encrypt(cs, public, value, r_bits);
+    cs.assert_equal(ciphertext.c1.x, expected.c1.x);
+    cs.assert_equal(ciphertext.c1.y, expected.c1.y);
+    cs.assert_equal(ciphertext.c2, expected.c2);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::elgamal::{encrypt_with, Keypair};
+    use ark_std::UniformRand;
+
+    #[test]
+    fn test_matches_native() {
+        let mut rng = ark_std::test_rng();
+        let keys = Keypair::generate(&mut rng);
+        let value = Fq::rand(&mut rng);
+        let r = JubjubScalar::rand(&mut rng);
+        let ciphertext = encrypt_with(&keys.public, value, r);
+        assert!(ciphertext.is_encryption_of(&keys.public, value, r));
+
+        let prove = |ciphertext: &Ciphertext, value: Fq, r: JubjubScalar| {
+            let mut cs = Circuit::<Fq>::new(1 << 15);
+            let public = alloc_point(&mut cs, &keys.public);
+            let assigned = alloc_ciphertext(&mut cs, ciphertext);
+            let value = cs.alloc(value);
+            let r_bits = alloc_randomness(&mut cs, r);
+            assert_encryption_of(&mut cs, assigned, public, value, &r_bits);
+            assert!(cs.error().is_none());
+            cs.first_unsatisfied().is_none() && cs.verify_rows() && cs.verify_copy_constraints()
+        };
+        assert!(prove(&ciphertext, value, r));
+
+        // Wrong value, wrong randomness, tampered c2
+        assert!(!prove(&ciphertext, value + Fq::from(1u64), r));
+        assert!(!prove(&ciphertext, value, r + JubjubScalar::from(1u64)));
+        let tampered = Ciphertext { c2: ciphertext.c2 + Fq::from(1u64), ..ciphertext };
+        assert!(!prove(&tampered, value, r));
+    }
+}
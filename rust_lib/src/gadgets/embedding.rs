@@ -0,0 +1,197 @@
+use alloc::vec::Vec;
+use ark_ec::short_weierstrass::{Affine, SWCurveConfig};
+use ark_ec::twisted_edwards::{self, TECurveConfig};
+use ark_ff::{BigInteger, PrimeField};
+
+use super::boolean::assert_boolean;
+use super::range::assert_bits;
+use crate::circuit::{Circuit, Variable};
+use crate::embedding::{edwards_to_limbs, limb_value, num_limbs, point_to_limbs, to_limbs, LIMB_BITS};
+
+/// A point whose coordinates live in another field, as the limbs of
+/// `embedding::point_to_limbs`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AssignedPoint {
+    pub x: Vec<Variable>,
+    pub y: Vec<Variable>,
+    pub infinity: Variable,
+}
+
+/// An Edwards point as the limbs of `embedding::edwards_to_limbs`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AssignedEdwardsPoint {
+    pub x: Vec<Variable>,
+    pub y: Vec<Variable>,
+}
+
+/// Requires `limbs` to be the canonical limbs of an element of `T`: each
+/// below 2^64, and their value below the modulus of `T`. The second part
+/// witnesses d = (q − 1) − x as limbs and checks x + d = q − 1 limb by
+/// limb, carrying one bit between limbs.
+pub fn assert_canonical<T: PrimeField, F: PrimeField>(cs: &mut Circuit<F>, limbs: &[Variable]) {
+    cs.namespace("embedding", |cs| {
+        for limb in limbs {
+            assert_bits(cs, *limb, LIMB_BITS);
+        }
+        let mut bound = T::MODULUS;
+        bound.sub_with_borrow(&T::BigInt::from(1u64));
+        let bound = &bound.as_ref()[..num_limbs::<T>()];
+        if limbs.len() != bound.len() {
+            // No assignment of the wrong number of limbs is canonical
+            let one = cs.constant(F::one());
+            return cs.assert_zero(one);
+        }
+
+        let shift = cs.constant(F::from(2u128.pow(LIMB_BITS as u32)));
+        let mut carry: Option<Variable> = None;
+        for (i, (limb, m)) in limbs.iter().zip(bound).enumerate() {
+            // m_i − x_i − borrow_i, wrapping, and the borrow it takes
+            let x = limb_value(cs.value(*limb)).unwrap_or(u64::MAX);
+            let borrow_in = carry.is_some_and(|c| !cs.value(c).is_zero());
+            let (d, b1) = m.overflowing_sub(x);
+            let (d, b2) = d.overflowing_sub(borrow_in as u64);
+            let d = cs.alloc(F::from(d));
+            assert_bits(cs, d, LIMB_BITS);
+
+            let mut sum = cs.add(*limb, d);
+            if let Some(c) = carry {
+                sum = cs.add(sum, c);
+            }
+            let m = cs.constant(F::from(*m));
+            if i + 1 == bound.len() {
+                cs.assert_equal(sum, m);
+            } else {
+                let out = cs.alloc(if b1 || b2 { F::one() } else { F::zero() });
+                assert_boolean(cs, out);
+                let high = cs.mul(out, shift);
+                let expected = cs.add(m, high);
+                cs.assert_equal(sum, expected);
+                carry = Some(out);
+            }
+        }
+    })
+}
+
+/// Allocates the canonical limbs of `x` and checks them with
+/// `assert_canonical`
+pub fn alloc_limbs<T: PrimeField, F: PrimeField>(cs: &mut Circuit<F>, x: T) -> Vec<Variable> {
+    let limbs: Vec<Variable> = to_limbs::<T, F>(x).into_iter().map(|limb| cs.alloc(limb)).collect();
+    assert_canonical::<T, F>(cs, &limbs);
+    limbs
+}
+
+/// Allocates the limbs of `point`, checked by `assert_point`
+pub fn alloc_point<P: SWCurveConfig, F: PrimeField>(cs: &mut Circuit<F>, point: &Affine<P>) -> AssignedPoint
+where
+    P::BaseField: PrimeField,
+{
+    let limbs: Vec<Variable> = point_to_limbs(point).into_iter().map(|limb| cs.alloc(limb)).collect();
+    assert_point::<P, F>(cs, &limbs)
+}
+
+/// Exposes `point` as the public inputs `embedding::point_to_limbs`
+/// lists, on consecutive rows, checked by `assert_point`. The verifier
+/// passes `embedding::commitment_inputs` for them.
+pub fn expose_point<P: SWCurveConfig, F: PrimeField>(cs: &mut Circuit<F>, point: &Affine<P>) -> AssignedPoint
+where
+    P::BaseField: PrimeField,
+{
+    let limbs: Vec<Variable> = point_to_limbs(point).into_iter().map(|limb| Variable::from(&cs.add_public_input(limb))).collect();
+    assert_point::<P, F>(cs, &limbs)
+}
+
+/// Requires `limbs` to be an encoding `embedding::point_to_limbs` could
+/// produce: canonical coordinates, a boolean infinity flag, and zero
+/// coordinates when it is set, so every point has one encoding. Whether
+/// the point is on the curve is not checked.
+pub fn assert_point<P: SWCurveConfig, F: PrimeField>(cs: &mut Circuit<F>, limbs: &[Variable]) -> AssignedPoint
+where
+    P::BaseField: PrimeField,
+{
+    let (coordinates, infinity) = match limbs.split_last() {
+        Some((infinity, coordinates)) => (coordinates, *infinity),
+        None => (limbs, cs.constant(F::zero())),
+    };
+    let (x, y) = coordinates.split_at(coordinates.len() / 2);
+    assert_canonical::<P::BaseField, F>(cs, x);
+    assert_canonical::<P::BaseField, F>(cs, y);
+    cs.namespace("embedding", |cs| {
+        assert_boolean(cs, infinity);
+        for limb in coordinates {
+            let product = cs.mul(infinity, *limb);
+            cs.assert_zero(product);
+        }
+    });
+    AssignedPoint { x: x.to_vec(), y: y.to_vec(), infinity }
+}
+
+/// Exposes `point` as the public inputs `embedding::edwards_to_limbs`
+/// lists, with canonical coordinates. Whether the point is on the curve
+/// is not checked.
+pub fn expose_edwards_point<P: TECurveConfig, F: PrimeField>(cs: &mut Circuit<F>, point: &twisted_edwards::Affine<P>) -> AssignedEdwardsPoint
+where
+    P::BaseField: PrimeField,
+{
+    let limbs: Vec<Variable> = edwards_to_limbs(point).into_iter().map(|limb| Variable::from(&cs.add_public_input(limb))).collect();
+    let (x, y) = limbs.split_at(limbs.len() / 2);
+    assert_canonical::<P::BaseField, F>(cs, x);
+    assert_canonical::<P::BaseField, F>(cs, y);
+    AssignedEdwardsPoint { x: x.to_vec(), y: y.to_vec() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bls12_381::{Fq, Fr, G1Affine};
+    use ark_ec::AffineRepr;
+    use ark_ff::Field;
+
+    #[test]
+    fn test_canonical_limbs() {
+        let mut cs = Circuit::<Fr>::new(1 << 12);
+        for x in [Fq::from(0u64), -Fq::ONE, Fq::from(u64::MAX)] {
+            let limbs = alloc_limbs(&mut cs, x);
+            assert_eq!(limbs.iter().map(|limb| cs.value(*limb)).collect::<Vec<_>>(), to_limbs::<Fq, Fr>(x));
+        }
+        let g = G1Affine::generator();
+        let point = alloc_point(&mut cs, &g);
+        assert_eq!(point.y.iter().map(|limb| cs.value(*limb)).collect::<Vec<_>>(), to_limbs::<Fq, Fr>(g.y));
+        assert_eq!(cs.value(point.infinity), Fr::from(0u64));
+        assert!(cs.error().is_none());
+        assert!(cs.first_unsatisfied().is_none());
+        assert!(cs.verify_rows() && cs.verify_copy_constraints());
+
+        // An infinity flag next to nonzero coordinates is not an encoding
+        let mut cs = Circuit::<Fr>::new(1 << 12);
+        let mut limbs = point_to_limbs::<_, Fr>(&g);
+        limbs[12] = Fr::from(1u64);
+        let limbs: Vec<Variable> = limbs.into_iter().map(|limb| cs.alloc(limb)).collect();
+        assert_point::<ark_bls12_381::g1::Config, Fr>(&mut cs, &limbs);
+        assert_eq!(cs.first_unsatisfied().map(|gate| gate.namespace.as_str()), Some("embedding"));
+
+        // The modulus has 64-bit limbs but is not a field element
+        let mut cs = Circuit::<Fr>::new(1 << 12);
+        let limbs: Vec<Variable> = Fq::MODULUS.as_ref().iter().map(|limb| cs.alloc(Fr::from(*limb))).collect();
+        assert_canonical::<Fq, Fr>(&mut cs, &limbs);
+        assert_eq!(cs.first_unsatisfied().map(|gate| gate.namespace.as_str()), Some("embedding"));
+    }
+
+    #[test]
+    fn test_exposed_commitments() {
+        let g = G1Affine::generator();
+        let commitments = [g, G1Affine::identity()];
+        let h = ark_ed_on_bls12_381::EdwardsAffine::generator();
+        let mut cs = Circuit::<Fr>::new(1 << 13);
+        for commitment in &commitments {
+            expose_point(&mut cs, commitment);
+        }
+        let pedersen = expose_edwards_point(&mut cs, &h);
+        assert_eq!(pedersen.x.len(), 4);
+        assert!(cs.first_unsatisfied().is_none());
+        assert!(cs.verify_rows() && cs.verify_copy_constraints());
+
+        let mut expected = crate::embedding::commitment_inputs::<_, Fr>(&commitments);
+        expected.extend(edwards_to_limbs::<_, Fr>(&h));
+        assert_eq!(cs.public_input_values(), expected);
+    }
+}
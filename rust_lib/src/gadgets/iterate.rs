@@ -0,0 +1,62 @@
+//! Unrolled iterative computations: one gadget applied n times, each
+//! output feeding the next input. An iterated Poseidon hash chain is the
+//! standard workload for benchmarks and the IVC examples, since its size
+//! grows linearly with the step count and every step looks the same.
+
+use ark_ff::PrimeField;
+
+use super::poseidon::hash;
+use crate::circuit::{Circuit, Variable};
+use crate::poseidon::{self, Native, PoseidonParams};
+
+/// Applies `gadget` `n_steps` times starting from `input`, wiring each
+/// output to the next input, and returns the last output. The steps run
+/// in namespace "iterate"; `input` comes back unchanged for zero steps.
+pub fn iterate<F: PrimeField, T>(
+    cs: &mut Circuit<F>,
+    input: T,
+    n_steps: usize,
+    mut gadget: impl FnMut(&mut Circuit<F>, T) -> T,
+) -> T {
+    cs.namespace("iterate", |cs| (0..n_steps).fold(input, |state, _| gadget(cs, state)))
+}
+
+/// h_n where h_0 = `seed` and h_{i+1} = `poseidon::hash([h_i])`
+pub fn hash_chain<F: PrimeField>(cs: &mut Circuit<F>, params: &PoseidonParams<F>, seed: Variable, n_steps: usize) -> Variable {
+    iterate(cs, seed, n_steps, |cs, state| hash(cs, params, &[state]))
+}
+
+/// `hash_chain` computed natively
+pub fn hash_chain_native<F: PrimeField>(params: &PoseidonParams<F>, seed: F, n_steps: usize) -> F {
+    (0..n_steps).fold(seed, |state, _| poseidon::hash(params, &mut Native, &[state]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bls12_381::Fr;
+
+    #[test]
+    fn test_hash_chain() {
+        let params = PoseidonParams::<Fr>::new();
+        let seed = Fr::from(7u64);
+        let mut cs = Circuit::<Fr>::new(1 << 13);
+        let var = cs.alloc(seed);
+        let rows = cs.gates.len();
+        assert_eq!(hash_chain(&mut cs, &params, var, 0), var);
+        assert_eq!(cs.gates.len(), rows);
+
+        let out = hash_chain(&mut cs, &params, var, 3);
+        assert_eq!(cs.value(out), hash_chain_native(&params, seed, 3));
+        assert_ne!(hash_chain_native(&params, seed, 3), hash_chain_native(&params, seed, 2));
+        assert!(cs.error().is_none());
+        assert!(cs.first_unsatisfied().is_none());
+        assert!(cs.verify_rows() && cs.verify_copy_constraints());
+
+        // Steps over a pair of variables
+        let mut cs = Circuit::<Fr>::new(64);
+        let (a, b) = (cs.alloc(Fr::from(0u64)), cs.alloc(Fr::from(1u64)));
+        let (_, fib) = iterate(&mut cs, (a, b), 10, |cs, (a, b)| (b, cs.add(a, b)));
+        assert_eq!(cs.value(fib), Fr::from(89u64));
+    }
+}
@@ -0,0 +1,134 @@
+//! Merkle authentication paths over the Poseidon hash. A parent is
+//! `poseidon::hash([left, right])`, and a path lists, from the leaf up,
+//! each sibling with a bit telling whether the node being proven is the
+//! right child. The depth is the length of the path.
+
+use alloc::{vec, vec::Vec};
+use ark_ff::PrimeField;
+
+use super::boolean::select;
+use super::poseidon::hash;
+use crate::circuit::{Circuit, Variable};
+use crate::poseidon::{self, Native, PoseidonParams};
+
+/// One level of a path in the circuit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PathNode {
+    pub sibling: Variable,
+    /// 1 when the node on the path is the right child, checked boolean
+    pub is_right: Variable,
+}
+
+/// The root `leaf` hashes up to along `path`, given as (sibling,
+/// is_right) pairs, for witness generation and for checking outside a
+/// circuit
+pub fn compute_root<F: PrimeField>(params: &PoseidonParams<F>, leaf: F, path: &[(F, bool)]) -> F {
+    path.iter().fold(leaf, |node, (sibling, is_right)| {
+        let pair = if *is_right { [*sibling, node] } else { [node, *sibling] };
+        poseidon::hash(params, &mut Native, &pair)
+    })
+}
+
+/// Allocates `path` for `verify_path`
+pub fn alloc_path<F: PrimeField>(cs: &mut Circuit<F>, path: &[(F, bool)]) -> Vec<PathNode> {
+    path.iter()
+        .map(|(sibling, is_right)| PathNode {
+            sibling: cs.alloc(*sibling),
+            is_right: cs.alloc(if *is_right { F::one() } else { F::zero() }),
+        })
+        .collect()
+}
+
+/// Requires `leaf` to hash up to `root` along `path`, and returns the
+/// root computed. Each level orders the pair with two `select`s and
+/// hashes it with the Poseidon gadget.
+pub fn verify_path<F: PrimeField>(
+    cs: &mut Circuit<F>,
+    params: &PoseidonParams<F>,
+    root: Variable,
+    leaf: Variable,
+    path: &[PathNode],
+) -> Variable {
+    cs.namespace("merkle", |cs| {
+        let mut node = leaf;
+        for level in path {
+            let left = select(cs, level.is_right, level.sibling, node);
+            let right = select(cs, level.is_right, node, level.sibling);
+            node = hash(cs, params, &[left, right]);
+        }
+        cs.assert_equal(node, root);
+        node
+    })
+}
+
+/// Root of the tree over `leaves`, in order. A level of odd length has
+/// its last node paired with zero; one leaf is its own root.
+pub fn tree_root<F: PrimeField>(params: &PoseidonParams<F>, leaves: &[F]) -> F {
+    let mut level = leaves.to_vec();
+    while level.len() > 1 {
+        level = level.chunks(2).map(|pair| poseidon::hash(params, &mut Native, &[pair[0], pair.get(1).copied().unwrap_or_else(F::zero)])).collect();
+    }
+    level.first().copied().unwrap_or_else(F::zero)
+}
+
+/// `tree_root` of the leaf variables in the circuit, one Poseidon hash
+/// per inner node
+pub fn hash_tree<F: PrimeField>(cs: &mut Circuit<F>, params: &PoseidonParams<F>, leaves: &[Variable]) -> Variable {
+    cs.namespace("merkle", |cs| {
+        let zero = cs.constant(F::zero());
+        let mut level = if leaves.is_empty() { vec![zero] } else { leaves.to_vec() };
+        while level.len() > 1 {
+            level = level.chunks(2).map(|pair| hash(cs, params, &[pair[0], pair.get(1).copied().unwrap_or(zero)])).collect();
+        }
+        level[0]
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bls12_381::Fr;
+
+    #[test]
+    fn test_verify_path() {
+        let params = PoseidonParams::<Fr>::new();
+        let leaf = Fr::from(42u64);
+        let path: Vec<(Fr, bool)> = (0..4u64).map(|i| (Fr::from(100 + i), i % 2 == 1)).collect();
+        let root = compute_root(&params, leaf, &path);
+
+        let mut cs = Circuit::<Fr>::new(1 << 14);
+        let (root_var, leaf_var) = (cs.alloc(root), cs.alloc(leaf));
+        let nodes = alloc_path(&mut cs, &path);
+        let computed = verify_path(&mut cs, &params, root_var, leaf_var, &nodes);
+        assert_eq!(cs.value(computed), root);
+        assert!(cs.error().is_none());
+        assert!(cs.first_unsatisfied().is_none());
+        assert!(cs.verify_rows() && cs.verify_copy_constraints());
+
+        // Flipping one direction bit leads to another root
+        let mut flipped = path.clone();
+        flipped[2].1 = !flipped[2].1;
+        let mut cs = Circuit::<Fr>::new(1 << 14);
+        let (root_var, leaf_var) = (cs.alloc(root), cs.alloc(leaf));
+        let nodes = alloc_path(&mut cs, &flipped);
+        verify_path(&mut cs, &params, root_var, leaf_var, &nodes);
+        assert_eq!(cs.first_unsatisfied().map(|gate| gate.namespace.as_str()), Some("merkle"));
+    }
+
+    #[test]
+    fn test_hash_tree() {
+        let params = PoseidonParams::<Fr>::new();
+        let leaves: Vec<Fr> = (1..=5u64).map(Fr::from).collect();
+        let root = tree_root(&params, &leaves);
+        // The fifth leaf is paired with zero, and so is its parent
+        let right = tree_root(&params, &[tree_root(&params, &[leaves[4], Fr::from(0u64)]), Fr::from(0u64)]);
+        let path = [(leaves[3], false), (tree_root(&params, &leaves[..2]), true), (right, false)];
+        assert_eq!(compute_root(&params, leaves[2], &path), root);
+
+        let mut cs = Circuit::<Fr>::new(1 << 13);
+        let vars: Vec<Variable> = leaves.iter().map(|leaf| cs.alloc(*leaf)).collect();
+        let computed = hash_tree(&mut cs, &params, &vars);
+        assert_eq!(cs.value(computed), root);
+        assert!(cs.verify_rows() && cs.verify_copy_constraints());
+    }
+}
@@ -0,0 +1,16 @@
+//! Reusable circuit fragments built on `Circuit`
+
+#[cfg(feature = "bw6")]
+pub mod bls12_377;
+pub mod boolean;
+pub mod edwards;
+pub mod elgamal;
+pub mod iterate;
+pub mod merkle;
+pub mod multiexp;
+#[cfg(feature = "verifier")]
+pub mod plonk;
+pub mod embedding;
+pub mod poseidon;
+pub mod range;
+pub mod sha256;
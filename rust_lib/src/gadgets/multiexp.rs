@@ -0,0 +1,223 @@
+//! Σ e_i·P_i over an embedded twisted Edwards curve, the linear
+//! combinations of commitments an in-circuit IPA or accumulator verifier
+//! checks. Scalars come as bits, least significant first, and are cut
+//! into windows of `MultiexpConfig::window_bits`.
+//!
+//! A fixed base, known when the circuit is built, gets a lookup table of
+//! k·2^(w·j)·P for each window j and every k < 2^w, so each window of w
+//! bits costs one lookup and one addition: wider windows mean fewer rows
+//! and larger tables. A variable base has no table to read from and is
+//! added bit by bit, with one doubling per bit shared by every variable
+//! base (Straus's method), so k of them cost about 60% of what k separate
+//! `edwards::scalar_mul`s do.
+
+use alloc::vec::Vec;
+use ark_ec::twisted_edwards::{Affine, TECurveConfig};
+use ark_ec::{AffineRepr, CurveGroup};
+use ark_ff::{AdditiveGroup, Field, PrimeField};
+
+use super::boolean::{assert_boolean, select};
+use super::edwards::{add, constant_point, double, Point};
+use super::range::bit_gate;
+use crate::circuit::{Circuit, CircuitError, Variable};
+use crate::lookup::Table;
+
+/// Largest supported window; a fixed base takes 2^w table rows per window
+pub const MAX_WINDOW_BITS: usize = 8;
+
+/// Window width, the rows against lookups trade-off of `multiexp`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MultiexpConfig {
+    /// Scalar bits per window, 1 to `MAX_WINDOW_BITS`
+    pub window_bits: usize,
+}
+
+impl Default for MultiexpConfig {
+    fn default() -> Self {
+        Self { window_bits: 4 }
+    }
+}
+
+/// The base of one term
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Base<P: TECurveConfig> {
+    /// Known when the circuit is built, read from lookup tables
+    Fixed(Affine<P>),
+    /// A point of the circuit, taken to be on the curve
+    Variable(Point),
+}
+
+/// Σ [e_i]·P_i for `terms` of (bits of e_i, P_i). Every bit is checked
+/// boolean. A fixed base costs w + 12 gates and 2^w table rows per
+/// window of w bits, a variable base 14 gates per bit, and the 9 gates of
+/// doubling per bit are shared by all variable bases. A window outside
+/// 1..=`MAX_WINDOW_BITS` fails the circuit with
+/// `CircuitError::WindowSize`.
+pub fn multiexp<P: TECurveConfig>(cs: &mut Circuit<P::BaseField>, terms: &[(&[Variable], Base<P>)], config: &MultiexpConfig) -> Point
+where
+    P::BaseField: PrimeField,
+{
+    let w = config.window_bits;
+    if !(1..=MAX_WINDOW_BITS).contains(&w) {
+        cs.fail(CircuitError::WindowSize(w));
+        return constant_point::<P>(cs, &Affine::zero());
+    }
+    cs.namespace("multiexp", |cs| {
+        let one = cs.constant(P::BaseField::ONE);
+        let mut acc = Point { x: cs.constant(P::BaseField::ZERO), y: one };
+
+        let bits = terms.iter().filter(|(_, base)| matches!(base, Base::Variable(_))).map(|(bits, _)| bits.len()).max().unwrap_or(0);
+        for i in (0..bits).rev() {
+            if i + 1 < bits {
+                acc = double::<P>(cs, acc);
+            }
+            for (scalar, base) in terms {
+                if let (Some(bit), Base::Variable(point)) = (scalar.get(i), base) {
+                    // point where the bit is set, the identity (0, 1)
+                    // elsewhere; `select` checks the bit
+                    let addend = Point { x: cs.mul(*bit, point.x), y: select(cs, *bit, point.y, one) };
+                    acc = add::<P>(cs, acc, addend);
+                }
+            }
+        }
+
+        for (scalar, base) in terms {
+            if let Base::Fixed(point) = base {
+                let mut shifted = *point;
+                for window in scalar.chunks(w) {
+                    let addend = lookup_multiple::<P>(cs, window, &shifted, w);
+                    acc = add::<P>(cs, acc, addend);
+                    shifted = (0..w).fold(shifted.into_group(), |point, _| point.double()).into_affine();
+                }
+            }
+        }
+        acc
+    })
+}
+
+/// [k]·`point` for the k the bits of `window` spell, looked up in a table
+/// of all 2^w multiples tagged apart from every other table
+fn lookup_multiple<P: TECurveConfig>(cs: &mut Circuit<P::BaseField>, window: &[Variable], point: &Affine<P>, w: usize) -> Point
+where
+    P::BaseField: PrimeField,
+{
+    // Tags count down from −2^32, far from the small values of the other
+    // tables, and start after every row already added
+    let offset = -P::BaseField::from(1u64 << 32) - P::BaseField::from(cs.table.len() as u64);
+    let mut rows = Vec::with_capacity(1 << w);
+    let mut multiple = Affine::<P>::zero().into_group();
+    for k in 0..1u64 << w {
+        let affine = multiple.into_affine();
+        rows.push([offset + P::BaseField::from(k), affine.x, affine.y]);
+        multiple += point;
+    }
+    cs.add_table(Table::new(rows.clone()));
+
+    let k = window_value(cs, window);
+    let offset = cs.constant(offset);
+    let tag = cs.add(k, offset);
+    let row = rows.iter().find(|row| row[0] == cs.value(tag)).unwrap_or(&rows[0]);
+    let (x, y) = (cs.alloc(row[1]), cs.alloc(row[2]));
+    cs.lookup(tag, x, y);
+    Point { x, y }
+}
+
+/// Σ 2^i·window[i] with every bit checked boolean, folded from the top
+/// bit down by `bit_gate` rows as in `range::assert_bits`
+fn window_value<F: PrimeField>(cs: &mut Circuit<F>, window: &[Variable]) -> Variable {
+    let gate = cs.register_gate(bit_gate());
+    let (top, rest) = window.split_last().expect("chunks are nonempty");
+    assert_boolean(cs, *top);
+    rest.iter().rev().fold(*top, |acc, bit| {
+        let next = cs.alloc(cs.value(acc).double() + cs.value(*bit));
+        cs.custom_gate(gate, acc, *bit, next);
+        next
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gadgets::range::assert_bits;
+    use ark_bls12_381::Fr;
+    use crate::gadgets::edwards::alloc_point;
+    use ark_ed_on_bls12_381::{EdwardsAffine, EdwardsConfig, EdwardsProjective, Fr as JubjubScalar};
+    use ark_std::UniformRand;
+
+    #[test]
+    fn test_multiexp_matches_native() {
+        let mut rng = ark_std::test_rng();
+        let points: Vec<EdwardsAffine> = (0..3).map(|_| EdwardsAffine::rand(&mut rng)).collect();
+        let scalars = [200u64, 13, 0x3fff];
+        let expected = points.iter().zip(scalars).map(|(p, e)| *p * JubjubScalar::from(e)).sum::<EdwardsProjective>().into_affine();
+
+        for window_bits in [1, 3, 4] {
+            let mut cs = Circuit::<Fr>::new(1 << 14);
+            let bits: Vec<Vec<Variable>> = scalars
+                .iter()
+                .map(|e| {
+                    let var = cs.alloc(Fr::from(*e));
+                    assert_bits(&mut cs, var, 14)
+                })
+                .collect();
+            let variable = [alloc_point(&mut cs, &points[1]), alloc_point(&mut cs, &points[2])];
+            let terms = [
+                (&bits[0][..], Base::Fixed(points[0])),
+                (&bits[1][..], Base::Variable(variable[0])),
+                (&bits[2][..], Base::Variable(variable[1])),
+            ];
+            let sum = multiexp::<EdwardsConfig>(&mut cs, &terms, &MultiexpConfig { window_bits });
+            assert_eq!((cs.value(sum.x), cs.value(sum.y)), (expected.x, expected.y));
+            assert!(cs.error().is_none());
+            assert!(cs.verify_rows() && cs.verify_copy_constraints());
+        }
+    }
+
+    #[test]
+    fn test_window_costs() {
+        let point = EdwardsAffine::rand(&mut ark_std::test_rng());
+        let gates = |window_bits: usize, variable: bool| {
+            let mut cs = Circuit::<Fr>::new(1 << 12);
+            let x = cs.alloc(Fr::from(1000u64));
+            let bits = assert_bits(&mut cs, x, 12);
+            let base = if variable { Base::Variable(alloc_point(&mut cs, &point)) } else { Base::Fixed(point) };
+            let (start, tables) = (cs.gates.len(), cs.table.len());
+            multiexp::<EdwardsConfig>(&mut cs, &[(&bits[..], base)], &MultiexpConfig { window_bits });
+            (cs.gates.len() - start, cs.table.len() - tables)
+        };
+        // Two constants for the identity, then w + 12 gates per window
+        assert_eq!(gates(1, false), (2 + 12 * 13, 12 * 2));
+        assert_eq!(gates(4, false), (2 + 3 * 16, 3 * 16));
+        assert_eq!(gates(3, false), (2 + 4 * 15, 4 * 8));
+        // 14 gates per bit and 9 per doubling between them
+        assert_eq!(gates(4, true), (2 + 12 * 14 + 11 * 9, 0));
+    }
+
+    #[test]
+    fn test_fixed_bases_and_bad_windows() {
+        let mut rng = ark_std::test_rng();
+        let points: Vec<EdwardsAffine> = (0..2).map(|_| EdwardsAffine::rand(&mut rng)).collect();
+        let mut cs = Circuit::<Fr>::new(1 << 12);
+        let (e, f) = (cs.alloc(Fr::from(0xa5u64)), cs.alloc(Fr::from(0u64)));
+        let (e_bits, f_bits) = (assert_bits(&mut cs, e, 8), assert_bits(&mut cs, f, 8));
+        let terms = [(&e_bits[..], Base::Fixed(points[0])), (&f_bits[..], Base::Fixed(points[1])), (&[][..], Base::Fixed(points[1]))];
+        let sum = multiexp::<EdwardsConfig>(&mut cs, &terms, &MultiexpConfig { window_bits: 3 });
+        let expected = (points[0] * JubjubScalar::from(0xa5u64)).into_affine();
+        assert_eq!((cs.value(sum.x), cs.value(sum.y)), (expected.x, expected.y));
+        // Two tables of 2^3 rows per window, three windows each
+        assert_eq!(cs.table.len(), 2 * 3 * 8);
+        assert!(cs.verify_rows());
+
+        // A forged lookup output is not a row of the table
+        let mut forged = Circuit::<Fr>::new(1 << 12);
+        let bits: Vec<Variable> = [1u64, 0].iter().map(|b| forged.alloc(Fr::from(*b))).collect();
+        multiexp::<EdwardsConfig>(&mut forged, &[(&bits[..], Base::Fixed(points[0]))], &MultiexpConfig { window_bits: 2 });
+        let lookup = forged.gates.iter().position(|gate| gate.gate_type == crate::circuit::GateType::Lookup).unwrap();
+        forged.b[lookup] += Fr::from(1u64);
+        assert!(!forged.verify_rows());
+
+        let mut wide = Circuit::<Fr>::new(16);
+        multiexp::<EdwardsConfig>(&mut wide, &[], &MultiexpConfig { window_bits: MAX_WINDOW_BITS + 1 });
+        assert_eq!(wide.error(), Some(&CircuitError::WindowSize(9)));
+    }
+}
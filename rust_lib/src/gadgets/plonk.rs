@@ -0,0 +1,765 @@
+//! Recursive PLONK verification: a circuit over the scalar field of a
+//! curve checks proofs made on that curve with
+//! `TranscriptHash::Poseidon`, all but the pairing.
+//!
+//! `assign_proof` places a proof in the circuit and replays its
+//! Fiat-Shamir transcript there with `PoseidonTranscript` over
+//! `poseidon::Gates`: the statement, the commitments as the
+//! `commitment_chunks` of their encoding and the evaluations go in, the
+//! challenges come out as circuit values. The key and the session are
+//! absorbed natively, entering as the sponge's starting state.
+//! `verify_deferred` then makes every check of `Verifier::verify` that
+//! lives in the scalar field: ζ^n, the Lagrange values, PI(ζ), the
+//! quotient identity r̄ + r₀ = 0, and the scalars the batched opening
+//! weighs each commitment with.
+//!
+//! The group half, the multi-exponentiation over the commitments and the
+//! pairing, has coordinates in the base field and is deferred.
+//! `expose` makes the commitment chunks and the opening's scalars public
+//! inputs of the outer circuit, so its proof binds them, and whoever
+//! settles the outer proof hands those values with the inner proof to a
+//! `DeferredAccumulator`, which checks the chunks against the proof's
+//! commitments and every opening of every proof in one multi-pairing.
+
+use alloc::{vec, vec::Vec};
+use ark_ec::{AffineRepr, CurveGroup};
+use ark_ff::{FftField, PrimeField};
+use ark_serialize::CanonicalSerialize;
+use ark_std::Zero;
+
+use crate::circuit::{Circuit, CircuitError, Variable};
+use crate::custom_gate::Expression;
+use crate::gadgets::poseidon::Gates;
+use crate::kgz::KZGVerifierKey;
+use crate::lowering::Lowering;
+use crate::msm::{curve_mul, CurvePairing};
+use crate::poseidon::Native;
+use crate::proof::{key_transcript, Oracle, Proof, VerifyingKey, OPENED_AT_ZETA, OPENED_AT_ZETA_OMEGA, PROTOCOL_LABEL};
+use crate::transcript::{byte_chunks, PoseidonTranscript, Transcript, TranscriptHash};
+
+/// Protocol label of the transcript `DeferredAccumulator::settle` draws
+/// its scalars from
+const ACCUMULATOR_LABEL: &[u8] = b"plonk-deferred-openings-v1";
+
+/// The Fiat-Shamir challenges of a proof
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AssignedChallenges {
+    pub theta: Variable,
+    pub beta: Variable,
+    pub gamma: Variable,
+    pub alpha: Variable,
+    pub zeta: Variable,
+    pub v: Variable,
+    pub u: Variable,
+}
+
+/// `ProofEvaluations` as circuit variables
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AssignedEvaluations {
+    pub wires: [Variable; 3],
+    pub sigmas: [Variable; 2],
+    pub z_omega: Variable,
+    pub lookup_selector: Variable,
+    pub table: [Variable; 3],
+    pub permuted: [Variable; 2],
+    pub z_lookup_omega: Variable,
+    pub permuted_input_omega: Variable,
+    pub linearization: Variable,
+}
+
+impl AssignedEvaluations {
+    /// In `OPENED_AT_ZETA` order
+    fn at_zeta(&self) -> [Variable; 11] {
+        let [a, b, c] = self.wires;
+        let [t_1, t_2, t_3] = self.table;
+        [a, b, c, self.sigmas[0], self.sigmas[1], self.lookup_selector, t_1, t_2, t_3, self.permuted[0], self.permuted[1]]
+    }
+
+    /// In `OPENED_AT_ZETA_OMEGA` order
+    fn at_zeta_omega(&self) -> [Variable; 3] {
+        [self.z_omega, self.z_lookup_omega, self.permuted_input_omega]
+    }
+}
+
+/// The field elements of a proof, as `verify_deferred` reads them
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AssignedProof {
+    /// The `commitment_chunks` of the proof, private until `expose`
+    pub commitments: Vec<Variable>,
+    pub challenges: AssignedChallenges,
+    pub evaluations: AssignedEvaluations,
+}
+
+/// The opening check e(L, [s]) = e(R, [1]) left after `verify_deferred`, as the
+/// scalars of
+///
+///   L = W + u·W'
+///   R = ζ·W + uζω·W' + Σ s_o·[o] + g·[1]
+///
+/// over the commitments [o] of `oracles`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeferredOpening<T> {
+    pub u: T,
+    pub w_zeta: T,
+    pub w_zeta_omega: T,
+    /// Each commitment once, with the sum of its scalars, in the order of
+    /// `deferred_oracles`
+    pub oracles: Vec<(Oracle, T)>,
+    pub generator: T,
+}
+
+impl<T: Clone> DeferredOpening<T> {
+    /// u, the weights of W and W', each oracle's scalar, then the
+    /// generator's
+    pub fn scalars(&self) -> Vec<T> {
+        let mut scalars = vec![self.u.clone(), self.w_zeta.clone(), self.w_zeta_omega.clone()];
+        scalars.extend(self.oracles.iter().map(|(_, s)| s.clone()));
+        scalars.push(self.generator.clone());
+        scalars
+    }
+}
+
+impl DeferredOpening<Variable> {
+    /// The values the circuit assigned
+    pub fn values<F: PrimeField>(&self, cs: &Circuit<F>) -> DeferredOpening<F> {
+        DeferredOpening {
+            u: cs.value(self.u),
+            w_zeta: cs.value(self.w_zeta),
+            w_zeta_omega: cs.value(self.w_zeta_omega),
+            oracles: self.oracles.iter().map(|(oracle, s)| (*oracle, cs.value(*s))).collect(),
+            generator: cs.value(self.generator),
+        }
+    }
+}
+
+impl<F: PrimeField> DeferredOpening<F> {
+    /// The opening from its `scalars`, `None` for another number of them
+    /// than the key's oracles take
+    pub fn from_scalars<E: CurvePairing<ScalarField = F>>(vk: &VerifyingKey<E>, scalars: &[F]) -> Option<Self> {
+        let oracles = deferred_oracles(vk);
+        if scalars.len() != oracles.len() + 4 {
+            return None;
+        }
+        let (generator, scalars) = scalars.split_last()?;
+        Some(Self {
+            u: scalars[0],
+            w_zeta: scalars[1],
+            w_zeta_omega: scalars[2],
+            oracles: oracles.into_iter().zip(scalars[3..].iter().copied()).collect(),
+            generator: *generator,
+        })
+    }
+
+    /// L and R from the commitments of `proof` and `vk`, for a pairing
+    /// check or for batching with other openings
+    pub fn pairing_inputs<E: CurvePairing<ScalarField = F>>(&self, vk: &VerifyingKey<E>, proof: &Proof<E>) -> (E::G1, E::G1) {
+        let lhs = proof.w_zeta.into_group() + proof.w_zeta_omega * self.u;
+        let commitments: E::G1 = self.oracles.iter().map(|(oracle, s)| proof.commitment(vk, *oracle) * s).sum();
+        let rhs = proof.w_zeta * self.w_zeta + proof.w_zeta_omega * self.w_zeta_omega + commitments + vk.opening_key.g1 * self.generator;
+        (lhs, rhs)
+    }
+
+    /// Whether the opening holds: e(L, [s]) = e(R, [1])
+    pub fn is_valid<E: CurvePairing<ScalarField = F>>(&self, vk: &VerifyingKey<E>, proof: &Proof<E>) -> bool {
+        let (lhs, rhs) = self.pairing_inputs(vk, proof);
+        E::multi_pairing([lhs, -rhs], [vk.opening_key.g2_s, vk.opening_key.g2]).is_zero()
+    }
+}
+
+/// The commitments `verify_deferred` weighs, in the order its
+/// `DeferredOpening` lists them: the selectors but q_k, z, σ_c, z_L, the
+/// custom selectors and the quotient parts from [r], then the
+/// `OPENED_AT_ZETA` polynomials
+pub fn deferred_oracles<E: CurvePairing>(vk: &VerifyingKey<E>) -> Vec<Oracle> {
+    let mut oracles: Vec<Oracle> = (0..5).map(Oracle::Selector).collect();
+    oracles.extend([Oracle::Z, Oracle::Sigma(2), Oracle::ZLookup]);
+    oracles.extend((0..vk.custom_gates.len()).map(Oracle::CustomSelector));
+    oracles.extend((0..3).map(Oracle::Quotient));
+    oracles.extend(OPENED_AT_ZETA);
+    oracles
+}
+
+/// The commitments of `proof` as its Poseidon transcript absorbs them,
+/// the compressed encoding of each absorbed group in `byte_chunks`: the
+/// wire, lookup, z, z_L and quotient commitments, then W and W'
+pub fn commitment_chunks<E: CurvePairing>(proof: &Proof<E>) -> Vec<E::ScalarField> {
+    let groups: [&[E::G1Affine]; 6] = [
+        &proof.wire_commitments,
+        &proof.lookup_commitments,
+        core::slice::from_ref(&proof.z_commitment),
+        core::slice::from_ref(&proof.z_lookup_commitment),
+        &proof.quotient_commitments,
+        &[proof.w_zeta, proof.w_zeta_omega],
+    ];
+    groups.into_iter().flat_map(|points| byte_chunks(&encode(points))).collect()
+}
+
+/// Compressed encoding of `points` as an array, which has no length prefix
+fn encode<G: CanonicalSerialize>(points: &[G]) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    for point in points {
+        point.serialize_compressed(&mut bytes).expect("writing to a Vec");
+    }
+    bytes
+}
+
+/// How many of `commitment_chunks` each absorbed group takes
+fn chunk_counts<E: CurvePairing>() -> [usize; 6] {
+    let size = E::G1Affine::generator().compressed_size();
+    [3, 2, 1, 1, 3, 2].map(|points: usize| (points * size).div_ceil(31))
+}
+
+/// Places `proof`, made with `TranscriptHash::Poseidon` in `session`, in
+/// the circuit against the statement `public_inputs`: its commitment
+/// chunks and evaluations as private values, and the challenges its
+/// transcript derives from them, replayed in namespace "transcript"
+pub fn assign_proof<E: CurvePairing>(
+    cs: &mut Circuit<E::ScalarField>,
+    vk: &VerifyingKey<E>,
+    session: &[u8],
+    public_inputs: &[Variable],
+    proof: &Proof<E>,
+) -> AssignedProof {
+    let commitments: Vec<Variable> = commitment_chunks(proof).into_iter().map(|x| cs.alloc(x)).collect();
+    let e = &proof.evaluations;
+    let mut alloc = |x: E::ScalarField| cs.alloc(x);
+    let evaluations = AssignedEvaluations {
+        wires: e.wires.map(&mut alloc),
+        sigmas: e.sigmas.map(&mut alloc),
+        z_omega: alloc(e.z_omega),
+        lookup_selector: alloc(e.lookup_selector),
+        table: e.table.map(&mut alloc),
+        permuted: e.permuted.map(&mut alloc),
+        z_lookup_omega: alloc(e.z_lookup_omega),
+        permuted_input_omega: alloc(e.permuted_input_omega),
+        linearization: alloc(e.linearization),
+    };
+
+    let native = key_transcript(vk, session, TranscriptHash::Poseidon)
+        .map(|transcript| transcript.into_poseidon().expect("a transcript started over Poseidon"))
+        .unwrap_or_else(|error| {
+            cs.fail(CircuitError::Schedule(error));
+            PoseidonTranscript::new(Native, PROTOCOL_LABEL)
+        });
+    let counts = chunk_counts::<E>();
+    let challenges = cs.namespace("transcript", |cs| {
+        let mut groups = Vec::new();
+        let mut rest = &commitments[..];
+        for count in counts {
+            let (group, tail) = rest.split_at(count);
+            groups.push(group);
+            rest = tail;
+        }
+        let e = &evaluations;
+        // The steps of `proof::challenges` after the key and session
+        let mut t = native.continue_in(Gates::new(cs));
+        t.absorb(b"public_inputs", public_inputs);
+        t.absorb(b"wire_commitments", groups[0]);
+        let theta = t.squeeze(b"theta");
+        t.absorb(b"lookup_commitments", groups[1]);
+        let beta = t.squeeze(b"beta");
+        let gamma = t.squeeze(b"gamma");
+        t.absorb(b"z_commitment", groups[2]);
+        t.absorb(b"z_lookup_commitment", groups[3]);
+        let alpha = t.squeeze(b"alpha");
+        t.absorb(b"quotient_commitments", groups[4]);
+        let zeta = t.squeeze(b"zeta");
+        t.absorb(b"wire_evaluations", &e.wires);
+        t.absorb(b"sigma_evaluations", &e.sigmas);
+        t.absorb(b"z_omega_evaluation", &[e.z_omega]);
+        t.absorb(b"table_evaluations", &e.table);
+        t.absorb(b"lookup_evaluations", &[e.lookup_selector, e.permuted[0], e.permuted[1]]);
+        t.absorb(b"lookup_omega_evaluations", &[e.z_lookup_omega, e.permuted_input_omega]);
+        t.absorb(b"linearization_evaluation", &[e.linearization]);
+        let v = t.squeeze(b"v");
+        t.absorb(b"opening_proofs", groups[5]);
+        let u = t.squeeze(b"u");
+        AssignedChallenges { theta, beta, gamma, alpha, zeta, v, u }
+    });
+    AssignedProof { commitments, challenges, evaluations }
+}
+
+/// Makes public, after the inputs `cs` already has, what settling
+/// `opening` needs: the commitment chunks of `proof`, then the
+/// opening's `scalars`. An outer proof then binds them, and its verifier
+/// passes them to `DeferredAccumulator::push`.
+pub fn expose<F: PrimeField>(cs: &mut Circuit<F>, proof: &AssignedProof, opening: &DeferredOpening<Variable>) {
+    for var in exposed(proof, opening) {
+        let public = cs.add_public_input(cs.value(var));
+        cs.assert_equal(var, Variable::from(&public));
+    }
+}
+
+/// The values `expose` makes public, for a circuit that commits to them
+/// some other way: the commitment chunks of `proof`, then the scalars of
+/// `opening`
+pub fn exposed(proof: &AssignedProof, opening: &DeferredOpening<Variable>) -> Vec<Variable> {
+    proof.commitments.iter().copied().chain(opening.scalars()).collect()
+}
+
+/// Requires the quotient identity of `proof` to hold at ζ for the circuit
+/// of `vk` and the statement `public_inputs`, and returns the opening
+/// check left to settle with a `DeferredAccumulator`. Runs in namespace "plonk". A key `Verifier`
+/// would reject as malformed fails the circuit with
+/// `CircuitError::MalformedKey`, a statement of the wrong length with
+/// `CircuitError::PublicInputCount`, and ζ in the domain leaves an
+/// inverse unsatisfied.
+pub fn verify_deferred<E: CurvePairing>(
+    cs: &mut Circuit<E::ScalarField>,
+    vk: &VerifyingKey<E>,
+    public_inputs: &[Variable],
+    proof: &AssignedProof,
+) -> DeferredOpening<Variable> {
+    let n = vk.domain_size;
+    let malformed = !vk.is_consistent();
+    let omega = (n >= 2 && n.is_power_of_two() && !malformed).then(|| E::ScalarField::get_root_of_unity(n)).flatten();
+    let Some(omega) = omega else {
+        return failed(cs, CircuitError::MalformedKey);
+    };
+    if public_inputs.len() != vk.public_rows.len() {
+        return failed(cs, CircuitError::PublicInputCount { expected: vk.public_rows.len(), got: public_inputs.len() });
+    }
+    cs.namespace("plonk", |cs| Gadget { l: Lowering::new(cs) }.verify(vk, omega, public_inputs, proof))
+}
+
+/// Fails the circuit with `error`, with zeros for the opening
+fn failed<F: PrimeField>(cs: &mut Circuit<F>, error: CircuitError) -> DeferredOpening<Variable> {
+    cs.fail(error);
+    let zero = cs.constant(F::zero());
+    DeferredOpening { u: zero, w_zeta: zero, w_zeta_omega: zero, oracles: Vec::new(), generator: zero }
+}
+
+/// Field arithmetic for `verify_deferred`, with each constant made once
+struct Gadget<'a, F: PrimeField> {
+    l: Lowering<'a, F>,
+}
+
+impl<F: PrimeField> Gadget<'_, F> {
+    fn constant(&mut self, value: F) -> Variable {
+        self.l.constant(value)
+    }
+
+    fn add(&mut self, a: Variable, b: Variable) -> Variable {
+        self.l.circuit.add(a, b)
+    }
+
+    fn mul(&mut self, a: Variable, b: Variable) -> Variable {
+        self.l.circuit.mul(a, b)
+    }
+
+    fn sum(&mut self, terms: impl IntoIterator<Item = (F, Variable)>, offset: F) -> Variable {
+        self.l.sum(terms, offset)
+    }
+
+    fn product(&mut self, factors: impl IntoIterator<Item = Variable>) -> Variable {
+        let mut factors = factors.into_iter();
+        let first = factors.next().expect("at least one factor");
+        factors.fold(first, |acc, x| self.mul(acc, x))
+    }
+
+    /// The inverse of `a`, witnessed and checked by a·a⁻¹ = 1, which no
+    /// value satisfies for a = 0
+    fn inverse(&mut self, a: Variable) -> Variable {
+        let value = self.l.circuit.value(a).inverse().unwrap_or_default();
+        let inverse = self.l.circuit.alloc(value);
+        let product = self.mul(a, inverse);
+        let one = self.constant(F::one());
+        self.l.circuit.assert_equal(product, one);
+        inverse
+    }
+
+    /// e(a, b, c) term by term, each monomial a product of the wires
+    fn evaluate(&mut self, expression: &Expression<F>, wires: [Variable; 3]) -> Variable {
+        let mut terms = Vec::new();
+        let mut offset = F::zero();
+        for (exponents, coeff) in &expression.terms {
+            let factors: Vec<Variable> = (0..3).flat_map(|j| core::iter::repeat_n(wires[j], exponents[j] as usize)).collect();
+            if factors.is_empty() {
+                offset += coeff;
+            } else {
+                terms.push((*coeff, self.product(factors)));
+            }
+        }
+        self.sum(terms, offset)
+    }
+
+    /// L_i(ζ) = ω^i·Z_H(ζ) / (n·(ζ − ω^i)) for ω^i = `root`
+    fn lagrange(&mut self, n: u64, root: F, zeta: Variable, vanishing: Variable) -> Variable {
+        let n = F::from(n);
+        let denominator = self.sum([(n, zeta)], -n * root);
+        let inverse = self.inverse(denominator);
+        let scaled = self.sum([(root, vanishing)], F::zero());
+        self.mul(scaled, inverse)
+    }
+
+    fn verify<E: CurvePairing<ScalarField = F>>(
+        &mut self,
+        vk: &VerifyingKey<E>,
+        omega: F,
+        public_inputs: &[Variable],
+        proof: &AssignedProof,
+    ) -> DeferredOpening<Variable> {
+        let one = F::one();
+        let (ch, e) = (&proof.challenges, &proof.evaluations);
+        let n = vk.domain_size;
+
+        // ζ^n by squaring, n being a power of two
+        let zeta_n = (0..n.trailing_zeros()).fold(ch.zeta, |x, _| self.mul(x, x));
+        let vanishing = self.sum([(one, zeta_n)], -one);
+        let l1 = self.lagrange(n, one, ch.zeta, vanishing);
+        let l_last = self.lagrange(n, omega.pow([n - 1]), ch.zeta, vanishing);
+
+        // PI(ζ) = −Σ x_j·L_{i_j}(ζ)
+        let mut pi_terms = Vec::new();
+        for (x, row) in public_inputs.iter().zip(&vk.public_rows) {
+            let l = self.lagrange(n, omega.pow([*row]), ch.zeta, vanishing);
+            pi_terms.push((-one, self.mul(*x, l)));
+        }
+        let pi = self.sum(pi_terms, F::zero());
+
+        let identities = vk.custom_gates.iter().map(|gate| gate.identities.len()).sum::<usize>();
+        let mut alphas = vec![ch.alpha];
+        for _ in 1..6 + identities {
+            let next = self.mul(alphas[alphas.len() - 1], ch.alpha);
+            alphas.push(next);
+        }
+
+        // (a + β·σ_a + γ)(b + β·σ_b + γ)·z(ζω)
+        let [a, b, c] = e.wires;
+        let shifted = |cs: &mut Self, w: Variable, sigma: Variable| {
+            let beta_sigma = cs.mul(ch.beta, sigma);
+            cs.sum([(one, w), (one, beta_sigma), (one, ch.gamma)], F::zero())
+        };
+        let first = shifted(self, a, e.sigmas[0]);
+        let second = shifted(self, b, e.sigmas[1]);
+        let prefix = self.product([first, second, e.z_omega]);
+
+        // r₀, as `linearization::constant_term`
+        let [a_p, s_p] = e.permuted;
+        let c_gamma = self.add(c, ch.gamma);
+        let a_beta = self.add(a_p, ch.beta);
+        let s_gamma = self.add(s_p, ch.gamma);
+        let a_s = self.sum([(one, a_p), (-one, s_p)], F::zero());
+        let a_omega = self.sum([(one, a_p), (-one, e.permuted_input_omega)], F::zero());
+        let permutation = self.mul(prefix, c_gamma);
+        let lookup = self.product([e.z_lookup_omega, a_beta, s_gamma]);
+        let last = self.mul(l_last, a_s);
+        let shuffle = self.mul(a_s, a_omega);
+        let constant_terms = [(-one, permutation), (-one, l1), (one, lookup), (-one, l1), (one, last), (one, shuffle)];
+        let mut r0 = vec![(one, e.linearization), (one, pi)];
+        for (alpha, (sign, term)) in alphas.iter().zip(constant_terms) {
+            r0.push((sign, self.mul(*alpha, term)));
+        }
+        let identity = self.sum(r0, F::zero());
+        self.l.circuit.assert_zero(identity);
+
+        // The scalars of [r], as `linearization::linear_terms`
+        let mut oracles: Vec<(Oracle, Variable)> = Vec::new();
+        let ab = self.mul(a, b);
+        let one_var = self.constant(one);
+        oracles.extend([a, b, c, ab, one_var].into_iter().enumerate().map(|(j, s)| (Oracle::Selector(j), s)));
+
+        let mut factors = Vec::new();
+        for (j, w) in e.wires.into_iter().enumerate() {
+            let k_zeta = self.sum([(vk.coset_multipliers[j], ch.zeta)], F::zero());
+            let beta_k_zeta = self.mul(ch.beta, k_zeta);
+            factors.push(self.sum([(one, w), (one, beta_k_zeta), (one, ch.gamma)], F::zero()));
+        }
+        let product = self.product(factors);
+        let z_first = self.mul(alphas[0], product);
+        let z_second = self.mul(alphas[1], l1);
+        oracles.push((Oracle::Z, self.add(z_first, z_second)));
+        let beta_prefix = self.product([alphas[0], ch.beta, prefix]);
+        oracles.push((Oracle::Sigma(2), self.sum([(-one, beta_prefix)], F::zero())));
+
+        // −α³·(A + β)(T + γ) + α⁴·L_1 for z_L, rows compressed with θ
+        let compress = |cs: &mut Self, [x, y, z]: [Variable; 3]| {
+            let theta_z = cs.mul(ch.theta, z);
+            let inner = cs.add(y, theta_z);
+            let theta_inner = cs.mul(ch.theta, inner);
+            cs.add(x, theta_inner)
+        };
+        let table = compress(self, e.table);
+        let wires = compress(self, e.wires);
+        let selected = self.mul(e.lookup_selector, wires);
+        let unselected = self.mul(e.lookup_selector, table);
+        let input = self.sum([(one, selected), (one, table), (-one, unselected)], F::zero());
+        let input_beta = self.add(input, ch.beta);
+        let table_gamma = self.add(table, ch.gamma);
+        let lookup = self.product([alphas[2], input_beta, table_gamma]);
+        let l1_alpha = self.mul(alphas[3], l1);
+        oracles.push((Oracle::ZLookup, self.sum([(-one, lookup), (one, l1_alpha)], F::zero())));
+
+        let mut alpha = alphas[6..].iter();
+        for (g, gate) in vk.custom_gates.iter().enumerate() {
+            let mut terms = Vec::new();
+            for identity in &gate.identities {
+                let value = self.evaluate(identity, e.wires);
+                terms.push((one, self.mul(*alpha.next().expect("a power per identity"), value)));
+            }
+            oracles.push((Oracle::CustomSelector(g), self.sum(terms, F::zero())));
+        }
+
+        let mut power = self.sum([(-one, vanishing)], F::zero());
+        for j in 0..3 {
+            oracles.push((Oracle::Quotient(j), power));
+            if j < 2 {
+                power = self.mul(power, zeta_n);
+            }
+        }
+
+        // v, v², … on the openings at ζ after r, then u·1, u·v, … at ζω
+        let mut value = vec![e.linearization];
+        let mut power = ch.v;
+        for (oracle, eval) in OPENED_AT_ZETA.iter().zip(e.at_zeta()) {
+            self.weigh(&mut oracles, *oracle, power);
+            value.push(self.mul(power, eval));
+            power = self.mul(power, ch.v);
+        }
+        let mut power = ch.u;
+        for (oracle, eval) in OPENED_AT_ZETA_OMEGA.iter().zip(e.at_zeta_omega()) {
+            self.weigh(&mut oracles, *oracle, power);
+            value.push(self.mul(power, eval));
+            power = self.mul(power, ch.v);
+        }
+        let generator = self.sum(value.into_iter().map(|x| (-one, x)), F::zero());
+        let u_zeta = self.mul(ch.u, ch.zeta);
+        let w_zeta_omega = self.sum([(omega, u_zeta)], F::zero());
+        DeferredOpening { u: ch.u, w_zeta: ch.zeta, w_zeta_omega, oracles, generator }
+    }
+
+    /// Adds `scalar` to the one `oracle` has so far
+    fn weigh(&mut self, oracles: &mut Vec<(Oracle, Variable)>, oracle: Oracle, scalar: Variable) {
+        match oracles.iter_mut().find(|(known, _)| *known == oracle) {
+            Some((_, s)) => *s = self.l.circuit.add(*s, scalar),
+            None => oracles.push((oracle, scalar)),
+        }
+    }
+}
+
+/// Why `DeferredAccumulator` refused an opening or failed to settle
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SettleError {
+    /// Exposed values of another length than `expose` makes for the key
+    Length { expected: usize, got: usize },
+    /// Exposed commitment chunks that are not those of the proof
+    Commitments,
+    /// The combined pairing check failed, so at least one opening is
+    /// invalid
+    InvalidOpening,
+}
+
+impl core::fmt::Display for SettleError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            SettleError::Length { expected, got } => write!(f, "expected {} exposed values, got {}", expected, got),
+            SettleError::Commitments => write!(f, "exposed commitments are not the proof's"),
+            SettleError::InvalidOpening => write!(f, "deferred openings do not verify"),
+        }
+    }
+}
+
+/// Openings deferred by `verify_deferred`, settled together. Each one
+/// pushed gives its (L, R), and `settle` checks
+///
+///   e(Σ r_i·L_i, [s]) = e(Σ r_i·R_i, [1])
+///
+/// per distinct SRS in one multi-pairing, as `Verifier::verify_batch`
+/// does. The r_i are squeezed from a transcript of every key and (L, R),
+/// so no opening can be chosen knowing its scalar.
+#[derive(Debug, Clone)]
+pub struct DeferredAccumulator<E: CurvePairing> {
+    openings: Vec<(KZGVerifierKey<E>, E::G1Affine, E::G1Affine)>,
+}
+
+impl<E: CurvePairing> Default for DeferredAccumulator<E> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<E: CurvePairing> DeferredAccumulator<E> {
+    pub fn new() -> Self {
+        Self { openings: Vec::new() }
+    }
+
+    pub fn len(&self) -> usize {
+        self.openings.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.openings.is_empty()
+    }
+
+    /// Adds the opening of `proof` an outer circuit checked, from the
+    /// values `expose` made public there, after checking that the
+    /// commitment chunks among them are those of `proof`
+    pub fn push(&mut self, vk: &VerifyingKey<E>, proof: &Proof<E>, exposed: &[E::ScalarField]) -> Result<(), SettleError> {
+        let chunks = commitment_chunks(proof);
+        let expected = chunks.len() + deferred_oracles(vk).len() + 4;
+        if exposed.len() != expected {
+            return Err(SettleError::Length { expected, got: exposed.len() });
+        }
+        let (commitments, scalars) = exposed.split_at(chunks.len());
+        if commitments != chunks {
+            return Err(SettleError::Commitments);
+        }
+        let opening = DeferredOpening::from_scalars(vk, scalars).expect("as many scalars as the key's oracles take");
+        self.push_opening(vk, proof, &opening);
+        Ok(())
+    }
+
+    /// Adds an opening whose scalars are known to be those of `proof`
+    pub fn push_opening(&mut self, vk: &VerifyingKey<E>, proof: &Proof<E>, opening: &DeferredOpening<E::ScalarField>) {
+        let (lhs, rhs) = opening.pairing_inputs(vk, proof);
+        self.openings.push((vk.opening_key, lhs.into_affine(), rhs.into_affine()));
+    }
+
+    /// Takes in the openings of `other`
+    pub fn extend(&mut self, other: Self) {
+        self.openings.extend(other.openings);
+    }
+
+    /// Checks every opening pushed, in one multi-pairing. Nothing pushed
+    /// settles trivially.
+    pub fn settle(&self) -> Result<(), SettleError> {
+        let mut transcript = Transcript::new(ACCUMULATOR_LABEL);
+        transcript.absorb(b"openings", &(self.openings.len() as u64));
+        for (key, lhs, rhs) in &self.openings {
+            transcript.absorb(b"opening_key", key);
+            transcript.absorb(b"lhs", lhs);
+            transcript.absorb(b"rhs", rhs);
+        }
+
+        // (key, Σ r·L, Σ r·R) per SRS
+        let mut groups: Vec<(KZGVerifierKey<E>, E::G1, E::G1)> = Vec::new();
+        for (key, lhs, rhs) in &self.openings {
+            let r: E::ScalarField = transcript.squeeze(b"r");
+            let (lhs, rhs) = (curve_mul(lhs.into_group(), r), curve_mul(rhs.into_group(), r));
+            match groups.iter_mut().find(|group| group.0.g2 == key.g2 && group.0.g2_s == key.g2_s) {
+                Some(group) => {
+                    group.1 += lhs;
+                    group.2 += rhs;
+                }
+                None => groups.push((*key, lhs, rhs)),
+            }
+        }
+        let g1 = groups.iter().flat_map(|group| [group.1, -group.2]);
+        let g2 = groups.iter().flat_map(|group| [group.0.g2_s, group.0.g2]);
+        if E::multi_pairing(g1, g2).is_zero() {
+            Ok(())
+        } else {
+            Err(SettleError::InvalidOpening)
+        }
+    }
+}
+
+#[cfg(all(test, feature = "prover"))]
+mod tests {
+    use super::*;
+    use crate::bn254::{Fr, Params, ProvingKey};
+    use crate::constraint_system::{ConstraintSystem, Synthesize};
+    use crate::custom_gate::CustomGate;
+    use crate::lookup::Table;
+    use crate::proof::challenges;
+    use crate::prover::{Prover, ProverConfig};
+    use crate::verifier::Verifier;
+
+    /// x³ + x + 5 = 35 with 35 public, a lookup and a custom gate
+    struct Inner;
+
+    impl Synthesize<Fr> for Inner {
+        fn synthesize(&self, circuit: &mut Circuit<Fr>) {
+            let public = Variable::from(&circuit.add_public_input(Fr::from(35u64)));
+            let x = circuit.alloc(Fr::from(3u64));
+            let square = circuit.mul(x, x);
+            let cube = circuit.mul(square, x);
+            let sum = circuit.add(cube, x);
+            let five = circuit.constant(Fr::from(5u64));
+            let out = circuit.add(sum, five);
+            circuit.assert_equal(out, public);
+            circuit.add_table(Table::from_column((0..8u64).map(Fr::from)));
+            let zero = circuit.constant(Fr::from(0u64));
+            circuit.lookup(x, zero, zero);
+            let sbox = circuit.register_gate(CustomGate::quintic_sbox());
+            let (two, four, thirty_two) = (circuit.alloc(Fr::from(2u64)), circuit.alloc(Fr::from(4u64)), circuit.alloc(Fr::from(32u64)));
+            circuit.custom_gate(sbox, two, four, thirty_two);
+        }
+    }
+
+    #[test]
+    fn test_verify_in_circuit() {
+        let mut rng = ark_std::test_rng();
+        let (cs, witness) = ConstraintSystem::synthesize(&Inner, 16).unwrap();
+        let circuit = cs.assign(&witness).unwrap();
+        let pk = ProvingKey::preprocess(Params::setup(40, &mut rng), &circuit).unwrap();
+        let config = ProverConfig { transcript: TranscriptHash::Poseidon, ..Default::default() };
+        let proof = Prover::prove_with_config(&circuit, &pk, &config, &mut rng).unwrap();
+        let statement = [Fr::from(35u64)];
+        assert_eq!(Verifier::verify_with_transcript(&pk.vk, &[], TranscriptHash::Poseidon, &statement, &proof), Ok(()));
+        assert!(Verifier::verify(&pk.vk, &statement, &proof).is_err());
+
+        let mut outer = Circuit::new(1 << 15);
+        let public_inputs = [Variable::from(&outer.add_public_input(statement[0]))];
+        let assigned = assign_proof(&mut outer, &pk.vk, &[], &public_inputs, &proof);
+        let deferred = verify_deferred(&mut outer, &pk.vk, &public_inputs, &assigned);
+        expose(&mut outer, &assigned, &deferred);
+        assert_eq!((outer.error(), outer.first_unsatisfied()), (None, None));
+
+        // The circuit derives the native verifier's challenges
+        let ch = challenges(&pk.vk, &[], TranscriptHash::Poseidon, &statement, &proof).unwrap();
+        let c = assigned.challenges;
+        let derived = [c.theta, c.beta, c.gamma, c.alpha, c.zeta, c.v, c.u].map(|x| outer.value(x));
+        assert_eq!(derived, [ch.theta, ch.beta, ch.gamma, ch.alpha, ch.zeta, ch.v, ch.u]);
+        let oracles: Vec<Oracle> = deferred.oracles.iter().map(|(oracle, _)| *oracle).collect();
+        assert_eq!(oracles, deferred_oracles(&pk.vk));
+
+        // Settled from the outer statement, the opening is the native
+        // verifier's and holds
+        let exposed = &outer.public_input_values()[1..];
+        let opening = DeferredOpening::from_scalars(&pk.vk, &exposed[assigned.commitments.len()..]).unwrap();
+        assert_eq!(opening, deferred.values(&outer));
+        let mut accumulator = DeferredAccumulator::new();
+        accumulator.push(&pk.vk, &proof, exposed).unwrap();
+        assert_eq!(accumulator.settle(), Ok(()));
+
+        // Another proof of the statement settles with it in one pairing
+        let other = Prover::prove_with_config(&circuit, &pk, &config, &mut rng).unwrap();
+        let mut second = Circuit::new(1 << 15);
+        let public_inputs = [Variable::from(&second.add_public_input(statement[0]))];
+        let assigned = assign_proof(&mut second, &pk.vk, &[], &public_inputs, &other);
+        let deferred = verify_deferred(&mut second, &pk.vk, &public_inputs, &assigned);
+        accumulator.push_opening(&pk.vk, &other, &deferred.values(&second));
+        assert_eq!((accumulator.len(), accumulator.settle()), (2, Ok(())));
+
+        // The exposed commitments have to be the proof's, and a forged
+        // opening proof fails the pairing
+        assert_eq!(DeferredAccumulator::new().push(&pk.vk, &other, exposed), Err(SettleError::Commitments));
+        let expected = exposed.len();
+        assert_eq!(
+            DeferredAccumulator::new().push(&pk.vk, &proof, &exposed[1..]),
+            Err(SettleError::Length { expected, got: expected - 1 })
+        );
+        let mut forged = proof.clone();
+        forged.w_zeta = (forged.w_zeta + pk.vk.opening_key.g1).into();
+        let mut accumulator = DeferredAccumulator::new();
+        accumulator.push_opening(&pk.vk, &forged, &opening);
+        accumulator.push_opening(&pk.vk, &other, &deferred.values(&second));
+        assert_eq!(accumulator.settle(), Err(SettleError::InvalidOpening));
+
+        // Another statement, or a proof made over SHA-256, breaks the
+        // quotient identity in the circuit
+        let sha = Prover::prove(&circuit, &pk, &mut rng).unwrap();
+        for (value, proof) in [(36u64, &proof), (35, &sha)] {
+            let mut outer = Circuit::new(1 << 15);
+            let public_inputs = [outer.alloc(Fr::from(value))];
+            let assigned = assign_proof(&mut outer, &pk.vk, &[], &public_inputs, proof);
+            verify_deferred(&mut outer, &pk.vk, &public_inputs, &assigned);
+            assert_eq!(outer.first_unsatisfied().map(|gate| gate.namespace.as_str()), Some("plonk"));
+        }
+
+        let mut outer = Circuit::new(16);
+        verify_deferred(&mut outer, &pk.vk, &[], &assigned);
+        assert_eq!(outer.error(), Some(&CircuitError::PublicInputCount { expected: 1, got: 0 }));
+    }
+}
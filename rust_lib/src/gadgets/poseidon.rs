@@ -0,0 +1,128 @@
+use alloc::collections::BTreeMap;
+use ark_ff::PrimeField;
+
+use crate::circuit::{Circuit, Variable};
+use crate::custom_gate::CustomGate;
+use crate::poseidon::{self, square_and_multiply, Arithmetic, PoseidonParams, WIDTH};
+
+/// `Arithmetic` over a `Circuit`, for the permutation and for
+/// `PoseidonTranscript` in a circuit: add and mul gates, one constant
+/// gate per distinct constant, a weighted gate per two terms of a linear
+/// combination and, for α = 5, one `CustomGate::quintic_sbox` row per
+/// S-box. A permutation takes 474 rows.
+pub struct Gates<'a, F: PrimeField> {
+    cs: &'a mut Circuit<F>,
+    constants: BTreeMap<F, Variable>,
+}
+
+impl<'a, F: PrimeField> Gates<'a, F> {
+    pub fn new(cs: &'a mut Circuit<F>) -> Self {
+        Self { cs, constants: BTreeMap::new() }
+    }
+
+    pub fn circuit(&mut self) -> &mut Circuit<F> {
+        self.cs
+    }
+}
+
+impl<F: PrimeField> Arithmetic<F> for Gates<'_, F> {
+    type Value = Variable;
+
+    fn constant(&mut self, value: F) -> Variable {
+        if let Some(var) = self.constants.get(&value) {
+            return *var;
+        }
+        let var = self.cs.constant(value);
+        self.constants.insert(value, var);
+        var
+    }
+
+    fn add(&mut self, a: &Variable, b: &Variable) -> Variable {
+        self.cs.add(*a, *b)
+    }
+
+    fn mul(&mut self, a: &Variable, b: &Variable) -> Variable {
+        self.cs.mul(*a, *b)
+    }
+
+    fn linear(&mut self, terms: &[(F, Variable)], offset: F) -> Variable {
+        let (zero, one) = (F::zero(), F::one());
+        match terms {
+            [] => self.constant(offset),
+            [single] => self.cs.weighted_sum(*single, (zero, single.1), offset),
+            [first, second, rest @ ..] => {
+                // The offset goes on the last gate
+                let offset_at = |last: bool| if last { offset } else { zero };
+                let mut acc = self.cs.weighted_sum(*first, *second, offset_at(rest.is_empty()));
+                for (i, term) in rest.iter().enumerate() {
+                    acc = self.cs.weighted_sum((one, acc), *term, offset_at(i + 1 == rest.len()));
+                }
+                acc
+            }
+        }
+    }
+
+    fn power(&mut self, x: &Variable, alpha: u64) -> Variable {
+        if alpha != 5 {
+            return square_and_multiply(self, x, alpha);
+        }
+        let sbox = self.cs.register_gate(CustomGate::quintic_sbox());
+        let value = self.cs.value(*x);
+        let square = self.cs.alloc(value.square());
+        let fifth = self.cs.alloc(value.pow([5]));
+        self.cs.custom_gate(sbox, *x, square, fifth);
+        fifth
+    }
+}
+
+/// The Poseidon permutation of `state`, computing exactly what
+/// `poseidon::permute` computes natively
+pub fn permute<F: PrimeField>(cs: &mut Circuit<F>, params: &PoseidonParams<F>, state: [Variable; WIDTH]) -> [Variable; WIDTH] {
+    cs.namespace("poseidon", |cs| {
+        let mut state = state;
+        poseidon::permute(params, &mut Gates::new(cs), &mut state);
+        state
+    })
+}
+
+/// `poseidon::hash` of `inputs`, so the result equals the native digest
+/// of their values
+pub fn hash<F: PrimeField>(cs: &mut Circuit<F>, params: &PoseidonParams<F>, inputs: &[Variable]) -> Variable {
+    cs.namespace("poseidon", |cs| poseidon::hash(params, &mut Gates::new(cs), inputs))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::poseidon::Native;
+    use ark_bls12_381::Fr;
+
+    #[test]
+    fn test_gadget_matches_native() {
+        let params = PoseidonParams::<Fr>::new();
+        let values = [3u64, 1, 4].map(Fr::from);
+        let mut cs = Circuit::<Fr>::new(1 << 13);
+        let inputs = values.map(|x| cs.alloc(x));
+
+        let digest = hash(&mut cs, &params, &inputs);
+        assert_eq!(cs.value(digest), poseidon::hash(&params, &mut Native, &values));
+
+        let mut native = values;
+        poseidon::permute(&params, &mut Native, &mut native);
+        let rows = cs.gates.len();
+        let state = permute(&mut cs, &params, inputs);
+        assert_eq!(cs.gates.len() - rows, 474);
+        assert_eq!(state.map(|var| cs.value(var)), native);
+
+        assert!(cs.error().is_none());
+        assert!(cs.first_unsatisfied().is_none());
+        assert!(cs.verify_rows() && cs.verify_copy_constraints());
+
+        // Out of rows the gates are skipped, but the values still come out
+        let mut small = Circuit::<Fr>::new(16);
+        let inputs = values.map(|x| small.alloc(x));
+        let digest = hash(&mut small, &params, &inputs);
+        assert_eq!(small.value(digest), poseidon::hash(&params, &mut Native, &values));
+        assert_eq!(small.error(), Some(&crate::circuit::CircuitError::Full { capacity: 16 }));
+    }
+}
@@ -0,0 +1,105 @@
+use alloc::{string::ToString, vec, vec::Vec};
+use ark_ff::{BigInteger, PrimeField};
+
+use super::boolean::assert_boolean;
+use crate::circuit::{Circuit, Variable};
+use crate::custom_gate::{CustomGate, Expression};
+
+/// One step of a binary decomposition, read from the most significant
+/// bit down: b is a bit and c = 2a + b
+pub fn bit_gate<F: PrimeField>() -> CustomGate<F> {
+    let (a, b, c) = (Expression::a(), Expression::b(), Expression::c());
+    let two = Expression::constant(F::from(2u64));
+    CustomGate { name: "range_bit".to_string(), identities: vec![b.clone() * b.clone() - b.clone(), two * a + b - c] }
+}
+
+/// Requires `var` < 2^`n_bits` and returns its bits, least significant
+/// first. The top bit is checked by `assert_boolean`, and each lower
+/// one by a `bit_gate` row that also folds it into the running value;
+/// the last row's output is `var` itself. `n_bits` gates in total, one
+/// for `n_bits` = 0.
+pub fn assert_bits<F: PrimeField>(cs: &mut Circuit<F>, var: Variable, n_bits: usize) -> Vec<Variable> {
+    cs.namespace("range", |cs| {
+        if n_bits == 0 {
+            cs.assert_zero(var);
+            return Vec::new();
+        }
+        let value = cs.value(var).into_bigint();
+        let bits: Vec<Variable> =
+            (0..n_bits).map(|i| cs.alloc(if value.get_bit(i) { F::one() } else { F::zero() })).collect();
+
+        let top = bits[n_bits - 1];
+        let (top, acc) = if n_bits == 1 { (var, var) } else { (top, top) };
+        assert_boolean(cs, top);
+
+        let gate = cs.register_gate(bit_gate());
+        let mut acc = acc;
+        for i in (0..n_bits - 1).rev() {
+            let next = if i == 0 {
+                var
+            } else {
+                let value = cs.value(acc).double() + cs.value(bits[i]);
+                cs.alloc(value)
+            };
+            cs.custom_gate(gate, acc, bits[i], next);
+            acc = next;
+        }
+        bits
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bls12_381::Fr;
+
+    #[test]
+    fn test_assert_bits() {
+        let mut cs = Circuit::<Fr>::new(16);
+        let x = cs.alloc(Fr::from(200u64));
+        let bits = assert_bits(&mut cs, x, 8);
+        assert_eq!(bits.iter().map(|b| cs.value(*b)).collect::<Vec<_>>(), [0u64, 0, 0, 1, 0, 0, 1, 1].map(Fr::from));
+        let flag = cs.alloc(Fr::from(1u64));
+        assert_bits(&mut cs, flag, 1);
+        assert_eq!(cs.gates.len(), 9);
+        assert_eq!(cs.custom_gates.len(), 1);
+        assert!(cs.first_unsatisfied().is_none());
+        assert!(cs.verify_rows() && cs.verify_copy_constraints());
+
+        let mut cs = Circuit::<Fr>::new(16);
+        let x = cs.alloc(Fr::from(256u64));
+        assert_bits(&mut cs, x, 8);
+        assert_eq!(cs.first_unsatisfied().map(|gate| gate.namespace.as_str()), Some("range"));
+    }
+
+    #[test]
+    #[cfg(all(feature = "prover", feature = "verifier"))]
+    fn test_range_check_proves() {
+        use crate::constraint_system::{ConstraintSystem, Synthesize};
+        use crate::kgz::KZGParams;
+        use crate::prover::{Prover, ProvingKey};
+        use crate::verifier::Verifier;
+        use ark_bls12_381::Bls12_381;
+
+        /// Private x below 2^4
+        struct Nibble(u64);
+
+        impl Synthesize<Fr> for Nibble {
+            fn synthesize(&self, circuit: &mut Circuit<Fr>) {
+                let x = circuit.alloc(Fr::from(self.0));
+                assert_bits(circuit, x, 4);
+            }
+        }
+
+        let mut rng = ark_std::test_rng();
+        let (cs, witness) = ConstraintSystem::synthesize(&Nibble(11), 4).unwrap();
+        let pk = ProvingKey::<Bls12_381>::preprocess(KZGParams::setup(9, &mut rng), &cs.assign(&witness).unwrap()).unwrap();
+        let proof = Prover::prove(&cs.assign(&witness).unwrap(), &pk, &mut rng).unwrap();
+        assert_eq!(Verifier::verify(&pk.vk, &[], &proof), Ok(()));
+
+        let (_, witness) = ConstraintSystem::synthesize(&Nibble(16), 4).unwrap();
+        let circuit = cs.assign(&witness).unwrap();
+        assert!(!circuit.is_satisfied());
+        assert!(Verifier::verify(&pk.vk, &[], &Prover::prove(&circuit, &pk, &mut rng).unwrap()).is_err());
+    }
+}
@@ -0,0 +1,220 @@
+//! SHA-256 compression in the circuit. Words are carried as 32 boolean
+//! variables, so rotations and shifts only rename wires; xor and the
+//! choose function are one- and three-row custom gate sequences per bit.
+//! Additions mod 2^32 run on packed words and are reduced by a bit
+//! decomposition that drops the carries. Bitwise lookups would need a
+//! tag column to stay apart from other tables, so none are used.
+
+use alloc::{string::ToString, vec, vec::Vec};
+use ark_ff::PrimeField;
+
+use super::boolean::difference_gate;
+use super::range::{assert_bits, bit_gate};
+use crate::circuit::{Circuit, Variable};
+use crate::custom_gate::{CustomGate, Expression};
+
+/// Initial hash value H(0)
+pub const IV: [u32; 8] = [
+    0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+];
+
+const K: [u32; 64] = [
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+    0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+    0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+    0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+    0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+    0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+    0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+    0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+];
+
+/// c = a xor b for bits a and b, as a + b − 2ab
+pub fn xor_gate<F: PrimeField>() -> CustomGate<F> {
+    let (a, b, c) = (Expression::a(), Expression::b(), Expression::c());
+    let two = Expression::constant(F::from(2u64));
+    CustomGate { name: "xor".to_string(), identities: vec![a.clone() + b.clone() - two * a * b - c] }
+}
+
+/// A word as its bits, least significant first
+type Word = [Variable; 32];
+
+/// Gadget state shared by one compression: the gate ids and a zero bit
+/// for the shifts
+struct Sha256<'a, F: PrimeField> {
+    cs: &'a mut Circuit<F>,
+    xor: usize,
+    difference: usize,
+    bit: usize,
+    zero: Variable,
+}
+
+impl<F: PrimeField> Sha256<'_, F> {
+    fn bit(&mut self, value: bool) -> Variable {
+        self.cs.alloc(if value { F::one() } else { F::zero() })
+    }
+
+    fn xor(&mut self, a: Variable, b: Variable) -> Variable {
+        let value = self.cs.value(a) != self.cs.value(b);
+        let c = self.bit(value);
+        self.cs.custom_gate(self.xor, a, b, c);
+        c
+    }
+
+    /// `f` where `e` is set and `g` elsewhere, as e·(f − g) + g
+    fn choose(&mut self, e: Variable, f: Variable, g: Variable) -> Variable {
+        let diff = self.cs.alloc(self.cs.value(f) - self.cs.value(g));
+        self.cs.custom_gate(self.difference, f, g, diff);
+        let scaled = self.cs.mul(e, diff);
+        self.cs.add(scaled, g)
+    }
+
+    fn xor3(&mut self, a: &Word, b: &Word, c: &Word) -> Word {
+        core::array::from_fn(|i| {
+            let ab = self.xor(a[i], b[i]);
+            self.xor(ab, c[i])
+        })
+    }
+
+    fn ch(&mut self, e: &Word, f: &Word, g: &Word) -> Word {
+        core::array::from_fn(|i| self.choose(e[i], f[i], g[i]))
+    }
+
+    /// Where a and b differ the majority is c, elsewhere a
+    fn maj(&mut self, a: &Word, b: &Word, c: &Word) -> Word {
+        core::array::from_fn(|i| {
+            let differ = self.xor(a[i], b[i]);
+            self.choose(differ, c[i], a[i])
+        })
+    }
+
+    fn shr(&self, word: &Word, n: usize) -> Word {
+        core::array::from_fn(|i| if i + n < 32 { word[i + n] } else { self.zero })
+    }
+
+    /// Σ of three rotations, or two rotations and a shift for σ
+    fn sigma(&mut self, word: &Word, [r1, r2, r3]: [usize; 3], shift: bool) -> Word {
+        let (a, b) = (rotr(word, r1), rotr(word, r2));
+        let c = if shift { self.shr(word, r3) } else { rotr(word, r3) };
+        self.xor3(&a, &b, &c)
+    }
+
+    /// The packed value Σ 2^i·bits[i], folding from the top bit down with
+    /// one `bit_gate` row per lower bit
+    fn pack(&mut self, word: &Word) -> Variable {
+        let mut acc = word[31];
+        for &bit in word[..31].iter().rev() {
+            let next = self.cs.alloc(self.cs.value(acc).double() + self.cs.value(bit));
+            self.cs.custom_gate(self.bit, acc, bit, next);
+            acc = next;
+        }
+        acc
+    }
+
+    /// The bits of `var` mod 2^32, where `var` < 2^(32 + carry_bits)
+    fn unpack(&mut self, var: Variable, carry_bits: usize) -> Word {
+        let bits = assert_bits(self.cs, var, 32 + carry_bits);
+        core::array::from_fn(|i| bits[i])
+    }
+
+    fn sum(&mut self, terms: &[Variable]) -> Variable {
+        terms[1..].iter().fold(terms[0], |acc, term| self.cs.add(acc, *term))
+    }
+}
+
+fn rotr(word: &Word, n: usize) -> Word {
+    core::array::from_fn(|i| word[(i + n) % 32])
+}
+
+/// One SHA-256 compression of `block` into `state`, both as 32-bit words
+/// in packed variables. Every input word is range checked here, and the
+/// new state comes back packed the same way.
+pub fn compress<F: PrimeField>(cs: &mut Circuit<F>, state: [Variable; 8], block: [Variable; 16]) -> [Variable; 8] {
+    cs.namespace("sha256", |cs| {
+        let (xor, difference, bit) = (cs.register_gate(xor_gate()), cs.register_gate(difference_gate()), cs.register_gate(bit_gate()));
+        let zero = cs.constant(F::zero());
+        let mut sha = Sha256 { cs, xor, difference, bit, zero };
+
+        // Message schedule, each word as bits and packed
+        let mut w: Vec<(Word, Variable)> = block.iter().map(|word| (sha.unpack(*word, 0), *word)).collect();
+        for t in 16..64 {
+            let s0 = sha.sigma(&w[t - 15].0, [7, 18, 3], true);
+            let s1 = sha.sigma(&w[t - 2].0, [17, 19, 10], true);
+            let terms = [w[t - 16].1, sha.pack(&s0), w[t - 7].1, sha.pack(&s1)];
+            let total = sha.sum(&terms);
+            let word = sha.unpack(total, 2);
+            let packed = sha.pack(&word);
+            w.push((word, packed));
+        }
+
+        let initial: Vec<Word> = state.iter().map(|word| sha.unpack(*word, 0)).collect();
+        let mut v: [Word; 8] = core::array::from_fn(|i| initial[i]);
+        for ((_, w_t), k) in w.iter().zip(K) {
+            let [a, b, c, d, e, f, g, h] = v;
+            let s1 = sha.sigma(&e, [6, 11, 25], false);
+            let ch = sha.ch(&e, &f, &g);
+            let k = sha.cs.constant(F::from(k));
+            let terms = [sha.pack(&h), sha.pack(&s1), sha.pack(&ch), k, *w_t];
+            let temp = sha.sum(&terms);
+
+            let s0 = sha.sigma(&a, [2, 13, 22], false);
+            let maj = sha.maj(&a, &b, &c);
+            let terms = [sha.pack(&d), temp];
+            let new_e = sha.sum(&terms);
+            let terms = [temp, sha.pack(&s0), sha.pack(&maj)];
+            let new_a = sha.sum(&terms);
+            v = [sha.unpack(new_a, 3), a, b, c, sha.unpack(new_e, 3), e, f, g];
+        }
+
+        core::array::from_fn(|i| {
+            let terms = [state[i], sha.pack(&v[i])];
+            let total = sha.sum(&terms);
+            let word = sha.unpack(total, 1);
+            sha.pack(&word)
+        })
+    })
+}
+
+/// `IV` as constant variables, the state `compress` starts from
+pub fn initial_state<F: PrimeField>(cs: &mut Circuit<F>) -> [Variable; 8] {
+    IV.map(|word| cs.constant(F::from(word)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bls12_381::Fr;
+    use sha2::Digest;
+
+    /// "abc" padded to one block
+    fn abc_block() -> [u32; 16] {
+        let mut block = [0u32; 16];
+        block[0] = 0x61626380;
+        block[15] = 24;
+        block
+    }
+
+    #[test]
+    fn test_compress_matches_sha256() {
+        let mut cs = Circuit::<Fr>::new(1 << 16);
+        let state = initial_state(&mut cs);
+        let block = abc_block().map(|word| cs.alloc(Fr::from(word)));
+        let digest = compress(&mut cs, state, block);
+
+        let expected = sha2::Sha256::digest(b"abc");
+        let words: Vec<Fr> = expected.chunks(4).map(|word| Fr::from(u32::from_be_bytes(word.try_into().unwrap()))).collect();
+        assert_eq!(digest.map(|var| cs.value(var)).to_vec(), words);
+
+        assert!(cs.error().is_none());
+        assert!(cs.first_unsatisfied().is_none());
+        assert!(cs.verify_rows() && cs.verify_copy_constraints());
+
+        // A block word of 33 bits fails its range check
+        let mut cs = Circuit::<Fr>::new(1 << 16);
+        let state = initial_state(&mut cs);
+        let mut block = abc_block().map(|word| cs.alloc(Fr::from(word)));
+        block[3] = cs.alloc(Fr::from(1u64 << 32));
+        compress(&mut cs, state, block);
+        assert_eq!(cs.first_unsatisfied().map(|gate| gate.namespace.as_str()), Some("sha256/range"));
+    }
+}
@@ -0,0 +1,131 @@
+//! The permutation argument's grand product z, in evaluation form over H.
+//!
+//! Every cell (j, i) is labelled k_j·ω^i and mapped by σ to the label of
+//! the cell it is copied to. z accumulates, row by row, the ratio of the
+//! wires shifted by their own labels to the wires shifted by their
+//! images' labels:
+//!
+//!   z(ω^0) = 1
+//!   z(ω^{i+1}) = z(ω^i) · Π_j (w_j + β·k_j·ω^i + γ) / Π_j (w_j + β·σ_j(ω^i) + γ)
+//!
+//! When the copy constraints hold, the numerators and denominators over
+//! all rows are the same multiset and the product comes back to 1 after
+//! row n − 1, which is what lets z(ωX) wrap around to z(ω^0).
+
+use alloc::vec::Vec;
+use ark_ff::{batch_inversion, PrimeField};
+
+use crate::store::PolyStore;
+
+/// z over H, from the wire values, σ over H, the column multipliers k_j
+/// and the challenges β, γ. One batch inversion covers every row's
+/// denominator.
+pub(crate) fn grand_product<F: PrimeField>(
+    wires: [&dyn PolyStore<F>; 3],
+    sigma_evals: &[Vec<F>; 3],
+    ks: &[F; 3],
+    omega: F,
+    beta: F,
+    gamma: F,
+) -> Vec<F> {
+    let (numerators, mut denominators) = row_ratios(wires, sigma_evals, ks, omega, beta, gamma);
+    batch_inversion(&mut denominators);
+
+    let n = numerators.len();
+    let mut z = Vec::with_capacity(n);
+    let mut acc = F::one();
+    for i in 0..n {
+        z.push(acc);
+        acc *= numerators[i] * denominators[i];
+    }
+    z
+}
+
+/// Π_j (w_j + β·k_j·ω^i + γ) and Π_j (w_j + β·σ_j(ω^i) + γ) for each row i
+fn row_ratios<F: PrimeField>(
+    wires: [&dyn PolyStore<F>; 3],
+    sigma_evals: &[Vec<F>; 3],
+    ks: &[F; 3],
+    omega: F,
+    beta: F,
+    gamma: F,
+) -> (Vec<F>, Vec<F>) {
+    let n = wires[0].len();
+    let mut numerators = Vec::with_capacity(n);
+    let mut denominators = Vec::with_capacity(n);
+    let mut x = F::one();
+    for i in 0..n {
+        let mut num = F::one();
+        let mut den = F::one();
+        for ((wire, sigma), k) in wires.iter().zip(sigma_evals).zip(ks) {
+            let w = wire.get(i);
+            num *= w + beta * *k * x + gamma;
+            den *= w + beta * sigma[i] + gamma;
+        }
+        numerators.push(num);
+        denominators.push(den);
+        x *= omega;
+    }
+    (numerators, denominators)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bls12_381::Fr;
+    use ark_ff::Field;
+    use ark_std::{One, UniformRand};
+
+    use crate::permutation::{coset_multipliers, Cell, PermutationBuilder};
+    use crate::prover::root_of_unity;
+    use crate::store::MemoryStore;
+
+    /// σ over H for two rows with a(ω^1) copied from c(ω^0)
+    fn sigmas(omega: Fr, ks: &[Fr; 3]) -> [Vec<Fr>; 3] {
+        let mut builder = PermutationBuilder::new(3, 2);
+        builder.connect(Cell::new(2, 0), Cell::new(0, 1)).unwrap();
+        builder.build().sigma_evaluations(omega, ks).unwrap().try_into().unwrap()
+    }
+
+    #[test]
+    fn test_hand_computed_grand_product() {
+        let omega = root_of_unity::<Fr>(2);
+        assert_eq!(omega, -Fr::one());
+        let ks: [Fr; 3] = coset_multipliers::<Fr>(3, 2).try_into().unwrap();
+        let sigma = sigmas(omega, &ks);
+        // The copy swaps the labels of (2, 0) and (0, 1): k_2·ω^0 and ω
+        assert_eq!((sigma[0][1], sigma[2][0]), (ks[2], omega));
+
+        let f = |values: [u64; 2]| MemoryStore::from_vec(values.map(Fr::from).to_vec());
+        let (a, b, c) = (f([2, 5]), f([3, 3]), f([5, 15]));
+        let mut rng = ark_std::test_rng();
+        let (beta, gamma) = (Fr::rand(&mut rng), Fr::rand(&mut rng));
+        let z = grand_product([&a, &b, &c], &sigma, &ks, omega, beta, gamma);
+
+        // Row 0 differs from the identity only in c, whose label became ω
+        let row_0 = (c.get(0) + beta * ks[2] + gamma) / (c.get(0) + beta * omega + gamma);
+        assert_eq!(z, vec![Fr::one(), row_0]);
+        // and row 1 only in a, closing the product
+        let row_1 = (a.get(1) + beta * omega + gamma) / (a.get(1) + beta * ks[2] + gamma);
+        assert_eq!(z[1] * row_1, Fr::one());
+    }
+
+    #[test]
+    fn test_broken_copy_does_not_close() {
+        let omega = root_of_unity::<Fr>(2);
+        let ks: [Fr; 3] = coset_multipliers::<Fr>(3, 2).try_into().unwrap();
+        let sigma = sigmas(omega, &ks);
+        let f = |values: [u64; 2]| MemoryStore::from_vec(values.map(Fr::from).to_vec());
+        let mut rng = ark_std::test_rng();
+        let (beta, gamma) = (Fr::rand(&mut rng), Fr::rand(&mut rng));
+
+        for (a, closes) in [(f([2, 5]), true), (f([2, 6]), false)] {
+            let (b, c) = (f([3, 3]), f([5, 15]));
+            let (numerators, denominators) = row_ratios([&a, &b, &c], &sigma, &ks, omega, beta, gamma);
+            let z = grand_product([&a, &b, &c], &sigma, &ks, omega, beta, gamma);
+            let last = numerators[1] * denominators[1].inverse().unwrap();
+            assert_eq!(z[0], Fr::one());
+            assert_eq!(z[1] * last == Fr::one(), closes);
+        }
+    }
+}
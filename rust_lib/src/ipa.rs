@@ -0,0 +1,275 @@
+//! Inner product argument commitments, the Bulletproofs argument as Halo
+//! uses it for polynomials. A commitment is the Pedersen vector
+//! commitment Σ a_i·G_i to the coefficients, and an opening at z proves
+//! ⟨a, (1, z, z², …)⟩ = p(z) in log2(n) rounds of halving.
+//!
+//! The generators are hashed to the curve, so nobody knows a relation
+//! between them and there is no trusted setup. The price is a proof of
+//! 2·log2(n) points and a verifier doing O(n) group operations, against
+//! KZG's one point and one pairing.
+
+use alloc::vec::Vec;
+use ark_ec::short_weierstrass::{Affine, Projective, SWCurveConfig};
+use ark_ec::{AffineRepr, CurveGroup};
+use ark_ff::{Field, PrimeField};
+use ark_poly::polynomial::univariate::DensePolynomial;
+use ark_poly::polynomial::DenseUVPolynomial;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_std::Zero;
+use sha2::{Digest, Sha256};
+
+use crate::kgz::{batch_challenge, CommitError};
+use crate::msm::{msm, CurveMul, MsmConfig};
+use crate::pcs::{combine, Opening, PolynomialCommitment};
+use crate::transcript::Transcript;
+
+/// Generators for coefficient vectors up to a power-of-two length
+#[derive(Debug, Clone, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct IpaParams<G: CurveGroup> {
+    /// G_i, one per coefficient
+    pub generators: Vec<G::Affine>,
+    /// U, which binds the inner product claimed in an opening
+    pub u: G::Affine,
+}
+
+/// Opening of an `IpaParams` commitment: the cross terms L_j, R_j of each
+/// round and the one coefficient left at the end
+#[derive(Debug, Clone, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct IpaProof<G: CurveGroup> {
+    pub l: Vec<G::Affine>,
+    pub r: Vec<G::Affine>,
+    pub a: G::ScalarField,
+}
+
+impl<P: SWCurveConfig> IpaParams<Projective<P>> {
+    /// Generators for polynomials of degree up to `degree`, rounded up to
+    /// one less than a power of two, hashed from `label`
+    pub fn new(label: &[u8], degree: usize) -> Self {
+        let n = (degree + 1).next_power_of_two();
+        Self {
+            generators: (0..n as u64).map(|i| hash_to_curve(label, i)).collect(),
+            u: hash_to_curve(label, u64::MAX),
+        }
+    }
+}
+
+impl<G: CurveGroup> IpaParams<G> {
+    /// Σ a_i·G_i
+    pub fn commit(&self, poly: &DensePolynomial<G::ScalarField>) -> Result<G::Affine, CommitError> {
+        let coeffs = poly.coeffs();
+        if coeffs.len() > self.generators.len() {
+            return Err(CommitError::SrsTooSmall { needed: coeffs.len(), powers: self.generators.len() });
+        }
+        Ok(msm::<G>(&self.generators[..coeffs.len()], coeffs, &MsmConfig::default())
+            .expect("bases sliced to the coefficients")
+            .into_affine())
+    }
+
+    /// Halves a and b = (1, z, z², …) together with the generators each
+    /// round, committing to the cross terms on the way
+    pub fn open(&self, poly: &DensePolynomial<G::ScalarField>, z: G::ScalarField) -> Result<(IpaProof<G>, G::ScalarField), CommitError> {
+        let commitment = self.commit(poly)?;
+        let mut a = poly.coeffs().to_vec();
+        a.resize(self.generators.len(), G::ScalarField::zero());
+        let mut b = powers(z, a.len());
+        let value = inner_product(&a, &b);
+        let (mut transcript, u) = self.transcript(&commitment, z, value);
+
+        let mut g: Vec<G> = self.generators.iter().map(|point| point.into_group()).collect();
+        let (mut l, mut r) = (Vec::new(), Vec::new());
+        while a.len() > 1 {
+            let half = a.len() / 2;
+            let bases = G::normalize_batch(&g);
+            let cross = |bases: &[G::Affine], scalars: &[G::ScalarField], product: G::ScalarField| {
+                msm::<G>(bases, scalars, &MsmConfig::default()).expect("halves of one length") + u * product
+            };
+            let l_j = cross(&bases[half..], &a[..half], inner_product(&a[..half], &b[half..])).into_affine();
+            let r_j = cross(&bases[..half], &a[half..], inner_product(&a[half..], &b[..half])).into_affine();
+            let (x, x_inv) = challenge(&mut transcript, &l_j, &r_j).expect("a hashed challenge is nonzero");
+            a = fold(&a, x, x_inv);
+            b = fold(&b, x_inv, x);
+            g = fold(&g, x_inv, x);
+            l.push(l_j);
+            r.push(r_j);
+        }
+        Ok((IpaProof { l, r, a: a[0] }, value))
+    }
+
+    /// Folds C + p(z)·U with every round's L_j·x_j² + R_j·x_j⁻² and
+    /// checks the result against the folded generator and b
+    pub fn verify(&self, commitment: &G::Affine, proof: &IpaProof<G>, z: G::ScalarField, value: G::ScalarField) -> bool {
+        let rounds = self.generators.len().trailing_zeros() as usize;
+        if proof.l.len() != rounds || proof.r.len() != rounds {
+            return false;
+        }
+        let (mut transcript, u) = self.transcript(commitment, z, value);
+        let mut q = commitment.into_group() + u * value;
+        let mut g: Vec<G> = self.generators.iter().map(|point| point.into_group()).collect();
+        let mut b = powers(z, g.len());
+        for (l_j, r_j) in proof.l.iter().zip(&proof.r) {
+            let Some((x, x_inv)) = challenge(&mut transcript, l_j, r_j) else {
+                return false;
+            };
+            q += *l_j * x.square() + *r_j * x_inv.square();
+            b = fold(&b, x_inv, x);
+            g = fold(&g, x_inv, x);
+        }
+        q == g[0] * proof.a + u * (proof.a * b[0])
+    }
+
+    /// Transcript bound to the claim, and U scaled by a challenge so the
+    /// prover cannot pick the inner product term
+    fn transcript(&self, commitment: &G::Affine, z: G::ScalarField, value: G::ScalarField) -> (Transcript, G) {
+        let mut transcript = Transcript::new(b"plonk-ipa");
+        transcript.absorb(b"u", &self.u);
+        transcript.absorb(b"commitment", commitment);
+        transcript.absorb(b"z", &z);
+        transcript.absorb(b"value", &value);
+        let scale: G::ScalarField = transcript.squeeze(b"u_scale");
+        (transcript, self.u * scale)
+    }
+}
+
+impl<G: CurveMul> PolynomialCommitment<G::ScalarField> for IpaParams<G> {
+    type Commitment = G::Affine;
+    type Proof = IpaProof<G>;
+    /// The generators, which the linear-time verifier folds
+    type VerifierKey = Self;
+
+    fn max_degree(&self) -> Option<usize> {
+        self.generators.len().checked_sub(1)
+    }
+
+    fn verifier_key(&self) -> Self {
+        self.clone()
+    }
+
+    fn commit(&self, poly: &DensePolynomial<G::ScalarField>) -> Result<G::Affine, CommitError> {
+        IpaParams::commit(self, poly)
+    }
+
+    fn open(&self, poly: &DensePolynomial<G::ScalarField>, z: G::ScalarField) -> Result<(IpaProof<G>, G::ScalarField), CommitError> {
+        IpaParams::open(self, poly, z)
+    }
+
+    fn verify(&self, commitment: &G::Affine, proof: &IpaProof<G>, z: G::ScalarField, value: G::ScalarField) -> bool {
+        IpaParams::verify(self, commitment, proof, z, value)
+    }
+
+    fn combine(commitments: &[G::Affine], scalars: &[G::ScalarField]) -> G::Affine {
+        combine::<G>(commitments, scalars)
+    }
+
+    fn verify_opening(key: &Self, opening: &Opening<G::ScalarField, Self>) -> bool {
+        key.verify(&opening.commitment, opening.proof, opening.z, opening.value)
+    }
+
+    /// Opens Σ v^i·polys[i], which the commitments fold to linearly
+    fn open_batch(
+        &self,
+        polys: &[DensePolynomial<G::ScalarField>],
+        z: G::ScalarField,
+        transcript: &mut Transcript,
+    ) -> Result<(IpaProof<G>, Vec<G::ScalarField>), CommitError> {
+        let values: Vec<G::ScalarField> = polys.iter().map(|poly| ark_poly::Polynomial::evaluate(poly, &z)).collect();
+        let v = batch_challenge(transcript, z, &values);
+        let mut combined = DensePolynomial::zero();
+        for poly in polys.iter().rev() {
+            combined = &(&combined * v) + poly;
+        }
+        let (proof, _) = self.open(&combined, z)?;
+        Ok((proof, values))
+    }
+
+    fn verify_batch(
+        &self,
+        commitments: &[G::Affine],
+        proof: &IpaProof<G>,
+        z: G::ScalarField,
+        values: &[G::ScalarField],
+        transcript: &mut Transcript,
+    ) -> bool {
+        if commitments.len() != values.len() {
+            return false;
+        }
+        let v = batch_challenge(transcript, z, values);
+        let (mut commitment, mut value) = (G::zero(), G::ScalarField::zero());
+        for (c, y) in commitments.iter().zip(values).rev() {
+            commitment = commitment * v + c;
+            value = value * v + y;
+        }
+        self.verify(&commitment.into_affine(), proof, z, value)
+    }
+}
+
+/// A point of the prime-order subgroup from SHA-256(label ‖ index ‖
+/// counter), trying counters until the hash is the x of a point
+fn hash_to_curve<P: SWCurveConfig>(label: &[u8], index: u64) -> Affine<P> {
+    (0u64..)
+        .find_map(|counter| {
+            let digest = Sha256::new().chain_update(label).chain_update(index.to_le_bytes()).chain_update(counter.to_le_bytes()).finalize();
+            let x = <P::BaseField as Field>::BasePrimeField::from_le_bytes_mod_order(&digest);
+            let point = Affine::<P>::get_point_from_x_unchecked(P::BaseField::from_base_prime_field(x), digest[31] & 1 == 1)?;
+            Some(point.clear_cofactor()).filter(|point| !point.is_zero())
+        })
+        .expect("about half of all x are on the curve")
+}
+
+fn powers<F: Field>(z: F, n: usize) -> Vec<F> {
+    core::iter::successors(Some(F::one()), |power| Some(*power * z)).take(n).collect()
+}
+
+fn inner_product<F: Field>(a: &[F], b: &[F]) -> F {
+    a.iter().zip(b).map(|(a, b)| *a * b).sum()
+}
+
+/// lo·lo_scale + hi·hi_scale for the halves of `v`
+fn fold<T, F>(v: &[T], lo_scale: F, hi_scale: F) -> Vec<T>
+where
+    T: Copy + core::ops::Mul<F, Output = T> + core::ops::Add<Output = T>,
+    F: Copy,
+{
+    let (lo, hi) = v.split_at(v.len() / 2);
+    lo.iter().zip(hi).map(|(lo, hi)| *lo * lo_scale + *hi * hi_scale).collect()
+}
+
+/// The round challenge x and its inverse, after absorbing L and R
+fn challenge<A: AffineRepr>(transcript: &mut Transcript, l: &A, r: &A) -> Option<(A::ScalarField, A::ScalarField)> {
+    transcript.absorb(b"l", l);
+    transcript.absorb(b"r", r);
+    let x: A::ScalarField = transcript.squeeze(b"x");
+    Some((x, x.inverse()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bls12_381::{Fr, G1Projective};
+
+    #[test]
+    fn test_ipa_opening() {
+        let params = IpaParams::<G1Projective>::new(b"plonk-ipa-test", 12);
+        assert_eq!(params.generators.len(), 16);
+        assert_eq!(params, IpaParams::new(b"plonk-ipa-test", 15));
+        assert_ne!(params.generators[0], params.generators[1]);
+        assert!(params.generators.iter().all(|point| point.is_on_curve() && point.is_in_correct_subgroup_assuming_on_curve()));
+
+        let poly = DensePolynomial::rand(12, &mut ark_std::test_rng());
+        let commitment = params.commit(&poly).unwrap();
+        let z = Fr::from(3u64);
+        let (proof, value) = params.open(&poly, z).unwrap();
+        assert_eq!((proof.l.len(), proof.r.len()), (4, 4));
+        assert!(params.verify(&commitment, &proof, z, value));
+
+        let mut swapped = proof.clone();
+        swapped.l.swap(0, 1);
+        assert!(!params.verify(&commitment, &swapped, z, value));
+        let mut short = proof.clone();
+        short.l.pop();
+        short.r.pop();
+        assert!(!params.verify(&commitment, &short, z, value));
+        // Generators under another label open nothing committed here
+        let other = IpaParams::<G1Projective>::new(b"another label", 12);
+        assert!(!other.verify(&commitment, &proof, z, value));
+    }
+}
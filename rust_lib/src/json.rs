@@ -0,0 +1,344 @@
+//! Serde support for proofs and constraint systems, for web clients and
+//! JSON APIs.
+//!
+//! A proof is an object with one member per field of `Proof` and a
+//! `version`. Every point and scalar is a "0x"-prefixed hex string of its
+//! canonical compressed encoding, the same bytes `Proof::to_bytes` writes
+//! for it, so a value can be moved between the two representations
+//! without touching the curve. Decoding checks every point as `from_bytes`
+//! does.
+//!
+//! A `ConstraintSystem` is laid out the same way, with its selector
+//! columns as arrays of hex scalars, σ as `[column, row]` pairs per column
+//! and each custom gate as the hex of its canonical encoding. Decoding
+//! applies the checks the binary artifact does.
+
+use alloc::{format, string::String, vec::Vec};
+use ark_ff::PrimeField;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, Valid};
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::circuit::CircuitSelectors;
+use crate::constraint_system::ConstraintSystem;
+use crate::custom_gate::CustomGate;
+use crate::msm::CurvePairing;
+use crate::permutation::{Cell, Permutation};
+use crate::proof::{Proof, ProofEvaluations};
+
+/// Layout of the JSON objects; bumped whenever a member is added, removed
+/// or changes meaning
+pub const JSON_VERSION: u32 = 1;
+
+/// "0x" followed by two lowercase digits per byte
+pub fn to_hex(bytes: &[u8]) -> String {
+    const DIGITS: &[u8; 16] = b"0123456789abcdef";
+    let mut out = String::with_capacity(2 + 2 * bytes.len());
+    out.push_str("0x");
+    for byte in bytes {
+        out.push(DIGITS[(byte >> 4) as usize] as char);
+        out.push(DIGITS[(byte & 0xf) as usize] as char);
+    }
+    out
+}
+
+/// Inverse of `to_hex`; the prefix is optional and either case is read
+pub fn from_hex(s: &str) -> Option<Vec<u8>> {
+    let digits = s.strip_prefix("0x").unwrap_or(s).as_bytes();
+    if !digits.len().is_multiple_of(2) {
+        return None;
+    }
+    let digit = |c: u8| (c as char).to_digit(16).map(|d| d as u8);
+    digits.chunks(2).map(|pair| Some(digit(pair[0])? << 4 | digit(pair[1])?)).collect()
+}
+
+/// A point or scalar written as the hex of its compressed encoding
+struct Hex<T>(T);
+
+impl<T: CanonicalSerialize> Serialize for Hex<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut bytes = Vec::new();
+        self.0.serialize_compressed(&mut bytes).expect("writing to a Vec");
+        serializer.serialize_str(&to_hex(&bytes))
+    }
+}
+
+impl<'de, T: CanonicalDeserialize> Deserialize<'de> for Hex<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        let bytes = from_hex(&s).ok_or_else(|| D::Error::custom("invalid hex string"))?;
+        T::deserialize_compressed(&bytes[..]).map(Hex).map_err(|_| D::Error::custom("invalid point or scalar encoding"))
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(bound = "", deny_unknown_fields)]
+struct EvaluationsJson<F: PrimeField> {
+    wires: [Hex<F>; 3],
+    sigmas: [Hex<F>; 2],
+    z_omega: Hex<F>,
+    lookup_selector: Hex<F>,
+    table: [Hex<F>; 3],
+    permuted: [Hex<F>; 2],
+    z_lookup_omega: Hex<F>,
+    permuted_input_omega: Hex<F>,
+    linearization: Hex<F>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(bound = "", deny_unknown_fields)]
+struct ProofJson<E: CurvePairing> {
+    version: u32,
+    wire_commitments: [Hex<E::G1Affine>; 3],
+    lookup_commitments: [Hex<E::G1Affine>; 2],
+    z_commitment: Hex<E::G1Affine>,
+    z_lookup_commitment: Hex<E::G1Affine>,
+    quotient_commitments: [Hex<E::G1Affine>; 3],
+    evaluations: EvaluationsJson<E::ScalarField>,
+    w_zeta: Hex<E::G1Affine>,
+    w_zeta_omega: Hex<E::G1Affine>,
+}
+
+impl<E: CurvePairing> Serialize for Proof<E> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let e = &self.evaluations;
+        ProofJson::<E> {
+            version: JSON_VERSION,
+            wire_commitments: self.wire_commitments.map(Hex),
+            lookup_commitments: self.lookup_commitments.map(Hex),
+            z_commitment: Hex(self.z_commitment),
+            z_lookup_commitment: Hex(self.z_lookup_commitment),
+            quotient_commitments: self.quotient_commitments.map(Hex),
+            evaluations: EvaluationsJson {
+                wires: e.wires.map(Hex),
+                sigmas: e.sigmas.map(Hex),
+                z_omega: Hex(e.z_omega),
+                lookup_selector: Hex(e.lookup_selector),
+                table: e.table.map(Hex),
+                permuted: e.permuted.map(Hex),
+                z_lookup_omega: Hex(e.z_lookup_omega),
+                permuted_input_omega: Hex(e.permuted_input_omega),
+                linearization: Hex(e.linearization),
+            },
+            w_zeta: Hex(self.w_zeta),
+            w_zeta_omega: Hex(self.w_zeta_omega),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de, E: CurvePairing> Deserialize<'de> for Proof<E> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let json = ProofJson::<E>::deserialize(deserializer)?;
+        if json.version != JSON_VERSION {
+            return Err(D::Error::custom(format!("unsupported proof version {}", json.version)));
+        }
+        let e = json.evaluations;
+        Ok(Self {
+            wire_commitments: json.wire_commitments.map(|p| p.0),
+            lookup_commitments: json.lookup_commitments.map(|p| p.0),
+            z_commitment: json.z_commitment.0,
+            z_lookup_commitment: json.z_lookup_commitment.0,
+            quotient_commitments: json.quotient_commitments.map(|p| p.0),
+            evaluations: ProofEvaluations {
+                wires: e.wires.map(|x| x.0),
+                sigmas: e.sigmas.map(|x| x.0),
+                z_omega: e.z_omega.0,
+                lookup_selector: e.lookup_selector.0,
+                table: e.table.map(|x| x.0),
+                permuted: e.permuted.map(|x| x.0),
+                z_lookup_omega: e.z_lookup_omega.0,
+                permuted_input_omega: e.permuted_input_omega.0,
+                linearization: e.linearization.0,
+            },
+            w_zeta: json.w_zeta.0,
+            w_zeta_omega: json.w_zeta_omega.0,
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(bound = "", deny_unknown_fields)]
+struct SelectorsJson<F: PrimeField> {
+    q_l: Vec<Hex<F>>,
+    q_r: Vec<Hex<F>>,
+    q_o: Vec<Hex<F>>,
+    q_m: Vec<Hex<F>>,
+    q_c: Vec<Hex<F>>,
+    q_k: Vec<Hex<F>>,
+    custom: Vec<Vec<Hex<F>>>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(bound = "", deny_unknown_fields)]
+struct ConstraintSystemJson<F: PrimeField> {
+    version: u32,
+    rows: usize,
+    selectors: SelectorsJson<F>,
+    permutation: Vec<Vec<[usize; 2]>>,
+    public_rows: Vec<usize>,
+    table: Vec<[Hex<F>; 3]>,
+    custom_gates: Vec<Hex<CustomGate<F>>>,
+}
+
+impl<F: PrimeField> Serialize for ConstraintSystem<F> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let hex = |column: &Vec<F>| column.iter().copied().map(Hex).collect();
+        let [q_l, q_r, q_o, q_m, q_c, q_k] = self.selectors.columns().map(hex);
+        ConstraintSystemJson::<F> {
+            version: JSON_VERSION,
+            rows: self.rows,
+            selectors: SelectorsJson { q_l, q_r, q_o, q_m, q_c, q_k, custom: self.selectors.custom.iter().map(hex).collect() },
+            permutation: self
+                .permutation
+                .mapping()
+                .iter()
+                .map(|column| column.iter().map(|cell| [cell.column, cell.row]).collect())
+                .collect(),
+            public_rows: self.public_rows.clone(),
+            table: self.table.iter().map(|row| row.map(Hex)).collect(),
+            custom_gates: self.custom_gates.iter().cloned().map(Hex).collect(),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de, F: PrimeField> Deserialize<'de> for ConstraintSystem<F> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let json = ConstraintSystemJson::<F>::deserialize(deserializer)?;
+        if json.version != JSON_VERSION {
+            return Err(D::Error::custom(format!("unsupported constraint system version {}", json.version)));
+        }
+        let scalars = |column: Vec<Hex<F>>| column.into_iter().map(|x| x.0).collect();
+        let s = json.selectors;
+        let mut selectors = CircuitSelectors::from_columns([s.q_l, s.q_r, s.q_o, s.q_m, s.q_c, s.q_k].map(scalars));
+        selectors.custom = s.custom.into_iter().map(scalars).collect();
+        let sigma = json
+            .permutation
+            .into_iter()
+            .map(|column| column.into_iter().map(|[column, row]| Cell::new(column, row)).collect())
+            .collect();
+        let cs = Self {
+            rows: json.rows,
+            selectors,
+            permutation: Permutation::from_mapping(sigma).ok_or_else(|| D::Error::custom("permutation is not a bijection"))?,
+            public_rows: json.public_rows,
+            table: json.table.into_iter().map(|row| row.map(|x| x.0)).collect(),
+            custom_gates: json.custom_gates.into_iter().map(|gate| gate.0).collect(),
+        };
+        cs.check().map_err(|_| D::Error::custom("malformed constraint system"))?;
+        Ok(cs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bls12_381::{Bls12_381, Fr, G1Affine};
+    use ark_ec::AffineRepr;
+    use ark_std::UniformRand;
+
+    fn proof() -> Proof<Bls12_381> {
+        let mut rng = ark_std::test_rng();
+        let points = [(); 11].map(|_| (G1Affine::generator() * Fr::rand(&mut rng)).into());
+        let mut scalars = [(); 15].map(|_| Fr::rand(&mut rng)).into_iter();
+        let mut next = || scalars.next().unwrap();
+        Proof {
+            wire_commitments: [points[0], points[1], points[2]],
+            lookup_commitments: [points[3], points[4]],
+            z_commitment: points[5],
+            z_lookup_commitment: points[6],
+            quotient_commitments: [points[7], points[8], points[9]],
+            evaluations: ProofEvaluations {
+                wires: [next(), next(), next()],
+                sigmas: [next(), next()],
+                z_omega: next(),
+                lookup_selector: next(),
+                table: [next(), next(), next()],
+                permuted: [next(), next()],
+                z_lookup_omega: next(),
+                permuted_input_omega: next(),
+                linearization: next(),
+            },
+            w_zeta: points[10],
+            w_zeta_omega: G1Affine::zero(),
+        }
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let proof = proof();
+        let json = serde_json::to_string(&proof).unwrap();
+        assert_eq!(serde_json::from_str::<Proof<Bls12_381>>(&json).unwrap(), proof);
+
+        // Hex of the same bytes the binary encoding writes
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["version"], JSON_VERSION);
+        let mut bytes = Vec::new();
+        proof.wire_commitments[0].serialize_compressed(&mut bytes).unwrap();
+        assert_eq!(value["wire_commitments"][0], to_hex(&bytes));
+        assert_eq!(from_hex(value["evaluations"]["linearization"].as_str().unwrap()).unwrap().len(), 32);
+    }
+
+    #[test]
+    fn test_json_rejects_bad_input() {
+        let proof = proof();
+        let value: serde_json::Value = serde_json::to_value(&proof).unwrap();
+        let rejected = |edit: &dyn Fn(&mut serde_json::Value)| {
+            let mut value = value.clone();
+            edit(&mut value);
+            serde_json::from_value::<Proof<Bls12_381>>(value).is_err()
+        };
+        assert!(rejected(&|v| v["version"] = 2.into()));
+        assert!(rejected(&|v| v["w_zeta"] = "0x12".into()));
+        assert!(rejected(&|v| v["z_commitment"] = "not hex".into()));
+        assert!(rejected(&|v| v["extra"] = 0.into()));
+        // An x with no point on the curve
+        assert!(rejected(&|v| v["z_commitment"] = to_hex(&[0x11; 48]).into()));
+        assert!(!rejected(&|_| ()));
+    }
+
+    #[test]
+    fn test_constraint_system_json() {
+        use crate::circuit::{Circuit, Variable};
+        use crate::constraint_system::Synthesize;
+
+        /// x·y exposed as a public input
+        struct Product;
+
+        impl Synthesize<Fr> for Product {
+            fn synthesize(&self, circuit: &mut Circuit<Fr>) {
+                let [x, y] = [3u64, 7].map(|value| circuit.alloc(Fr::from(value)));
+                let product = circuit.mul(x, y);
+                let public = Variable::from(&circuit.add_public_input(Fr::from(21u64)));
+                circuit.assert_equal(product, public);
+            }
+        }
+
+        let (cs, _) = ConstraintSystem::synthesize(&Product, 4).unwrap();
+        let value = serde_json::to_value(&cs).unwrap();
+        assert_eq!(serde_json::from_value::<ConstraintSystem<Fr>>(value.clone()).unwrap(), cs);
+        assert_eq!(value["rows"], cs.rows);
+        assert_eq!(value["public_rows"], serde_json::json!(cs.public_rows));
+        assert_eq!(value["selectors"]["q_m"][0], to_hex(&[&[1], &[0; 31][..]].concat()));
+
+        let rejected = |edit: &dyn Fn(&mut serde_json::Value)| {
+            let mut value = value.clone();
+            edit(&mut value);
+            serde_json::from_value::<ConstraintSystem<Fr>>(value).is_err()
+        };
+        assert!(rejected(&|v| v["version"] = 2.into()));
+        assert!(rejected(&|v| v["permutation"][0][0] = serde_json::json!([0, 1])));
+        assert!(rejected(&|v| v["public_rows"] = serde_json::json!([cs.rows])));
+        assert!(rejected(&|v| v["selectors"]["q_l"] = serde_json::json!([])));
+    }
+
+    #[test]
+    fn test_hex() {
+        assert_eq!(to_hex(&[0x00, 0xab, 0x7f]), "0x00ab7f");
+        assert_eq!(from_hex("0x00AB7f"), Some(vec![0x00, 0xab, 0x7f]));
+        assert_eq!(from_hex("00ab"), Some(vec![0x00, 0xab]));
+        assert_eq!(from_hex("0xabc"), None);
+        assert_eq!(from_hex("0xzz"), None);
+    }
+}
@@ -1,9 +1,10 @@
-use ark_ec::{AdditiveGroup, PrimeGroup, AffineRepr, CurveGroup};
-use ark_ff::{PrimeField, Field};
+use alloc::{vec, vec::Vec};
+use ark_ec::{PrimeGroup, AffineRepr, CurveGroup};
 use ark_std::{Zero, One, UniformRand, ops::Mul};
 use ark_ec::pairing::Pairing;
 use ark_std::rand::Rng;
 
+#[cfg(test)]
 use ark_bls12_381::{
     Bls12_381,
     G1Projective as G1, 
@@ -14,18 +15,94 @@ use ark_bls12_381::{
 use ark_poly::polynomial::{Polynomial, DenseUVPolynomial};
 use ark_poly::polynomial::univariate::DensePolynomial;
 
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
 
-pub struct KZGParams<E: Pairing> {
+use crate::fft::DomainError;
+use crate::msm::{curve_msm, curve_mul, CurvePairing, MsmConfig, MsmError};
+use crate::pcs::Opening;
+use crate::transcript::Transcript;
+
+/// Why a commitment could not be formed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CommitError {
+    /// More coefficients or entries than the SRS has bases for
+    SrsTooSmall { needed: usize, powers: usize },
+    /// A vector commitment over a size without an FFT domain
+    Domain(DomainError),
+    /// A polynomial is opened twice at the same point
+    DuplicatePoint,
+}
+
+impl core::fmt::Display for CommitError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            CommitError::SrsTooSmall { needed, powers } => write!(f, "needs {} SRS bases, only {} available", needed, powers),
+            CommitError::Domain(error) => write!(f, "{}", error),
+            CommitError::DuplicatePoint => write!(f, "polynomial opened twice at the same point"),
+        }
+    }
+}
+
+/// An MSM refuses only bases and scalars of different lengths, which for
+/// a commitment means fewer bases than coefficients
+impl From<MsmError> for CommitError {
+    fn from(error: MsmError) -> Self {
+        CommitError::SrsTooSmall { needed: error.scalars, powers: error.bases }
+    }
+}
+
+impl From<DomainError> for CommitError {
+    fn from(error: DomainError) -> Self {
+        CommitError::Domain(error)
+    }
+}
+
+/// Powers s^i·g1 and the pair g2, s·g2
+#[derive(Debug, Clone, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct KZGParams<E: CurvePairing> {
     pub powers_of_g: Vec<E::G1Affine>,
     pub g2: E::G2Affine,
     pub g2_s: E::G2Affine,
 }
 
+/// The part of `KZGParams` a verifier keeps: [1]₁, [1]₂ and [s]₂
+#[derive(Debug, Clone, Copy, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct KZGVerifierKey<E: CurvePairing> {
+    pub g1: E::G1Affine,
+    pub g2: E::G2Affine,
+    pub g2_s: E::G2Affine,
+}
 
+impl<E: CurvePairing> KZGVerifierKey<E> {
+    pub fn verify(&self, commitment: &E::G1Affine, proof: &E::G1Affine, z: E::ScalarField, value: E::ScalarField) -> bool {
+        let (g1_value, g2_z) = (curve_mul(self.g1.into_group(), value), curve_mul(self.g2.into_group(), z));
+        check_opening::<E>(&self.g2, &self.g2_s, commitment, proof, g1_value, g2_z)
+    }
 
-impl<E: Pairing> KZGParams<E> {
+    /// L and R of e(L, [s]₂) = e(R, [1]₂), which holds for every opening
+    /// (C_i, W_i, z_i, y_i) when it holds for their combination with u^i:
+    ///
+    ///   L = Σ u^i·W_i,  R = Σ u^i·(z_i·W_i + C_i − [y_i]₁)
+    pub fn fold(&self, openings: &[Opening<E::ScalarField, KZGParams<E>>], u: E::ScalarField) -> (E::G1, E::G1) {
+        let (mut lhs, mut rhs, mut value) = (E::G1::zero(), E::G1::zero(), E::ScalarField::zero());
+        let mut power = E::ScalarField::one();
+        for opening in openings {
+            let proof = curve_mul(opening.proof.into_group(), power);
+            lhs += proof;
+            rhs += curve_mul(proof, opening.z) + curve_mul(opening.commitment.into_group(), power);
+            value += opening.value * power;
+            power *= u;
+        }
+        (lhs, rhs - curve_mul(self.g1.into_group(), value))
+    }
+}
+
+impl<E: CurvePairing> KZGParams<E> {
     pub fn setup<R: Rng>(degree: usize, rng: &mut R) -> Self {
-        let s = E::ScalarField::rand(rng);
+        Self::from_trapdoor(degree, E::ScalarField::rand(rng))
+    }
+
+    fn from_trapdoor(degree: usize, s: E::ScalarField) -> Self {
         let g1 = E::G1::generator();
         let g2 = E::G2::generator();
 
@@ -45,34 +122,56 @@ impl<E: Pairing> KZGParams<E> {
         }
     }
 
-    pub fn commit(&self, poly: &DensePolynomial<E::ScalarField>) -> E::G1Affine {
-        let mut result = E::G1::zero();
-        
-        for (i, coeff) in poly.coeffs().iter().enumerate() {
-            if i >= self.powers_of_g.len() {
-                panic!("Polynomial degree too large for parameters");
-            }
-            result += self.powers_of_g[i].mul(*coeff);
+    /// What a verifier needs; an empty SRS leaves g1 at the identity,
+    /// which rejects every opening
+    pub fn verifier_key(&self) -> KZGVerifierKey<E> {
+        KZGVerifierKey {
+            g1: self.powers_of_g.first().copied().unwrap_or_else(E::G1Affine::zero),
+            g2: self.g2,
+            g2_s: self.g2_s,
+        }
+    }
+
+    /// [p(s)]₁ by a bucketed MSM over the SRS, with the default `MsmConfig`
+    pub fn commit(&self, poly: &DensePolynomial<E::ScalarField>) -> Result<E::G1Affine, CommitError> {
+        self.commit_with(poly, &MsmConfig::default())
+    }
+
+    fn check_degree(&self, poly: &DensePolynomial<E::ScalarField>) -> Result<(), CommitError> {
+        let needed = poly.coeffs().len();
+        if needed > self.powers_of_g.len() {
+            return Err(CommitError::SrsTooSmall { needed, powers: self.powers_of_g.len() });
         }
-        
-        result.into_affine()
+        Ok(())
+    }
+
+    /// Same as `commit`, but runs a chunked bucketed MSM tuned by `config`,
+    /// split through the endomorphism on curves that have one
+    pub fn commit_with(&self, poly: &DensePolynomial<E::ScalarField>, config: &MsmConfig) -> Result<E::G1Affine, CommitError> {
+        self.check_degree(poly)?;
+        let coeffs = poly.coeffs();
+        Ok(curve_msm::<E::G1>(&self.powers_of_g[..coeffs.len()], coeffs, config)?.into_affine())
     }
 
     pub fn open(
         &self,
         poly: &DensePolynomial<E::ScalarField>,
         z: E::ScalarField,
-    ) -> (E::G1Affine, E::ScalarField) {
+    ) -> Result<(E::G1Affine, E::ScalarField), CommitError> {
+        self.open_with(poly, z, &MsmConfig::default())
+    }
+
+    /// Same as `open`, committing to the quotient with `commit_with`
+    pub fn open_with(
+        &self,
+        poly: &DensePolynomial<E::ScalarField>,
+        z: E::ScalarField,
+        config: &MsmConfig,
+    ) -> Result<(E::G1Affine, E::ScalarField), CommitError> {
 
         let value = poly.evaluate(&z);
-        
-        // Compute quotient polynomial q(X) = (p(X) - p(z)) / (X - z)
-        let numerator = poly - &DensePolynomial::from_coefficients_vec(vec![value]);
-        let divisor = DensePolynomial::from_coefficients_vec(vec![-z, E::ScalarField::one()]);
-        let quotient = numerator / &divisor ;
-        
-        let proof = self.commit(&quotient);
-        (proof, value)
+        let proof = self.commit_with(&divide_by_linear(poly, z), config)?;
+        Ok((proof, value))
     }
 
     pub fn verify(
@@ -82,19 +181,337 @@ impl<E: Pairing> KZGParams<E> {
         z: E::ScalarField,
         value: E::ScalarField,
     ) -> bool {
+        let Some(g1) = self.powers_of_g.first() else {
+            return false;
+        };
+        let g1_value = curve_mul(g1.into_group(), value);
+        let g2_z = curve_mul(self.g2.into_group(), z);
+        check_opening::<E>(&self.g2, &self.g2_s, commitment, proof, g1_value, g2_z)
+    }
 
-        // (proof, [x]₂ - [z]₂) = e(commitment - [value]₁, [1]₂)
-        let g1_value = self.powers_of_g[0].mul(value);
-        let commitment_minus_value = commitment.into_group() - g1_value;
-        let g2_z = self.g2.mul(z);
-        let g2_s_minus_z = self.g2_s.into_group() - g2_z;
-        let pairing1 = E::pairing(proof.into_group(), g2_s_minus_z);
-        let pairing2 = E::pairing(commitment_minus_value, self.g2.into_group());
-        
-        pairing1 == pairing2
+    /// Opens every polynomial of `polys` at `z` with one proof: the
+    /// values are absorbed into `transcript` after `z`, a challenge v is
+    /// squeezed, and Σ v^i·polys[i] is opened. The commitments must
+    /// already be in `transcript` for v to bind them. Returns the proof
+    /// and the values, in order.
+    pub fn open_batch(
+        &self,
+        polys: &[DensePolynomial<E::ScalarField>],
+        z: E::ScalarField,
+        transcript: &mut Transcript,
+    ) -> Result<(E::G1Affine, Vec<E::ScalarField>), CommitError> {
+        let values: Vec<E::ScalarField> = polys.iter().map(|poly| poly.evaluate(&z)).collect();
+        let v = batch_challenge::<E::ScalarField>(transcript, z, &values);
+        let mut combined = DensePolynomial::zero();
+        for poly in polys.iter().rev() {
+            combined = &(&combined * v) + poly;
+        }
+        let (proof, _) = self.open(&combined, z)?;
+        Ok((proof, values))
+    }
+
+    /// Checks a proof of `open_batch` for `commitments`, replaying the
+    /// same transcript. False when the counts differ.
+    pub fn verify_batch(
+        &self,
+        commitments: &[E::G1Affine],
+        proof: &E::G1Affine,
+        z: E::ScalarField,
+        values: &[E::ScalarField],
+        transcript: &mut Transcript,
+    ) -> bool {
+        if commitments.len() != values.len() {
+            return false;
+        }
+        let v = batch_challenge::<E::ScalarField>(transcript, z, values);
+        let (mut commitment, mut value) = (E::G1::zero(), E::ScalarField::zero());
+        for (c, y) in commitments.iter().zip(values).rev() {
+            commitment = commitment * v + c;
+            value = value * v + y;
+        }
+        self.verify(&commitment.into_affine(), proof, z, value)
     }
 }
 
+impl<E: CurvePairing> KZGParams<E> {
+    /// Opens each of `polys` at its own set of points, `points[i]` for
+    /// `polys[i]`, with two group elements whatever the counts (SHPLONK,
+    /// Boneh et al. 2020). With r_i interpolating polys[i] on its points
+    /// and T the union of all points, the prover commits to
+    ///   h = Σ γ^i·Z_{T∖S_i}·(f_i − r_i) / Z_T
+    /// and then opens the linear combination L at a fresh z, where
+    ///   L = Σ γ^i·Z_{T∖S_i}(z)·(f_i − r_i(z)) − Z_T(z)·h
+    /// vanishes. As with `open_batch`, the commitments must already be in
+    /// `transcript`. Returns the proof and the values, per polynomial.
+    pub fn open_multi(
+        &self,
+        polys: &[DensePolynomial<E::ScalarField>],
+        points: &[Vec<E::ScalarField>],
+        transcript: &mut Transcript,
+    ) -> Result<MultiPointOpening<E>, CommitError> {
+        let values: Vec<Vec<E::ScalarField>> = polys
+            .iter()
+            .zip(points)
+            .map(|(poly, points)| points.iter().map(|x| poly.evaluate(x)).collect())
+            .collect();
+        let interpolants = points
+            .iter()
+            .zip(&values)
+            .map(|(points, values)| interpolate_points(points, values).ok_or(CommitError::DuplicatePoint))
+            .collect::<Result<Vec<_>, _>>()?;
+        let all = union(points);
+        let gamma = multi_challenge::<E::ScalarField>(transcript, points, &values);
+
+        let mut numerator = DensePolynomial::zero();
+        for (i, (poly, interpolant)) in polys.iter().zip(&interpolants).enumerate().rev() {
+            let others = vanishing(all.iter().filter(|x| !points[i].contains(x)));
+            numerator = &(&numerator * gamma) + &(&others * &(poly - interpolant));
+        }
+        let h = &numerator / &vanishing(all.iter());
+        let w = self.commit(&h)?;
+
+        transcript.absorb(b"shplonk_w", &w);
+        let z: E::ScalarField = transcript.squeeze(b"shplonk_z");
+        let mut l = DensePolynomial::zero();
+        for (i, (poly, interpolant)) in polys.iter().zip(&interpolants).enumerate().rev() {
+            let others = vanishing_at(all.iter().filter(|x| !points[i].contains(x)), z);
+            let shifted = poly - &DensePolynomial::from_coefficients_vec(vec![interpolant.evaluate(&z)]);
+            l = &(&l * gamma) + &(&shifted * others);
+        }
+        l = &l - &(&h * vanishing_at(all.iter(), z));
+        let (w_z, _) = self.open(&l, z)?;
+        Ok((MultiPointProof { w, w_z }, values))
+    }
+
+    /// Checks a proof of `open_multi`, replaying the same transcript, with
+    /// the two pairings of e([L] + z·W_z, [1]) = e(W_z, [s]). False when
+    /// the counts differ or a polynomial repeats a point.
+    pub fn verify_multi(
+        &self,
+        commitments: &[E::G1Affine],
+        points: &[Vec<E::ScalarField>],
+        values: &[Vec<E::ScalarField>],
+        proof: &MultiPointProof<E>,
+        transcript: &mut Transcript,
+    ) -> bool {
+        let Some(g1) = self.powers_of_g.first() else {
+            return false;
+        };
+        if commitments.len() != points.len()
+            || values.len() != points.len()
+            || points.iter().zip(values).any(|(points, values)| points.len() != values.len())
+        {
+            return false;
+        }
+        let Some(interpolants) = points.iter().zip(values).map(|(points, values)| interpolate_points(points, values)).collect::<Option<Vec<_>>>() else {
+            return false;
+        };
+        let all = union(points);
+        let gamma = multi_challenge::<E::ScalarField>(transcript, points, values);
+        transcript.absorb(b"shplonk_w", &proof.w);
+        let z: E::ScalarField = transcript.squeeze(b"shplonk_z");
+
+        let (mut l, mut value) = (E::G1::zero(), E::ScalarField::zero());
+        for (i, (commitment, interpolant)) in commitments.iter().zip(&interpolants).enumerate().rev() {
+            let others = vanishing_at(all.iter().filter(|x| !points[i].contains(x)), z);
+            l = l * gamma + *commitment * others;
+            value = value * gamma + interpolant.evaluate(&z) * others;
+        }
+        let l = l - *g1 * value - proof.w * vanishing_at(all.iter(), z);
+        E::multi_pairing([l + proof.w_z * z, -proof.w_z.into_group()], [self.g2, self.g2_s]).is_zero()
+    }
+}
+
+/// Powers s^i·γg1 of a second generator γg1, with γ discarded like s, for
+/// hiding commitments p(s)g1 + r(s)γg1 under a random r of degree
+/// `hiding_bound`. PLONK proofs do not need them: the prover hides its
+/// polynomials with multiples of Z_H instead. They are for standalone
+/// commitments that must not reveal anything beyond the openings made.
+#[derive(Debug, Clone, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct HidingKey<E: CurvePairing> {
+    pub powers_of_gamma_g: Vec<E::G1Affine>,
+}
+
+impl<E: CurvePairing> HidingKey<E> {
+    /// Degree of the blinding polynomials
+    pub fn hiding_bound(&self) -> usize {
+        self.powers_of_gamma_g.len().saturating_sub(1)
+    }
+}
+
+/// Opening of a hiding commitment at z: the witness q(s)g1 + q̂(s)γg1 for
+/// the quotients of p and r by X − z, p(z), and r(z)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HidingOpening<E: CurvePairing> {
+    pub w: E::G1Affine,
+    pub value: E::ScalarField,
+    pub blinding: E::ScalarField,
+}
+
+impl<E: CurvePairing> KZGParams<E> {
+    /// `setup` together with a hiding key for blinding polynomials of
+    /// degree `hiding_bound`
+    pub fn setup_hiding<R: Rng>(degree: usize, hiding_bound: usize, rng: &mut R) -> (Self, HidingKey<E>) {
+        let s = E::ScalarField::rand(rng);
+        let gamma = E::ScalarField::rand(rng);
+        let gamma_g = E::G1::generator() * gamma;
+        let mut power = E::ScalarField::one();
+        let powers_of_gamma_g: Vec<E::G1> = (0..=hiding_bound)
+            .map(|_| {
+                let point = gamma_g * power;
+                power *= s;
+                point
+            })
+            .collect();
+        (Self::from_trapdoor(degree, s), HidingKey { powers_of_gamma_g: E::G1::normalize_batch(&powers_of_gamma_g) })
+    }
+
+    /// p(s)g1 + r(s)γg1 for a fresh random r, returned with the commitment
+    /// as the opener's secret
+    pub fn commit_hiding<R: Rng>(
+        &self,
+        key: &HidingKey<E>,
+        poly: &DensePolynomial<E::ScalarField>,
+        rng: &mut R,
+    ) -> Result<(E::G1Affine, DensePolynomial<E::ScalarField>), CommitError> {
+        let blinding = DensePolynomial::rand(key.hiding_bound(), rng);
+        let commitment = self.commit(poly)?.into_group() + key.commit(&blinding)?;
+        Ok((commitment.into_affine(), blinding))
+    }
+
+    /// Opens a `commit_hiding` commitment at z; only r(z) of the blinding
+    /// is revealed
+    pub fn open_hiding(
+        &self,
+        key: &HidingKey<E>,
+        poly: &DensePolynomial<E::ScalarField>,
+        blinding: &DensePolynomial<E::ScalarField>,
+        z: E::ScalarField,
+    ) -> Result<HidingOpening<E>, CommitError> {
+        let (w, value) = self.open(poly, z)?;
+        let r_z = blinding.evaluate(&z);
+        let divisor = DensePolynomial::from_coefficients_vec(vec![-z, E::ScalarField::one()]);
+        let quotient = &(blinding - &DensePolynomial::from_coefficients_vec(vec![r_z])) / &divisor;
+        let w = w.into_group() + key.commit(&quotient)?;
+        Ok(HidingOpening { w: w.into_affine(), value, blinding: r_z })
+    }
+
+    /// e(C − p(z)g1 − r(z)γg1, g2) = e(w, s·g2 − z·g2)
+    pub fn verify_hiding(&self, key: &HidingKey<E>, commitment: &E::G1Affine, opening: &HidingOpening<E>, z: E::ScalarField) -> bool {
+        let (Some(g1), Some(gamma_g)) = (self.powers_of_g.first(), key.powers_of_gamma_g.first()) else {
+            return false;
+        };
+        let g1_value = g1.mul(opening.value) + gamma_g.mul(opening.blinding);
+        check_opening::<E>(&self.g2, &self.g2_s, commitment, &opening.w, g1_value, self.g2.mul(z))
+    }
+}
+
+impl<E: CurvePairing> HidingKey<E> {
+    /// r(s)γg1
+    fn commit(&self, blinding: &DensePolynomial<E::ScalarField>) -> Result<E::G1, CommitError> {
+        let coeffs = blinding.coeffs();
+        if coeffs.len() > self.powers_of_gamma_g.len() {
+            return Err(CommitError::SrsTooSmall { needed: coeffs.len(), powers: self.powers_of_gamma_g.len() });
+        }
+        Ok(curve_msm::<E::G1>(&self.powers_of_gamma_g[..coeffs.len()], coeffs, &MsmConfig::default())?)
+    }
+}
+
+/// Proof of `KZGParams::open_multi`: the commitment to h and the opening
+/// of L at z
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MultiPointProof<E: CurvePairing> {
+    pub w: E::G1Affine,
+    pub w_z: E::G1Affine,
+}
+
+/// A `MultiPointProof` with the values it opens, per polynomial
+type MultiPointOpening<E> = (MultiPointProof<E>, Vec<Vec<<E as Pairing>::ScalarField>>);
+
+/// The polynomial through (points[j], values[j]), `None` if a point
+/// repeats
+fn interpolate_points<F: ark_ff::PrimeField>(points: &[F], values: &[F]) -> Option<DensePolynomial<F>> {
+    let mut out = DensePolynomial::zero();
+    for (j, (xj, yj)) in points.iter().zip(values).enumerate() {
+        let others = points.iter().enumerate().filter(|(k, _)| *k != j).map(|(_, x)| x);
+        let denominator: F = others.clone().map(|x| *xj - x).product();
+        out = &out + &(&vanishing(others) * (*yj * denominator.inverse()?));
+    }
+    Some(out)
+}
+
+/// Π (X − x) over `points`
+fn vanishing<'a, F: ark_ff::PrimeField>(points: impl Iterator<Item = &'a F>) -> DensePolynomial<F> {
+    points.fold(DensePolynomial::from_coefficients_vec(vec![F::one()]), |acc, x| {
+        &acc * &DensePolynomial::from_coefficients_vec(vec![-*x, F::one()])
+    })
+}
+
+/// Π (z − x) over `points`
+fn vanishing_at<'a, F: ark_ff::Field>(points: impl Iterator<Item = &'a F>, z: F) -> F {
+    points.map(|x| z - x).product()
+}
+
+/// Every point of `points` once, in first-seen order
+fn union<F: ark_ff::Field>(points: &[Vec<F>]) -> Vec<F> {
+    let mut all = Vec::new();
+    for x in points.iter().flatten() {
+        if !all.contains(x) {
+            all.push(*x);
+        }
+    }
+    all
+}
+
+/// The challenge γ combining the polynomials of `open_multi`
+fn multi_challenge<F: ark_ff::PrimeField>(transcript: &mut Transcript, points: &[Vec<F>], values: &[Vec<F>]) -> F {
+    transcript.absorb(b"shplonk_points", points);
+    transcript.absorb(b"shplonk_values", values);
+    transcript.squeeze(b"shplonk_gamma")
+}
+
+/// (p(X) − p(z)) / (X − z) by synthetic division
+pub(crate) fn divide_by_linear<F: ark_ff::Field>(poly: &DensePolynomial<F>, z: F) -> DensePolynomial<F> {
+    let coeffs = &poly.coeffs;
+    if coeffs.len() < 2 {
+        return DensePolynomial::zero();
+    }
+    let mut quotient = vec![F::zero(); coeffs.len() - 1];
+    let mut carry = F::zero();
+    for i in (1..coeffs.len()).rev() {
+        carry = coeffs[i] + carry * z;
+        quotient[i - 1] = carry;
+    }
+    DensePolynomial::from_coefficients_vec(quotient)
+}
+
+/// The challenge combining a batch opened at `z` to `values`
+pub(crate) fn batch_challenge<F: ark_ff::PrimeField>(transcript: &mut Transcript, z: F, values: &[F]) -> F {
+    transcript.absorb(b"kzg_batch_point", &z);
+    transcript.absorb(b"kzg_batch_values", values);
+    transcript.squeeze(b"kzg_batch_v")
+}
+
+// e(proof, [x]₂ - [z]₂) = e(commitment - [value]₁, [1]₂), checked as
+// e(proof, [x - z]₂) · e(-(commitment - [value]₁), [1]₂) = 1 so both
+// Miller loops share a single final exponentiation
+pub(crate) fn check_opening<E: CurvePairing>(
+    g2: &E::G2Affine,
+    g2_s: &E::G2Affine,
+    commitment: &E::G1Affine,
+    proof: &E::G1Affine,
+    g1_value: E::G1,
+    g2_z: E::G2,
+) -> bool {
+    let value_minus_commitment = g1_value - commitment.into_group();
+    let g2_s_minus_z = g2_s.into_group() - g2_z;
+    E::multi_pairing(
+        [proof.into_group(), value_minus_commitment],
+        [g2_s_minus_z, g2.into_group()],
+    )
+    .is_zero()
+}
+
 #[test]
 fn test_kgz_setup() {
     let mut rng = ark_std::test_rng();
@@ -125,6 +542,8 @@ fn test_kgz_edge_cases() {
     
     let params: KZGParams<Bls12_381> = KZGParams::setup(1, &mut rng);
     assert_eq!(params.powers_of_g.len(), 2);
+    let poly = DensePolynomial::from_coefficients_vec(vec![ScalarField::one(); 3]);
+    assert_eq!(params.commit(&poly), Err(CommitError::SrsTooSmall { needed: 3, powers: 2 }));
 }
 
 #[test]
@@ -140,10 +559,10 @@ fn test_kzg_commit_verify() {
             ScalarField::from(1u64),
         ]
     );
-    let commitment = params.commit(&poly);
+    let commitment = params.commit(&poly).unwrap();
     
     let z = ScalarField::from(2u64);
-    let (proof, value) = params.open(&poly, z);
+    let (proof, value) = params.open(&poly, z).unwrap();
     
     assert!(params.verify(&commitment, &proof, z, value));
     
@@ -151,4 +570,339 @@ fn test_kzg_commit_verify() {
     assert!(!params.verify(&commitment, &proof, z, wrong_value));
 }
 
+#[test]
+fn test_kzg_hiding_commitment() {
+    let mut rng = ark_std::test_rng();
+    let (params, key) = KZGParams::<Bls12_381>::setup_hiding(5, 1, &mut rng);
+    assert_eq!(key.hiding_bound(), 1);
+
+    let poly = DensePolynomial::from_coefficients_vec(vec![ScalarField::from(3u64), ScalarField::from(2u64), ScalarField::from(1u64)]);
+    let (commitment, blinding) = params.commit_hiding(&key, &poly, &mut rng).unwrap();
+    // Committing again to the same polynomial gives an unrelated point
+    assert_ne!(commitment, params.commit(&poly).unwrap());
+    assert_ne!(commitment, params.commit_hiding(&key, &poly, &mut rng).unwrap().0);
+
+    let z = ScalarField::from(2u64);
+    let opening = params.open_hiding(&key, &poly, &blinding, z).unwrap();
+    assert_eq!(opening.value, ScalarField::from(11u64));
+    assert!(params.verify_hiding(&key, &commitment, &opening, z));
+    let wrong = HidingOpening { value: opening.value + ScalarField::one(), ..opening };
+    assert!(!params.verify_hiding(&key, &commitment, &wrong, z));
+    let unblinded = HidingOpening { blinding: ScalarField::zero(), ..opening };
+    assert!(!params.verify_hiding(&key, &commitment, &unblinded, z));
+}
+
+#[test]
+fn test_kzg_commit_with_config() {
+    let mut rng = ark_std::test_rng();
+    let params: KZGParams<Bls12_381> = KZGParams::setup(31, &mut rng);
+    let poly = DensePolynomial::<ScalarField>::rand(31, &mut rng);
+
+    let expected: G1 = crate::naive::msm(&params.powers_of_g, poly.coeffs());
+    assert_eq!(params.commit(&poly).unwrap(), expected.into_affine());
+    let config = MsmConfig { window_bits: Some(3), chunk_size: 5, num_threads: None };
+    assert_eq!(params.commit_with(&poly, &config).unwrap(), params.commit(&poly).unwrap());
+}
+
+#[test]
+fn test_kzg_glv_paths() {
+    // BLS12-381 and BN254 commit and verify through the endomorphism
+    let mut rng = ark_std::test_rng();
+    let params: KZGParams<Bls12_381> = KZGParams::setup(15, &mut rng);
+    let poly = DensePolynomial::<ScalarField>::rand(15, &mut rng);
+    let commitment = params.commit(&poly).unwrap();
+    assert_eq!(commitment, crate::naive::msm::<G1>(&params.powers_of_g, poly.coeffs()).into_affine());
+
+    let z = ScalarField::rand(&mut rng);
+    let (proof, value) = params.open(&poly, z).unwrap();
+    for wrong in [false, true] {
+        let value = if wrong { value + ScalarField::one() } else { value };
+        assert_eq!(params.verify(&commitment, &proof, z, value), !wrong);
+        assert_eq!(params.verifier_key().verify(&commitment, &proof, z, value), !wrong);
+    }
+
+    let params: KZGParams<ark_bn254::Bn254> = KZGParams::setup(7, &mut rng);
+    let poly = DensePolynomial::<ark_bn254::Fr>::rand(7, &mut rng);
+    let commitment = params.commit(&poly).unwrap();
+    assert_eq!(commitment, crate::naive::msm::<ark_bn254::G1Projective>(&params.powers_of_g, poly.coeffs()).into_affine());
+    let z = ark_bn254::Fr::rand(&mut rng);
+    let (proof, value) = params.open(&poly, z).unwrap();
+    assert!(params.verify(&commitment, &proof, z, value));
+}
+
+#[test]
+fn test_kzg_batch_opening() {
+    let mut rng = ark_std::test_rng();
+    let params: KZGParams<Bls12_381> = KZGParams::setup(15, &mut rng);
+    let polys: Vec<_> = [15, 7, 0].map(|degree| DensePolynomial::<ScalarField>::rand(degree, &mut rng)).to_vec();
+    let commitments: Vec<_> = polys.iter().map(|poly| params.commit(poly).unwrap()).collect();
+    let z = ScalarField::rand(&mut rng);
+    let transcript = || {
+        let mut transcript = Transcript::new(b"test");
+        transcript.absorb(b"commitments", &commitments);
+        transcript
+    };
+
+    let (proof, values) = params.open_batch(&polys, z, &mut transcript()).unwrap();
+    assert_eq!(values, polys.iter().map(|poly| poly.evaluate(&z)).collect::<Vec<_>>());
+    assert!(params.verify_batch(&commitments, &proof, z, &values, &mut transcript()));
+
+    let mut wrong = values.clone();
+    wrong[1] += ScalarField::one();
+    assert!(!params.verify_batch(&commitments, &proof, z, &wrong, &mut transcript()));
+    assert!(!params.verify_batch(&commitments[..2], &proof, z, &values, &mut transcript()));
+    assert!(!params.verify_batch(&commitments, &proof, z, &values, &mut Transcript::new(b"other")));
+
+    let long = DensePolynomial::<ScalarField>::rand(17, &mut rng);
+    assert!(params.open_batch(&[long], z, &mut transcript()).is_err());
+}
+
+#[test]
+fn test_kzg_multi_point_opening() {
+    use ark_ff::FftField;
+
+    let mut rng = ark_std::test_rng();
+    let params: KZGParams<Bls12_381> = KZGParams::setup(15, &mut rng);
+    let polys: Vec<_> = [15, 15, 3].map(|degree| DensePolynomial::<ScalarField>::rand(degree, &mut rng)).to_vec();
+    let commitments: Vec<_> = polys.iter().map(|poly| params.commit(poly).unwrap()).collect();
+    // As in PLONK: z for all of them, z·ω for the second one as well
+    let z = ScalarField::rand(&mut rng);
+    let omega = ScalarField::get_root_of_unity(16).unwrap();
+    let points = vec![vec![z], vec![z, z * omega], vec![z]];
+    let transcript = || {
+        let mut transcript = Transcript::new(b"test");
+        transcript.absorb(b"commitments", &commitments);
+        transcript
+    };
+
+    let (proof, values) = params.open_multi(&polys, &points, &mut transcript()).unwrap();
+    assert_eq!(values[1], vec![polys[1].evaluate(&z), polys[1].evaluate(&(z * omega))]);
+    assert!(params.verify_multi(&commitments, &points, &values, &proof, &mut transcript()));
+
+    let mut wrong = values.clone();
+    wrong[1][1] += ScalarField::one();
+    assert!(!params.verify_multi(&commitments, &points, &wrong, &proof, &mut transcript()));
+    let mut swapped = commitments.clone();
+    swapped.swap(0, 1);
+    assert!(!params.verify_multi(&swapped, &points, &values, &proof, &mut transcript()));
+    assert!(!params.verify_multi(&commitments[..2], &points, &values, &proof, &mut transcript()));
+
+    let repeated = vec![vec![z], vec![z, z], vec![z]];
+    assert_eq!(params.open_multi(&polys, &repeated, &mut transcript()).err(), Some(CommitError::DuplicatePoint));
+}
+
+#[test]
+fn test_divide_by_linear() {
+    let poly = DensePolynomial::from_coefficients_vec(vec![ScalarField::from(3u64), ScalarField::from(5u64), ScalarField::from(7u64)]);
+    let z = ScalarField::from(11u64);
+    let quotient = divide_by_linear(&poly, z);
+    let divisor = DensePolynomial::from_coefficients_vec(vec![-z, ScalarField::one()]);
+    let remainder = &poly - &(&quotient * &divisor);
+    assert_eq!(remainder.coeffs, vec![poly.evaluate(&z)]);
+}
+
+/// Interop with the `blst` library for BLS12-381: lossless point
+/// conversions plus commit/verify running on blst's MSM and pairing.
+/// Commitments produced either way encode to identical compressed bytes.
+#[cfg(feature = "blst")]
+pub mod blst_interop {
+    use super::{CommitError, KZGParams};
+    use ark_bls12_381::{Bls12_381, Fq, Fq2, Fr, G1Affine, G2Affine};
+    use ark_ff::{BigInt, BigInteger, PrimeField};
+    use ark_poly::polynomial::univariate::DensePolynomial;
+    use ark_poly::polynomial::DenseUVPolynomial;
+    use blst::{blst_fp, blst_fp12, blst_fp2, blst_p1, blst_p1_affine, blst_p2, blst_p2_affine, MultiPoint};
+
+    // Fq is kept in Montgomery form with R = 2^384 by both libraries, so
+    // coordinates move across as raw limbs
+    fn fq_to_blst(x: &Fq) -> blst_fp {
+        blst_fp { l: x.0 .0 }
+    }
+
+    fn fq_from_blst(x: &blst_fp) -> Fq {
+        Fq::new_unchecked(BigInt(x.l))
+    }
+
+    fn fq2_to_blst(x: &Fq2) -> blst_fp2 {
+        blst_fp2 { fp: [fq_to_blst(&x.c0), fq_to_blst(&x.c1)] }
+    }
+
+    fn fq2_from_blst(x: &blst_fp2) -> Fq2 {
+        Fq2::new(fq_from_blst(&x.fp[0]), fq_from_blst(&x.fp[1]))
+    }
+
+    /// Little-endian scalar bytes in the layout blst's multiplication expects
+    pub fn fr_to_blst_scalar(x: &Fr) -> [u8; 32] {
+        let mut out = [0u8; 32];
+        out.copy_from_slice(&x.into_bigint().to_bytes_le());
+        out
+    }
+
+    pub fn g1_to_blst(p: &G1Affine) -> blst_p1_affine {
+        if p.infinity {
+            return blst_p1_affine::default();
+        }
+        blst_p1_affine { x: fq_to_blst(&p.x), y: fq_to_blst(&p.y) }
+    }
+
+    pub fn g1_from_blst(p: &blst_p1_affine) -> G1Affine {
+        // Safety: p is a valid reference
+        if unsafe { blst::blst_p1_affine_is_inf(p) } {
+            return G1Affine::identity();
+        }
+        G1Affine::new_unchecked(fq_from_blst(&p.x), fq_from_blst(&p.y))
+    }
+
+    pub fn g2_to_blst(p: &G2Affine) -> blst_p2_affine {
+        if p.infinity {
+            return blst_p2_affine::default();
+        }
+        blst_p2_affine { x: fq2_to_blst(&p.x), y: fq2_to_blst(&p.y) }
+    }
+
+    pub fn g2_from_blst(p: &blst_p2_affine) -> G2Affine {
+        // Safety: p is a valid reference
+        if unsafe { blst::blst_p2_affine_is_inf(p) } {
+            return G2Affine::identity();
+        }
+        G2Affine::new_unchecked(fq2_from_blst(&p.x), fq2_from_blst(&p.y))
+    }
+
+    /// 48-byte ZCash-format compressed encoding, as produced by blst
+    pub fn g1_to_compressed(p: &G1Affine) -> [u8; 48] {
+        let mut out = [0u8; 48];
+        // Safety: out has the 48 bytes blst writes
+        unsafe { blst::blst_p1_affine_compress(out.as_mut_ptr(), &g1_to_blst(p)) };
+        out
+    }
+
+    fn p1_to_affine(p: &blst_p1) -> blst_p1_affine {
+        let mut out = blst_p1_affine::default();
+        // Safety: both pointers refer to live values
+        unsafe { blst::blst_p1_to_affine(&mut out, p) };
+        out
+    }
+
+    fn p1_mult(p: &blst_p1_affine, scalar: &Fr) -> blst_p1 {
+        let mut projective = blst_p1::default();
+        let mut out = blst_p1::default();
+        let bytes = fr_to_blst_scalar(scalar);
+        // Safety: all pointers refer to live values, bytes holds 255 bits
+        unsafe {
+            blst::blst_p1_from_affine(&mut projective, p);
+            blst::blst_p1_mult(&mut out, &projective, bytes.as_ptr(), 255);
+        }
+        out
+    }
 
+    fn p2_mult(p: &blst_p2_affine, scalar: &Fr) -> blst_p2 {
+        let mut projective = blst_p2::default();
+        let mut out = blst_p2::default();
+        let bytes = fr_to_blst_scalar(scalar);
+        // Safety: all pointers refer to live values, bytes holds 255 bits
+        unsafe {
+            blst::blst_p2_from_affine(&mut projective, p);
+            blst::blst_p2_mult(&mut out, &projective, bytes.as_ptr(), 255);
+        }
+        out
+    }
+
+    impl KZGParams<Bls12_381> {
+        /// `commit` computed with blst's Pippenger MSM
+        pub fn commit_blst(&self, poly: &DensePolynomial<Fr>) -> Result<G1Affine, CommitError> {
+            self.check_degree(poly)?;
+            let coeffs = poly.coeffs();
+            if coeffs.is_empty() {
+                return Ok(G1Affine::identity());
+            }
+
+            let bases: Vec<blst_p1_affine> = self.powers_of_g[..coeffs.len()].iter().map(g1_to_blst).collect();
+            let scalars: Vec<u8> = coeffs.iter().flat_map(fr_to_blst_scalar).collect();
+            Ok(g1_from_blst(&p1_to_affine(&bases.as_slice().mult(&scalars, 255))))
+        }
+
+        /// `verify` computed with blst's Miller loop and final exponentiation
+        pub fn verify_blst(&self, commitment: &G1Affine, proof: &G1Affine, z: Fr, value: Fr) -> bool {
+            let Some(g1) = self.powers_of_g.first() else {
+                return false;
+            };
+            let g1 = g1_to_blst(g1);
+            let g2 = g2_to_blst(&self.g2);
+            let g2_s = g2_to_blst(&self.g2_s);
+
+            // value·g1 - commitment
+            let mut commitment_p = blst_p1::default();
+            let mut lhs = p1_mult(&g1, &value);
+            // g2_s - z·g2
+            let mut s_minus_z = p2_mult(&g2, &z);
+            let mut g2_s_p = blst_p2::default();
+
+            let mut lhs_affine = blst_p1_affine::default();
+            let mut s_minus_z_affine = blst_p2_affine::default();
+            let mut ml1 = blst_fp12::default();
+            let mut ml2 = blst_fp12::default();
+            let mut product = blst_fp12::default();
+            let mut result = blst_fp12::default();
+            // Safety: every pointer refers to a live local value
+            unsafe {
+                blst::blst_p1_from_affine(&mut commitment_p, &g1_to_blst(commitment));
+                blst::blst_p1_cneg(&mut commitment_p, true);
+                blst::blst_p1_add_or_double(&mut lhs, &lhs, &commitment_p);
+                blst::blst_p1_to_affine(&mut lhs_affine, &lhs);
+
+                blst::blst_p2_cneg(&mut s_minus_z, true);
+                blst::blst_p2_from_affine(&mut g2_s_p, &g2_s);
+                blst::blst_p2_add_or_double(&mut s_minus_z, &s_minus_z, &g2_s_p);
+                blst::blst_p2_to_affine(&mut s_minus_z_affine, &s_minus_z);
+
+                blst::blst_miller_loop(&mut ml1, &s_minus_z_affine, &g1_to_blst(proof));
+                blst::blst_miller_loop(&mut ml2, &g2, &lhs_affine);
+                blst::blst_fp12_mul(&mut product, &ml1, &ml2);
+                blst::blst_final_exp(&mut result, &product);
+                blst::blst_fp12_is_one(&result)
+            }
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use ark_serialize::CanonicalSerialize;
+        use ark_std::{One, UniformRand};
+
+        #[test]
+        fn test_point_conversions() {
+            let mut rng = ark_std::test_rng();
+            let p = G1Affine::rand(&mut rng);
+            let q = G2Affine::rand(&mut rng);
+            assert_eq!(g1_from_blst(&g1_to_blst(&p)), p);
+            assert_eq!(g2_from_blst(&g2_to_blst(&q)), q);
+            assert_eq!(g1_from_blst(&g1_to_blst(&G1Affine::identity())), G1Affine::identity());
+
+            // Safety: the converted points are valid references
+            unsafe {
+                assert!(blst::blst_p1_affine_in_g1(&g1_to_blst(&p)));
+                assert!(blst::blst_p2_affine_in_g2(&g2_to_blst(&q)));
+            }
+
+            let mut ark_bytes = Vec::new();
+            p.serialize_compressed(&mut ark_bytes).unwrap();
+            assert_eq!(ark_bytes, g1_to_compressed(&p).to_vec());
+        }
+
+        #[test]
+        fn test_blst_commit_verify() {
+            let mut rng = ark_std::test_rng();
+            let params: KZGParams<Bls12_381> = KZGParams::setup(9, &mut rng);
+            let poly = DensePolynomial::<Fr>::rand(9, &mut rng);
+
+            let commitment = params.commit_blst(&poly).unwrap();
+            assert_eq!(commitment, params.commit(&poly).unwrap());
+
+            let z = Fr::rand(&mut rng);
+            let (proof, value) = params.open(&poly, z).unwrap();
+            assert!(params.verify_blst(&commitment, &proof, z, value));
+            assert!(!params.verify_blst(&commitment, &proof, z, value + Fr::one()));
+        }
+    }
+}
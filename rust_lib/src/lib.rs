@@ -1,7 +1,124 @@
+//! PLONK proving system over arkworks curves.
+//!
+//! The supported surface is re-exported from [`prelude`], alongside the
+//! curve aliases in `bn254` and `bw6` and the feature-gated front ends
+//! (`cli`, `ffi`, `json`, `wasm`, `contract_gen`). Every other module is
+//! `doc(hidden)`: its paths are internal and carry no stability guarantee.
+//!
+//! Without the `std` feature the crate is `no_std` and needs only
+//! `alloc`, so the verifier and KZG checks run on embedded targets and in
+//! zkVM guests. Artifact files, memory-mapped stores, the remote prover,
+//! timing-based tuning and `thread_rng` are the parts left out.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+#[doc(hidden)]
+pub mod acir;
+#[cfg(feature = "verifier")]
+#[doc(hidden)]
+pub mod aggregation;
+#[doc(hidden)]
+pub mod artifact;
+#[doc(hidden)]
+pub mod backend;
+pub mod bn254;
+#[cfg(feature = "bw6")]
+pub mod bw6;
+#[doc(hidden)]
 pub mod circuit;
+#[cfg(feature = "cli")]
+pub mod cli;
+#[doc(hidden)]
+pub mod constraint_system;
+#[cfg(feature = "verifier")]
+pub mod contract_gen;
+#[doc(hidden)]
+pub mod custom_gate;
+#[doc(hidden)]
+pub mod digest;
+#[doc(hidden)]
+pub mod elgamal;
+#[doc(hidden)]
+pub mod embedding;
+#[doc(hidden)]
+pub mod encoding;
+#[doc(hidden)]
+pub mod equivalence;
+#[doc(hidden)]
+pub mod error;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[doc(hidden)]
+pub mod evaluations;
+#[doc(hidden)]
 pub mod fft;
+#[doc(hidden)]
+pub mod gadgets;
+#[cfg(feature = "prover")]
+mod grand_product;
+#[doc(hidden)]
+pub mod ipa;
+#[cfg(feature = "serde")]
+pub mod json;
+#[doc(hidden)]
 pub mod kgz;
+#[cfg(any(feature = "prover", feature = "verifier"))]
+mod linearization;
+#[doc(hidden)]
+pub mod lint;
+#[doc(hidden)]
+pub mod lookup;
+mod lowering;
+#[doc(hidden)]
+pub mod metadata;
+#[doc(hidden)]
+pub mod msm;
+#[cfg(test)]
+mod naive;
+#[doc(hidden)]
+pub mod pcs;
+#[doc(hidden)]
+pub mod permutation;
+#[doc(hidden)]
+pub mod poseidon;
+#[cfg(feature = "prover")]
+#[doc(hidden)]
+pub mod pipeline;
+pub mod prelude;
+#[doc(hidden)]
+pub mod proof;
+#[cfg(feature = "prover")]
+#[doc(hidden)]
 pub mod prover;
+#[cfg(feature = "prover")]
+mod quotient;
+#[cfg(feature = "r1cs")]
+#[doc(hidden)]
+pub mod r1cs;
+#[cfg(feature = "verifier")]
+#[doc(hidden)]
+pub mod registry;
+#[doc(hidden)]
+pub mod remote;
+#[doc(hidden)]
+pub mod srs;
+#[cfg(feature = "prover")]
+#[doc(hidden)]
+pub mod store;
+#[doc(hidden)]
+pub mod threshold;
+#[doc(hidden)]
+pub mod transcript;
+#[doc(hidden)]
+pub mod vector_commitment;
+#[cfg(feature = "verifier")]
+#[doc(hidden)]
 pub mod verifier;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+#[doc(hidden)]
+pub mod witness_encryption;
 
-
+pub use backend::backend_name;
@@ -0,0 +1,179 @@
+//! The linearization polynomial r(X), which lets the verifier check the
+//! quotient identity at ζ from a handful of evaluations.
+//!
+//! Every term of the identity is linear in at least one committed
+//! polynomial once the others are replaced by their values at ζ (or ζω):
+//! the selectors in the gate, z and σ_c in the permutation, z_L in the
+//! lookup, q_g in each custom gate and t_lo, t_mid, t_hi in Z_H·t. The
+//! prover opens r, the sum of those polynomials with the scalars they
+//! are multiplied by, at ζ; the verifier builds [r] from the same
+//! scalars and the commitments, and needs only r̄ = r(ζ), the wires,
+//! σ_a, σ_b, z(ζω) and the evaluations the lookup reads:
+//!
+//!   r̄ + r₀ = 0
+//!
+//! where r₀ collects the terms left without a polynomial.
+
+use alloc::{vec, vec::Vec};
+use ark_ff::{batch_inversion, FftField, PrimeField};
+use ark_std::One;
+
+use crate::lookup::compress;
+use crate::msm::CurvePairing;
+use crate::pcs::PolynomialCommitment;
+use crate::proof::{Oracle, ProofEvaluations, VerifyingKey};
+
+/// The challenges and the values at ζ of the polynomials every verifier
+/// can compute itself
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct EvaluationPoint<F> {
+    pub theta: F,
+    pub beta: F,
+    pub gamma: F,
+    pub alpha: F,
+    pub zeta: F,
+    /// ζ^n
+    pub zeta_n: F,
+    /// Z_H(ζ) = ζ^n − 1
+    pub vanishing: F,
+    /// L_1(ζ), the Lagrange polynomial of the first row
+    pub l1: F,
+    /// L_n(ζ), the Lagrange polynomial of the last row, read by r₀ alone
+    #[cfg_attr(not(any(feature = "verifier", test)), allow(dead_code))]
+    pub l_last: F,
+}
+
+impl<F: FftField> EvaluationPoint<F> {
+    /// The point ζ of a domain of `n` rows generated by `omega`. ζ in the
+    /// domain leaves both Lagrange values at zero; the verifier rejects
+    /// such a ζ before it gets here.
+    pub fn new(n: u64, omega: F, [theta, beta, gamma, alpha]: [F; 4], zeta: F) -> Self {
+        let zeta_n = zeta.pow([n]);
+        let vanishing = zeta_n - F::one();
+        // L_i(ζ) = ω^i·Z_H(ζ) / (n·(ζ − ω^i))
+        let rows = [F::one(), omega.pow([n - 1])];
+        let mut lagrange = rows.map(|w| F::from(n) * (zeta - w));
+        batch_inversion(&mut lagrange);
+        let [l1, l_last] = [0, 1].map(|i| lagrange[i] * rows[i] * vanishing);
+        Self { theta, beta, gamma, alpha, zeta, zeta_n, vanishing, l1, l_last }
+    }
+
+    /// α, α², … for the terms after the gate
+    fn alpha_powers(&self) -> impl Iterator<Item = F> {
+        let alpha = self.alpha;
+        core::iter::successors(Some(alpha), move |x| Some(*x * alpha))
+    }
+}
+
+/// (a + β·σ_a + γ)(b + β·σ_b + γ)·z(ζω), the part of the permuted
+/// product both r and r₀ share
+fn permuted_prefix<F: PrimeField>(e: &ProofEvaluations<F>, p: &EvaluationPoint<F>) -> F {
+    (e.wires[0] + p.beta * e.sigmas[0] + p.gamma) * (e.wires[1] + p.beta * e.sigmas[1] + p.gamma) * e.z_omega
+}
+
+/// The polynomials r sums and the scalar of each: r = Σ s·p over the
+/// pairs, and [r] = Σ s·[p]
+pub(crate) fn linear_terms<E: CurvePairing, S: PolynomialCommitment<E::ScalarField>>(
+    vk: &VerifyingKey<E, S>,
+    e: &ProofEvaluations<E::ScalarField>,
+    p: &EvaluationPoint<E::ScalarField>,
+) -> Vec<(Oracle, E::ScalarField)> {
+    let one = E::ScalarField::one();
+    let [a, b, c] = e.wires;
+    let ks = vk.coset_multipliers;
+    let mut alpha = p.alpha_powers();
+    let [alpha_1, alpha_2, alpha_3, alpha_4, _, _] = [(); 6].map(|_| alpha.next().expect("the powers never run out"));
+
+    // q_l·a + q_r·b + q_o·c + q_m·a·b + q_c
+    let mut terms = vec![
+        (Oracle::Selector(0), a),
+        (Oracle::Selector(1), b),
+        (Oracle::Selector(2), c),
+        (Oracle::Selector(3), a * b),
+        (Oracle::Selector(4), one),
+    ];
+
+    // α·[Π (w_j + β·k_j·ζ + γ)·z − (…)·β·σ_c·z(ζω)] + α²·L_1·z
+    let identity: E::ScalarField = (0..3).map(|j| e.wires[j] + p.beta * ks[j] * p.zeta + p.gamma).product();
+    terms.push((Oracle::Z, alpha_1 * identity + alpha_2 * p.l1));
+    terms.push((Oracle::Sigma(2), -alpha_1 * p.beta * permuted_prefix(e, p)));
+
+    // −α³·(A + β)(T + γ)·z_L + α⁴·L_1·z_L
+    let table = compress(e.table, p.theta);
+    let input = e.lookup_selector * compress(e.wires, p.theta) + (one - e.lookup_selector) * table;
+    terms.push((Oracle::ZLookup, -alpha_3 * (input + p.beta) * (table + p.gamma) + alpha_4 * p.l1));
+
+    // q_g·Σ α^k·e_k(a, b, c), α^k continuing past the lookup terms
+    for (g, gate) in vk.custom_gates.iter().enumerate() {
+        let scalar = gate.identities.iter().map(|identity| alpha.next().expect("the powers never run out") * identity.evaluate(e.wires)).sum();
+        terms.push((Oracle::CustomSelector(g), scalar));
+    }
+
+    // −Z_H(ζ)·(t_lo + ζ^n·t_mid + ζ^2n·t_hi)
+    let mut power = -p.vanishing;
+    for j in 0..3 {
+        terms.push((Oracle::Quotient(j), power));
+        power *= p.zeta_n;
+    }
+    terms
+}
+
+/// r₀: the terms of the identity at ζ that no polynomial of r carries,
+/// with `pi` the public input polynomial at ζ
+#[cfg(any(feature = "verifier", test))]
+pub(crate) fn constant_term<F: PrimeField>(e: &ProofEvaluations<F>, p: &EvaluationPoint<F>, pi: F) -> F {
+    let mut alpha = p.alpha_powers();
+    let mut next = || alpha.next().expect("the powers never run out");
+    let [a_p, s_p] = e.permuted;
+    let terms = [
+        // Permutation, with z and σ_c in r
+        -permuted_prefix(e, p) * (e.wires[2] + p.gamma),
+        // L_1·(z − 1)
+        -p.l1,
+        // Lookup product, with z_L in r
+        e.z_lookup_omega * (a_p + p.beta) * (s_p + p.gamma),
+        // L_1·(z_L − 1)
+        -p.l1,
+        p.l_last * (a_p - s_p),
+        (a_p - s_p) * (a_p - e.permuted_input_omega),
+    ];
+    terms.into_iter().fold(pi, |acc, term| acc + next() * term)
+}
+
+#[cfg(all(test, feature = "prover"))]
+mod tests {
+    use super::*;
+    use ark_bls12_381::{Bls12_381, Fr};
+    use ark_poly::Polynomial;
+    use ark_std::{UniformRand, Zero};
+
+    use crate::kgz::KZGParams;
+    use crate::prover::fixtures::circuit;
+    use crate::prover::{root_of_unity, Prover, ProvingKey};
+
+    #[test]
+    fn test_linearization_matches_identity() {
+        let mut rng = ark_std::test_rng();
+        let circuit = circuit::<Fr>(15);
+        let pk = ProvingKey::<Bls12_381>::preprocess(KZGParams::setup(9, &mut rng), &circuit).unwrap();
+        let proof = Prover::prove(&circuit, &pk, &mut rng).unwrap();
+        let ch = crate::proof::challenges(&pk.vk, &[], crate::transcript::TranscriptHash::Sha256, &[], &proof).unwrap();
+        let omega = root_of_unity::<Fr>(4);
+        let point = EvaluationPoint::new(4, omega, [ch.theta, ch.beta, ch.gamma, ch.alpha], ch.zeta);
+
+        // The proof's r̄ cancels r₀, and moving ζ breaks it
+        let e = &proof.evaluations;
+        assert_eq!(e.linearization + constant_term(e, &point, Fr::zero()), Fr::zero());
+        let elsewhere = EvaluationPoint::new(4, omega, [ch.theta, ch.beta, ch.gamma, ch.alpha], Fr::rand(&mut rng));
+        assert_ne!(e.linearization + constant_term(e, &elsewhere, Fr::zero()), Fr::zero());
+
+        // L_1 and L_n against their interpolations
+        let l = |row: usize| crate::prover::interpolate(&(0..4).map(|i| Fr::from((i == row) as u64)).collect::<Vec<_>>(), omega);
+        assert_eq!((point.l1, point.l_last), (l(0).evaluate(&ch.zeta), l(3).evaluate(&ch.zeta)));
+
+        // Five selectors, z and σ_c, z_L and the three quotient parts
+        let oracles: Vec<Oracle> = linear_terms(&pk.vk, e, &point).into_iter().map(|(oracle, _)| oracle).collect();
+        assert_eq!(oracles.len(), 11);
+        assert!(oracles.iter().all(|oracle| !crate::proof::OPENED_AT_ZETA.contains(oracle)));
+    }
+}
@@ -0,0 +1,205 @@
+use alloc::{collections::BTreeSet, vec::Vec};
+use ark_ff::PrimeField;
+
+use crate::circuit::{Circuit, CircuitSelectors, GateType};
+
+/// Highest constraint degree the prover's quotient split supports, that
+/// of the permutation argument
+pub const DEFAULT_MAX_DEGREE: usize = 4;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LintKind {
+    /// q_m is enabled together with q_l or q_r, mixing the mul and add gate shapes
+    ConflictingSelectors,
+    /// The row's selectors disagree with its gate type
+    SelectorMismatch { gate_type: GateType },
+    /// Selector or wire column lengths disagree with `n`
+    ColumnLength { column: &'static str, len: usize },
+    /// The row's constraint has higher degree than the backend supports
+    DegreeTooHigh { degree: usize, max: usize },
+    /// q_k = 1 but (a, b, c) is not a row of the lookup table, of which
+    /// `table_rows` were added
+    UnregisteredLookup { table_rows: usize },
+}
+
+/// One finding of `lint`. `row` is `None` for circuit-wide issues.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub row: Option<usize>,
+    pub kind: LintKind,
+}
+
+impl Diagnostic {
+    /// Stable identifier for tooling
+    pub fn code(&self) -> &'static str {
+        match self.kind {
+            LintKind::ConflictingSelectors => "conflicting-selectors",
+            LintKind::SelectorMismatch { .. } => "selector-mismatch",
+            LintKind::ColumnLength { .. } => "column-length",
+            LintKind::DegreeTooHigh { .. } => "degree-too-high",
+            LintKind::UnregisteredLookup { .. } => "unregistered-lookup",
+        }
+    }
+}
+
+impl core::fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        if let Some(row) = self.row {
+            write!(f, "row {}: ", row)?;
+        }
+        match &self.kind {
+            LintKind::ConflictingSelectors => write!(f, "q_m is enabled together with q_l or q_r"),
+            LintKind::SelectorMismatch { gate_type } => write!(f, "selectors do not match {:?} gate", gate_type),
+            LintKind::ColumnLength { column, len } => write!(f, "column {} has length {}", column, len),
+            LintKind::DegreeTooHigh { degree, max } => write!(f, "constraint degree {} exceeds maximum {}", degree, max),
+            LintKind::UnregisteredLookup { table_rows: 0 } => write!(f, "lookup into a circuit with no table"),
+            LintKind::UnregisteredLookup { .. } => write!(f, "lookup of (a, b, c) matches no table row"),
+        }?;
+        write!(f, " [{}]", self.code())
+    }
+}
+
+/// Degree of a row's constraint as a polynomial in selectors and wires:
+/// q_m * a * b is 3, q_l * a and the like are 2, q_c alone is 1
+fn row_degree(linear: bool, q_m: bool, q_c: bool) -> usize {
+    match (linear, q_m, q_c) {
+        (_, true, _) => 3,
+        (true, false, _) => 2,
+        (false, false, true) => 1,
+        (false, false, false) => 0,
+    }
+}
+
+/// Checks `circuit` for structural problems against `DEFAULT_MAX_DEGREE`
+pub fn lint<F: PrimeField>(circuit: &Circuit<F>) -> Vec<Diagnostic> {
+    lint_with_max_degree(circuit, DEFAULT_MAX_DEGREE)
+}
+
+pub fn lint_with_max_degree<F: PrimeField>(circuit: &Circuit<F>, max_degree: usize) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    let selectors = &circuit.selectors;
+
+    for (column, values) in CircuitSelectors::<F>::NAMES.into_iter().zip(selectors.columns()) {
+        let len = values.len();
+        if len != circuit.n {
+            diagnostics.push(Diagnostic { row: None, kind: LintKind::ColumnLength { column, len } });
+        }
+    }
+    for (column, len) in [("a", circuit.a.len()), ("b", circuit.b.len()), ("c", circuit.c.len())] {
+        // Wire columns cover the gates, or every row once padded
+        if len != circuit.gates.len() && len != circuit.n {
+            diagnostics.push(Diagnostic { row: None, kind: LintKind::ColumnLength { column, len } });
+        }
+    }
+
+    let rows = selectors.columns().iter().map(|column| column.len()).min().unwrap_or(0);
+    let (zero, one) = (F::zero(), F::one());
+    let table: BTreeSet<[F; 3]> = circuit.table.iter().copied().collect();
+    for row in 0..rows {
+        let q = selectors.columns().map(|column| column[row]);
+        let enabled = q.map(|x| !x.is_zero());
+        let linear = enabled[0] || enabled[1] || enabled[2];
+
+        if enabled[3] && (enabled[0] || enabled[1]) {
+            diagnostics.push(Diagnostic { row: Some(row), kind: LintKind::ConflictingSelectors });
+        }
+        if let Some(gate) = circuit.gates.get(row) {
+            let expected = match gate.gate_type {
+                GateType::Add => [one, one, -one, zero, zero, zero],
+                GateType::Mul => [zero, zero, -one, one, zero, zero],
+                // Only the multiplication and lookup selectors are fixed
+                GateType::Linear => [q[0], q[1], q[2], zero, q[4], zero],
+                GateType::Lookup => [zero, zero, zero, zero, zero, one],
+                GateType::Custom(_) => [zero; 6],
+            };
+            if q != expected {
+                diagnostics.push(Diagnostic {
+                    row: Some(row),
+                    kind: LintKind::SelectorMismatch { gate_type: gate.gate_type.clone() },
+                });
+            }
+        }
+
+        // A custom gate's selector scales each of its identities
+        let custom = circuit.selectors.custom.iter().zip(&circuit.custom_gates);
+        let custom_degree = custom
+            .filter(|(column, _)| column.get(row).is_some_and(|q| !q.is_zero()))
+            .map(|(_, gate)| gate.degree() as usize + 1)
+            .max();
+        let degree = row_degree(linear, enabled[3], enabled[4]).max(custom_degree.unwrap_or(0));
+        if degree > max_degree {
+            diagnostics.push(Diagnostic { row: Some(row), kind: LintKind::DegreeTooHigh { degree, max: max_degree } });
+        }
+
+        let wires = [&circuit.a, &circuit.b, &circuit.c].map(|column| column.get(row).copied().unwrap_or(zero));
+        if q[5] == one && !table.contains(&wires) {
+            diagnostics.push(Diagnostic { row: Some(row), kind: LintKind::UnregisteredLookup { table_rows: circuit.table.len() } });
+        }
+    }
+    diagnostics
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuit::{Gate, Wire};
+    use ark_bls12_381::Fr as F;
+    use ark_ff::One;
+
+    fn circuit() -> Circuit<F> {
+        let mut circuit = Circuit::new(2);
+        for gate_type in [GateType::Add, GateType::Mul] {
+            circuit.add_gate(Gate {
+                gate_type,
+                left_wire: Wire { index: 0, value: F::one() },
+                right_wire: Wire { index: 1, value: F::one() },
+                output_wire: Wire { index: 2, value: F::one() },
+            });
+        }
+        circuit
+    }
+
+    #[test]
+    fn test_clean_circuit() {
+        assert!(lint(&circuit()).is_empty());
+    }
+
+    #[test]
+    fn test_conflicting_selectors() {
+        let mut circuit = circuit();
+        circuit.selectors.q_m[0] = F::one();
+
+        let diagnostics = lint(&circuit);
+        assert_eq!(diagnostics.len(), 2);
+        assert_eq!(diagnostics[0], Diagnostic { row: Some(0), kind: LintKind::ConflictingSelectors });
+        assert_eq!(diagnostics[1].code(), "selector-mismatch");
+        assert_eq!(diagnostics[0].to_string(), "row 0: q_m is enabled together with q_l or q_r [conflicting-selectors]");
+    }
+
+    #[test]
+    fn test_degree_and_lengths() {
+        let mut circuit = circuit();
+        let diagnostics = lint_with_max_degree(&circuit, 2);
+        assert_eq!(diagnostics, vec![Diagnostic { row: Some(1), kind: LintKind::DegreeTooHigh { degree: 3, max: 2 } }]);
+
+        circuit.selectors.q_c.pop();
+        assert_eq!(lint(&circuit)[0].kind, LintKind::ColumnLength { column: "q_c", len: 1 });
+    }
+
+    #[test]
+    fn test_unregistered_lookup() {
+        use crate::lookup::Table;
+
+        let mut circuit = Circuit::<F>::new(4);
+        let [x, y, z] = [1u64, 2, 3].map(|value| circuit.alloc(F::from(value)));
+        circuit.lookup(x, y, z);
+        let diagnostics = lint(&circuit);
+        assert_eq!(diagnostics, vec![Diagnostic { row: Some(0), kind: LintKind::UnregisteredLookup { table_rows: 0 } }]);
+        assert_eq!(diagnostics[0].to_string(), "row 0: lookup into a circuit with no table [unregistered-lookup]");
+
+        circuit.add_table(Table::new(vec![[1u64, 2, 4].map(F::from)]));
+        assert_eq!(lint(&circuit)[0].kind, LintKind::UnregisteredLookup { table_rows: 1 });
+        circuit.add_table(Table::new(vec![[1u64, 2, 3].map(F::from)]));
+        assert!(lint(&circuit).is_empty());
+    }
+}
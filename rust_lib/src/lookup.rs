@@ -0,0 +1,195 @@
+#[cfg(feature = "prover")]
+use alloc::vec;
+use alloc::vec::Vec;
+use ark_ff::PrimeField;
+
+#[cfg(feature = "prover")]
+use crate::store::PolyStore;
+
+/// Rows (x, y, z) a lookup gate may take. Tables with fewer columns leave
+/// the rest zero.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Table<F> {
+    pub rows: Vec<[F; 3]>,
+}
+
+impl<F: PrimeField> Table<F> {
+    pub fn new(rows: Vec<[F; 3]>) -> Self {
+        Self { rows }
+    }
+
+    /// One-column table of `values`, e.g. 0..2^k for range checks
+    pub fn from_column(values: impl IntoIterator<Item = F>) -> Self {
+        Self {
+            rows: values.into_iter().map(|x| [x, F::zero(), F::zero()]).collect(),
+        }
+    }
+
+    /// Two-input table of `op(x, y)` over all x, y below `size`, e.g. XOR
+    /// of 4-bit limbs
+    pub fn from_binary_op(size: u64, op: impl Fn(u64, u64) -> u64) -> Self {
+        let rows = (0..size)
+            .flat_map(|x| (0..size).map(move |y| (x, y)))
+            .map(|(x, y)| [F::from(x), F::from(y), F::from(op(x, y))])
+            .collect();
+        Self { rows }
+    }
+
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+}
+
+/// The table as three columns of `rows` rows, the last entry repeated
+/// into the unused ones (all zero for an empty table). `None` if the
+/// table has more entries than rows.
+pub fn padded_columns<F: PrimeField>(table: &[[F; 3]], rows: usize) -> Option<[Vec<F>; 3]> {
+    if table.len() > rows {
+        return None;
+    }
+    let last = table.last().copied().unwrap_or([F::zero(); 3]);
+    Some([0, 1, 2].map(|j| {
+        let mut column: Vec<F> = table.iter().map(|row| row[j]).collect();
+        column.resize(rows, last[j]);
+        column
+    }))
+}
+
+/// x + θ·y + θ²·z, the single value the argument compares per row
+pub fn compress<F: PrimeField>(row: [F; 3], theta: F) -> F {
+    row[0] + theta * (row[1] + theta * row[2])
+}
+
+/// A and S over H: the compressed wires on lookup rows and the table row
+/// elsewhere, so every input is a table entry on an honest witness, and
+/// the compressed table
+#[cfg(feature = "prover")]
+pub(crate) fn compressed_columns<F: PrimeField>(
+    q_k: &[F],
+    wires: [&dyn PolyStore<F>; 3],
+    table: &[Vec<F>; 3],
+    theta: F,
+) -> (Vec<F>, Vec<F>) {
+    let table: Vec<F> = (0..q_k.len()).map(|i| compress([table[0][i], table[1][i], table[2][i]], theta)).collect();
+    let input = (0..q_k.len())
+        .map(|i| {
+            let looked_up = compress(wires.map(|wire| wire.get(i)), theta);
+            q_k[i] * looked_up + (F::one() - q_k[i]) * table[i]
+        })
+        .collect();
+    (input, table)
+}
+
+/// A' and S': the inputs sorted, and the table rearranged so that the
+/// last input of every run of equal values sits next to that value in
+/// S'. The argument then only has to check, row by row, that
+///
+///   A'_i = S'_i or A'_i = A'_{i+1}
+///
+/// and A' = S' on the last row. An input missing from the table leaves
+/// its slot to a leftover entry and the proof fails to verify.
+#[cfg(feature = "prover")]
+pub(crate) fn permute<F: PrimeField>(input: &[F], table: &[F]) -> (Vec<F>, Vec<F>) {
+    let n = input.len();
+    let mut permuted_input = input.to_vec();
+    permuted_input.sort();
+
+    let mut remaining = alloc::collections::BTreeMap::new();
+    for x in table {
+        *remaining.entry(*x).or_insert(0usize) += 1;
+    }
+    let mut permuted_table = vec![None; n];
+    for i in 0..n {
+        let x = permuted_input[i];
+        if i + 1 < n && permuted_input[i + 1] == x {
+            continue;
+        }
+        if let Some(count) = remaining.get_mut(&x).filter(|count| **count > 0) {
+            *count -= 1;
+            permuted_table[i] = Some(x);
+        }
+    }
+
+    let mut leftovers = remaining.into_iter().flat_map(|(x, count)| core::iter::repeat_n(x, count));
+    let permuted_table = permuted_table
+        .into_iter()
+        .map(|slot| slot.or_else(|| leftovers.next()).expect("one leftover entry per empty slot"))
+        .collect();
+    (permuted_input, permuted_table)
+}
+
+/// z_L over H: z_L(ω^0) = 1 and
+///   z_L(ω^{i+1}) = z_L(ω^i) · (A_i + β)(S_i + γ) / ((A'_i + β)(S'_i + γ))
+#[cfg(feature = "prover")]
+pub(crate) fn grand_product<F: PrimeField>(
+    input: &[F],
+    table: &[F],
+    permuted: [&Vec<F>; 2],
+    beta: F,
+    gamma: F,
+) -> Vec<F> {
+    let n = input.len();
+    let mut denominators: Vec<F> = (0..n).map(|i| (permuted[0][i] + beta) * (permuted[1][i] + gamma)).collect();
+    ark_ff::batch_inversion(&mut denominators);
+
+    let mut z = Vec::with_capacity(n);
+    let mut acc = F::one();
+    for i in 0..n {
+        z.push(acc);
+        acc *= (input[i] + beta) * (table[i] + gamma) * denominators[i];
+    }
+    z
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bls12_381::Fr;
+
+    #[test]
+    #[cfg(feature = "prover")]
+    fn test_permute() {
+        let f = |values: &[u64]| values.iter().map(|x| Fr::from(*x)).collect::<Vec<_>>();
+        let table = f(&[1, 2, 3, 4, 4, 4, 4, 4]);
+        let input = f(&[3, 1, 3, 3, 4, 2, 1, 4]);
+        let (a, s) = permute(&input, &table);
+
+        let mut sorted_s = s.clone();
+        sorted_s.sort();
+        assert_eq!(sorted_s, f(&[1, 2, 3, 4, 4, 4, 4, 4]));
+        assert_eq!(a, f(&[1, 1, 2, 3, 3, 3, 4, 4]));
+        for i in 0..a.len() {
+            let next = a[(i + 1) % a.len()];
+            assert!(a[i] == s[i] || a[i] == next, "row {}", i);
+        }
+        assert_eq!(a[7], s[7]);
+
+        let z = grand_product(&input, &table, [&a, &s], Fr::from(7u64), Fr::from(11u64));
+        assert_eq!(z[0], Fr::from(1u64));
+        let last = (input[7] + Fr::from(7u64)) * (table[7] + Fr::from(11u64))
+            / ((a[7] + Fr::from(7u64)) * (s[7] + Fr::from(11u64)));
+        assert_eq!(z[7] * last, Fr::from(1u64));
+
+        // 5 is not in the table, so some run has no matching entry
+        let (a, s) = permute(&f(&[5, 1, 1, 1, 1, 1, 1, 1]), &table);
+        assert!((0..8).any(|i| a[i] != s[i] && a[i] != a[(i + 1) % 8]));
+    }
+
+    #[test]
+    fn test_tables() {
+        let xor = Table::<Fr>::from_binary_op(4, |x, y| x ^ y);
+        assert_eq!(xor.len(), 16);
+        assert!(xor.rows.contains(&[Fr::from(3u64), Fr::from(1u64), Fr::from(2u64)]));
+
+        let range = Table::from_column((0..3u64).map(Fr::from));
+        let columns = padded_columns(&range.rows, 4).unwrap();
+        assert_eq!(columns[0], [0u64, 1, 2, 2].map(Fr::from));
+        assert_eq!(columns[2], [Fr::from(0u64); 4]);
+        assert_eq!(padded_columns::<Fr>(&[], 2).unwrap()[0], [Fr::from(0u64); 2]);
+        assert!(padded_columns(&range.rows, 2).is_none());
+    }
+}
@@ -0,0 +1,93 @@
+//! Weighted sums over a `Circuit` for the frontends that import other
+//! constraint formats, and the gate count they come to ahead of time, so
+//! a frontend can size the circuit it synthesizes into.
+
+use alloc::collections::{BTreeMap, BTreeSet};
+use ark_ff::PrimeField;
+
+use crate::circuit::{Circuit, Variable};
+
+/// A circuit being filled by a frontend, with each constant made once
+pub(crate) struct Lowering<'a, F: PrimeField> {
+    pub circuit: &'a mut Circuit<F>,
+    constants: BTreeMap<F, Variable>,
+}
+
+impl<'a, F: PrimeField> Lowering<'a, F> {
+    pub fn new(circuit: &'a mut Circuit<F>) -> Self {
+        Self { circuit, constants: BTreeMap::new() }
+    }
+
+    /// A variable fixed to `value`, one gate the first time it is asked for
+    pub fn constant(&mut self, value: F) -> Variable {
+        if let Some(var) = self.constants.get(&value) {
+            return *var;
+        }
+        let var = self.circuit.constant(value);
+        self.constants.insert(value, var);
+        var
+    }
+
+    /// Σ coefficient·x + offset: a mul by the constant coefficient for
+    /// each term whose coefficient is not 1, and an add gate for each
+    /// term and nonzero offset after the first
+    pub fn sum(&mut self, terms: impl IntoIterator<Item = (F, Variable)>, offset: F) -> Variable {
+        let mut acc: Option<Variable> = None;
+        for (coefficient, x) in terms {
+            let term = if coefficient.is_one() {
+                x
+            } else {
+                let c = self.constant(coefficient);
+                self.circuit.mul(c, x)
+            };
+            acc = Some(match acc {
+                None => term,
+                Some(acc) => self.circuit.add(acc, term),
+            });
+        }
+        match acc {
+            None => self.constant(offset),
+            Some(acc) if offset.is_zero() => acc,
+            Some(acc) => {
+                let offset = self.constant(offset);
+                self.circuit.add(acc, offset)
+            }
+        }
+    }
+}
+
+/// Gates a `Lowering` making the same calls would add
+#[derive(Debug, Default)]
+pub(crate) struct GateCount<F: PrimeField> {
+    pub gates: usize,
+    constants: BTreeSet<F>,
+}
+
+impl<F: PrimeField> GateCount<F> {
+    pub fn constant(&mut self, value: F) {
+        if self.constants.insert(value) {
+            self.gates += 1;
+        }
+    }
+
+    /// `sum` over terms with these coefficients
+    pub fn sum(&mut self, coefficients: impl IntoIterator<Item = F>, offset: F) {
+        let mut terms = 0;
+        for coefficient in coefficients {
+            if !coefficient.is_one() {
+                self.constant(coefficient);
+                self.gates += 1;
+            }
+            terms += 1;
+        }
+        if terms == 0 {
+            self.constant(offset);
+            return;
+        }
+        self.gates += terms - 1;
+        if !offset.is_zero() {
+            self.constant(offset);
+            self.gates += 1;
+        }
+    }
+}
@@ -0,0 +1,303 @@
+use alloc::{string::String, vec::Vec};
+use ark_ec::{AffineRepr, CurveGroup};
+use ark_ff::PrimeField;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_std::rand::Rng;
+use ark_std::UniformRand;
+use sha2::{Digest, Sha256};
+use zeroize::Zeroize;
+
+/// Information about how a proof was produced. It travels next to the
+/// proof bytes and never changes them.
+#[derive(Debug, Clone, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct ProofMetadata {
+    pub crate_version: String,
+    pub backend: String,
+    pub curve: String,
+    pub circuit_digest: [u8; 32],
+    /// Unix time in seconds
+    pub timestamp: u64,
+    /// Debug rendering of the prover config used
+    pub prover_config: String,
+    pub proving_time_ms: u64,
+}
+
+impl ProofMetadata {
+    /// Metadata for this build of the crate, stamped with the current time
+    #[cfg(feature = "std")]
+    pub fn new(curve: &str, circuit_digest: [u8; 32]) -> Self {
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        Self {
+            crate_version: env!("CARGO_PKG_VERSION").to_string(),
+            backend: crate::backend::backend_name().to_string(),
+            curve: curve.to_string(),
+            circuit_digest,
+            timestamp,
+            prover_config: String::new(),
+            proving_time_ms: 0,
+        }
+    }
+}
+
+/// Schnorr signing key over the group `G`
+#[derive(Debug, Clone)]
+pub struct SigningKey<G: CurveGroup> {
+    secret: G::ScalarField,
+    pub public: G::Affine,
+}
+
+impl<G: CurveGroup> SigningKey<G> {
+    pub fn generate<R: Rng>(rng: &mut R) -> Self {
+        let secret = G::ScalarField::rand(rng);
+        Self {
+            secret,
+            public: (G::generator() * secret).into_affine(),
+        }
+    }
+
+    /// Schnorr signature (R, s) with s = k + H(R, pk, msg) * sk
+    pub fn sign<R: Rng>(&self, message: &[u8], rng: &mut R) -> Signature<G> {
+        let k = G::ScalarField::rand(rng);
+        let r = (G::generator() * k).into_affine();
+        let e = challenge::<G>(&r, &self.public, message);
+        Signature { r, s: k + e * self.secret }
+    }
+}
+
+impl<G: CurveGroup> Drop for SigningKey<G> {
+    fn drop(&mut self) {
+        self.secret.zeroize();
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct Signature<G: CurveGroup> {
+    pub r: G::Affine,
+    pub s: G::ScalarField,
+}
+
+impl<G: CurveGroup> Signature<G> {
+    /// s·G == R + H(R, pk, msg)·pk
+    pub fn verify(&self, public: &G::Affine, message: &[u8]) -> bool {
+        let e = challenge::<G>(&self.r, public, message);
+        G::generator() * self.s == self.r.into_group() + *public * e
+    }
+}
+
+fn challenge<G: CurveGroup>(r: &G::Affine, public: &G::Affine, message: &[u8]) -> G::ScalarField {
+    let mut hasher = Sha256::new();
+    hasher.update(b"plonk-metadata-sig");
+    let mut bytes = Vec::new();
+    r.serialize_compressed(&mut bytes).expect("writing to a Vec");
+    public.serialize_compressed(&mut bytes).expect("writing to a Vec");
+    hasher.update(&bytes);
+    hasher.update(message);
+    G::ScalarField::from_le_bytes_mod_order(&hasher.finalize())
+}
+
+/// Serialized proof wrapped with signed metadata
+#[derive(Debug, Clone, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct ProofEnvelope<G: CurveGroup> {
+    pub metadata: ProofMetadata,
+    pub proof: Vec<u8>,
+    pub signer: G::Affine,
+    pub signature: Signature<G>,
+}
+
+/// What the signature covers: the metadata and a hash of the proof bytes
+fn signed_message(metadata: &ProofMetadata, proof: &[u8]) -> Vec<u8> {
+    let mut message = Vec::new();
+    metadata.serialize_compressed(&mut message).expect("writing to a Vec");
+    message.extend_from_slice(&Sha256::digest(proof));
+    message
+}
+
+impl<G: CurveGroup> ProofEnvelope<G> {
+    pub fn seal<R: Rng>(metadata: ProofMetadata, proof: Vec<u8>, key: &SigningKey<G>, rng: &mut R) -> Self {
+        let signature = key.sign(&signed_message(&metadata, &proof), rng);
+        Self {
+            metadata,
+            proof,
+            signer: key.public,
+            signature,
+        }
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        self.serialize_compressed(&mut bytes).expect("writing to a Vec");
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        Self::deserialize_compressed(bytes).ok()
+    }
+
+    /// Checks the signature and `policy`, returning the inner proof bytes.
+    /// `now` is the current unix time in seconds.
+    pub fn open(&self, policy: &MetadataPolicy<G>, now: u64) -> Result<&[u8], PolicyError> {
+        if !self.signature.verify(&self.signer, &signed_message(&self.metadata, &self.proof)) {
+            return Err(PolicyError::BadSignature);
+        }
+        policy.check(&self.signer, &self.metadata, now)?;
+        Ok(&self.proof)
+    }
+}
+
+/// Restrictions a verifier places on envelopes; empty lists allow anything
+#[derive(Debug, Clone)]
+pub struct MetadataPolicy<G: CurveGroup> {
+    pub allowed_signers: Vec<G::Affine>,
+    pub allowed_curves: Vec<String>,
+    pub allowed_backends: Vec<String>,
+    pub allowed_circuits: Vec<[u8; 32]>,
+    /// Oldest accepted crate version, as (major, minor, patch)
+    pub min_version: Option<(u64, u64, u64)>,
+    /// Maximum proof age in seconds
+    pub max_age: Option<u64>,
+}
+
+impl<G: CurveGroup> Default for MetadataPolicy<G> {
+    fn default() -> Self {
+        Self {
+            allowed_signers: Vec::new(),
+            allowed_curves: Vec::new(),
+            allowed_backends: Vec::new(),
+            allowed_circuits: Vec::new(),
+            min_version: None,
+            max_age: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PolicyError {
+    BadSignature,
+    SignerNotAllowed,
+    CurveNotAllowed(String),
+    BackendNotAllowed(String),
+    CircuitNotAllowed([u8; 32]),
+    VersionTooOld(String),
+    Expired { age: u64 },
+}
+
+impl core::fmt::Display for PolicyError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            PolicyError::BadSignature => write!(f, "metadata signature is invalid"),
+            PolicyError::SignerNotAllowed => write!(f, "signer is not in the allowed list"),
+            PolicyError::CurveNotAllowed(c) => write!(f, "curve {} is not allowed", c),
+            PolicyError::BackendNotAllowed(b) => write!(f, "backend {} is not allowed", b),
+            PolicyError::CircuitNotAllowed(_) => write!(f, "circuit digest is not allowed"),
+            PolicyError::VersionTooOld(v) => write!(f, "prover version {} is too old", v),
+            PolicyError::Expired { age } => write!(f, "proof is {} seconds old", age),
+        }
+    }
+}
+
+impl<G: CurveGroup> MetadataPolicy<G> {
+    pub fn check(&self, signer: &G::Affine, metadata: &ProofMetadata, now: u64) -> Result<(), PolicyError> {
+        if !self.allowed_signers.is_empty() && !self.allowed_signers.contains(signer) {
+            return Err(PolicyError::SignerNotAllowed);
+        }
+        if !self.allowed_curves.is_empty() && !self.allowed_curves.contains(&metadata.curve) {
+            return Err(PolicyError::CurveNotAllowed(metadata.curve.clone()));
+        }
+        if !self.allowed_backends.is_empty() && !self.allowed_backends.contains(&metadata.backend) {
+            return Err(PolicyError::BackendNotAllowed(metadata.backend.clone()));
+        }
+        if !self.allowed_circuits.is_empty() && !self.allowed_circuits.contains(&metadata.circuit_digest) {
+            return Err(PolicyError::CircuitNotAllowed(metadata.circuit_digest));
+        }
+        if let Some(min) = self.min_version {
+            match parse_version(&metadata.crate_version) {
+                Some(version) if version >= min => {}
+                _ => return Err(PolicyError::VersionTooOld(metadata.crate_version.clone())),
+            }
+        }
+        if let Some(max_age) = self.max_age {
+            let age = now.saturating_sub(metadata.timestamp);
+            if age > max_age {
+                return Err(PolicyError::Expired { age });
+            }
+        }
+        Ok(())
+    }
+}
+
+fn parse_version(version: &str) -> Option<(u64, u64, u64)> {
+    let mut parts = version.split('.').map(|p| p.parse::<u64>().ok());
+    Some((parts.next()??, parts.next()??, parts.next()??))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bls12_381::G1Projective as G1;
+
+    fn metadata() -> ProofMetadata {
+        ProofMetadata {
+            crate_version: "0.1.0".to_string(),
+            backend: "arkworks".to_string(),
+            curve: "bls12-381".to_string(),
+            circuit_digest: [7u8; 32],
+            timestamp: 1_000,
+            prover_config: String::new(),
+            proving_time_ms: 12,
+        }
+    }
+
+    #[test]
+    fn test_envelope_roundtrip() {
+        let mut rng = ark_std::test_rng();
+        let key = SigningKey::<G1>::generate(&mut rng);
+        let envelope = ProofEnvelope::seal(metadata(), vec![1, 2, 3], &key, &mut rng);
+
+        let decoded = ProofEnvelope::<G1>::from_bytes(&envelope.to_bytes()).unwrap();
+        assert_eq!(decoded, envelope);
+        assert_eq!(decoded.open(&MetadataPolicy::default(), 1_000).unwrap(), &[1, 2, 3]);
+    }
+
+    #[test]
+    fn test_envelope_tampering() {
+        let mut rng = ark_std::test_rng();
+        let key = SigningKey::<G1>::generate(&mut rng);
+        let envelope = ProofEnvelope::seal(metadata(), vec![1, 2, 3], &key, &mut rng);
+        let policy = MetadataPolicy::default();
+
+        let mut tampered = envelope.clone();
+        tampered.proof[0] = 9;
+        assert_eq!(tampered.open(&policy, 1_000), Err(PolicyError::BadSignature));
+
+        let mut tampered = envelope.clone();
+        tampered.metadata.curve = "bn254".to_string();
+        assert_eq!(tampered.open(&policy, 1_000), Err(PolicyError::BadSignature));
+    }
+
+    #[test]
+    fn test_policy_enforcement() {
+        let mut rng = ark_std::test_rng();
+        let key = SigningKey::<G1>::generate(&mut rng);
+        let other = SigningKey::<G1>::generate(&mut rng);
+        let envelope = ProofEnvelope::seal(metadata(), vec![1, 2, 3], &key, &mut rng);
+
+        let policy = MetadataPolicy::<G1> { allowed_signers: vec![other.public], ..Default::default() };
+        assert_eq!(envelope.open(&policy, 1_000), Err(PolicyError::SignerNotAllowed));
+
+        let policy = MetadataPolicy::<G1> { allowed_curves: vec!["bn254".to_string()], ..Default::default() };
+        assert!(matches!(envelope.open(&policy, 1_000), Err(PolicyError::CurveNotAllowed(_))));
+
+        let policy = MetadataPolicy::<G1> { allowed_circuits: vec![[0u8; 32]], ..Default::default() };
+        assert!(matches!(envelope.open(&policy, 1_000), Err(PolicyError::CircuitNotAllowed(_))));
+
+        let policy = MetadataPolicy::<G1> { min_version: Some((0, 2, 0)), ..Default::default() };
+        assert!(matches!(envelope.open(&policy, 1_000), Err(PolicyError::VersionTooOld(_))));
+
+        let policy = MetadataPolicy::<G1> { max_age: Some(60), ..Default::default() };
+        assert!(envelope.open(&policy, 1_060).is_ok());
+        assert_eq!(envelope.open(&policy, 1_061), Err(PolicyError::Expired { age: 61 }));
+    }
+}
@@ -0,0 +1,73 @@
+//! Quadratic-time reference implementations of the polynomial routines
+//! and a term-by-term MSM, used to cross-check the fast paths in tests.
+
+use ark_ec::CurveGroup;
+use ark_ff::Field;
+
+/// Lagrange interpolation through `(domain[i], evals[i])`
+pub fn interpolate<F: Field>(evals: &[F], domain: &[F]) -> Vec<F> {
+    assert_eq!(evals.len(), domain.len(), "Evaluation and domain size mismatch");
+    let n = evals.len();
+    let mut result = vec![F::zero(); n];
+
+    for i in 0..n {
+        // basis = prod_{j != i} (X - x_j), built up coefficient by coefficient
+        let mut basis = vec![F::one()];
+        let mut denominator = F::one();
+        for j in (0..n).filter(|&j| j != i) {
+            basis = multiply(&basis, &[-domain[j], F::one()]);
+            denominator *= domain[i] - domain[j];
+        }
+        let scale = evals[i] * denominator.inverse().expect("domain points must be distinct");
+        for (r, b) in result.iter_mut().zip(&basis) {
+            *r += scale * b;
+        }
+    }
+    result
+}
+
+/// Σ scalars[i]·bases[i], one full scalar multiplication per term
+pub fn msm<G: CurveGroup>(bases: &[G::Affine], scalars: &[G::ScalarField]) -> G {
+    bases.iter().zip(scalars).map(|(base, scalar)| *base * scalar).sum()
+}
+
+/// Schoolbook product of two coefficient vectors
+pub fn multiply<F: Field>(a: &[F], b: &[F]) -> Vec<F> {
+    if a.is_empty() || b.is_empty() {
+        return Vec::new();
+    }
+    let mut result = vec![F::zero(); a.len() + b.len() - 1];
+    for (i, x) in a.iter().enumerate() {
+        for (j, y) in b.iter().enumerate() {
+            result[i + j] += *x * y;
+        }
+    }
+    result
+}
+
+/// Long division, returning (quotient, remainder) with trailing zeros
+/// stripped
+pub fn divide<F: Field>(numerator: &[F], divisor: &[F]) -> (Vec<F>, Vec<F>) {
+    let divisor = trim(divisor);
+    let lead_inv = divisor.last().expect("division by zero polynomial").inverse().unwrap();
+    let mut remainder = trim(numerator).to_vec();
+    if remainder.len() < divisor.len() {
+        return (Vec::new(), remainder);
+    }
+
+    let mut quotient = vec![F::zero(); remainder.len() - divisor.len() + 1];
+    for k in (0..quotient.len()).rev() {
+        let coeff = remainder[k + divisor.len() - 1] * lead_inv;
+        quotient[k] = coeff;
+        for (j, d) in divisor.iter().enumerate() {
+            remainder[k + j] -= coeff * d;
+        }
+    }
+    remainder.truncate(divisor.len() - 1);
+    (trim(&quotient).to_vec(), trim(&remainder).to_vec())
+}
+
+pub fn trim<F: Field>(coeffs: &[F]) -> &[F] {
+    let len = coeffs.iter().rposition(|c| !c.is_zero()).map_or(0, |i| i + 1);
+    &coeffs[..len]
+}
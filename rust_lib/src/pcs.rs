@@ -0,0 +1,207 @@
+//! Polynomial commitment schemes behind one interface, so code written
+//! against `PolynomialCommitment` runs on KZG (`KZGParams`, constant-size
+//! openings, trusted setup) or on the inner product argument
+//! (`IpaParams`, logarithmic openings and linear-time verification, no
+//! trusted setup). Code written against the trait takes the scheme as a
+//! value, so the choice is made once, where that code is set up.
+//!
+//! `ProvingKey`, `VerifyingKey`, `Proof` and `Verifier` are generic over
+//! the scheme, `KZGParams` by default: the key is preprocessed from
+//! whichever scheme is passed in, and the verifier checks the two batched
+//! openings through `verify_openings`, which KZG folds into one pairing check.
+
+use alloc::vec::Vec;
+use ark_ff::PrimeField;
+use ark_poly::polynomial::univariate::DensePolynomial;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_std::Zero;
+
+use crate::kgz::{CommitError, KZGParams, KZGVerifierKey};
+use crate::msm::{curve_msm, CurveMul, CurvePairing, MsmConfig};
+use crate::transcript::Transcript;
+
+/// Commitments to polynomials over `F` with single-point openings. The
+/// parameters serialize, since proving keys hold them.
+pub trait PolynomialCommitment<F: PrimeField>: CanonicalSerialize + CanonicalDeserialize {
+    type Commitment: Copy + Eq + core::fmt::Debug + CanonicalSerialize + CanonicalDeserialize;
+    type Proof: Clone + Eq + core::fmt::Debug + CanonicalSerialize + CanonicalDeserialize;
+    /// What a verifier keeps of the scheme
+    type VerifierKey: Clone + Eq + core::fmt::Debug + CanonicalSerialize + CanonicalDeserialize;
+
+    /// Largest degree `commit` accepts, `None` when it accepts nothing
+    fn max_degree(&self) -> Option<usize>;
+
+    fn verifier_key(&self) -> Self::VerifierKey;
+
+    fn commit(&self, poly: &DensePolynomial<F>) -> Result<Self::Commitment, CommitError>;
+
+    /// `commit` with the MSM tuned by `config`, for schemes that commit
+    /// with one; the same commitment either way
+    fn commit_with(&self, poly: &DensePolynomial<F>, _config: &MsmConfig) -> Result<Self::Commitment, CommitError> {
+        self.commit(poly)
+    }
+
+    /// A proof of p(z) and the value
+    fn open(&self, poly: &DensePolynomial<F>, z: F) -> Result<(Self::Proof, F), CommitError>;
+
+    /// `open` with the MSM tuned by `config`; the same proof either way
+    fn open_with(&self, poly: &DensePolynomial<F>, z: F, _config: &MsmConfig) -> Result<(Self::Proof, F), CommitError> {
+        self.open(poly, z)
+    }
+
+    fn verify(&self, commitment: &Self::Commitment, proof: &Self::Proof, z: F, value: F) -> bool;
+
+    /// Σ scalars[i]·commitments[i], the commitment to the same combination
+    /// of the polynomials
+    fn combine(commitments: &[Self::Commitment], scalars: &[F]) -> Self::Commitment;
+
+    /// `verify` from the verifier key alone
+    fn verify_opening(key: &Self::VerifierKey, opening: &Opening<F, Self>) -> bool;
+
+    /// Checks every one of `openings`. `u` is a verifier challenge a
+    /// scheme may fold them with; by default each is checked alone.
+    fn verify_openings(key: &Self::VerifierKey, openings: &[Opening<F, Self>], _u: F) -> bool {
+        openings.iter().all(|opening| Self::verify_opening(key, opening))
+    }
+
+    /// One proof of every polynomial of `polys` at `z`, and the values in
+    /// order. The commitments must already be in `transcript`.
+    fn open_batch(&self, polys: &[DensePolynomial<F>], z: F, transcript: &mut Transcript) -> Result<(Self::Proof, Vec<F>), CommitError>;
+
+    /// Checks a proof of `open_batch`, replaying the same transcript
+    fn verify_batch(&self, commitments: &[Self::Commitment], proof: &Self::Proof, z: F, values: &[F], transcript: &mut Transcript) -> bool;
+}
+
+/// A claim p(z) = value about the polynomial under `commitment`, with
+/// its proof
+pub struct Opening<'a, F: PrimeField, S: PolynomialCommitment<F>> {
+    pub commitment: S::Commitment,
+    pub proof: &'a S::Proof,
+    pub z: F,
+    pub value: F,
+}
+
+impl<E: CurvePairing> PolynomialCommitment<E::ScalarField> for KZGParams<E> {
+    type Commitment = E::G1Affine;
+    type Proof = E::G1Affine;
+    type VerifierKey = KZGVerifierKey<E>;
+
+    fn max_degree(&self) -> Option<usize> {
+        self.powers_of_g.len().checked_sub(1)
+    }
+
+    fn verifier_key(&self) -> KZGVerifierKey<E> {
+        KZGParams::verifier_key(self)
+    }
+
+    fn commit(&self, poly: &DensePolynomial<E::ScalarField>) -> Result<E::G1Affine, CommitError> {
+        KZGParams::commit(self, poly)
+    }
+
+    fn commit_with(&self, poly: &DensePolynomial<E::ScalarField>, config: &MsmConfig) -> Result<E::G1Affine, CommitError> {
+        KZGParams::commit_with(self, poly, config)
+    }
+
+    fn open(&self, poly: &DensePolynomial<E::ScalarField>, z: E::ScalarField) -> Result<(E::G1Affine, E::ScalarField), CommitError> {
+        KZGParams::open(self, poly, z)
+    }
+
+    fn open_with(
+        &self,
+        poly: &DensePolynomial<E::ScalarField>,
+        z: E::ScalarField,
+        config: &MsmConfig,
+    ) -> Result<(E::G1Affine, E::ScalarField), CommitError> {
+        KZGParams::open_with(self, poly, z, config)
+    }
+
+    fn verify(&self, commitment: &E::G1Affine, proof: &E::G1Affine, z: E::ScalarField, value: E::ScalarField) -> bool {
+        KZGParams::verify(self, commitment, proof, z, value)
+    }
+
+    fn combine(commitments: &[E::G1Affine], scalars: &[E::ScalarField]) -> E::G1Affine {
+        combine::<E::G1>(commitments, scalars)
+    }
+
+    fn verify_opening(key: &KZGVerifierKey<E>, opening: &Opening<E::ScalarField, Self>) -> bool {
+        key.verify(&opening.commitment, opening.proof, opening.z, opening.value)
+    }
+
+    /// All openings in one pairing check, `KZGVerifierKey::fold`
+    fn verify_openings(key: &KZGVerifierKey<E>, openings: &[Opening<E::ScalarField, Self>], u: E::ScalarField) -> bool {
+        let (lhs, rhs) = key.fold(openings, u);
+        E::multi_pairing([lhs, -rhs], [key.g2_s, key.g2]).is_zero()
+    }
+
+    fn open_batch(
+        &self,
+        polys: &[DensePolynomial<E::ScalarField>],
+        z: E::ScalarField,
+        transcript: &mut Transcript,
+    ) -> Result<(E::G1Affine, Vec<E::ScalarField>), CommitError> {
+        KZGParams::open_batch(self, polys, z, transcript)
+    }
+
+    fn verify_batch(
+        &self,
+        commitments: &[E::G1Affine],
+        proof: &E::G1Affine,
+        z: E::ScalarField,
+        values: &[E::ScalarField],
+        transcript: &mut Transcript,
+    ) -> bool {
+        KZGParams::verify_batch(self, commitments, proof, z, values, transcript)
+    }
+}
+
+/// Σ scalars[i]·points[i], for schemes whose commitments are points;
+/// pairs past the shorter slice are ignored
+pub(crate) fn combine<G: CurveMul>(points: &[G::Affine], scalars: &[G::ScalarField]) -> G::Affine {
+    let len = points.len().min(scalars.len());
+    curve_msm::<G>(&points[..len], &scalars[..len], &MsmConfig::default()).expect("sliced to one length").into_affine()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ipa::IpaParams;
+    use ark_bls12_381::{Bls12_381, Fr};
+    use ark_ff::One;
+    use ark_poly::DenseUVPolynomial;
+
+    /// The same checks on any scheme
+    fn check_scheme<S: PolynomialCommitment<Fr>>(scheme: &S) {
+        let mut rng = ark_std::test_rng();
+        let poly = DensePolynomial::<Fr>::rand(scheme.max_degree().unwrap(), &mut rng);
+        let commitment = scheme.commit(&poly).unwrap();
+        let z = Fr::from(7u64);
+        let (proof, value) = scheme.open(&poly, z).unwrap();
+        assert_eq!(value, ark_poly::Polynomial::evaluate(&poly, &z));
+        assert!(scheme.verify(&commitment, &proof, z, value));
+        assert!(!scheme.verify(&commitment, &proof, z, value + Fr::one()));
+        assert!(!scheme.verify(&commitment, &proof, z + Fr::one(), value));
+
+        let other = scheme.commit(&(&poly + &DensePolynomial::from_coefficients_vec(vec![Fr::one()]))).unwrap();
+        assert!(!scheme.verify(&other, &proof, z, value));
+        assert_eq!(scheme.commit_with(&poly, &MsmConfig { window_bits: Some(3), ..MsmConfig::default() }).unwrap(), commitment);
+
+        let polys = [poly.clone(), DensePolynomial::<Fr>::rand(3, &mut rng), DensePolynomial::<Fr>::rand(1, &mut rng)];
+        let commitments: Vec<S::Commitment> = polys.iter().map(|poly| scheme.commit(poly).unwrap()).collect();
+        let (proof, values) = scheme.open_batch(&polys, z, &mut Transcript::new(b"test")).unwrap();
+        assert_eq!(values[0], value);
+        assert!(scheme.verify_batch(&commitments, &proof, z, &values, &mut Transcript::new(b"test")));
+        assert!(!scheme.verify_batch(&commitments, &proof, z, &values, &mut Transcript::new(b"other")));
+        assert!(!scheme.verify_batch(&commitments[..2], &proof, z, &values, &mut Transcript::new(b"test")));
+        let swapped = [values[1], values[0], values[2]];
+        assert!(!scheme.verify_batch(&commitments, &proof, z, &swapped, &mut Transcript::new(b"test")));
+
+        let too_long = DensePolynomial::<Fr>::rand(scheme.max_degree().unwrap() + 1, &mut rng);
+        assert!(matches!(scheme.commit(&too_long), Err(CommitError::SrsTooSmall { .. })));
+    }
+
+    #[test]
+    fn test_schemes_agree_on_the_interface() {
+        check_scheme(&KZGParams::<Bls12_381>::setup(15, &mut ark_std::test_rng()));
+        check_scheme(&IpaParams::<ark_bls12_381::G1Projective>::new(b"plonk-ipa-test", 15));
+    }
+}
@@ -0,0 +1,361 @@
+use alloc::{vec, vec::Vec};
+use ark_ff::FftField;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, Compress, Read, SerializationError, Valid, Validate};
+
+/// Position of a wire value: `column` (0 = a, 1 = b, 2 = c, ...) and `row`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, CanonicalSerialize, CanonicalDeserialize)]
+pub struct Cell {
+    pub column: usize,
+    pub row: usize,
+}
+
+impl Cell {
+    pub fn new(column: usize, row: usize) -> Self {
+        Self { column, row }
+    }
+}
+
+/// Arguments that do not fit the permutation they are applied to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PermutationError {
+    /// A cell outside the columns and rows of the permutation
+    OutOfRange(Cell),
+    /// `sigma_evaluations` needs one coset multiplier per column
+    MultiplierCount { expected: usize, got: usize },
+}
+
+impl core::fmt::Display for PermutationError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            PermutationError::OutOfRange(cell) => write!(f, "cell {:?} is outside the permutation", cell),
+            PermutationError::MultiplierCount { expected, got } => {
+                write!(f, "expected {} coset multipliers, got {}", expected, got)
+            }
+        }
+    }
+}
+
+/// Collects copy constraints in a union-find and turns the resulting
+/// equivalence classes into the cycles of σ
+#[derive(Debug, Clone)]
+pub struct PermutationBuilder {
+    columns: usize,
+    rows: usize,
+    parent: Vec<usize>,
+    size: Vec<usize>,
+}
+
+impl PermutationBuilder {
+    pub fn new(columns: usize, rows: usize) -> Self {
+        let cells = columns * rows;
+        Self {
+            columns,
+            rows,
+            parent: (0..cells).collect(),
+            size: vec![1; cells],
+        }
+    }
+
+    pub fn columns(&self) -> usize {
+        self.columns
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    fn index(&self, cell: Cell) -> Result<usize, PermutationError> {
+        if cell.column < self.columns && cell.row < self.rows {
+            Ok(cell.column * self.rows + cell.row)
+        } else {
+            Err(PermutationError::OutOfRange(cell))
+        }
+    }
+
+    fn cell(&self, index: usize) -> Cell {
+        Cell::new(index / self.rows, index % self.rows)
+    }
+
+    fn find(&mut self, mut x: usize) -> usize {
+        let mut root = x;
+        while self.parent[root] != root {
+            root = self.parent[root];
+        }
+        while self.parent[x] != root {
+            let next = self.parent[x];
+            self.parent[x] = root;
+            x = next;
+        }
+        root
+    }
+
+    /// Requires the values in `a` and `b` to be equal
+    pub fn connect(&mut self, a: Cell, b: Cell) -> Result<(), PermutationError> {
+        let (a, b) = (self.index(a)?, self.index(b)?);
+        let (mut ra, mut rb) = (self.find(a), self.find(b));
+        if ra == rb {
+            return Ok(());
+        }
+        if self.size[ra] < self.size[rb] {
+            core::mem::swap(&mut ra, &mut rb);
+        }
+        self.parent[rb] = ra;
+        self.size[ra] += self.size[rb];
+        Ok(())
+    }
+
+    /// Whether `a` and `b` are constrained equal; false if either lies
+    /// outside the permutation
+    pub fn are_connected(&mut self, a: Cell, b: Cell) -> bool {
+        match (self.index(a), self.index(b)) {
+            (Ok(a), Ok(b)) => self.find(a) == self.find(b),
+            _ => false,
+        }
+    }
+
+    /// Every cell constrained equal to `cell`, itself included, in
+    /// column-major order; empty for a cell outside the permutation
+    pub fn cycle_of(&mut self, cell: Cell) -> Vec<Cell> {
+        let Ok(index) = self.index(cell) else {
+            return Vec::new();
+        };
+        let root = self.find(index);
+        let members: Vec<usize> = (0..self.parent.len()).filter(|&i| self.find(i) == root).collect();
+        members.into_iter().map(|i| self.cell(i)).collect()
+    }
+
+    /// All cycles with more than one cell, each in column-major order.
+    /// Useful to see which wires a copy constraint actually joined.
+    pub fn cycles(&mut self) -> Vec<Vec<Cell>> {
+        let mut classes: Vec<Vec<usize>> = vec![Vec::new(); self.parent.len()];
+        for i in 0..self.parent.len() {
+            let root = self.find(i);
+            classes[root].push(i);
+        }
+        let mut cycles: Vec<Vec<Cell>> = classes
+            .into_iter()
+            .filter(|class| class.len() > 1)
+            .map(|class| class.into_iter().map(|i| self.cell(i)).collect())
+            .collect();
+        cycles.sort();
+        cycles
+    }
+
+    /// σ from the cycle decomposition: each cell maps to the next cell of
+    /// its cycle, the last one back to the first
+    pub fn build(mut self) -> Permutation {
+        let mut sigma: Vec<Vec<Cell>> = (0..self.columns)
+            .map(|column| (0..self.rows).map(|row| Cell::new(column, row)).collect())
+            .collect();
+        for cycle in self.cycles() {
+            for (i, cell) in cycle.iter().enumerate() {
+                sigma[cell.column][cell.row] = cycle[(i + 1) % cycle.len()];
+            }
+        }
+        Permutation { sigma }
+    }
+}
+
+/// σ as an explicit mapping, `sigma[column][row]` being the image of that
+/// cell. Serialized as that mapping; decoding with validation rejects one
+/// that is not a bijection.
+#[derive(Debug, Clone, PartialEq, Eq, CanonicalSerialize)]
+pub struct Permutation {
+    sigma: Vec<Vec<Cell>>,
+}
+
+impl Permutation {
+    /// Takes σ given directly as a vector per column, for callers that
+    /// construct it themselves. Returns `None` unless it is a bijection on
+    /// the cells.
+    pub fn from_mapping(sigma: Vec<Vec<Cell>>) -> Option<Self> {
+        is_bijection(&sigma).then_some(Self { sigma })
+    }
+
+    pub fn columns(&self) -> usize {
+        self.sigma.len()
+    }
+
+    pub fn rows(&self) -> usize {
+        self.sigma.first().map_or(0, |c| c.len())
+    }
+
+    pub fn image(&self, cell: Cell) -> Cell {
+        self.sigma[cell.column][cell.row]
+    }
+
+    pub fn mapping(&self) -> &[Vec<Cell>] {
+        &self.sigma
+    }
+
+    /// Evaluations of the σ polynomials over the domain generated by
+    /// `omega`: cell (j, i) is labelled k_j·ω^i, so σ_j(ω^i) is the label
+    /// of the image of (j, i). `ks` holds one coset multiplier per column.
+    pub fn sigma_evaluations<F: FftField>(&self, omega: F, ks: &[F]) -> Result<Vec<Vec<F>>, PermutationError> {
+        if ks.len() != self.columns() {
+            return Err(PermutationError::MultiplierCount { expected: self.columns(), got: ks.len() });
+        }
+        let powers: Vec<F> = core::iter::successors(Some(F::one()), |p| Some(*p * omega))
+            .take(self.rows())
+            .collect();
+        Ok(self
+            .sigma
+            .iter()
+            .map(|column| column.iter().map(|cell| ks[cell.column] * powers[cell.row]).collect())
+            .collect())
+    }
+
+    /// Whether `values[column][row]` agrees on every cycle
+    pub fn is_satisfied_by<T: PartialEq>(&self, values: &[Vec<T>]) -> bool {
+        self.sigma.iter().enumerate().all(|(column, images)| {
+            images
+                .iter()
+                .enumerate()
+                .all(|(row, image)| values[column][row] == values[image.column][image.row])
+        })
+    }
+}
+
+fn is_bijection(sigma: &[Vec<Cell>]) -> bool {
+    let rows = sigma.first().map_or(0, |c| c.len());
+    if sigma.iter().any(|c| c.len() != rows) {
+        return false;
+    }
+    let mut seen = vec![false; sigma.len() * rows];
+    sigma.iter().flatten().all(|cell| {
+        cell.column < sigma.len() && cell.row < rows && !core::mem::replace(&mut seen[cell.column * rows + cell.row], true)
+    })
+}
+
+impl Valid for Permutation {
+    fn check(&self) -> Result<(), SerializationError> {
+        if is_bijection(&self.sigma) {
+            Ok(())
+        } else {
+            Err(SerializationError::InvalidData)
+        }
+    }
+}
+
+impl CanonicalDeserialize for Permutation {
+    fn deserialize_with_mode<R: Read>(reader: R, compress: Compress, validate: Validate) -> Result<Self, SerializationError> {
+        let permutation = Self { sigma: Vec::deserialize_with_mode(reader, compress, validate)? };
+        if validate == Validate::Yes {
+            permutation.check()?;
+        }
+        Ok(permutation)
+    }
+}
+
+/// Whether the cosets k_i·H of the order-`domain_size` subgroup H are
+/// pairwise distinct. k_i·H = k_j·H exactly when (k_i / k_j)^n = 1.
+pub fn cosets_are_distinct<F: FftField>(ks: &[F], domain_size: usize) -> bool {
+    let n = [domain_size as u64];
+    ks.iter().enumerate().all(|(i, ki)| {
+        ks[..i].iter().all(|kj| match kj.inverse() {
+            Some(kj_inv) => (*ki * kj_inv).pow(n) != F::one(),
+            None => false,
+        })
+    })
+}
+
+/// One coset multiplier per column with k_0 = 1, so column 0 is labelled by
+/// H itself. Candidates 2, 3, ... are taken greedily and each is kept only
+/// if its coset differs from all those already chosen, which makes the
+/// result distinct by construction for any column count.
+pub fn coset_multipliers<F: FftField>(columns: usize, domain_size: usize) -> Vec<F> {
+    let n = [domain_size as u64];
+    let mut ks: Vec<F> = Vec::with_capacity(columns);
+    // (k^n) of the chosen multipliers; two cosets coincide iff these match
+    let mut tags: Vec<F> = Vec::with_capacity(columns);
+    let mut candidate = F::one();
+    while ks.len() < columns {
+        let tag = candidate.pow(n);
+        if !tag.is_zero() && !tags.contains(&tag) {
+            ks.push(candidate);
+            tags.push(tag);
+        }
+        candidate += F::one();
+    }
+    ks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bls12_381::Fr;
+    use ark_ff::Field;
+
+    #[test]
+    fn test_cycles() {
+        let mut builder = PermutationBuilder::new(3, 4);
+        builder.connect(Cell::new(2, 0), Cell::new(0, 1)).unwrap();
+        builder.connect(Cell::new(0, 1), Cell::new(1, 3)).unwrap();
+        builder.connect(Cell::new(1, 0), Cell::new(1, 2)).unwrap();
+        builder.connect(Cell::new(1, 2), Cell::new(1, 0)).unwrap();
+        assert_eq!(builder.connect(Cell::new(0, 0), Cell::new(3, 0)), Err(PermutationError::OutOfRange(Cell::new(3, 0))));
+
+        assert!(builder.are_connected(Cell::new(2, 0), Cell::new(1, 3)));
+        assert!(!builder.are_connected(Cell::new(2, 0), Cell::new(1, 0)));
+        assert_eq!(
+            builder.cycles(),
+            vec![
+                vec![Cell::new(0, 1), Cell::new(1, 3), Cell::new(2, 0)],
+                vec![Cell::new(1, 0), Cell::new(1, 2)],
+            ]
+        );
+        assert_eq!(builder.cycle_of(Cell::new(0, 0)), vec![Cell::new(0, 0)]);
+
+        let sigma = builder.build();
+        assert_eq!(sigma.image(Cell::new(0, 1)), Cell::new(1, 3));
+        assert_eq!(sigma.image(Cell::new(2, 0)), Cell::new(0, 1));
+        assert_eq!(sigma.image(Cell::new(2, 3)), Cell::new(2, 3));
+        assert_eq!(Permutation::from_mapping(sigma.mapping().to_vec()), Some(sigma));
+    }
+
+    #[test]
+    fn test_from_mapping_rejects_non_bijection() {
+        let identity = vec![vec![Cell::new(0, 0), Cell::new(0, 1)]];
+        assert!(Permutation::from_mapping(identity).is_some());
+        assert!(Permutation::from_mapping(vec![vec![Cell::new(0, 0), Cell::new(0, 0)]]).is_none());
+        assert!(Permutation::from_mapping(vec![vec![Cell::new(0, 0), Cell::new(1, 0)]]).is_none());
+
+        // Decoding checks the same
+        let mut bytes = Vec::new();
+        vec![vec![Cell::new(0, 0), Cell::new(0, 0)]].serialize_compressed(&mut bytes).unwrap();
+        assert!(Permutation::deserialize_compressed(&bytes[..]).is_err());
+    }
+
+    #[test]
+    fn test_satisfaction_and_sigma_evaluations() {
+        let mut builder = PermutationBuilder::new(2, 2);
+        builder.connect(Cell::new(0, 0), Cell::new(1, 1)).unwrap();
+        let sigma = builder.build();
+
+        assert!(sigma.is_satisfied_by(&[vec![5, 1], vec![2, 5]]));
+        assert!(!sigma.is_satisfied_by(&[vec![5, 1], vec![2, 6]]));
+
+        let omega = Fr::from(-1i64);
+        let ks = [Fr::from(1u64), Fr::from(7u64)];
+        let evals = sigma.sigma_evaluations(omega, &ks).unwrap();
+        assert_eq!(sigma.sigma_evaluations(omega, &ks[..1]), Err(PermutationError::MultiplierCount { expected: 2, got: 1 }));
+        assert_eq!(evals[0][0], ks[1] * omega);
+        assert_eq!(evals[1][1], ks[0]);
+        assert_eq!(evals[1][0], ks[1]);
+        assert_eq!(omega.pow([2u64]), Fr::from(1u64));
+    }
+
+    #[test]
+    fn test_coset_multipliers() {
+        for columns in [1, 3, 4, 10] {
+            let ks = coset_multipliers::<Fr>(columns, 8);
+            assert_eq!(ks.len(), columns);
+            assert_eq!(ks[0], Fr::from(1u64));
+            assert!(cosets_are_distinct(&ks, 8));
+        }
+
+        let omega = <Fr as ark_ff::FftField>::get_root_of_unity(8).unwrap();
+        assert!(!cosets_are_distinct(&[Fr::from(1u64), omega], 8));
+        assert!(!cosets_are_distinct(&[Fr::from(3u64), Fr::from(3u64) * omega.pow([3u64])], 8));
+    }
+}
@@ -0,0 +1,477 @@
+use alloc::vec::Vec;
+use ark_poly::polynomial::univariate::DensePolynomial;
+use ark_poly::polynomial::Polynomial;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_std::rand::Rng;
+use ark_std::{One, Zero};
+use sha2::{Digest, Sha256};
+use zeroize::Zeroize;
+
+use crate::constraint_system::Witness;
+use crate::lookup;
+use crate::grand_product::grand_product;
+use crate::kgz::KZGParams;
+use crate::linearization::{linear_terms, EvaluationPoint};
+use crate::msm::CurvePairing;
+use crate::pcs::PolynomialCommitment;
+use crate::proof::{
+    absorb_evaluations, transcript_for, Oracle, Proof, ProofEvaluations, ScheduleError, ScheduledTranscript, OPENED_AT_ZETA,
+    OPENED_AT_ZETA_OMEGA,
+};
+use crate::prover::{
+    blind, check_public_rows, commit_each, domain_evaluations, interpolate, public_input_evaluations, root_of_unity,
+    PlonkCircuit, ProveError, Prover, ProverConfig, ProvingKey,
+};
+use crate::quotient::{quotient, split, LookupPolys, RoundChallenges};
+use crate::transcript::TranscriptHash;
+
+/// A stage output whose digest does not match its contents, the proving
+/// key, or the stage it claims to follow
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StageError {
+    pub stage: &'static str,
+}
+
+impl core::fmt::Display for StageError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "integrity check failed for the {} stage", self.stage)
+    }
+}
+
+/// Output of round 1: the wire polynomials and their commitments.
+///
+/// Every stage output carries a digest over its own contents chained with
+/// the digest of the stage before it (the first one with the verifying key
+/// and public inputs), so a stage received from another machine is checked
+/// before any work is done on it.
+#[derive(Clone, CanonicalSerialize, CanonicalDeserialize)]
+pub struct WireStage<E: CurvePairing, S: PolynomialCommitment<E::ScalarField> = KZGParams<E>> {
+    pub session: Vec<u8>,
+    pub transcript: TranscriptHash,
+    pub public_inputs: Vec<E::ScalarField>,
+    wires: [DensePolynomial<E::ScalarField>; 3],
+    pub wire_commitments: [S::Commitment; 3],
+    digest: [u8; 32],
+}
+
+/// Output of round 2: the lookup columns A' and S', and the permutation
+/// and lookup grand products z and z_L
+#[derive(Clone, CanonicalSerialize, CanonicalDeserialize)]
+pub struct PermutationStage<E: CurvePairing, S: PolynomialCommitment<E::ScalarField> = KZGParams<E>> {
+    permuted: [DensePolynomial<E::ScalarField>; 2],
+    pub lookup_commitments: [S::Commitment; 2],
+    z: DensePolynomial<E::ScalarField>,
+    pub z_commitment: S::Commitment,
+    z_lookup: DensePolynomial<E::ScalarField>,
+    pub z_lookup_commitment: S::Commitment,
+    digest: [u8; 32],
+}
+
+/// Output of round 3: t split into t_lo, t_mid, t_hi
+#[derive(Clone, CanonicalSerialize, CanonicalDeserialize)]
+pub struct QuotientStage<E: CurvePairing, S: PolynomialCommitment<E::ScalarField> = KZGParams<E>> {
+    parts: [DensePolynomial<E::ScalarField>; 3],
+    pub quotient_commitments: [S::Commitment; 3],
+    digest: [u8; 32],
+}
+
+/// The transcript up to α, and the challenges drawn on the way
+type RoundTranscript<F> = (ScheduledTranscript<F>, RoundChallenges<F>);
+
+fn encode<T: CanonicalSerialize + ?Sized>(item: &T) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    item.serialize_compressed(&mut bytes).expect("writing to a Vec");
+    bytes
+}
+
+fn digest(stage: &str, previous: &[u8; 32], body: &[Vec<u8>]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(stage.as_bytes());
+    hasher.update(previous);
+    for part in body {
+        hasher.update(part);
+    }
+    hasher.finalize().into()
+}
+
+fn check(stage: &'static str, expected: [u8; 32], actual: &[u8; 32]) -> Result<(), StageError> {
+    if expected == *actual {
+        Ok(())
+    } else {
+        Err(StageError { stage })
+    }
+}
+
+impl<E: CurvePairing, S: PolynomialCommitment<E::ScalarField>> WireStage<E, S> {
+    fn origin(pk: &ProvingKey<E, S>, session: &[u8], transcript: TranscriptHash, public_inputs: &[E::ScalarField]) -> [u8; 32] {
+        digest("origin", &[0u8; 32], &[encode(&pk.vk), encode(session), encode(&transcript), encode(public_inputs)])
+    }
+
+    fn body_digest(&self, pk: &ProvingKey<E, S>) -> [u8; 32] {
+        let origin = Self::origin(pk, &self.session, self.transcript, &self.public_inputs);
+        digest("wires", &origin, &[encode(&self.wires), encode(&self.wire_commitments)])
+    }
+
+    /// Checks the digest against `pk` and the contents
+    pub fn check(&self, pk: &ProvingKey<E, S>) -> Result<(), StageError> {
+        check("wire", self.body_digest(pk), &self.digest)
+    }
+}
+
+impl<E: CurvePairing, S: PolynomialCommitment<E::ScalarField>> PermutationStage<E, S> {
+    fn body_digest(&self, previous: &[u8; 32]) -> [u8; 32] {
+        let body = [
+            encode(&self.permuted),
+            encode(&self.lookup_commitments),
+            encode(&self.z),
+            encode(&self.z_commitment),
+            encode(&self.z_lookup),
+            encode(&self.z_lookup_commitment),
+        ];
+        digest("permutation", previous, &body)
+    }
+
+    /// The transcript up to α, with the challenges drawn on the way
+    fn transcript(
+        &self,
+        pk: &ProvingKey<E, S>,
+        wires: &WireStage<E, S>,
+    ) -> Result<RoundTranscript<E::ScalarField>, ScheduleError> {
+        let mut transcript = transcript_for(&pk.vk, &wires.session, wires.transcript, &wires.public_inputs)?;
+        transcript.absorb("wire_commitments", &wires.wire_commitments)?;
+        let theta = transcript.squeeze("theta")?;
+        transcript.absorb("lookup_commitments", &self.lookup_commitments)?;
+        let beta = transcript.squeeze("beta")?;
+        let gamma = transcript.squeeze("gamma")?;
+        transcript.absorb("z_commitment", &self.z_commitment)?;
+        transcript.absorb("z_lookup_commitment", &self.z_lookup_commitment)?;
+        let alpha = transcript.squeeze("alpha")?;
+        Ok((transcript, RoundChallenges { theta, beta, gamma, alpha }))
+    }
+
+    /// Checks the digest against the contents and the wire stage
+    pub fn check(&self, pk: &ProvingKey<E, S>, wires: &WireStage<E, S>) -> Result<(), StageError> {
+        wires.check(pk)?;
+        check("permutation", self.body_digest(&wires.digest), &self.digest)
+    }
+}
+
+impl<E: CurvePairing, S: PolynomialCommitment<E::ScalarField>> QuotientStage<E, S> {
+    fn body_digest(&self, previous: &[u8; 32]) -> [u8; 32] {
+        digest("quotient", previous, &[encode(&self.parts), encode(&self.quotient_commitments)])
+    }
+
+    /// Checks the digest against the contents and both earlier stages
+    pub fn check(
+        &self,
+        pk: &ProvingKey<E, S>,
+        wires: &WireStage<E, S>,
+        permutation: &PermutationStage<E, S>,
+    ) -> Result<(), StageError> {
+        permutation.check(pk, wires)?;
+        check("quotient", self.body_digest(&permutation.digest), &self.digest)
+    }
+}
+
+impl<E: CurvePairing, S: PolynomialCommitment<E::ScalarField>> Drop for WireStage<E, S> {
+    fn drop(&mut self) {
+        for wire in &mut self.wires {
+            wire.coeffs.zeroize();
+        }
+    }
+}
+
+impl<E: CurvePairing, S: PolynomialCommitment<E::ScalarField>> Drop for PermutationStage<E, S> {
+    fn drop(&mut self) {
+        self.permuted[0].coeffs.zeroize();
+        self.z.coeffs.zeroize();
+        self.z_lookup.coeffs.zeroize();
+    }
+}
+
+impl Prover {
+    /// Round 1, the only stage that reads the witness. The wires get
+    /// blinding from `rng`, as do the round 2 polynomials.
+    pub fn commit_wires<E: CurvePairing, S: PolynomialCommitment<E::ScalarField>, R: Rng>(
+        circuit: &PlonkCircuit<E::ScalarField>,
+        pk: &ProvingKey<E, S>,
+        config: &ProverConfig,
+        rng: &mut R,
+    ) -> Result<WireStage<E, S>, ProveError> {
+        Self::commit_columns([&circuit.a, &circuit.b, &circuit.c], circuit.public_inputs.clone(), pk, config, rng)
+    }
+
+    /// `commit_wires` for a witness without its circuit, with the public
+    /// inputs read off the key's public rows
+    pub fn commit_witness<E: CurvePairing, S: PolynomialCommitment<E::ScalarField>, R: Rng>(
+        witness: &Witness<E::ScalarField>,
+        pk: &ProvingKey<E, S>,
+        config: &ProverConfig,
+        rng: &mut R,
+    ) -> Result<WireStage<E, S>, ProveError> {
+        let public_inputs = pk.vk.public_rows.iter().map(|row| witness.a.get(*row as usize).copied().unwrap_or_default()).collect();
+        Self::commit_columns([&witness.a, &witness.b, &witness.c], public_inputs, pk, config, rng)
+    }
+
+    fn commit_columns<E: CurvePairing, S: PolynomialCommitment<E::ScalarField>, R: Rng>(
+        columns: [&Vec<E::ScalarField>; 3],
+        public_inputs: Vec<E::ScalarField>,
+        pk: &ProvingKey<E, S>,
+        config: &ProverConfig,
+        rng: &mut R,
+    ) -> Result<WireStage<E, S>, ProveError> {
+        let n = pk.vk.domain_size as usize;
+        if columns.iter().any(|column| column.len() != n) {
+            return Err(ProveError::Rows { expected: n, got: columns[0].len() });
+        }
+        ProvingKey::<E, S>::check_domain(n)?;
+        ProvingKey::<E, S>::check_srs_degree(&pk.params, n)?;
+        check_public_rows(pk.vk.public_rows.iter().map(|row| *row as usize), n)?;
+        if public_inputs.len() != pk.vk.public_rows.len() {
+            return Err(ProveError::PublicInputCount { expected: pk.vk.public_rows.len(), got: public_inputs.len() });
+        }
+        let omega = root_of_unity::<E::ScalarField>(n);
+
+        let mut wires = columns.map(|column| interpolate(column, omega));
+        for wire in &mut wires {
+            blind(wire, n, 2, rng);
+        }
+        let wire_commitments = commit_each(&pk.params, &wires, &config.msm)?;
+        let mut stage = WireStage {
+            session: config.session.clone(),
+            transcript: config.transcript,
+            public_inputs,
+            wires,
+            wire_commitments,
+            digest: [0u8; 32],
+        };
+        stage.digest = stage.body_digest(pk);
+        Ok(stage)
+    }
+
+    /// Round 2, run from the wire polynomials alone
+    pub fn commit_permutation<E: CurvePairing, S: PolynomialCommitment<E::ScalarField>, R: Rng>(
+        pk: &ProvingKey<E, S>,
+        wires: &WireStage<E, S>,
+        config: &ProverConfig,
+        rng: &mut R,
+    ) -> Result<PermutationStage<E, S>, ProveError> {
+        wires.check(pk)?;
+        let n = pk.vk.domain_size as usize;
+        let omega = root_of_unity::<E::ScalarField>(n);
+
+        let evaluations = |poly: &DensePolynomial<E::ScalarField>| domain_evaluations(poly, n, omega);
+        let [a, b, c] = wires.wires.each_ref().map(|wire| config.store.store(evaluations(wire)));
+        let stores = [a?, b?, c?];
+        let columns = stores.each_ref().map(|store| &**store);
+
+        let mut transcript = transcript_for(&pk.vk, &wires.session, wires.transcript, &wires.public_inputs)?;
+        transcript.absorb("wire_commitments", &wires.wire_commitments)?;
+        let theta = transcript.squeeze("theta")?;
+        let q_k = evaluations(&pk.selectors[5]);
+        let (mut input, table) = lookup::compressed_columns(&q_k, columns, &pk.table_evals, theta);
+        let (mut permuted_input, permuted_table) = lookup::permute(&input, &table);
+        let mut permuted = [interpolate(&permuted_input, omega), interpolate(&permuted_table, omega)];
+        // A' is also opened at ζω
+        blind(&mut permuted[0], n, 3, rng);
+        blind(&mut permuted[1], n, 2, rng);
+        let lookup_commitments = commit_each(&pk.params, &permuted, &config.msm)?;
+        transcript.absorb("lookup_commitments", &lookup_commitments)?;
+        let beta = transcript.squeeze("beta")?;
+        let gamma = transcript.squeeze("gamma")?;
+
+        let z_lookup_evals = lookup::grand_product(&input, &table, [&permuted_input, &permuted_table], beta, gamma);
+        input.zeroize();
+        permuted_input.zeroize();
+        // The stores wipe the wire evaluations when dropped
+        let z_evals = grand_product(columns, &pk.sigma_evals, &pk.vk.coset_multipliers, omega, beta, gamma);
+        drop(stores);
+
+        let mut z = interpolate(&z_evals, omega);
+        blind(&mut z, n, 3, rng);
+        let mut z_lookup = interpolate(&z_lookup_evals, omega);
+        blind(&mut z_lookup, n, 3, rng);
+        let products = [z, z_lookup];
+        let [z_commitment, z_lookup_commitment] = commit_each(&pk.params, &products, &config.msm)?;
+        let [z, z_lookup] = products;
+        let mut stage = PermutationStage {
+            permuted,
+            lookup_commitments,
+            z,
+            z_commitment,
+            z_lookup,
+            z_lookup_commitment,
+            digest: [0u8; 32],
+        };
+        stage.digest = stage.body_digest(&wires.digest);
+        Ok(stage)
+    }
+
+    /// Round 3, the FFT-heavy stage
+    pub fn commit_quotient<E: CurvePairing, S: PolynomialCommitment<E::ScalarField>>(
+        pk: &ProvingKey<E, S>,
+        wires: &WireStage<E, S>,
+        permutation: &PermutationStage<E, S>,
+        config: &ProverConfig,
+    ) -> Result<QuotientStage<E, S>, ProveError> {
+        permutation.check(pk, wires)?;
+        let n = pk.vk.domain_size as usize;
+        let omega = root_of_unity::<E::ScalarField>(n);
+
+        let (_, challenges) = permutation.transcript(pk, wires)?;
+
+        let public_rows: Vec<usize> = pk.vk.public_rows.iter().map(|row| *row as usize).collect();
+        let pi = interpolate(&public_input_evaluations(&public_rows, &wires.public_inputs, n), omega);
+        let lookup = LookupPolys { permuted: &permutation.permuted, z: &permutation.z_lookup };
+        let t = quotient(pk, &wires.wires, &permutation.z, lookup, &pi, &challenges, config)?;
+        let parts = split(&t, n);
+        let quotient_commitments = commit_each(&pk.params, &parts, &config.msm)?;
+        let mut stage = QuotientStage {
+            parts,
+            quotient_commitments,
+            digest: [0u8; 32],
+        };
+        stage.digest = stage.body_digest(&permutation.digest);
+        Ok(stage)
+    }
+
+    /// Rounds 4 and 5: evaluations at ζ, the linearization r and the
+    /// opening proofs
+    pub fn finish<E: CurvePairing, S: PolynomialCommitment<E::ScalarField>>(
+        pk: &ProvingKey<E, S>,
+        wires: &WireStage<E, S>,
+        permutation: &PermutationStage<E, S>,
+        quotient: &QuotientStage<E, S>,
+        config: &ProverConfig,
+    ) -> Result<Proof<E, S>, ProveError> {
+        quotient.check(pk, wires, permutation)?;
+        let n = pk.vk.domain_size as usize;
+        let omega = root_of_unity::<E::ScalarField>(n);
+        let z = &permutation.z;
+
+        let (mut transcript, ch) = permutation.transcript(pk, wires)?;
+        transcript.absorb("quotient_commitments", &quotient.quotient_commitments)?;
+        let zeta = transcript.squeeze("zeta")?;
+
+        let permuted = &permutation.permuted;
+        let z_lookup = &permutation.z_lookup;
+        let poly = |oracle: Oracle| match oracle {
+            Oracle::Wire(j) => &wires.wires[j],
+            Oracle::Selector(j) => &pk.selectors[j],
+            Oracle::CustomSelector(j) => &pk.custom_selectors[j],
+            Oracle::Sigma(j) => &pk.sigmas[j],
+            Oracle::Z => z,
+            Oracle::Table(j) => &pk.table[j],
+            Oracle::Permuted(j) => &permuted[j],
+            Oracle::ZLookup => z_lookup,
+            Oracle::Quotient(j) => &quotient.parts[j],
+        };
+        let mut evaluations = ProofEvaluations {
+            wires: [0, 1, 2].map(|j| wires.wires[j].evaluate(&zeta)),
+            sigmas: [0, 1].map(|j| pk.sigmas[j].evaluate(&zeta)),
+            z_omega: z.evaluate(&(zeta * omega)),
+            lookup_selector: pk.selectors[5].evaluate(&zeta),
+            table: [0, 1, 2].map(|j| pk.table[j].evaluate(&zeta)),
+            permuted: [0, 1].map(|j| permuted[j].evaluate(&zeta)),
+            z_lookup_omega: z_lookup.evaluate(&(zeta * omega)),
+            permuted_input_omega: permuted[0].evaluate(&(zeta * omega)),
+            linearization: E::ScalarField::zero(),
+        };
+        let point = EvaluationPoint::new(n as u64, omega, [ch.theta, ch.beta, ch.gamma, ch.alpha], zeta);
+        let mut linearization = DensePolynomial::zero();
+        for (oracle, scalar) in linear_terms(&pk.vk, &evaluations, &point) {
+            linearization += (scalar, poly(oracle));
+        }
+        evaluations.linearization = linearization.evaluate(&zeta);
+        absorb_evaluations(&mut transcript, &evaluations)?;
+        let v = transcript.squeeze("v")?;
+
+        // Batched openings at ζ, r + Σ v^(i+1)·p_i, and at ζω, Σ v^i·p_i
+        let batch = |mut combined: DensePolynomial<E::ScalarField>, mut power: E::ScalarField, oracles: &[Oracle]| {
+            for oracle in oracles {
+                combined += (power, poly(*oracle));
+                power *= v;
+            }
+            combined
+        };
+        let (w_zeta, _) = pk.params.open_with(&batch(linearization, v, &OPENED_AT_ZETA), zeta, &config.msm)?;
+        let at_zeta_omega = batch(DensePolynomial::zero(), E::ScalarField::one(), &OPENED_AT_ZETA_OMEGA);
+        let (w_zeta_omega, _) = pk.params.open_with(&at_zeta_omega, zeta * omega, &config.msm)?;
+
+        Ok(Proof {
+            wire_commitments: wires.wire_commitments,
+            lookup_commitments: permutation.lookup_commitments,
+            z_commitment: permutation.z_commitment,
+            z_lookup_commitment: permutation.z_lookup_commitment,
+            quotient_commitments: quotient.quotient_commitments,
+            evaluations,
+            w_zeta,
+            w_zeta_omega,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prover::fixtures::circuit;
+    use ark_bls12_381::{Bls12_381, Fr};
+
+    fn roundtrip<T: CanonicalSerialize + CanonicalDeserialize>(stage: &T) -> T {
+        let mut bytes = Vec::new();
+        stage.serialize_compressed(&mut bytes).unwrap();
+        T::deserialize_compressed(&bytes[..]).unwrap()
+    }
+
+    #[test]
+    fn test_staged_proof_matches_direct_proof() {
+        let mut rng = ark_std::test_rng();
+        let circuit = circuit::<Fr>(15);
+        let pk = ProvingKey::<Bls12_381>::preprocess(KZGParams::setup(9, &mut rng), &circuit).unwrap();
+        let config = ProverConfig::default();
+
+        // Each stage crosses a serialization boundary, as between machines
+        // Blinding is drawn from the same seed as the direct proof's
+        let mut blinding = ark_std::test_rng();
+        let wires = roundtrip(&Prover::commit_wires(&circuit, &pk, &config, &mut blinding).unwrap());
+        let permutation = roundtrip(&Prover::commit_permutation(&pk, &wires, &config, &mut blinding).unwrap());
+        let quotient = roundtrip(&Prover::commit_quotient(&pk, &wires, &permutation, &config).unwrap());
+        let proof = Prover::finish(&pk, &wires, &permutation, &quotient, &config).unwrap();
+
+        assert_eq!(proof, Prover::prove(&circuit, &pk, &mut ark_std::test_rng()).unwrap());
+
+        let mut extra = circuit.clone();
+        extra.public_inputs.push(Fr::one());
+        assert_eq!(
+            Prover::commit_wires(&extra, &pk, &config, &mut rng).err(),
+            Some(ProveError::PublicInputCount { expected: 0, got: 1 })
+        );
+    }
+
+    #[test]
+    fn test_tampered_stage_is_rejected() {
+        let mut rng = ark_std::test_rng();
+        let circuit = circuit::<Fr>(15);
+        let pk = ProvingKey::<Bls12_381>::preprocess(KZGParams::setup(9, &mut rng), &circuit).unwrap();
+        let config = ProverConfig::default();
+
+        let wires = Prover::commit_wires(&circuit, &pk, &config, &mut rng).unwrap();
+        let mut forged = wires.clone();
+        forged.public_inputs.push(Fr::one());
+        assert_eq!(
+            Prover::commit_permutation(&pk, &forged, &config, &mut rng).err(),
+            Some(ProveError::Stage(StageError { stage: "wire" }))
+        );
+
+        // A permutation stage from another run does not chain onto `wires`
+        let mut other = circuit.clone();
+        other.a[0] = Fr::from(1u64);
+        other.c[0] = Fr::from(4u64);
+        let other_wires = Prover::commit_wires(&other, &pk, &config, &mut rng).unwrap();
+        let foreign = Prover::commit_permutation(&pk, &other_wires, &config, &mut rng).unwrap();
+        assert_eq!(
+            Prover::commit_quotient(&pk, &wires, &foreign, &config).err(),
+            Some(ProveError::Stage(StageError { stage: "permutation" }))
+        );
+    }
+}
@@ -0,0 +1,352 @@
+use alloc::{collections::BTreeMap, vec::Vec};
+use ark_ff::PrimeField;
+use sha2::{Digest, Sha256};
+
+use crate::circuit::CircuitSelectors;
+use crate::permutation::Cell;
+
+/// State size: one capacity element and a rate of two
+pub const WIDTH: usize = 3;
+pub const FULL_ROUNDS: usize = 8;
+pub const PARTIAL_ROUNDS: usize = 57;
+
+/// Field operations the Poseidon permutation is written against. `Native`
+/// computes on field elements and `CircuitArithmetic` records a gate for
+/// every operation, so the in-circuit sponge performs exactly the native
+/// computation.
+pub trait Arithmetic<F: PrimeField> {
+    type Value: Clone;
+
+    fn constant(&mut self, value: F) -> Self::Value;
+    fn add(&mut self, a: &Self::Value, b: &Self::Value) -> Self::Value;
+    fn mul(&mut self, a: &Self::Value, b: &Self::Value) -> Self::Value;
+
+    /// Σ c·x + offset, by default a mul by the constant c and an add for
+    /// each term; an arithmetic with weighted gates does better
+    fn linear(&mut self, terms: &[(F, Self::Value)], offset: F) -> Self::Value {
+        let mut acc: Option<Self::Value> = None;
+        for (coefficient, x) in terms {
+            let coefficient = self.constant(*coefficient);
+            let term = self.mul(&coefficient, x);
+            acc = Some(match acc {
+                None => term,
+                Some(acc) => self.add(&acc, &term),
+            });
+        }
+        match acc {
+            None => self.constant(offset),
+            Some(acc) if offset.is_zero() => acc,
+            Some(acc) => {
+                let offset = self.constant(offset);
+                self.add(&acc, &offset)
+            }
+        }
+    }
+
+    /// x^α, by default by square and multiply
+    fn power(&mut self, x: &Self::Value, alpha: u64) -> Self::Value {
+        square_and_multiply(self, x, alpha)
+    }
+}
+
+/// Plain field arithmetic
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Native;
+
+impl<F: PrimeField> Arithmetic<F> for Native {
+    type Value = F;
+
+    fn constant(&mut self, value: F) -> F {
+        value
+    }
+
+    fn add(&mut self, a: &F, b: &F) -> F {
+        *a + b
+    }
+
+    fn mul(&mut self, a: &F, b: &F) -> F {
+        *a * b
+    }
+}
+
+/// A value placed in a circuit cell
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AssignedCell<F> {
+    pub cell: Cell,
+    pub value: F,
+}
+
+/// Records one row of the prover's gate set per operation:
+///
+/// - `add`/`mul`: a + b − c or a·b − c, inputs copied into a and b
+/// - `constant(k)`: q_l = 1, q_c = −k, forcing a to k; each distinct
+///   constant takes one row
+#[derive(Debug, Clone, Default)]
+pub struct CircuitArithmetic<F: PrimeField> {
+    pub selectors: CircuitSelectors<F>,
+    pub a: Vec<F>,
+    pub b: Vec<F>,
+    pub c: Vec<F>,
+    pub copies: Vec<(Cell, Cell)>,
+    constants: BTreeMap<F, Cell>,
+}
+
+impl<F: PrimeField> CircuitArithmetic<F> {
+    pub fn new() -> Self {
+        Self {
+            selectors: CircuitSelectors::default(),
+            a: Vec::new(),
+            b: Vec::new(),
+            c: Vec::new(),
+            copies: Vec::new(),
+            constants: BTreeMap::new(),
+        }
+    }
+
+    pub fn rows(&self) -> usize {
+        self.a.len()
+    }
+
+    /// Appends a row with selectors q_l, q_r, q_o, q_m, q_c, q_k
+    fn row(&mut self, selectors: [F; 6], values: (F, F, F)) -> usize {
+        for (column, q) in self.selectors.columns_mut().into_iter().zip(selectors) {
+            column.push(q);
+        }
+        self.a.push(values.0);
+        self.b.push(values.1);
+        self.c.push(values.2);
+        self.rows() - 1
+    }
+
+    /// A free witness value. Nothing constrains it until it is used.
+    pub fn input(&mut self, value: F) -> AssignedCell<F> {
+        let row = self.row([F::zero(); 6], (value, F::zero(), F::zero()));
+        AssignedCell { cell: Cell::new(0, row), value }
+    }
+
+    fn gate(&mut self, selectors: [F; 6], a: &AssignedCell<F>, b: &AssignedCell<F>, value: F) -> AssignedCell<F> {
+        let row = self.row(selectors, (a.value, b.value, value));
+        self.copies.push((a.cell, Cell::new(0, row)));
+        self.copies.push((b.cell, Cell::new(1, row)));
+        AssignedCell { cell: Cell::new(2, row), value }
+    }
+
+    /// The recorded rows as a provable circuit, padded with zero rows to a
+    /// power of two. Fails on a copy naming a cell past the recorded rows.
+    #[cfg(feature = "prover")]
+    pub fn into_circuit(self) -> Result<crate::prover::PlonkCircuit<F>, crate::permutation::PermutationError> {
+        use crate::permutation::PermutationBuilder;
+
+        let n = self.rows().next_power_of_two().max(2);
+        let column = |values: &Vec<F>| {
+            let mut values = values.clone();
+            values.resize(n, F::zero());
+            values
+        };
+        let mut builder = PermutationBuilder::new(3, n);
+        for (a, b) in &self.copies {
+            builder.connect(*a, *b)?;
+        }
+        Ok(crate::prover::PlonkCircuit {
+            q_l: column(&self.selectors.q_l),
+            q_r: column(&self.selectors.q_r),
+            q_o: column(&self.selectors.q_o),
+            q_m: column(&self.selectors.q_m),
+            q_c: column(&self.selectors.q_c),
+            q_k: column(&self.selectors.q_k),
+            a: column(&self.a),
+            b: column(&self.b),
+            c: column(&self.c),
+            permutation: builder.build(),
+            public_rows: Vec::new(),
+            public_inputs: Vec::new(),
+            table: Vec::new(),
+            custom_gates: Vec::new(),
+            q_custom: Vec::new(),
+        })
+    }
+}
+
+impl<F: PrimeField> Arithmetic<F> for CircuitArithmetic<F> {
+    type Value = AssignedCell<F>;
+
+    fn constant(&mut self, value: F) -> AssignedCell<F> {
+        if let Some(cell) = self.constants.get(&value) {
+            return AssignedCell { cell: *cell, value };
+        }
+        let row = self.row([F::one(), F::zero(), F::zero(), F::zero(), -value, F::zero()], (value, F::zero(), F::zero()));
+        let cell = Cell::new(0, row);
+        self.constants.insert(value, cell);
+        AssignedCell { cell, value }
+    }
+
+    fn add(&mut self, a: &AssignedCell<F>, b: &AssignedCell<F>) -> AssignedCell<F> {
+        self.gate([F::one(), F::one(), -F::one(), F::zero(), F::zero(), F::zero()], a, b, a.value + b.value)
+    }
+
+    fn mul(&mut self, a: &AssignedCell<F>, b: &AssignedCell<F>) -> AssignedCell<F> {
+        self.gate([F::zero(), F::zero(), -F::one(), F::one(), F::zero(), F::zero()], a, b, a.value * b.value)
+    }
+}
+
+/// Round constants, MDS matrix and S-box exponent for one field
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PoseidonParams<F: PrimeField> {
+    /// Smallest α ≥ 3 coprime to p − 1, so x^α permutes the field
+    pub alpha: u64,
+    pub round_constants: Vec<[F; WIDTH]>,
+    /// Cauchy matrix 1 / (i + (WIDTH + j)), MDS by construction
+    pub mds: [[F; WIDTH]; WIDTH],
+}
+
+impl<F: PrimeField> PoseidonParams<F> {
+    /// Parameters derived deterministically from SHA-256, identical on
+    /// every machine for a given field
+    pub fn new() -> Self {
+        let p_minus_one_mod = |m: u64| {
+            let m = m as u128;
+            let p = F::characteristic().iter().rev().fold(0u128, |acc, limb| ((acc << 64) + *limb as u128) % m);
+            (p + m - 1) % m
+        };
+        let alpha = [3u64, 5, 7, 11, 13]
+            .into_iter()
+            .find(|alpha| p_minus_one_mod(*alpha) != 0)
+            .expect("p − 1 is coprime to one of 3, 5, 7, 11, 13 in every field of cryptographic size");
+
+        let round_constants = (0..FULL_ROUNDS + PARTIAL_ROUNDS)
+            .map(|round| {
+                [0, 1, 2].map(|i| {
+                    let mut hasher = Sha256::new();
+                    hasher.update(b"plonk-lib-poseidon");
+                    hasher.update((round * WIDTH + i).to_le_bytes());
+                    F::from_le_bytes_mod_order(&hasher.finalize())
+                })
+            })
+            .collect();
+        let mds = [0u64, 1, 2].map(|i| {
+            [0u64, 1, 2].map(|j| F::from(i + WIDTH as u64 + j).inverse().expect("3..=7 are nonzero in a field of cryptographic size"))
+        });
+        Self { alpha, round_constants, mds }
+    }
+}
+
+impl<F: PrimeField> Default for PoseidonParams<F> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// x^α in a mul per bit of α and one per set bit below the top one
+pub(crate) fn square_and_multiply<F: PrimeField, A: Arithmetic<F> + ?Sized>(arith: &mut A, x: &A::Value, alpha: u64) -> A::Value {
+    let mut result: Option<A::Value> = None;
+    for bit in (0..64 - alpha.leading_zeros()).rev() {
+        if let Some(r) = result.take() {
+            result = Some(arith.mul(&r, &r));
+        }
+        if (alpha >> bit) & 1 == 1 {
+            result = Some(match result.take() {
+                Some(r) => arith.mul(&r, x),
+                None => x.clone(),
+            });
+        }
+    }
+    result.expect("alpha has a set bit")
+}
+
+/// The Poseidon permutation: `FULL_ROUNDS / 2` full rounds, then
+/// `PARTIAL_ROUNDS` rounds with a single S-box, then the remaining full
+/// rounds. Each round adds constants, applies the S-box and mixes with the
+/// MDS matrix; the constants of every round after the first are added in
+/// the mixing of the round before, which computes the same state in fewer
+/// gates.
+pub fn permute<F: PrimeField, A: Arithmetic<F>>(params: &PoseidonParams<F>, arith: &mut A, state: &mut [A::Value; WIDTH]) {
+    let half = FULL_ROUNDS / 2;
+    for (x, k) in state.iter_mut().zip(&params.round_constants[0]) {
+        *x = arith.linear(&[(F::one(), x.clone())], *k);
+    }
+    for round in 0..params.round_constants.len() {
+        let full = round < half || round >= half + PARTIAL_ROUNDS;
+        let sboxed = if full { WIDTH } else { 1 };
+        for x in &mut state[..sboxed] {
+            *x = arith.power(x, params.alpha);
+        }
+
+        let next = params.round_constants.get(round + 1).copied().unwrap_or([F::zero(); WIDTH]);
+        let mixed: [A::Value; WIDTH] = core::array::from_fn(|i| {
+            let terms: Vec<(F, A::Value)> = params.mds[i].iter().copied().zip(state.iter().cloned()).collect();
+            arith.linear(&terms, next[i])
+        });
+        *state = mixed;
+    }
+}
+
+/// Sponge hash of `inputs`: the capacity element starts at the input
+/// length, pairs of inputs are added into the rate and permuted, and the
+/// first rate element is the digest. An empty input is one permutation
+/// of the initial state.
+pub fn hash<F: PrimeField, A: Arithmetic<F>>(params: &PoseidonParams<F>, arith: &mut A, inputs: &[A::Value]) -> A::Value {
+    let zero = arith.constant(F::zero());
+    let mut state = [arith.constant(F::from(inputs.len() as u64)), zero.clone(), zero];
+    let mut blocks = inputs.chunks(WIDTH - 1).peekable();
+    if blocks.peek().is_none() {
+        permute(params, arith, &mut state);
+    }
+    for block in blocks {
+        for (x, input) in state[1..].iter_mut().zip(block) {
+            *x = arith.add(x, input);
+        }
+        permute(params, arith, &mut state);
+    }
+    state[1].clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bls12_381::Fr;
+
+    #[test]
+    fn test_params() {
+        let params = PoseidonParams::<Fr>::new();
+        // 3 divides r − 1 for BLS12-381, 5 does not
+        assert_eq!(params.alpha, 5);
+        assert_eq!(params.round_constants.len(), FULL_ROUNDS + PARTIAL_ROUNDS);
+        assert_eq!(params, PoseidonParams::new());
+        assert_eq!(PoseidonParams::<ark_bn254::Fr>::new().alpha, 5);
+    }
+
+    #[test]
+    fn test_circuit_matches_native() {
+        let params = PoseidonParams::<Fr>::new();
+        let inputs = [1u64, 2, 3].map(Fr::from);
+
+        let mut native = inputs;
+        permute(&params, &mut Native, &mut native);
+        let mut other = [Fr::from(1u64), Fr::from(2u64), Fr::from(4u64)];
+        permute(&params, &mut Native, &mut other);
+        assert_ne!(native, other);
+
+        let mut circuit = CircuitArithmetic::new();
+        let mut state = inputs.map(|x| circuit.input(x));
+        permute(&params, &mut circuit, &mut state);
+        assert_eq!(state.map(|x| x.value), native);
+    }
+
+    #[test]
+    fn test_hash() {
+        let params = PoseidonParams::<Fr>::new();
+        let inputs = [1u64, 2, 3].map(Fr::from);
+        let digest = hash(&params, &mut Native, &inputs);
+        assert_eq!(digest, hash(&params, &mut Native, &inputs));
+        assert_ne!(digest, hash(&params, &mut Native, &[inputs[0], inputs[1], Fr::from(4u64)]));
+
+        // The length is absorbed, so trailing zeros change the digest
+        let empty = hash::<Fr, _>(&params, &mut Native, &[]);
+        assert_ne!(empty, hash(&params, &mut Native, &[Fr::from(0u64)]));
+        assert_ne!(hash(&params, &mut Native, &inputs[..2]), hash(&params, &mut Native, &[inputs[0], inputs[1], Fr::from(0u64)]));
+
+        let mut circuit = CircuitArithmetic::new();
+        let cells: Vec<_> = inputs.iter().map(|x| circuit.input(*x)).collect();
+        assert_eq!(hash(&params, &mut circuit, &cells).value, digest);
+    }
+}
@@ -0,0 +1,51 @@
+//! Stable public API. Downstream crates should import from here; the
+//! modules behind it are `doc(hidden)` and their paths may change between
+//! minor versions. CI runs `cargo semver-checks` against this surface.
+//!
+//! Naming: a check that needs randomness takes it from a transcript by
+//! default, and the variant taking a caller's `Rng` carries a `_with_rng`
+//! suffix, as `Verifier::verify_batch` and `Verifier::verify_batch_with_rng`.
+
+pub use crate::acir::{AcirCircuit, AcirError, Program, WitnessMap};
+#[cfg(feature = "verifier")]
+pub use crate::aggregation::{Aggregate, AggregateError, AggregationKeys, Node};
+#[cfg(all(feature = "prover", feature = "verifier"))]
+pub use crate::aggregation::Aggregator;
+pub use crate::artifact::{Artifact, ArtifactError};
+pub use crate::circuit::{Circuit, CircuitSelectors, CircuitStats, ConstraintReport, FailedGate, Gate, GateType, CircuitError, PaddingStrategy, UnsatisfiedGate, Variable, Violation, Wire};
+pub use crate::constraint_system::{BatchGadget, ConstraintSystem, Synthesize, Witness};
+pub use crate::custom_gate::{CustomGate, Expression};
+pub use crate::digest::{CircuitDigest, DigestScheme, Keccak256, Poseidon, Sha256};
+pub use crate::elgamal::{Ciphertext, Keypair};
+pub use crate::encoding::{Encoding, Endianness, PointFormat};
+pub use crate::equivalence::{EquivalenceCircuit, EquivalenceError, EquivalenceProof};
+pub use crate::error::{DomainError, Error};
+pub use crate::fft::root_of_unity;
+pub use crate::ipa::{IpaParams, IpaProof};
+pub use crate::kgz::{CommitError, HidingKey, HidingOpening, KZGParams, KZGVerifierKey, MultiPointProof};
+pub use crate::lint::{lint, lint_with_max_degree, Diagnostic, LintKind};
+pub use crate::lookup::Table;
+pub use crate::metadata::{MetadataPolicy, PolicyError, ProofEnvelope, ProofMetadata};
+pub use crate::msm::{CurveMul, CurvePairing, MsmConfig, MsmError};
+pub use crate::pcs::{Opening, PolynomialCommitment};
+pub use crate::permutation::{Cell, Permutation, PermutationBuilder, PermutationError};
+pub use crate::poseidon::{Arithmetic, AssignedCell, CircuitArithmetic, Native, PoseidonParams};
+#[cfg(feature = "prover")]
+pub use crate::pipeline::{PermutationStage, QuotientStage, StageError, WireStage};
+pub use crate::proof::{Oracle, Proof, ProofEvaluations, ScheduleError, Step, VerifyingKey};
+#[cfg(feature = "prover")]
+pub use crate::prover::{CosetStrategy, KeyError, PlonkCircuit, Profile, ProveError, Prover, ProverConfig, ProvingKey, SelfTestError, SelfTestReport};
+#[cfg(feature = "r1cs")]
+pub use crate::r1cs::{read_circom_witness, LinearCombination, R1cs, R1csError};
+#[cfg(feature = "verifier")]
+pub use crate::registry::{RegistryError, VkRegistry};
+pub use crate::remote::{ProofRequest, ProofResponse, RemoteError, RemoteProver};
+pub use crate::srs::{check_powers, from_ethereum_setup, SrsError};
+#[cfg(feature = "prover")]
+pub use crate::store::Storage;
+pub use crate::threshold::ThresholdError;
+pub use crate::transcript::{PoseidonTranscript, Transcript, TranscriptHash};
+pub use crate::vector_commitment::VectorCommitment;
+#[cfg(feature = "verifier")]
+pub use crate::verifier::{BatchError, BatchItem, OpeningCache, PreparedVerifyingKey, Verifier, VerifyError};
+pub use crate::witness_encryption::{encrypt_witness, EncryptedWitness, WitnessDecryptError};
@@ -0,0 +1,559 @@
+use alloc::vec::Vec;
+use ark_ff::PrimeField;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+
+use crate::custom_gate::CustomGate;
+use crate::encoding::{Decoder, Encoder, Encoding};
+use crate::kgz::{KZGParams, KZGVerifierKey};
+use crate::msm::CurvePairing;
+use crate::pcs::PolynomialCommitment;
+#[cfg(any(feature = "prover", feature = "verifier"))]
+use crate::poseidon::Native;
+#[cfg(any(feature = "prover", feature = "verifier"))]
+use crate::transcript::{PoseidonTranscript, Transcript, TranscriptHash};
+
+/// Preprocessed commitments and SRS elements a verifier needs for one
+/// circuit. The gate identity on every row is
+///
+///   q_l·a + q_r·b + q_o·c + q_m·a·b + q_c + PI = 0
+///
+/// where PI(ω^i) = −x_j on row i = `public_rows[j]`, the row holding
+/// public input x_j, and zero elsewhere. The copy constraints are σ over
+/// the columns a, b, c, and rows with q_k = 1 take (a, b, c) from the
+/// table t_1, t_2, t_3. Each custom gate adds q_g·e(a, b, c) = 0 for
+/// every one of its identities e. The commitments and the opening key
+/// are those of the scheme `S`.
+#[derive(Debug, Clone, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct VerifyingKey<E: CurvePairing, S: PolynomialCommitment<E::ScalarField> = KZGParams<E>> {
+    /// Number of rows n, a power of two
+    pub domain_size: u64,
+    /// k_j labelling column j's coset k_j·H in the permutation argument
+    pub coset_multipliers: [E::ScalarField; 3],
+    /// [q_l], [q_r], [q_o], [q_m], [q_c], [q_k]
+    pub selector_commitments: [S::Commitment; SELECTORS],
+    /// [σ_a], [σ_b], [σ_c]
+    pub sigma_commitments: [S::Commitment; 3],
+    /// [t_1], [t_2], [t_3]
+    pub table_commitments: [S::Commitment; 3],
+    /// Row of each public input, in statement order
+    pub public_rows: Vec<u64>,
+    pub custom_gates: Vec<CustomGate<E::ScalarField>>,
+    /// [q_g] for each custom gate
+    pub custom_selector_commitments: Vec<S::Commitment>,
+    pub opening_key: S::VerifierKey,
+}
+
+impl<E: CurvePairing, S: PolynomialCommitment<E::ScalarField>> VerifyingKey<E, S> {
+    /// Whether the public rows are distinct rows of the domain and every
+    /// custom gate has its selector commitment. The verifiers sum one PI
+    /// term per public row, so a repeated row would count twice there
+    /// while the prover's PI holds it once.
+    #[cfg(feature = "verifier")]
+    pub(crate) fn is_consistent(&self) -> bool {
+        let mut rows = alloc::collections::BTreeSet::new();
+        self.public_rows.iter().all(|row| *row < self.domain_size && rows.insert(*row))
+            && self.custom_selector_commitments.len() == self.custom_gates.len()
+    }
+}
+
+impl<E: CurvePairing> VerifyingKey<E> {
+    /// The key in `encoding`, `None` when it has another number of custom
+    /// gates than custom selector commitments and so no valid encoding
+    pub fn to_bytes_with(&self, encoding: Encoding) -> Option<Vec<u8>> {
+        if self.custom_gates.len() != self.custom_selector_commitments.len() {
+            return None;
+        }
+        let mut out = Encoder::new(encoding);
+        out.u64(self.domain_size);
+        self.coset_multipliers.iter().for_each(|k| out.field(k));
+        self.selector_commitments
+            .iter()
+            .chain(&self.sigma_commitments)
+            .chain(&self.table_commitments)
+            .chain([&self.opening_key.g1])
+            .for_each(|p| out.point(p));
+        out.point(&self.opening_key.g2);
+        out.point(&self.opening_key.g2_s);
+        out.u64(self.public_rows.len() as u64);
+        self.public_rows.iter().for_each(|row| out.u64(*row));
+        out.u64(self.custom_gates.len() as u64);
+        for (gate, commitment) in self.custom_gates.iter().zip(&self.custom_selector_commitments) {
+            gate.write(&mut out);
+            out.point(commitment);
+        }
+        Some(out.finish())
+    }
+
+    pub fn from_bytes_with(bytes: &[u8], encoding: Encoding) -> Option<Self> {
+        let mut input = Decoder::new(encoding, bytes);
+        let domain_size = input.u64()?;
+        let coset_multipliers = [input.field()?, input.field()?, input.field()?];
+        let selector_commitments = [(); SELECTORS].map(|_| input.point());
+        let selector_commitments = selector_commitments.into_iter().collect::<Option<Vec<_>>>()?.try_into().ok()?;
+        let sigma_commitments = [input.point()?, input.point()?, input.point()?];
+        let table_commitments = [input.point()?, input.point()?, input.point()?];
+        let (g1, g2, g2_s) = (input.point()?, input.point()?, input.point()?);
+        let count = input.u64()?;
+        if count > domain_size {
+            return None;
+        }
+        let public_rows = (0..count).map(|_| input.u64()).collect::<Option<_>>()?;
+        let count = input.count(16)?;
+        let (custom_gates, custom_selector_commitments): (Vec<_>, Vec<_>) =
+            (0..count).map(|_| Some((CustomGate::read(&mut input)?, input.point::<E::G1Affine>()?))).collect::<Option<_>>()?;
+        let vk = Self {
+            domain_size,
+            coset_multipliers,
+            selector_commitments,
+            sigma_commitments,
+            table_commitments,
+            public_rows,
+            custom_gates,
+            custom_selector_commitments,
+            opening_key: KZGVerifierKey { g1, g2, g2_s },
+        };
+        input.finish()?;
+        Some(vk)
+    }
+}
+
+/// Number of selector columns: q_l, q_r, q_o, q_m, q_c, q_k
+pub const SELECTORS: usize = 6;
+
+/// Polynomial evaluations at the challenge ζ, and at ζω for the
+/// `OPENED_AT_ZETA_OMEGA` ones. Everything else the quotient identity
+/// reads enters through the linearization polynomial r.
+#[derive(Debug, Clone, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct ProofEvaluations<F: PrimeField> {
+    pub wires: [F; 3],
+    /// σ_a, σ_b; σ_c is in r
+    pub sigmas: [F; 2],
+    pub z_omega: F,
+    /// q_k, which the lookup multiplies z_L by
+    pub lookup_selector: F,
+    /// t_1, t_2, t_3
+    pub table: [F; 3],
+    /// A', S'
+    pub permuted: [F; 2],
+    pub z_lookup_omega: F,
+    /// A' at ζω
+    pub permuted_input_omega: F,
+    /// r(ζ)
+    pub linearization: F,
+}
+
+/// A committed polynomial of the protocol
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Oracle {
+    /// a, b, c
+    Wire(usize),
+    /// q_l, q_r, q_o, q_m, q_c, q_k
+    Selector(usize),
+    /// q_g of custom gate g
+    CustomSelector(usize),
+    /// σ_a, σ_b, σ_c
+    Sigma(usize),
+    /// Permutation grand product
+    Z,
+    /// Lookup table columns t_1, t_2, t_3
+    Table(usize),
+    /// A', the sorted lookup inputs, and S', the table arranged against it
+    Permuted(usize),
+    /// Lookup grand product
+    ZLookup,
+    /// t_lo, t_mid, t_hi
+    Quotient(usize),
+}
+
+/// Polynomials opened at ζ besides r, in the order the batched opening
+/// combines them with v, v², … after r
+pub const OPENED_AT_ZETA: [Oracle; 11] = [
+    Oracle::Wire(0),
+    Oracle::Wire(1),
+    Oracle::Wire(2),
+    Oracle::Sigma(0),
+    Oracle::Sigma(1),
+    Oracle::Selector(5),
+    Oracle::Table(0),
+    Oracle::Table(1),
+    Oracle::Table(2),
+    Oracle::Permuted(0),
+    Oracle::Permuted(1),
+];
+
+/// Polynomials opened at ζω, batched with powers of v the same way
+pub const OPENED_AT_ZETA_OMEGA: [Oracle; 3] = [Oracle::Z, Oracle::ZLookup, Oracle::Permuted(0)];
+
+/// One transcript operation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Step {
+    /// Hash in the named proof or key element
+    Absorb(&'static str),
+    /// Derive the named challenge
+    Squeeze(&'static str),
+}
+
+/// The proof schedule: every element absorbed into the Fiat-Shamir
+/// transcript and every challenge derived from it, in order. The label of
+/// each step is hashed too, and the transcript refuses any call that
+/// deviates from this list with a `ScheduleError`, so prover and verifier
+/// cannot drift apart.
+pub const SCHEDULE: [Step; 23] = [
+    Step::Absorb("verifying_key"),
+    Step::Absorb("session"),
+    Step::Absorb("public_inputs"),
+    Step::Absorb("wire_commitments"),
+    Step::Squeeze("theta"),
+    Step::Absorb("lookup_commitments"),
+    Step::Squeeze("beta"),
+    Step::Squeeze("gamma"),
+    Step::Absorb("z_commitment"),
+    Step::Absorb("z_lookup_commitment"),
+    Step::Squeeze("alpha"),
+    Step::Absorb("quotient_commitments"),
+    Step::Squeeze("zeta"),
+    Step::Absorb("wire_evaluations"),
+    Step::Absorb("sigma_evaluations"),
+    Step::Absorb("z_omega_evaluation"),
+    Step::Absorb("table_evaluations"),
+    Step::Absorb("lookup_evaluations"),
+    Step::Absorb("lookup_omega_evaluations"),
+    Step::Absorb("linearization_evaluation"),
+    Step::Squeeze("v"),
+    Step::Absorb("opening_proofs"),
+    Step::Squeeze("u"),
+];
+
+/// A transcript call that deviates from `SCHEDULE`: step `index` was
+/// `got` where the schedule has `expected`, `None` past its end
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScheduleError {
+    pub index: usize,
+    pub expected: Option<Step>,
+    pub got: Step,
+}
+
+impl core::fmt::Display for ScheduleError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self.expected {
+            Some(expected) => write!(f, "transcript step {} is {:?}, the proof schedule has {:?}", self.index, self.got, expected),
+            None => write!(f, "transcript step {} is {:?}, past the end of the proof schedule", self.index, self.got),
+        }
+    }
+}
+
+impl<F: PrimeField> ProofEvaluations<F> {
+    /// Everything opened at ζ but r, in `OPENED_AT_ZETA` order
+    pub fn at_zeta(&self) -> [F; 11] {
+        let [a, b, c] = self.wires;
+        let [t_1, t_2, t_3] = self.table;
+        [a, b, c, self.sigmas[0], self.sigmas[1], self.lookup_selector, t_1, t_2, t_3, self.permuted[0], self.permuted[1]]
+    }
+
+    /// Everything opened at ζω, in `OPENED_AT_ZETA_OMEGA` order
+    pub fn at_zeta_omega(&self) -> [F; 3] {
+        [self.z_omega, self.z_lookup_omega, self.permuted_input_omega]
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct Proof<E: CurvePairing, S: PolynomialCommitment<E::ScalarField> = KZGParams<E>> {
+    /// [a], [b], [c]
+    pub wire_commitments: [S::Commitment; 3],
+    /// [A'], [S']
+    pub lookup_commitments: [S::Commitment; 2],
+    /// [z], the permutation grand product
+    pub z_commitment: S::Commitment,
+    /// [z_L], the lookup grand product
+    pub z_lookup_commitment: S::Commitment,
+    /// [t_lo], [t_mid], [t_hi]
+    pub quotient_commitments: [S::Commitment; 3],
+    pub evaluations: ProofEvaluations<E::ScalarField>,
+    /// Batched opening of r and the `OPENED_AT_ZETA` polynomials at ζ
+    pub w_zeta: S::Proof,
+    /// Batched opening of z, z_L and A' at ζω
+    pub w_zeta_omega: S::Proof,
+}
+
+impl<E: CurvePairing, S: PolynomialCommitment<E::ScalarField>> Proof<E, S> {
+    /// Commitment to `oracle`, taken from the proof or from `vk`
+    pub fn commitment(&self, vk: &VerifyingKey<E, S>, oracle: Oracle) -> S::Commitment {
+        match oracle {
+            Oracle::Wire(j) => self.wire_commitments[j],
+            Oracle::Selector(j) => vk.selector_commitments[j],
+            Oracle::CustomSelector(j) => vk.custom_selector_commitments[j],
+            Oracle::Sigma(j) => vk.sigma_commitments[j],
+            Oracle::Z => self.z_commitment,
+            Oracle::Table(j) => vk.table_commitments[j],
+            Oracle::Permuted(j) => self.lookup_commitments[j],
+            Oracle::ZLookup => self.z_lookup_commitment,
+            Oracle::Quotient(j) => self.quotient_commitments[j],
+        }
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        self.serialize_compressed(&mut bytes).expect("writing to a Vec");
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        Self::deserialize_compressed(bytes).ok()
+    }
+}
+
+impl<E: CurvePairing> Proof<E> {
+    /// Encoding for a particular consumer; `Encoding::ARKWORKS` gives the
+    /// same bytes as `to_bytes`
+    pub fn to_bytes_with(&self, encoding: Encoding) -> Vec<u8> {
+        let mut out = Encoder::new(encoding);
+        let e = &self.evaluations;
+        self.wire_commitments
+            .iter()
+            .chain(&self.lookup_commitments)
+            .chain([&self.z_commitment, &self.z_lookup_commitment])
+            .chain(&self.quotient_commitments)
+            .for_each(|p| out.point(p));
+        e.wires
+            .iter()
+            .chain(&e.sigmas)
+            .chain([&e.z_omega, &e.lookup_selector])
+            .chain(&e.table)
+            .chain(&e.permuted)
+            .chain([&e.z_lookup_omega, &e.permuted_input_omega, &e.linearization])
+            .for_each(|x| out.field(x));
+        out.point(&self.w_zeta);
+        out.point(&self.w_zeta_omega);
+        out.finish()
+    }
+
+    pub fn from_bytes_with(bytes: &[u8], encoding: Encoding) -> Option<Self> {
+        let mut input = Decoder::new(encoding, bytes);
+        let wire_commitments = [input.point()?, input.point()?, input.point()?];
+        let lookup_commitments = [input.point()?, input.point()?];
+        let z_commitment = input.point()?;
+        let z_lookup_commitment = input.point()?;
+        let quotient_commitments = [input.point()?, input.point()?, input.point()?];
+        let evaluations = ProofEvaluations {
+            wires: [input.field()?, input.field()?, input.field()?],
+            sigmas: [input.field()?, input.field()?],
+            z_omega: input.field()?,
+            lookup_selector: input.field()?,
+            table: [input.field()?, input.field()?, input.field()?],
+            permuted: [input.field()?, input.field()?],
+            z_lookup_omega: input.field()?,
+            permuted_input_omega: input.field()?,
+            linearization: input.field()?,
+        };
+        let proof = Self {
+            wire_commitments,
+            lookup_commitments,
+            z_commitment,
+            z_lookup_commitment,
+            quotient_commitments,
+            evaluations,
+            w_zeta: input.point()?,
+            w_zeta_omega: input.point()?,
+        };
+        input.finish()?;
+        Some(proof)
+    }
+}
+
+/// Fiat-Shamir challenges, derived by hashing everything sent so far
+#[cfg(any(feature = "verifier", all(test, feature = "prover")))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Challenges<F> {
+    /// Compresses a lookup row (x, y, z) into x + θ·y + θ²·z
+    pub theta: F,
+    pub beta: F,
+    pub gamma: F,
+    pub alpha: F,
+    pub zeta: F,
+    pub v: F,
+    /// Separates the two opening checks when the verifier batches them
+    pub u: F,
+}
+
+/// `Transcript`, or its Poseidon counterpart, that only accepts the
+/// steps of `SCHEDULE`, in order
+#[cfg(any(feature = "prover", feature = "verifier"))]
+pub(crate) struct ScheduledTranscript<F: PrimeField> {
+    hasher: Hasher<F>,
+    step: usize,
+}
+
+#[cfg(any(feature = "prover", feature = "verifier"))]
+enum Hasher<F: PrimeField> {
+    Sha256(Transcript),
+    Poseidon(PoseidonTranscript<F, Native>),
+}
+
+#[cfg(any(feature = "prover", feature = "verifier"))]
+impl<F: PrimeField> ScheduledTranscript<F> {
+    pub fn new(label: &[u8], hash: TranscriptHash) -> Self {
+        let hasher = match hash {
+            TranscriptHash::Sha256 => Hasher::Sha256(Transcript::new(label)),
+            TranscriptHash::Poseidon => Hasher::Poseidon(PoseidonTranscript::new(Native, label)),
+        };
+        Self { hasher, step: 0 }
+    }
+
+    /// Moves past `step` if it is the next one of `SCHEDULE`. A refused
+    /// step leaves the transcript where it was.
+    fn advance(&mut self, step: Step) -> Result<(), ScheduleError> {
+        let expected = SCHEDULE.get(self.step).copied();
+        if expected != Some(step) {
+            return Err(ScheduleError { index: self.step, expected, got: step });
+        }
+        self.step += 1;
+        Ok(())
+    }
+
+    /// Absorbs the compressed encoding of `item`, which Poseidon takes
+    /// in `byte_chunks`
+    pub fn absorb<T: CanonicalSerialize + ?Sized>(&mut self, label: &'static str, item: &T) -> Result<(), ScheduleError> {
+        self.advance(Step::Absorb(label))?;
+        match &mut self.hasher {
+            Hasher::Sha256(transcript) => transcript.absorb(label.as_bytes(), item),
+            Hasher::Poseidon(transcript) => {
+                let mut bytes = Vec::new();
+                item.serialize_compressed(&mut bytes).expect("writing to a Vec");
+                transcript.absorb_bytes(label.as_bytes(), &bytes);
+            }
+        }
+        Ok(())
+    }
+
+    /// Absorbs field elements: their encoding under SHA-256, the same
+    /// bytes `absorb` hashes, and the elements themselves under Poseidon
+    pub fn absorb_scalars<T: CanonicalSerialize + AsRef<[F]> + ?Sized>(
+        &mut self,
+        label: &'static str,
+        item: &T,
+    ) -> Result<(), ScheduleError> {
+        self.advance(Step::Absorb(label))?;
+        match &mut self.hasher {
+            Hasher::Sha256(transcript) => transcript.absorb(label.as_bytes(), item),
+            Hasher::Poseidon(transcript) => transcript.absorb(label.as_bytes(), item.as_ref()),
+        }
+        Ok(())
+    }
+
+    pub fn squeeze(&mut self, label: &'static str) -> Result<F, ScheduleError> {
+        self.advance(Step::Squeeze(label))?;
+        Ok(match &mut self.hasher {
+            Hasher::Sha256(transcript) => transcript.squeeze(label.as_bytes()),
+            Hasher::Poseidon(transcript) => transcript.squeeze(label.as_bytes()),
+        })
+    }
+
+    /// The Poseidon sponge, `None` under SHA-256
+    #[cfg(feature = "verifier")]
+    pub fn into_poseidon(self) -> Option<PoseidonTranscript<F, Native>> {
+        match self.hasher {
+            Hasher::Sha256(_) => None,
+            Hasher::Poseidon(transcript) => Some(transcript),
+        }
+    }
+}
+
+#[cfg(any(feature = "prover", feature = "verifier"))]
+pub(crate) const PROTOCOL_LABEL: &[u8] = b"plonk-lib-v1";
+
+/// Replays the prover's transcript for `proof`
+#[cfg(any(feature = "verifier", all(test, feature = "prover")))]
+pub(crate) fn challenges<E: CurvePairing, S: PolynomialCommitment<E::ScalarField>>(
+    vk: &VerifyingKey<E, S>,
+    session: &[u8],
+    hash: TranscriptHash,
+    public_inputs: &[E::ScalarField],
+    proof: &Proof<E, S>,
+) -> Result<Challenges<E::ScalarField>, ScheduleError> {
+    let mut hasher = transcript_for(vk, session, hash, public_inputs)?;
+    hasher.absorb("wire_commitments", &proof.wire_commitments)?;
+    let theta = hasher.squeeze("theta")?;
+    hasher.absorb("lookup_commitments", &proof.lookup_commitments)?;
+    let beta = hasher.squeeze("beta")?;
+    let gamma = hasher.squeeze("gamma")?;
+    hasher.absorb("z_commitment", &proof.z_commitment)?;
+    hasher.absorb("z_lookup_commitment", &proof.z_lookup_commitment)?;
+    let alpha = hasher.squeeze("alpha")?;
+    hasher.absorb("quotient_commitments", &proof.quotient_commitments)?;
+    let zeta = hasher.squeeze("zeta")?;
+    absorb_evaluations(&mut hasher, &proof.evaluations)?;
+    let v = hasher.squeeze("v")?;
+    hasher.absorb("opening_proofs", &[proof.w_zeta.clone(), proof.w_zeta_omega.clone()])?;
+    let u = hasher.squeeze("u")?;
+    Ok(Challenges { theta, beta, gamma, alpha, zeta, v, u })
+}
+
+/// Transcript bound to the circuit through its verifying key, to the
+/// session, and to the statement through the public inputs. Each call
+/// starts from scratch, so concurrent sessions share no state.
+#[cfg(any(feature = "prover", feature = "verifier"))]
+pub(crate) fn transcript_for<E: CurvePairing, S: PolynomialCommitment<E::ScalarField>>(
+    vk: &VerifyingKey<E, S>,
+    session: &[u8],
+    hash: TranscriptHash,
+    public_inputs: &[E::ScalarField],
+) -> Result<ScheduledTranscript<E::ScalarField>, ScheduleError> {
+    let mut hasher = key_transcript(vk, session, hash)?;
+    hasher.absorb_scalars("public_inputs", public_inputs)?;
+    Ok(hasher)
+}
+
+/// The transcript up to the statement, which depends on the key and the
+/// session alone
+#[cfg(any(feature = "prover", feature = "verifier"))]
+pub(crate) fn key_transcript<E: CurvePairing, S: PolynomialCommitment<E::ScalarField>>(
+    vk: &VerifyingKey<E, S>,
+    session: &[u8],
+    hash: TranscriptHash,
+) -> Result<ScheduledTranscript<E::ScalarField>, ScheduleError> {
+    let mut hasher = ScheduledTranscript::new(PROTOCOL_LABEL, hash);
+    hasher.absorb("verifying_key", vk)?;
+    hasher.absorb("session", session)?;
+    Ok(hasher)
+}
+
+#[cfg(any(feature = "prover", feature = "verifier"))]
+pub(crate) fn absorb_evaluations<F: PrimeField>(
+    hasher: &mut ScheduledTranscript<F>,
+    evaluations: &ProofEvaluations<F>,
+) -> Result<(), ScheduleError> {
+    hasher.absorb_scalars("wire_evaluations", &evaluations.wires)?;
+    hasher.absorb_scalars("sigma_evaluations", &evaluations.sigmas)?;
+    hasher.absorb_scalars("z_omega_evaluation", &[evaluations.z_omega])?;
+    hasher.absorb_scalars("table_evaluations", &evaluations.table)?;
+    hasher.absorb_scalars("lookup_evaluations", &[evaluations.lookup_selector, evaluations.permuted[0], evaluations.permuted[1]])?;
+    hasher.absorb_scalars("lookup_omega_evaluations", &[evaluations.z_lookup_omega, evaluations.permuted_input_omega])?;
+    hasher.absorb_scalars("linearization_evaluation", &[evaluations.linearization])
+}
+
+#[cfg(all(test, any(feature = "prover", feature = "verifier")))]
+mod tests {
+    use super::*;
+    use ark_bls12_381::Fr;
+
+    #[test]
+    fn test_schedule_labels_are_unique() {
+        for (i, step) in SCHEDULE.iter().enumerate() {
+            assert!(!SCHEDULE[..i].contains(step), "{:?} appears twice", step);
+        }
+    }
+
+    #[test]
+    fn test_out_of_order_transcript_is_refused() {
+        let mut hasher = ScheduledTranscript::<Fr>::new(PROTOCOL_LABEL, TranscriptHash::Sha256);
+        hasher.absorb("verifying_key", &0u64).unwrap();
+        let error = ScheduleError { index: 1, expected: Some(Step::Absorb("session")), got: Step::Squeeze("beta") };
+        assert_eq!(hasher.squeeze("beta"), Err(error));
+        assert_eq!(error.to_string(), "transcript step 1 is Squeeze(\"beta\"), the proof schedule has Absorb(\"session\")");
+        // The refused step did not count
+        hasher.absorb("session", &[0u8; 0][..]).unwrap();
+
+        let mut finished = ScheduledTranscript::<Fr> { hasher: Hasher::Sha256(Transcript::new(PROTOCOL_LABEL)), step: SCHEDULE.len() };
+        assert_eq!(finished.absorb("session", &0u64).map_err(|error| error.expected), Err(None));
+    }
+}
@@ -0,0 +1,1049 @@
+use alloc::collections::BTreeSet;
+use alloc::{vec, vec::Vec};
+use ark_ec::AffineRepr;
+use ark_ff::{FftField, PrimeField};
+use ark_poly::polynomial::univariate::DensePolynomial;
+use ark_poly::polynomial::DenseUVPolynomial;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_std::rand::Rng;
+use ark_std::UniformRand;
+use zeroize::Zeroize;
+
+use crate::constraint_system::Witness;
+use crate::custom_gate::CustomGate;
+use crate::encoding::{Encoding, Endianness, PointFormat};
+use crate::circuit::quotient_domain_size;
+use crate::fft::{fft, ifft, DomainError};
+use crate::kgz::{CommitError, KZGParams};
+use crate::lookup::padded_columns;
+use crate::msm::{msm, CurvePairing, MsmConfig};
+use crate::pcs::PolynomialCommitment;
+use crate::permutation::{coset_multipliers, Cell, Permutation, PermutationBuilder};
+use crate::pipeline::StageError;
+use crate::proof::{Proof, ScheduleError, VerifyingKey, SELECTORS};
+use crate::store::Storage;
+use crate::transcript::TranscriptHash;
+
+/// Degree blinding adds to the largest committed polynomial on top of
+/// the domain. Every polynomial built from the witness gets a random
+/// multiple of Z_H, which leaves its values on H alone and hides them
+/// everywhere else: one more random coefficient than the points it is
+/// opened at, so degree n + 1 for the wires and S' and n + 2 for z, z_L
+/// and A'. The quotient then reaches degree 3n + 5, putting t_hi at
+/// n + 5, six above the unblinded n − 1.
+pub const BLINDING_DEGREE: usize = 6;
+
+/// How the quotient round holds the evaluations of the ~20 committed
+/// polynomials on its coset of 4n points (8n below n = 8)
+///
+/// `Cache` evaluates each polynomial once and keeps them all, about
+/// (20 + custom gates)·4n field elements, 160 MiB at n = 2^16 over
+/// BLS12-381. `Recompute` evaluates them term by term and keeps at most
+/// six at a time, at the cost of about 30% more FFTs (each wire is
+/// transformed three times, four with custom gates). Prefer `Cache`
+/// while that memory is available; switch when the quotient round is
+/// what runs out of memory, typically from n = 2^20 on machines with a
+/// few GiB. Both give the same proof.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CosetStrategy {
+    #[default]
+    Cache,
+    Recompute,
+}
+
+/// Named setups for callers who would rather not weigh every knob,
+/// applied with `ProverConfig::with_profile`. A profile sets the prover
+/// knobs and the encoding proofs are written in, nothing else: the
+/// curve and opening scheme are the `ProvingKey`'s type parameters, and
+/// every proof has the same three wire columns and lookup argument.
+///
+/// `FastProver` caches the quotient cosets and hands the MSM larger
+/// chunks, and writes uncompressed points, which load faster and take
+/// more bytes. `SmallProof` compresses the points and recomputes the
+/// cosets. `CheapVerifier` writes the EVM encoding the generated
+/// Solidity verifier reads, so it is meant for BN254 keys. `Balanced`
+/// is the default config. The proof itself does not depend on the
+/// profile, only the bytes it is written as.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Profile {
+    #[default]
+    Balanced,
+    FastProver,
+    SmallProof,
+    CheapVerifier,
+}
+
+impl Profile {
+    pub fn msm(self) -> MsmConfig {
+        match self {
+            Profile::FastProver => MsmConfig { chunk_size: 1 << 14, ..MsmConfig::default() },
+            _ => MsmConfig::default(),
+        }
+    }
+
+    pub fn coset(self) -> CosetStrategy {
+        match self {
+            Profile::Balanced | Profile::FastProver => CosetStrategy::Cache,
+            Profile::SmallProof | Profile::CheapVerifier => CosetStrategy::Recompute,
+        }
+    }
+
+    /// Encoding for `Proof::to_bytes_with` and `VerifyingKey::to_bytes_with`
+    pub fn encoding(self) -> Encoding {
+        match self {
+            Profile::Balanced | Profile::SmallProof => Encoding::ARKWORKS,
+            Profile::FastProver => Encoding { points: PointFormat::Uncompressed, endianness: Endianness::Little },
+            Profile::CheapVerifier => Encoding::EVM,
+        }
+    }
+}
+
+/// Tuning knobs for the prover
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProverConfig {
+    pub msm: MsmConfig,
+    pub coset: CosetStrategy,
+    /// Where wire and coset evaluations are kept
+    pub store: Storage,
+    /// Session the proof is bound to, hashed into its transcript; the
+    /// verifier has to name the same one. Empty outside any session.
+    pub session: Vec<u8>,
+    /// Hash of the Fiat-Shamir transcript, which the verifier has to name
+    /// too: `TranscriptHash::Poseidon` for proofs verified in a circuit
+    pub transcript: TranscriptHash,
+}
+
+impl ProverConfig {
+    /// Config with the knobs of `profile`
+    pub fn with_profile(profile: Profile) -> Self {
+        Self {
+            msm: profile.msm(),
+            coset: profile.coset(),
+            store: Storage::default(),
+            session: Vec::new(),
+            transcript: TranscriptHash::default(),
+        }
+    }
+
+    /// Config with MSM parameters benchmarked on this machine
+    #[cfg(feature = "std")]
+    pub fn auto_tuned<G: ark_ec::CurveGroup>() -> Self {
+        Self {
+            msm: MsmConfig::auto_tune::<G>(1 << 10),
+            ..Self::default()
+        }
+    }
+}
+
+/// Circuit rows over the scalar field, the form the prover works on.
+/// All columns have the same power-of-two length (pad unused rows with
+/// zeros), and `permutation` holds the copy constraints over a, b, c.
+///
+/// Each row satisfies q_l·a + q_r·b + q_o·c + q_m·a·b + q_c + PI = 0.
+/// `public_inputs[i]` is subtracted on row `public_rows[i]`, so a row with
+/// only q_l = 1 exposes its `a` value to the verifier. Public rows may sit
+/// anywhere in the circuit. Rows with q_k = 1 also need (a, b, c) to be
+/// one of the `table` entries, of which there are at most as many as rows.
+/// `q_custom[g]` is the selector column of `custom_gates[g]`, and every row
+/// satisfies q_g·e(a, b, c) = 0 for each identity e of that gate.
+#[derive(Debug, Clone)]
+pub struct PlonkCircuit<F: PrimeField> {
+    pub q_l: Vec<F>,
+    pub q_r: Vec<F>,
+    pub q_o: Vec<F>,
+    pub q_m: Vec<F>,
+    pub q_c: Vec<F>,
+    pub q_k: Vec<F>,
+    pub a: Vec<F>,
+    pub b: Vec<F>,
+    pub c: Vec<F>,
+    pub permutation: Permutation,
+    pub public_rows: Vec<usize>,
+    pub public_inputs: Vec<F>,
+    pub table: Vec<[F; 3]>,
+    pub custom_gates: Vec<CustomGate<F>>,
+    pub q_custom: Vec<Vec<F>>,
+}
+
+impl<F: PrimeField> PlonkCircuit<F> {
+    pub fn rows(&self) -> usize {
+        self.a.len()
+    }
+
+    /// Checks that every column has `rows` entries, a power of two of at
+    /// least two, and that the permutation, public rows, table and custom
+    /// selectors fit them. Each public row holds one input: the verifier
+    /// sums a PI term per entry of `public_rows`, so a row listed twice
+    /// would be checked against the sum of its inputs.
+    pub fn check_shape(&self) -> Result<(), KeyError> {
+        let n = self.rows();
+        if !n.is_power_of_two() || n < 2 {
+            return Err(KeyError::Rows(n));
+        }
+        let columns = [&self.q_l, &self.q_r, &self.q_o, &self.q_m, &self.q_c, &self.q_k, &self.b, &self.c];
+        if let Some(column) = columns.into_iter().chain(&self.q_custom).find(|column| column.len() != n) {
+            return Err(KeyError::ColumnLength { expected: n, got: column.len() });
+        }
+        if self.permutation.columns() != 3 || self.permutation.rows() != n {
+            return Err(KeyError::Permutation { columns: self.permutation.columns(), rows: self.permutation.rows() });
+        }
+        check_public_rows(self.public_rows.iter().copied(), n)?;
+        if self.table.len() > n {
+            return Err(KeyError::TableTooLarge { entries: self.table.len(), rows: n });
+        }
+        if self.q_custom.len() != self.custom_gates.len() {
+            return Err(KeyError::CustomSelectors { gates: self.custom_gates.len(), columns: self.q_custom.len() });
+        }
+        Ok(())
+    }
+
+    /// Checks the gate identity on every row and the copy constraints
+    pub fn is_satisfied(&self) -> bool {
+        if self.check_shape().is_err() || self.public_inputs.len() != self.public_rows.len() {
+            return false;
+        }
+        let pi = public_input_evaluations(&self.public_rows, &self.public_inputs, self.rows());
+        let gates = (0..self.rows()).all(|i| {
+            let (a, b, c) = (self.a[i], self.b[i], self.c[i]);
+            let gate = self.q_l[i] * a + self.q_r[i] * b + self.q_o[i] * c + self.q_m[i] * a * b + self.q_c[i] + pi[i];
+            let custom = self.custom_gates.iter().zip(&self.q_custom).all(|(gate, q)| {
+                gate.identities.iter().all(|identity| (q[i] * identity.evaluate([a, b, c])).is_zero())
+            });
+            gate.is_zero() && custom && (self.q_k[i].is_zero() || self.table.contains(&[a, b, c]))
+        });
+        gates && self.permutation.is_satisfied_by(&[self.a.clone(), self.b.clone(), self.c.clone()])
+    }
+}
+
+/// Checks that each of `public_rows` is a row below `n` that no other
+/// public input sits on
+pub(crate) fn check_public_rows(public_rows: impl IntoIterator<Item = usize>, n: usize) -> Result<(), KeyError> {
+    let mut rows = BTreeSet::new();
+    for row in public_rows {
+        if row >= n {
+            return Err(KeyError::PublicRow(row));
+        }
+        if !rows.insert(row) {
+            return Err(KeyError::DuplicatePublicRow(row));
+        }
+    }
+    Ok(())
+}
+
+/// PI over H: −x_i on row `public_rows[i]`, zero on the rest
+pub(crate) fn public_input_evaluations<F: PrimeField>(public_rows: &[usize], public_inputs: &[F], rows: usize) -> Vec<F> {
+    let mut evals = vec![F::zero(); rows];
+    for (row, x) in public_rows.iter().zip(public_inputs) {
+        evals[*row] = -*x;
+    }
+    evals
+}
+
+impl<F: PrimeField> Drop for PlonkCircuit<F> {
+    fn drop(&mut self) {
+        self.a.zeroize();
+        self.b.zeroize();
+        self.c.zeroize();
+    }
+}
+
+/// Everything the prover needs besides the witness: the commitment
+/// scheme's parameters (the SRS for KZG), the preprocessed polynomials
+/// and the matching verifying key
+#[derive(CanonicalSerialize, CanonicalDeserialize)]
+pub struct ProvingKey<E: CurvePairing, S: PolynomialCommitment<E::ScalarField> = KZGParams<E>> {
+    pub params: S,
+    pub vk: VerifyingKey<E, S>,
+    /// q_l, q_r, q_o, q_m, q_c, q_k in coefficient form
+    pub(crate) selectors: [DensePolynomial<E::ScalarField>; SELECTORS],
+    /// q_g for each custom gate in coefficient form
+    pub(crate) custom_selectors: Vec<DensePolynomial<E::ScalarField>>,
+    /// The lookup table columns t_1, t_2, t_3 in coefficient form
+    pub(crate) table: [DensePolynomial<E::ScalarField>; 3],
+    /// t_1, t_2, t_3 over H
+    pub(crate) table_evals: [Vec<E::ScalarField>; 3],
+    /// σ_a, σ_b, σ_c in coefficient form
+    pub(crate) sigmas: [DensePolynomial<E::ScalarField>; 3],
+    /// σ_a, σ_b, σ_c evaluated over H
+    pub(crate) sigma_evals: [Vec<E::ScalarField>; 3],
+}
+
+impl<E: CurvePairing, S: PolynomialCommitment<E::ScalarField>> ProvingKey<E, S> {
+    /// Interpolates and commits to the selectors, custom selectors, σ and
+    /// lookup table of `circuit` under `params`, whose scheme proofs made
+    /// with the key then open with. Only the circuit structure is read,
+    /// never its wire values.
+    pub fn preprocess(params: S, circuit: &PlonkCircuit<E::ScalarField>) -> Result<Self, KeyError> {
+        circuit.check_shape()?;
+        let n = circuit.rows();
+        Self::check_srs_degree(&params, n)?;
+        Self::check_domain(n)?;
+
+        let omega = root_of_unity::<E::ScalarField>(n);
+        let ks = coset_multipliers::<E::ScalarField>(3, n);
+        let config = MsmConfig::default();
+
+        let selectors = [&circuit.q_l, &circuit.q_r, &circuit.q_o, &circuit.q_m, &circuit.q_c, &circuit.q_k]
+            .map(|column| interpolate(column, omega));
+        let custom_selectors: Vec<_> = circuit.q_custom.iter().map(|column| interpolate(column, omega)).collect();
+        let sigma_evals: [Vec<E::ScalarField>; 3] = circuit
+            .permutation
+            .sigma_evaluations(omega, &ks)
+            .ok()
+            .and_then(|evals| evals.try_into().ok())
+            .ok_or(KeyError::Permutation { columns: circuit.permutation.columns(), rows: circuit.permutation.rows() })?;
+        let sigmas = [0, 1, 2].map(|j| interpolate(&sigma_evals[j], omega));
+        let table_evals = padded_columns(&circuit.table, n).ok_or(KeyError::TableTooLarge { entries: circuit.table.len(), rows: n })?;
+        let table = [0, 1, 2].map(|j| interpolate(&table_evals[j], omega));
+
+        let vk = VerifyingKey {
+            domain_size: n as u64,
+            coset_multipliers: [ks[0], ks[1], ks[2]],
+            selector_commitments: commit_each(&params, &selectors, &config)?,
+            sigma_commitments: commit_each(&params, &sigmas, &config)?,
+            table_commitments: commit_each(&params, &table, &config)?,
+            public_rows: circuit.public_rows.iter().map(|row| *row as u64).collect(),
+            custom_gates: circuit.custom_gates.clone(),
+            custom_selector_commitments: custom_selectors.iter().map(|q| params.commit_with(q, &config)).collect::<Result<_, _>>()?,
+            opening_key: params.verifier_key(),
+        };
+
+        Ok(Self {
+            params,
+            vk,
+            selectors,
+            custom_selectors,
+            sigmas,
+            sigma_evals,
+            table,
+            table_evals,
+        })
+    }
+
+    /// SRS degree a circuit of `rows` rows needs: rows − 1 for the
+    /// polynomials interpolated over the domain, plus `BLINDING_DEGREE`
+    pub fn srs_degree_for(rows: usize) -> usize {
+        rows.saturating_sub(1) + BLINDING_DEGREE
+    }
+
+    /// SRS degree this key's circuit needs
+    pub fn required_srs_degree(&self) -> usize {
+        Self::srs_degree_for(self.vk.domain_size as usize)
+    }
+
+    /// Checks that the field has the domains a circuit of `rows` rows is
+    /// transformed over: the rows themselves, a power of two of at least
+    /// two, and the coset the quotient is evaluated on
+    pub fn check_domain(rows: usize) -> Result<(), KeyError> {
+        if !rows.is_power_of_two() || rows < 2 {
+            return Err(KeyError::Rows(rows));
+        }
+        for size in [rows, quotient_domain_size(rows)] {
+            crate::fft::root_of_unity::<E::ScalarField>(size.trailing_zeros())?;
+        }
+        Ok(())
+    }
+
+    /// Checks that `params` can commit to everything a proof of `rows`
+    /// rows commits to, before any polynomial is built
+    pub fn check_srs_degree(params: &S, rows: usize) -> Result<(), KeyError> {
+        let needed = Self::srs_degree_for(rows);
+        match params.max_degree() {
+            Some(supported) if supported >= needed => Ok(()),
+            supported => Err(KeyError::SrsDegree { rows, blinding: BLINDING_DEGREE, supported }),
+        }
+    }
+}
+
+/// Commitments to each of `polys` under `scheme`, in order
+pub(crate) fn commit_each<F: PrimeField, S: PolynomialCommitment<F>, const N: usize>(
+    scheme: &S,
+    polys: &[DensePolynomial<F>; N],
+    config: &MsmConfig,
+) -> Result<[S::Commitment; N], CommitError> {
+    let commitments = polys.iter().map(|poly| scheme.commit_with(poly, config)).collect::<Result<Vec<_>, _>>()?;
+    // One commitment per polynomial, N of them, or an error returned above
+    Ok(commitments.try_into().expect("one commitment per polynomial"))
+}
+
+/// Why `ProvingKey::preprocess` rejected a circuit
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KeyError {
+    /// The row count is not a power of two of at least two
+    Rows(usize),
+    /// A column of another length than `a`
+    ColumnLength { expected: usize, got: usize },
+    /// The permutation is not over three columns of the circuit's rows
+    Permutation { columns: usize, rows: usize },
+    /// A public input row past the last row
+    PublicRow(usize),
+    /// A row holding two public inputs
+    DuplicatePublicRow(usize),
+    /// More table entries than rows
+    TableTooLarge { entries: usize, rows: usize },
+    /// Custom gates and selector columns differ in number
+    CustomSelectors { gates: usize, columns: usize },
+    /// The field has no subgroup of the circuit's order
+    Domain(DomainError),
+    /// The SRS is below the degree a circuit of `rows` rows needs, `None`
+    /// for an SRS without powers
+    SrsDegree { rows: usize, blinding: usize, supported: Option<usize> },
+    /// The SRS is too short for the circuit
+    Commit(CommitError),
+}
+
+impl core::fmt::Display for KeyError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            KeyError::Rows(n) => write!(f, "circuit rows must be a power of two of at least two, got {}", n),
+            KeyError::ColumnLength { expected, got } => write!(f, "column of {} rows in a circuit of {}", got, expected),
+            KeyError::Permutation { columns, rows } => write!(f, "permutation over {}x{} cells does not match the circuit", columns, rows),
+            KeyError::PublicRow(row) => write!(f, "public input row {} out of range", row),
+            KeyError::DuplicatePublicRow(row) => write!(f, "row {} holds more than one public input", row),
+            KeyError::TableTooLarge { entries, rows } => write!(f, "lookup table of {} entries does not fit {} rows", entries, rows),
+            KeyError::CustomSelectors { gates, columns } => {
+                write!(f, "{} custom gates but {} custom selector columns", gates, columns)
+            }
+            KeyError::SrsDegree { rows, blinding, supported } => {
+                let needed = rows.saturating_sub(1) + blinding;
+                write!(f, "circuit needs degree 2^{} - 1 + {} blinding = {}", rows.trailing_zeros(), blinding, needed)?;
+                match supported {
+                    Some(degree) => write!(f, ", SRS supports {}", degree),
+                    None => write!(f, ", SRS is empty"),
+                }
+            }
+            KeyError::Domain(error) => write!(f, "{}", error),
+            KeyError::Commit(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl From<DomainError> for KeyError {
+    fn from(error: DomainError) -> Self {
+        KeyError::Domain(error)
+    }
+}
+
+impl From<CommitError> for KeyError {
+    fn from(error: CommitError) -> Self {
+        KeyError::Commit(error)
+    }
+}
+
+/// Why a proof could not be produced. An unsatisfied witness is not one of
+/// them: it still yields a proof, which fails verification.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProveError {
+    /// The circuit has another row count than the key
+    Rows { expected: usize, got: usize },
+    /// The circuit has another number of public inputs than the key
+    PublicInputCount { expected: usize, got: usize },
+    /// A polynomial exceeds the SRS of the key
+    Commit(CommitError),
+    /// A stage output failed its integrity check
+    Stage(StageError),
+    /// The prover's transcript left the proof schedule
+    Schedule(ScheduleError),
+    /// The key's SRS is below the degree of its circuit
+    Key(KeyError),
+    /// A `Storage::Mmap` buffer could not be created
+    #[cfg(feature = "mmap")]
+    Store(std::io::ErrorKind),
+}
+
+impl core::fmt::Display for ProveError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            ProveError::Rows { expected, got } => write!(f, "circuit has {} rows, the key {}", got, expected),
+            ProveError::PublicInputCount { expected, got } => {
+                write!(f, "expected {} public inputs, got {}", expected, got)
+            }
+            ProveError::Commit(error) => write!(f, "{}", error),
+            ProveError::Stage(error) => write!(f, "{}", error),
+            ProveError::Schedule(error) => write!(f, "{}", error),
+            ProveError::Key(error) => write!(f, "{}", error),
+            #[cfg(feature = "mmap")]
+            ProveError::Store(kind) => write!(f, "prover store could not be created: {}", kind),
+        }
+    }
+}
+
+impl From<CommitError> for ProveError {
+    fn from(error: CommitError) -> Self {
+        ProveError::Commit(error)
+    }
+}
+
+impl From<KeyError> for ProveError {
+    fn from(error: KeyError) -> Self {
+        ProveError::Key(error)
+    }
+}
+
+impl From<ScheduleError> for ProveError {
+    fn from(error: ScheduleError) -> Self {
+        ProveError::Schedule(error)
+    }
+}
+
+impl From<StageError> for ProveError {
+    fn from(error: StageError) -> Self {
+        ProveError::Stage(error)
+    }
+}
+
+pub struct Prover;
+
+impl Prover {
+    /// Proves that the wire values of `circuit` satisfy the circuit `pk`
+    /// was preprocessed from. An unsatisfied circuit still yields a proof,
+    /// one that fails verification. The proof bytes depend on the circuit,
+    /// the key and the blinding drawn from `rng`, not on `ProverConfig` or
+    /// how many threads ran.
+    pub fn prove<E: CurvePairing, S: PolynomialCommitment<E::ScalarField>, R: Rng>(
+        circuit: &PlonkCircuit<E::ScalarField>,
+        pk: &ProvingKey<E, S>,
+        rng: &mut R,
+    ) -> Result<Proof<E, S>, ProveError> {
+        Self::prove_with_config(circuit, pk, &ProverConfig::default(), rng)
+    }
+
+    /// `prove` from a witness alone, for a prover that holds the key but
+    /// not the circuit's structure; the statement is read off the rows the
+    /// key marks public
+    pub fn prove_witness<E: CurvePairing, S: PolynomialCommitment<E::ScalarField>, R: Rng>(
+        witness: &Witness<E::ScalarField>,
+        pk: &ProvingKey<E, S>,
+        rng: &mut R,
+    ) -> Result<Proof<E, S>, ProveError> {
+        let config = ProverConfig::default();
+        let wires = Self::commit_witness(witness, pk, &config, rng)?;
+        let permutation = Self::commit_permutation(pk, &wires, &config, rng)?;
+        let quotient = Self::commit_quotient(pk, &wires, &permutation, &config)?;
+        Self::finish(pk, &wires, &permutation, &quotient, &config)
+    }
+
+    pub fn prove_with_config<E: CurvePairing, S: PolynomialCommitment<E::ScalarField>, R: Rng>(
+        circuit: &PlonkCircuit<E::ScalarField>,
+        pk: &ProvingKey<E, S>,
+        config: &ProverConfig,
+        rng: &mut R,
+    ) -> Result<Proof<E, S>, ProveError> {
+        let wires = Self::commit_wires(circuit, pk, config, rng)?;
+        let permutation = Self::commit_permutation(pk, &wires, config, rng)?;
+        let quotient = Self::commit_quotient(pk, &wires, &permutation, config)?;
+        Self::finish(pk, &wires, &permutation, &quotient, config)
+    }
+
+    /// Proves a fixed four-row circuit with one public input against
+    /// `params` under `config`, so a service can fail fast on a bad setup
+    /// before accepting jobs. Checks, in order:
+    ///
+    /// - every consecutive pair of SRS powers is related by the trapdoor in
+    ///   `g2_s`, batched into one pairing check over a random combination
+    /// - the configured MSM thread pool can be built
+    /// - the test proof verifies (with the `verifier` feature)
+    ///
+    /// The report names the field backend and worker count that ran.
+    pub fn self_test<E: CurvePairing, R: Rng>(
+        params: &KZGParams<E>,
+        config: &ProverConfig,
+        rng: &mut R,
+    ) -> Result<SelfTestReport, SelfTestError> {
+        const ROWS: usize = 4;
+
+        let powers = &params.powers_of_g;
+        if powers.len() <= ProvingKey::<E>::srs_degree_for(ROWS) {
+            return Err(SelfTestError::SrsTooSmall { powers: powers.len() });
+        }
+        if powers[0].is_zero() || params.g2.is_zero() {
+            return Err(SelfTestError::InconsistentSrs);
+        }
+        let weights: Vec<E::ScalarField> = (1..powers.len()).map(|_| E::ScalarField::rand(rng)).collect();
+        let msm_config = MsmConfig::default();
+        // Both slices have powers.len() − 1 bases, one per weight, and an
+        // MSM only fails on a length mismatch
+        let lower: E::G1 = msm(&powers[..powers.len() - 1], &weights, &msm_config).expect("one weight per power");
+        let upper: E::G1 = msm(&powers[1..], &weights, &msm_config).expect("one weight per power");
+        if E::pairing(upper, params.g2) != E::pairing(lower, params.g2_s) {
+            return Err(SelfTestError::InconsistentSrs);
+        }
+
+        #[cfg(feature = "parallel")]
+        let threads = match config.msm.num_threads {
+            Some(threads) => rayon::ThreadPoolBuilder::new()
+                .num_threads(threads)
+                .build()
+                .map_err(|_| SelfTestError::ThreadPool)?
+                .current_num_threads(),
+            None => rayon::current_num_threads(),
+        };
+        #[cfg(not(feature = "parallel"))]
+        let threads = 1;
+
+        // Exposes 15 on row 0, then 2 + 3 = 5 and 5 · 3 = 15
+        let f = |values: [i64; ROWS]| values.map(E::ScalarField::from).to_vec();
+        let mut builder = PermutationBuilder::new(3, ROWS);
+        // Fixed cells, all within the three columns of four rows
+        for (a, b) in [((0, 0), (2, 2)), ((2, 1), (0, 2)), ((1, 1), (1, 2))] {
+            builder.connect(Cell::new(a.0, a.1), Cell::new(b.0, b.1)).expect("cells of the four rows");
+        }
+        let statement = [E::ScalarField::from(15u64)];
+        let circuit = PlonkCircuit {
+            q_l: f([1, 1, 0, 0]),
+            q_r: f([0, 1, 0, 0]),
+            q_o: f([0, -1, -1, 0]),
+            q_m: f([0, 0, 1, 0]),
+            q_c: f([0, 0, 0, 0]),
+            q_k: f([0, 0, 0, 0]),
+            a: f([15, 2, 5, 0]),
+            b: f([0, 3, 3, 0]),
+            c: f([0, 5, 15, 0]),
+            permutation: builder.build(),
+            public_rows: vec![0],
+            public_inputs: statement.to_vec(),
+            table: Vec::new(),
+            custom_gates: Vec::new(),
+            q_custom: Vec::new(),
+        };
+        let params = KZGParams::<E> {
+            powers_of_g: powers[..=ProvingKey::<E>::srs_degree_for(ROWS)].to_vec(),
+            g2: params.g2,
+            g2_s: params.g2_s,
+        };
+        let pk = ProvingKey::<E>::preprocess(params, &circuit).map_err(SelfTestError::Key)?;
+        let _proof = Self::prove_with_config(&circuit, &pk, config, rng).map_err(SelfTestError::Prove)?;
+
+        #[cfg(feature = "verifier")]
+        crate::verifier::Verifier::verify(&pk.vk, &statement, &_proof).map_err(|_| SelfTestError::ProofRejected)?;
+
+        Ok(SelfTestReport {
+            backend: crate::backend::backend_name(),
+            threads,
+        })
+    }
+}
+
+/// What `Prover::self_test` ran on
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SelfTestReport {
+    /// Field arithmetic backend, see `backend::backend_name`
+    pub backend: &'static str,
+    /// Worker threads available to the MSM
+    pub threads: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SelfTestError {
+    /// The SRS cannot commit to the four-row test circuit
+    SrsTooSmall { powers: usize },
+    /// The SRS powers are not consecutive powers of one trapdoor
+    InconsistentSrs,
+    /// The configured thread pool could not be started
+    ThreadPool,
+    /// The test circuit could not be keyed
+    Key(KeyError),
+    /// The test proof could not be produced
+    Prove(ProveError),
+    /// The test proof failed verification
+    ProofRejected,
+}
+
+impl core::fmt::Display for SelfTestError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            SelfTestError::SrsTooSmall { powers } => write!(f, "SRS has only {} powers of g", powers),
+            SelfTestError::InconsistentSrs => write!(f, "SRS powers do not share one trapdoor"),
+            SelfTestError::ThreadPool => write!(f, "thread pool could not be started"),
+            SelfTestError::Key(error) => write!(f, "self-test circuit could not be keyed: {}", error),
+            SelfTestError::Prove(error) => write!(f, "self-test proof could not be produced: {}", error),
+            SelfTestError::ProofRejected => write!(f, "self-test proof did not verify"),
+        }
+    }
+}
+
+// The helpers below run on domains `check_domain` has passed, a power of
+// two the field has a subgroup of, so their transforms cannot fail.
+// `preprocess` checks the circuit's, and every proof starts in
+// `commit_columns`, which checks the key's again since a deserialized
+// key never went through `preprocess`.
+
+pub(crate) fn root_of_unity<F: FftField>(n: usize) -> F {
+    crate::fft::root_of_unity(n.trailing_zeros()).expect("preprocess checked the domain")
+}
+
+/// ω⁻¹ for `omega` of order `n`, as ω^(n−1)
+pub(crate) fn inverse_root<F: FftField>(omega: F, n: usize) -> F {
+    omega.pow([n as u64 - 1])
+}
+
+/// Adds b(X)·Z_H(X) to `poly` for a random b of `scalars` coefficients,
+/// with Z_H = X^n − 1
+pub(crate) fn blind<F: PrimeField, R: Rng>(poly: &mut DensePolynomial<F>, n: usize, scalars: usize, rng: &mut R) {
+    poly.coeffs.resize(n + scalars, F::zero());
+    for i in 0..scalars {
+        let b = F::rand(rng);
+        poly.coeffs[i] -= b;
+        poly.coeffs[n + i] += b;
+    }
+}
+
+/// Values of `poly` on ⟨omega⟩ of order n, reducing it mod X^n − 1 first
+/// so a blinding multiple of Z_H drops out
+pub(crate) fn domain_evaluations<F: FftField>(poly: &DensePolynomial<F>, n: usize, omega: F) -> Vec<F> {
+    let mut evals = vec![F::zero(); n];
+    for (i, coeff) in poly.coeffs.iter().enumerate() {
+        evals[i % n] += coeff;
+    }
+    fft(&mut evals, omega).expect("preprocess checked the domain");
+    evals
+}
+
+/// Coefficients of the polynomial taking `evals` on ⟨omega⟩
+pub(crate) fn interpolate<F: FftField>(evals: &[F], omega: F) -> DensePolynomial<F> {
+    let mut coeffs = evals.to_vec();
+    ifft(&mut coeffs, inverse_root(omega, evals.len())).expect("preprocess checked the domain");
+    DensePolynomial::from_coefficients_vec(coeffs)
+}
+
+/// Four-row circuits shared by the tests of the prover, the verifier and
+/// the modules built on them
+#[cfg(test)]
+pub(crate) mod fixtures {
+    use super::PlonkCircuit;
+    use crate::permutation::{Cell, PermutationBuilder};
+    use alloc::{vec, vec::Vec};
+    use ark_ff::PrimeField;
+
+    fn column<F: PrimeField>(values: [i64; 4]) -> Vec<F> {
+        values.map(F::from).to_vec()
+    }
+
+    /// 2 + 3 = 5, 5 · 3 = `product` with the 5s and 3s wired together,
+    /// padded to four rows
+    pub(crate) fn circuit<F: PrimeField>(product: i64) -> PlonkCircuit<F> {
+        let mut builder = PermutationBuilder::new(3, 4);
+        builder.connect(Cell::new(2, 0), Cell::new(0, 1)).unwrap();
+        builder.connect(Cell::new(1, 0), Cell::new(1, 1)).unwrap();
+        PlonkCircuit {
+            q_l: column([1, 0, 0, 0]),
+            q_r: column([1, 0, 0, 0]),
+            q_o: column([-1, -1, 0, 0]),
+            q_m: column([0, 1, 0, 0]),
+            q_c: column([0, 0, 0, 0]),
+            q_k: column([0, 0, 0, 0]),
+            a: column([2, 5, 0, 0]),
+            b: column([3, 3, 0, 0]),
+            c: column([5, product, 0, 0]),
+            permutation: builder.build(),
+            public_rows: Vec::new(),
+            public_inputs: Vec::new(),
+            table: Vec::new(),
+            custom_gates: Vec::new(),
+            q_custom: Vec::new(),
+        }
+    }
+
+    /// Exposes `product` on row 0, then 2 + 3 = 5 and 5 · 3 = `product`
+    /// with the output wired to the public row
+    pub(crate) fn public_circuit<F: PrimeField>(product: i64) -> PlonkCircuit<F> {
+        let mut builder = PermutationBuilder::new(3, 4);
+        builder.connect(Cell::new(0, 0), Cell::new(2, 2)).unwrap();
+        builder.connect(Cell::new(2, 1), Cell::new(0, 2)).unwrap();
+        builder.connect(Cell::new(1, 1), Cell::new(1, 2)).unwrap();
+        PlonkCircuit {
+            q_l: column([1, 1, 0, 0]),
+            q_r: column([0, 1, 0, 0]),
+            q_o: column([0, -1, -1, 0]),
+            q_m: column([0, 0, 1, 0]),
+            q_c: column([0, 0, 0, 0]),
+            q_k: column([0, 0, 0, 0]),
+            a: column([product, 2, 5, 0]),
+            b: column([0, 3, 3, 0]),
+            c: column([0, 5, product, 0]),
+            permutation: builder.build(),
+            public_rows: vec![0],
+            public_inputs: vec![F::from(product)],
+            table: Vec::new(),
+            custom_gates: Vec::new(),
+            q_custom: Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::fixtures::{circuit, public_circuit};
+    use crate::linearization::{constant_term, linear_terms, EvaluationPoint};
+    use crate::proof::{challenges, Oracle, OPENED_AT_ZETA, OPENED_AT_ZETA_OMEGA};
+    use ark_bls12_381::{Bls12_381, Fr};
+    use ark_ec::{AffineRepr, CurveGroup};
+    use ark_std::{One, Zero};
+
+    fn proving_key() -> ProvingKey<Bls12_381> {
+        let params = KZGParams::setup(9, &mut ark_std::test_rng());
+        ProvingKey::preprocess(params, &circuit(15)).unwrap()
+    }
+
+    /// The quotient identity at ζ, r̄ + r₀ = 0, from the proof's
+    /// evaluations alone
+    fn identity_holds(vk: &VerifyingKey<Bls12_381>, proof: &Proof<Bls12_381>) -> bool {
+        let ch = challenges(vk, &[], TranscriptHash::Sha256, &[], proof).unwrap();
+        let omega = root_of_unity::<Fr>(vk.domain_size as usize);
+        let point = EvaluationPoint::new(vk.domain_size, omega, [ch.theta, ch.beta, ch.gamma, ch.alpha], ch.zeta);
+        proof.evaluations.linearization + constant_term(&proof.evaluations, &point, Fr::zero()) == Fr::zero()
+    }
+
+    #[test]
+    fn test_prove_satisfied_circuit() {
+        let mut rng = ark_std::test_rng();
+        let pk = proving_key();
+        let circuit = circuit(15);
+        assert!(circuit.is_satisfied());
+
+        let proof = Prover::prove(&circuit, &pk, &mut rng).unwrap();
+        assert!(identity_holds(&pk.vk, &proof));
+
+        // The batched openings match the commitments and evaluations, r
+        // first at ζ with [r] built from the commitments
+        let ch = challenges(&pk.vk, &[], TranscriptHash::Sha256, &[], &proof).unwrap();
+        let omega = root_of_unity::<Fr>(4);
+        let batch = |mut combined: <Bls12_381 as ark_ec::pairing::Pairing>::G1, mut value: Fr, mut power: Fr, oracles: &[Oracle], evals: &[Fr]| {
+            for (oracle, eval) in oracles.iter().zip(evals) {
+                combined += proof.commitment(&pk.vk, *oracle).into_group() * power;
+                value += *eval * power;
+                power *= ch.v;
+            }
+            (combined.into_affine(), value)
+        };
+        let point = EvaluationPoint::new(4, omega, [ch.theta, ch.beta, ch.gamma, ch.alpha], ch.zeta);
+        let linearization = linear_terms(&pk.vk, &proof.evaluations, &point)
+            .into_iter()
+            .map(|(oracle, s)| proof.commitment(&pk.vk, oracle) * s)
+            .sum();
+        let e = &proof.evaluations;
+        let (combined, value) = batch(linearization, e.linearization, ch.v, &OPENED_AT_ZETA, &e.at_zeta());
+        assert!(pk.params.verify(&combined, &proof.w_zeta, ch.zeta, value));
+
+        let (combined, value) = batch(Zero::zero(), Fr::zero(), Fr::one(), &OPENED_AT_ZETA_OMEGA, &e.at_zeta_omega());
+        assert!(pk.params.verify(&combined, &proof.w_zeta_omega, ch.zeta * omega, value));
+        assert_eq!(Proof::from_bytes(&proof.to_bytes()), Some(proof.clone()));
+
+        // Fresh blinding commits to other wire polynomials with the same
+        // values on H
+        let again = Prover::prove(&circuit, &pk, &mut rng).unwrap();
+        assert!(identity_holds(&pk.vk, &again));
+        for j in 0..3 {
+            assert_ne!(again.wire_commitments[j], proof.wire_commitments[j]);
+        }
+        assert_ne!(again.z_commitment, proof.z_commitment);
+    }
+
+    #[test]
+    fn test_proof_independent_of_threads() {
+        let mut rng = ark_std::test_rng();
+        let pk = proving_key();
+        let circuit = circuit(15);
+        let prove = |msm: MsmConfig| Prover::prove_with_config(&circuit, &pk, &ProverConfig { msm, ..Default::default() }, &mut ark_std::test_rng()).unwrap();
+
+        let serial = Prover::prove(&circuit, &pk, &mut rng).unwrap().to_bytes();
+        for (chunk_size, num_threads) in [(1, Some(1)), (1, Some(4)), (3, Some(3)), (2, None)] {
+            let proof = prove(MsmConfig { window_bits: None, chunk_size, num_threads });
+            assert_eq!(proof.to_bytes(), serial);
+        }
+    }
+
+    #[test]
+    fn test_profiles() {
+        let pk = proving_key();
+        let circuit = circuit(15);
+        let reference = Prover::prove(&circuit, &pk, &mut ark_std::test_rng()).unwrap();
+        let mut sizes = Vec::new();
+        for profile in [Profile::Balanced, Profile::FastProver, Profile::SmallProof, Profile::CheapVerifier] {
+            let config = ProverConfig::with_profile(profile);
+            assert_eq!((config.msm, config.coset), (profile.msm(), profile.coset()));
+            let proof = Prover::prove_with_config(&circuit, &pk, &config, &mut ark_std::test_rng()).unwrap();
+            assert_eq!(proof, reference);
+            let bytes = proof.to_bytes_with(profile.encoding());
+            assert_eq!(Proof::from_bytes_with(&bytes, profile.encoding()), Some(proof));
+            sizes.push(bytes.len());
+        }
+        assert_eq!(sizes, [1056, 1632, 1056, 1632]);
+        assert_eq!(ProverConfig::with_profile(Profile::default()), ProverConfig::default());
+    }
+
+    #[test]
+    fn test_coset_strategies_agree() {
+        use crate::circuit::Circuit;
+        use crate::constraint_system::{ConstraintSystem, Synthesize};
+        use crate::lookup::Table;
+
+        /// Every quotient term in use: a public x⁵ through the S-box gate,
+        /// a XOR lookup and copy constraints between them
+        struct Mixed;
+
+        impl Synthesize<Fr> for Mixed {
+            fn synthesize(&self, circuit: &mut Circuit<Fr>) {
+                let sbox = circuit.register_gate(CustomGate::quintic_sbox());
+                circuit.add_table(Table::from_binary_op(4, |x, y| x ^ y));
+                let y = circuit.add_public_input(Fr::from(243u64));
+                let x = circuit.alloc(Fr::from(3u64));
+                let square = circuit.mul(x, x);
+                circuit.custom_gate(sbox, x, square, (&y).into());
+                let [p, q] = [1u64, 2].map(|value| circuit.alloc(Fr::from(value)));
+                let r = circuit.add(p, q);
+                circuit.lookup(p, q, r);
+            }
+        }
+
+        let (cs, witness) = ConstraintSystem::synthesize(&Mixed, 8).unwrap();
+        let circuit = cs.assign(&witness).unwrap();
+        assert!(circuit.is_satisfied());
+        let pk = ProvingKey::<Bls12_381>::preprocess(KZGParams::setup(21, &mut ark_std::test_rng()), &circuit).unwrap();
+        let prove = |coset: CosetStrategy| {
+            let config = ProverConfig { coset, ..Default::default() };
+            Prover::prove_with_config(&circuit, &pk, &config, &mut ark_std::test_rng()).unwrap()
+        };
+        let cached = prove(CosetStrategy::Cache);
+        assert_eq!(prove(CosetStrategy::Recompute).to_bytes(), cached.to_bytes());
+        #[cfg(feature = "verifier")]
+        assert!(crate::verifier::Verifier::verify(&pk.vk, &[Fr::from(243u64)], &cached).is_ok());
+
+        // The terms of an unsatisfied circuit do not cancel, and still agree
+        let pk = proving_key();
+        let unsatisfied = self::circuit(16);
+        let prove = |coset: CosetStrategy| {
+            let config = ProverConfig { coset, ..Default::default() };
+            Prover::prove_with_config(&unsatisfied, &pk, &config, &mut ark_std::test_rng()).unwrap().to_bytes()
+        };
+        assert_eq!(prove(CosetStrategy::Recompute), prove(CosetStrategy::Cache));
+    }
+
+    #[cfg(feature = "mmap")]
+    #[test]
+    fn test_mmap_store_matches_memory() {
+        let pk = proving_key();
+        let circuit = circuit(15);
+        let prove = |coset: CosetStrategy, store: Storage| {
+            let config = ProverConfig { coset, store, ..Default::default() };
+            Prover::prove_with_config(&circuit, &pk, &config, &mut ark_std::test_rng()).unwrap().to_bytes()
+        };
+        let memory = prove(CosetStrategy::Cache, Storage::Memory);
+        assert_eq!(prove(CosetStrategy::Cache, Storage::Mmap), memory);
+        assert_eq!(prove(CosetStrategy::Recompute, Storage::Mmap), memory);
+    }
+
+    #[test]
+    fn test_unsatisfied_circuit() {
+        let mut rng = ark_std::test_rng();
+        let pk = proving_key();
+
+        let wrong_product = circuit(16);
+        assert!(!wrong_product.is_satisfied());
+        assert!(!identity_holds(&pk.vk, &Prover::prove(&wrong_product, &pk, &mut rng).unwrap()));
+
+        // Gates hold but a copied value differs
+        let mut broken_wiring = circuit(15);
+        broken_wiring.a[1] = Fr::from(4u64);
+        broken_wiring.c[1] = Fr::from(12u64);
+        assert!(!broken_wiring.is_satisfied());
+        assert!(!identity_holds(&pk.vk, &Prover::prove(&broken_wiring, &pk, &mut rng).unwrap()));
+    }
+
+    #[test]
+    fn test_malformed_inputs_are_errors() {
+        let mut rng = ark_std::test_rng();
+        let small = KZGParams::<Bls12_381>::setup(2, &mut rng);
+        assert_eq!(
+            ProvingKey::<Bls12_381>::preprocess(small, &circuit(15)).err(),
+            Some(KeyError::SrsDegree { rows: 4, blinding: BLINDING_DEGREE, supported: Some(2) })
+        );
+        assert_eq!(
+            KeyError::SrsDegree { rows: 4, blinding: 0, supported: Some(2) }.to_string(),
+            "circuit needs degree 2^2 - 1 + 0 blinding = 3, SRS supports 2"
+        );
+        let params = KZGParams::<Bls12_381> { powers_of_g: Vec::new(), ..KZGParams::setup(0, &mut rng) };
+        assert_eq!(
+            ProvingKey::<Bls12_381>::preprocess(params, &circuit(15)).err(),
+            Some(KeyError::SrsDegree { rows: 4, blinding: BLINDING_DEGREE, supported: None })
+        );
+
+        // A key whose SRS was swapped for a shorter one fails before round 1
+        let mut truncated = proving_key();
+        assert_eq!(truncated.required_srs_degree(), 9);
+        truncated.params.powers_of_g.truncate(2);
+        assert_eq!(
+            Prover::prove(&circuit(15), &truncated, &mut rng).err(),
+            Some(ProveError::Key(KeyError::SrsDegree { rows: 4, blinding: BLINDING_DEGREE, supported: Some(1) }))
+        );
+
+        // A key tampered with after `preprocess` is refused before any
+        // transform runs over its domain
+        let witness = |rows: usize| Witness { a: vec![Fr::zero(); rows], b: vec![Fr::zero(); rows], c: vec![Fr::zero(); rows] };
+        let mut tampered = proving_key();
+        tampered.vk.domain_size = 6;
+        assert_eq!(Prover::prove_witness(&witness(6), &tampered, &mut rng).err(), Some(ProveError::Key(KeyError::Rows(6))));
+        let mut tampered = proving_key();
+        tampered.vk.public_rows = vec![9];
+        assert_eq!(Prover::prove_witness(&witness(4), &tampered, &mut rng).err(), Some(ProveError::Key(KeyError::PublicRow(9))));
+
+        let mut short = circuit(15);
+        short.q_m.pop();
+        assert!(!short.is_satisfied());
+        let params = KZGParams::<Bls12_381>::setup(9, &mut rng);
+        assert_eq!(ProvingKey::<Bls12_381>::preprocess(params, &short).err(), Some(KeyError::ColumnLength { expected: 4, got: 3 }));
+
+        let pk = proving_key();
+        let mut public = circuit(15);
+        public.public_inputs.push(Fr::one());
+        assert_eq!(
+            Prover::prove(&public, &pk, &mut rng).err(),
+            Some(ProveError::PublicInputCount { expected: 0, got: 1 })
+        );
+    }
+
+    #[test]
+    fn test_duplicate_public_rows() {
+        let mut twice = public_circuit::<Fr>(15);
+        twice.public_rows.push(0);
+        twice.public_inputs.push(Fr::zero());
+        assert_eq!(twice.check_shape(), Err(KeyError::DuplicatePublicRow(0)));
+        assert!(!twice.is_satisfied());
+        let params = KZGParams::<Bls12_381>::setup(9, &mut ark_std::test_rng());
+        assert_eq!(ProvingKey::<Bls12_381>::preprocess(params, &twice).err(), Some(KeyError::DuplicatePublicRow(0)));
+
+        #[cfg(feature = "verifier")]
+        {
+            use crate::verifier::{VerifyError, Verifier};
+            let mut rng = ark_std::test_rng();
+            let params = KZGParams::<Bls12_381>::setup(9, &mut rng);
+            let mut pk = ProvingKey::<Bls12_381>::preprocess(params, &public_circuit(15)).unwrap();
+            let proof = Prover::prove(&public_circuit(15), &pk, &mut rng).unwrap();
+            pk.vk.public_rows.push(0);
+            let statement = [Fr::from(15u64), Fr::zero()];
+            assert_eq!(Verifier::verify(&pk.vk, &statement, &proof), Err(VerifyError::MalformedKey));
+        }
+    }
+
+    #[test]
+    fn test_self_test() {
+        let mut rng = ark_std::test_rng();
+        let mut params = KZGParams::<Bls12_381>::setup(12, &mut rng);
+        let report = Prover::self_test(&params, &ProverConfig::default(), &mut rng).unwrap();
+        assert_eq!(report.backend, crate::backend::backend_name());
+        assert!(report.threads >= 1);
+
+        params.powers_of_g[6] = (params.powers_of_g[6] + params.powers_of_g[0]).into_affine();
+        assert_eq!(
+            Prover::self_test(&params, &ProverConfig::default(), &mut rng),
+            Err(SelfTestError::InconsistentSrs)
+        );
+
+        let small = KZGParams::<Bls12_381>::setup(8, &mut rng);
+        assert_eq!(
+            Prover::self_test(&small, &ProverConfig::default(), &mut rng),
+            Err(SelfTestError::SrsTooSmall { powers: 9 })
+        );
+    }
+}
@@ -0,0 +1,419 @@
+//! Round 3 of the prover: the quotient
+//!
+//!   t(X) = (gate + permutation + lookup + custom gate identities) / Z_H(X)
+//!
+//! computed pointwise on a coset of H, where Z_H has no zeros, and split
+//! into t_lo, t_mid and t_hi so each part fits the SRS.
+
+use alloc::{vec, vec::Vec};
+use ark_ff::{batch_inversion, FftField};
+use ark_poly::polynomial::univariate::DensePolynomial;
+use ark_poly::polynomial::DenseUVPolynomial;
+use ark_std::{One, Zero};
+
+use crate::circuit::quotient_domain_size;
+use crate::fft::{DomainError, EvaluationDomain};
+use crate::lookup::compress;
+use crate::msm::CurvePairing;
+use crate::pcs::PolynomialCommitment;
+use crate::prover::{inverse_root, root_of_unity, CosetStrategy, ProveError, ProverConfig, ProvingKey, BLINDING_DEGREE};
+use crate::store::Storage;
+
+/// Challenges the quotient is built with
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct RoundChallenges<F> {
+    pub theta: F,
+    pub beta: F,
+    pub gamma: F,
+    pub alpha: F,
+}
+
+/// Round 2 polynomials of the lookup argument: A', S' and z_L
+pub(crate) struct LookupPolys<'a, F: FftField> {
+    pub permuted: &'a [DensePolynomial<F>; 2],
+    pub z: &'a DensePolynomial<F>,
+}
+
+/// t(X) = [gate + PI + α·(permutation) + α²·L_1·(z − 1) + α³..α⁶·(lookup)] / Z_H,
+/// computed on a coset of `quotient_domain_size(n)` roots of unity, where
+/// Z_H has no zeros.
+/// With A = q_k·(a + θb + θ²c) + (1 − q_k)·T and T = t_1 + θt_2 + θ²t_3,
+/// the lookup terms are
+///
+/// - z_L(ωX)·(A' + β)(S' + γ) − z_L·(A + β)(T + γ)
+/// - L_1·(z_L − 1)
+/// - L_n·(A' − S'), L_n the Lagrange polynomial of the last row
+/// - (A' − S')·(A' − A'(ωX))
+///
+/// followed by q_g·e for every identity e of every custom gate g, each
+/// with the next power of α. `config.coset` and `config.store` only
+/// change how the coset evaluations are held, never the result.
+pub(crate) fn quotient<E: CurvePairing, S: PolynomialCommitment<E::ScalarField>>(
+    pk: &ProvingKey<E, S>,
+    wires: &[DensePolynomial<E::ScalarField>; 3],
+    z: &DensePolynomial<E::ScalarField>,
+    lookup: LookupPolys<E::ScalarField>,
+    pi: &DensePolynomial<E::ScalarField>,
+    ch: &RoundChallenges<E::ScalarField>,
+    config: &ProverConfig,
+) -> Result<DensePolynomial<E::ScalarField>, ProveError> {
+    let n = pk.vk.domain_size as usize;
+    let terms = 6 + pk.vk.custom_gates.iter().map(|gate| gate.identities.len()).sum::<usize>();
+    let domain = QuotientDomain::new(n, terms, ch.alpha, config.store).map_err(|error| ProveError::Key(error.into()))?;
+    let evals = match config.coset {
+        CosetStrategy::Cache => cached_numerator(pk, wires, z, lookup, pi, ch, &domain)?,
+        CosetStrategy::Recompute => recomputed_numerator(pk, wires, z, lookup, pi, ch, &domain)?,
+    };
+    Ok(coset_interpolate(evals, &domain.fft))
+}
+
+/// t_lo, t_mid and t_hi with t = t_lo + X^n·t_mid + X^2n·t_hi. t_lo and
+/// t_mid take n coefficients each and t_hi the remaining n + 6 of a
+/// satisfied circuit's deg t ≤ 3n + 5; anything past that only shows up
+/// for unsatisfied ones and is dropped, which the verifier catches
+pub(crate) fn split<F: FftField>(t: &DensePolynomial<F>, n: usize) -> [DensePolynomial<F>; 3] {
+    let bounds = [(0, n), (n, 2 * n), (2 * n, 3 * n + BLINDING_DEGREE)];
+    bounds.map(|(start, end)| {
+        let coeffs = t.coeffs.get(start..end.min(t.coeffs.len())).unwrap_or(&[]);
+        DensePolynomial::from_coefficients_slice(coeffs)
+    })
+}
+
+/// The coset g·⟨ω⟩ the quotient is computed on, with the Lagrange and
+/// vanishing values every term shares
+struct QuotientDomain<F: FftField> {
+    size: usize,
+    /// size / n, the index step of X ↦ ωX for the ω of H
+    ratio: usize,
+    /// The transforms onto and off the coset, twiddles computed once for
+    /// every polynomial the quotient reads
+    fft: EvaluationDomain<F>,
+    points: Vec<F>,
+    /// Z_H on the coset, which takes `ratio` values
+    vanishing_at: Vec<F>,
+    /// 1 / Z_H on the coset
+    vanishing: Vec<F>,
+    /// n·(x − 1) inverted, L_1 once multiplied by Z_H
+    l1: Vec<F>,
+    /// ω^{n−1}, the last row
+    last: F,
+    /// n·(x − ω^{n−1}) inverted
+    l_last: Vec<F>,
+    alpha_powers: Vec<F>,
+    /// Where the coset evaluations of each polynomial are kept
+    store: Storage,
+}
+
+impl<F: FftField> QuotientDomain<F> {
+    fn new(n: usize, terms: usize, alpha: F, store: Storage) -> Result<Self, DomainError> {
+        let size = quotient_domain_size(n);
+        let ratio = size / n;
+        let fft = EvaluationDomain::<F>::new_for_size(size)?;
+        let omega = fft.omega;
+
+        // x^n on the coset cycles through g^n·ω_r^i for ω_r = ω^n of
+        // order r = size / n, so Z_H takes r values
+        let g_n = F::GENERATOR.pow([n as u64]);
+        let omega_r = omega.pow([n as u64]);
+        let mut vanishing: Vec<F> = (0..ratio as u64).map(|i| g_n * omega_r.pow([i]) - F::one()).collect();
+        let vanishing_at = vanishing.clone();
+        batch_inversion(&mut vanishing);
+
+        // L_1(x) = Z_H(x) / (n·(x − 1))
+        let points: Vec<F> = core::iter::successors(Some(F::GENERATOR), |x| Some(*x * omega)).take(size).collect();
+        let mut l1: Vec<F> = points.iter().map(|x| F::from(n as u64) * (*x - F::one())).collect();
+        batch_inversion(&mut l1);
+        // L_n(x) = ω^{n−1}·Z_H(x) / (n·(x − ω^{n−1}))
+        let last = inverse_root(root_of_unity::<F>(n), n);
+        let mut l_last: Vec<F> = points.iter().map(|x| F::from(n as u64) * (*x - last)).collect();
+        batch_inversion(&mut l_last);
+
+        let alpha_powers = core::iter::successors(Some(alpha), |x| Some(*x * alpha)).take(terms).collect();
+        Ok(Self { size, ratio, fft, points, vanishing_at, vanishing, l1, last, l_last, alpha_powers, store })
+    }
+}
+
+/// The quotient on the coset from every coset evaluation at once
+fn cached_numerator<E: CurvePairing, S: PolynomialCommitment<E::ScalarField>>(
+    pk: &ProvingKey<E, S>,
+    wires: &[DensePolynomial<E::ScalarField>; 3],
+    z: &DensePolynomial<E::ScalarField>,
+    lookup: LookupPolys<E::ScalarField>,
+    pi: &DensePolynomial<E::ScalarField>,
+    ch: &RoundChallenges<E::ScalarField>,
+    d: &QuotientDomain<E::ScalarField>,
+) -> Result<Vec<E::ScalarField>, ProveError> {
+    let (size, ratio) = (d.size, d.ratio);
+    let coset = |poly: &DensePolynomial<E::ScalarField>| d.store.store(coset_evaluations(poly, &d.fft));
+    let each = |polys: &[DensePolynomial<E::ScalarField>]| polys.iter().map(coset).collect::<Result<Vec<_>, _>>();
+    let RoundChallenges { theta, beta, gamma, .. } = *ch;
+    let one = E::ScalarField::one();
+
+    let w = each(wires)?;
+    let q = each(&pk.selectors)?;
+    let q_custom = each(&pk.custom_selectors)?;
+    let s = each(&pk.sigmas)?;
+    let z_evals = coset(z)?;
+    let pi_evals = coset(pi)?;
+    let t = each(&pk.table)?;
+    let permuted_evals = each(lookup.permuted)?;
+    let z_lookup = coset(lookup.z)?;
+    let ks = pk.vk.coset_multipliers;
+    let (vanishing_at, l1, l_last, last) = (&d.vanishing_at, &d.l1, &d.l_last, d.last);
+
+    let custom_gates = &pk.vk.custom_gates;
+    let numerator = (0..size)
+        .map(|i| {
+            let x = d.points[i];
+            let shifted = (i + ratio) % size;
+            let (a, b, c) = (w[0].get(i), w[1].get(i), w[2].get(i));
+            let q_k = q[5].get(i);
+            let gate = q[0].get(i) * a + q[1].get(i) * b + q[2].get(i) * c + q[3].get(i) * a * b + q[4].get(i) + pi_evals.get(i);
+
+            let z_i = z_evals.get(i);
+            let identity = (a + beta * ks[0] * x + gamma)
+                * (b + beta * ks[1] * x + gamma)
+                * (c + beta * ks[2] * x + gamma)
+                * z_i;
+            let permuted = (a + beta * s[0].get(i) + gamma)
+                * (b + beta * s[1].get(i) + gamma)
+                * (c + beta * s[2].get(i) + gamma)
+                * z_evals.get(shifted);
+            let first_row = vanishing_at[i % ratio] * l1[i] * (z_i - one);
+
+            let table = compress([t[0].get(i), t[1].get(i), t[2].get(i)], theta);
+            let input = q_k * compress([a, b, c], theta) + (one - q_k) * table;
+            let (a_p, s_p, z_l) = (permuted_evals[0].get(i), permuted_evals[1].get(i), z_lookup.get(i));
+            let lookup_product = z_lookup.get(shifted) * (a_p + beta) * (s_p + gamma) - z_l * (input + beta) * (table + gamma);
+            let lookup_first = vanishing_at[i % ratio] * l1[i] * (z_l - one);
+            let lookup_last = vanishing_at[i % ratio] * last * l_last[i] * (a_p - s_p);
+            let lookup_step = (a_p - s_p) * (a_p - permuted_evals[0].get(shifted));
+
+            let terms = [identity - permuted, first_row, lookup_product, lookup_first, lookup_last, lookup_step];
+            let custom = custom_gates.iter().zip(&q_custom).flat_map(|(gate, q_g)| {
+                let q_g = q_g.get(i);
+                gate.identities.iter().map(move |identity| q_g * identity.evaluate([a, b, c]))
+            });
+            let total = terms.into_iter().chain(custom).zip(&d.alpha_powers).fold(gate, |acc, (term, power)| acc + *power * term);
+            total * d.vanishing[i % ratio]
+        })
+        .collect();
+    Ok(numerator)
+}
+
+/// The quotient on the coset one constraint term at a time, each term
+/// evaluating the polynomials it reads and dropping them afterwards
+fn recomputed_numerator<E: CurvePairing, S: PolynomialCommitment<E::ScalarField>>(
+    pk: &ProvingKey<E, S>,
+    wires: &[DensePolynomial<E::ScalarField>; 3],
+    z: &DensePolynomial<E::ScalarField>,
+    lookup: LookupPolys<E::ScalarField>,
+    pi: &DensePolynomial<E::ScalarField>,
+    ch: &RoundChallenges<E::ScalarField>,
+    d: &QuotientDomain<E::ScalarField>,
+) -> Result<Vec<E::ScalarField>, ProveError> {
+    let (size, ratio) = (d.size, d.ratio);
+    let coset = |poly: &DensePolynomial<E::ScalarField>| d.store.store(coset_evaluations(poly, &d.fft));
+    let RoundChallenges { theta, beta, gamma, .. } = *ch;
+    let one = E::ScalarField::one();
+    let shifted = |i: usize| (i + ratio) % size;
+    let (vanishing_at, l1, l_last, last) = (&d.vanishing_at, &d.l1, &d.l_last, d.last);
+    // α, α², … in the order the terms are added, as `alpha_powers` holds
+    // them for `cached_numerator`
+    let mut current = one;
+    let mut power = || {
+        current *= ch.alpha;
+        current
+    };
+
+    // q_l·a + q_r·b + q_o·c + q_m·a·b + q_c + PI, accumulated in memory
+    let mut acc = coset_evaluations(pi, &d.fft);
+    {
+        let a = coset(&wires[0])?;
+        let b = coset(&wires[1])?;
+        for (selector, w) in [(0, &a), (1, &b)] {
+            let q = coset(&pk.selectors[selector])?;
+            acc.iter_mut().enumerate().for_each(|(i, acc)| *acc += q.get(i) * w.get(i));
+        }
+        let q_m = coset(&pk.selectors[3])?;
+        acc.iter_mut().enumerate().for_each(|(i, acc)| *acc += q_m.get(i) * a.get(i) * b.get(i));
+    }
+    {
+        let (c, q_o, q_c) = (coset(&wires[2])?, coset(&pk.selectors[2])?, coset(&pk.selectors[4])?);
+        acc.iter_mut().enumerate().for_each(|(i, acc)| *acc += q_o.get(i) * c.get(i) + q_c.get(i));
+    }
+
+    // Permutation, then L_1·(z − 1)
+    {
+        let z_evals = coset(z)?;
+        let mut identity = z_evals.to_vec();
+        let mut permuted: Vec<_> = (0..size).map(|i| z_evals.get(shifted(i))).collect();
+        let ks = pk.vk.coset_multipliers;
+        for j in 0..3 {
+            let (w, s) = (coset(&wires[j])?, coset(&pk.sigmas[j])?);
+            for i in 0..size {
+                let w_i = w.get(i);
+                identity[i] *= w_i + beta * ks[j] * d.points[i] + gamma;
+                permuted[i] *= w_i + beta * s.get(i) + gamma;
+            }
+        }
+        let (alpha_1, alpha_2) = (power(), power());
+        for i in 0..size {
+            acc[i] += alpha_1 * (identity[i] - permuted[i]) + alpha_2 * vanishing_at[i % ratio] * l1[i] * (z_evals.get(i) - one);
+        }
+    }
+
+    // Lookup
+    {
+        let (mut table, mut input) = (vec![E::ScalarField::zero(); size], vec![E::ScalarField::zero(); size]);
+        for j in (0..3).rev() {
+            let (t, w) = (coset(&pk.table[j])?, coset(&wires[j])?);
+            for i in 0..size {
+                table[i] = table[i] * theta + t.get(i);
+                input[i] = input[i] * theta + w.get(i);
+            }
+        }
+        let q_k = coset(&pk.selectors[5])?;
+        input.iter_mut().enumerate().for_each(|(i, input)| *input = q_k.get(i) * *input + (one - q_k.get(i)) * table[i]);
+        drop(q_k);
+
+        let (a_p, s_p, z_lookup) = (coset(&lookup.permuted[0])?, coset(&lookup.permuted[1])?, coset(lookup.z)?);
+        let alpha = [power(), power(), power(), power()];
+        for i in 0..size {
+            let (a_i, s_i, z_i) = (a_p.get(i), s_p.get(i), z_lookup.get(i));
+            let lookup_product = z_lookup.get(shifted(i)) * (a_i + beta) * (s_i + gamma)
+                - z_i * (input[i] + beta) * (table[i] + gamma);
+            let lookup_first = vanishing_at[i % ratio] * l1[i] * (z_i - one);
+            let lookup_last = vanishing_at[i % ratio] * last * l_last[i] * (a_i - s_i);
+            let lookup_step = (a_i - s_i) * (a_i - a_p.get(shifted(i)));
+            acc[i] += alpha[0] * lookup_product + alpha[1] * lookup_first + alpha[2] * lookup_last + alpha[3] * lookup_step;
+        }
+    }
+
+    // Custom gates
+    if !pk.vk.custom_gates.is_empty() {
+        let w = [coset(&wires[0])?, coset(&wires[1])?, coset(&wires[2])?];
+        for (gate, selector) in pk.vk.custom_gates.iter().zip(&pk.custom_selectors) {
+            let q_g = coset(selector)?;
+            for identity in &gate.identities {
+                let alpha = power();
+                for (i, acc) in acc.iter_mut().enumerate() {
+                    *acc += alpha * q_g.get(i) * identity.evaluate([w[0].get(i), w[1].get(i), w[2].get(i)]);
+                }
+            }
+        }
+    }
+
+    acc.iter_mut().enumerate().for_each(|(i, acc)| *acc *= d.vanishing[i % ratio]);
+    Ok(acc)
+}
+
+/// Evaluations of `poly` on the coset g·H of `domain`, g the field generator
+fn coset_evaluations<F: FftField>(poly: &DensePolynomial<F>, domain: &EvaluationDomain<F>) -> Vec<F> {
+    let mut coeffs = poly.coeffs.clone();
+    coeffs.resize(domain.size, F::zero());
+    // The length is the domain's own size, the only thing checked
+    domain.coset_fft(&mut coeffs).expect("preprocess checked the domain");
+    coeffs
+}
+
+fn coset_interpolate<F: FftField>(evals: Vec<F>, domain: &EvaluationDomain<F>) -> DensePolynomial<F> {
+    let mut coeffs = evals;
+    // `evals` holds one value per point of the domain, the only thing
+    // checked
+    domain.coset_ifft(&mut coeffs).expect("preprocess checked the domain");
+    DensePolynomial::from_coefficients_vec(coeffs)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bls12_381::{Bls12_381, Fr};
+
+    use crate::kgz::KZGParams;
+    use crate::permutation::PermutationBuilder;
+    use crate::prover::PlonkCircuit;
+
+    fn poly(coeffs: &[i64]) -> DensePolynomial<Fr> {
+        DensePolynomial::from_coefficients_vec(coeffs.iter().map(|c| Fr::from(*c)).collect())
+    }
+
+    /// Four rows, no copies and no table, with the given selectors set on
+    /// every row and the others zero
+    fn proving_key(q_l: i64, q_o: i64, q_m: i64) -> ProvingKey<Bls12_381> {
+        let column = |value: i64| vec![Fr::from(value); 4];
+        let circuit = PlonkCircuit {
+            q_l: column(q_l),
+            q_r: column(0),
+            q_o: column(q_o),
+            q_m: column(q_m),
+            q_c: column(0),
+            q_k: column(0),
+            a: column(0),
+            b: column(0),
+            c: column(0),
+            permutation: PermutationBuilder::new(3, 4).build(),
+            public_rows: Vec::new(),
+            public_inputs: Vec::new(),
+            table: Vec::new(),
+            custom_gates: Vec::new(),
+            q_custom: Vec::new(),
+        };
+        ProvingKey::preprocess(KZGParams::setup(9, &mut ark_std::test_rng()), &circuit).unwrap()
+    }
+
+    /// t for the given wires with z = z_L = 1, A' = S' = 0 and no public
+    /// inputs, under both strategies
+    fn quotients(pk: &ProvingKey<Bls12_381>, wires: [DensePolynomial<Fr>; 3]) -> [DensePolynomial<Fr>; 2] {
+        let one = poly(&[1]);
+        let permuted = [DensePolynomial::zero(), DensePolynomial::zero()];
+        let ch = RoundChallenges { theta: Fr::from(2u64), beta: Fr::from(3u64), gamma: Fr::from(5u64), alpha: Fr::from(7u64) };
+        [CosetStrategy::Cache, CosetStrategy::Recompute].map(|coset| {
+            let lookup = LookupPolys { permuted: &permuted, z: &one };
+            let config = ProverConfig { coset, ..Default::default() };
+            quotient(pk, &wires, &one, lookup, &DensePolynomial::zero(), &ch, &config).unwrap()
+        })
+    }
+
+    #[test]
+    fn test_hand_computed_quotients() {
+        // Z_H = X^4 − 1. With σ the identity and z = 1 the permutation
+        // terms cancel, and with an empty table so do the lookup terms,
+        // leaving the gate over Z_H.
+        let z_h = poly(&[-1, 0, 0, 0, 1]);
+
+        // q_l·a + q_m·a·b for a = Z_H, b = X·Z_H: t = 1 + X·Z_H
+        let pk = proving_key(1, 0, 1);
+        let wires = [z_h.clone(), &z_h * &poly(&[0, 1]), DensePolynomial::zero()];
+        for t in quotients(&pk, wires) {
+            assert_eq!(t, poly(&[1, -1, 0, 0, 0, 1]));
+            assert_eq!(split(&t, 4), [poly(&[1, -1]), poly(&[0, 1]), DensePolynomial::zero()]);
+        }
+
+        // q_o·c for c = 3X^2·Z_H: t = 3X^2
+        let pk = proving_key(0, 1, 0);
+        let wires = [DensePolynomial::zero(), DensePolynomial::zero(), &z_h * &poly(&[0, 0, 3])];
+        for t in quotients(&pk, wires) {
+            assert_eq!(t, poly(&[0, 0, 3]));
+        }
+    }
+
+    #[test]
+    fn test_split_recombines() {
+        let n = 4;
+        let t = poly(&(1..=(3 * n + BLINDING_DEGREE) as i64).collect::<Vec<_>>());
+        let [lo, mid, hi] = split(&t, n);
+        assert_eq!((lo.coeffs.len(), mid.coeffs.len(), hi.coeffs.len()), (n, n, n + BLINDING_DEGREE));
+        let x_n = poly(&[0, 0, 0, 0, 1]);
+        assert_eq!(&lo + &(&x_n * &(&mid + &(&x_n * &hi))), t);
+
+        // Coefficients past deg t ≤ 3n + 5 are dropped
+        let mut longer = t.coeffs.clone();
+        longer.push(Fr::from(99u64));
+        assert_eq!(split(&DensePolynomial::from_coefficients_vec(longer), n), [lo, mid, hi]);
+
+        let short = split(&poly(&[1, 2]), n);
+        assert_eq!(short, [poly(&[1, 2]), DensePolynomial::zero(), DensePolynomial::zero()]);
+    }
+}
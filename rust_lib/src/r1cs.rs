@@ -0,0 +1,403 @@
+//! R1CS frontend, so circuits written for Groth16 backends can be proved
+//! here: an arkworks `ConstraintSystemRef`, or a Circom `.r1cs` file with
+//! the `.wtns` witness its witness generator writes.
+//!
+//! Both number the variables z the same way: z[0] is the constant 1, the
+//! next `public` are the public inputs in statement order (Circom's
+//! outputs, then its public inputs) and the rest are private. Each
+//! constraint ⟨A, z⟩·⟨B, z⟩ = ⟨C, z⟩ is lowered to gates: a side is summed
+//! with add gates, a term whose coefficient is not 1 is first multiplied
+//! by that coefficient as a constant, and the product of A and B is tied
+//! to C by `assert_equal`. Every use of a variable reads the same
+//! `Variable`, so the copy constraints between constraints come for free.
+//!
+//! Circom files are read as described in iden3's `r1csfile` and
+//! `snarkjs` specifications; sections other than the header and the
+//! constraints (or the witness values) are skipped.
+
+use alloc::vec::Vec;
+use ark_ff::{BigInteger, PrimeField};
+use ark_relations::r1cs::ConstraintSystemRef;
+
+use crate::circuit::{Circuit, Variable};
+use crate::constraint_system::Synthesize;
+use crate::lowering::{GateCount, Lowering};
+
+/// Σ coefficient·z[index] over `(coefficient, index)` terms
+pub type LinearCombination<F> = Vec<(F, usize)>;
+
+/// Why a constraint system could not be imported
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum R1csError {
+    /// Not a version 1 `.r1cs` or version 2 `.wtns` file, or cut short
+    Format,
+    /// The file is over another prime field
+    FieldMismatch,
+    /// A constraint reads a variable past the last one, or the public
+    /// inputs do not fit in the variables
+    VariableOutOfRange(usize),
+    /// An assignment that is not one value per variable
+    Assignment { expected: usize, got: usize },
+    /// `ConstraintSystemRef::None`, or one that did not keep its matrices
+    NoMatrices,
+}
+
+impl core::fmt::Display for R1csError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            R1csError::Format => write!(f, "malformed Circom file"),
+            R1csError::FieldMismatch => write!(f, "Circom file is over another field"),
+            R1csError::VariableOutOfRange(index) => write!(f, "constraint reads variable {} past the last one", index),
+            R1csError::Assignment { expected, got } => write!(f, "assignment has {} values, needs {}", got, expected),
+            R1csError::NoMatrices => write!(f, "constraint system has no matrices"),
+        }
+    }
+}
+
+/// A rank-one constraint system and, once known, its assignment. Lay it
+/// out with `ConstraintSystem::synthesize(&r1cs, r1cs.gates())`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct R1cs<F: PrimeField> {
+    public: usize,
+    variables: usize,
+    constraints: Vec<[LinearCombination<F>; 3]>,
+    assignment: Vec<F>,
+}
+
+impl<F: PrimeField> R1cs<F> {
+    /// `variables` counts z[0]; each constraint is its (A, B, C)
+    pub fn new(public: usize, variables: usize, constraints: Vec<[LinearCombination<F>; 3]>) -> Result<Self, R1csError> {
+        if public + 1 > variables {
+            return Err(R1csError::VariableOutOfRange(public));
+        }
+        let terms = constraints.iter().flatten().flatten();
+        if let Some((_, index)) = terms.into_iter().find(|(_, index)| *index >= variables) {
+            return Err(R1csError::VariableOutOfRange(*index));
+        }
+        Ok(Self { public, variables, constraints, assignment: Vec::new() })
+    }
+
+    /// The matrices of `cs`, and its assignment unless it was built in
+    /// setup mode. Finalizes `cs`, inlining its symbolic combinations.
+    pub fn from_arkworks(cs: &ConstraintSystemRef<F>) -> Result<Self, R1csError> {
+        cs.finalize();
+        let matrices = cs.to_matrices().ok_or(R1csError::NoMatrices)?;
+        let constraints = matrices.a.into_iter().zip(matrices.b).zip(matrices.c).map(|((a, b), c)| [a, b, c]).collect();
+        let variables = matrices.num_instance_variables + matrices.num_witness_variables;
+        let r1cs = Self::new(matrices.num_instance_variables - 1, variables, constraints)?;
+        match cs.borrow() {
+            Some(inner) if !inner.is_in_setup_mode() => {
+                r1cs.with_assignment([&inner.instance_assignment[..], &inner.witness_assignment[..]].concat())
+            }
+            _ => Ok(r1cs),
+        }
+    }
+
+    /// The constraints of a Circom `.r1cs` file, without an assignment
+    pub fn from_circom(r1cs: &[u8]) -> Result<Self, R1csError> {
+        let sections = sections(r1cs, b"r1cs", 1)?;
+        let mut header = Reader(section(&sections, 1)?);
+        let n8 = field_header::<F>(&mut header)?;
+        let variables = header.u32()?;
+        let public = header.u32()? + header.u32()?;
+        let _private_inputs = header.u32()?;
+        let _labels = header.u64()?;
+        let count = header.u32()?;
+
+        let mut body = Reader(section(&sections, 2)?);
+        let mut combination = || -> Result<LinearCombination<F>, R1csError> {
+            let terms = body.u32()?;
+            (0..terms)
+                .map(|_| {
+                    let index = body.u32()?;
+                    Ok((body.field(n8)?, index))
+                })
+                .collect()
+        };
+        let constraints = (0..count)
+            .map(|_| Ok([combination()?, combination()?, combination()?]))
+            .collect::<Result<Vec<_>, R1csError>>()?;
+        Self::new(public, variables, constraints)
+    }
+
+    /// The same constraints with z = `assignment`, z[0] included
+    pub fn with_assignment(mut self, assignment: Vec<F>) -> Result<Self, R1csError> {
+        if assignment.len() != self.variables {
+            return Err(R1csError::Assignment { expected: self.variables, got: assignment.len() });
+        }
+        self.assignment = assignment;
+        Ok(self)
+    }
+
+    /// Public inputs, not counting z[0]
+    pub fn public(&self) -> usize {
+        self.public
+    }
+
+    /// Variables, z[0] included
+    pub fn variables(&self) -> usize {
+        self.variables
+    }
+
+    pub fn constraints(&self) -> &[[LinearCombination<F>; 3]] {
+        &self.constraints
+    }
+
+    /// Public input values, empty without an assignment
+    pub fn public_inputs(&self) -> Vec<F> {
+        self.assignment.get(1..=self.public).map_or_else(Vec::new, <[F]>::to_vec)
+    }
+
+    /// Whether the assignment satisfies every constraint
+    pub fn is_satisfied(&self) -> bool {
+        let z = &self.assignment;
+        let dot = |lc: &LinearCombination<F>| lc.iter().map(|(coefficient, index)| *coefficient * z[*index]).sum::<F>();
+        !z.is_empty() && self.constraints.iter().all(|[a, b, c]| dot(a) * dot(b) == dot(c))
+    }
+
+    /// Gates the lowering adds, the capacity to synthesize with
+    pub fn gates(&self) -> usize {
+        let mut count = GateCount::default();
+        for lc in self.constraints.iter().flatten() {
+            let (offset, terms) = split(lc);
+            count.sum(terms.map(|(coefficient, _)| coefficient), offset);
+        }
+        self.public + count.gates + 2 * self.constraints.len()
+    }
+}
+
+/// The constant term of `lc`, read off z[0] = 1, and the other terms
+fn split<F: PrimeField>(lc: &LinearCombination<F>) -> (F, impl Iterator<Item = (F, usize)> + '_) {
+    let offset = lc.iter().filter(|(_, index)| *index == 0).map(|(coefficient, _)| *coefficient).sum();
+    (offset, lc.iter().copied().filter(|(_, index)| *index != 0))
+}
+
+/// Without an assignment every variable is zero, which lays out the same
+/// gates for keying
+impl<F: PrimeField> Synthesize<F> for R1cs<F> {
+    fn synthesize(&self, circuit: &mut Circuit<F>) {
+        circuit.namespace("r1cs", |circuit| {
+            let value = |index: usize| self.assignment.get(index).copied().unwrap_or_default();
+            // z[1..], z[0] being folded into the constants
+            let mut z: Vec<Variable> = (1..=self.public).map(|index| Variable::from(&circuit.add_public_input(value(index)))).collect();
+            z.extend((self.public + 1..self.variables).map(|index| circuit.alloc(value(index))));
+
+            let mut lowering = Lowering::new(circuit);
+            for [a, b, c] in &self.constraints {
+                let [a, b, c] = [a, b, c].map(|lc| {
+                    let (offset, terms) = split(lc);
+                    lowering.sum(terms.map(|(coefficient, index)| (coefficient, z[index - 1])), offset)
+                });
+                let product = lowering.circuit.mul(a, b);
+                lowering.circuit.assert_equal(product, c);
+            }
+        });
+    }
+}
+
+/// z from a Circom `.wtns` file, z[0] included
+pub fn read_circom_witness<F: PrimeField>(wtns: &[u8]) -> Result<Vec<F>, R1csError> {
+    let sections = sections(wtns, b"wtns", 2)?;
+    let mut header = Reader(section(&sections, 1)?);
+    let n8 = field_header::<F>(&mut header)?;
+    let count = header.u32()?;
+    let mut values = Reader(section(&sections, 2)?);
+    (0..count).map(|_| values.field(n8)).collect()
+}
+
+/// Little-endian reads off the front of a byte slice
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8], R1csError> {
+        if self.0.len() < n {
+            return Err(R1csError::Format);
+        }
+        let (head, rest) = self.0.split_at(n);
+        self.0 = rest;
+        Ok(head)
+    }
+
+    fn u32(&mut self) -> Result<usize, R1csError> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().expect("4 bytes")) as usize)
+    }
+
+    fn u64(&mut self) -> Result<u64, R1csError> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().expect("8 bytes")))
+    }
+
+    /// An `n8`-byte little-endian element, rejected unless below the modulus
+    fn field<F: PrimeField>(&mut self, n8: usize) -> Result<F, R1csError> {
+        F::deserialize_uncompressed(self.take(n8)?).map_err(|_| R1csError::Format)
+    }
+}
+
+/// The (type, body) sections of a Circom binary file
+fn sections<'a>(bytes: &'a [u8], magic: &[u8; 4], version: usize) -> Result<Vec<(usize, &'a [u8])>, R1csError> {
+    let mut reader = Reader(bytes);
+    if reader.take(4)? != magic || reader.u32()? != version {
+        return Err(R1csError::Format);
+    }
+    let count = reader.u32()?;
+    (0..count)
+        .map(|_| {
+            let kind = reader.u32()?;
+            let size = usize::try_from(reader.u64()?).map_err(|_| R1csError::Format)?;
+            Ok((kind, reader.take(size)?))
+        })
+        .collect()
+}
+
+fn section<'a>(sections: &[(usize, &'a [u8])], kind: usize) -> Result<&'a [u8], R1csError> {
+    sections.iter().find(|(k, _)| *k == kind).map(|(_, body)| *body).ok_or(R1csError::Format)
+}
+
+/// Reads the element size and prime that open both headers, returning
+/// the size once the prime is checked to be `F`'s modulus
+fn field_header<F: PrimeField>(header: &mut Reader) -> Result<usize, R1csError> {
+    let n8 = header.u32()?;
+    let mut modulus = F::MODULUS.to_bytes_le();
+    modulus.resize(n8, 0);
+    if header.take(n8)? != modulus || n8 != F::zero().uncompressed_size() {
+        return Err(R1csError::FieldMismatch);
+    }
+    Ok(n8)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bn254::Fr;
+    use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystem as ArkConstraintSystem, LinearCombination as ArkCombination, SynthesisError, Variable as ArkVariable};
+    use ark_serialize::CanonicalSerialize;
+
+    use crate::constraint_system::ConstraintSystem;
+
+    /// x³ + 2x + 5 = y for private x and public y, with the 2x and the 5
+    /// folded into the last constraint as a Circom compiler would
+    struct Cubic {
+        x: u64,
+        y: u64,
+    }
+
+    impl ConstraintSynthesizer<Fr> for Cubic {
+        fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
+            let y = cs.new_input_variable(|| Ok(Fr::from(self.y)))?;
+            let x = cs.new_witness_variable(|| Ok(Fr::from(self.x)))?;
+            let square = cs.new_witness_variable(|| Ok(Fr::from(self.x * self.x)))?;
+            let cube = cs.new_witness_variable(|| Ok(Fr::from(self.x * self.x * self.x)))?;
+            cs.enforce_constraint(x.into(), x.into(), square.into())?;
+            cs.enforce_constraint(square.into(), x.into(), cube.into())?;
+            let lhs = ArkCombination::from(cube) + (Fr::from(2u64), x) + (Fr::from(5u64), ArkVariable::One);
+            cs.enforce_constraint(lhs, ArkVariable::One.into(), y.into())?;
+            Ok(())
+        }
+    }
+
+    fn import(x: u64, y: u64) -> R1cs<Fr> {
+        let cs = ArkConstraintSystem::new_ref();
+        Cubic { x, y }.generate_constraints(cs.clone()).unwrap();
+        R1cs::from_arkworks(&cs).unwrap()
+    }
+
+    #[test]
+    fn test_arkworks_import() {
+        let r1cs = import(3, 38);
+        assert_eq!((r1cs.public(), r1cs.variables(), r1cs.constraints().len()), (1, 5, 3));
+        assert!(r1cs.is_satisfied());
+        assert_eq!(r1cs.public_inputs(), vec![Fr::from(38u64)]);
+
+        let mut circuit = Circuit::new(r1cs.gates());
+        r1cs.synthesize(&mut circuit);
+        assert_eq!((circuit.error(), circuit.gates.len()), (None, r1cs.gates()));
+        assert!(circuit.verify_constraints().is_satisfied() && circuit.verify_copy_constraints());
+        assert_eq!(circuit.public_input_values(), r1cs.public_inputs());
+
+        // A wrong output is caught by the gates it was lowered to
+        let wrong = import(3, 39);
+        assert!(!wrong.is_satisfied());
+        let mut circuit = Circuit::new(wrong.gates());
+        wrong.synthesize(&mut circuit);
+        assert!(circuit.first_unsatisfied().is_some_and(|gate| gate.namespace == "r1cs"));
+
+        // The layout does not depend on the assignment
+        let (cs, _) = ConstraintSystem::synthesize(&r1cs, r1cs.gates()).unwrap();
+        let blank = R1cs::new(r1cs.public(), r1cs.variables(), r1cs.constraints().to_vec()).unwrap();
+        assert_eq!(ConstraintSystem::synthesize(&blank, blank.gates()).unwrap().0, cs);
+    }
+
+    #[test]
+    #[cfg(all(feature = "prover", feature = "verifier"))]
+    fn test_imported_circuit_proves() {
+        use crate::bn254::{Params, Proof, ProvingKey};
+        use crate::prover::Prover;
+        use crate::verifier::Verifier;
+
+        let mut rng = ark_std::test_rng();
+        let r1cs = import(3, 38);
+        let (cs, witness) = ConstraintSystem::synthesize(&r1cs, r1cs.gates()).unwrap();
+        let circuit = cs.assign(&witness).unwrap();
+        let pk = ProvingKey::preprocess(Params::setup(ProvingKey::srs_degree_for(cs.rows), &mut rng), &circuit).unwrap();
+        let proof: Proof = Prover::prove(&circuit, &pk, &mut rng).unwrap();
+        assert_eq!(Verifier::verify(&pk.vk, &r1cs.public_inputs(), &proof), Ok(()));
+        assert!(Verifier::verify(&pk.vk, &[Fr::from(39u64)], &proof).is_err());
+    }
+
+    /// Circom's layout of `r1cs`: the header, then the constraints; the
+    /// wire-to-label map is left out
+    fn circom_r1cs(r1cs: &R1cs<Fr>) -> Vec<u8> {
+        let element = |value: &Fr, out: &mut Vec<u8>| value.serialize_uncompressed(out).unwrap();
+        let mut header = 32u32.to_le_bytes().to_vec();
+        header.extend(Fr::MODULUS.to_bytes_le());
+        for count in [r1cs.variables(), r1cs.public(), 0, r1cs.variables() - r1cs.public() - 1] {
+            header.extend((count as u32).to_le_bytes());
+        }
+        header.extend(0u64.to_le_bytes());
+        header.extend((r1cs.constraints().len() as u32).to_le_bytes());
+        let mut body = Vec::new();
+        for lc in r1cs.constraints().iter().flatten() {
+            body.extend((lc.len() as u32).to_le_bytes());
+            for (coefficient, index) in lc {
+                body.extend((*index as u32).to_le_bytes());
+                element(coefficient, &mut body);
+            }
+        }
+        file(b"r1cs", 1, &[(1, header), (2, body)])
+    }
+
+    fn file(magic: &[u8; 4], version: u32, sections: &[(u32, Vec<u8>)]) -> Vec<u8> {
+        let mut bytes = magic.to_vec();
+        bytes.extend(version.to_le_bytes());
+        bytes.extend((sections.len() as u32).to_le_bytes());
+        for (kind, body) in sections {
+            bytes.extend(kind.to_le_bytes());
+            bytes.extend((body.len() as u64).to_le_bytes());
+            bytes.extend(body);
+        }
+        bytes
+    }
+
+    #[test]
+    fn test_circom_import() {
+        let r1cs = import(3, 38);
+        let assignment: Vec<Fr> = [1u64, 38, 3, 9, 27].map(Fr::from).to_vec();
+        let mut values = Vec::new();
+        for value in &assignment {
+            value.serialize_uncompressed(&mut values).unwrap();
+        }
+        let mut header = 32u32.to_le_bytes().to_vec();
+        header.extend(Fr::MODULUS.to_bytes_le());
+        header.extend(5u32.to_le_bytes());
+        let wtns = file(b"wtns", 2, &[(1, header), (2, values)]);
+
+        let bytes = circom_r1cs(&r1cs);
+        let imported = R1cs::<Fr>::from_circom(&bytes).unwrap().with_assignment(read_circom_witness(&wtns).unwrap()).unwrap();
+        assert_eq!(imported, r1cs);
+
+        assert_eq!(R1cs::<Fr>::from_circom(&bytes[..bytes.len() - 1]), Err(R1csError::Format));
+        assert_eq!(R1cs::<Fr>::from_circom(&wtns), Err(R1csError::Format));
+        assert_eq!(R1cs::<ark_bls12_381::Fr>::from_circom(&bytes), Err(R1csError::FieldMismatch));
+        assert_eq!(imported.clone().with_assignment(assignment[..4].to_vec()), Err(R1csError::Assignment { expected: 5, got: 4 }));
+        assert_eq!(R1cs::new(1, 5, vec![[vec![(Fr::from(1u64), 5)], vec![], vec![]]]), Err(R1csError::VariableOutOfRange(5)));
+    }
+}
@@ -0,0 +1,250 @@
+use alloc::{collections::{BTreeMap, BTreeSet}, vec::Vec};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use core::marker::PhantomData;
+
+pub use crate::digest::CircuitDigest;
+use crate::digest::{fingerprint, DigestScheme, Sha256};
+
+/// SHA-256 of the compressed encoding of a verifying key
+pub fn vk_digest<V: CanonicalSerialize>(vk: &V) -> CircuitDigest {
+    fingerprint::<Sha256, _>(vk)
+}
+
+/// Hooks deciding which circuits a registry accepts and serves
+pub trait RegistryPolicy {
+    fn allow_register(&self, digest: &CircuitDigest) -> bool;
+
+    fn allow_lookup(&self, digest: &CircuitDigest) -> bool {
+        self.allow_register(digest)
+    }
+}
+
+/// Accepts every circuit
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AllowAll;
+
+impl RegistryPolicy for AllowAll {
+    fn allow_register(&self, _digest: &CircuitDigest) -> bool {
+        true
+    }
+}
+
+/// Accepts only the listed circuits
+#[derive(Debug, Clone, Default)]
+pub struct AllowList(pub BTreeSet<CircuitDigest>);
+
+impl RegistryPolicy for AllowList {
+    fn allow_register(&self, digest: &CircuitDigest) -> bool {
+        self.0.contains(digest)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RegistryError {
+    NotAllowed(CircuitDigest),
+    NotFound(CircuitDigest),
+    /// A digest that is not the key's fingerprint in the registry's scheme
+    DigestMismatch(CircuitDigest),
+    Malformed,
+}
+
+impl core::fmt::Display for RegistryError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            RegistryError::NotAllowed(_) => write!(f, "circuit is not allowed by the registry policy"),
+            RegistryError::NotFound(_) => write!(f, "no verifying key registered for circuit"),
+            RegistryError::DigestMismatch(_) => write!(f, "digest does not match the verifying key"),
+            RegistryError::Malformed => write!(f, "malformed registry encoding"),
+        }
+    }
+}
+
+/// Verifying keys of many circuits, keyed by their fingerprint in digest
+/// scheme `S`, for services that verify proofs from a set of registered
+/// circuits
+pub struct VkRegistry<V, P: RegistryPolicy = AllowAll, S: DigestScheme = Sha256> {
+    keys: BTreeMap<CircuitDigest, V>,
+    policy: P,
+    scheme: PhantomData<S>,
+}
+
+impl<V> VkRegistry<V, AllowAll> {
+    pub fn new() -> Self {
+        Self::with_policy(AllowAll)
+    }
+}
+
+impl<V> Default for VkRegistry<V, AllowAll> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<V, P: RegistryPolicy> VkRegistry<V, P> {
+    pub fn with_policy(policy: P) -> Self {
+        Self::with_scheme(policy)
+    }
+}
+
+impl<V, P: RegistryPolicy, S: DigestScheme> VkRegistry<V, P, S> {
+    /// A registry keyed by fingerprints in `S` rather than SHA-256
+    pub fn with_scheme(policy: P) -> Self {
+        Self {
+            keys: BTreeMap::new(),
+            policy,
+            scheme: PhantomData,
+        }
+    }
+
+    pub fn policy(&self) -> &P {
+        &self.policy
+    }
+
+    pub fn get(&self, digest: &CircuitDigest) -> Result<&V, RegistryError> {
+        if !self.policy.allow_lookup(digest) {
+            return Err(RegistryError::NotAllowed(*digest));
+        }
+        self.keys.get(digest).ok_or(RegistryError::NotFound(*digest))
+    }
+
+    pub fn remove(&mut self, digest: &CircuitDigest) -> Option<V> {
+        self.keys.remove(digest)
+    }
+
+    pub fn contains(&self, digest: &CircuitDigest) -> bool {
+        self.keys.contains_key(digest)
+    }
+
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    pub fn digests(&self) -> impl Iterator<Item = &CircuitDigest> {
+        self.keys.keys()
+    }
+}
+
+impl<V: CanonicalSerialize + CanonicalDeserialize, P: RegistryPolicy, S: DigestScheme> VkRegistry<V, P, S> {
+    /// Registers `vk` under `digest`, returning the key it replaced.
+    /// `digest` must be the fingerprint of `vk` in `S`.
+    pub fn register(&mut self, digest: CircuitDigest, vk: V) -> Result<Option<V>, RegistryError> {
+        if !self.policy.allow_register(&digest) {
+            return Err(RegistryError::NotAllowed(digest));
+        }
+        if fingerprint::<S, _>(&vk) != digest {
+            return Err(RegistryError::DigestMismatch(digest));
+        }
+        Ok(self.keys.insert(digest, vk))
+    }
+
+    /// Registers `vk` under its own fingerprint
+    pub fn register_vk(&mut self, vk: V) -> Result<CircuitDigest, RegistryError> {
+        let digest = fingerprint::<S, _>(&vk);
+        self.register(digest, vk)?;
+        Ok(digest)
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        (self.keys.len() as u64).serialize_compressed(&mut bytes).expect("writing to a Vec");
+        for (digest, vk) in &self.keys {
+            digest.serialize_compressed(&mut bytes).expect("writing to a Vec");
+            vk.serialize_compressed(&mut bytes).expect("writing to a Vec");
+        }
+        bytes
+    }
+
+    /// Decodes a registry, re-applying `policy` to every entry and
+    /// checking every digest against its key
+    pub fn from_bytes(mut bytes: &[u8], policy: P) -> Result<Self, RegistryError> {
+        let count = u64::deserialize_compressed(&mut bytes).map_err(|_| RegistryError::Malformed)?;
+        let mut registry = Self::with_scheme(policy);
+        for _ in 0..count {
+            let digest = CircuitDigest::deserialize_compressed(&mut bytes).map_err(|_| RegistryError::Malformed)?;
+            let vk = V::deserialize_compressed(&mut bytes).map_err(|_| RegistryError::Malformed)?;
+            registry.register(digest, vk)?;
+        }
+        if !bytes.is_empty() {
+            return Err(RegistryError::Malformed);
+        }
+        Ok(registry)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::digest::Keccak256;
+    use ark_bls12_381::G1Affine;
+    use ark_std::UniformRand;
+
+    #[test]
+    fn test_register_and_lookup() {
+        let mut rng = ark_std::test_rng();
+        let vk_a = G1Affine::rand(&mut rng);
+        let vk_b = G1Affine::rand(&mut rng);
+
+        let mut registry = VkRegistry::new();
+        let a = registry.register_vk(vk_a).unwrap();
+        let b = registry.register_vk(vk_b).unwrap();
+        assert_ne!(a, b);
+        assert_eq!(registry.len(), 2);
+        assert_eq!(registry.get(&a), Ok(&vk_a));
+        assert_eq!(registry.get(&[0u8; 32]), Err(RegistryError::NotFound([0u8; 32])));
+
+        let mut keccak_registry = VkRegistry::<_, _, Keccak256>::with_scheme(AllowAll);
+        let keccak = keccak_registry.register_vk(vk_a).unwrap();
+        assert_ne!(keccak, a);
+        assert_eq!(keccak_registry.get(&keccak), Ok(&vk_a));
+        assert_eq!(keccak_registry.register(a, vk_a), Err(RegistryError::DigestMismatch(a)));
+        assert_eq!(registry.register(a, vk_b), Err(RegistryError::DigestMismatch(a)));
+
+        let decoded = VkRegistry::<G1Affine>::from_bytes(&registry.to_bytes(), AllowAll).unwrap();
+        assert_eq!(decoded.get(&b), Ok(&vk_b));
+        assert!(VkRegistry::<G1Affine>::from_bytes(&registry.to_bytes()[..40], AllowAll).is_err());
+        // Read as keyed by Keccak-256, the SHA-256 digests do not match
+        let wrong_scheme = VkRegistry::<G1Affine, _, Keccak256>::from_bytes(&registry.to_bytes(), AllowAll);
+        assert!(matches!(wrong_scheme, Err(RegistryError::DigestMismatch(_))));
+    }
+
+    #[test]
+    fn test_tampered_bytes() {
+        let mut rng = ark_std::test_rng();
+        let vk = G1Affine::rand(&mut rng);
+        let mut registry = VkRegistry::new();
+        let digest = registry.register_vk(vk).unwrap();
+
+        // Swap in another key under the same digest
+        let mut bytes = registry.to_bytes();
+        let mut other = Vec::new();
+        G1Affine::rand(&mut rng).serialize_compressed(&mut other).unwrap();
+        bytes.truncate(bytes.len() - other.len());
+        bytes.extend(other);
+        let tampered = VkRegistry::<G1Affine>::from_bytes(&bytes, AllowAll);
+        assert_eq!(tampered.err(), Some(RegistryError::DigestMismatch(digest)));
+    }
+
+    #[test]
+    fn test_allow_list_policy() {
+        let mut rng = ark_std::test_rng();
+        let vk_a = G1Affine::rand(&mut rng);
+        let vk_b = G1Affine::rand(&mut rng);
+        let a = vk_digest(&vk_a);
+        let b = vk_digest(&vk_b);
+
+        let mut registry = VkRegistry::with_policy(AllowList([a].into_iter().collect()));
+        assert!(registry.register(a, vk_a).is_ok());
+        assert_eq!(registry.register(b, vk_b), Err(RegistryError::NotAllowed(b)));
+        assert_eq!(registry.get(&b), Err(RegistryError::NotAllowed(b)));
+
+        let mut open = VkRegistry::new();
+        open.register(a, vk_a).unwrap();
+        open.register(b, vk_b).unwrap();
+        let restricted = VkRegistry::<G1Affine, _>::from_bytes(&open.to_bytes(), AllowList([a].into_iter().collect()));
+        assert_eq!(restricted.err(), Some(RegistryError::NotAllowed(b)));
+    }
+}
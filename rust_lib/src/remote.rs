@@ -0,0 +1,315 @@
+use alloc::vec::Vec;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use zeroize::Zeroize;
+
+/// Job sent to a remote prover. Witness and public inputs travel in their
+/// serialized form, the circuit is identified by its digest.
+#[derive(Debug, Clone, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct ProofRequest {
+    pub circuit_digest: [u8; 32],
+    pub public_inputs: Vec<u8>,
+    pub witness: Vec<u8>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct ProofResponse {
+    pub circuit_digest: [u8; 32],
+    pub proof: Vec<u8>,
+}
+
+impl Drop for ProofRequest {
+    fn drop(&mut self) {
+        self.witness.zeroize();
+    }
+}
+
+impl ProofRequest {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        self.serialize_compressed(&mut bytes).expect("writing to a Vec");
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        Self::deserialize_compressed(bytes).ok()
+    }
+}
+
+impl ProofResponse {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        self.serialize_compressed(&mut bytes).expect("writing to a Vec");
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        Self::deserialize_compressed(bytes).ok()
+    }
+}
+
+#[derive(Debug)]
+pub enum RemoteError {
+    #[cfg(feature = "std")]
+    Io(std::io::Error),
+    /// Non-200 status from the service
+    Http(u16),
+    /// Response could not be parsed
+    Malformed,
+    /// Response was for a different circuit than requested
+    CircuitMismatch,
+    /// Returned proof failed local verification
+    InvalidProof,
+}
+
+impl core::fmt::Display for RemoteError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            #[cfg(feature = "std")]
+            RemoteError::Io(e) => write!(f, "remote prover I/O error: {}", e),
+            RemoteError::Http(status) => write!(f, "remote prover returned HTTP {}", status),
+            RemoteError::Malformed => write!(f, "malformed response from remote prover"),
+            RemoteError::CircuitMismatch => write!(f, "remote prover answered for a different circuit"),
+            RemoteError::InvalidProof => write!(f, "remote proof failed local verification"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<std::io::Error> for RemoteError {
+    fn from(e: std::io::Error) -> Self {
+        RemoteError::Io(e)
+    }
+}
+
+/// Something that turns proof requests into proofs, locally or elsewhere
+pub trait RemoteProver {
+    fn prove(&self, request: &ProofRequest) -> Result<ProofResponse, RemoteError>;
+}
+
+/// Delegates `request` and only returns the proof once `verify` accepts it,
+/// so callers never have to trust the remote side.
+pub fn prove_verified<P, V>(prover: &P, request: &ProofRequest, verify: V) -> Result<Vec<u8>, RemoteError>
+where
+    P: RemoteProver + ?Sized,
+    V: FnOnce(&[u8]) -> bool,
+{
+    let response = prover.prove(request)?;
+    if response.circuit_digest != request.circuit_digest {
+        return Err(RemoteError::CircuitMismatch);
+    }
+    if !verify(&response.proof) {
+        return Err(RemoteError::InvalidProof);
+    }
+    Ok(response.proof)
+}
+
+/// Reference client: POSTs the encoded request to `http://{addr}{path}`
+/// and reads the encoded response from the body.
+#[cfg(feature = "std")]
+pub struct HttpProver {
+    pub addr: String,
+    pub path: String,
+    pub timeout: Option<std::time::Duration>,
+    /// Largest response body accepted, `http::DEFAULT_MAX_BODY` by default
+    pub max_body: usize,
+}
+
+#[cfg(feature = "std")]
+impl HttpProver {
+    pub fn new(addr: &str, path: &str) -> Self {
+        Self {
+            addr: addr.to_string(),
+            path: path.to_string(),
+            timeout: Some(std::time::Duration::from_secs(600)),
+            max_body: http::DEFAULT_MAX_BODY,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl RemoteProver for HttpProver {
+    fn prove(&self, request: &ProofRequest) -> Result<ProofResponse, RemoteError> {
+        use std::io::Write;
+
+        let mut stream = std::net::TcpStream::connect(&self.addr)?;
+        stream.set_read_timeout(self.timeout)?;
+
+        let body = request.to_bytes();
+        write!(
+            stream,
+            "POST {} HTTP/1.1\r\nHost: {}\r\nContent-Type: application/octet-stream\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+            self.path,
+            self.addr,
+            body.len()
+        )?;
+        stream.write_all(&body)?;
+        stream.flush()?;
+
+        let (status, body) = http::read_message(&mut stream, self.max_body)?;
+        if status != 200 {
+            return Err(RemoteError::Http(status));
+        }
+        ProofResponse::from_bytes(&body).ok_or(RemoteError::Malformed)
+    }
+}
+
+/// Minimal HTTP/1.1 framing shared by the client and the reference server
+#[cfg(feature = "std")]
+pub mod http {
+    use super::{ProofRequest, ProofResponse};
+    use std::io::{BufRead, BufReader, Read, Write};
+
+    /// Body size limit of `HttpProver::new`, well above any proof or
+    /// witness the crate produces
+    pub const DEFAULT_MAX_BODY: usize = 64 << 20;
+
+    /// Reads a request or response, returning the status code (0 for
+    /// requests) and the Content-Length delimited body. A Content-Length
+    /// above `max_body` is rejected with `InvalidData` before any of the
+    /// body is read.
+    pub fn read_message<R: Read>(reader: R, max_body: usize) -> std::io::Result<(u16, Vec<u8>)> {
+        let mut reader = BufReader::new(reader);
+        let mut line = String::new();
+        reader.read_line(&mut line)?;
+
+        let status = if line.starts_with("HTTP/") {
+            line.split_whitespace().nth(1).and_then(|s| s.parse().ok()).unwrap_or(0)
+        } else {
+            0
+        };
+
+        let mut content_length = 0usize;
+        loop {
+            line.clear();
+            if reader.read_line(&mut line)? == 0 || line == "\r\n" || line == "\n" {
+                break;
+            }
+            if let Some((name, value)) = line.split_once(':') {
+                if name.trim().eq_ignore_ascii_case("content-length") {
+                    content_length = value.trim().parse().map_err(|_| {
+                        std::io::Error::new(std::io::ErrorKind::InvalidData, "bad Content-Length")
+                    })?;
+                }
+            }
+        }
+
+        if content_length > max_body {
+            return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, "body exceeds the size limit"));
+        }
+        let mut body = Vec::new();
+        reader.take(content_length as u64).read_to_end(&mut body)?;
+        if body.len() < content_length {
+            return Err(std::io::ErrorKind::UnexpectedEof.into());
+        }
+        Ok((status, body))
+    }
+
+    /// Answers a single connection by running `handler` on the decoded
+    /// request, accepting bodies up to `max_body` bytes; a reference for
+    /// services exposing a prover over HTTP
+    pub fn serve_one<S, H>(mut stream: S, max_body: usize, handler: H) -> std::io::Result<()>
+    where
+        S: Read + Write,
+        H: FnOnce(ProofRequest) -> Option<ProofResponse>,
+    {
+        let (_, body) = read_message(&mut stream, max_body)?;
+        let response = ProofRequest::from_bytes(&body).and_then(handler);
+
+        match response {
+            Some(response) => {
+                let body = response.to_bytes();
+                write!(
+                    stream,
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/octet-stream\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                    body.len()
+                )?;
+                stream.write_all(&body)?;
+            }
+            None => write!(stream, "HTTP/1.1 400 Bad Request\r\nContent-Length: 0\r\nConnection: close\r\n\r\n")?,
+        }
+        stream.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request() -> ProofRequest {
+        ProofRequest {
+            circuit_digest: [3u8; 32],
+            public_inputs: vec![1, 2],
+            witness: vec![4, 5, 6],
+        }
+    }
+
+    struct EchoProver;
+
+    impl RemoteProver for EchoProver {
+        fn prove(&self, request: &ProofRequest) -> Result<ProofResponse, RemoteError> {
+            Ok(ProofResponse {
+                circuit_digest: request.circuit_digest,
+                proof: request.witness.clone(),
+            })
+        }
+    }
+
+    #[test]
+    fn test_prove_verified() {
+        let request = request();
+        assert_eq!(prove_verified(&EchoProver, &request, |p| p == [4, 5, 6]).unwrap(), vec![4, 5, 6]);
+        assert!(matches!(
+            prove_verified(&EchoProver, &request, |_| false),
+            Err(RemoteError::InvalidProof)
+        ));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_http_roundtrip() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+
+        let server = std::thread::spawn(move || {
+            for _ in 0..2 {
+                let (stream, _) = listener.accept().unwrap();
+                http::serve_one(stream, http::DEFAULT_MAX_BODY, |req| {
+                    if req.witness.is_empty() {
+                        return None;
+                    }
+                    Some(ProofResponse {
+                        circuit_digest: req.circuit_digest,
+                        proof: req.witness.iter().rev().copied().collect(),
+                    })
+                })
+                .unwrap();
+            }
+        });
+
+        let prover = HttpProver::new(&addr, "/prove");
+        let response = prover.prove(&request()).unwrap();
+        assert_eq!(response.proof, vec![6, 5, 4]);
+
+        let mut empty = request();
+        empty.witness.clear();
+        assert!(matches!(prover.prove(&empty), Err(RemoteError::Http(400))));
+
+        server.join().unwrap();
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_body_limit() {
+        let message = b"HTTP/1.1 200 OK\r\nContent-Length: 5\r\n\r\nhello";
+        assert_eq!(http::read_message(&message[..], 5).unwrap(), (200, b"hello".to_vec()));
+        let err = http::read_message(&message[..], 4).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+
+        let huge = b"POST / HTTP/1.1\r\nContent-Length: 18446744073709551615\r\n\r\n";
+        let err = http::read_message(&huge[..], http::DEFAULT_MAX_BODY).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+        let short = b"POST / HTTP/1.1\r\nContent-Length: 5\r\n\r\nhi";
+        let err = http::read_message(&short[..], 5).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::UnexpectedEof);
+    }
+}
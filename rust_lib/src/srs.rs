@@ -0,0 +1,179 @@
+//! Loading a structured reference string from a public ceremony instead
+//! of `KZGParams::setup`, whose locally sampled s is known to whoever ran
+//! it.
+//!
+//! The Ethereum KZG ceremony (EIP-4844) publishes its BLS12-381 powers in
+//! the `trusted_setup.txt` layout of c-kzg-4844: the G1 count and the G2
+//! count on one line each, then the G1 points in Lagrange form, the G2
+//! powers and the G1 powers, one hex-encoded compressed point per line.
+//! Only the monomial powers are used; the Lagrange block is skipped.
+//!
+//! Every point is decompressed with its subgroup check, and
+//! `check_powers` then confirms the G1 powers all share one s with the
+//! G2 pair in a single pairing equation.
+
+use alloc::vec::Vec;
+use ark_bls12_381::{Bls12_381, G1Affine, G2Affine};
+use ark_ec::AffineRepr;
+use ark_serialize::CanonicalDeserialize;
+use ark_std::Zero;
+
+use crate::kgz::KZGParams;
+use crate::msm::CurvePairing;
+use crate::transcript::Transcript;
+
+/// Why an SRS file was rejected
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SrsError {
+    /// The file ends early or a count line is not a number
+    Format { line: usize },
+    /// Line `line` is not a valid compressed point of the prime-order
+    /// subgroup
+    Point { line: usize },
+    /// Fewer than two G2 powers, or no G1 power
+    TooFewPowers,
+    /// The powers are not those of one s
+    Inconsistent,
+}
+
+impl core::fmt::Display for SrsError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            SrsError::Format { line } => write!(f, "malformed SRS file at line {}", line),
+            SrsError::Point { line } => write!(f, "invalid point at line {}", line),
+            SrsError::TooFewPowers => write!(f, "SRS needs one G1 and two G2 powers"),
+            SrsError::Inconsistent => write!(f, "SRS powers are not consistent"),
+        }
+    }
+}
+
+/// Parses an Ethereum KZG ceremony `trusted_setup.txt` into parameters of
+/// degree one less than its G1 count, checked with `check_powers`
+pub fn from_ethereum_setup(text: &str) -> Result<KZGParams<Bls12_381>, SrsError> {
+    let mut lines = text.lines().enumerate().map(|(i, line)| (i + 1, line.trim())).filter(|(_, line)| !line.is_empty());
+    let mut count = || {
+        let (line, text) = lines.next().ok_or(SrsError::Format { line: 0 })?;
+        text.parse::<usize>().map_err(|_| SrsError::Format { line })
+    };
+    let (n_g1, n_g2) = (count()?, count()?);
+    if n_g1 == 0 || n_g2 < 2 {
+        return Err(SrsError::TooFewPowers);
+    }
+
+    let mut next = |expected: usize| {
+        let (line, text) = lines.next().ok_or(SrsError::Format { line: 0 })?;
+        let bytes = decode_hex(text).filter(|bytes| bytes.len() == expected).ok_or(SrsError::Point { line })?;
+        Ok::<_, SrsError>((line, bytes))
+    };
+    for _ in 0..n_g1 {
+        next(48)?;
+    }
+    let mut g2 = Vec::with_capacity(2);
+    for i in 0..n_g2 {
+        let (line, bytes) = next(96)?;
+        if i < 2 {
+            g2.push(G2Affine::deserialize_compressed(&bytes[..]).map_err(|_| SrsError::Point { line })?);
+        }
+    }
+    let powers_of_g = (0..n_g1)
+        .map(|_| {
+            let (line, bytes) = next(48)?;
+            G1Affine::deserialize_compressed(&bytes[..]).map_err(|_| SrsError::Point { line })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let params = KZGParams { powers_of_g, g2: g2[0], g2_s: g2[1] };
+    check_powers(&params)?;
+    Ok(params)
+}
+
+/// Checks that powers_of_g[i] = s^i·g1 for the s of g2_s = s·g2, with
+/// nonzero generators, by a random combination of the relations
+/// e(powers[i + 1], g2) = e(powers[i], g2_s):
+///
+///   e(Σ r^i·powers[i + 1], g2) = e(Σ r^i·powers[i], g2_s)
+///
+/// r is hashed from the powers, so no caller randomness is needed.
+pub fn check_powers<E: CurvePairing>(params: &KZGParams<E>) -> Result<(), SrsError> {
+    let powers = &params.powers_of_g;
+    let Some(g1) = powers.first() else {
+        return Err(SrsError::TooFewPowers);
+    };
+    if g1.is_zero() || params.g2.is_zero() || params.g2_s.is_zero() {
+        return Err(SrsError::Inconsistent);
+    }
+    let mut transcript = Transcript::new(b"plonk-srs-check");
+    transcript.absorb(b"g1_powers", powers);
+    transcript.absorb(b"g2", &[params.g2, params.g2_s]);
+    let r: E::ScalarField = transcript.squeeze(b"r");
+
+    let (mut shifted, mut unshifted) = (E::G1::zero(), E::G1::zero());
+    for pair in powers.windows(2).rev() {
+        shifted = shifted * r + pair[1];
+        unshifted = unshifted * r + pair[0];
+    }
+    if E::multi_pairing([shifted, -unshifted], [params.g2, params.g2_s]).is_zero() {
+        Ok(())
+    } else {
+        Err(SrsError::Inconsistent)
+    }
+}
+
+/// Bytes of a hex string with an optional 0x prefix
+fn decode_hex(text: &str) -> Option<Vec<u8>> {
+    let text = text.strip_prefix("0x").unwrap_or(text);
+    if !text.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..text.len()).step_by(2).map(|i| u8::from_str_radix(text.get(i..i + 2)?, 16).ok()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_serialize::CanonicalSerialize;
+
+    fn hex<T: CanonicalSerialize>(point: &T) -> String {
+        let mut bytes = Vec::new();
+        point.serialize_compressed(&mut bytes).unwrap();
+        bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+    }
+
+    /// `params` in the ceremony layout, with the G1 powers standing in for
+    /// the skipped Lagrange block
+    fn ethereum_file(params: &KZGParams<Bls12_381>) -> String {
+        let g1: Vec<String> = params.powers_of_g.iter().map(hex).collect();
+        let g2 = [hex(&params.g2), hex(&params.g2_s), hex(&params.g2)];
+        let mut lines = vec![g1.len().to_string(), g2.len().to_string()];
+        lines.extend(g1.iter().cloned());
+        lines.extend(g2);
+        lines.extend(g1);
+        lines.join("\n")
+    }
+
+    #[test]
+    fn test_ethereum_setup() {
+        // The G1 generator as the ceremony files encode it
+        let generator = "97f1d3a73197d7942695638c4fa9ac0fc3688c4f9774b905a14e3a3f171bac586c55e83ff97a1aeffb3af00adb22c6bb";
+        assert_eq!(G1Affine::deserialize_compressed(&decode_hex(generator).unwrap()[..]).unwrap(), G1Affine::generator());
+
+        let params = KZGParams::<Bls12_381>::setup(7, &mut ark_std::test_rng());
+        let loaded = from_ethereum_setup(&ethereum_file(&params)).unwrap();
+        assert_eq!(loaded.powers_of_g, params.powers_of_g);
+        assert_eq!((loaded.g2, loaded.g2_s), (params.g2, params.g2_s));
+
+        // A valid point in the wrong place
+        let mut swapped = params.powers_of_g.clone();
+        swapped.swap(3, 4);
+        let file = ethereum_file(&KZGParams { powers_of_g: swapped, g2: params.g2, g2_s: params.g2_s });
+        assert_eq!(from_ethereum_setup(&file).err(), Some(SrsError::Inconsistent));
+
+        // Line 14 is the first monomial power, the generator
+        let file = ethereum_file(&params);
+        let broken: Vec<String> = file.lines().enumerate().map(|(i, line)| if i == 13 { line.replacen("97f1", "97f2", 1) } else { line.to_string() }).collect();
+        assert_eq!(from_ethereum_setup(&broken.join("\n")).err(), Some(SrsError::Point { line: 14 }));
+        let truncated: String = file.lines().take(12).collect::<Vec<_>>().join("\n");
+        assert_eq!(from_ethereum_setup(&truncated).err(), Some(SrsError::Format { line: 0 }));
+        assert_eq!(from_ethereum_setup("8\nx\n").err(), Some(SrsError::Format { line: 2 }));
+    }
+}
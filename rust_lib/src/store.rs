@@ -0,0 +1,285 @@
+use alloc::{boxed::Box, vec, vec::Vec};
+use ark_ff::PrimeField;
+use zeroize::Zeroize;
+
+use crate::prover::ProveError;
+
+#[cfg(feature = "mmap")]
+use std::fs::{File, OpenOptions};
+#[cfg(feature = "mmap")]
+use std::path::{Path, PathBuf};
+#[cfg(feature = "mmap")]
+use memmap2::MmapMut;
+
+/// Storage for the long field-element vectors the prover works with
+/// (wire columns, coset evaluations). Access goes through indices and
+/// chunks so an implementation does not have to keep everything in RAM.
+pub trait PolyStore<F: PrimeField> {
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn get(&self, index: usize) -> F;
+
+    fn set(&mut self, index: usize, value: F);
+
+    /// Reads `out.len()` elements starting at `start`
+    fn read_chunk(&self, start: usize, out: &mut [F]) {
+        for (i, value) in out.iter_mut().enumerate() {
+            *value = self.get(start + i);
+        }
+    }
+
+    /// Writes `values` starting at `start`
+    fn write_chunk(&mut self, start: usize, values: &[F]) {
+        for (i, value) in values.iter().enumerate() {
+            self.set(start + i, *value);
+        }
+    }
+
+    /// Loads the whole vector into memory
+    fn to_vec(&self) -> Vec<F> {
+        let mut out = vec![F::zero(); self.len()];
+        self.read_chunk(0, &mut out);
+        out
+    }
+}
+
+/// Plain `Vec` backed store
+#[derive(Debug, Clone, PartialEq)]
+pub struct MemoryStore<F: PrimeField> {
+    values: Vec<F>,
+}
+
+impl<F: PrimeField> MemoryStore<F> {
+    pub fn new(len: usize) -> Self {
+        Self { values: vec![F::zero(); len] }
+    }
+
+    pub fn from_vec(values: Vec<F>) -> Self {
+        Self { values }
+    }
+
+    pub fn as_slice(&self) -> &[F] {
+        &self.values
+    }
+
+    pub fn into_vec(mut self) -> Vec<F> {
+        core::mem::take(&mut self.values)
+    }
+}
+
+/// Stored columns are witness-derived, so they are wiped on drop
+impl<F: PrimeField> Drop for MemoryStore<F> {
+    fn drop(&mut self) {
+        self.values.zeroize();
+    }
+}
+
+impl<F: PrimeField> PolyStore<F> for MemoryStore<F> {
+    fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    fn get(&self, index: usize) -> F {
+        self.values[index]
+    }
+
+    fn set(&mut self, index: usize, value: F) {
+        self.values[index] = value;
+    }
+
+    fn read_chunk(&self, start: usize, out: &mut [F]) {
+        out.copy_from_slice(&self.values[start..start + out.len()]);
+    }
+
+    fn write_chunk(&mut self, start: usize, values: &[F]) {
+        self.values[start..start + values.len()].copy_from_slice(values);
+    }
+
+    fn to_vec(&self) -> Vec<F> {
+        self.values.clone()
+    }
+}
+
+/// Where the prover keeps its wire evaluations and the coset
+/// evaluations of the quotient round, set through `ProverConfig::store`.
+/// Both give the same proof.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Storage {
+    #[default]
+    Memory,
+    /// A temporary `MmapStore` per buffer, for circuits whose quotient
+    /// round does not fit in RAM
+    #[cfg(feature = "mmap")]
+    Mmap,
+}
+
+impl Storage {
+    /// A store of this kind holding `values`
+    pub fn store<F: PrimeField>(self, values: Vec<F>) -> Result<Box<dyn PolyStore<F>>, ProveError> {
+        match self {
+            Storage::Memory => Ok(Box::new(MemoryStore::from_vec(values))),
+            #[cfg(feature = "mmap")]
+            Storage::Mmap => {
+                let mut values = values;
+                let mut store = MmapStore::temp(values.len()).map_err(|error| ProveError::Store(error.kind()))?;
+                store.write_chunk(0, &values);
+                values.zeroize();
+                Ok(Box::new(store))
+            }
+        }
+    }
+}
+
+/// Store backed by a memory-mapped file, so the OS pages elements in and
+/// out as needed. Elements are kept in their uncompressed canonical encoding.
+#[cfg(feature = "mmap")]
+pub struct MmapStore<F: PrimeField> {
+    mmap: MmapMut,
+    len: usize,
+    element_size: usize,
+    path: PathBuf,
+    remove_on_drop: bool,
+    _file: File,
+    _field: core::marker::PhantomData<F>,
+}
+
+#[cfg(feature = "mmap")]
+impl<F: PrimeField> MmapStore<F> {
+    /// Creates (or truncates) the file at `path` holding `len` zero elements
+    pub fn create<P: AsRef<Path>>(path: P, len: usize) -> std::io::Result<Self> {
+        let element_size = F::zero().uncompressed_size();
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(path.as_ref())?;
+        file.set_len((len * element_size).max(1) as u64)?;
+
+        // Safety: the file was just created by us and is only accessed
+        // through this mapping for the lifetime of the store.
+        let mut mmap = unsafe { MmapMut::map_mut(&file)? };
+
+        let mut zero = Vec::with_capacity(element_size);
+        F::zero().serialize_uncompressed(&mut zero).expect("writing to a Vec");
+        for chunk in mmap[..len * element_size].chunks_exact_mut(element_size) {
+            chunk.copy_from_slice(&zero);
+        }
+
+        Ok(Self {
+            mmap,
+            len,
+            element_size,
+            path: path.as_ref().to_path_buf(),
+            remove_on_drop: false,
+            _file: file,
+            _field: core::marker::PhantomData,
+        })
+    }
+
+    /// Creates a store in the system temp directory, deleted again on drop
+    pub fn temp(len: usize) -> std::io::Result<Self> {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+        let name = format!(
+            "plonk-store-{}-{}.bin",
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        );
+        let mut store = Self::create(std::env::temp_dir().join(name), len)?;
+        store.remove_on_drop = true;
+        Ok(store)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn flush(&self) -> std::io::Result<()> {
+        self.mmap.flush()
+    }
+
+    fn offset(&self, index: usize) -> usize {
+        assert!(index < self.len, "index {} out of bounds for store of length {}", index, self.len);
+        index * self.element_size
+    }
+}
+
+#[cfg(feature = "mmap")]
+impl<F: PrimeField> PolyStore<F> for MmapStore<F> {
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn get(&self, index: usize) -> F {
+        let offset = self.offset(index);
+        F::deserialize_uncompressed_unchecked(&self.mmap[offset..offset + self.element_size])
+            .expect("mmap store holds canonical field elements")
+    }
+
+    fn set(&mut self, index: usize, value: F) {
+        let offset = self.offset(index);
+        value
+            .serialize_uncompressed(&mut self.mmap[offset..offset + self.element_size])
+            .expect("element size is fixed");
+    }
+}
+
+#[cfg(feature = "mmap")]
+impl<F: PrimeField> Drop for MmapStore<F> {
+    fn drop(&mut self) {
+        if self.remove_on_drop {
+            // Temp stores hold prover intermediates; wipe them before the
+            // file is unlinked so they do not stay on disk
+            self.mmap.zeroize();
+            let _ = self.mmap.flush();
+            let _ = std::fs::remove_file(&self.path);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bls12_381::Fr as ScalarField;
+    use ark_std::UniformRand;
+
+    fn check_store<S: PolyStore<ScalarField>>(store: &mut S) {
+        let mut rng = ark_std::test_rng();
+        let values: Vec<ScalarField> = (0..store.len()).map(|_| ScalarField::rand(&mut rng)).collect();
+
+        store.write_chunk(0, &values);
+        assert_eq!(store.to_vec(), values);
+        assert_eq!(store.get(3), values[3]);
+
+        store.set(3, ScalarField::from(7u64));
+        let mut chunk = vec![ScalarField::from(0u64); 4];
+        store.read_chunk(2, &mut chunk);
+        assert_eq!(chunk, vec![values[2], ScalarField::from(7u64), values[4], values[5]]);
+    }
+
+    #[test]
+    fn test_memory_store() {
+        let mut store = MemoryStore::<ScalarField>::new(16);
+        assert_eq!(store.len(), 16);
+        check_store(&mut store);
+    }
+
+    #[cfg(feature = "mmap")]
+    #[test]
+    fn test_mmap_store() {
+        let mut store = MmapStore::<ScalarField>::temp(16).unwrap();
+        let path = store.path().to_path_buf();
+        assert!(path.exists());
+        assert!(store.to_vec().iter().all(|v| *v == ScalarField::from(0u64)));
+        check_store(&mut store);
+
+        drop(store);
+        assert!(!path.exists());
+    }
+}
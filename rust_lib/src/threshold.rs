@@ -0,0 +1,313 @@
+use alloc::{vec, vec::Vec};
+use ark_ec::hashing::curve_maps::wb::WBMap;
+use ark_ec::hashing::map_to_curve_hasher::MapToCurveBasedHasher;
+use ark_ec::hashing::HashToCurve;
+use ark_ec::pairing::Pairing;
+use ark_ec::{AffineRepr, CurveGroup, PrimeGroup};
+use ark_ff::field_hashers::DefaultFieldHasher;
+use ark_ff::PrimeField;
+use ark_serialize::CanonicalSerialize;
+use ark_std::rand::Rng;
+use ark_std::{UniformRand, Zero};
+use sha2::{Digest, Sha256};
+use zeroize::Zeroize;
+
+use ark_bls12_381::{Bls12_381, G1Affine, G1Projective, G2Affine, G2Projective};
+use ark_ed_on_bls12_381::{EdwardsAffine, EdwardsProjective, Fq, Fr as JubjubScalar};
+
+use crate::elgamal::Ciphertext;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ThresholdError {
+    /// Threshold outside 1..=n
+    Threshold { threshold: usize, n: usize },
+    /// The same share index given twice
+    DuplicateIndex(u64),
+}
+
+impl core::fmt::Display for ThresholdError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            ThresholdError::Threshold { threshold, n } => write!(f, "threshold {} is not in 1..={}", threshold, n),
+            ThresholdError::DuplicateIndex(index) => write!(f, "share index {} appears twice", index),
+        }
+    }
+}
+
+/// Shamir shares of `secret`: any `threshold` of the `n` shares recover it.
+/// Share `i` is f(i) for i = 1..=n.
+pub fn split_secret<F: PrimeField, R: Rng>(
+    secret: F,
+    threshold: usize,
+    n: usize,
+    rng: &mut R,
+) -> Result<Vec<(u64, F)>, ThresholdError> {
+    if threshold < 1 || threshold > n {
+        return Err(ThresholdError::Threshold { threshold, n });
+    }
+    let mut coeffs = vec![secret];
+    coeffs.extend((1..threshold).map(|_| F::rand(rng)));
+
+    let shares = (1..=n as u64)
+        .map(|i| {
+            let x = F::from(i);
+            let value = coeffs.iter().rev().fold(F::zero(), |acc, c| acc * x + c);
+            (i, value)
+        })
+        .collect();
+    coeffs.zeroize();
+    Ok(shares)
+}
+
+/// Lagrange coefficients at 0 for the given share indices, which must be
+/// distinct
+pub fn lagrange_at_zero<F: PrimeField>(indices: &[u64]) -> Result<Vec<F>, ThresholdError> {
+    if let Some(pos) = (1..indices.len()).find(|&pos| indices[..pos].contains(&indices[pos])) {
+        return Err(ThresholdError::DuplicateIndex(indices[pos]));
+    }
+    indices
+        .iter()
+        .map(|&i| {
+            let xi = F::from(i);
+            let (num, den) = indices.iter().filter(|&&j| j != i).fold((F::one(), F::one()), |(num, den), &j| {
+                let xj = F::from(j);
+                (num * xj, den * (xj - xi))
+            });
+            // Indices equal modulo a small field are duplicates too
+            den.inverse().map(|inv| num * inv).ok_or(ThresholdError::DuplicateIndex(i))
+        })
+        .collect()
+}
+
+/// Recovers the secret from at least `threshold` shares
+pub fn recover_secret<F: PrimeField>(shares: &[(u64, F)]) -> Result<F, ThresholdError> {
+    let indices: Vec<u64> = shares.iter().map(|(i, _)| *i).collect();
+    Ok(lagrange_at_zero::<F>(&indices)?
+        .iter()
+        .zip(shares)
+        .map(|(l, (_, s))| *l * s)
+        .sum())
+}
+
+/// One committee member's share of an ElGamal decryption key
+#[derive(Debug, Clone)]
+pub struct ElGamalKeyShare {
+    pub index: u64,
+    pub secret: JubjubScalar,
+    /// secret·G, published so partial decryptions can be checked
+    pub public: EdwardsAffine,
+}
+
+impl Drop for ElGamalKeyShare {
+    fn drop(&mut self) {
+        self.secret.zeroize();
+    }
+}
+
+/// sk_i·c1 together with a Chaum-Pedersen proof that the same sk_i was
+/// used as in the member's public share
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PartialDecryption {
+    pub index: u64,
+    pub point: EdwardsAffine,
+    challenge: JubjubScalar,
+    response: JubjubScalar,
+}
+
+/// Splits a fresh ElGamal key among `n` members; returns the joint public
+/// key to encrypt to and the members' shares
+pub fn deal_elgamal<R: Rng>(
+    threshold: usize,
+    n: usize,
+    rng: &mut R,
+) -> Result<(EdwardsAffine, Vec<ElGamalKeyShare>), ThresholdError> {
+    let mut secret = JubjubScalar::rand(rng);
+    let g = EdwardsProjective::generator();
+    let shares = split_secret(secret, threshold, n, rng)
+        .inspect_err(|_| secret.zeroize())?
+        .into_iter()
+        .map(|(index, secret)| ElGamalKeyShare {
+            index,
+            secret,
+            public: (g * secret).into_affine(),
+        })
+        .collect();
+    let public = (g * secret).into_affine();
+    secret.zeroize();
+    Ok((public, shares))
+}
+
+fn dleq_challenge(points: &[EdwardsAffine]) -> JubjubScalar {
+    let mut bytes = Vec::new();
+    for p in points {
+        p.serialize_compressed(&mut bytes).expect("writing to a Vec");
+    }
+    let mut hasher = Sha256::new();
+    hasher.update(b"plonk-threshold-dleq");
+    hasher.update(&bytes);
+    JubjubScalar::from_le_bytes_mod_order(&hasher.finalize())
+}
+
+impl ElGamalKeyShare {
+    pub fn partial_decrypt<R: Rng>(&self, ciphertext: &Ciphertext, rng: &mut R) -> PartialDecryption {
+        let g = EdwardsProjective::generator();
+        let point = (ciphertext.c1 * self.secret).into_affine();
+
+        // Chaum-Pedersen: log_G(public) == log_c1(point)
+        let k = JubjubScalar::rand(rng);
+        let a = (g * k).into_affine();
+        let b = (ciphertext.c1 * k).into_affine();
+        let challenge = dleq_challenge(&[g.into_affine(), self.public, ciphertext.c1, point, a, b]);
+        PartialDecryption {
+            index: self.index,
+            point,
+            challenge,
+            response: k + challenge * self.secret,
+        }
+    }
+}
+
+impl PartialDecryption {
+    pub fn verify(&self, public_share: &EdwardsAffine, ciphertext: &Ciphertext) -> bool {
+        let g = EdwardsProjective::generator();
+        let a = (g * self.response - *public_share * self.challenge).into_affine();
+        let b = (ciphertext.c1 * self.response - self.point * self.challenge).into_affine();
+        self.challenge == dleq_challenge(&[g.into_affine(), *public_share, ciphertext.c1, self.point, a, b])
+    }
+}
+
+/// Combines at least `threshold` partial decryptions into the plaintext
+pub fn combine_decryptions(ciphertext: &Ciphertext, partials: &[PartialDecryption]) -> Result<Fq, ThresholdError> {
+    let indices: Vec<u64> = partials.iter().map(|p| p.index).collect();
+    let shared = lagrange_at_zero::<JubjubScalar>(&indices)?
+        .iter()
+        .zip(partials)
+        .fold(EdwardsProjective::zero(), |acc, (l, p)| acc + p.point * l);
+    Ok(ciphertext.decrypt_with_shared(&shared.into_affine()))
+}
+
+type G1Hasher = MapToCurveBasedHasher<
+    G1Projective,
+    DefaultFieldHasher<Sha256, 128>,
+    WBMap<ark_bls12_381::g1::Config>,
+>;
+
+const BLS_DST: &[u8] = b"BLS_SIG_BLS12381G1_XMD:SHA-256_SSWU_RO_NUL_";
+
+/// Hash-to-G1 used for BLS signatures
+pub fn hash_to_g1(message: &[u8]) -> G1Affine {
+    G1Hasher::new(BLS_DST)
+        .expect("BLS12-381 G1 map parameters are valid")
+        .hash(message)
+        .expect("hash to curve cannot fail for BLS12-381 G1")
+}
+
+/// One committee member's share of a BLS signing key (signatures in G1,
+/// keys in G2)
+#[derive(Debug, Clone)]
+pub struct BlsKeyShare {
+    pub index: u64,
+    pub secret: ark_bls12_381::Fr,
+    pub public: G2Affine,
+}
+
+/// Splits a fresh BLS key among `n` members; returns the joint public key
+/// and the members' shares
+pub fn deal_bls<R: Rng>(threshold: usize, n: usize, rng: &mut R) -> Result<(G2Affine, Vec<BlsKeyShare>), ThresholdError> {
+    let mut secret = ark_bls12_381::Fr::rand(rng);
+    let g2 = G2Projective::generator();
+    let shares = split_secret(secret, threshold, n, rng)
+        .inspect_err(|_| secret.zeroize())?
+        .into_iter()
+        .map(|(index, secret)| BlsKeyShare {
+            index,
+            secret,
+            public: (g2 * secret).into_affine(),
+        })
+        .collect();
+    let public = (g2 * secret).into_affine();
+    secret.zeroize();
+    Ok((public, shares))
+}
+
+impl Drop for BlsKeyShare {
+    fn drop(&mut self) {
+        self.secret.zeroize();
+    }
+}
+
+impl BlsKeyShare {
+    pub fn sign(&self, message: &[u8]) -> (u64, G1Affine) {
+        (self.index, (hash_to_g1(message) * self.secret).into_affine())
+    }
+}
+
+/// e(signature, g2) == e(H(m), public)
+pub fn bls_verify(public: &G2Affine, message: &[u8], signature: &G1Affine) -> bool {
+    Bls12_381::multi_pairing(
+        [signature.into_group(), -hash_to_g1(message).into_group()],
+        [G2Affine::generator(), *public],
+    )
+    .is_zero()
+}
+
+/// Combines at least `threshold` partial signatures into a signature under
+/// the joint public key
+pub fn combine_signatures(partials: &[(u64, G1Affine)]) -> Result<G1Affine, ThresholdError> {
+    let indices: Vec<u64> = partials.iter().map(|(i, _)| *i).collect();
+    Ok(lagrange_at_zero::<ark_bls12_381::Fr>(&indices)?
+        .iter()
+        .zip(partials)
+        .fold(G1Projective::zero(), |acc, (l, (_, sig))| acc + *sig * l)
+        .into_affine())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::elgamal::encrypt;
+
+    #[test]
+    fn test_shamir() {
+        let mut rng = ark_std::test_rng();
+        let secret = ark_bls12_381::Fr::rand(&mut rng);
+        let shares = split_secret(secret, 3, 5, &mut rng).unwrap();
+        assert_eq!(recover_secret(&shares[..3]), Ok(secret));
+        assert_eq!(recover_secret(&[shares[4], shares[1], shares[2]]), Ok(secret));
+        assert_ne!(recover_secret(&shares[..2]), Ok(secret));
+        assert_eq!(recover_secret(&[shares[1], shares[1], shares[2]]), Err(ThresholdError::DuplicateIndex(2)));
+        assert_eq!(split_secret(secret, 6, 5, &mut rng), Err(ThresholdError::Threshold { threshold: 6, n: 5 }));
+        assert_eq!(split_secret(secret, 0, 5, &mut rng), Err(ThresholdError::Threshold { threshold: 0, n: 5 }));
+    }
+
+    #[test]
+    fn test_threshold_elgamal() {
+        let mut rng = ark_std::test_rng();
+        let (public, shares) = deal_elgamal(2, 3, &mut rng).unwrap();
+        let value = Fq::from(1234u64);
+        let ciphertext = encrypt(&public, value, &mut rng);
+
+        let partials: Vec<_> = [&shares[0], &shares[2]]
+            .iter()
+            .map(|s| s.partial_decrypt(&ciphertext, &mut rng))
+            .collect();
+        assert!(partials[0].verify(&shares[0].public, &ciphertext));
+        assert!(!partials[0].verify(&shares[1].public, &ciphertext));
+        assert_eq!(combine_decryptions(&ciphertext, &partials), Ok(value));
+        assert_ne!(combine_decryptions(&ciphertext, &partials[..1]), Ok(value));
+    }
+
+    #[test]
+    fn test_threshold_bls() {
+        let mut rng = ark_std::test_rng();
+        let (public, shares) = deal_bls(2, 3, &mut rng).unwrap();
+        let message = b"ciphertext 7 decrypted to 1234";
+
+        let partials = [shares[1].sign(message), shares[2].sign(message)];
+        assert!(bls_verify(&shares[1].public, message, &partials[0].1));
+
+        let signature = combine_signatures(&partials).unwrap();
+        assert!(bls_verify(&public, message, &signature));
+        assert!(!bls_verify(&public, b"another message", &signature));
+    }
+}
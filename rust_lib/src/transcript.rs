@@ -0,0 +1,256 @@
+use alloc::vec::Vec;
+use ark_ff::PrimeField;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, Compress, SerializationError, Valid, Validate};
+use sha2::{Digest, Sha256};
+
+use crate::poseidon::{permute, Arithmetic, Native, PoseidonParams, WIDTH};
+
+/// Hash a proof's Fiat-Shamir transcript runs over. SHA-256 is the
+/// default and the one the generated Solidity verifier replays. Poseidon
+/// costs a circuit a few hundred gates per permutation where SHA-256
+/// costs thousands per block, so proofs meant to be verified inside
+/// another circuit, by `gadgets::plonk`, are made with it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum TranscriptHash {
+    #[default]
+    Sha256,
+    Poseidon,
+}
+
+impl CanonicalSerialize for TranscriptHash {
+    fn serialize_with_mode<W: ark_serialize::Write>(&self, writer: W, compress: Compress) -> Result<(), SerializationError> {
+        (*self as u8).serialize_with_mode(writer, compress)
+    }
+
+    fn serialized_size(&self, _: Compress) -> usize {
+        1
+    }
+}
+
+impl Valid for TranscriptHash {
+    fn check(&self) -> Result<(), SerializationError> {
+        Ok(())
+    }
+}
+
+impl CanonicalDeserialize for TranscriptHash {
+    fn deserialize_with_mode<R: ark_serialize::Read>(reader: R, compress: Compress, validate: Validate) -> Result<Self, SerializationError> {
+        match u8::deserialize_with_mode(reader, compress, validate)? {
+            0 => Ok(TranscriptHash::Sha256),
+            1 => Ok(TranscriptHash::Poseidon),
+            _ => Err(SerializationError::InvalidData),
+        }
+    }
+}
+
+/// `bytes` as field elements of 31 little-endian bytes each, the last one
+/// shorter, so that every chunk is below the modulus of a field of at
+/// least 248 bits
+pub fn byte_chunks<F: PrimeField>(bytes: &[u8]) -> Vec<F> {
+    bytes.chunks(31).map(F::from_le_bytes_mod_order).collect()
+}
+
+/// Fiat-Shamir transcript over SHA-256. Commitments, field elements and
+/// anything else with a canonical encoding are absorbed under a label,
+/// and challenges are squeezed deterministically from everything absorbed
+/// so far. Each squeezed challenge is fed back into the state, so two
+/// squeezes in a row yield different values.
+#[derive(Clone)]
+pub struct Transcript {
+    hasher: Sha256,
+}
+
+impl Transcript {
+    /// Starts a transcript bound to a protocol `label`
+    pub fn new(label: &[u8]) -> Self {
+        let mut hasher = Sha256::new();
+        hasher.update(label);
+        Self { hasher }
+    }
+
+    /// Starts a transcript for one session of the protocol `label`. The
+    /// session id is absorbed length-prefixed, so distinct ids never
+    /// yield the same challenges, and each transcript owns its state:
+    /// sessions run concurrently share nothing.
+    pub fn for_session(label: &[u8], session: &[u8]) -> Self {
+        let mut transcript = Self::new(label);
+        transcript.absorb(b"session", session);
+        transcript
+    }
+
+    /// Hashes `label` followed by the compressed encoding of `item`
+    pub fn absorb<T: CanonicalSerialize + ?Sized>(&mut self, label: &[u8], item: &T) {
+        let mut bytes = Vec::new();
+        item.serialize_compressed(&mut bytes).expect("writing to a Vec");
+        self.hasher.update(label);
+        self.hasher.update(&bytes);
+    }
+
+    /// Challenge derived from the state and `label`, reduced into `F`
+    pub fn squeeze<F: PrimeField>(&mut self, label: &[u8]) -> F {
+        let mut hasher = self.hasher.clone();
+        hasher.update(label);
+        let out = hasher.finalize();
+        self.hasher.update(out);
+        F::from_le_bytes_mod_order(&out)
+    }
+}
+
+/// Fiat-Shamir transcript over a Poseidon sponge, for protocols that are
+/// verified inside a circuit. It is written once against `Arithmetic`, so
+/// `PoseidonTranscript<F, Native>` and the gadget
+/// `PoseidonTranscript<F, CircuitArithmetic<F>>` derive the same challenges
+/// from the same absorbed values.
+///
+/// Labels are absorbed as field elements ahead of the values they tag, and
+/// squeezing absorbs its label, permutes and returns the first rate element.
+pub struct PoseidonTranscript<F: PrimeField, A: Arithmetic<F>> {
+    params: PoseidonParams<F>,
+    arith: A,
+    state: [A::Value; WIDTH],
+    position: usize,
+}
+
+impl<F: PrimeField, A: Arithmetic<F>> PoseidonTranscript<F, A> {
+    /// Starts a transcript bound to a protocol `label`, which becomes the
+    /// capacity element
+    pub fn new(mut arith: A, label: &[u8]) -> Self {
+        let capacity = arith.constant(F::from_le_bytes_mod_order(label));
+        let zero = arith.constant(F::zero());
+        Self {
+            params: PoseidonParams::new(),
+            arith,
+            state: [capacity, zero.clone(), zero],
+            position: 0,
+        }
+    }
+
+    fn element(&mut self, value: &A::Value) {
+        if self.position == WIDTH - 1 {
+            permute(&self.params, &mut self.arith, &mut self.state);
+            self.position = 0;
+        }
+        let slot = 1 + self.position;
+        self.state[slot] = self.arith.add(&self.state[slot], value);
+        self.position += 1;
+    }
+
+    fn label(&mut self, label: &[u8]) {
+        let label = self.arith.constant(F::from_le_bytes_mod_order(label));
+        self.element(&label);
+    }
+
+    /// Absorbs `label` followed by `values`
+    pub fn absorb(&mut self, label: &[u8], values: &[A::Value]) {
+        self.label(label);
+        for value in values {
+            self.element(value);
+        }
+    }
+
+    /// Absorbs `label` followed by `bytes` as `byte_chunks`
+    pub fn absorb_bytes(&mut self, label: &[u8], bytes: &[u8]) {
+        let chunks: Vec<A::Value> = byte_chunks(bytes).into_iter().map(|x| self.arith.constant(x)).collect();
+        self.absorb(label, &chunks);
+    }
+
+    /// Challenge derived from everything absorbed and `label`
+    pub fn squeeze(&mut self, label: &[u8]) -> A::Value {
+        self.label(label);
+        permute(&self.params, &mut self.arith, &mut self.state);
+        self.position = 0;
+        self.state[1].clone()
+    }
+
+    pub fn arithmetic(&mut self) -> &mut A {
+        &mut self.arith
+    }
+
+    pub fn into_inner(self) -> A {
+        self.arith
+    }
+}
+
+impl<F: PrimeField> PoseidonTranscript<F, Native> {
+    /// The transcript carried on in `arith`, its state entering as
+    /// constants: whatever was absorbed natively costs a circuit nothing,
+    /// and the challenges come out as if it had all been absorbed there
+    pub fn continue_in<A: Arithmetic<F>>(self, mut arith: A) -> PoseidonTranscript<F, A> {
+        let state = self.state.map(|x| arith.constant(x));
+        PoseidonTranscript { params: self.params, arith, state, position: self.position }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bls12_381::{Fr, G1Affine};
+    use ark_ec::AffineRepr;
+
+    #[test]
+    fn test_challenges_depend_on_everything_absorbed() {
+        let run = |label: &[u8], value: u64| {
+            let mut transcript = Transcript::new(b"test");
+            transcript.absorb(b"commitment", &G1Affine::generator());
+            transcript.absorb(label, &Fr::from(value));
+            let first: Fr = transcript.squeeze(b"beta");
+            let second: Fr = transcript.squeeze(b"beta");
+            (first, second)
+        };
+
+        let (beta, next) = run(b"value", 1);
+        assert_eq!(run(b"value", 1), (beta, next));
+        assert_ne!(beta, next);
+        assert_ne!(run(b"value", 2).0, beta);
+        assert_ne!(run(b"other", 1).0, beta);
+    }
+
+    #[test]
+    fn test_sessions_are_independent() {
+        let challenge = |session: &[u8]| {
+            let mut transcript = Transcript::for_session(b"test", session);
+            transcript.absorb(b"value", &Fr::from(1u64));
+            transcript.squeeze::<Fr>(b"beta")
+        };
+        assert_ne!(challenge(b"a"), challenge(b"b"));
+        assert_ne!(challenge(b""), challenge(b"a"));
+
+        // Interleaved use of many sessions across threads gives what each
+        // gives alone
+        let alone: Vec<Fr> = (0..8u8).map(|i| challenge(&[i])).collect();
+        let together: Vec<Fr> = std::thread::scope(|scope| {
+            let handles: Vec<_> = (0..8u8).map(|i| scope.spawn(move || challenge(&[i]))).collect();
+            handles.into_iter().map(|handle| handle.join().unwrap()).collect()
+        });
+        assert_eq!(together, alone);
+    }
+
+    #[test]
+    fn test_poseidon_gadget_matches_native() {
+        use crate::poseidon::{CircuitArithmetic, Native};
+
+        let values = [3u64, 5, 7].map(Fr::from);
+        let mut native = PoseidonTranscript::new(Native, b"test");
+        native.absorb(b"commitment", &values);
+        let beta: Fr = native.squeeze(b"beta");
+        let gamma: Fr = native.squeeze(b"gamma");
+        assert_ne!(beta, gamma);
+
+        let mut gadget = PoseidonTranscript::new(CircuitArithmetic::new(), b"test");
+        let cells = values.map(|x| gadget.arithmetic().input(x));
+        gadget.absorb(b"commitment", &cells);
+        assert_eq!(gadget.squeeze(b"beta").value, beta);
+        assert_eq!(gadget.squeeze(b"gamma").value, gamma);
+
+        #[cfg(feature = "prover")]
+        {
+            use ark_std::Zero;
+
+            let mut circuit = gadget.into_inner().into_circuit().unwrap();
+            assert!(circuit.is_satisfied());
+            let last = circuit.c.iter().rposition(|x| !x.is_zero()).unwrap();
+            circuit.c[last] += Fr::from(1u64);
+            assert!(!circuit.is_satisfied());
+        }
+    }
+}
@@ -0,0 +1,137 @@
+use alloc::vec::Vec;
+use ark_ec::{AffineRepr, CurveGroup};
+use ark_ff::{FftField, Field};
+
+use crate::fft::DomainError;
+use crate::kgz::{CommitError, KZGParams};
+use crate::msm::{msm, CurvePairing, MsmConfig};
+
+/// KZG commitments to vectors of `size` values, each committed as the
+/// polynomial taking value i at ω^i. Keeping the SRS in the Lagrange basis,
+/// [L_i(s)]₁, turns committing into one MSM over the values and makes a
+/// commitment linear in each entry, so changing one entry is one scalar
+/// multiplication rather than a recommit.
+pub struct VectorCommitment<E: CurvePairing> {
+    pub lagrange_basis: Vec<E::G1Affine>,
+}
+
+impl<E: CurvePairing> VectorCommitment<E> {
+    /// Lagrange basis of the order-`size` subgroup, an inverse FFT over the
+    /// first `size` powers of g
+    pub fn new(params: &KZGParams<E>, size: usize) -> Result<Self, CommitError> {
+        if !size.is_power_of_two() {
+            return Err(DomainError::NotPowerOfTwo(size).into());
+        }
+        if params.powers_of_g.len() < size {
+            return Err(CommitError::SrsTooSmall { needed: size, powers: params.powers_of_g.len() });
+        }
+
+        let omega = E::ScalarField::get_root_of_unity(size as u64).ok_or(DomainError::NoSubgroup(size))?;
+        let mut basis: Vec<E::G1> = params.powers_of_g[..size].iter().map(|p| p.into_group()).collect();
+        // ω⁻¹ = ω^(size−1)
+        group_fft(&mut basis, omega.pow([size as u64 - 1]));
+        let size_inv = E::ScalarField::from(size as u64).inverse().ok_or(DomainError::NoSubgroup(size))?;
+        for point in basis.iter_mut() {
+            *point *= size_inv;
+        }
+        Ok(Self {
+            lagrange_basis: E::G1::normalize_batch(&basis),
+        })
+    }
+
+    pub fn size(&self) -> usize {
+        self.lagrange_basis.len()
+    }
+
+    pub fn commit(&self, values: &[E::ScalarField]) -> Result<E::G1Affine, CommitError> {
+        if values.len() > self.size() {
+            return Err(CommitError::SrsTooSmall { needed: values.len(), powers: self.size() });
+        }
+        Ok(msm::<E::G1>(&self.lagrange_basis[..values.len()], values, &MsmConfig::default()).expect("basis sliced to the values").into_affine())
+    }
+
+    /// `commitment` with entry `index` changed from `old` to `new`:
+    /// C + (new − old)·[L_index(s)]₁
+    pub fn update(
+        &self,
+        commitment: &E::G1Affine,
+        index: usize,
+        old: E::ScalarField,
+        new: E::ScalarField,
+    ) -> Result<E::G1Affine, CommitError> {
+        let basis = self.lagrange_basis.get(index).ok_or(CommitError::SrsTooSmall { needed: index + 1, powers: self.size() })?;
+        Ok((commitment.into_group() + *basis * (new - old)).into_affine())
+    }
+}
+
+/// `fft` with group elements as coefficients
+fn group_fft<G: CurveGroup>(points: &mut [G], omega: G::ScalarField) {
+    let n = points.len();
+    let bits = n.trailing_zeros();
+    for i in 0..n {
+        let j = if bits == 0 { 0 } else { i.reverse_bits() >> (usize::BITS - bits) };
+        if i < j {
+            points.swap(i, j);
+        }
+    }
+
+    let mut m = 1;
+    while m < n {
+        let half_m = m;
+        m *= 2;
+        let w_m = omega.pow([(n / m) as u64]);
+        for k in (0..n).step_by(m) {
+            let mut w = G::ScalarField::from(1u64);
+            for j in 0..half_m {
+                let t = points[k + j + half_m] * w;
+                points[k + j + half_m] = points[k + j] - t;
+                points[k + j] += t;
+                w *= w_m;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bls12_381::{Bls12_381, Fr};
+    use ark_poly::univariate::DensePolynomial;
+    use ark_poly::DenseUVPolynomial;
+    use ark_std::UniformRand;
+
+    #[test]
+    fn test_commit_and_update() {
+        let mut rng = ark_std::test_rng();
+        let params = KZGParams::<Bls12_381>::setup(8, &mut rng);
+        let vc = VectorCommitment::new(&params, 8).unwrap();
+        let omega = Fr::get_root_of_unity(8).unwrap();
+
+        let mut values: Vec<Fr> = (0..8).map(|_| Fr::rand(&mut rng)).collect();
+        let commit_poly = |values: &[Fr]| {
+            let poly = crate::fft::interpolate(values, &(0..8).map(|i| omega.pow([i])).collect::<Vec<_>>()).unwrap();
+            (params.commit(&poly).unwrap(), poly)
+        };
+        let mut commitment = vc.commit(&values).unwrap();
+        assert_eq!(commitment, commit_poly(&values).0);
+
+        for (index, new) in [(3, Fr::from(7u64)), (0, Fr::rand(&mut rng)), (7, Fr::from(0u64))] {
+            commitment = vc.update(&commitment, index, values[index], new).unwrap();
+            values[index] = new;
+            assert_eq!(commitment, commit_poly(&values).0);
+        }
+
+        // Entry i opens at ω^i
+        let (_, poly) = commit_poly(&values);
+        let (proof, value) = params.open(&poly, omega.pow([3])).unwrap();
+        assert_eq!(value, Fr::from(7u64));
+        assert!(params.verify(&commitment, &proof, omega.pow([3]), value));
+
+        let short = VectorCommitment::new(&params, 1).unwrap();
+        let constant = DensePolynomial::from_coefficients_vec(vec![Fr::from(9u64)]);
+        assert_eq!(short.commit(&[Fr::from(9u64)]), params.commit(&constant));
+        assert_eq!(short.commit(&values), Err(CommitError::SrsTooSmall { needed: 8, powers: 1 }));
+        assert!(VectorCommitment::new(&params, 6).is_err());
+        assert!(VectorCommitment::new(&params, 16).is_err());
+    }
+}
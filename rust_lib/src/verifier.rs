@@ -0,0 +1,656 @@
+use alloc::{collections::BTreeMap, vec::Vec};
+use ark_ec::pairing::Pairing;
+use ark_ec::AffineRepr;
+use ark_ff::{batch_inversion, FftField, Field};
+use ark_serialize::CanonicalSerialize;
+use ark_std::rand::Rng;
+use ark_std::{One, UniformRand, Zero};
+
+use crate::kgz::{check_opening, KZGParams};
+use crate::msm::{curve_mul, CurvePairing, FixedBaseTable};
+use crate::linearization::{constant_term, linear_terms, EvaluationPoint};
+use crate::pcs::{Opening, PolynomialCommitment};
+use crate::proof::{challenges, Oracle, Proof, ScheduleError, VerifyingKey, OPENED_AT_ZETA, OPENED_AT_ZETA_OMEGA};
+use crate::transcript::{Transcript, TranscriptHash};
+
+/// Protocol label of the transcript `Verifier::verify_batch` draws its
+/// scalars from
+const BATCH_LABEL: &[u8] = b"plonk-batch-verify-v1";
+
+/// Window width for the fixed-base tables, 2^8 points per window
+pub const DEFAULT_TABLE_WINDOW: usize = 8;
+
+/// Verifier-side key with window tables for every base the verifier keeps
+/// multiplying (the generators and fixed commitments such as selectors),
+/// so each of those multiplications costs only additions.
+pub struct PreparedVerifyingKey<E: CurvePairing> {
+    pub g1: E::G1Affine,
+    pub g2: E::G2Affine,
+    pub g2_s: E::G2Affine,
+    pub fixed_commitments: Vec<E::G1Affine>,
+    g1_table: FixedBaseTable<E::G1>,
+    g2_table: FixedBaseTable<E::G2>,
+    commitment_tables: Vec<FixedBaseTable<E::G1>>,
+}
+
+impl<E: CurvePairing> PreparedVerifyingKey<E> {
+    pub fn new(params: &KZGParams<E>, fixed_commitments: &[E::G1Affine]) -> Self {
+        Self::with_window(params, fixed_commitments, DEFAULT_TABLE_WINDOW)
+    }
+
+    pub fn with_window(params: &KZGParams<E>, fixed_commitments: &[E::G1Affine], window_bits: usize) -> Self {
+        // An empty SRS leaves g1 at the identity, which rejects every proof
+        let g1 = params.powers_of_g.first().copied().unwrap_or_else(E::G1Affine::zero);
+        Self {
+            g1,
+            g2: params.g2,
+            g2_s: params.g2_s,
+            fixed_commitments: fixed_commitments.to_vec(),
+            g1_table: FixedBaseTable::new(g1.into_group(), window_bits),
+            g2_table: FixedBaseTable::new(params.g2.into_group(), window_bits),
+            commitment_tables: fixed_commitments
+                .iter()
+                .map(|c| FixedBaseTable::new(c.into_group(), window_bits))
+                .collect(),
+        }
+    }
+
+    /// `[scalar]₁`
+    pub fn mul_g1(&self, scalar: E::ScalarField) -> E::G1 {
+        self.g1_table.mul(scalar)
+    }
+
+    /// `[scalar]₂`
+    pub fn mul_g2(&self, scalar: E::ScalarField) -> E::G2 {
+        self.g2_table.mul(scalar)
+    }
+
+    /// `scalar * fixed_commitments[index]`
+    pub fn mul_commitment(&self, index: usize, scalar: E::ScalarField) -> E::G1 {
+        self.commitment_tables[index].mul(scalar)
+    }
+
+    /// KZG opening check with the g1/g2 multiplications taken from the tables
+    pub fn verify_opening(
+        &self,
+        commitment: &E::G1Affine,
+        proof: &E::G1Affine,
+        z: E::ScalarField,
+        value: E::ScalarField,
+    ) -> bool {
+        check_opening::<E>(&self.g2, &self.g2_s, commitment, proof, self.mul_g1(value), self.mul_g2(z))
+    }
+
+    /// `verify_opening` through `cache`: an opening already accepted for
+    /// the same (commitment, point) is answered without pairings, and the
+    /// prepared [s − z]₂ is reused across commitments opened at z
+    pub fn verify_opening_cached(
+        &self,
+        cache: &mut OpeningCache<E>,
+        commitment: &E::G1Affine,
+        proof: &E::G1Affine,
+        z: E::ScalarField,
+        value: E::ScalarField,
+    ) -> bool {
+        let point = encode(&z);
+        let key = (encode(commitment), point.clone());
+        if cache.openings.get(&key) == Some(&(value, *proof)) {
+            cache.hits += 1;
+            return true;
+        }
+        cache.make_room();
+
+        let g2 = cache.g2.get_or_insert_with(|| E::G2Prepared::from(self.g2)).clone();
+        let s_minus_z = cache
+            .points
+            .entry(point)
+            .or_insert_with(|| E::G2Prepared::from(self.g2_s.into_group() - self.mul_g2(z)))
+            .clone();
+        let value_minus_commitment = self.mul_g1(value) - commitment.into_group();
+        let valid = E::multi_pairing([proof.into_group(), value_minus_commitment], [s_minus_z, g2]).is_zero();
+        if valid {
+            cache.openings.insert(key, (value, *proof));
+        }
+        valid
+    }
+}
+
+fn encode<T: CanonicalSerialize>(item: &T) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    // Writing to a Vec cannot fail, and the points and field elements
+    // encoded here serialize every value they can hold
+    item.serialize_compressed(&mut bytes).expect("writing to a Vec");
+    bytes
+}
+
+/// Encoded (commitment, point)
+type OpeningKey = (Vec<u8>, Vec<u8>);
+
+/// Memo for services verifying streams of proofs that open the same
+/// preprocessed commitments at the same points. Keeps the prepared G2
+/// inputs per point and the accepted opening per (commitment, point);
+/// rejected openings are never stored. Once `max_entries` is reached the
+/// cache is emptied and refills from the stream.
+pub struct OpeningCache<E: CurvePairing> {
+    max_entries: usize,
+    g2: Option<E::G2Prepared>,
+    points: BTreeMap<Vec<u8>, E::G2Prepared>,
+    openings: BTreeMap<OpeningKey, (E::ScalarField, E::G1Affine)>,
+    hits: u64,
+}
+
+impl<E: CurvePairing> OpeningCache<E> {
+    pub fn new(max_entries: usize) -> Self {
+        Self {
+            max_entries,
+            g2: None,
+            points: BTreeMap::new(),
+            openings: BTreeMap::new(),
+            hits: 0,
+        }
+    }
+
+    /// Accepted openings currently stored
+    pub fn len(&self) -> usize {
+        self.openings.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.openings.is_empty()
+    }
+
+    /// Verifications answered from the cache so far
+    pub fn hits(&self) -> u64 {
+        self.hits
+    }
+
+    pub fn clear(&mut self) {
+        self.points.clear();
+        self.openings.clear();
+    }
+
+    fn make_room(&mut self) {
+        if self.openings.len() >= self.max_entries || self.points.len() >= self.max_entries {
+            self.clear();
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VerifyError {
+    /// The key's domain size is not a power of two the field supports
+    MalformedKey,
+    /// More public inputs than the circuit has rows
+    TooManyPublicInputs { count: usize, rows: u64 },
+    /// The statement does not have one value per public row of the key
+    PublicInputCount { expected: usize, got: usize },
+    /// ζ landed in the evaluation domain, where Z_H vanishes
+    DegenerateChallenge,
+    /// The gate and permutation identity does not hold at ζ
+    QuotientMismatch,
+    /// The openings at ζ and ζω do not match the commitments
+    InvalidOpening,
+    /// The verifier's transcript left the proof schedule
+    Schedule(ScheduleError),
+}
+
+impl core::fmt::Display for VerifyError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            VerifyError::MalformedKey => write!(f, "malformed verifying key"),
+            VerifyError::TooManyPublicInputs { count, rows } => {
+                write!(f, "{} public inputs for a circuit of {} rows", count, rows)
+            }
+            VerifyError::PublicInputCount { expected, got } => {
+                write!(f, "expected {} public inputs, got {}", expected, got)
+            }
+            VerifyError::DegenerateChallenge => write!(f, "evaluation challenge lies in the domain"),
+            VerifyError::QuotientMismatch => write!(f, "quotient identity does not hold"),
+            VerifyError::InvalidOpening => write!(f, "opening proof does not verify"),
+            VerifyError::Schedule(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+/// One proof of a batch: its circuit's key, the session it was made in
+/// (empty for none, as in `verify`), its statement and the proof
+pub type BatchItem<'a, E> = (&'a VerifyingKey<E>, &'a [u8], &'a [<E as Pairing>::ScalarField], &'a Proof<E>);
+
+/// A proof's batched openings at ζ and ζω, and the challenge u
+type Openings<'a, E, S> = ([Opening<'a, <E as Pairing>::ScalarField, S>; 2], <E as Pairing>::ScalarField);
+
+/// Proofs over one SRS, as (g2, g2_s, Σ r·L, Σ r·R)
+type SrsGroup<E> = (<E as Pairing>::G2Affine, <E as Pairing>::G2Affine, <E as Pairing>::G1, <E as Pairing>::G1);
+
+/// Why `Verifier::verify_batch` or `Verifier::verify_batch_with_rng`
+/// rejected a batch
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BatchError {
+    /// Proof `index` failed a check made before the pairings
+    Proof { index: usize, error: VerifyError },
+    /// The combined pairing check failed, so at least one proof has an
+    /// invalid opening; verify them one by one to find it
+    InvalidOpening,
+}
+
+impl core::fmt::Display for BatchError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            BatchError::Proof { index, error } => write!(f, "proof {}: {}", index, error),
+            BatchError::InvalidOpening => write!(f, "batched opening proofs do not verify"),
+        }
+    }
+}
+
+pub struct Verifier;
+
+impl Verifier {
+    /// Checks `proof` for the circuit of `vk` and the statement
+    /// `public_inputs`: first the quotient identity at ζ on the claimed
+    /// evaluations, then the openings through the key's scheme, which for
+    /// KZG is a single pairing equation
+    pub fn verify<E: CurvePairing, S: PolynomialCommitment<E::ScalarField>>(
+        vk: &VerifyingKey<E, S>,
+        public_inputs: &[E::ScalarField],
+        proof: &Proof<E, S>,
+    ) -> Result<(), VerifyError> {
+        Self::verify_in_session(vk, &[], public_inputs, proof)
+    }
+
+    /// `verify` for a proof bound to `session` through
+    /// `ProverConfig::session`. A proof made in another session, or in
+    /// none, is rejected.
+    pub fn verify_in_session<E: CurvePairing, S: PolynomialCommitment<E::ScalarField>>(
+        vk: &VerifyingKey<E, S>,
+        session: &[u8],
+        public_inputs: &[E::ScalarField],
+        proof: &Proof<E, S>,
+    ) -> Result<(), VerifyError> {
+        Self::verify_with_transcript(vk, session, TranscriptHash::Sha256, public_inputs, proof)
+    }
+
+    /// `verify_in_session` for a proof whose transcript runs over `hash`,
+    /// as `ProverConfig::transcript` chose it
+    pub fn verify_with_transcript<E: CurvePairing, S: PolynomialCommitment<E::ScalarField>>(
+        vk: &VerifyingKey<E, S>,
+        session: &[u8],
+        hash: TranscriptHash,
+        public_inputs: &[E::ScalarField],
+        proof: &Proof<E, S>,
+    ) -> Result<(), VerifyError> {
+        let (openings, u) = Self::openings(vk, session, hash, public_inputs, proof)?;
+        if S::verify_openings(&vk.opening_key, &openings, u) {
+            Ok(())
+        } else {
+            Err(VerifyError::InvalidOpening)
+        }
+    }
+
+    /// Checks proofs of any number of circuits on the same curve, each
+    /// item being a key, a session, a statement and a proof. Every proof gets its own
+    /// checks up to the final pairing equation e(L_i, [s]) = e(R_i, [1]);
+    /// those are then combined with random scalars r_i into
+    ///
+    ///   e(Σ r_i·L_i, [s]) = e(Σ r_i·R_i, [1])
+    ///
+    /// per distinct SRS, all in one multi-pairing. A batch holding an
+    /// invalid proof passes only with negligible probability over the r_i.
+    ///
+    /// Each r_i is squeezed from a transcript of every key, session,
+    /// statement and proof in the batch, so no proof can be chosen knowing its scalar.
+    /// The pairings shrink from two per proof to two per SRS.
+    pub fn verify_batch<E: CurvePairing>(items: &[BatchItem<E>]) -> Result<(), BatchError> {
+        let mut transcript = Transcript::new(BATCH_LABEL);
+        transcript.absorb(b"items", &(items.len() as u64));
+        for (vk, session, public_inputs, proof) in items {
+            transcript.absorb(b"verifying_key", *vk);
+            transcript.absorb(b"session", *session);
+            transcript.absorb(b"public_inputs", *public_inputs);
+            transcript.absorb(b"proof", *proof);
+        }
+        Self::combine(items, || transcript.squeeze(b"r"))
+    }
+
+    /// `verify_batch` with the r_i drawn from `rng` instead of the batch
+    /// transcript
+    pub fn verify_batch_with_rng<E: CurvePairing, R: Rng>(
+        items: &[BatchItem<E>],
+        rng: &mut R,
+    ) -> Result<(), BatchError> {
+        Self::combine(items, || E::ScalarField::rand(rng))
+    }
+
+    /// Runs every check but the pairing on each item, then the pairing
+    /// equations weighted by `scalar()` per item, one multi-pairing for all
+    fn combine<E: CurvePairing>(
+        items: &[BatchItem<E>],
+        mut scalar: impl FnMut() -> E::ScalarField,
+    ) -> Result<(), BatchError> {
+        let mut groups: Vec<SrsGroup<E>> = Vec::new();
+        for (index, (vk, session, public_inputs, proof)) in items.iter().enumerate() {
+            let (lhs, rhs) =
+                Self::pairing_inputs(vk, session, public_inputs, proof).map_err(|error| BatchError::Proof { index, error })?;
+            let r = scalar();
+            let key = &vk.opening_key;
+            match groups.iter_mut().find(|group| group.0 == key.g2 && group.1 == key.g2_s) {
+                Some(group) => {
+                    group.2 += curve_mul(lhs, r);
+                    group.3 += curve_mul(rhs, r);
+                }
+                None => groups.push((key.g2, key.g2_s, curve_mul(lhs, r), curve_mul(rhs, r))),
+            }
+        }
+
+        let g1 = groups.iter().flat_map(|group| [group.2, -group.3]);
+        let g2 = groups.iter().flat_map(|group| [group.1, group.0]);
+        if E::multi_pairing(g1, g2).is_zero() {
+            Ok(())
+        } else {
+            Err(BatchError::InvalidOpening)
+        }
+    }
+
+    /// Every check of `verify` but the last, returning L and R of the
+    /// opening check e(L, [s]) = e(R, [1])
+    pub(crate) fn pairing_inputs<E: CurvePairing>(
+        vk: &VerifyingKey<E>,
+        session: &[u8],
+        public_inputs: &[E::ScalarField],
+        proof: &Proof<E>,
+    ) -> Result<(E::G1, E::G1), VerifyError> {
+        let (openings, u) = Self::openings(vk, session, TranscriptHash::Sha256, public_inputs, proof)?;
+        Ok(vk.opening_key.fold(&openings, u))
+    }
+
+    /// Every check of `verify` but the openings, which it returns with the
+    /// challenge u a scheme may fold them with
+    fn openings<'a, E: CurvePairing, S: PolynomialCommitment<E::ScalarField>>(
+        vk: &VerifyingKey<E, S>,
+        session: &[u8],
+        hash: TranscriptHash,
+        public_inputs: &[E::ScalarField],
+        proof: &'a Proof<E, S>,
+    ) -> Result<Openings<'a, E, S>, VerifyError> {
+        let n = vk.domain_size;
+        if n < 2 || !n.is_power_of_two() {
+            return Err(VerifyError::MalformedKey);
+        }
+        let omega = E::ScalarField::get_root_of_unity(n).ok_or(VerifyError::MalformedKey)?;
+        if public_inputs.len() as u64 > n {
+            return Err(VerifyError::TooManyPublicInputs { count: public_inputs.len(), rows: n });
+        }
+        if public_inputs.len() != vk.public_rows.len() {
+            return Err(VerifyError::PublicInputCount { expected: vk.public_rows.len(), got: public_inputs.len() });
+        }
+        if !vk.is_consistent() {
+            return Err(VerifyError::MalformedKey);
+        }
+
+        let ch = challenges(vk, session, hash, public_inputs, proof).map_err(VerifyError::Schedule)?;
+        let one = E::ScalarField::one();
+        let point = EvaluationPoint::new(n, omega, [ch.theta, ch.beta, ch.gamma, ch.alpha], ch.zeta);
+        if point.vanishing.is_zero() {
+            return Err(VerifyError::DegenerateChallenge);
+        }
+
+        // PI(ζ) = −Σ x_j·L_{i_j}(ζ), L_i(ζ) = ω^i·Z_H(ζ) / (n·(ζ − ω^i))
+        // over the public input rows i_j
+        let rows: Vec<E::ScalarField> = vk.public_rows.iter().map(|row| omega.pow([*row])).collect();
+        let mut lagrange: Vec<E::ScalarField> = rows.iter().map(|w| E::ScalarField::from(n) * (ch.zeta - w)).collect();
+        batch_inversion(&mut lagrange);
+        let pi: E::ScalarField = public_inputs.iter().zip(&lagrange).zip(&rows).map(|((x, l), w)| -*x * l * w * point.vanishing).sum();
+
+        // The quotient identity at ζ, r̄ + r₀ = 0
+        let e = &proof.evaluations;
+        if e.linearization + constant_term(e, &point, pi) != E::ScalarField::zero() {
+            return Err(VerifyError::QuotientMismatch);
+        }
+
+        // F = [r] + Σ v^(i+1)·[f_i] opens to y = r̄ + Σ v^(i+1)·f_i(ζ) at ζ
+        // over the polynomials opened there, [r] = Σ s·[p] over the linear
+        // terms, and F' = Σ v^i·[f'_i] to y' likewise at ζω
+        let batch = |mut terms: Vec<(Oracle, E::ScalarField)>, mut value: E::ScalarField, mut power: E::ScalarField, oracles: &[Oracle], evals: &[E::ScalarField]| {
+            for (oracle, eval) in oracles.iter().zip(evals) {
+                terms.push((*oracle, power));
+                value += power * eval;
+                power *= ch.v;
+            }
+            let (commitments, scalars): (Vec<_>, Vec<_>) = terms.into_iter().map(|(oracle, s)| (proof.commitment(vk, oracle), s)).unzip();
+            (S::combine(&commitments, &scalars), value)
+        };
+        let (at_zeta, y) = batch(linear_terms(vk, e, &point), e.linearization, ch.v, &OPENED_AT_ZETA, &e.at_zeta());
+        let (at_zeta_omega, y_omega) = batch(Vec::new(), E::ScalarField::zero(), one, &OPENED_AT_ZETA_OMEGA, &e.at_zeta_omega());
+        let openings = [
+            Opening { commitment: at_zeta, proof: &proof.w_zeta, z: ch.zeta, value: y },
+            Opening { commitment: at_zeta_omega, proof: &proof.w_zeta_omega, z: ch.zeta * omega, value: y_omega },
+        ];
+        Ok((openings, ch.u))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "prover")]
+    use crate::prover::fixtures::public_circuit;
+    use ark_bls12_381::{Bls12_381, Fr as ScalarField};
+    use ark_poly::polynomial::univariate::DensePolynomial;
+    use ark_poly::DenseUVPolynomial;
+    use ark_std::{One, UniformRand};
+
+    #[test]
+    fn test_prepared_verifying_key() {
+        let mut rng = ark_std::test_rng();
+        let params: KZGParams<Bls12_381> = KZGParams::setup(7, &mut rng);
+        let poly = DensePolynomial::<ScalarField>::rand(7, &mut rng);
+        let commitment = params.commit(&poly).unwrap();
+
+        let pvk = PreparedVerifyingKey::with_window(&params, &[commitment], 4);
+        let s = ScalarField::rand(&mut rng);
+        assert_eq!(pvk.mul_g1(s), params.powers_of_g[0] * s);
+        assert_eq!(pvk.mul_g2(s), params.g2 * s);
+        assert_eq!(pvk.mul_commitment(0, s), commitment * s);
+
+        let z = ScalarField::rand(&mut rng);
+        let (proof, value) = params.open(&poly, z).unwrap();
+        assert!(pvk.verify_opening(&commitment, &proof, z, value));
+        assert!(!pvk.verify_opening(&commitment, &proof, z, value + ScalarField::one()));
+    }
+
+    #[test]
+    fn test_opening_cache() {
+        let mut rng = ark_std::test_rng();
+        let params: KZGParams<Bls12_381> = KZGParams::setup(7, &mut rng);
+        let polys: Vec<_> = (0..3).map(|_| DensePolynomial::<ScalarField>::rand(7, &mut rng)).collect();
+        let commitments: Vec<_> = polys.iter().map(|p| params.commit(p).unwrap()).collect();
+        let pvk = PreparedVerifyingKey::with_window(&params, &commitments, 4);
+        let mut cache = OpeningCache::new(2);
+
+        let z = ScalarField::rand(&mut rng);
+        let (proof, value) = params.open(&polys[0], z).unwrap();
+        assert!(pvk.verify_opening_cached(&mut cache, &commitments[0], &proof, z, value));
+        assert!(pvk.verify_opening_cached(&mut cache, &commitments[0], &proof, z, value));
+        assert_eq!((cache.len(), cache.hits()), (1, 1));
+
+        // Same (commitment, point) with another claimed value is rechecked
+        assert!(!pvk.verify_opening_cached(&mut cache, &commitments[0], &proof, z, value + ScalarField::one()));
+        assert_eq!((cache.len(), cache.hits()), (1, 1));
+
+        // Filling past capacity empties the cache first
+        for (poly, commitment) in polys.iter().zip(&commitments).skip(1) {
+            let (proof, value) = params.open(poly, z).unwrap();
+            assert!(pvk.verify_opening_cached(&mut cache, commitment, &proof, z, value));
+        }
+        assert_eq!(cache.len(), 1);
+    }
+
+    #[test]
+    #[cfg(feature = "prover")]
+    fn test_verify_proof() {
+        use crate::prover::{Prover, ProvingKey};
+
+        let mut rng = ark_std::test_rng();
+        let circuit = public_circuit::<ScalarField>(15);
+        assert!(circuit.is_satisfied());
+        let pk = ProvingKey::<Bls12_381>::preprocess(KZGParams::setup(9, &mut rng), &circuit).unwrap();
+        let proof = Prover::prove(&circuit, &pk, &mut rng).unwrap();
+        let statement = [ScalarField::from(15u64)];
+
+        assert_eq!(Verifier::verify(&pk.vk, &statement, &proof), Ok(()));
+        assert_eq!(
+            Verifier::verify(&pk.vk, &[ScalarField::from(16u64)], &proof),
+            Err(VerifyError::QuotientMismatch)
+        );
+        assert_eq!(
+            Verifier::verify(&pk.vk, &[ScalarField::one(); 5], &proof),
+            Err(VerifyError::TooManyPublicInputs { count: 5, rows: 4 })
+        );
+
+        let mut forged = proof.clone();
+        forged.w_zeta = (forged.w_zeta + pk.vk.opening_key.g1).into();
+        assert_eq!(Verifier::verify(&pk.vk, &statement, &forged), Err(VerifyError::InvalidOpening));
+        assert!(Verifier::verify_in_session(&pk.vk, b"session", &statement, &proof).is_err());
+
+        let mut forged = proof;
+        forged.evaluations.wires[0] += ScalarField::one();
+        assert!(Verifier::verify(&pk.vk, &statement, &forged).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "prover")]
+    fn test_verify_ipa_proof() {
+        use crate::ipa::IpaParams;
+        use crate::prover::{Prover, ProvingKey};
+        use ark_bls12_381::G1Projective;
+
+        let mut rng = ark_std::test_rng();
+        let circuit = public_circuit::<ScalarField>(15);
+        let params = IpaParams::<G1Projective>::new(b"plonk-ipa-test", ProvingKey::<Bls12_381>::srs_degree_for(4));
+        let pk = ProvingKey::<Bls12_381, _>::preprocess(params, &circuit).unwrap();
+        let proof = Prover::prove(&circuit, &pk, &mut rng).unwrap();
+        let statement = [ScalarField::from(15u64)];
+
+        assert_eq!(Verifier::verify(&pk.vk, &statement, &proof), Ok(()));
+        assert_eq!(
+            Verifier::verify(&pk.vk, &[ScalarField::from(16u64)], &proof),
+            Err(VerifyError::QuotientMismatch)
+        );
+        let mut forged = proof.clone();
+        forged.w_zeta.a += ScalarField::one();
+        assert_eq!(Verifier::verify(&pk.vk, &statement, &forged), Err(VerifyError::InvalidOpening));
+
+        let mut forged = proof;
+        forged.evaluations.wires[0] += ScalarField::one();
+        assert!(Verifier::verify(&pk.vk, &statement, &forged).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "prover")]
+    fn test_concurrent_sessions() {
+        use crate::prover::{Prover, ProverConfig, ProvingKey};
+        use ark_std::rand::{rngs::StdRng, SeedableRng};
+
+        let circuit = public_circuit::<ScalarField>(15);
+        let pk = ProvingKey::<Bls12_381>::preprocess(KZGParams::setup(9, &mut ark_std::test_rng()), &circuit).unwrap();
+        let statement = [ScalarField::from(15u64)];
+        let sessions: Vec<Vec<u8>> = (0..4u8).map(|i| vec![b's', i]).collect();
+        let proofs: Vec<Proof<Bls12_381>> = std::thread::scope(|scope| {
+            let handles: Vec<_> = sessions
+                .iter()
+                .map(|session| {
+                    let (circuit, pk) = (&circuit, &pk);
+                    scope.spawn(move || {
+                        let config = ProverConfig { session: session.clone(), ..Default::default() };
+                        Prover::prove_with_config(circuit, pk, &config, &mut StdRng::seed_from_u64(0)).unwrap()
+                    })
+                })
+                .collect();
+            handles.into_iter().map(|handle| handle.join().unwrap()).collect()
+        });
+
+        for (i, proof) in proofs.iter().enumerate() {
+            for (j, session) in sessions.iter().enumerate() {
+                let result = Verifier::verify_in_session(&pk.vk, session, &statement, proof);
+                assert_eq!(result.is_ok(), i == j, "proof {} in session {}", i, j);
+            }
+            assert!(Verifier::verify(&pk.vk, &statement, proof).is_err());
+        }
+
+        // A batch checks each proof against its own session
+        let mut items: Vec<_> =
+            sessions.iter().zip(&proofs).map(|(session, proof)| (&pk.vk, &session[..], &statement[..], proof)).collect();
+        assert_eq!(Verifier::verify_batch(&items), Ok(()));
+        items[1].1 = &sessions[0];
+        assert_eq!(Verifier::verify_batch(&items), Err(BatchError::Proof { index: 1, error: VerifyError::QuotientMismatch }));
+        items[1].1 = &[];
+        assert!(Verifier::verify_batch(&items).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "prover")]
+    fn test_verify_batch_across_circuits() {
+        use crate::prover::{Prover, ProvingKey};
+        use ark_std::rand::{rngs::StdRng, SeedableRng};
+
+        let mut rng = ark_std::test_rng();
+        let srs = |seed| KZGParams::setup(9, &mut StdRng::seed_from_u64(seed));
+        let statement = [ScalarField::from(15u64)];
+        let first = public_circuit::<ScalarField>(15);
+        // Another circuit: an extra, trivially satisfied q_l on the last row
+        let mut second = public_circuit::<ScalarField>(15);
+        second.q_l[3] = ScalarField::one();
+
+        let keys = [
+            ProvingKey::<Bls12_381>::preprocess(srs(1), &first).unwrap(),
+            ProvingKey::<Bls12_381>::preprocess(srs(2), &second).unwrap(),
+        ];
+        // A third key over the first key's SRS, so two proofs share a group
+        let shared = ProvingKey::preprocess(srs(1), &second).unwrap();
+        assert_eq!(shared.vk.opening_key.g2_s, keys[0].vk.opening_key.g2_s);
+        assert_ne!(shared.vk.opening_key.g2_s, keys[1].vk.opening_key.g2_s);
+        let proofs = [
+            Prover::prove(&first, &keys[0], &mut rng).unwrap(),
+            Prover::prove(&second, &keys[1], &mut rng).unwrap(),
+            Prover::prove(&second, &shared, &mut rng).unwrap(),
+        ];
+        let vks = [&keys[0].vk, &keys[1].vk, &shared.vk];
+        let items: Vec<_> = vks.iter().zip(&proofs).map(|(vk, proof)| (*vk, &[][..], &statement[..], proof)).collect();
+        assert_eq!(Verifier::verify_batch_with_rng(&items, &mut rng), Ok(()));
+        assert_eq!(Verifier::verify_batch_with_rng::<Bls12_381, _>(&[], &mut rng), Ok(()));
+        assert_eq!(Verifier::verify_batch(&items), Ok(()));
+        assert_eq!(Verifier::verify_batch::<Bls12_381>(&[]), Ok(()));
+
+        let mut forged = proofs[2].clone();
+        forged.w_zeta = (forged.w_zeta + shared.vk.opening_key.g1).into();
+        let mut bad = items.clone();
+        bad[2].3 = &forged;
+        assert_eq!(Verifier::verify_batch_with_rng(&bad, &mut rng), Err(BatchError::InvalidOpening));
+        assert_eq!(Verifier::verify_batch(&bad), Err(BatchError::InvalidOpening));
+
+        let wrong = [ScalarField::from(16u64)];
+        let mut bad = items.clone();
+        bad[1].2 = &wrong;
+        assert_eq!(
+            Verifier::verify_batch_with_rng(&bad, &mut rng),
+            Err(BatchError::Proof { index: 1, error: VerifyError::QuotientMismatch })
+        );
+        assert_eq!(Verifier::verify_batch(&bad), Err(BatchError::Proof { index: 1, error: VerifyError::QuotientMismatch }));
+    }
+
+    #[test]
+    #[cfg(feature = "prover")]
+    fn test_reject_unsatisfied_circuit() {
+        use crate::prover::{Prover, ProvingKey};
+
+        let mut rng = ark_std::test_rng();
+        let pk = ProvingKey::<Bls12_381>::preprocess(KZGParams::setup(9, &mut rng), &public_circuit::<ScalarField>(15)).unwrap();
+        let mut circuit = public_circuit::<ScalarField>(15);
+        circuit.c[2] = ScalarField::from(16u64);
+        circuit.a[0] = ScalarField::from(16u64);
+        circuit.public_inputs[0] = ScalarField::from(16u64);
+        assert!(!circuit.is_satisfied());
+
+        let proof = Prover::prove(&circuit, &pk, &mut rng).unwrap();
+        assert!(Verifier::verify(&pk.vk, &circuit.public_inputs, &proof).is_err());
+    }
+}
@@ -0,0 +1,126 @@
+//! JavaScript bindings over BN254, so a browser can make proofs
+//! client-side and check them.
+//!
+//! Everything crosses the boundary as bytes: keys, witnesses and proofs
+//! in their `Artifact` encoding, statements as the canonical compressed
+//! `Vec<Fr>`. A browser has no `thread_rng`, so the prover's blinding is
+//! drawn from a 32-byte seed the caller takes from
+//! `crypto.getRandomValues`. Build with `--no-default-features --features
+//! wasm`: the default features bring in memory-mapped files, and
+//! `parallel` would need threads.
+
+use alloc::{string::ToString, vec::Vec};
+use ark_serialize::CanonicalDeserialize;
+use ark_std::rand::rngs::StdRng;
+use ark_std::rand::SeedableRng;
+use wasm_bindgen::prelude::*;
+
+use crate::artifact::Artifact;
+use crate::bn254::{Fr, Proof, ProvingKey, VerifyingKey};
+use crate::constraint_system::Witness;
+use crate::error::Error;
+use crate::prover::Prover;
+use crate::verifier::Verifier;
+
+#[derive(Debug)]
+pub enum WasmError {
+    /// The seed is not 32 bytes; carries its length
+    SeedLength(usize),
+    /// The statement bytes are not a list of field elements
+    MalformedStatement,
+    Plonk(Error),
+}
+
+impl core::fmt::Display for WasmError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            WasmError::SeedLength(len) => write!(f, "seed must be 32 bytes, got {}", len),
+            WasmError::MalformedStatement => write!(f, "malformed public inputs"),
+            WasmError::Plonk(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl<E: Into<Error>> From<E> for WasmError {
+    fn from(e: E) -> Self {
+        WasmError::Plonk(e.into())
+    }
+}
+
+/// Proof artifact for `witness` against `proving_key`, blinded from `seed`
+#[wasm_bindgen]
+pub fn prove(proving_key: &[u8], witness: &[u8], seed: &[u8]) -> Result<Vec<u8>, JsError> {
+    prove_bytes(proving_key, witness, seed).map_err(|e| JsError::new(&e.to_string()))
+}
+
+/// Whether `proof` is valid for `verifying_key` and `public_inputs`;
+/// throws only on bytes that do not decode
+#[wasm_bindgen]
+pub fn verify(verifying_key: &[u8], public_inputs: &[u8], proof: &[u8]) -> Result<bool, JsError> {
+    verify_bytes(verifying_key, public_inputs, proof).map_err(|e| JsError::new(&e.to_string()))
+}
+
+pub fn prove_bytes(proving_key: &[u8], witness: &[u8], seed: &[u8]) -> Result<Vec<u8>, WasmError> {
+    let seed: [u8; 32] = seed.try_into().map_err(|_| WasmError::SeedLength(seed.len()))?;
+    let pk = ProvingKey::decode_artifact(proving_key)?;
+    let witness = Witness::<Fr>::decode_artifact(witness)?;
+    let proof: Proof = Prover::prove_witness(&witness, &pk, &mut StdRng::from_seed(seed))?;
+    Ok(proof.encode_artifact())
+}
+
+pub fn verify_bytes(verifying_key: &[u8], public_inputs: &[u8], proof: &[u8]) -> Result<bool, WasmError> {
+    let vk = VerifyingKey::decode_artifact(verifying_key)?;
+    let public_inputs = Vec::<Fr>::deserialize_compressed(public_inputs).map_err(|_| WasmError::MalformedStatement)?;
+    let proof = Proof::decode_artifact(proof)?;
+    Ok(Verifier::verify(&vk, &public_inputs, &proof).is_ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_serialize::CanonicalSerialize;
+
+    use crate::bn254::Params;
+    use crate::circuit::{Circuit, Variable};
+    use crate::constraint_system::{ConstraintSystem, Synthesize};
+    use crate::error::ArtifactError;
+
+    /// x³ + x + 5 = 35, with 35 public
+    struct Cubic;
+
+    impl Synthesize<Fr> for Cubic {
+        fn synthesize(&self, circuit: &mut Circuit<Fr>) {
+            let x = circuit.alloc(Fr::from(3u64));
+            let square = circuit.mul(x, x);
+            let cube = circuit.mul(square, x);
+            let sum = circuit.add(cube, x);
+            let five = circuit.constant(Fr::from(5u64));
+            let out = circuit.add(sum, five);
+            let public = Variable::from(&circuit.add_public_input(Fr::from(35u64)));
+            circuit.assert_equal(out, public);
+        }
+    }
+
+    #[test]
+    fn test_prove_and_verify_bytes() {
+        let (cs, witness) = ConstraintSystem::synthesize(&Cubic, 8).unwrap();
+        let pk = ProvingKey::preprocess(Params::setup(20, &mut ark_std::test_rng()), &cs.assign(&witness).unwrap()).unwrap();
+        let (pk_bytes, vk_bytes, witness_bytes) = (pk.encode_artifact(), pk.vk.encode_artifact(), witness.encode_artifact());
+        let statement = |value: u64| {
+            let mut bytes = Vec::new();
+            vec![Fr::from(value)].serialize_compressed(&mut bytes).unwrap();
+            bytes
+        };
+
+        // The seed fixes the blinding, so the same seed gives the same proof
+        let proof = prove_bytes(&pk_bytes, &witness_bytes, &[7; 32]).unwrap();
+        assert_eq!(proof, prove_bytes(&pk_bytes, &witness_bytes, &[7; 32]).unwrap());
+        assert_ne!(proof, prove_bytes(&pk_bytes, &witness_bytes, &[8; 32]).unwrap());
+        assert!(verify_bytes(&vk_bytes, &statement(35), &proof).unwrap());
+        assert!(!verify_bytes(&vk_bytes, &statement(36), &proof).unwrap());
+
+        assert!(matches!(prove_bytes(&pk_bytes, &witness_bytes, &[7; 16]), Err(WasmError::SeedLength(16))));
+        assert!(matches!(prove_bytes(&vk_bytes, &witness_bytes, &[7; 32]), Err(WasmError::Plonk(Error::Artifact(ArtifactError::WrongKind)))));
+        assert!(matches!(verify_bytes(&vk_bytes, &[1, 2], &proof), Err(WasmError::MalformedStatement)));
+    }
+}
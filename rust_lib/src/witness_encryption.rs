@@ -0,0 +1,185 @@
+//! Encrypting a witness to an auditor, to travel next to its proof and
+//! be disclosed later, such as in a dispute: the auditor decrypts the
+//! columns, assigns them to the circuit again and re-derives the proof.
+//!
+//! The scheme is hybrid over Jubjub, with the `elgamal::Keypair` of the
+//! recipient. An ephemeral key e gives the shared point e·pk; SHAKE256
+//! over both points yields a keystream key and a MAC key. The serialized
+//! columns are XORed with the keystream, and SHA3-256 under the MAC key
+//! authenticates the ciphertext together with associated data, normally
+//! the proof bytes, so a witness cannot be moved to another proof.
+
+use alloc::vec::Vec;
+use ark_ec::{AffineRepr, CurveGroup, PrimeGroup};
+use ark_ed_on_bls12_381::{EdwardsAffine, EdwardsProjective, Fr as JubjubScalar};
+use ark_ff::PrimeField;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_std::rand::Rng;
+use ark_std::UniformRand;
+use sha3::digest::{ExtendableOutput, Update, XofReader};
+use sha3::{Digest, Sha3_256, Shake256};
+use zeroize::Zeroize;
+
+use crate::constraint_system::Witness;
+use crate::elgamal::Keypair;
+
+/// A witness encrypted to one recipient key
+#[derive(Debug, Clone, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct EncryptedWitness {
+    /// e·G for the ephemeral key e
+    pub ephemeral: EdwardsAffine,
+    pub ciphertext: Vec<u8>,
+    pub tag: [u8; 32],
+}
+
+/// Why `EncryptedWitness::decrypt` failed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WitnessDecryptError {
+    /// Wrong key, wrong associated data, or a modified ciphertext
+    Authentication,
+    /// The plaintext is not an encoded witness
+    Encoding,
+}
+
+impl core::fmt::Display for WitnessDecryptError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            WitnessDecryptError::Authentication => write!(f, "encrypted witness failed authentication"),
+            WitnessDecryptError::Encoding => write!(f, "decrypted witness is malformed"),
+        }
+    }
+}
+
+/// Keystream and MAC keys from the ephemeral and shared points
+struct Keys {
+    stream: [u8; 32],
+    mac: [u8; 32],
+}
+
+impl Keys {
+    fn derive(ephemeral: &EdwardsAffine, shared: &EdwardsAffine) -> Self {
+        let mut shake = Shake256::default();
+        shake.update(b"plonk-witness-encryption");
+        for point in [ephemeral, shared] {
+            let mut bytes = Vec::new();
+            point.serialize_compressed(&mut bytes).expect("writing to a Vec");
+            shake.update(&bytes);
+        }
+        let mut reader = shake.finalize_xof();
+        let mut keys = Self { stream: [0u8; 32], mac: [0u8; 32] };
+        reader.read(&mut keys.stream);
+        reader.read(&mut keys.mac);
+        keys
+    }
+
+    /// XORs `data` with the keystream
+    fn apply(&self, data: &mut [u8]) {
+        let mut shake = Shake256::default();
+        shake.update(&self.stream);
+        let mut reader = shake.finalize_xof();
+        let mut block = [0u8; 64];
+        for chunk in data.chunks_mut(block.len()) {
+            reader.read(&mut block[..chunk.len()]);
+            for (byte, key) in chunk.iter_mut().zip(&block) {
+                *byte ^= key;
+            }
+        }
+        block.zeroize();
+    }
+
+    fn tag(&self, associated: &[u8], ciphertext: &[u8]) -> [u8; 32] {
+        let mut hasher = Sha3_256::new();
+        Digest::update(&mut hasher, self.mac);
+        Digest::update(&mut hasher, (associated.len() as u64).to_le_bytes());
+        Digest::update(&mut hasher, associated);
+        Digest::update(&mut hasher, ciphertext);
+        hasher.finalize().into()
+    }
+}
+
+impl Drop for Keys {
+    fn drop(&mut self) {
+        self.stream.zeroize();
+        self.mac.zeroize();
+    }
+}
+
+/// Encrypts `witness` to `recipient`, binding `associated`
+pub fn encrypt_witness<F: PrimeField, R: Rng>(
+    recipient: &EdwardsAffine,
+    witness: &Witness<F>,
+    associated: &[u8],
+    rng: &mut R,
+) -> EncryptedWitness {
+    let mut e = JubjubScalar::rand(rng);
+    let ephemeral = (EdwardsProjective::generator() * e).into_affine();
+    let shared = (recipient.into_group() * e).into_affine();
+    e.zeroize();
+    let keys = Keys::derive(&ephemeral, &shared);
+
+    let mut ciphertext = Vec::new();
+    for column in [&witness.a, &witness.b, &witness.c] {
+        column.serialize_compressed(&mut ciphertext).expect("writing to a Vec");
+    }
+    keys.apply(&mut ciphertext);
+    let tag = keys.tag(associated, &ciphertext);
+    EncryptedWitness { ephemeral, ciphertext, tag }
+}
+
+impl EncryptedWitness {
+    /// Recovers the witness with the recipient's key, given the same
+    /// associated data it was encrypted with
+    pub fn decrypt<F: PrimeField>(&self, keypair: &Keypair, associated: &[u8]) -> Result<Witness<F>, WitnessDecryptError> {
+        let shared = (self.ephemeral.into_group() * keypair.secret).into_affine();
+        let keys = Keys::derive(&self.ephemeral, &shared);
+        let expected = keys.tag(associated, &self.ciphertext);
+        // Compared without an early exit, so timing says nothing about
+        // where the tags differ
+        if expected.iter().zip(&self.tag).fold(0u8, |acc, (x, y)| acc | (x ^ y)) != 0 {
+            return Err(WitnessDecryptError::Authentication);
+        }
+
+        let mut plaintext = self.ciphertext.clone();
+        keys.apply(&mut plaintext);
+        let mut reader = &plaintext[..];
+        let mut column = || Vec::<F>::deserialize_compressed(&mut reader).map_err(|_| WitnessDecryptError::Encoding);
+        let witness = (|| Ok(Witness { a: column()?, b: column()?, c: column()? }))();
+        let trailing = !reader.is_empty();
+        plaintext.zeroize();
+        match witness {
+            Ok(_) if trailing => Err(WitnessDecryptError::Encoding),
+            witness => witness,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bls12_381::Fr;
+
+    #[test]
+    fn test_witness_roundtrip() {
+        let mut rng = ark_std::test_rng();
+        let auditor = Keypair::generate(&mut rng);
+        let column = |values: [u64; 4]| values.map(Fr::from).to_vec();
+        let witness = Witness { a: column([15, 2, 5, 0]), b: column([0, 3, 3, 0]), c: column([0, 5, 15, 0]) };
+        let proof = b"proof bytes";
+
+        let encrypted = encrypt_witness(&auditor.public, &witness, proof, &mut rng);
+        assert_eq!(encrypted.decrypt::<Fr>(&auditor, proof), Ok(witness.clone()));
+        let mut bytes = Vec::new();
+        encrypted.serialize_compressed(&mut bytes).unwrap();
+        assert_eq!(EncryptedWitness::deserialize_compressed(&bytes[..]).unwrap(), encrypted);
+
+        let other = Keypair::generate(&mut rng);
+        assert_eq!(encrypted.decrypt::<Fr>(&other, proof), Err(WitnessDecryptError::Authentication));
+        assert_eq!(encrypted.decrypt::<Fr>(&auditor, b"another proof"), Err(WitnessDecryptError::Authentication));
+        let mut tampered = encrypted.clone();
+        tampered.ciphertext[0] ^= 1;
+        assert_eq!(tampered.decrypt::<Fr>(&auditor, proof), Err(WitnessDecryptError::Authentication));
+
+        // Fresh randomness each time
+        assert_ne!(encrypt_witness(&auditor.public, &witness, proof, &mut rng).ciphertext, encrypted.ciphertext);
+    }
+}